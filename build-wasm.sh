@@ -19,7 +19,7 @@ fi
 RUSTFLAGS="-Zlocation-detail=none -Zfmt-debug=none" cargo build \
   -Z build-std=core,alloc,panic_abort,std \
   -Z build-std-features=optimize_for_size,panic_immediate_abort \
-  --no-default-features --profile wasm-release \
+  --no-default-features --features webgpu --profile wasm-release \
   --target wasm32-unknown-unknown
 
 # Generate bindings
@@ -2,7 +2,7 @@ set -e
 
 # There's an issue with the first few frames needing longer frame intervals when running in worker with debug mode
 # https://github.com/bevyengine/bevy/issues/13345
-cargo build --no-default-features --profile dev-opt \
+cargo build --no-default-features --features webgpu --profile dev-opt \
 --target wasm32-unknown-unknown
 
 # Generate bindings
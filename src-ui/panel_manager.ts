@@ -1,5 +1,6 @@
 import { SessionAdapter, type RuntimeMode, type PanelRectMsg } from './runtime/session_adapter';
 import { InputManager } from './runtime/input_manager';
+import { TextInputBridge } from './runtime/text_input_bridge';
 import { InspectorClient } from './runtime/inspector_client';
 import { SystemState } from './system_state.svelte';
 
@@ -22,6 +23,7 @@ export class PanelManager {
   private mode: RuntimeMode = 'worker';
   private canvas: HTMLCanvasElement | null = null;
   private input = new InputManager({ enableRaw: true });
+  private textInput = new TextInputBridge();
   private inspector = new InspectorClient(new SystemState());
   private panels = new Map<string, PanelEntry>();
   private windowResizeHandler: (() => void) | null = null;
@@ -99,6 +101,7 @@ export class PanelManager {
 
     // 1. Detach input from the old canvas.
     this.input.dispose();
+    this.textInput.dispose();
 
     // 2. Dispose old session (stops RAF; main mode releases the Bevy app; worker terminates).
     this.session?.dispose();
@@ -114,6 +117,7 @@ export class PanelManager {
 
   dispose(): void {
     this.input.dispose();
+    this.textInput.dispose();
     this.session?.dispose();
     this.session = null;
     if (this.windowResizeHandler) {
@@ -157,11 +161,33 @@ export class PanelManager {
           this.input.init(this.canvas, {
             post: (payload: any) => this.session?.post(payload),
           });
+          this.textInput.init({ post: (payload: any) => this.session?.post(payload) });
         }
         if (this.onInitialized) try { this.onInitialized(); } catch { }
         break;
       case 'inspector_update':
-        this.inspector.handleUpdate(data.update);
+        this.inspector.handleUpdate(data.update, data.client_id);
+        break;
+      case 'inspector_update_gzip':
+        this.inspector.handleGzipUpdate(data.gzip, data.client_id);
+        break;
+      case 'inspector_connected':
+        this.inspector.handleConnected(data.requestId, data.client_id);
+        break;
+      case 'text_edit':
+        this.textInput.handleEdit(data.edit);
+        break;
+      case 'pointer_lock':
+        // Camera grab started/ended; locking makes mouse-look unbounded (raw deltas).
+        if (data.locked) {
+          this.canvas?.requestPointerLock?.();
+        } else if (document.pointerLockElement === this.canvas) {
+          document.exitPointerLock();
+        }
+        break;
+      case 'warning':
+        // e.g. { kind: 'memory_budget', metric, value_mb, limit_mb, message }
+        console.warn(`[ironfell] ${data.warning?.message ?? 'warning'}`, data.warning);
         break;
       case 'probeStats':
         (window as any).__lastProbeStats = data.stats;
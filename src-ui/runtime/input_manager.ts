@@ -9,12 +9,19 @@ export interface InputManagerOptions {
 /**
  * Binds pointer/keyboard/wheel input on the full-window canvas and forwards it to the
  * render session. All coordinates are canvas-relative CSS pixels; Rust converts to
- * physical pixels via the scale factor it was given at window creation.
+ * physical pixels via the scale factor it was given at window creation. Pointer
+ * messages carry `t`, the event time as `performance.timeOrigin + event.timeStamp`, so
+ * the worker can rebase it onto its own `performance.now()` clock (see `eventTime`).
  *
  * Fully rebindable: `dispose()` removes every listener so the manager can be re-attached
  * to a fresh canvas after a worker/main mode switch (the old canvas is replaced because
  * transferred canvases cannot be reused).
  */
+/** Rebase an input message's `t` onto this context's `performance.now()`; NaN if unset. */
+export function eventTime(t?: number): number {
+    return (t ?? NaN) - performance.timeOrigin;
+}
+
 export class InputManager {
     private canvas: HTMLCanvasElement | null = null;
     private poster: InputPoster | null = null;
@@ -41,6 +48,7 @@ export class InputManager {
         this.poster = poster;
         this.attachPointer();
         this.attachKeyboard();
+        this.attachGamepads();
         this.attached = true;
     }
 
@@ -84,25 +92,36 @@ export class InputManager {
         this.listen(window, 'resize', () => refreshRect(true), { passive: true });
         this.listen(window, 'scroll', () => refreshRect(true), { passive: true });
 
-        const moveMsg: any = { ty: 'mousemove', x: 0, y: 0 };
-        const send = (cx: number, cy: number) => {
+        const moveMsg: any = { ty: 'mousemove', x: 0, y: 0, t: 0 };
+        const send = (cx: number, cy: number, timeStamp: number) => {
             refreshRect();
             moveMsg.x = cx - rect.left;
             moveMsg.y = cy - rect.top;
+            moveMsg.t = performance.timeOrigin + timeStamp;
             this.latestPick = [];
             this.post(moveMsg);
         };
 
         const onPointerMove = (ev: PointerEvent) => {
+            if (document.pointerLockElement === canvas) {
+                // Locked: clientX/Y are frozen, only movementX/Y carries motion.
+                this.post({
+                    ty: 'mouseRawDelta',
+                    dx: ev.movementX,
+                    dy: ev.movementY,
+                    t: performance.timeOrigin + ev.timeStamp,
+                });
+                return;
+            }
             const coalesced = (ev as any).getCoalescedEvents ? (ev as any).getCoalescedEvents() : null;
             if (coalesced && coalesced.length > 0) {
                 // Rust consumes one cursor position per rendered frame, so posting the
                 // whole history only creates worker-queue backlog. Keep the newest point.
                 const latest = coalesced[coalesced.length - 1];
-                send(latest.clientX, latest.clientY);
+                send(latest.clientX, latest.clientY, latest.timeStamp);
                 return;
             }
-            send(ev.clientX, ev.clientY);
+            send(ev.clientX, ev.clientY, ev.timeStamp);
         };
 
         if (this.options.enableRaw && 'onpointerrawupdate' in window) {
@@ -114,16 +133,61 @@ export class InputManager {
         this.listen(canvas, 'pointerdown', (e: PointerEvent) => {
             refreshRect();
             const x = e.clientX - rect.left; const y = e.clientY - rect.top;
-            this.post({ ty: 'leftBtDown', x, y });
+            this.post({ ty: 'leftBtDown', x, y, t: performance.timeOrigin + e.timeStamp });
+        });
+        this.listen(canvas, 'pointerup', (e: PointerEvent) => {
+            this.post({ ty: 'leftBtUp', t: performance.timeOrigin + e.timeStamp });
         });
-        this.listen(canvas, 'pointerup', () => this.post({ ty: 'leftBtUp' }));
 
         this.listen(canvas, 'wheel', (e: WheelEvent) => {
             e.preventDefault();
-            this.post({ ty: 'mouseWheel', dx: e.deltaX, dy: e.deltaY, mode: e.deltaMode });
+            this.post({
+                ty: 'mouseWheel',
+                dx: e.deltaX,
+                dy: e.deltaY,
+                mode: e.deltaMode,
+                t: performance.timeOrigin + e.timeStamp,
+            });
         }, { passive: false });
     }
 
+    /**
+     * The Gamepad API is main-thread only: poll once per animation frame and forward a
+     * pad when it changed (new timestamp) or a stick is deflected, so held sticks keep
+     * the app rendering.
+     */
+    private attachGamepads() {
+        if (!('getGamepads' in navigator)) return;
+        const STICK_ACTIVE = 0.1;
+        const stamps = new Map<number, number>();
+        let rafId: number | null = null;
+        const poll = () => {
+            for (const pad of navigator.getGamepads()) {
+                if (!pad) continue;
+                const active = pad.axes.some(a => Math.abs(a) > STICK_ACTIVE);
+                if (stamps.get(pad.index) === pad.timestamp && !active) continue;
+                stamps.set(pad.index, pad.timestamp);
+                this.post({
+                    ty: 'gamepadUpdate',
+                    gamepad: {
+                        index: pad.index,
+                        id: pad.id,
+                        connected: pad.connected,
+                        buttons: pad.buttons.map(b => b.value),
+                        axes: Array.from(pad.axes),
+                    },
+                });
+            }
+            rafId = requestAnimationFrame(poll);
+        };
+        rafId = requestAnimationFrame(poll);
+        this.cleanups.push(() => { if (rafId !== null) cancelAnimationFrame(rafId); });
+        this.listen(window, 'gamepaddisconnected', (e: GamepadEvent) => {
+            stamps.delete(e.gamepad.index);
+            this.post({ ty: 'gamepadUpdate', gamepad: { index: e.gamepad.index, connected: false } });
+        });
+    }
+
     private attachKeyboard() {
         this.listen(window, 'keydown', (e: KeyboardEvent) => this.onKeyDown(e));
         this.listen(window, 'keyup', (e: KeyboardEvent) => this.onKeyUp(e));
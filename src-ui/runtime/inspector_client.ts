@@ -4,6 +4,12 @@ import { SystemState } from '../system_state.svelte';
 export class InspectorClient {
     private bridge: AdapterBridge | null = null;
     readonly state: SystemState;
+    /** Extra clients opened with `connect`, each with its own update handler. */
+    private views = new Map<number, (update: any) => void>();
+    private pendingConnects = new Map<number, (clientId: number) => void>();
+    private nextRequestId = 1;
+    /** Updates apply in arrival order even when one needs async decompression. */
+    private applying: Promise<void> = Promise.resolve();
 
     constructor(systemState: SystemState) {
         this.state = systemState;
@@ -12,14 +18,81 @@ export class InspectorClient {
     init(bridge: AdapterBridge) { this.bridge = bridge; }
     private post(data: any) { this.bridge?.post(data); }
 
-    handleUpdate(update: any) { this.state.process_update(update); }
+    /** Updates without a `clientId` (or with 0) belong to the default (host) client. */
+    handleUpdate(update: any, clientId?: number) {
+        this.applying = this.applying
+            .then(() => this.apply(update, clientId))
+            .catch(error => console.error('Failed to apply inspector update:', error));
+    }
+
+    /** A gzip-compressed update (a snapshot, see `set_inspector_snapshot_compression`). */
+    handleGzipUpdate(gzip: Uint8Array, clientId?: number) {
+        const json = new Response(
+            new Blob([gzip]).stream().pipeThrough(new DecompressionStream('gzip')),
+        ).text();
+        this.applying = this.applying
+            .then(() => json)
+            .then(text => this.apply(JSON.parse(text), clientId))
+            .catch(error => console.error('Failed to decompress inspector update:', error));
+    }
+
+    private apply(update: any, clientId?: number) {
+        if (!clientId) this.state.process_update(update);
+        else this.views.get(clientId)?.(update);
+    }
+
+    handleConnected(requestId: number, clientId: number) {
+        const resolve = this.pendingConnects.get(requestId);
+        this.pendingConnects.delete(requestId);
+        resolve?.(clientId);
+    }
+
+    /**
+     * Open another inspector client with independent tracking (e.g. a detail panel
+     * next to the hierarchy). It first receives a full snapshot, then its own deltas.
+     */
+    connect(onUpdate: (update: any) => void): Promise<number> {
+        const requestId = this.nextRequestId++;
+        return new Promise(resolve => {
+            this.pendingConnects.set(requestId, clientId => {
+                this.views.set(clientId, onUpdate);
+                resolve(clientId);
+            });
+            this.post({ ty: 'inspector_connect', requestId });
+        });
+    }
+
+    disconnect(clientId: number) {
+        this.views.delete(clientId);
+        this.post({ ty: 'inspector_disconnect', client_id: clientId });
+    }
+
+    setOutlineMode(clientId: number, enabled: boolean) { this.post({ ty: 'set_inspector_outline_mode', client_id: clientId, enabled }); }
+    /** Also stream internal helper entities (cameras, overlay scenes) to debug the app itself. */
+    setRevealHidden(clientId: number, enabled: boolean) { this.post({ ty: 'set_inspector_reveal_hidden', client_id: clientId, enabled }); }
+    watchEntity(clientId: number, e: string, enabled: boolean) { this.post({ ty: 'inspector_watch_entity', client_id: clientId, entity_id: e, enabled }); }
 
     updateComponent(e: string, c: number, valueJson: string) { this.post({ ty: 'inspector_update_component', entity_id: e, component_id: c, value_json: valueJson }); }
+    /** Apply one value to a component on many entities as a single undo step; `mergeMode` 'merge' patches fields. */
+    updateComponentMulti(entities: string[], c: number, valueJson: string, mergeMode: 'replace' | 'merge' = 'replace') { this.post({ ty: 'inspector_update_component_multi', entity_ids: entities, component_id: c, value_json: valueJson, merge_mode: mergeMode }); }
+    /** Add to, multiply or set a numeric field in place (`path` like 'translation.y'), without a read-modify-write. */
+    adjustComponentField(e: string, c: number, path: string, op: 'add' | 'mul' | 'set', amount: number) { this.post({ ty: 'inspector_adjust_component_field', entity_id: e, component_id: c, path, op, amount }); }
     toggleComponent(e: string, c: number) { this.post({ ty: 'inspector_toggle_component', entity_id: e, component_id: c }); }
     removeComponent(e: string, c: number) { this.post({ ty: 'inspector_remove_component', entity_id: e, component_id: c }); }
     insertComponent(e: string, c: number, v: string) { this.post({ ty: 'inspector_insert_component', entity_id: e, component_id: c, value_json: v }); }
     despawnEntity(e: string, kind = 'Recursive') { this.post({ ty: 'inspector_despawn_entity', entity_id: e, kind }); }
     toggleVisibility(e: string) { this.post({ ty: 'inspector_toggle_visibility', entity_id: e }); }
     reparentEntity(e: string, parentId?: string) { this.post({ ty: 'inspector_reparent_entity', entity_id: e, parent_id: parentId }); }
+    /** Component type globs to include/exclude, a value size limit, and delayed streaming of off-screen entities. */
+    setStreamingPolicy(policy: { include?: string[]; exclude?: string[]; max_value_bytes?: number; viewport_priority?: boolean; offscreen_interval?: number }) { this.post({ ty: 'set_inspector_streaming_policy', policy }); }
     spawnEntity(parentId?: string) { this.post({ ty: 'inspector_spawn_entity', parent_id: parentId }); }
+    /** Spawn an overlay shape template (position, size, end, fill, stroke, text, ... all optional). */
+    spawnShape(kind: 'rect' | 'ellipse' | 'line' | 'arrow' | 'text', params: Record<string, unknown> = {}, parentId?: string) { this.post({ ty: 'inspector_spawn_shape', kind, params, parent_id: parentId }); }
+    /** Spawn a template under the pointer: 3D objects rest on the surface below it, overlay shapes land at its overlay position. */
+    spawnAtCursor(template: 'cube' | 'sphere' | 'torus' | 'rect' | 'ellipse' | 'line' | 'arrow' | 'text') { this.post({ ty: 'spawn_at_cursor', template }); }
+    /** Jitter position / rotation (degrees) / scale of the entities (the selection when empty) from a seed, as one undo step. */
+    scatterEntities(entities: string[], params: { seed?: number; position_jitter?: [number, number, number]; rotation_jitter?: [number, number, number]; scale_jitter?: number }) { this.post({ ty: 'inspector_scatter_entities', entity_ids: entities, params }); }
+    /** Group the commands until `commitTransaction` into one named undo step and one stream flush; a failing command rolls the rest back. */
+    beginTransaction(label: string) { this.post({ ty: 'inspector_begin_transaction', label }); }
+    commitTransaction() { this.post({ ty: 'inspector_commit_transaction' }); }
 }
@@ -31,6 +31,7 @@ export class SessionAdapter {
   private pendingInit: { canvas: HTMLCanvasElement; dpr: number } | null = null;
   private latestCanvasSize: { width: number; height: number } | null = null;
   private latestPanelRects = new Map<string, PanelRectMsg>();
+  private latestExclusionRects: Omit<PanelRectMsg, 'id' | 'kind'>[] = [];
 
   constructor(mode: RuntimeMode) {
     this.mode = mode;
@@ -84,6 +85,14 @@ export class SessionAdapter {
     }
   }
 
+  /** Screen rects covered by HTML UI (physical px); pointer input there skips the app. */
+  setUiExclusionRects(rects: Omit<PanelRectMsg, 'id' | 'kind'>[]): void {
+    this.latestExclusionRects = rects;
+    if (this.enginePrepared) {
+      this.post({ ty: 'setUiExclusionRects', rects });
+    }
+  }
+
   /** Re-send canvas size and every known panel rect. Idempotent. */
   syncAll(): void {
     if (!this.enginePrepared) return;
@@ -93,6 +102,9 @@ export class SessionAdapter {
     for (const rect of this.latestPanelRects.values()) {
       this.post({ ty: 'setPanelViewport', ...rect });
     }
+    if (this.latestExclusionRects.length > 0) {
+      this.post({ ty: 'setUiExclusionRects', rects: this.latestExclusionRects });
+    }
   }
 
   start(): void {
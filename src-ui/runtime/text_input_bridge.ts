@@ -0,0 +1,85 @@
+import type { InputPoster } from './input_manager';
+
+const EDIT_KEYS: Record<string, string> = {
+    Backspace: 'backspace',
+    Delete: 'delete',
+    ArrowLeft: 'arrowleft',
+    ArrowRight: 'arrowright',
+    Home: 'home',
+    End: 'end',
+    Enter: 'enter',
+    Escape: 'escape',
+};
+
+/**
+ * Receives typed text for in-canvas `VectorText` editing. IME composition is only
+ * delivered to a focused DOM element, so while Rust reports an active edit
+ * (`text_edit` messages) a hidden textarea holds focus and forwards committed text,
+ * composition updates and the editing keys. The textarea is emptied after every
+ * commit; the text itself lives in Rust.
+ */
+export class TextInputBridge {
+    private textarea: HTMLTextAreaElement | null = null;
+    private poster: InputPoster | null = null;
+    private composing = false;
+
+    init(poster: InputPoster) {
+        this.poster = poster;
+    }
+
+    /** Handle a `text_edit` report from Rust. */
+    handleEdit(edit: { editing: boolean }) {
+        if (edit.editing) {
+            this.ensureTextarea().focus({ preventScroll: true });
+        } else {
+            this.textarea?.blur();
+        }
+    }
+
+    dispose() {
+        this.textarea?.remove();
+        this.textarea = null;
+        this.poster = null;
+        this.composing = false;
+    }
+
+    private post(data: any) { this.poster?.post(data); }
+
+    private ensureTextarea(): HTMLTextAreaElement {
+        if (this.textarea) return this.textarea;
+        const ta = document.createElement('textarea');
+        ta.setAttribute('autocomplete', 'off');
+        ta.setAttribute('autocapitalize', 'off');
+        ta.setAttribute('spellcheck', 'false');
+        ta.style.cssText = 'position:fixed;left:0;top:0;width:1px;height:1px;opacity:0;pointer-events:none;';
+        ta.addEventListener('compositionstart', () => { this.composing = true; });
+        ta.addEventListener('compositionupdate', (e: CompositionEvent) => {
+            this.post({ ty: 'imeComposition', text: e.data, start: ta.selectionStart, end: ta.selectionEnd });
+        });
+        ta.addEventListener('compositionend', (e: CompositionEvent) => {
+            this.composing = false;
+            this.post({ ty: 'imeComposition', text: '', start: -1, end: -1 });
+            if (e.data) this.post({ ty: 'textInput', text: e.data });
+            ta.value = '';
+        });
+        ta.addEventListener('input', (e: Event) => {
+            const ev = e as InputEvent;
+            if (this.composing || ev.isComposing) return;
+            if (ev.inputType === 'insertText' || ev.inputType === 'insertFromPaste') {
+                if (ev.data ?? ta.value) this.post({ ty: 'textInput', text: ev.data ?? ta.value });
+            }
+            ta.value = '';
+        });
+        ta.addEventListener('keydown', (e: KeyboardEvent) => {
+            const key = EDIT_KEYS[e.key];
+            if (!key || this.composing || e.isComposing) return;
+            e.preventDefault();
+            this.post({ ty: 'keydown', key });
+            this.post({ ty: 'keyup', key });
+        });
+        ta.addEventListener('blur', () => this.post({ ty: 'endTextEdit' }));
+        document.body.appendChild(ta);
+        this.textarea = ta;
+        return ta;
+    }
+}
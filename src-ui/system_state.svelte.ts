@@ -1,11 +1,13 @@
 import type { ComponentId, ComponentValue } from './types/component';
 import type { EntityId } from './types/entity';
-import type { ComponentsEvent, EntityMutation, StreamEvent, TypeRegistryEvent, EntityMutationChange } from './types/message';
+import type { ComponentsEvent, EntityMutation, StreamEvent, TypeRegistryEvent, EntityMutationChange, LifecycleRecord, Relation, DiagnosticStat } from './types/message';
 import { bevyTypes } from './types/bevy';
 import { COMMON_NAMES, bevyCrates } from './types/bevy';
 
 import { SvelteMap } from 'svelte/reactivity';
 
+const MAX_LIFECYCLE_RECORDS = 2000;
+
 export class SystemState {
     components = new SvelteMap<ComponentId, any>();
     registry = new SvelteMap<string, any>();
@@ -23,6 +25,12 @@ export class SystemState {
     > = new Map();
     childParentMap: Map<EntityId, EntityId | null> = new Map();
     entityNames: Map<EntityId, string> = new Map();
+    /** Recent component adds / replaces / removes, oldest first (for a timeline view). */
+    lifecycle: LifecycleRecord[] = [];
+    /** Typed relation edges, keyed by their JSON so removals can find them. */
+    relations: Map<string, Relation> = new Map();
+    /** Latest diagnostics (frame / GPU / Vello encode timings), keyed by path. */
+    stats = new SvelteMap<string, DiagnosticStat>();
 
     // Client-side deep comparison cache
     private componentValueCache = new Map<EntityId, Map<ComponentId, string>>();
@@ -198,6 +206,26 @@ export class SystemState {
                 this.setComponents(item.components);
             } else if (item.kind === 'entity') {
                 this.updateEntity(item.entity, item.mutation);
+            } else if (item.kind === 'snapshot') {
+                for (const [entity, changes] of item.entities) {
+                    this.updateEntity(entity, { kind: 'change', changes, removes: [] });
+                }
+            } else if (item.kind === 'lifecycle') {
+                this.lifecycle.push(...item.records);
+                if (this.lifecycle.length > MAX_LIFECYCLE_RECORDS) {
+                    this.lifecycle.splice(0, this.lifecycle.length - MAX_LIFECYCLE_RECORDS);
+                }
+            } else if (item.kind === 'relations') {
+                for (const relation of item.removed) {
+                    this.relations.delete(JSON.stringify(relation));
+                }
+                for (const relation of item.added) {
+                    this.relations.set(JSON.stringify(relation), relation);
+                }
+            } else if (item.kind === 'stats') {
+                for (const stat of item.stats) {
+                    this.stats.set(stat.path, stat);
+                }
             } else if (item.kind === 'schedules') {
                 this.updateSchedules(item.schedules);
             } else {
@@ -9,4 +9,13 @@ export type ComponentInfo = {
     name: ComponentName;
     reflected: boolean;
     required_components: ComponentId[];
-};
\ No newline at end of file
+};
+
+/** Value streamed for a component that can't be serialized; listed but not editable. */
+export type OpaqueComponentValue = {
+    $opaque: { short_name: string; size: number; reflectable: boolean };
+};
+
+export function isOpaqueComponentValue(value: unknown): value is OpaqueComponentValue {
+    return typeof value === 'object' && value !== null && '$opaque' in value;
+}
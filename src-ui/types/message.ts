@@ -3,7 +3,7 @@ import type { ComponentInfo, ComponentId, ComponentValue } from './component';
 import type { EntityId } from './entity';
 import type { ScheduleInfo } from './schedule';
 
-export type StreamEvent = TypeRegistryEvent | ComponentsEvent | EntityEvent | ScheduleEvent;
+export type StreamEvent = TypeRegistryEvent | ComponentsEvent | EntityEvent | ScheduleEvent | SnapshotEvent | LifecycleEvent | RelationsEvent | StatsEvent;
 
 export type TypeRegistryEvent = {
     kind: 'type_registry';
@@ -21,6 +21,57 @@ export type EntityEvent = {
     mutation: EntityMutation;
 };
 
+/** Every entity at once, sent as a client's first update instead of per-entity events. */
+export type SnapshotEvent = {
+    kind: 'snapshot';
+    entities: Array<[EntityId, EntityMutationChange['changes']]>;
+};
+
+export type LifecycleRecord = {
+    seq: number;
+    entity: EntityId;
+    component: ComponentId;
+    kind: 'add' | 'replace' | 'remove';
+};
+
+/** Component lifecycle changes since the last update, in the order they happened. */
+export type LifecycleEvent = {
+    kind: 'lifecycle';
+    records: LifecycleRecord[];
+};
+
+export type RelationTarget =
+    | { entity: EntityId }
+    | { asset: { type_name: string; id: string } };
+
+/** A typed edge beyond parent / child: camera -> window or image, mesh entity -> mesh or
+ * material asset, shadow-casting light -> caster entity. */
+export type Relation = {
+    source: EntityId;
+    kind: 'render_target' | 'mesh' | 'material' | 'shadow_caster';
+    target: RelationTarget;
+};
+
+/** Relation edges added and removed since the last update. */
+export type RelationsEvent = {
+    kind: 'relations';
+    added: Relation[];
+    removed: Relation[];
+};
+
+/** Smoothed value of a Bevy diagnostic, e.g. `frame/gpu_ms` or `vello/encode_ms`. */
+export type DiagnosticStat = {
+    path: string;
+    value: number;
+    suffix: string;
+};
+
+/** Diagnostics whose value changed since the last update. */
+export type StatsEvent = {
+    kind: 'stats';
+    stats: DiagnosticStat[];
+};
+
 export type ScheduleEvent = {
     kind: 'schedules';
     schedules: ScheduleInfo[];
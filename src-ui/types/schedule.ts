@@ -9,6 +9,14 @@ export type ScheduleInfo = {
 export type SystemInfo = {
     id: string;
     name: string;
+    ran: boolean;
+    condition_met: boolean | null;
+    ambiguities: Array<AmbiguityInfo>;
+};
+
+export type AmbiguityInfo = {
+    with: string;
+    components: string[];
 };
 
 export type SetInfo = {
@@ -2,10 +2,20 @@
 
 import init, {
   init_bevy_app,
+  init_bevy_app_with_options,
   is_preparation_completed,
   create_window_by_offscreen_canvas,
   enter_frame_with_mouse,
   left_bt_down,
+  mouse_raw_delta,
+  gamepad_update,
+  begin_text_edit,
+  end_text_edit,
+  text_input,
+  ime_composition,
+  set_a11y_export,
+  set_hover_info,
+  set_locale_strings,
   left_bt_up,
   set_auto_animation,
   resize,
@@ -14,9 +24,32 @@ import init, {
   key_up,
   set_panel_viewport,
   despawn_panel,
+  set_ui_exclusion_rects,
+  set_input_coordinate_frame,
+  set_idle_detection,
+  set_theme,
+  timeline_set_fps,
+  timeline_set_frame_snap,
+  set_cursor_effects,
+  set_input_hints,
+  set_overlay_damage_debug,
+  set_shortcuts,
   release_app,
+  reinit_canvas,
+  set_canvas_active,
+  sample_color_at,
+  sample_depth_at,
+  get_entity_path,
+  select_parent,
+  select_child,
+  list_annotations,
+  set_op_log_client,
+  get_ops_since,
+  apply_remote_ops,
   // Inspector FFI functions
   inspector_update_component,
+  inspector_update_component_multi,
+  inspector_adjust_component_field,
   inspector_toggle_component,
   inspector_remove_component,
   inspector_insert_component,
@@ -24,15 +57,41 @@ import init, {
   inspector_toggle_visibility,
   inspector_reparent_entity,
   inspector_spawn_entity,
+  inspector_spawn_shape,
+  spawn_at_cursor,
+  inspector_scatter_entities,
+  inspector_begin_transaction,
+  inspector_commit_transaction,
   // Streaming FFI functions
   enable_inspector_streaming,
   disable_inspector_streaming,
   set_inspector_streaming_frequency,
   force_inspector_update,
+  inspector_connect,
+  inspector_connect_websocket,
+  inspector_disconnect,
+  set_inspector_outline_mode,
+  set_inspector_reveal_hidden,
+  inspector_watch_entity,
+  set_inspector_snapshot_compression,
+  set_inspector_streaming_policy,
+  set_frame_trace_capture,
+  export_trace,
+  inspector_audit_world,
+  get_asset_dependencies,
+  fetch_image_preview,
+  get_mesh_info,
+  set_memory_budget,
+  unload_unused_assets,
+  unload_asset,
+  register_diagnostic,
+  push_diagnostic_value,
+  run_script,
   get_type_registry_schema,
   inspector_reset_streaming_state,
 } from "./wasm/ironfell.js";
 import { CadenceProbe } from "./runtime/cadence_probe";
+import { eventTime } from "./runtime/input_manager";
 
 class IronWorker {
   private probe = new CadenceProbe();
@@ -45,9 +104,13 @@ class IronWorker {
   private frameFlag = 0;
   private streamingEnabled = false;
   private rafId: number | null = null;
+  // Set while Rust reports the app settled (see set_idle_detection); the RAF loop
+  // stops until the next message comes in.
+  private idle = false;
   private latestMouseX: number = 0;
   private latestMouseY: number = 0;
   private hasMouseUpdate: boolean = false;
+  private latestMouseTime: number = NaN;
   private postedEnginePrepared: boolean = false;
 
   constructor() {
@@ -56,7 +119,33 @@ class IronWorker {
       send_pick_from_worker: (pickList: any[]) => this.sendPickFromWorker(pickList),
       send_hover_from_worker: (list: any[]) => this.sendHoverFromWorker(list),
       send_selection_from_worker: (list: any[]) => this.sendSelectionFromWorker(list),
-      send_inspector_update_from_worker: (updateJson: string) => this.sendInspectorUpdateFromWorker(updateJson)
+      send_inspector_update_from_worker: (updateJson: string) => this.sendInspectorUpdateFromWorker(updateJson),
+      send_overlay_png_from_worker: (requestId: number, png: Uint8Array) => this.sendOverlayPngFromWorker(requestId, png),
+      send_image_preview_from_worker: (requestId: number, png: Uint8Array) => this.sendImagePreviewFromWorker(requestId, png),
+      send_tool_changed_from_worker: (name: string) => this.sendToolChangedFromWorker(name),
+      send_cursor_from_worker: (cursor: string) => this.sendCursorFromWorker(cursor),
+      send_measurement_from_worker: (length: number, angleDeg: number) => this.sendMeasurementFromWorker(length, angleDeg),
+      send_log_from_worker: (recordJson: string) => this.sendLogFromWorker(recordJson),
+      send_canvas_status_from_worker: (statusJson: string) => this.sendCanvasStatusFromWorker(statusJson),
+      send_entity_id_error_from_worker: (errorJson: string) => this.sendEntityIdErrorFromWorker(errorJson),
+      send_benchmark_from_worker: (resultJson: string) => this.sendBenchmarkFromWorker(resultJson),
+      send_projections_from_worker: (projectionsJson: string) => this.sendProjectionsFromWorker(projectionsJson),
+      request_pointer_lock: (locked: boolean) => this.requestPointerLock(locked),
+      send_text_edit_from_worker: (editJson: string) => this.sendTextEditFromWorker(editJson),
+      send_a11y_tree_from_worker: (treeJson: string) => this.sendA11yTreeFromWorker(treeJson),
+      send_hover_info_from_worker: (infoJson: string) => this.sendHoverInfoFromWorker(infoJson),
+      send_inspector_client_update_from_worker: (clientId: number, updateJson: string) => this.sendInspectorClientUpdateFromWorker(clientId, updateJson),
+      send_inspector_gzip_update_from_worker: (clientId: number, gzip: Uint8Array) => this.sendInspectorGzipUpdateFromWorker(clientId, gzip),
+      send_warning_from_worker: (warningJson: string) => this.sendWarningFromWorker(warningJson),
+      send_screenshot_from_worker: (requestId: number, png: Uint8Array) => this.sendScreenshotFromWorker(requestId, png),
+      send_color_sample_from_worker: (requestId: number, colorJson: string) => this.sendColorSampleFromWorker(requestId, colorJson),
+      send_idle_from_worker: (isIdle: boolean) => this.sendIdleFromWorker(isIdle),
+      send_drag_preview_from_worker: (previewJson: string) => this.sendDragPreviewFromWorker(previewJson),
+      send_curve_edit_from_worker: (editJson: string) => this.sendCurveEditFromWorker(editJson),
+      send_timeline_state_from_worker: (stateJson: string) => this.sendTimelineStateFromWorker(stateJson),
+      send_timeline_event_from_worker: (time: number, payloadJson: string) => this.sendTimelineEventFromWorker(time, payloadJson),
+      send_shortcut_from_worker: (action: string, scope: string) => this.sendShortcutFromWorker(action, scope),
+      send_shortcut_conflicts_from_worker: (conflictsJson: string) => this.sendShortcutConflictsFromWorker(conflictsJson)
     };
 
     // Make it globally accessible
@@ -76,6 +165,10 @@ class IronWorker {
     // Listen for messages from the main thread
     self.onmessage = async (event) => {
       let data = event.data;
+      // Any message may change the world; run frames again until Rust reports idle.
+      if (this.idle && this.rafId === null && !this.isStoppedRunning) {
+        this.rafId = requestAnimationFrame((dt) => this.enterFrame(dt));
+      }
       switch (data.ty) {
 
         case "wasmData":
@@ -83,7 +176,10 @@ class IronWorker {
           console.log("Received WASM data from main thread, initializing...");
           await init(data.wasmData);
           console.log("WASM module initialized");
-          this.appHandle = init_bevy_app(data.variantFlags >>> 0);
+          // `options` (AppOptions JSON, e.g. { backend: "webgl2" }) takes precedence over variant flags
+          this.appHandle = data.options
+            ? init_bevy_app_with_options(JSON.stringify(data.options))
+            : init_bevy_app(data.variantFlags >>> 0);
           console.log("App handle initialized:", this.appHandle);
 
           // Notify the main thread that the worker is ready
@@ -95,6 +191,25 @@ class IronWorker {
           this.createWorkerAppWindow(data.canvas, data.devicePixelRatio);
           break;
 
+        case "reinitCanvas":
+          // New canvas after a context loss; progress comes back as "canvas_status"
+          if (this.appHandle !== BigInt(0) && reinit_canvas(this.appHandle, BigInt(data.canvasId ?? 0), data.canvas)) {
+            this.offscreenCanvas = data.canvas;
+          }
+          break;
+
+        case "setCanvasActive":
+          if (this.appHandle !== BigInt(0)) {
+            set_canvas_active(this.appHandle, BigInt(data.canvasId ?? 0), !!data.active);
+          }
+          break;
+
+        case "sampleColorAt":
+          if (this.appHandle !== BigInt(0)) {
+            sample_color_at(this.appHandle, BigInt(data.canvasId ?? 0), data.x, data.y);
+          }
+          break;
+
         case "resize":
           this.canvasResize(data.width, data.height);
           break;
@@ -105,6 +220,68 @@ class IronWorker {
           }
           break;
 
+        case "setUiExclusionRects":
+          if (this.appHandle !== BigInt(0)) {
+            set_ui_exclusion_rects(this.appHandle, JSON.stringify(data.rects ?? []));
+          }
+          break;
+
+        case "setTheme":
+          if (this.appHandle !== BigInt(0)) {
+            set_theme(this.appHandle, JSON.stringify(data.theme ?? {}));
+          }
+          break;
+
+        case "timelineSetFps":
+          if (this.appHandle !== BigInt(0)) {
+            timeline_set_fps(this.appHandle, data.fps >>> 0);
+          }
+          break;
+
+        case "timelineSetFrameSnap":
+          if (this.appHandle !== BigInt(0)) {
+            timeline_set_frame_snap(this.appHandle, !!data.enabled);
+          }
+          break;
+
+        case "setCursorEffects":
+          if (this.appHandle !== BigInt(0)) {
+            set_cursor_effects(this.appHandle, JSON.stringify(data.config ?? {}));
+          }
+          break;
+
+        case "setInputHints":
+          if (this.appHandle !== BigInt(0)) {
+            set_input_hints(this.appHandle, !!data.enabled);
+          }
+          break;
+
+        case "setOverlayDamageDebug":
+          if (this.appHandle !== BigInt(0)) {
+            set_overlay_damage_debug(this.appHandle, !!data.enabled);
+          }
+          break;
+
+        case "setShortcuts":
+          if (this.appHandle !== BigInt(0)) {
+            // `data.shortcuts`: [{ chord, action, scope }], or "default"
+            const json = data.shortcuts === "default" ? "default" : JSON.stringify(data.shortcuts ?? []);
+            set_shortcuts(this.appHandle, json);
+          }
+          break;
+
+        case "setIdleDetection":
+          if (this.appHandle !== BigInt(0)) {
+            set_idle_detection(this.appHandle, data.frames >>> 0);
+          }
+          break;
+
+        case "setInputCoordinateFrame":
+          if (this.appHandle !== BigInt(0)) {
+            set_input_coordinate_frame(this.appHandle, JSON.stringify(data.frame ?? {}));
+          }
+          break;
+
         case "despawnPanel":
           if (this.appHandle !== BigInt(0)) {
             despawn_panel(this.appHandle, data.id);
@@ -144,20 +321,76 @@ class IronWorker {
           // Buffer the latest mouse position; applied at the next frame tick
           this.latestMouseX = data.x;
           this.latestMouseY = data.y;
+          this.latestMouseTime = eventTime(data.t);
           this.hasMouseUpdate = true;
           break;
 
+        case "mouseRawDelta":
+          // Pointer-lock movementX/Y; every delta counts, so no coalescing
+          if (this.appHandle !== BigInt(0)) {
+            mouse_raw_delta(this.appHandle, data.dx, data.dy, eventTime(data.t));
+          }
+          break;
+
+        case "gamepadUpdate":
+          if (this.appHandle !== BigInt(0)) {
+            gamepad_update(this.appHandle, JSON.stringify(data.gamepad));
+          }
+          break;
+
+        case "beginTextEdit":
+          if (this.appHandle !== BigInt(0)) {
+            begin_text_edit(this.appHandle, BigInt(data.entity));
+          }
+          break;
+
+        case "endTextEdit":
+          if (this.appHandle !== BigInt(0)) {
+            end_text_edit(this.appHandle);
+          }
+          break;
+
+        case "textInput":
+          if (this.appHandle !== BigInt(0)) {
+            text_input(this.appHandle, data.text);
+          }
+          break;
+
+        case "imeComposition":
+          if (this.appHandle !== BigInt(0)) {
+            ime_composition(this.appHandle, data.text, data.start ?? -1, data.end ?? -1);
+          }
+          break;
+
+        case "setA11yExport":
+          if (this.appHandle !== BigInt(0)) {
+            set_a11y_export(this.appHandle, !!data.enabled);
+          }
+          break;
+
+        case "setHoverInfo":
+          if (this.appHandle !== BigInt(0)) {
+            set_hover_info(this.appHandle, !!data.enabled);
+          }
+          break;
+
+        case "setLocaleStrings":
+          if (this.appHandle !== BigInt(0)) {
+            set_locale_strings(this.appHandle, JSON.stringify(data.strings ?? {}));
+          }
+          break;
+
         case "leftBtDown":
-          left_bt_down(this.appHandle);
+          left_bt_down(this.appHandle, eventTime(data.t));
           break;
 
         case "leftBtUp":
-          left_bt_up(this.appHandle);
+          left_bt_up(this.appHandle, eventTime(data.t));
           break;
 
         case "mouseWheel":
           if (this.appHandle !== BigInt(0)) {
-            mouse_wheel(this.appHandle, data.dx, data.dy, data.mode);
+            mouse_wheel(this.appHandle, data.dx, data.dy, data.mode, eventTime(data.t));
           }
           break;
 
@@ -191,6 +424,33 @@ class IronWorker {
           }
           break;
 
+        case "inspector_update_component_multi":
+          if (this.appHandle !== BigInt(0)) {
+            const updated = inspector_update_component_multi(
+              this.appHandle,
+              `[${data.entity_ids.join(",")}]`,
+              data.component_id,
+              data.value_json,
+              data.merge_mode ?? "replace"
+            );
+            self.postMessage({ ty: "inspector_result", command: "update_component_multi", success: updated > 0, updated });
+          }
+          break;
+
+        case "inspector_adjust_component_field":
+          if (this.appHandle !== BigInt(0)) {
+            const value = inspector_adjust_component_field(
+              this.appHandle,
+              BigInt(data.entity_id),
+              data.component_id,
+              data.path,
+              data.op,
+              data.amount
+            );
+            self.postMessage({ ty: "inspector_result", command: "adjust_component_field", success: value !== undefined, value });
+          }
+          break;
+
         case "inspector_toggle_component":
           if (this.appHandle !== BigInt(0)) {
             const success = inspector_toggle_component(
@@ -272,6 +532,60 @@ class IronWorker {
           }
           break;
 
+        case "inspector_spawn_shape":
+          if (this.appHandle !== BigInt(0)) {
+            const entityId = inspector_spawn_shape(
+              this.appHandle,
+              data.kind,
+              JSON.stringify(data.params ?? {}),
+              data.parent_id ? BigInt(data.parent_id) : undefined
+            );
+            self.postMessage({
+              ty: "inspector_result",
+              command: "spawn_shape",
+              success: entityId !== BigInt(0),
+              entity_id: entityId.toString()
+            });
+          }
+          break;
+
+        case "spawn_at_cursor":
+          if (this.appHandle !== BigInt(0)) {
+            const entityId = spawn_at_cursor(this.appHandle, data.template);
+            self.postMessage({
+              ty: "inspector_result",
+              command: "spawn_at_cursor",
+              success: entityId !== BigInt(0),
+              entity_id: entityId.toString()
+            });
+          }
+          break;
+
+        case "inspector_scatter_entities":
+          if (this.appHandle !== BigInt(0)) {
+            const moved = inspector_scatter_entities(
+              this.appHandle,
+              `[${(data.entity_ids ?? []).join(",")}]`,
+              JSON.stringify(data.params ?? {})
+            );
+            self.postMessage({ ty: "inspector_result", command: "scatter_entities", success: moved > 0, moved });
+          }
+          break;
+
+        case "inspector_begin_transaction":
+          if (this.appHandle !== BigInt(0)) {
+            const success = inspector_begin_transaction(this.appHandle, data.label ?? "");
+            self.postMessage({ ty: "inspector_result", command: "begin_transaction", success });
+          }
+          break;
+
+        case "inspector_commit_transaction":
+          if (this.appHandle !== BigInt(0)) {
+            const success = inspector_commit_transaction(this.appHandle);
+            self.postMessage({ ty: "inspector_result", command: "commit_transaction", success });
+          }
+          break;
+
         case "enable_streaming":
           this.enableContinuousStreaming();
           break;
@@ -288,6 +602,187 @@ class IronWorker {
           this.forceInspectorUpdate();
           break;
 
+        case "inspector_connect":
+          if (this.appHandle !== BigInt(0)) {
+            const clientId = inspector_connect(this.appHandle);
+            self.postMessage({ ty: "inspector_connected", client_id: clientId, requestId: data.requestId });
+            force_inspector_update(this.appHandle);
+          }
+          break;
+
+        case "inspector_connect_websocket":
+          // Stream to a remote inspector UI over a WebSocket; client id 0 means it failed
+          if (this.appHandle !== BigInt(0)) {
+            const clientId = inspector_connect_websocket(this.appHandle, data.url);
+            self.postMessage({ ty: "inspector_connected", client_id: clientId, requestId: data.requestId });
+            force_inspector_update(this.appHandle);
+          }
+          break;
+
+        case "set_inspector_snapshot_compression":
+          if (this.appHandle !== BigInt(0)) {
+            set_inspector_snapshot_compression(this.appHandle, data.client_id ?? 0, !!data.enabled);
+          }
+          break;
+
+        case "set_inspector_streaming_policy":
+          if (this.appHandle !== BigInt(0)) {
+            set_inspector_streaming_policy(this.appHandle, JSON.stringify(data.policy ?? {}));
+          }
+          break;
+
+        case "set_frame_trace_capture":
+          if (this.appHandle !== BigInt(0)) {
+            set_frame_trace_capture(this.appHandle, data.frames ?? 0);
+          }
+          break;
+
+        case "export_trace":
+          if (this.appHandle !== BigInt(0)) {
+            self.postMessage({ ty: "frame_trace", trace: export_trace(this.appHandle), requestId: data.requestId });
+          }
+          break;
+
+        case "inspector_audit_world":
+          if (this.appHandle !== BigInt(0)) {
+            self.postMessage({ ty: "world_audit", report: JSON.parse(inspector_audit_world(this.appHandle)), requestId: data.requestId });
+          }
+          break;
+
+        case "get_asset_dependencies":
+          if (this.appHandle !== BigInt(0)) {
+            const graph = get_asset_dependencies(this.appHandle, data.asset);
+            self.postMessage({ ty: "asset_dependencies", graph: JSON.parse(graph), requestId: data.requestId });
+          }
+          break;
+
+        case "fetch_image_preview":
+          if (this.appHandle !== BigInt(0)) {
+            const previewId = fetch_image_preview(this.appHandle, BigInt(data.asset), data.max_size ?? 128);
+            self.postMessage({ ty: "image_preview_started", previewId, requestId: data.requestId });
+          }
+          break;
+
+        case "sample_depth_at":
+          if (this.appHandle !== BigInt(0)) {
+            const depth = sample_depth_at(this.appHandle, data.x, data.y);
+            self.postMessage({ ty: "depth_sample", sample: JSON.parse(depth), requestId: data.requestId });
+          }
+          break;
+
+        case "get_entity_path":
+          if (this.appHandle !== BigInt(0)) {
+            const path = get_entity_path(this.appHandle, BigInt(data.entity));
+            self.postMessage({ ty: "entity_path", path: JSON.parse(path), requestId: data.requestId });
+          }
+          break;
+
+        case "select_parent":
+          if (this.appHandle !== BigInt(0)) {
+            select_parent(this.appHandle);
+          }
+          break;
+
+        case "select_child":
+          if (this.appHandle !== BigInt(0)) {
+            select_child(this.appHandle, data.index ?? 0);
+          }
+          break;
+
+        case "list_annotations":
+          if (this.appHandle !== BigInt(0)) {
+            self.postMessage({ ty: "annotations", annotations: JSON.parse(list_annotations(this.appHandle)), requestId: data.requestId });
+          }
+          break;
+
+        case "set_op_log_client":
+          if (this.appHandle !== BigInt(0)) {
+            set_op_log_client(this.appHandle, data.client ?? "");
+          }
+          break;
+
+        case "get_ops_since":
+          if (this.appHandle !== BigInt(0)) {
+            const log = get_ops_since(this.appHandle, JSON.stringify(data.clock ?? {}));
+            self.postMessage({ ty: "op_log", log: JSON.parse(log), requestId: data.requestId });
+          }
+          break;
+
+        case "apply_remote_ops":
+          if (this.appHandle !== BigInt(0)) {
+            const result = apply_remote_ops(this.appHandle, JSON.stringify(data.ops ?? []));
+            self.postMessage({ ty: "remote_ops_applied", result: JSON.parse(result), requestId: data.requestId });
+          }
+          break;
+
+        case "get_mesh_info":
+          if (this.appHandle !== BigInt(0)) {
+            const info = get_mesh_info(this.appHandle, BigInt(data.asset), data.preview_edges ?? 0);
+            self.postMessage({ ty: "mesh_info", info: JSON.parse(info), requestId: data.requestId });
+          }
+          break;
+
+        case "set_memory_budget":
+          if (this.appHandle !== BigInt(0)) {
+            set_memory_budget(this.appHandle, JSON.stringify(data.budget ?? {}));
+          }
+          break;
+
+        case "unload_unused_assets":
+          if (this.appHandle !== BigInt(0)) {
+            self.postMessage({ ty: "assets_unloaded", result: JSON.parse(unload_unused_assets(this.appHandle)), requestId: data.requestId });
+          }
+          break;
+
+        case "unload_asset":
+          if (this.appHandle !== BigInt(0)) {
+            self.postMessage({ ty: "assets_unloaded", result: JSON.parse(unload_asset(this.appHandle, data.path)), requestId: data.requestId });
+          }
+          break;
+
+        case "register_diagnostic":
+          if (this.appHandle !== BigInt(0)) {
+            register_diagnostic(this.appHandle, data.name, data.unit ?? "");
+          }
+          break;
+
+        case "push_diagnostic_value":
+          if (this.appHandle !== BigInt(0)) {
+            push_diagnostic_value(this.appHandle, data.name, data.value);
+          }
+          break;
+
+        case "run_script":
+          if (this.appHandle !== BigInt(0)) {
+            const result = run_script(this.appHandle, JSON.stringify(data.script ?? { steps: [] }));
+            self.postMessage({ ty: "script_result", result: JSON.parse(result), requestId: data.requestId });
+          }
+          break;
+
+        case "inspector_disconnect":
+          if (this.appHandle !== BigInt(0)) {
+            inspector_disconnect(this.appHandle, data.client_id);
+          }
+          break;
+
+        case "set_inspector_outline_mode":
+          if (this.appHandle !== BigInt(0)) {
+            set_inspector_outline_mode(this.appHandle, data.client_id ?? 0, !!data.enabled);
+          }
+          break;
+
+        case "set_inspector_reveal_hidden":
+          if (this.appHandle !== BigInt(0)) {
+            set_inspector_reveal_hidden(this.appHandle, data.client_id ?? 0, !!data.enabled);
+          }
+          break;
+
+        case "inspector_watch_entity":
+          if (this.appHandle !== BigInt(0)) {
+            inspector_watch_entity(this.appHandle, data.client_id ?? 0, BigInt(data.entity_id), !!data.enabled);
+          }
+          break;
+
         case "get_type_registry_schema":
           const schema = this.getTypeRegistrySchema();
           self.postMessage({
@@ -356,10 +851,16 @@ class IronWorker {
       ) {
         const tickStart = performance.now();
         if (this.hasMouseUpdate) {
-          enter_frame_with_mouse(this.appHandle, this.latestMouseX, this.latestMouseY, true);
+          enter_frame_with_mouse(
+            this.appHandle,
+            this.latestMouseX,
+            this.latestMouseY,
+            true,
+            this.latestMouseTime,
+          );
           this.hasMouseUpdate = false;
         } else {
-          enter_frame_with_mouse(this.appHandle, 0, 0, false);
+          enter_frame_with_mouse(this.appHandle, 0, 0, false, NaN);
         }
         this.probe.record(rafTs, performance.now() - tickStart);
         this.frameIndex++;
@@ -369,7 +870,7 @@ class IronWorker {
       this.getPreparationState();
     }
 
-    if (!this.isStoppedRunning) {
+    if (!this.isStoppedRunning && !this.idle) {
       this.rafId = requestAnimationFrame((dt) => this.enterFrame(dt));
     }
   }
@@ -394,6 +895,113 @@ class IronWorker {
     self.postMessage({ ty: "selection", list });
   }
 
+  private sendOverlayPngFromWorker(requestId: number, png: Uint8Array) {
+    // Transfer the buffer; wasm-bindgen hands us a fresh copy of the bytes.
+    self.postMessage({ ty: "overlay_png", requestId, png }, { transfer: [png.buffer] });
+  }
+
+  private sendImagePreviewFromWorker(requestId: number, png: Uint8Array) {
+    self.postMessage({ ty: "image_preview", previewId: requestId, png }, { transfer: [png.buffer] });
+  }
+
+  private sendToolChangedFromWorker(name: string) {
+    self.postMessage({ ty: "tool_changed", name });
+  }
+
+  private sendCursorFromWorker(cursor: string) {
+    self.postMessage({ ty: "cursor", cursor });
+  }
+
+  private sendMeasurementFromWorker(length: number, angleDeg: number) {
+    self.postMessage({ ty: "measurement", length, angleDeg });
+  }
+
+  private sendLogFromWorker(recordJson: string) {
+    self.postMessage({ ty: "log", record: JSON.parse(recordJson) });
+  }
+
+  private sendShortcutFromWorker(action: string, scope: string) {
+    self.postMessage({ ty: "shortcut", action, scope });
+  }
+
+  private sendShortcutConflictsFromWorker(conflictsJson: string) {
+    self.postMessage({ ty: "shortcut_conflicts", conflicts: JSON.parse(conflictsJson) });
+  }
+
+  private sendTimelineEventFromWorker(time: number, payloadJson: string) {
+    self.postMessage({ ty: "timeline_event", time, payload: JSON.parse(payloadJson) });
+  }
+
+  private sendTimelineStateFromWorker(stateJson: string) {
+    self.postMessage({ ty: "timeline_state", state: JSON.parse(stateJson) });
+  }
+
+  private sendCurveEditFromWorker(editJson: string) {
+    self.postMessage({ ty: "curve_edit", edit: JSON.parse(editJson) });
+  }
+
+  private sendDragPreviewFromWorker(previewJson: string) {
+    self.postMessage({ ty: "drag_preview", preview: JSON.parse(previewJson) });
+  }
+
+  private sendIdleFromWorker(isIdle: boolean) {
+    this.idle = isIdle;
+    self.postMessage({ ty: "idle", idle: isIdle });
+  }
+
+  private sendEntityIdErrorFromWorker(errorJson: string) {
+    // Why an FFI call rejected an entity id: { caller, id, error, message, live }
+    self.postMessage({ ty: "entity_id_error", ...JSON.parse(errorJson) });
+  }
+
+  private sendCanvasStatusFromWorker(statusJson: string) {
+    self.postMessage({ ty: "canvas_status", status: JSON.parse(statusJson) });
+  }
+
+  private sendBenchmarkFromWorker(resultJson: string) {
+    self.postMessage({ ty: "benchmark", result: JSON.parse(resultJson) });
+  }
+
+  private sendProjectionsFromWorker(projectionsJson: string) {
+    self.postMessage({ ty: "projections", projections: JSON.parse(projectionsJson) });
+  }
+
+  private requestPointerLock(locked: boolean) {
+    self.postMessage({ ty: "pointer_lock", locked });
+  }
+
+  private sendTextEditFromWorker(editJson: string) {
+    self.postMessage({ ty: "text_edit", edit: JSON.parse(editJson) });
+  }
+
+  private sendA11yTreeFromWorker(treeJson: string) {
+    self.postMessage({ ty: "a11y_tree", tree: JSON.parse(treeJson) });
+  }
+
+  private sendHoverInfoFromWorker(infoJson: string) {
+    self.postMessage({ ty: "hover_info", info: JSON.parse(infoJson) });
+  }
+
+  private sendInspectorClientUpdateFromWorker(clientId: number, updateJson: string) {
+    self.postMessage({ ty: "inspector_update", client_id: clientId, update: JSON.parse(updateJson) });
+  }
+
+  private sendInspectorGzipUpdateFromWorker(clientId: number, gzip: Uint8Array) {
+    self.postMessage({ ty: "inspector_update_gzip", client_id: clientId, gzip }, { transfer: [gzip.buffer] });
+  }
+
+  private sendWarningFromWorker(warningJson: string) {
+    self.postMessage({ ty: "warning", warning: JSON.parse(warningJson) });
+  }
+
+  private sendScreenshotFromWorker(requestId: number, png: Uint8Array) {
+    self.postMessage({ ty: "screenshot", requestId, png }, { transfer: [png.buffer] });
+  }
+
+  private sendColorSampleFromWorker(requestId: number, colorJson: string) {
+    self.postMessage({ ty: "color_sample", requestId, color: JSON.parse(colorJson) });
+  }
+
   private sendInspectorUpdateFromWorker(updateJson: string) {
     try {
       const update = JSON.parse(updateJson);
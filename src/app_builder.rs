@@ -0,0 +1,155 @@
+//! Embedding API for downstream wasm crates.
+//!
+//! A consumer crate depends on `ironfell` as a library, builds the app with
+//! `IronfellAppBuilder` from its own `#[wasm_bindgen]` init function and hands the
+//! pointer to JS; every other Ironfell FFI function works on it unchanged.
+//!
+//! ```ignore
+//! #[wasm_bindgen]
+//! pub fn init_my_app() -> u64 {
+//!     IronfellAppBuilder::new()
+//!         .add_plugins(MyPlugin)
+//!         .track_component::<Spin>()
+//!         .add_action("reset_spin", |world, _args| {
+//!             world.query::<&mut Spin>().iter_mut(world).for_each(|mut s| s.0 = 0.0);
+//!             Ok(serde_json::Value::Null)
+//!         })
+//!         .into_ptr()
+//! }
+//! ```
+//!
+//! Actions are called from JS with `invoke_action(ptr, name, args_json)`.
+
+use bevy::app::Plugins;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy::reflect::GetTypeRegistration;
+use serde_json::{Value, json};
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::init_app;
+use crate::bevy_app::options::AppOptions;
+use crate::{ActivityControl, WorkerApp};
+
+pub type ActionFn = Box<dyn Fn(&mut World, Value) -> anyhow::Result<Value> + Send + Sync>;
+
+/// Named host-callable actions registered through `IronfellAppBuilder::add_action`.
+#[derive(Resource, Default)]
+pub struct Actions {
+    actions: HashMap<String, ActionFn>,
+}
+
+impl Actions {
+    pub fn insert(&mut self, name: impl Into<String>, action: ActionFn) {
+        self.actions.insert(name.into(), action);
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.actions.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+type AppHook = Box<dyn FnOnce(&mut App)>;
+
+#[derive(Default)]
+pub struct IronfellAppBuilder {
+    options: AppOptions,
+    hooks: Vec<AppHook>,
+    actions: Actions,
+}
+
+impl IronfellAppBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn options(mut self, options: AppOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Added after Ironfell's own plugins, so they can rely on its resources.
+    pub fn add_plugins<M>(mut self, plugins: impl Plugins<M> + 'static) -> Self {
+        self.hooks.push(Box::new(move |app: &mut App| {
+            app.add_plugins(plugins);
+        }));
+        self
+    }
+
+    /// Register a component's reflection data so the inspector can read and edit it.
+    pub fn track_component<C: Component + GetTypeRegistration>(mut self) -> Self {
+        self.hooks.push(Box::new(|app: &mut App| {
+            app.register_type::<C>();
+        }));
+        self
+    }
+
+    /// Expose `action` to JS as `invoke_action(ptr, name, args_json)`. It runs with
+    /// exclusive world access between frames.
+    pub fn add_action(
+        mut self,
+        name: impl Into<String>,
+        action: impl Fn(&mut World, Value) -> anyhow::Result<Value> + Send + Sync + 'static,
+    ) -> Self {
+        self.actions.insert(name, Box::new(action));
+        self
+    }
+
+    pub fn build(self) -> WorkerApp {
+        let mut app = init_app(0, &self.options);
+        for hook in self.hooks {
+            hook(&mut app);
+        }
+        app.insert_resource(self.actions);
+        app
+    }
+
+    /// `build` and leak the app as the pointer the FFI functions expect.
+    pub fn into_ptr(self) -> u64 {
+        Box::into_raw(Box::new(self.build())) as u64
+    }
+}
+
+/// Run a registered action. Returns `{"ok": result}` or `{"error": message}`.
+#[wasm_bindgen]
+pub fn invoke_action(ptr: u64, name: &str, args_json: &str) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let args = match args_json {
+        "" => Value::Null,
+        json => match serde_json::from_str(json) {
+            Ok(args) => args,
+            Err(err) => return json!({ "error": format!("invalid args: {err}") }).to_string(),
+        },
+    };
+    let world = app.world_mut();
+    if !world.contains_resource::<Actions>() {
+        return json!({ "error": "no actions registered" }).to_string();
+    }
+    let result = world.resource_scope(|world, actions: Mut<Actions>| {
+        let action = actions
+            .actions
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("unknown action `{name}`"))?;
+        action(world, args)
+    });
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    match result {
+        Ok(value) => json!({ "ok": value }),
+        Err(err) => json!({ "error": err.to_string() }),
+    }
+    .to_string()
+}
+
+/// JSON array of registered action names.
+#[wasm_bindgen]
+pub fn list_actions(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    app.world()
+        .get_resource::<Actions>()
+        .and_then(|actions| serde_json::to_string(&actions.names()).ok())
+        .unwrap_or_else(|| "[]".to_string())
+}
@@ -0,0 +1,248 @@
+//! Scripted stress tests for tracking picking / streaming performance regressions.
+//!
+//! `run_benchmark(ptr, name, params_json)` runs a scenario synchronously (driving
+//! `app.update()` itself) and posts the timings with `send_benchmark_from_worker`.
+//! Scenarios and their params (all optional):
+//! - `spawn_cubes` `{ count: 10000 }`: spawn, first frame, despawn, next frame.
+//! - `mutate_transforms` `{ count: 1000, frames: 60 }`: move every cube each frame.
+//! - `inspector_stream` `{ clients: 4, count: 1000, frames: 30 }`: move every cube
+//!   each frame and build + serialize inspector updates for each simulated client.
+//!
+//! Bench entities are despawned and bench inspector clients dropped afterwards.
+
+use bevy::platform::time::Instant;
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy_remote_inspector::{TrackedDatas, get_inspector_events};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::scene3d::{ActiveState, Shape};
+use crate::{ActivityControl, WorkerApp};
+
+/// Inspector client ids used by `inspector_stream`, clear of the host's own ids.
+const BENCH_CLIENT_BASE: u32 = 0xBE00_0000;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_benchmark_from_worker(result_json: &str);
+}
+
+#[derive(Component)]
+struct BenchEntity;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+struct BenchParams {
+    count: usize,
+    frames: usize,
+    clients: u32,
+}
+
+impl BenchParams {
+    fn defaults_for(name: &str) -> Self {
+        match name {
+            "spawn_cubes" => Self {
+                count: 10_000,
+                frames: 1,
+                clients: 0,
+            },
+            "inspector_stream" => Self {
+                count: 1000,
+                frames: 30,
+                clients: 4,
+            },
+            _ => Self::default(),
+        }
+    }
+}
+
+impl Default for BenchParams {
+    fn default() -> Self {
+        Self {
+            count: 1000,
+            frames: 60,
+            clients: 0,
+        }
+    }
+}
+
+/// Per-sample durations in milliseconds.
+#[derive(Default)]
+struct Samples(Vec<f64>);
+
+impl Samples {
+    fn time<T>(&mut self, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let out = f();
+        self.0.push(start.elapsed().as_secs_f64() * 1000.0);
+        out
+    }
+
+    fn summary(&self) -> Value {
+        let mut sorted = self.0.clone();
+        sorted.sort_by(f64::total_cmp);
+        let percentile = |p: f64| {
+            sorted
+                .get(((sorted.len() as f64 - 1.0) * p).round() as usize)
+                .copied()
+                .unwrap_or(0.0)
+        };
+        let total: f64 = sorted.iter().sum();
+        json!({
+            "samples": sorted.len(),
+            "total_ms": total,
+            "mean_ms": if sorted.is_empty() { 0.0 } else { total / sorted.len() as f64 },
+            "p50_ms": percentile(0.5),
+            "p95_ms": percentile(0.95),
+            "max_ms": sorted.last().copied().unwrap_or(0.0),
+        })
+    }
+}
+
+fn spawn_cubes(world: &mut World, count: usize) {
+    let mesh = world.resource_mut::<Assets<Mesh>>().add(Cuboid::default());
+    let material = world
+        .resource_mut::<Assets<StandardMaterial>>()
+        .add(Color::srgb(0.6, 0.62, 0.66));
+    let side = (count as f32).sqrt().ceil().max(1.0) as usize;
+    world.spawn_batch((0..count).map(move |i| {
+        let (x, z) = ((i % side) as f32, (i / side) as f32);
+        (
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_xyz(x * 1.5, 0.5, z * 1.5),
+            Shape::Box(Cuboid::default()),
+            ActiveState::default(),
+            RenderLayers::layer(0),
+            BenchEntity,
+        )
+    }));
+}
+
+fn despawn_bench_entities(world: &mut World) {
+    let entities: Vec<Entity> = world
+        .query_filtered::<Entity, With<BenchEntity>>()
+        .iter(world)
+        .collect();
+    for entity in entities {
+        world.despawn(entity);
+    }
+}
+
+fn move_cubes(world: &mut World, frame: usize) {
+    let offset = (frame as f32 * 0.1).sin() * 0.5;
+    let mut cubes = world.query_filtered::<&mut Transform, With<BenchEntity>>();
+    for mut transform in cubes.iter_mut(world) {
+        transform.translation.y = 0.5 + offset;
+    }
+}
+
+fn bench_spawn_cubes(app: &mut App, params: BenchParams) -> Value {
+    let mut spawn = Samples::default();
+    let mut first_frame = Samples::default();
+    let mut despawn = Samples::default();
+    let mut after_despawn = Samples::default();
+    spawn.time(|| spawn_cubes(app.world_mut(), params.count));
+    first_frame.time(|| app.update());
+    despawn.time(|| despawn_bench_entities(app.world_mut()));
+    after_despawn.time(|| app.update());
+    json!({
+        "spawn": spawn.summary(),
+        "first_frame": first_frame.summary(),
+        "despawn": despawn.summary(),
+        "frame_after_despawn": after_despawn.summary(),
+    })
+}
+
+fn bench_mutate_transforms(app: &mut App, params: BenchParams) -> Value {
+    spawn_cubes(app.world_mut(), params.count);
+    app.update();
+    let mut frames = Samples::default();
+    for frame in 0..params.frames {
+        frames.time(|| {
+            move_cubes(app.world_mut(), frame);
+            app.update();
+        });
+    }
+    despawn_bench_entities(app.world_mut());
+    json!({ "frame": frames.summary() })
+}
+
+fn bench_inspector_stream(app: &mut App, params: BenchParams) -> Value {
+    let clients: Vec<u32> = (0..params.clients).map(|i| BENCH_CLIENT_BASE + i).collect();
+    spawn_cubes(app.world_mut(), params.count);
+    app.update();
+    let (mut frames, mut stream) = (Samples::default(), Samples::default());
+    let mut bytes = 0;
+    for frame in 0..params.frames {
+        frames.time(|| {
+            move_cubes(app.world_mut(), frame);
+            app.update();
+        });
+        for &client in &clients {
+            bytes += stream.time(|| {
+                let events = get_inspector_events(app.world_mut(), client);
+                serde_json::to_string(&events).map_or(0, |json| json.len())
+            });
+        }
+    }
+    despawn_bench_entities(app.world_mut());
+    if let Some(mut tracked_datas) = app.world_mut().get_resource_mut::<TrackedDatas>() {
+        for client in &clients {
+            tracked_datas.remove(client);
+        }
+    }
+    json!({
+        "frame": frames.summary(),
+        "stream_per_client": stream.summary(),
+        "bytes": bytes,
+    })
+}
+
+/// Run a benchmark scenario (`spawn_cubes`, `mutate_transforms`, `inspector_stream`)
+/// with optional JSON params. Results are posted as `{ name, params, results }`.
+/// Returns false for an unknown scenario, invalid params, or before the renderer
+/// is ready.
+#[wasm_bindgen]
+pub fn run_benchmark(ptr: u64, name: &str, params_json: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Ok(Value::Object(mut merged)) = serde_json::to_value(BenchParams::defaults_for(name))
+    else {
+        return false;
+    };
+    if !params_json.is_empty() {
+        let Ok(Value::Object(fields)) = serde_json::from_str::<Value>(params_json) else {
+            return false;
+        };
+        merged.extend(fields);
+    }
+    let Ok(params) = serde_json::from_value::<BenchParams>(Value::Object(merged)) else {
+        return false;
+    };
+    if app.plugins_state() != bevy::app::PluginsState::Cleaned
+        || !app.world().contains_resource::<Assets<StandardMaterial>>()
+    {
+        return false;
+    }
+
+    let results = match name {
+        "spawn_cubes" => bench_spawn_cubes(app, params),
+        "mutate_transforms" => bench_mutate_transforms(app, params),
+        "inspector_stream" => bench_inspector_stream(app, params),
+        _ => return false,
+    };
+    let report = json!({
+        "name": name,
+        "params": params,
+        "results": results,
+    });
+    send_benchmark_from_worker(&report.to_string());
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
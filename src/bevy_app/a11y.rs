@@ -0,0 +1,254 @@
+//! Accessibility tree export, so the host page can mirror the scene into ARIA.
+//!
+//! Off by default; `set_a11y_export(ptr, true)` turns it on. While on,
+//! `export_a11y_tree` summarizes selectable content (3D objects with a pick volume,
+//! overlay vector paths and text labels) after it changes, at most every
+//! `REFRESH_SECS`, and pushes it through `send_a11y_tree_from_worker(json)` when it
+//! differs from the last push:
+//!
+//! `{ role: "application", name, children: [{ role: "group", name, children: [node] }] }`
+//! with `node = { entity, name, role, bounds: { x, y, w, h } | null, selected }`
+//! (plus `value` for text). Bounds are canvas physical px like panel rects; null when
+//! the entity is off screen, behind the camera or (text) not measurable.
+
+use bevy::prelude::*;
+use bevy_vello::prelude::*;
+use kurbo::Shape as _;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::scene3d::{CurrentVolume, MainCamera3D};
+use crate::bevy_app::text_edit::VectorText;
+use crate::bevy_app::vector::{VectorNode, transform_affine};
+use crate::panels::{Panels, VIEWER_PANEL, overlay_affine};
+use crate::{ActivityControl, Locale, SelectionState, WorkerApp};
+
+/// Minimum time between two tree rebuilds, seconds.
+const REFRESH_SECS: f32 = 0.25;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_a11y_tree_from_worker(tree_json: &str);
+}
+
+#[derive(Resource, Default)]
+pub struct A11yExport {
+    pub enabled: bool,
+    /// Something the tree depends on changed since the last rebuild.
+    pending: bool,
+    since_refresh: f32,
+    last_sent: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+struct Bounds {
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+}
+
+impl Bounds {
+    fn from_points(points: impl IntoIterator<Item = Vec2>) -> Option<Self> {
+        let (min, max) = points.into_iter().fold(
+            (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+            |(min, max), p| (min.min(p), max.max(p)),
+        );
+        (min.x <= max.x).then_some(Bounds {
+            x: min.x,
+            y: min.y,
+            w: max.x - min.x,
+            h: max.y - min.y,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct A11yNode {
+    entity: u64,
+    name: String,
+    role: &'static str,
+    bounds: Option<Bounds>,
+    selected: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct A11yGroup {
+    role: &'static str,
+    name: String,
+    children: Vec<A11yNode>,
+}
+
+fn display_name(entity: Entity, name: Option<&Name>, role: &str) -> String {
+    name.map_or_else(|| format!("{role} {}", entity.index()), |n| n.to_string())
+}
+
+/// Screen rect of a world-space box through the 3D camera; None if any corner is
+/// behind the camera or the box is entirely outside the viewport.
+fn project_volume(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    volume: &CurrentVolume,
+) -> Option<Bounds> {
+    let (min, max) = (Vec3::from(volume.min), Vec3::from(volume.max));
+    let corners = (0..8).map(|i| {
+        Vec3::new(
+            if i & 1 == 0 { min.x } else { max.x },
+            if i & 2 == 0 { min.y } else { max.y },
+            if i & 4 == 0 { min.z } else { max.z },
+        )
+    });
+    let points: Option<Vec<Vec2>> = corners
+        .map(|corner| camera.world_to_viewport(camera_transform, corner).ok())
+        .collect();
+    let bounds = Bounds::from_points(points?)?;
+    let viewport = camera.logical_viewport_rect()?;
+    let visible = bounds.x <= viewport.max.x
+        && bounds.y <= viewport.max.y
+        && bounds.x + bounds.w >= viewport.min.x
+        && bounds.y + bounds.h >= viewport.min.y;
+    visible.then_some(bounds)
+}
+
+type A11yContent = Or<(With<CurrentVolume>, With<VectorNode>, With<VectorText>)>;
+type A11yChanged = Or<(
+    Changed<CurrentVolume>,
+    Changed<VectorNode>,
+    Changed<VectorText>,
+    Changed<Transform>,
+    Changed<Name>,
+)>;
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn export_a11y_tree(
+    time: Res<Time>,
+    mut export: ResMut<A11yExport>,
+    activity: Option<ResMut<ActivityControl>>,
+    selection: Res<SelectionState>,
+    panels: Res<Panels>,
+    locale: Res<Locale>,
+    cameras: Query<(&Camera, Ref<GlobalTransform>), With<MainCamera3D>>,
+    changed: Query<(), (A11yChanged, A11yContent)>,
+    mut removed: (
+        RemovedComponents<CurrentVolume>,
+        RemovedComponents<VectorNode>,
+        RemovedComponents<VectorText>,
+    ),
+    objects: Query<(Entity, Option<&Name>, &CurrentVolume)>,
+    paths: Query<(Entity, Option<&Name>, &Transform, &VectorNode)>,
+    texts: Query<(Entity, Option<&Name>, &VectorText)>,
+) {
+    if !export.enabled {
+        return;
+    }
+    let any_removed =
+        removed.0.read().count() + removed.1.read().count() + removed.2.read().count() > 0;
+    let camera_moved = cameras.iter().any(|(_, transform)| transform.is_changed());
+    export.pending |= any_removed
+        || camera_moved
+        || selection.is_changed()
+        || panels.is_changed()
+        || locale.is_changed()
+        || !changed.is_empty();
+    export.since_refresh += time.delta_secs();
+    if !export.pending {
+        return;
+    }
+    if export.since_refresh < REFRESH_SECS {
+        // Keep frames coming until the throttled rebuild goes out.
+        if let Some(mut activity) = activity {
+            activity.remaining_frames = activity.remaining_frames.max(1);
+        }
+        return;
+    }
+    export.pending = false;
+    export.since_refresh = 0.0;
+
+    let selected = |entity: Entity| selection.selected.contains_key(&entity);
+    let camera = cameras
+        .single()
+        .ok()
+        .map(|(camera, transform)| (camera, transform.into_inner()));
+    let mut viewer: Vec<A11yNode> = objects
+        .iter()
+        .map(|(entity, name, volume)| A11yNode {
+            entity: entity.to_bits(),
+            name: display_name(entity, name, locale.get("a11y.object")),
+            role: "object",
+            bounds: camera
+                .and_then(|(camera, transform)| project_volume(camera, transform, volume)),
+            selected: selected(entity),
+            value: None,
+        })
+        .collect();
+
+    let base = panels.rect(VIEWER_PANEL).map(overlay_affine);
+    let mut overlay: Vec<A11yNode> = paths
+        .iter()
+        .map(|(entity, name, transform, node)| {
+            let bounds = base.map(|base| {
+                let rect = (base * transform_affine(transform) * node.bez_path()).bounding_box();
+                Bounds {
+                    x: rect.x0 as f32,
+                    y: rect.y0 as f32,
+                    w: rect.width() as f32,
+                    h: rect.height() as f32,
+                }
+            });
+            A11yNode {
+                entity: entity.to_bits(),
+                name: display_name(entity, name, locale.get("a11y.path")),
+                role: "path",
+                bounds: bounds.filter(|_| !node.anchors.is_empty()),
+                selected: selected(entity),
+                value: None,
+            }
+        })
+        .collect();
+    overlay.extend(texts.iter().map(|(entity, name, text)| A11yNode {
+        entity: entity.to_bits(),
+        name: display_name(entity, name, locale.get("a11y.text")),
+        role: "text",
+        bounds: None,
+        selected: selected(entity),
+        value: Some(text.text.clone()),
+    }));
+    // Stable order so unchanged scenes serialize identically.
+    viewer.sort_by_key(|node| node.entity);
+    overlay.sort_by_key(|node| node.entity);
+
+    let tree = serde_json::json!({
+        "role": "application",
+        "name": locale.get("a11y.application"),
+        "children": [
+            A11yGroup { role: "group", name: locale.get("a11y.viewer").to_string(), children: viewer },
+            A11yGroup { role: "group", name: locale.get("a11y.overlay").to_string(), children: overlay },
+        ],
+    });
+    let json = tree.to_string();
+    if json != export.last_sent {
+        send_a11y_tree_from_worker(&json);
+        export.last_sent = json;
+    }
+}
+
+/// Turn the accessibility tree export on or off. Turning it on pushes the current
+/// tree on the next frame.
+#[wasm_bindgen]
+pub fn set_a11y_export(ptr: u64, enabled: bool) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(mut export) = app.world_mut().get_resource_mut::<A11yExport>() else {
+        return false;
+    };
+    export.enabled = enabled;
+    export.pending = true;
+    export.since_refresh = REFRESH_SECS;
+    export.last_sent.clear();
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
@@ -0,0 +1,219 @@
+//! Annotations: sticky notes attached to entities, for reviewing shared scenes.
+//!
+//! `Annotation` is reflected with a default, so a note can be added to any entity from
+//! the inspector and its text, author, color and anchor offset edited there like any
+//! other component. Each note shows as a small marker over the 3D viewer at the
+//! entity's position plus `offset`, drawn on the overlay layer; the note text itself
+//! stays in the host UI, which reads every note with `list_annotations(ptr)` (and can
+//! pin its own popups to the markers with `track_entity_projections`).
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy_vello::prelude::*;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::CoordinateMapper;
+use crate::bevy_app::encode_cache::{ContentHasher, EncodeKey};
+use crate::bevy_app::render_timing::VelloEncodeSet;
+use crate::bevy_app::scene3d::MainCamera3D;
+use crate::bevy_app::theme::Theme;
+use crate::{SelectionState, WorkerApp};
+
+/// Marker note side, px.
+const NOTE_SIZE: f64 = 14.0;
+/// Gap between the anchor dot and the note above it, px.
+const NOTE_LIFT: f64 = 6.0;
+const DOG_EAR: f64 = 4.0;
+const ANCHOR_RADIUS: f64 = 2.5;
+
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+pub struct Annotation {
+    pub text: String,
+    pub author: String,
+    pub color: Color,
+    /// Marker anchor relative to the entity's position, world units.
+    pub offset: Vec3,
+}
+
+impl Default for Annotation {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            author: String::new(),
+            color: Color::srgb(1.0, 0.85, 0.3),
+            offset: Vec3::ZERO,
+        }
+    }
+}
+
+impl Annotation {
+    fn anchor(&self, transform: &GlobalTransform) -> Vec3 {
+        transform.translation() + self.offset
+    }
+}
+
+#[derive(Component)]
+struct AnnotationScene;
+
+pub struct AnnotationsPlugin;
+
+impl Plugin for AnnotationsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Annotation>()
+            .add_systems(Startup, setup_annotation_markers)
+            .add_systems(Update, render_annotation_markers.in_set(VelloEncodeSet));
+    }
+}
+
+fn setup_annotation_markers(mut commands: Commands) {
+    // Editor chrome: not an `OverlayLayer`, so exports skip it.
+    commands.spawn((
+        VelloScene::new(),
+        VelloScreenSpace,
+        RenderLayers::layer(1),
+        AnnotationScene,
+        EncodeKey::default(),
+    ));
+}
+
+type AnnotatedEntity<'a> = (
+    Entity,
+    &'a Annotation,
+    &'a GlobalTransform,
+    Option<&'a InheritedVisibility>,
+);
+
+fn render_annotation_markers(
+    mut scenes: Query<(&mut VelloScene, &mut EncodeKey), With<AnnotationScene>>,
+    annotations: Query<AnnotatedEntity>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera3D>>,
+    mapper: Res<CoordinateMapper>,
+    selection: Res<SelectionState>,
+    theme: Res<Theme>,
+) {
+    let Ok((mut scene, mut key)) = scenes.single_mut() else {
+        return;
+    };
+    let viewer = cameras.single().ok().filter(|(camera, _)| camera.is_active);
+    let viewport = viewer.and_then(|(camera, _)| mapper.viewport_rect(camera));
+
+    // (window position, camera distance, color, selected), drawn far to near so nearer
+    // notes overlap farther ones.
+    let mut markers: Vec<(Vec2, f32, [f32; 4], bool)> = Vec::new();
+    if let (Some((camera, camera_transform)), Some(viewport)) = (viewer, viewport) {
+        for (entity, annotation, transform, visibility) in &annotations {
+            if visibility.is_some_and(|v| !v.get()) {
+                continue;
+            }
+            let anchor = annotation.anchor(transform);
+            let Some(position) = mapper.world_to_window(camera, camera_transform, anchor)
+            else {
+                continue;
+            };
+            if !viewport.contains(position) {
+                continue;
+            }
+            markers.push((
+                position,
+                anchor.distance(camera_transform.translation()),
+                annotation.color.to_srgba().to_f32_array(),
+                selection.selected.contains_key(&entity),
+            ));
+        }
+    }
+    markers.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut hasher = ContentHasher::default().u32(theme.revision);
+    for (position, _, color, selected) in &markers {
+        hasher = hasher.vec2(*position).bool(*selected);
+        for channel in color {
+            hasher = hasher.f32(*channel);
+        }
+    }
+    if !key.update(hasher.finish()) {
+        return;
+    }
+    scene.reset();
+    let outline = kurbo::Stroke::new(1.0);
+    let selected_outline = kurbo::Stroke::new(2.0);
+    for (position, _, color, selected) in &markers {
+        let anchor = kurbo::Point::new(position.x as f64, position.y as f64);
+        let note = note_path(anchor);
+        let border = if *selected {
+            Theme::faded(theme.selection, 1.0)
+        } else {
+            Theme::faded([0.0, 0.0, 0.0, 1.0], 0.6)
+        };
+        scene.fill(
+            peniko::Fill::NonZero,
+            kurbo::Affine::IDENTITY,
+            Theme::faded(*color, 1.0),
+            None,
+            &note,
+        );
+        scene.stroke(
+            if *selected { &selected_outline } else { &outline },
+            kurbo::Affine::IDENTITY,
+            border,
+            None,
+            &note,
+        );
+        scene.fill(
+            peniko::Fill::NonZero,
+            kurbo::Affine::IDENTITY,
+            border,
+            None,
+            &kurbo::Circle::new(anchor, ANCHOR_RADIUS),
+        );
+    }
+}
+
+/// Note outline with a folded top-right corner, sitting just above `anchor`.
+fn note_path(anchor: kurbo::Point) -> kurbo::BezPath {
+    let x0 = anchor.x - NOTE_SIZE / 2.0;
+    let x1 = anchor.x + NOTE_SIZE / 2.0;
+    let y1 = anchor.y - NOTE_LIFT;
+    let y0 = y1 - NOTE_SIZE;
+    let mut path = kurbo::BezPath::new();
+    path.move_to((x0, y0));
+    path.line_to((x1 - DOG_EAR, y0));
+    path.line_to((x1, y0 + DOG_EAR));
+    path.line_to((x1, y1));
+    path.line_to((x0, y1));
+    path.close_path();
+    // The fold.
+    path.move_to((x1 - DOG_EAR, y0));
+    path.line_to((x1 - DOG_EAR, y0 + DOG_EAR));
+    path.line_to((x1, y0 + DOG_EAR));
+    path
+}
+
+/// Every annotation as a JSON array of `{ entity, name, text, author, color, offset,
+/// position }` in entity order: `color` is sRGB `[r, g, b, a]` (0-1), `offset` the
+/// anchor offset and `position` the anchor's world position.
+#[wasm_bindgen]
+pub fn list_annotations(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    let mut query =
+        world.query::<(Entity, &Annotation, Option<&GlobalTransform>, Option<&Name>)>();
+    let mut annotations: Vec<_> = query.iter(world).collect();
+    annotations.sort_by_key(|(entity, ..)| *entity);
+    let annotations: Vec<serde_json::Value> = annotations
+        .into_iter()
+        .map(|(entity, annotation, transform, name)| {
+            let position = transform.map_or(annotation.offset, |t| annotation.anchor(t));
+            serde_json::json!({
+                "entity": entity.to_bits(),
+                "name": name.map(Name::as_str),
+                "text": annotation.text,
+                "author": annotation.author,
+                "color": annotation.color.to_srgba().to_f32_array(),
+                "offset": annotation.offset.to_array(),
+                "position": position.to_array(),
+            })
+        })
+        .collect();
+    serde_json::to_string(&annotations).unwrap_or_else(|_| "[]".to_string())
+}
@@ -0,0 +1,419 @@
+//! Asset dependency queries for the asset browser.
+//!
+//! `get_asset_dependencies` resolves an asset by path or id and returns what it depends
+//! on, recursively, plus the entities that use it directly, through another asset or
+//! through one of its labeled sub-assets, so the UI can tell whether deleting it is
+//! safe. Dependencies are:
+//! - handles in the asset's reflected value (a material's textures);
+//! - for a `Scene`, handles in its entities' reflected components;
+//! - for an unlabeled path (e.g. a glTF file), the labeled sub-assets loaded from it.
+//!
+//! Image nodes also carry their sampler settings.
+
+use std::any::TypeId;
+
+use bevy::asset::{AssetIndex, AssetPath, LoadState, ReflectAsset, ReflectHandle, UntypedAssetId};
+use bevy::ecs::component::ComponentInfo;
+use bevy::ecs::world::EntityRef;
+use bevy::gltf::Gltf;
+use bevy::image::ImageSampler;
+use bevy::platform::collections::{HashMap, HashSet};
+use bevy::prelude::*;
+use bevy::reflect::{PartialReflect, ReflectRef, TypeRegistry};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use wasm_bindgen::prelude::*;
+
+use crate::WorkerApp;
+
+/// Nesting limit when walking reflected values for handles.
+const MAX_REFLECT_DEPTH: usize = 8;
+/// Nesting limit of the returned dependency tree.
+const MAX_TREE_DEPTH: usize = 16;
+
+/// Collect the ids of the asset handles in `value` and its fields.
+pub(crate) fn find_handles(
+    value: &dyn PartialReflect,
+    registry: &TypeRegistry,
+    depth: usize,
+    found: &mut Vec<UntypedAssetId>,
+) {
+    if depth > MAX_REFLECT_DEPTH {
+        return;
+    }
+    if let Some(reflect) = value.try_as_reflect() {
+        let any = reflect.as_any();
+        if let Some(reflect_handle) = registry.get_type_data::<ReflectHandle>(any.type_id()) {
+            if let Some(handle) = reflect_handle.downcast_handle_untyped(any) {
+                found.push(handle.id());
+            }
+            return;
+        }
+    }
+    let depth = depth + 1;
+    match value.reflect_ref() {
+        ReflectRef::Struct(s) => s
+            .iter_fields()
+            .for_each(|field| find_handles(field, registry, depth, found)),
+        ReflectRef::TupleStruct(s) => s
+            .iter_fields()
+            .for_each(|field| find_handles(field, registry, depth, found)),
+        ReflectRef::Tuple(t) => t
+            .iter_fields()
+            .for_each(|field| find_handles(field, registry, depth, found)),
+        ReflectRef::List(l) => l
+            .iter()
+            .for_each(|item| find_handles(item, registry, depth, found)),
+        ReflectRef::Array(a) => a
+            .iter()
+            .for_each(|item| find_handles(item, registry, depth, found)),
+        ReflectRef::Map(m) => m
+            .iter()
+            .for_each(|(_, item)| find_handles(item, registry, depth, found)),
+        ReflectRef::Set(s) => s
+            .iter()
+            .for_each(|item| find_handles(item, registry, depth, found)),
+        ReflectRef::Enum(e) => e
+            .iter_fields()
+            .for_each(|field| find_handles(field.value(), registry, depth, found)),
+        _ => {}
+    }
+}
+
+/// Call `f` with each reflected component of `entity_ref` that holds asset handles.
+pub(crate) fn component_handles(
+    world: &World,
+    registry: &TypeRegistry,
+    entity_ref: EntityRef,
+    mut f: impl FnMut(&ComponentInfo, Vec<UntypedAssetId>),
+) {
+    for component_id in entity_ref.archetype().components() {
+        let Some(info) = world.components().get_info(component_id) else {
+            continue;
+        };
+        let Some(reflect_component) = info
+            .type_id()
+            .and_then(|type_id| registry.get_type_data::<ReflectComponent>(type_id))
+        else {
+            continue;
+        };
+        let Some(value) = reflect_component.reflect(entity_ref) else {
+            continue;
+        };
+        let mut found = Vec::new();
+        find_handles(value.as_partial_reflect(), registry, 0, &mut found);
+        if !found.is_empty() {
+            f(info, found);
+        }
+    }
+}
+
+fn asset_type_name(registry: &TypeRegistry, type_id: TypeId) -> &'static str {
+    if let Some(registration) = registry.get(type_id) {
+        return registration.type_info().type_path_table().short_path();
+    }
+    if type_id == TypeId::of::<Scene>() {
+        "Scene"
+    } else if type_id == TypeId::of::<Gltf>() {
+        "Gltf"
+    } else {
+        "?"
+    }
+}
+
+/// `{ "asset_type", "asset" }` identifying `id`; `asset` is the index bits or UUID.
+/// `get_asset_dependencies` accepts the same object.
+pub(crate) fn asset_ref(registry: &TypeRegistry, id: UntypedAssetId) -> Value {
+    let asset = match id {
+        UntypedAssetId::Index { index, .. } => json!(index.to_bits()),
+        UntypedAssetId::Uuid { uuid, .. } => json!(uuid.to_string()),
+    };
+    json!({ "asset_type": asset_type_name(registry, id.type_id()), "asset": asset })
+}
+
+#[derive(Deserialize)]
+struct AssetRefInput {
+    asset_type: String,
+    asset: Value,
+}
+
+/// Resolve a path (`"Spaceship.glb"`, `"Spaceship.glb#Scene0"`) or an `asset_ref`
+/// object to an asset id.
+fn resolve(
+    world: &World,
+    registry: &TypeRegistry,
+    handle_or_path: &str,
+) -> Result<UntypedAssetId, String> {
+    if handle_or_path.trim_start().starts_with('{') {
+        let input: AssetRefInput =
+            serde_json::from_str(handle_or_path).map_err(|e| format!("invalid handle: {e}"))?;
+        let type_id = match input.asset_type.as_str() {
+            "Scene" => TypeId::of::<Scene>(),
+            "Gltf" => TypeId::of::<Gltf>(),
+            name => registry
+                .get_with_short_type_path(name)
+                .or_else(|| registry.get_with_type_path(name))
+                .ok_or_else(|| format!("unknown asset type {name}"))?
+                .type_id(),
+        };
+        return match input.asset {
+            Value::Number(bits) => bits
+                .as_u64()
+                .map(|bits| UntypedAssetId::Index {
+                    type_id,
+                    index: AssetIndex::from_bits(bits),
+                })
+                .ok_or_else(|| "invalid asset index".to_string()),
+            Value::String(uuid) => uuid
+                .parse()
+                .map(|uuid| UntypedAssetId::Uuid { type_id, uuid })
+                .map_err(|e| format!("invalid asset uuid: {e}")),
+            _ => Err("asset must be an index or a uuid".to_string()),
+        };
+    }
+    let server = world
+        .get_resource::<AssetServer>()
+        .ok_or_else(|| "no asset server".to_string())?;
+    let path = AssetPath::try_parse(handle_or_path)
+        .map_err(|e| format!("invalid asset path {handle_or_path}: {e}"))?;
+    server
+        .get_path_id(path)
+        .ok_or_else(|| format!("no asset loaded from {handle_or_path}"))
+}
+
+/// Every reflected or scene asset that was loaded from a path, with the path.
+pub(crate) fn asset_paths(
+    world: &World,
+    registry: &TypeRegistry,
+) -> Vec<(UntypedAssetId, AssetPath<'static>)> {
+    let Some(server) = world.get_resource::<AssetServer>() else {
+        return Vec::new();
+    };
+    let mut ids: Vec<UntypedAssetId> = registry
+        .iter_with_data::<ReflectAsset>()
+        .flat_map(|(_, reflect_asset)| reflect_asset.ids(world))
+        .collect();
+    if let Some(scenes) = world.get_resource::<Assets<Scene>>() {
+        ids.extend(scenes.ids().map(UntypedAssetId::from));
+    }
+    ids.into_iter()
+        .filter_map(|id| Some((id, server.get_path(id)?.into_owned())))
+        .collect()
+}
+
+/// The assets in `paths` loaded from `file` under a label.
+pub(crate) fn sub_assets<'a>(
+    paths: &'a [(UntypedAssetId, AssetPath<'static>)],
+    file: &'a AssetPath,
+) -> impl Iterator<Item = UntypedAssetId> + 'a {
+    paths
+        .iter()
+        .filter(move |(_, path)| path.label().is_some() && path.clone().without_label() == *file)
+        .map(|(id, _)| *id)
+}
+
+struct Graph<'a> {
+    world: &'a World,
+    registry: &'a TypeRegistry,
+    server: Option<&'a AssetServer>,
+    /// Every reflected or scene asset with a path, for finding labeled sub-assets.
+    paths: Vec<(UntypedAssetId, AssetPath<'static>)>,
+    dependencies: HashMap<UntypedAssetId, Vec<UntypedAssetId>>,
+}
+
+impl<'a> Graph<'a> {
+    fn new(world: &'a World, registry: &'a TypeRegistry) -> Self {
+        Self {
+            world,
+            registry,
+            server: world.get_resource::<AssetServer>(),
+            paths: asset_paths(world, registry),
+            dependencies: HashMap::default(),
+        }
+    }
+
+    fn path(&self, id: UntypedAssetId) -> Option<AssetPath<'static>> {
+        self.server?.get_path(id).map(AssetPath::into_owned)
+    }
+
+    /// Whether `id` was loaded from `file` under a label (a glTF's meshes, scenes, ...).
+    fn is_sub_asset(&self, id: UntypedAssetId, file: UntypedAssetId) -> bool {
+        match (self.path(id), self.path(file)) {
+            (Some(path), Some(file)) => {
+                file.label().is_none() && path.label().is_some() && path.without_label() == file
+            }
+            _ => false,
+        }
+    }
+
+    /// Direct dependencies of `id`, cached.
+    fn dependencies(&mut self, id: UntypedAssetId) -> Vec<UntypedAssetId> {
+        if let Some(dependencies) = self.dependencies.get(&id) {
+            return dependencies.clone();
+        }
+        let mut found = Vec::new();
+        let value = self
+            .registry
+            .get_type_data::<ReflectAsset>(id.type_id())
+            .and_then(|reflect_asset| reflect_asset.get(self.world, UntypedHandle::Weak(id)));
+        if let Some(value) = value {
+            find_handles(value.as_partial_reflect(), self.registry, 0, &mut found);
+        }
+        let scene = id
+            .try_typed::<Scene>()
+            .ok()
+            .and_then(|scene| self.world.get_resource::<Assets<Scene>>()?.get(scene));
+        if let Some(scene) = scene {
+            for entity_ref in scene.world.iter_entities() {
+                component_handles(&scene.world, self.registry, entity_ref, |_, handles| {
+                    found.extend(handles);
+                });
+            }
+        }
+        if let Some(path) = self.path(id).filter(|path| path.label().is_none()) {
+            found.extend(sub_assets(&self.paths, &path));
+        }
+        let mut seen = HashSet::new();
+        found.retain(|dependency| *dependency != id && seen.insert(*dependency));
+        self.dependencies.insert(id, found.clone());
+        found
+    }
+
+    /// Whether `target` is among the dependencies of `id`, transitively.
+    fn depends_on(&mut self, id: UntypedAssetId, target: UntypedAssetId) -> bool {
+        let mut stack = vec![id];
+        let mut visited = HashSet::new();
+        while let Some(current) = stack.pop() {
+            if !visited.insert(current) {
+                continue;
+            }
+            for dependency in self.dependencies(current) {
+                if dependency == target {
+                    return true;
+                }
+                stack.push(dependency);
+            }
+        }
+        false
+    }
+
+    fn state(&self, id: UntypedAssetId) -> Value {
+        let loaded = self
+            .registry
+            .get_type_data::<ReflectAsset>(id.type_id())
+            .map(|reflect_asset| {
+                reflect_asset
+                    .get(self.world, UntypedHandle::Weak(id))
+                    .is_some()
+            });
+        if loaded == Some(true) {
+            return json!("loaded");
+        }
+        match self.server.and_then(|server| server.get_load_state(id)) {
+            Some(LoadState::Loaded) => json!("loaded"),
+            Some(LoadState::Loading) => json!("loading"),
+            Some(LoadState::Failed(error)) => json!({ "failed": error.to_string() }),
+            _ if loaded.is_none() => json!("unknown"),
+            _ => json!("missing"),
+        }
+    }
+
+    fn sampler(&self, id: UntypedAssetId) -> Option<Value> {
+        let image = self
+            .world
+            .get_resource::<Assets<Image>>()?
+            .get(id.try_typed::<Image>().ok()?)?;
+        Some(match &image.sampler {
+            ImageSampler::Default => json!("default"),
+            ImageSampler::Descriptor(descriptor) => json!({
+                "address_mode": [
+                    format!("{:?}", descriptor.address_mode_u),
+                    format!("{:?}", descriptor.address_mode_v),
+                    format!("{:?}", descriptor.address_mode_w),
+                ],
+                "mag_filter": format!("{:?}", descriptor.mag_filter),
+                "min_filter": format!("{:?}", descriptor.min_filter),
+                "mipmap_filter": format!("{:?}", descriptor.mipmap_filter),
+            }),
+        })
+    }
+
+    fn node(&self, id: UntypedAssetId) -> Value {
+        let mut node = asset_ref(self.registry, id);
+        node["path"] = json!(self.path(id).map(|path| path.to_string()));
+        node
+    }
+
+    /// The dependency tree under `id`. Assets already shown elsewhere in the tree are
+    /// marked `"repeated": true` and not expanded again.
+    fn tree(
+        &mut self,
+        id: UntypedAssetId,
+        depth: usize,
+        shown: &mut HashSet<UntypedAssetId>,
+    ) -> Value {
+        let mut node = self.node(id);
+        node["state"] = self.state(id);
+        if let Some(sampler) = self.sampler(id) {
+            node["sampler"] = sampler;
+        }
+        if !shown.insert(id) {
+            node["repeated"] = json!(true);
+            return node;
+        }
+        let dependencies = if depth < MAX_TREE_DEPTH {
+            self.dependencies(id)
+        } else {
+            Vec::new()
+        };
+        node["dependencies"] = dependencies
+            .into_iter()
+            .map(|dependency| self.tree(dependency, depth + 1, shown))
+            .collect();
+        node
+    }
+}
+
+/// Dependencies and users of the asset at a path (`"Spaceship.glb"`,
+/// `"Spaceship.glb#Material0"`) or with an id (`{ "asset_type": "Image", "asset": bits }`).
+/// Returns `{ "root": node, "users": [{ "entity", "component", "via" }] }`, where a
+/// node is `{ "asset_type", "asset", "path", "state", "sampler"?, "dependencies": [node] }`
+/// and `via` is null for direct use or the node of the asset the use goes through.
+/// `state` is "loaded", "loading", "missing", "unknown" or `{ "failed": message }`.
+/// Returns `{ "error": message }` if the asset cannot be resolved.
+#[wasm_bindgen]
+pub fn get_asset_dependencies(ptr: u64, handle_or_path: &str) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = &*app.world_mut();
+    let type_registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = type_registry.read();
+
+    let target = match resolve(world, &registry, handle_or_path) {
+        Ok(id) => id,
+        Err(error) => return json!({ "error": error }).to_string(),
+    };
+    let mut graph = Graph::new(world, &registry);
+    let root = graph.tree(target, 0, &mut HashSet::new());
+
+    let mut users = Vec::new();
+    for entity_ref in world.iter_entities() {
+        component_handles(world, &registry, entity_ref, |info, handles| {
+            for handle in handles {
+                let via = if handle == target {
+                    Value::Null
+                } else if graph.is_sub_asset(handle, target) || graph.depends_on(handle, target) {
+                    graph.node(handle)
+                } else {
+                    continue;
+                };
+                users.push(json!({
+                    "entity": entity_ref.id().to_bits(),
+                    "component": info.name(),
+                    "via": via,
+                }));
+            }
+        });
+    }
+
+    json!({ "root": root, "users": users }).to_string()
+}
@@ -0,0 +1,218 @@
+//! Distance fog and a procedural gradient sky for the 3D viewer.
+//!
+//! The `Atmosphere` resource is inserted by `setup_3d_scene` (both parts off, so the
+//! default look is unchanged) and edited with `set_atmosphere(ptr, json)`, which merges
+//! the given fields. The sky is an infinitely distant cubemap `Skybox` generated from
+//! horizon / zenith / ground colors and a sun disk; an environment map set through
+//! `set_environment` takes precedence over it. Colors are sRGB.
+
+use bevy::core_pipeline::Skybox;
+use bevy::pbr::{DistanceFog, FogFalloff};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::environment::{ENVIRONMENT_BRIGHTNESS, cubemap_image, render_faces};
+use crate::bevy_app::history;
+use crate::bevy_app::scene3d::MainCamera3D;
+use crate::{ActivityControl, WorkerApp};
+
+const SKY_SIZE: u32 = 128;
+
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkySettings {
+    pub enabled: bool,
+    pub horizon: [f32; 3],
+    pub zenith: [f32; 3],
+    pub ground: [f32; 3],
+    /// Direction towards the sun (y-up); normalized when the sky is built.
+    pub sun_direction: Vec3,
+    pub sun_color: [f32; 3],
+    /// Angular radius of the sun disk, degrees.
+    pub sun_size_deg: f32,
+}
+
+impl Default for SkySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            horizon: [0.87, 0.91, 0.95],
+            zenith: [0.35, 0.55, 0.85],
+            ground: [0.35, 0.33, 0.3],
+            sun_direction: Vec3::new(0.4, 0.6, 0.3),
+            sun_color: [1.0, 0.95, 0.85],
+            sun_size_deg: 1.5,
+        }
+    }
+}
+
+impl SkySettings {
+    fn radiance(&self, dir: Vec3) -> Vec3 {
+        let linear = |c: [f32; 3]| {
+            Vec3::from_slice(&Color::srgb_from_array(c).to_linear().to_f32_array()[..3])
+        };
+        let (horizon, zenith, ground) = (
+            linear(self.horizon),
+            linear(self.zenith),
+            linear(self.ground),
+        );
+        let base = if dir.y < 0.0 {
+            horizon.lerp(ground, (-dir.y * 10.0).min(1.0))
+        } else {
+            horizon.lerp(zenith, dir.y.sqrt())
+        };
+        let sun_cos = self.sun_size_deg.to_radians().cos();
+        let sun = dir.dot(self.sun_direction.normalize_or(Vec3::Y)) >= sun_cos;
+        if sun {
+            linear(self.sun_color) * 20.0
+        } else {
+            base
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FogSettings {
+    pub enabled: bool,
+    pub color: [f32; 3],
+    /// Linear falloff distances (world units).
+    pub start: f32,
+    pub end: f32,
+}
+
+impl Default for FogSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            color: [0.85, 0.88, 0.92],
+            start: 20.0,
+            end: 120.0,
+        }
+    }
+}
+
+#[derive(Resource, Debug, Clone, Default, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct Atmosphere {
+    pub sky: SkySettings,
+    pub fog: FogSettings,
+}
+
+/// Push `Atmosphere` onto the main camera. Also re-runs when an environment map is
+/// removed so the gradient sky comes back.
+pub(crate) fn apply_atmosphere(
+    mut commands: Commands,
+    atmosphere: Option<Res<Atmosphere>>,
+    cameras: Query<(Entity, Has<EnvironmentMapLight>), With<MainCamera3D>>,
+    mut removed_environment: RemovedComponents<EnvironmentMapLight>,
+    mut images: ResMut<Assets<Image>>,
+    mut sky_image: Local<Option<(SkySettings, Handle<Image>)>>,
+) {
+    let environment_removed = removed_environment.read().count() > 0;
+    let Some(atmosphere) = atmosphere else {
+        return;
+    };
+    if !(atmosphere.is_changed() || environment_removed) {
+        return;
+    }
+    let Ok((camera, has_environment)) = cameras.single() else {
+        return;
+    };
+    let mut camera = commands.entity(camera);
+
+    let fog = &atmosphere.fog;
+    if fog.enabled {
+        camera.insert(DistanceFog {
+            color: Color::srgb_from_array(fog.color),
+            directional_light_color: Color::srgb_from_array(atmosphere.sky.sun_color)
+                .with_alpha(0.5),
+            falloff: FogFalloff::Linear {
+                start: fog.start,
+                end: fog.end.max(fog.start),
+            },
+            ..default()
+        });
+    } else {
+        camera.remove::<DistanceFog>();
+    }
+
+    if has_environment {
+        return;
+    }
+    let sky = &atmosphere.sky;
+    if !sky.enabled {
+        camera.remove::<Skybox>();
+        return;
+    }
+    let image = match &*sky_image {
+        Some((built, image)) if built == sky => image.clone(),
+        _ => {
+            let faces = render_faces(SKY_SIZE, |dir| sky.radiance(dir));
+            let image = images.add(cubemap_image(faces, SKY_SIZE));
+            *sky_image = Some((sky.clone(), image.clone()));
+            image
+        }
+    };
+    camera.insert(Skybox {
+        image,
+        brightness: ENVIRONMENT_BRIGHTNESS,
+        ..default()
+    });
+}
+
+/// Merge atmosphere fields (`{"sky": {...}, "fog": {...}}`, any subset) into the
+/// current settings. Returns false on invalid JSON.
+#[wasm_bindgen]
+pub fn set_atmosphere(ptr: u64, json: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Ok(patch) = serde_json::from_str::<Value>(json) else {
+        return false;
+    };
+    let Some(atmosphere) = app.world().get_resource::<Atmosphere>() else {
+        return false;
+    };
+    let Ok(mut merged) = serde_json::to_value(atmosphere) else {
+        return false;
+    };
+    merge_json(&mut merged, patch);
+    let Ok(updated) = serde_json::from_value::<Atmosphere>(merged) else {
+        return false;
+    };
+    history::edit_resource(
+        app.world_mut(),
+        "Atmosphere",
+        Atmosphere::clone,
+        |atmosphere, value| *atmosphere = value,
+        |atmosphere| *atmosphere = updated,
+    );
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
+
+/// Current atmosphere as JSON.
+#[wasm_bindgen]
+pub fn get_atmosphere(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    app.world()
+        .get_resource::<Atmosphere>()
+        .and_then(|atmosphere| serde_json::to_string(atmosphere).ok())
+        .unwrap_or_else(|| "{}".to_string())
+}
+
+/// Recursively overwrite `base` with the fields present in `patch`.
+pub(crate) fn merge_json(base: &mut Value, patch: Value) {
+    match (base, patch) {
+        (Value::Object(base), Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge_json(base.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (base, patch) => *base = patch,
+    }
+}
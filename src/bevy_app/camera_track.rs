@@ -0,0 +1,253 @@
+//! Camera keyframing and cinematic playback for the main 3D camera.
+//!
+//! The camera's `CameraTrack` holds poses (translation, rotation, vertical FOV) keyed on
+//! the timeline; each key's `easing` shapes the move to the next key (`Bezier` moves
+//! linearly, cameras have no tangent handles). `camera_add_key` records the current
+//! pose at the playhead.
+//!
+//! With "play through camera" on (`set_play_through_camera`), the track drives the
+//! camera whenever the timeline time changes, and the interactive `CameraController` is
+//! disabled while the timeline plays. When playback stops it is re-enabled, picking up
+//! yaw and pitch from wherever the fly-through left the camera.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::keyframes::Easing;
+use crate::bevy_app::scene3d::MainCamera3D;
+use crate::bevy_app::timeline::TimelineState;
+use crate::camera_controller::CameraController;
+use crate::{ActivityControl, WorkerApp};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraKey {
+    /// Seconds.
+    pub time: f64,
+    pub translation: Vec3,
+    pub rotation: Quat,
+    /// Vertical field of view, degrees.
+    pub fov_deg: f32,
+    /// Interpolation from this key to the next.
+    pub easing: Easing,
+}
+
+impl Default for CameraKey {
+    fn default() -> Self {
+        Self {
+            time: 0.0,
+            translation: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            fov_deg: 60.0,
+            easing: Easing::Linear,
+        }
+    }
+}
+
+/// Keyed poses of the camera it is on, sorted by time.
+#[derive(Component, Debug, Clone, Default)]
+pub struct CameraTrack {
+    pub keys: Vec<CameraKey>,
+}
+
+impl CameraTrack {
+    /// Insert `key`, replacing a key at the same time.
+    pub fn insert(&mut self, key: CameraKey) {
+        match self.keys.iter_mut().find(|k| (k.time - key.time).abs() < 1e-6) {
+            Some(existing) => *existing = key,
+            None => {
+                self.keys.push(key);
+                self.sort();
+            }
+        }
+    }
+
+    pub fn sort(&mut self) {
+        self.keys.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+
+    /// Pose at `time`; held before the first and after the last key.
+    pub fn sample(&self, time: f64) -> Option<CameraKey> {
+        let first = self.keys.first()?;
+        let last = self.keys.last()?;
+        if time <= first.time {
+            return Some(*first);
+        }
+        if time >= last.time {
+            return Some(*last);
+        }
+        let i = self.keys.partition_point(|k| k.time <= time) - 1;
+        let (a, b) = (&self.keys[i], &self.keys[i + 1]);
+        let span = (b.time - a.time).max(f64::EPSILON);
+        let t = a.easing.ease(((time - a.time) / span) as f32);
+        Some(CameraKey {
+            time,
+            translation: a.translation.lerp(b.translation, t),
+            rotation: a.rotation.slerp(b.rotation, t),
+            fov_deg: a.fov_deg + (b.fov_deg - a.fov_deg) * t,
+            easing: a.easing,
+        })
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct CinematicPlayback {
+    /// Drive the camera from its track ("play through camera").
+    pub play_through: bool,
+    /// The controller's `enabled` from before playback took it over.
+    suspended: Option<bool>,
+}
+
+/// Drive the main camera from its track and hand the controller over during playback.
+pub(crate) fn apply_camera_track(
+    timeline: Res<TimelineState>,
+    mut playback: ResMut<CinematicPlayback>,
+    mut cameras: Query<
+        (
+            &CameraTrack,
+            &mut Transform,
+            &mut Projection,
+            Option<&mut CameraController>,
+        ),
+        With<MainCamera3D>,
+    >,
+) {
+    let Ok((track, mut transform, mut projection, controller)) = cameras.single_mut() else {
+        return;
+    };
+    let driving = playback.play_through && !track.keys.is_empty();
+    let playing = driving && timeline.playing;
+    if let Some(mut controller) = controller {
+        if playing && playback.suspended.is_none() {
+            playback.suspended = Some(controller.enabled);
+            controller.enabled = false;
+        } else if !playing && playback.suspended.is_some() {
+            controller.enabled = playback.suspended.take().unwrap_or(true);
+            // Re-read yaw / pitch from the pose the playback left.
+            controller.initialized = false;
+            controller.velocity = Vec3::ZERO;
+        }
+    }
+    if !driving || !(timeline.is_changed() || playback.is_changed()) {
+        return;
+    }
+    let Some(pose) = track.sample(timeline.current_time) else {
+        return;
+    };
+    transform.translation = pose.translation;
+    transform.rotation = pose.rotation;
+    if let Projection::Perspective(perspective) = projection.as_mut() {
+        perspective.fov = pose.fov_deg.to_radians();
+    }
+}
+
+fn request_frames(app: &mut WorkerApp) {
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+}
+
+fn main_camera(world: &mut World) -> Option<Entity> {
+    world
+        .query_filtered::<Entity, With<MainCamera3D>>()
+        .iter(world)
+        .next()
+}
+
+/// Key the main camera's current pose at the playhead (replacing a key at that time).
+/// Returns the number of camera keys, or 0 without a 3D camera.
+#[wasm_bindgen]
+pub fn camera_add_key(ptr: u64) -> u32 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    let Some(camera) = main_camera(world) else {
+        return 0;
+    };
+    let time = world
+        .get_resource::<TimelineState>()
+        .map_or(0.0, |timeline| timeline.current_time);
+    let transform = world.get::<Transform>(camera).copied().unwrap_or_default();
+    let fov_deg = match world.get::<Projection>(camera) {
+        Some(Projection::Perspective(perspective)) => perspective.fov.to_degrees(),
+        _ => CameraKey::default().fov_deg,
+    };
+    let key = CameraKey {
+        time,
+        translation: transform.translation,
+        rotation: transform.rotation,
+        fov_deg,
+        easing: Easing::EaseInOut,
+    };
+    if world.get::<CameraTrack>(camera).is_none() {
+        world.entity_mut(camera).insert(CameraTrack::default());
+    }
+    let Some(mut track) = world.get_mut::<CameraTrack>(camera) else {
+        return 0;
+    };
+    track.insert(key);
+    let count = track.keys.len() as u32;
+    request_frames(app);
+    count
+}
+
+/// Replace the camera keys with a JSON array of `CameraKey`. False on invalid JSON.
+#[wasm_bindgen]
+pub fn camera_set_keys(ptr: u64, keys_json: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Ok(keys) = serde_json::from_str::<Vec<CameraKey>>(keys_json) else {
+        return false;
+    };
+    let world = app.world_mut();
+    let Some(camera) = main_camera(world) else {
+        return false;
+    };
+    let mut track = CameraTrack { keys };
+    track.sort();
+    world.entity_mut(camera).insert(track);
+    request_frames(app);
+    true
+}
+
+/// The camera keys as a JSON array of `CameraKey`.
+#[wasm_bindgen]
+pub fn camera_get_keys(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    let keys = main_camera(world)
+        .and_then(|camera| world.get::<CameraTrack>(camera))
+        .map_or(&[][..], |track| &track.keys);
+    serde_json::to_string(keys).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Remove camera key `index`.
+#[wasm_bindgen]
+pub fn camera_remove_key(ptr: u64, index: usize) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    let Some(camera) = main_camera(world) else {
+        return false;
+    };
+    let Some(mut track) = world.get_mut::<CameraTrack>(camera) else {
+        return false;
+    };
+    if index >= track.keys.len() {
+        return false;
+    }
+    track.keys.remove(index);
+    request_frames(app);
+    true
+}
+
+/// Turn "play through camera" on or off: the camera follows its track, and the
+/// interactive controller is disabled while the timeline plays.
+#[wasm_bindgen]
+pub fn set_play_through_camera(ptr: u64, enabled: bool) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(mut playback) = app.world_mut().get_resource_mut::<CinematicPlayback>() else {
+        return false;
+    };
+    playback.play_through = enabled;
+    request_frames(app);
+    true
+}
@@ -0,0 +1,163 @@
+//! Transform constraints for simple rigging.
+//!
+//! Each constraint is its own component, referencing a target entity, so an entity can
+//! combine them (e.g. follow one entity while looking at another). They are applied in
+//! `PostUpdate` before transform propagation, in the order `CopyPosition`,
+//! `LimitDistance`, `LookAt`, and write the constrained entity's local `Transform`
+//! (converted through its parent's transform). Targets are read at their current
+//! `Transform`s, so a chain of constrained entities settles within a frame per link.
+//! A missing or despawned target leaves the entity as it is. All three are reflected
+//! with defaults, so they can be added and edited from the inspector.
+
+use bevy::prelude::*;
+use bevy::transform::helper::TransformHelper;
+
+/// Rotate the entity so its forward (-Z) axis points at `target`, keeping `up` up.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+pub struct LookAt {
+    pub target: Entity,
+    pub up: Vec3,
+}
+
+impl Default for LookAt {
+    fn default() -> Self {
+        Self {
+            target: Entity::PLACEHOLDER,
+            up: Vec3::Y,
+        }
+    }
+}
+
+/// Place the entity at `target`'s world position plus a world-space `offset`.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+pub struct CopyPosition {
+    pub target: Entity,
+    pub offset: Vec3,
+}
+
+impl Default for CopyPosition {
+    fn default() -> Self {
+        Self {
+            target: Entity::PLACEHOLDER,
+            offset: Vec3::ZERO,
+        }
+    }
+}
+
+/// Keep the entity between `min` and `max` world units from `target`, moving it along
+/// the line to the target.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+pub struct LimitDistance {
+    pub target: Entity,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Default for LimitDistance {
+    fn default() -> Self {
+        Self {
+            target: Entity::PLACEHOLDER,
+            min: 0.0,
+            max: 10.0,
+        }
+    }
+}
+
+type Transforms<'w, 's> = ParamSet<
+    'w,
+    's,
+    (
+        TransformHelper<'static, 'static>,
+        Query<'static, 'static, &'static mut Transform>,
+    ),
+>;
+
+/// World transform of `entity`'s parent (identity for roots).
+fn parent_global(
+    helper: &TransformHelper,
+    parents: &Query<&ChildOf>,
+    entity: Entity,
+) -> GlobalTransform {
+    parents
+        .get(entity)
+        .ok()
+        .and_then(|child_of| helper.compute_global_transform(child_of.parent()).ok())
+        .unwrap_or_default()
+}
+
+/// Move `entity` so its world position is `position`.
+fn set_world_position(
+    transforms: &mut Transforms,
+    parents: &Query<&ChildOf>,
+    entity: Entity,
+    position: Vec3,
+) {
+    let parent = parent_global(&transforms.p0(), parents, entity);
+    let local = parent.affine().inverse().transform_point3(position);
+    if let Ok(mut transform) = transforms.p1().get_mut(entity)
+        && transform.translation != local
+    {
+        transform.translation = local;
+    }
+}
+
+pub(crate) fn apply_constraints(
+    copy_position: Query<(Entity, &CopyPosition)>,
+    limit_distance: Query<(Entity, &LimitDistance)>,
+    look_at: Query<(Entity, &LookAt)>,
+    parents: Query<&ChildOf>,
+    mut transforms: Transforms,
+) {
+    for (entity, constraint) in &copy_position {
+        let Ok(target) = transforms.p0().compute_global_transform(constraint.target) else {
+            continue;
+        };
+        let position = target.translation() + constraint.offset;
+        set_world_position(&mut transforms, &parents, entity, position);
+    }
+
+    for (entity, constraint) in &limit_distance {
+        let helper = transforms.p0();
+        let (Ok(own), Ok(target)) = (
+            helper.compute_global_transform(entity),
+            helper.compute_global_transform(constraint.target),
+        ) else {
+            continue;
+        };
+        let offset = own.translation() - target.translation();
+        let distance = offset.length();
+        let limited = distance.clamp(constraint.min, constraint.max.max(constraint.min));
+        // Direction is undefined at the target itself; leave the entity there.
+        if distance <= f32::EPSILON || limited == distance {
+            continue;
+        }
+        let position = target.translation() + offset / distance * limited;
+        set_world_position(&mut transforms, &parents, entity, position);
+    }
+
+    for (entity, constraint) in &look_at {
+        let helper = transforms.p0();
+        let (Ok(own), Ok(target)) = (
+            helper.compute_global_transform(entity),
+            helper.compute_global_transform(constraint.target),
+        ) else {
+            continue;
+        };
+        if own.translation().distance_squared(target.translation()) <= f32::EPSILON {
+            continue;
+        }
+        let world_rotation = Transform::from_translation(own.translation())
+            .looking_at(target.translation(), constraint.up)
+            .rotation;
+        let parent = parent_global(&helper, &parents, entity);
+        let local = parent.rotation().inverse() * world_rotation;
+        if let Ok(mut transform) = transforms.p1().get_mut(entity)
+            && transform.rotation != local
+        {
+            transform.rotation = local;
+        }
+    }
+}
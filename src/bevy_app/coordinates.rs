@@ -0,0 +1,428 @@
+//! One place for every coordinate convention between the DOM and Bevy.
+//!
+//! The host reports positions in CSS px relative to a corner of the canvas; Bevy's
+//! window space is physical px, top-left origin, y-down; the 2D overlay is authored
+//! with its origin at the viewer panel center, y-up; the 3D camera wants viewport NDC.
+//! `CoordinateMapper` holds the per-window host convention (origin corner, y direction,
+//! device pixel ratio) plus the window's physical size, and every FFI input conversion,
+//! overlay mapping and camera ray goes through it instead of flipping axes in place.
+
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::window::WindowRef;
+
+use crate::panels::PanelRect;
+
+/// Corner (or center) of a rect that a coordinate frame measures from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Origin {
+    #[default]
+    TopLeft,
+    BottomLeft,
+    Center,
+}
+
+/// Direction of increasing y in a coordinate frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum YAxis {
+    #[default]
+    Down,
+    Up,
+}
+
+/// A 2D coordinate convention relative to some rect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+pub struct Frame {
+    #[serde(default)]
+    pub origin: Origin,
+    #[serde(default)]
+    pub y_axis: YAxis,
+}
+
+impl Frame {
+    /// Bevy window space (and DOM client coordinates): top-left origin, y-down.
+    pub const WINDOW: Frame = Frame {
+        origin: Origin::TopLeft,
+        y_axis: YAxis::Down,
+    };
+    /// 2D overlay world: panel-center origin, y-up.
+    pub const OVERLAY: Frame = Frame {
+        origin: Origin::Center,
+        y_axis: YAxis::Up,
+    };
+
+    fn origin_in(self, rect: Rect) -> Vec2 {
+        match self.origin {
+            Origin::TopLeft => rect.min,
+            Origin::BottomLeft => Vec2::new(rect.min.x, rect.max.y),
+            Origin::Center => rect.center(),
+        }
+    }
+
+    fn y_sign(self) -> f32 {
+        match self.y_axis {
+            YAxis::Down => 1.0,
+            YAxis::Up => -1.0,
+        }
+    }
+
+    /// Point in this frame (relative to `rect`) to window space.
+    pub fn frame_to_window(self, rect: Rect, p: Vec2) -> Vec2 {
+        self.origin_in(rect) + Vec2::new(p.x, p.y * self.y_sign())
+    }
+
+    /// Window-space point to this frame (relative to `rect`).
+    pub fn window_to_frame(self, rect: Rect, p: Vec2) -> Vec2 {
+        let d = p - self.origin_in(rect);
+        Vec2::new(d.x, d.y * self.y_sign())
+    }
+}
+
+/// Host convention and size of one window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowCoordinates {
+    /// Frame the host reports positions in, relative to the canvas.
+    pub host: Frame,
+    /// CSS px to physical px.
+    pub dpr: f32,
+    /// Physical size of the window.
+    pub size: Vec2,
+}
+
+impl Default for WindowCoordinates {
+    fn default() -> Self {
+        Self {
+            host: Frame::WINDOW,
+            dpr: 1.0,
+            size: Vec2::ZERO,
+        }
+    }
+}
+
+impl WindowCoordinates {
+    /// Canvas rect in host units (CSS px).
+    fn host_rect(&self) -> Rect {
+        Rect::from_corners(Vec2::ZERO, self.size / self.dpr)
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct CoordinateMapper {
+    windows: HashMap<Entity, WindowCoordinates>,
+    primary: Option<Entity>,
+}
+
+impl CoordinateMapper {
+    /// Register a window (or change its device pixel ratio). The first window
+    /// registered is the primary.
+    pub fn configure(&mut self, window: Entity, dpr: f32) {
+        self.windows.entry(window).or_default().dpr = if dpr > 0.0 { dpr } else { 1.0 };
+        self.primary.get_or_insert(window);
+    }
+
+    /// Change the frame the host reports positions in for `window`.
+    pub fn set_host_frame(&mut self, window: Entity, host: Frame) {
+        self.windows.entry(window).or_default().host = host;
+    }
+
+    /// Track a window's new physical size after the canvas was resized.
+    pub fn resize(&mut self, window: Entity, size: Vec2) {
+        self.windows.entry(window).or_default().size = size;
+    }
+
+    pub fn window(&self, window: Entity) -> WindowCoordinates {
+        self.windows.get(&window).copied().unwrap_or_default()
+    }
+
+    /// Host position (CSS px, host frame) to window space (physical px, top-left, y-down).
+    pub fn host_to_window(&self, window: Entity, p: Vec2) -> Vec2 {
+        let coords = self.window(window);
+        coords.host.frame_to_window(coords.host_rect(), p) * coords.dpr
+    }
+
+    /// Host movement delta to a window-space delta (scaled, y flipped to y-down).
+    pub fn host_delta_to_window(&self, window: Entity, d: Vec2) -> Vec2 {
+        let coords = self.window(window);
+        Vec2::new(d.x, d.y * coords.host.y_sign()) * coords.dpr
+    }
+
+    /// Window-space position back to host units, e.g. for projections sent to the DOM.
+    pub fn window_to_host(&self, window: Entity, p: Vec2) -> Vec2 {
+        let coords = self.window(window);
+        coords.host.window_to_frame(coords.host_rect(), p / coords.dpr)
+    }
+
+    /// Window-space position to overlay world (panel-center origin, y-up).
+    pub fn overlay_world(&self, panel: PanelRect, p: Vec2) -> Vec2 {
+        Frame::OVERLAY.window_to_frame(panel_rect(panel), p)
+    }
+
+    /// Overlay-world position back to window space.
+    pub fn window_from_overlay(&self, panel: PanelRect, p: Vec2) -> Vec2 {
+        Frame::OVERLAY.frame_to_window(panel_rect(panel), p)
+    }
+
+    /// Window-space rect the camera renders into: its viewport sub-rect, or the whole
+    /// target window.
+    pub fn viewport_rect(&self, camera: &Camera) -> Option<Rect> {
+        if let Some(vp) = &camera.viewport {
+            let min = vp.physical_position.as_vec2();
+            return Some(Rect::from_corners(min, min + vp.physical_size.as_vec2()));
+        }
+        let window = match &camera.target {
+            RenderTarget::Window(WindowRef::Primary) => self.primary?,
+            RenderTarget::Window(WindowRef::Entity(entity)) => *entity,
+            _ => return None,
+        };
+        let size = self.window(window).size;
+        (size.x > 0.0 && size.y > 0.0).then(|| Rect::from_corners(Vec2::ZERO, size))
+    }
+
+    /// Window-space position to NDC of the camera's viewport; None outside the viewport
+    /// (e.g. over the timeline or an HTML panel).
+    pub fn viewport_ndc(&self, camera: &Camera, p: Vec2) -> Option<Vec2> {
+        ndc_in_rect(self.viewport_rect(camera)?, p)
+    }
+
+    /// World ray through a window-space position. Replaces `Camera::viewport_to_world`,
+    /// which needs the camera's render target info and is easy to feed viewport-local
+    /// positions by mistake.
+    pub fn viewport_ray(
+        &self,
+        camera: &Camera,
+        cam_transform: &GlobalTransform,
+        p: Vec2,
+    ) -> Option<Ray3d> {
+        let ndc = self.viewport_ndc(camera, p)?;
+        ray_from_ndc(camera.clip_from_view(), cam_transform, ndc)
+    }
+
+    /// Window-space position of a world point seen through the camera, the inverse of
+    /// `viewport_ray`. None behind the camera or past its far plane; points beside the
+    /// viewport map outside its rect.
+    pub fn world_to_window(
+        &self,
+        camera: &Camera,
+        cam_transform: &GlobalTransform,
+        world: Vec3,
+    ) -> Option<Vec2> {
+        let rect = self.viewport_rect(camera)?;
+        let ndc = ndc_from_world(camera.clip_from_view(), cam_transform, world)?;
+        Some(window_in_rect(rect, ndc))
+    }
+}
+
+fn panel_rect(panel: PanelRect) -> Rect {
+    Rect::new(panel.x, panel.y, panel.x + panel.w, panel.y + panel.h)
+}
+
+fn ndc_in_rect(rect: Rect, p: Vec2) -> Option<Vec2> {
+    let size = rect.size();
+    if size.x <= 0.0 || size.y <= 0.0 || !rect.contains(p) {
+        return None;
+    }
+    let rel = (p - rect.min) / size;
+    Some(Vec2::new(rel.x * 2.0 - 1.0, 1.0 - rel.y * 2.0))
+}
+
+fn window_in_rect(rect: Rect, ndc: Vec2) -> Vec2 {
+    let rel = Vec2::new(ndc.x + 1.0, 1.0 - ndc.y) / 2.0;
+    rect.min + rel * rect.size()
+}
+
+/// Same projection as `Camera::world_to_ndc`, keeping only points between the near
+/// and far planes (reverse-z: depth in 0..=1, in front of the camera).
+fn ndc_from_world(
+    clip_from_view: Mat4,
+    cam_transform: &GlobalTransform,
+    world: Vec3,
+) -> Option<Vec2> {
+    let clip = clip_from_view * cam_transform.compute_matrix().inverse() * world.extend(1.0);
+    if clip.w <= 0.0 {
+        return None;
+    }
+    let ndc = clip.truncate() / clip.w;
+    (ndc.is_finite() && (0.0..=1.0).contains(&ndc.z)).then_some(ndc.truncate())
+}
+
+/// Same construction as `Camera::viewport_to_world`: unproject the NDC point on the
+/// near and far planes (reverse-z, so near is 1.0).
+fn ray_from_ndc(
+    clip_from_view: Mat4,
+    cam_transform: &GlobalTransform,
+    ndc: Vec2,
+) -> Option<Ray3d> {
+    let world_from_ndc = cam_transform.compute_matrix() * clip_from_view.inverse();
+    let near = world_from_ndc.project_point3(ndc.extend(1.0));
+    let far = world_from_ndc.project_point3(ndc.extend(f32::EPSILON));
+    if near.is_nan() || far.is_nan() {
+        return None;
+    }
+    let direction = Dir3::new(far - near).ok()?;
+    Some(Ray3d::new(near, direction))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::render::camera::{CameraProjection, PerspectiveProjection, Viewport};
+
+    fn mapper(dpr: f32, size: Vec2) -> (CoordinateMapper, Entity) {
+        let window = Entity::from_raw(1);
+        let mut mapper = CoordinateMapper::default();
+        mapper.configure(window, dpr);
+        mapper.resize(window, size);
+        (mapper, window)
+    }
+
+    fn viewer_camera(x: u32, y: u32, w: u32, h: u32) -> Camera {
+        Camera {
+            viewport: Some(Viewport {
+                physical_position: UVec2::new(x, y),
+                physical_size: UVec2::new(w, h),
+                ..default()
+            }),
+            ..default()
+        }
+    }
+
+    fn projection(aspect: f32) -> Mat4 {
+        let mut projection = PerspectiveProjection::default();
+        projection.update(aspect, 1.0);
+        projection.get_clip_from_view()
+    }
+
+    #[test]
+    fn host_input_scales_and_flips() {
+        let (mut mapper, window) = mapper(2.0, Vec2::new(800.0, 600.0));
+        assert_eq!(
+            mapper.host_to_window(window, Vec2::new(10.0, 20.0)),
+            Vec2::new(20.0, 40.0)
+        );
+        mapper.set_host_frame(
+            window,
+            Frame {
+                origin: Origin::BottomLeft,
+                y_axis: YAxis::Up,
+            },
+        );
+        // 300 CSS px tall canvas; 20 px above the bottom edge.
+        assert_eq!(
+            mapper.host_to_window(window, Vec2::new(10.0, 20.0)),
+            Vec2::new(20.0, 560.0)
+        );
+        assert_eq!(
+            mapper.host_delta_to_window(window, Vec2::new(1.0, 1.0)),
+            Vec2::new(2.0, -2.0)
+        );
+        let p = Vec2::new(33.0, 71.0);
+        let back = mapper.window_to_host(window, mapper.host_to_window(window, p));
+        assert!(back.abs_diff_eq(p, 1e-4));
+    }
+
+    #[test]
+    fn bottom_left_host_tracks_resizes() {
+        let (mut mapper, window) = mapper(1.0, Vec2::new(400.0, 300.0));
+        mapper.set_host_frame(
+            window,
+            Frame {
+                origin: Origin::BottomLeft,
+                y_axis: YAxis::Up,
+            },
+        );
+        assert_eq!(mapper.host_to_window(window, Vec2::ZERO), Vec2::new(0.0, 300.0));
+        mapper.resize(window, Vec2::new(400.0, 500.0));
+        assert_eq!(mapper.host_to_window(window, Vec2::ZERO), Vec2::new(0.0, 500.0));
+    }
+
+    #[test]
+    fn overlay_picking_after_panel_resize() {
+        let (mapper, window) = mapper(2.0, Vec2::new(1600.0, 1200.0));
+        let panel = PanelRect {
+            x: 200.0,
+            y: 0.0,
+            w: 800.0,
+            h: 600.0,
+        };
+        let cursor = mapper.host_to_window(window, Vec2::new(300.0, 75.0));
+        assert_eq!(mapper.overlay_world(panel, cursor), Vec2::new(0.0, 150.0));
+
+        // After the panel is resized the overlay origin follows its new center, and
+        // the mapping still round-trips to the same cursor.
+        let panel = PanelRect {
+            w: 1000.0,
+            h: 1000.0,
+            ..panel
+        };
+        let world = mapper.overlay_world(panel, cursor);
+        assert_eq!(world, Vec2::new(-100.0, 350.0));
+        assert_eq!(mapper.window_from_overlay(panel, world), cursor);
+    }
+
+    #[test]
+    fn ray_through_viewport_center_follows_resize() {
+        let (mut mapper, _) = mapper(1.0, Vec2::new(1200.0, 800.0));
+        let transform = GlobalTransform::from(
+            Transform::from_xyz(0.0, 0.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y),
+        );
+        for camera in [viewer_camera(300, 0, 900, 600), viewer_camera(200, 100, 400, 400)] {
+            let rect = mapper.viewport_rect(&camera).unwrap();
+            assert_eq!(mapper.viewport_ndc(&camera, rect.center()), Some(Vec2::ZERO));
+            let clip = projection(rect.width() / rect.height());
+            let ndc = mapper.viewport_ndc(&camera, rect.center()).unwrap();
+            let ray = ray_from_ndc(clip, &transform, ndc).unwrap();
+            assert!(ray.direction.abs_diff_eq(Vec3::NEG_Z, 1e-4));
+        }
+
+        // Outside the viewer viewport (over the left panel): no ray.
+        let camera = viewer_camera(300, 0, 900, 600);
+        assert_eq!(mapper.viewport_ndc(&camera, Vec2::new(100.0, 100.0)), None);
+
+        // Without a viewport the camera covers the window, which follows resizes.
+        let camera = Camera::default();
+        let window = mapper.primary.unwrap();
+        mapper.resize(window, Vec2::new(600.0, 400.0));
+        assert_eq!(
+            mapper.viewport_ndc(&camera, Vec2::new(600.0, 0.0)),
+            Some(Vec2::new(1.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn ray_hits_point_under_cursor() {
+        let (mapper, _) = mapper(1.0, Vec2::new(1200.0, 800.0));
+        let camera = viewer_camera(400, 0, 800, 800);
+        let transform = GlobalTransform::from(
+            Transform::from_xyz(0.0, 0.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y),
+        );
+        // Upper half of the viewport looks up (+y), right half looks right (+x).
+        let p = Vec2::new(1000.0, 200.0);
+        let ndc = mapper.viewport_ndc(&camera, p).unwrap();
+        assert_eq!(ndc, Vec2::new(0.5, 0.5));
+        let ray = ray_from_ndc(projection(1.0), &transform, ndc).unwrap();
+        assert!(ray.direction.x > 0.0 && ray.direction.y > 0.0);
+    }
+
+    #[test]
+    fn world_point_projects_back_under_cursor() {
+        let (mapper, _) = mapper(1.0, Vec2::new(1200.0, 800.0));
+        let camera = viewer_camera(400, 0, 800, 800);
+        let rect = mapper.viewport_rect(&camera).unwrap();
+        let transform = GlobalTransform::from(
+            Transform::from_xyz(2.0, 3.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y),
+        );
+        let clip = projection(1.0);
+        let p = Vec2::new(730.0, 215.0);
+        let ray = ray_from_ndc(clip, &transform, mapper.viewport_ndc(&camera, p).unwrap())
+            .unwrap();
+        let ndc = ndc_from_world(clip, &transform, ray.get_point(7.5)).unwrap();
+        assert!(window_in_rect(rect, ndc).abs_diff_eq(p, 1e-2));
+
+        // Behind the camera: no window position.
+        let behind = transform.translation() - *ray.direction;
+        assert_eq!(ndc_from_world(clip, &transform, behind), None);
+    }
+}
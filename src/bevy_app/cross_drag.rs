@@ -0,0 +1,108 @@
+//! Dragging an entity from the viewer panel onto the timeline panel.
+//!
+//! Both panels share the one canvas, so the gesture is an ordinary viewer drag
+//! (`DragState::target`) whose pointer wanders into the timeline rect. While it is
+//! there, `CrossWindowDrag` holds the time under the pointer and the host gets
+//! `send_drag_preview_from_worker(json)` whenever that changes, to draw a drop
+//! indicator: `{ "state": "over", "entity", "name", "time", "x", "y" }` (host
+//! coordinates), then `{ "state": "left", "entity" }` when the pointer leaves the
+//! timeline again. Releasing over the timeline adds a `TimelineTrack` for the entity
+//! starting at the drop time and reports `{ "state": "dropped", "entity", "time" }`.
+
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::CoordinateMapper;
+use crate::bevy_app::timeline::TimelineState;
+use crate::panels::{Panels, TIMELINE_PANEL};
+use crate::{DragState, PointerState};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_drag_preview_from_worker(preview_json: &str);
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct CrossWindowDrag {
+    /// Entity being dragged out of the viewer.
+    pub entity: Option<Entity>,
+    /// Time under the pointer while it is over the timeline.
+    pub drop_time: Option<f64>,
+}
+
+impl CrossWindowDrag {
+    pub fn over_timeline(&self) -> bool {
+        self.entity.is_some() && self.drop_time.is_some()
+    }
+}
+
+/// Follow viewer drags into the timeline panel and turn drops there into tracks.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn cross_window_drag_system(
+    pointer: Res<PointerState>,
+    drag: Res<DragState>,
+    panels: Res<Panels>,
+    mapper: Res<CoordinateMapper>,
+    mut cross: ResMut<CrossWindowDrag>,
+    mut timeline: ResMut<TimelineState>,
+    windows: Query<Entity, With<PrimaryWindow>>,
+    names: Query<&Name>,
+) {
+    if pointer.just_released_left {
+        if let (Some(entity), Some(time)) = (cross.entity, cross.drop_time) {
+            timeline.add_track(entity, time);
+            let preview = json!({
+                "state": "dropped",
+                "entity": entity.to_bits(),
+                "time": time,
+            });
+            send_drag_preview_from_worker(&preview.to_string());
+        }
+        *cross = CrossWindowDrag::default();
+        return;
+    }
+    // `interaction_decide_system` sets the target after the press frame, so a drag
+    // is picked up on its second frame.
+    let Some(entity) = drag.target.filter(|_| pointer.buttons.left) else {
+        if cross.entity.is_some() {
+            *cross = CrossWindowDrag::default();
+        }
+        return;
+    };
+    if cross.entity != Some(entity) {
+        *cross = CrossWindowDrag {
+            entity: Some(entity),
+            drop_time: None,
+        };
+    }
+
+    let over = panels
+        .rect(TIMELINE_PANEL)
+        .filter(|rect| rect.contains(pointer.screen));
+    let drop_time = over.map(|rect| timeline.time_at(rect, pointer.screen.x));
+    if drop_time == cross.drop_time {
+        return;
+    }
+    let preview = match drop_time {
+        Some(time) => {
+            let host = windows
+                .single()
+                .map(|window| mapper.window_to_host(window, pointer.screen))
+                .unwrap_or(pointer.screen);
+            json!({
+                "state": "over",
+                "entity": entity.to_bits(),
+                "name": names.get(entity).ok().map(|name| name.as_str()),
+                "time": time,
+                "x": host.x,
+                "y": host.y,
+            })
+        }
+        None => json!({ "state": "left", "entity": entity.to_bits() }),
+    };
+    send_drag_preview_from_worker(&preview.to_string());
+    cross.drop_time = drop_time;
+}
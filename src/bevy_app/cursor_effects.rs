@@ -0,0 +1,279 @@
+//! Cursor effects for presentations and screen recordings.
+//!
+//! Drawn in window space over everything else, at the pointer's window position: a
+//! `ring` around the cursor, a `crosshair`, a `trail` of recent positions fading out
+//! over `trail_seconds`, and a `ripple` growing out of each left click over
+//! `ripple_seconds`. Styles combine and are all off by default. The host configures
+//! them with `set_cursor_effects(ptr, config_json)` and reads them back with
+//! `get_cursor_effects(ptr)`.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy_vello::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::encode_cache::{ContentHasher, EncodeKey};
+use crate::bevy_app::pointer::pointer_collect_system;
+use crate::bevy_app::render_timing::VelloEncodeSet;
+use crate::bevy_app::theme::Theme;
+use crate::{ActivityControl, PointerState, WorkerApp};
+
+/// Flattening tolerance for circles, px.
+const PATH_TOLERANCE: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CursorStyle {
+    Ring,
+    Crosshair,
+    Trail,
+    Ripple,
+}
+
+/// `{ styles, color, radius, line_width, trail_seconds, ripple_seconds }`; omitted
+/// fields take their defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CursorEffectConfig {
+    pub styles: Vec<CursorStyle>,
+    /// sRGB RGBA, 0-1.
+    pub color: [f32; 4],
+    /// Ring and crosshair radius, and how far ripples grow, px.
+    pub radius: f32,
+    /// px.
+    pub line_width: f32,
+    pub trail_seconds: f32,
+    pub ripple_seconds: f32,
+}
+
+impl Default for CursorEffectConfig {
+    fn default() -> Self {
+        Self {
+            styles: Vec::new(),
+            color: [0.0, 0.62, 1.0, 0.6],
+            radius: 24.0,
+            line_width: 3.0,
+            trail_seconds: 0.5,
+            ripple_seconds: 0.6,
+        }
+    }
+}
+
+impl CursorEffectConfig {
+    fn has(&self, style: CursorStyle) -> bool {
+        self.styles.contains(&style)
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct CursorEffects {
+    pub config: CursorEffectConfig,
+    /// Window position of the cursor; None until it first moves.
+    cursor: Option<Vec2>,
+    /// Trail samples (position, time), oldest first.
+    trail: VecDeque<(Vec2, f64)>,
+    /// Clicks (position, time) whose ripple is still running.
+    ripples: Vec<(Vec2, f64)>,
+    /// Seconds, as of the last sample.
+    now: f64,
+}
+
+impl CursorEffects {
+    fn animating(&self) -> bool {
+        !self.trail.is_empty() || !self.ripples.is_empty()
+    }
+}
+
+#[derive(Component)]
+struct CursorEffectsScene;
+
+pub struct CursorEffectsPlugin;
+
+impl Plugin for CursorEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CursorEffects>()
+            .add_systems(Startup, setup_cursor_effects)
+            .add_systems(PreUpdate, sample_cursor_effects.after(pointer_collect_system))
+            .add_systems(Update, render_cursor_effects.in_set(VelloEncodeSet));
+    }
+}
+
+fn setup_cursor_effects(mut commands: Commands) {
+    // Editor chrome: not an `OverlayLayer`, so exports skip it.
+    commands.spawn((
+        VelloScene::new(),
+        VelloScreenSpace,
+        RenderLayers::layer(1),
+        CursorEffectsScene,
+        EncodeKey::default(),
+    ));
+}
+
+/// Follow the pointer, record trail samples and clicks, and expire old ones.
+fn sample_cursor_effects(
+    time: Res<Time>,
+    pointer: Res<PointerState>,
+    mut effects: ResMut<CursorEffects>,
+    mut activity: Option<ResMut<ActivityControl>>,
+) {
+    if effects.config.styles.is_empty() {
+        return;
+    }
+    let effects = &mut *effects;
+    let now = time.elapsed_secs_f64();
+    effects.now = now;
+    // The pointer sits at the origin until the first move.
+    if effects.cursor.is_some() || pointer.screen != Vec2::ZERO {
+        effects.cursor = Some(pointer.screen);
+    }
+    let config = &effects.config;
+
+    if config.has(CursorStyle::Trail)
+        && let Some(cursor) = effects.cursor
+        && effects.trail.back().is_none_or(|&(last, _)| last != cursor)
+    {
+        effects.trail.push_back((cursor, now));
+    }
+    let trail_seconds = f64::from(config.trail_seconds);
+    while effects
+        .trail
+        .front()
+        .is_some_and(|&(_, t)| now - t > trail_seconds)
+    {
+        effects.trail.pop_front();
+    }
+
+    if config.has(CursorStyle::Ripple) && pointer.just_pressed_left {
+        effects.ripples.push((pointer.screen, now));
+    }
+    let ripple_seconds = f64::from(config.ripple_seconds);
+    effects.ripples.retain(|&(_, t)| now - t <= ripple_seconds);
+
+    // Keep frames coming until trails and ripples have faded.
+    if effects.animating()
+        && let Some(activity) = activity.as_mut()
+    {
+        activity.remaining_frames = activity.remaining_frames.max(1);
+    }
+}
+
+fn render_cursor_effects(
+    mut scenes: Query<(&mut VelloScene, &mut EncodeKey), With<CursorEffectsScene>>,
+    effects: Res<CursorEffects>,
+) {
+    let Ok((mut scene, mut key)) = scenes.single_mut() else {
+        return;
+    };
+    let config = &effects.config;
+    let mut hasher = ContentHasher::default()
+        .f32(config.radius)
+        .f32(config.line_width);
+    for style in &config.styles {
+        hasher = hasher.variant(style);
+    }
+    for channel in config.color {
+        hasher = hasher.f32(channel);
+    }
+    if let Some(cursor) = effects.cursor {
+        hasher = hasher.vec2(cursor);
+    }
+    if effects.animating() {
+        hasher = hasher.f64(effects.now);
+    }
+    if !key.update(hasher.finish()) {
+        return;
+    }
+    scene.reset();
+    let scene = &mut *scene;
+
+    let point = |v: Vec2| kurbo::Point::new(v.x as f64, v.y as f64);
+    let radius = config.radius as f64;
+    let width = config.line_width as f64;
+    let stroke = |scene: &mut VelloScene, w: f64, alpha: f32, shape: kurbo::BezPath| {
+        scene.stroke(
+            &kurbo::Stroke::new(w).with_caps(kurbo::Cap::Round),
+            kurbo::Affine::IDENTITY,
+            Theme::faded(config.color, alpha),
+            None,
+            &shape,
+        );
+    };
+    let circle = |center: kurbo::Point, radius: f64| {
+        kurbo::Shape::to_path(&kurbo::Circle::new(center, radius), PATH_TOLERANCE)
+    };
+
+    let trail_seconds = f64::from(config.trail_seconds).max(f64::EPSILON);
+    for (&(a, _), &(b, t)) in effects.trail.iter().zip(effects.trail.iter().skip(1)) {
+        let life = (1.0 - (effects.now - t) / trail_seconds).clamp(0.0, 1.0);
+        let segment = kurbo::Shape::to_path(&kurbo::Line::new(point(a), point(b)), 0.0);
+        stroke(scene, width * life, life as f32, segment);
+    }
+
+    let ripple_seconds = f64::from(config.ripple_seconds).max(f64::EPSILON);
+    for &(at, t) in &effects.ripples {
+        let progress = ((effects.now - t) / ripple_seconds).clamp(0.0, 1.0);
+        // Ease out: fast at first, settling at twice the ring radius.
+        let grown = 1.0 - (1.0 - progress).powi(2);
+        let ripple = circle(point(at), radius * 2.0 * grown);
+        stroke(scene, width, (1.0 - progress) as f32, ripple);
+    }
+
+    let Some(cursor) = effects.cursor else {
+        return;
+    };
+    let center = point(cursor);
+    if config.has(CursorStyle::Ring) {
+        stroke(scene, width, 1.0, circle(center, radius));
+    }
+    if config.has(CursorStyle::Crosshair) {
+        let mut cross = kurbo::BezPath::new();
+        for d in [kurbo::Vec2::new(radius, 0.0), kurbo::Vec2::new(0.0, radius)] {
+            cross.move_to(center - d);
+            cross.line_to(center + d);
+        }
+        stroke(scene, width * 0.5, 1.0, cross);
+    }
+}
+
+/// Configure cursor effects from `{ styles: ["ring" | "crosshair" | "trail" |
+/// "ripple"], color: [r, g, b, a], radius, line_width, trail_seconds, ripple_seconds }`;
+/// omitted fields take their defaults and no styles turns the effects off. False on
+/// invalid JSON.
+#[wasm_bindgen]
+pub fn set_cursor_effects(ptr: u64, config_json: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let config = match serde_json::from_str::<CursorEffectConfig>(config_json) {
+        Ok(config) => config,
+        Err(e) => {
+            warn!("set_cursor_effects: {e}");
+            return false;
+        }
+    };
+    let Some(mut effects) = app.world_mut().get_resource_mut::<CursorEffects>() else {
+        return false;
+    };
+    // Trails and ripples restart with the new look.
+    *effects = CursorEffects {
+        config,
+        cursor: effects.cursor,
+        ..default()
+    };
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
+
+/// The cursor effect configuration as JSON (see `set_cursor_effects`).
+#[wasm_bindgen]
+pub fn get_cursor_effects(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    app.world()
+        .get_resource::<CursorEffects>()
+        .and_then(|effects| serde_json::to_string(&effects.config).ok())
+        .unwrap_or_else(|| "{}".to_string())
+}
@@ -0,0 +1,532 @@
+//! Curve editor lane: the timeline panel shows one track's keyframe curve instead of
+//! the track rows, with draggable keys and Bezier tangent handles.
+//!
+//! `set_curve_editor(ptr, entity)` opens the lane for the entity's track (0 closes it).
+//! The value axis fits the keys and handles when a drag starts and stays fixed until
+//! it ends, so the point under the pointer doesn't slide. Keys move between their
+//! neighbours; handles only ever point away from their key in time. Each finished drag
+//! is reported through `send_curve_edit_from_worker` as `{ entity, property, keyframes }`.
+//! Keys and their easing are also set over FFI (`set_track_keyframes`,
+//! `set_keyframe_easing`).
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy_vello::prelude::*;
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::encode_cache::{ContentHasher, EncodeKey};
+use crate::bevy_app::keyframes::{self, Easing, Keyframe};
+use crate::bevy_app::theme::Theme;
+use crate::bevy_app::timeline::{TimelineState, TimelineTrack};
+use crate::entity_id::live_entity_or_warn;
+use crate::panels::{PanelRect, Panels, TIMELINE_PANEL};
+use crate::{ActivityControl, PointerState, WorkerApp};
+
+/// Pick radius around keys and handles, px.
+const HIT_RADIUS: f32 = 6.0;
+/// Vertical padding of the value axis inside the panel, px.
+const VALUE_PADDING: f32 = 16.0;
+/// Spacing of the curve samples, px.
+const SAMPLE_STEP: f32 = 2.0;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_curve_edit_from_worker(edit_json: &str);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyPart {
+    Key,
+    HandleIn,
+    HandleOut,
+}
+
+/// A key (or one of its handles) of the edited curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurveTarget {
+    pub key: usize,
+    pub part: KeyPart,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct CurveEditor {
+    /// Entity whose track is shown; `None` = the lane is closed.
+    pub track: Option<Entity>,
+    pub hovered: Option<CurveTarget>,
+    pub dragging: Option<CurveTarget>,
+    /// Value range frozen for the current drag.
+    range: Option<(f32, f32)>,
+}
+
+/// Mapping between (time, value) and window px for the lane.
+#[derive(Debug, Clone, Copy)]
+struct CurveView {
+    rect: PanelRect,
+    duration: f64,
+    lo: f32,
+    hi: f32,
+}
+
+impl CurveView {
+    fn to_screen(&self, time: f64, value: f32) -> Vec2 {
+        let x = self.rect.x + (time / self.duration.max(f64::EPSILON)) as f32 * self.rect.w;
+        let h = (self.rect.h - 2.0 * VALUE_PADDING).max(1.0);
+        let y = self.rect.y + VALUE_PADDING + (self.hi - value) / (self.hi - self.lo) * h;
+        Vec2::new(x, y)
+    }
+
+    fn from_screen(&self, p: Vec2) -> (f64, f32) {
+        let time = ((p.x - self.rect.x) / self.rect.w.max(1.0)) as f64 * self.duration;
+        let h = (self.rect.h - 2.0 * VALUE_PADDING).max(1.0);
+        let value = self.hi - (p.y - self.rect.y - VALUE_PADDING) / h * (self.hi - self.lo);
+        (time.clamp(0.0, self.duration), value)
+    }
+}
+
+/// Value range covering the keys and their visible handles.
+fn value_range(keys: &[Keyframe]) -> (f32, f32) {
+    let mut values = keys.iter().map(|k| k.value).collect::<Vec<_>>();
+    for target in visible_handles(keys) {
+        values.push(handle_point(keys, target).y);
+    }
+    let lo = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let hi = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    if !lo.is_finite() || !hi.is_finite() {
+        return (-1.0, 1.0);
+    }
+    if hi - lo < 1e-3 {
+        return (lo - 1.0, hi + 1.0);
+    }
+    (lo, hi)
+}
+
+/// Handles shown in the lane: those of Bezier segments.
+fn visible_handles(keys: &[Keyframe]) -> impl Iterator<Item = CurveTarget> + '_ {
+    keys.windows(2)
+        .enumerate()
+        .filter(|(_, pair)| pair[0].easing == Easing::Bezier)
+        .flat_map(|(i, _)| {
+            [
+                CurveTarget {
+                    key: i,
+                    part: KeyPart::HandleOut,
+                },
+                CurveTarget {
+                    key: i + 1,
+                    part: KeyPart::HandleIn,
+                },
+            ]
+        })
+}
+
+/// (time, value) of a key or handle.
+fn handle_point(keys: &[Keyframe], target: CurveTarget) -> Vec2 {
+    let key = &keys[target.key];
+    let base = Vec2::new(key.time as f32, key.value);
+    match target.part {
+        KeyPart::Key => base,
+        KeyPart::HandleIn => base + key.handle_in,
+        KeyPart::HandleOut => base + key.handle_out,
+    }
+}
+
+fn track_of(timeline: &TimelineState, entity: Option<Entity>) -> Option<&TimelineTrack> {
+    let entity = entity?;
+    timeline.tracks.iter().find(|track| track.entity == entity)
+}
+
+fn view_for(editor: &CurveEditor, rect: PanelRect, timeline: &TimelineState) -> CurveView {
+    let keys = track_of(timeline, editor.track).map_or(&[][..], |t| &t.keyframes);
+    let (lo, hi) = editor.range.unwrap_or_else(|| {
+        let (lo, hi) = value_range(keys);
+        let pad = (hi - lo) * 0.1;
+        (lo - pad, hi + pad)
+    });
+    CurveView {
+        rect,
+        duration: timeline.duration,
+        lo,
+        hi,
+    }
+}
+
+/// Closest key or handle within `HIT_RADIUS` of `p`; keys win ties.
+fn hit_test(keys: &[Keyframe], view: &CurveView, p: Vec2) -> Option<CurveTarget> {
+    let keys_targets = (0..keys.len()).map(|key| CurveTarget {
+        key,
+        part: KeyPart::Key,
+    });
+    keys_targets
+        .chain(visible_handles(keys))
+        .map(|target| {
+            let point = handle_point(keys, target);
+            let screen = view.to_screen(point.x as f64, point.y);
+            (target, screen.distance(p))
+        })
+        .filter(|(_, distance)| *distance <= HIT_RADIUS)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(target, _)| target)
+}
+
+/// Hover, drag and drop of keys and handles in the lane.
+pub(crate) fn curve_editor_system(
+    pointer: Res<PointerState>,
+    panels: Res<Panels>,
+    mut editor: ResMut<CurveEditor>,
+    mut timeline: ResMut<TimelineState>,
+) {
+    if editor.track.is_none() {
+        return;
+    }
+    let Some(track) = track_of(&timeline, editor.track) else {
+        *editor = CurveEditor::default();
+        return;
+    };
+    let Some(rect) = panels.rect(TIMELINE_PANEL) else {
+        return;
+    };
+
+    if pointer.just_released_left && editor.dragging.is_some() {
+        let edit = json!({
+            "entity": track.entity.to_bits(),
+            "property": track.property,
+            "keyframes": track.keyframes,
+        });
+        send_curve_edit_from_worker(&edit.to_string());
+        editor.dragging = None;
+        editor.range = None;
+    }
+
+    let view = view_for(&editor, rect, &timeline);
+    let inside = rect.contains(pointer.screen) && !pointer.excluded;
+    let hovered = inside
+        .then(|| hit_test(&track.keyframes, &view, pointer.screen))
+        .flatten();
+    editor.hovered = hovered;
+
+    if pointer.just_pressed_left && inside {
+        editor.dragging = hovered;
+        editor.range = hovered.map(|_| (view.lo, view.hi));
+    }
+    let Some(target) = editor.dragging.filter(|_| pointer.buttons.left) else {
+        return;
+    };
+
+    let (time, value) = view.from_screen(pointer.screen);
+    let key_time = timeline.snap(time);
+    let Some(track) = editor.track.and_then(|entity| timeline.track_mut(entity)) else {
+        return;
+    };
+    let keys = &mut track.keyframes;
+    let prev_time = target.key.checked_sub(1).map(|i| keys[i].time);
+    let next_time = keys.get(target.key + 1).map(|k| k.time);
+    let key = &mut keys[target.key];
+    match target.part {
+        KeyPart::Key => {
+            key.time = key_time.clamp(prev_time.unwrap_or(0.0), next_time.unwrap_or(f64::MAX));
+            key.value = value;
+        }
+        KeyPart::HandleIn => {
+            let offset = Vec2::new((time - key.time) as f32, value - key.value);
+            key.handle_in = Vec2::new(offset.x.min(0.0), offset.y);
+        }
+        KeyPart::HandleOut => {
+            let offset = Vec2::new((time - key.time) as f32, value - key.value);
+            key.handle_out = Vec2::new(offset.x.max(0.0), offset.y);
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct CurveEditorScene;
+
+pub(crate) fn setup_curve_editor(mut commands: Commands) {
+    commands.spawn((
+        VelloScene::new(),
+        VelloScreenSpace,
+        RenderLayers::layer(1),
+        CurveEditorScene,
+        EncodeKey::default(),
+    ));
+}
+
+fn to_point(p: Vec2) -> kurbo::Point {
+    kurbo::Point::new(p.x as f64, p.y as f64)
+}
+
+fn hash_easing(hasher: ContentHasher, easing: Easing) -> ContentHasher {
+    let hasher = hasher.variant(&easing);
+    match easing {
+        Easing::CubicBezier { x1, y1, x2, y2 } => hasher.f32(x1).f32(y1).f32(x2).f32(y2),
+        Easing::Steps { count } => hasher.u32(count),
+        _ => hasher,
+    }
+}
+
+/// Draw the lane over the timeline panel while it is open.
+pub(crate) fn render_curve_editor(
+    mut q_scene: Query<(&mut VelloScene, &mut EncodeKey), With<CurveEditorScene>>,
+    editor: Res<CurveEditor>,
+    timeline: Res<TimelineState>,
+    panels: Res<Panels>,
+    theme: Res<Theme>,
+) {
+    let Ok((mut scene, mut key)) = q_scene.single_mut() else {
+        return;
+    };
+    let rect = panels.rect(TIMELINE_PANEL);
+    let track = track_of(&timeline, editor.track);
+    let keys = track.map_or(&[][..], |t| &t.keyframes);
+    let target_id =
+        |t: Option<CurveTarget>| t.map_or(0, |t| 1 + t.key as u32 * 3 + t.part as u32);
+    let mut hasher = ContentHasher::default()
+        .rect(rect)
+        .bool(track.is_some())
+        .f64(timeline.duration)
+        .u32(theme.revision)
+        .u32(target_id(editor.hovered))
+        .u32(target_id(editor.dragging))
+        .u32(keys.len() as u32);
+    for k in keys {
+        hasher = hasher
+            .f64(k.time)
+            .f32(k.value)
+            .vec2(k.handle_in)
+            .vec2(k.handle_out);
+        hasher = hash_easing(hasher, k.easing);
+    }
+    if !key.update(hasher.finish()) {
+        return;
+    }
+    scene.reset();
+    let (Some(rect), Some(_)) = (rect, track) else {
+        return;
+    };
+    let view = view_for(&editor, rect, &timeline);
+    let clip = rect.to_kurbo();
+    let screen = |p: Vec2| to_point(view.to_screen(p.x as f64, p.y));
+
+    scene.push_layer(peniko::Mix::Clip, 1.0, kurbo::Affine::IDENTITY, &clip);
+    scene.fill(
+        peniko::Fill::NonZero,
+        kurbo::Affine::IDENTITY,
+        peniko::Color::new(theme.grid.background),
+        None,
+        &clip,
+    );
+    // Zero line, when it is in range.
+    if view.lo < 0.0 && view.hi > 0.0 {
+        let y = view.to_screen(0.0, 0.0).y as f64;
+        scene.stroke(
+            &kurbo::Stroke::new(1.0),
+            kurbo::Affine::IDENTITY,
+            peniko::Color::new(theme.grid.minor),
+            None,
+            &kurbo::Line::new((rect.x as f64, y), ((rect.x + rect.w) as f64, y)),
+        );
+    }
+
+    // Curve, sampled across the panel width.
+    let mut curve = kurbo::BezPath::new();
+    let mut x = rect.x;
+    while x <= rect.x + rect.w {
+        let (time, _) = view.from_screen(Vec2::new(x, rect.y));
+        if let Some(value) = keyframes::sample(keys, time) {
+            let p = to_point(view.to_screen(time, value));
+            if curve.elements().is_empty() {
+                curve.move_to(p);
+            } else {
+                curve.line_to(p);
+            }
+        }
+        x += SAMPLE_STEP;
+    }
+    scene.stroke(
+        &kurbo::Stroke::new(2.0),
+        kurbo::Affine::IDENTITY,
+        peniko::Color::new(theme.selection),
+        None,
+        &curve,
+    );
+
+    let active = |target: CurveTarget| {
+        editor.hovered == Some(target) || editor.dragging == Some(target)
+    };
+    let fill_for = |target: CurveTarget| {
+        if active(target) {
+            peniko::Color::new(theme.selection)
+        } else {
+            peniko::Color::new(theme.gizmo.handle)
+        }
+    };
+
+    // Tangent handles.
+    for target in visible_handles(keys) {
+        let anchor = screen(handle_point(
+            keys,
+            CurveTarget {
+                part: KeyPart::Key,
+                ..target
+            },
+        ));
+        let handle = screen(handle_point(keys, target));
+        scene.stroke(
+            &kurbo::Stroke::new(1.0),
+            kurbo::Affine::IDENTITY,
+            peniko::Color::new(theme.grid.major),
+            None,
+            &kurbo::Line::new(anchor, handle),
+        );
+        scene.fill(
+            peniko::Fill::NonZero,
+            kurbo::Affine::IDENTITY,
+            fill_for(target),
+            None,
+            &kurbo::Circle::new(handle, 3.5),
+        );
+    }
+
+    // Keys as diamonds.
+    for i in 0..keys.len() {
+        let target = CurveTarget {
+            key: i,
+            part: KeyPart::Key,
+        };
+        let c = screen(handle_point(keys, target));
+        let r = 5.0;
+        let mut diamond = kurbo::BezPath::new();
+        diamond.move_to((c.x, c.y - r));
+        diamond.line_to((c.x + r, c.y));
+        diamond.line_to((c.x, c.y + r));
+        diamond.line_to((c.x - r, c.y));
+        diamond.close_path();
+        scene.fill(
+            peniko::Fill::NonZero,
+            kurbo::Affine::IDENTITY,
+            fill_for(target),
+            None,
+            &diamond,
+        );
+        scene.stroke(
+            &kurbo::Stroke::new(1.0),
+            kurbo::Affine::IDENTITY,
+            peniko::Color::new(theme.selection),
+            None,
+            &diamond,
+        );
+    }
+    scene.pop_layer();
+}
+
+fn request_frames(app: &mut WorkerApp) {
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+}
+
+/// Open the curve lane for `entity_id`'s timeline track, or close it with 0. Returns
+/// false if the entity has no track.
+#[wasm_bindgen]
+pub fn set_curve_editor(ptr: u64, entity_id: u64) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let track = if entity_id == 0 {
+        None
+    } else {
+        let Some(entity) = live_entity_or_warn(app.world(), entity_id, "set_curve_editor") else {
+            return false;
+        };
+        let has_track = app
+            .world()
+            .get_resource::<TimelineState>()
+            .is_some_and(|timeline| track_of(timeline, Some(entity)).is_some());
+        if !has_track {
+            return false;
+        }
+        Some(entity)
+    };
+    let Some(mut editor) = app.world_mut().get_resource_mut::<CurveEditor>() else {
+        return false;
+    };
+    *editor = CurveEditor {
+        track,
+        ..default()
+    };
+    request_frames(app);
+    true
+}
+
+/// Replace the keyframes (JSON array of `Keyframe`) and animated `Transform` field of
+/// `entity_id`'s track, adding the track if needed. Returns false on invalid JSON.
+#[wasm_bindgen]
+pub fn set_track_keyframes(
+    ptr: u64,
+    entity_id: u64,
+    property: &str,
+    keyframes_json: &str,
+) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(entity) = live_entity_or_warn(app.world(), entity_id, "set_track_keyframes") else {
+        return false;
+    };
+    let Ok(mut keys) = serde_json::from_str::<Vec<Keyframe>>(keyframes_json) else {
+        return false;
+    };
+    keyframes::sort_keys(&mut keys);
+    let Some(mut timeline) = app.world_mut().get_resource_mut::<TimelineState>() else {
+        return false;
+    };
+    if timeline.track_mut(entity).is_none() {
+        let start = keys.first().map_or(0.0, |k| k.time);
+        timeline.add_track(entity, start);
+    }
+    let Some(track) = timeline.track_mut(entity) else {
+        return false;
+    };
+    track.property = property.to_string();
+    track.keyframes = keys;
+    request_frames(app);
+    true
+}
+
+/// `{ property, keyframes }` of `entity_id`'s track, or `null`.
+#[wasm_bindgen]
+pub fn get_track_keyframes(ptr: u64, entity_id: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(entity) = live_entity_or_warn(app.world(), entity_id, "get_track_keyframes") else {
+        return "null".to_string();
+    };
+    app.world()
+        .get_resource::<TimelineState>()
+        .and_then(|timeline| track_of(timeline, Some(entity)))
+        .map(|track| json!({ "property": track.property, "keyframes": track.keyframes }))
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Set the easing of the segment starting at keyframe `index`: a mode name ("linear",
+/// "ease_in_out", "sine_out", "back_in", "bezier", ...), "cubic_bezier(x1, y1, x2, y2)",
+/// "steps(n)", or the serialized `Easing` JSON.
+#[wasm_bindgen]
+pub fn set_keyframe_easing(ptr: u64, entity_id: u64, index: usize, easing: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(easing) = Easing::parse(easing) else {
+        return false;
+    };
+    let Some(entity) = live_entity_or_warn(app.world(), entity_id, "set_keyframe_easing") else {
+        return false;
+    };
+    let Some(mut timeline) = app.world_mut().get_resource_mut::<TimelineState>() else {
+        return false;
+    };
+    let Some(key) = timeline
+        .track_mut(entity)
+        .and_then(|track| track.keyframes.get_mut(index))
+    else {
+        return false;
+    };
+    key.easing = easing;
+    request_frames(app);
+    true
+}
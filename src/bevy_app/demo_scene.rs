@@ -0,0 +1,325 @@
+//! Replaceable demo content for exercising different content profiles.
+//!
+//! `DemoScenePlugin` (added when `AppOptions::demo_scene` is set) spawns the default
+//! torus/ground/lights scene at startup. `load_demo_scene(ptr, name)` swaps in another
+//! named demo and `clear_scene(ptr)` removes the current one; both only touch entities
+//! tagged `DemoContent`, so user-authored content stays. Demos:
+//! - `default`: textured torus, ground plane, two point lights.
+//! - `shapes_grid`: 10×10 grid of pickable cubes, spheres and tori in varied materials.
+//! - `gltf_showcase`: the Khronos DamagedHelmet sample, fetched over https.
+//! - `vector_2d`: a few vector paths in the 2D overlay (3D viewer left empty).
+//! - `particles`: 5000 GPU-animated instanced particles (see `particles`).
+
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::encode_cache::ShapeFragment;
+use crate::bevy_app::particles::{ParticleParams, spawn_particle_field};
+use crate::bevy_app::scene3d::{ActiveState, Shape, uv_debug_texture};
+use crate::bevy_app::vector::{PathAnchor, VectorNode};
+use crate::{ActivityControl, WorkerApp};
+
+const GLTF_SHOWCASE_URL: &str = "https://raw.githubusercontent.com/KhronosGroup/glTF-Sample-Assets/main/Models/DamagedHelmet/glTF-Binary/DamagedHelmet.glb";
+const GRID_SIZE: i32 = 10;
+
+/// Everything a demo spawns; `clear_scene` despawns these.
+#[derive(Component)]
+pub(crate) struct DemoContent;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DemoScene {
+    Default,
+    ShapesGrid,
+    GltfShowcase,
+    Vector2d,
+    Particles,
+}
+
+impl DemoScene {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::Default),
+            "shapes_grid" => Some(Self::ShapesGrid),
+            "gltf_showcase" => Some(Self::GltfShowcase),
+            "vector_2d" => Some(Self::Vector2d),
+            "particles" => Some(Self::Particles),
+            _ => None,
+        }
+    }
+}
+
+pub struct DemoScenePlugin;
+
+impl Plugin for DemoScenePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, |world: &mut World| {
+            spawn_demo(world, DemoScene::Default);
+        });
+    }
+}
+
+fn clear_demo(world: &mut World) {
+    let demo: Vec<Entity> = world
+        .query_filtered::<Entity, With<DemoContent>>()
+        .iter(world)
+        .collect();
+    for entity in demo {
+        // Children (e.g. glTF scene nodes) go with their root.
+        if let Ok(entity) = world.get_entity_mut(entity) {
+            entity.despawn();
+        }
+    }
+}
+
+fn spawn_demo(world: &mut World, scene: DemoScene) {
+    match scene {
+        DemoScene::Default => spawn_default(world),
+        DemoScene::ShapesGrid => spawn_shapes_grid(world),
+        DemoScene::GltfShowcase => spawn_gltf_showcase(world),
+        DemoScene::Vector2d => spawn_vector_2d(world),
+        DemoScene::Particles => spawn_particles_demo(world),
+    }
+}
+
+fn spawn_point_lights(world: &mut World) {
+    world.spawn((
+        PointLight {
+            shadows_enabled: false,
+            intensity: 07_000_000.,
+            range: 100.0,
+            ..default()
+        },
+        Transform::from_xyz(8.0, 9.0, 16.0),
+        DemoContent,
+    ));
+    world.spawn((
+        PointLight {
+            shadows_enabled: false,
+            intensity: 2_000_000.,
+            range: 100.0,
+            ..default()
+        },
+        Transform::from_xyz(-8.0, 9.0, -10.0),
+        DemoContent,
+    ));
+}
+
+fn spawn_ground(world: &mut World) {
+    let mesh = world
+        .resource_mut::<Assets<Mesh>>()
+        .add(Plane3d::default().mesh().size(50.0, 50.0).subdivisions(10));
+    let material = world
+        .resource_mut::<Assets<StandardMaterial>>()
+        .add(Color::srgb(0.75, 0.75, 0.75));
+    world.spawn((Mesh3d(mesh), MeshMaterial3d(material), DemoContent));
+}
+
+fn spawn_default(world: &mut World) {
+    let texture = world
+        .resource_mut::<Assets<Image>>()
+        .add(uv_debug_texture());
+    let debug_material = world
+        .resource_mut::<Assets<StandardMaterial>>()
+        .add(StandardMaterial {
+            base_color_texture: Some(texture),
+            ..default()
+        });
+    let torus = world.resource_mut::<Assets<Mesh>>().add(
+        Torus::default()
+            .mesh()
+            .major_resolution(8)
+            .minor_resolution(6),
+    );
+    world.spawn((
+        Mesh3d(torus),
+        MeshMaterial3d(debug_material),
+        Transform::from_xyz(0.0, 1.5, 0.0),
+        Shape::Box(Cuboid::from_size(Vec3::new(1.75, 0.52, 1.75))),
+        ActiveState::default(),
+        RenderLayers::layer(0),
+        DemoContent,
+    ));
+    spawn_point_lights(world);
+    spawn_ground(world);
+}
+
+fn spawn_shapes_grid(world: &mut World) {
+    let meshes = {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        [
+            (meshes.add(Cuboid::default()), Vec3::splat(1.0)),
+            (
+                meshes.add(Sphere::new(0.5).mesh().ico(3).unwrap()),
+                Vec3::splat(1.0),
+            ),
+            (meshes.add(Torus::new(0.25, 0.5)), Vec3::new(1.0, 0.5, 1.0)),
+        ]
+    };
+    let spacing = 2.0;
+    let offset = (GRID_SIZE - 1) as f32 * spacing / 2.0;
+    for x in 0..GRID_SIZE {
+        for z in 0..GRID_SIZE {
+            let index = (x * GRID_SIZE + z) as usize;
+            let (mesh, bounds) = meshes[index % meshes.len()].clone();
+            let material = world
+                .resource_mut::<Assets<StandardMaterial>>()
+                .add(StandardMaterial {
+                    base_color: Color::hsl(index as f32 * 360.0 / 100.0, 0.6, 0.55),
+                    metallic: x as f32 / (GRID_SIZE - 1) as f32,
+                    perceptual_roughness: 1.0 - z as f32 / (GRID_SIZE - 1) as f32 * 0.9,
+                    ..default()
+                });
+            world.spawn((
+                Name::new(format!("Shape {x},{z}")),
+                Mesh3d(mesh),
+                MeshMaterial3d(material),
+                Transform::from_xyz(
+                    x as f32 * spacing - offset,
+                    0.5,
+                    z as f32 * spacing - offset,
+                ),
+                Shape::Box(Cuboid::from_size(bounds)),
+                ActiveState::default(),
+                RenderLayers::layer(0),
+                DemoContent,
+            ));
+        }
+    }
+    spawn_point_lights(world);
+    spawn_ground(world);
+}
+
+fn spawn_gltf_showcase(world: &mut World) {
+    let scene = world
+        .resource::<AssetServer>()
+        .load(GltfAssetLabel::Scene(0).from_asset(GLTF_SHOWCASE_URL));
+    world.spawn((
+        Name::new("DamagedHelmet"),
+        SceneRoot(scene),
+        Transform::from_xyz(0.0, 3.0, 0.0).with_scale(Vec3::splat(3.0)),
+        DemoContent,
+    ));
+    world.spawn((
+        DirectionalLight {
+            illuminance: 8_000.0,
+            ..default()
+        },
+        Transform::from_xyz(4.0, 10.0, 6.0).looking_at(Vec3::ZERO, Vec3::Y),
+        DemoContent,
+    ));
+    spawn_ground(world);
+}
+
+fn spawn_vector_2d(world: &mut World) {
+    let star: Vec<PathAnchor> = (0..10)
+        .map(|i| {
+            let radius = if i % 2 == 0 { 80.0 } else { 35.0 };
+            let angle = i as f32 * PI / 5.0 - PI / 2.0;
+            PathAnchor::corner(Vec2::from_angle(angle) * radius)
+        })
+        .collect();
+    let blob: Vec<PathAnchor> = (0..4)
+        .map(|i| {
+            let dir = Vec2::from_angle(i as f32 * PI / 2.0);
+            PathAnchor {
+                position: dir * 60.0,
+                handle_in: dir.perp() * -33.0,
+                handle_out: dir.perp() * 33.0,
+            }
+        })
+        .collect();
+    let wave = vec![
+        PathAnchor {
+            position: Vec2::new(0.0, 0.0),
+            handle_in: Vec2::ZERO,
+            handle_out: Vec2::new(40.0, -60.0),
+        },
+        PathAnchor {
+            position: Vec2::new(120.0, 0.0),
+            handle_in: Vec2::new(-40.0, 60.0),
+            handle_out: Vec2::new(40.0, -60.0),
+        },
+        PathAnchor {
+            position: Vec2::new(240.0, 0.0),
+            handle_in: Vec2::new(-40.0, 60.0),
+            handle_out: Vec2::ZERO,
+        },
+    ];
+    let paths = [
+        (
+            "Star",
+            star,
+            true,
+            Some([0.98, 0.78, 0.2, 1.0]),
+            Vec2::new(-200.0, 0.0),
+        ),
+        (
+            "Blob",
+            blob,
+            true,
+            Some([0.3, 0.6, 0.95, 0.8]),
+            Vec2::new(0.0, 0.0),
+        ),
+        ("Wave", wave, false, None, Vec2::new(120.0, 40.0)),
+    ];
+    for (name, anchors, closed, fill, position) in paths {
+        world.spawn((
+            Name::new(name),
+            Transform::from_translation(position.extend(0.0)),
+            GlobalTransform::default(),
+            VectorNode {
+                anchors,
+                closed,
+                fill,
+                stroke_width: 3.0,
+                ..default()
+            },
+            ShapeFragment::default(),
+            DemoContent,
+        ));
+    }
+}
+
+fn spawn_particles_demo(world: &mut World) {
+    let root = spawn_particle_field(world, &ParticleParams::default());
+    world
+        .entity_mut(root)
+        .insert((Transform::from_xyz(0.0, 6.5, 0.0), DemoContent));
+    spawn_point_lights(world);
+    spawn_ground(world);
+}
+
+/// Despawn the current demo content (entities spawned by a demo scene).
+#[wasm_bindgen]
+pub fn clear_scene(ptr: u64) {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    clear_demo(world);
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+}
+
+/// Replace the demo content with a named demo: `default`, `shapes_grid`,
+/// `gltf_showcase`, `vector_2d` or `particles`. Returns false for an unknown name.
+#[wasm_bindgen]
+pub fn load_demo_scene(ptr: u64, name: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(scene) = DemoScene::parse(name) else {
+        return false;
+    };
+    let world = app.world_mut();
+    // Perf-grid variants without the 3D plugins have nothing to spawn into.
+    if !world.contains_resource::<Assets<StandardMaterial>>() {
+        return false;
+    }
+    clear_demo(world);
+    spawn_demo(world, scene);
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
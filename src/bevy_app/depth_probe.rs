@@ -0,0 +1,466 @@
+//! Depth probe: the world-space surface point under a screen position, read from the
+//! GPU, for placement and measurement in scenes where box raycasts are too coarse.
+//!
+//! Built the same way as the `gpu` picking backend:
+//!
+//! - Every mesh the main camera renders gets a `DepthProxy` child on `DEPTH_LAYER`,
+//!   drawn with `DepthProbeMaterial`, which writes the fragment's view-space depth as a
+//!   24-bit fixed-point number (`depth_probe.wgsl`).
+//! - A depth camera, a child of the main camera with its projection, renders that layer
+//!   into a `REGION`×`REGION` image through a `SubCameraView` of the pixels around the
+//!   probed point, and a `Readback` copies them back each frame.
+//! - The probed pixel's depth, or the nearest covered pixel's when the point sits on an
+//!   edge, is turned back into a world position along that pixel's camera ray.
+//!
+//! `sample_depth_at(ptr, x, y)` moves the probe and reports the latest settled sample:
+//! the answer for a new point arrives a few frames later, so hosts call it on pointer
+//! move and use the result once it stops being pending. The probe's entities are
+//! created on the first request and removed after `IDLE_SECS` without one.
+
+use bevy::asset::{load_internal_asset, weak_handle};
+use bevy::core_pipeline::tonemapping::{DebandDither, Tonemapping};
+use bevy::pbr::NotShadowCaster;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy::render::camera::{RenderTarget, SubCameraView};
+use bevy::render::gpu_readback::{Readback, ReadbackComplete};
+use bevy::render::mesh::skinning::SkinnedMesh;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{
+    AsBindGroup, Extent3d, ShaderRef, TextureDimension, TextureFormat, TextureUsages,
+};
+use bevy::render::view::RenderLayers;
+use bevy_remote_inspector::InspectorHidden;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::CoordinateMapper;
+use crate::bevy_app::scene3d::MainCamera3D;
+use crate::{ActivityControl, WorkerApp};
+
+const DEPTH_PROBE_SHADER_HANDLE: Handle<Shader> =
+    weak_handle!("b7e2f0a4-6c1d-4e8b-9a53-2d7c4f1e8a60");
+
+/// Render layer of the depth proxies; only the depth camera renders it.
+pub const DEPTH_LAYER: usize = 30;
+
+/// Side of the square of pixels read back around the probed point.
+const REGION: u32 = 5;
+/// Depth encoded by the brightest value; must match `MAX_DEPTH` in depth_probe.wgsl.
+const MAX_DEPTH: f32 = 4096.0;
+/// Readbacks still in flight for a previous region when the probe moves.
+const READBACK_LATENCY: u32 = 3;
+/// Seconds without a request before the probe's entities are removed.
+const IDLE_SECS: f64 = 2.0;
+
+/// Unlit material writing view-space depth (see module docs).
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone, Default)]
+pub struct DepthProbeMaterial {}
+
+impl Material for DepthProbeMaterial {
+    fn fragment_shader() -> ShaderRef {
+        DEPTH_PROBE_SHADER_HANDLE.into()
+    }
+}
+
+/// Depth-encoding copy of a mesh entity, rendered only by the depth camera.
+#[derive(Component, Debug)]
+#[require(InspectorHidden)]
+pub struct DepthProxy {
+    pub source: Entity,
+}
+
+#[derive(Component)]
+struct DepthCamera;
+
+/// Surface found under a probed point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct DepthHit {
+    pub position: Vec3,
+    /// From the camera, world units.
+    pub distance: f32,
+}
+
+/// Point the host last asked about (window px) and when.
+#[derive(Resource, Debug)]
+struct DepthProbeRequest {
+    point: Vec2,
+    at: f64,
+}
+
+/// Pixels the depth camera is rendering: the viewer viewport's origin and the
+/// region's offset in it (window px), and the probed pixel within the region.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ProbeRegion {
+    viewport_min: Vec2,
+    offset: Vec2,
+    center: UVec2,
+}
+
+/// The probe's entities and latest sample; present only while it is in use.
+#[derive(Resource)]
+pub(crate) struct DepthProbe {
+    image: Handle<Image>,
+    material: Handle<DepthProbeMaterial>,
+    camera: Entity,
+    readback: Entity,
+    /// Source entity → proxy.
+    proxies: HashMap<Entity, Entity>,
+    point: Option<Vec2>,
+    region: Option<ProbeRegion>,
+    /// Readbacks to drop before the current region's arrive.
+    stale: u32,
+    /// Settled sample for `point`: None while pending, `Some(None)` for no surface.
+    sample: Option<Option<DepthHit>>,
+}
+
+impl DepthProbe {
+    /// Settled sample at `point` (window px): None while pending or for another point.
+    pub(crate) fn sample_at(&self, point: Vec2) -> Option<Option<DepthHit>> {
+        self.point
+            .filter(|probed| probed.distance(point) < 0.5)
+            .and(self.sample)
+    }
+}
+
+pub struct DepthProbePlugin;
+
+impl Plugin for DepthProbePlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            DEPTH_PROBE_SHADER_HANDLE,
+            "depth_probe.wgsl",
+            Shader::from_wgsl
+        );
+        app.add_plugins(MaterialPlugin::<DepthProbeMaterial> {
+            prepass_enabled: false,
+            shadows_enabled: false,
+            ..default()
+        })
+        .add_systems(
+            PostUpdate,
+            (
+                apply_depth_probe,
+                (sync_depth_proxies, update_depth_camera).run_if(resource_exists::<DepthProbe>),
+            )
+                .chain()
+                .after(bevy::transform::TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// Create the probe for a new request; remove it once requests stop.
+fn apply_depth_probe(
+    mut commands: Commands,
+    request: Option<Res<DepthProbeRequest>>,
+    probe: Option<Res<DepthProbe>>,
+    main_camera: Query<Entity, With<MainCamera3D>>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<DepthProbeMaterial>>,
+    time: Res<Time>,
+) {
+    let active = request
+        .as_ref()
+        .is_some_and(|request| time.elapsed_secs_f64() - request.at < IDLE_SECS);
+    match (active, probe) {
+        (true, None) => {
+            let Ok(main_camera) = main_camera.single() else {
+                return;
+            };
+            let mut image = Image::new_fill(
+                Extent3d {
+                    width: REGION,
+                    height: REGION,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                &[0; 4],
+                TextureFormat::Rgba8UnormSrgb,
+                RenderAssetUsages::RENDER_WORLD,
+            );
+            image.texture_descriptor.usage |=
+                TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC;
+            let image = images.add(image);
+
+            let camera = commands
+                .spawn((
+                    DepthCamera,
+                    Camera3d::default(),
+                    Camera {
+                        order: -2,
+                        target: RenderTarget::Image(image.clone().into()),
+                        clear_color: ClearColorConfig::Custom(Color::NONE),
+                        is_active: false,
+                        ..default()
+                    },
+                    Tonemapping::None,
+                    DebandDither::Disabled,
+                    Msaa::Off,
+                    RenderLayers::layer(DEPTH_LAYER),
+                    Transform::IDENTITY,
+                    ChildOf(main_camera),
+                ))
+                .id();
+            let readback = commands
+                .spawn((Readback::texture(image.clone()), InspectorHidden))
+                .observe(read_depth_region)
+                .id();
+            commands.insert_resource(DepthProbe {
+                image,
+                material: materials.add(DepthProbeMaterial::default()),
+                camera,
+                readback,
+                proxies: HashMap::default(),
+                point: None,
+                region: None,
+                stale: 0,
+                sample: None,
+            });
+        }
+        (false, Some(probe)) => {
+            commands.entity(probe.camera).despawn();
+            commands.entity(probe.readback).despawn();
+            for proxy in probe.proxies.values() {
+                if let Ok(mut proxy) = commands.get_entity(*proxy) {
+                    proxy.despawn();
+                }
+            }
+            images.remove(&probe.image);
+            materials.remove(&probe.material);
+            commands.remove_resource::<DepthProbe>();
+            commands.remove_resource::<DepthProbeRequest>();
+        }
+        _ => {}
+    }
+}
+
+type ProbedMesh<'a> = (
+    Entity,
+    Ref<'a, Mesh3d>,
+    Option<&'a RenderLayers>,
+    Option<&'a SkinnedMesh>,
+);
+
+/// Spawn proxies for new meshes, follow mesh swaps and forget despawned sources.
+fn sync_depth_proxies(
+    mut commands: Commands,
+    mut probe: ResMut<DepthProbe>,
+    meshes: Query<ProbedMesh, Without<DepthProxy>>,
+    mut proxy_meshes: Query<&mut Mesh3d, With<DepthProxy>>,
+) {
+    let probe = &mut *probe;
+    probe.proxies.retain(|source, _| meshes.contains(*source));
+
+    let main_layer = RenderLayers::default();
+    for (entity, mesh, layers, skin) in &meshes {
+        if !layers.unwrap_or(&main_layer).intersects(&main_layer) {
+            continue;
+        }
+        if let Some(proxy) = probe.proxies.get(&entity) {
+            if mesh.is_changed()
+                && let Ok(mut proxy_mesh) = proxy_meshes.get_mut(*proxy)
+            {
+                proxy_mesh.0 = mesh.0.clone();
+            }
+            continue;
+        }
+        let mut proxy = commands.spawn((
+            DepthProxy { source: entity },
+            Mesh3d(mesh.0.clone()),
+            MeshMaterial3d(probe.material.clone()),
+            RenderLayers::layer(DEPTH_LAYER),
+            NotShadowCaster,
+            Transform::IDENTITY,
+            ChildOf(entity),
+        ));
+        if let Some(skin) = skin {
+            proxy.insert(skin.clone());
+        }
+        probe.proxies.insert(entity, proxy.id());
+    }
+}
+
+type MainCameraView<'a> = (&'a Camera, Ref<'a, Projection>, Ref<'a, GlobalTransform>);
+
+/// Point the depth camera at the region around the requested point. A point outside
+/// the viewer viewport settles at once as no surface.
+fn update_depth_camera(
+    request: Option<Res<DepthProbeRequest>>,
+    mapper: Res<CoordinateMapper>,
+    mut probe: ResMut<DepthProbe>,
+    main_camera: Query<MainCameraView, (With<MainCamera3D>, Without<DepthCamera>)>,
+    mut depth_camera: Query<(&mut Camera, &mut Projection), With<DepthCamera>>,
+    mut activity: Option<ResMut<ActivityControl>>,
+) {
+    let (Some(request), Ok((main, main_projection, main_transform))) =
+        (request, main_camera.single())
+    else {
+        return;
+    };
+    let Ok((mut camera, mut projection)) = depth_camera.single_mut() else {
+        return;
+    };
+    let point = request.point;
+    if probe.point != Some(point) {
+        probe.point = Some(point);
+        probe.sample = None;
+    }
+    let region = mapper.viewport_rect(main).and_then(|rect| {
+        let local = (point - rect.min).floor();
+        let size = rect.size().floor();
+        let region = Vec2::splat(REGION as f32);
+        let inside = main.is_active
+            && size.cmpge(region).all()
+            && local.cmpge(Vec2::ZERO).all()
+            && local.cmplt(size).all();
+        if !inside {
+            return None;
+        }
+        let offset = (local - (region / 2.0).floor()).clamp(Vec2::ZERO, size - region);
+        Some((
+            ProbeRegion {
+                viewport_min: rect.min,
+                offset,
+                center: (local - offset).as_uvec2(),
+            },
+            size.as_uvec2(),
+        ))
+    });
+
+    let Some((region, full_size)) = region else {
+        if camera.is_active {
+            camera.is_active = false;
+        }
+        probe.region = None;
+        probe.sample = Some(None);
+        return;
+    };
+    let moved = main_projection.is_changed() || main_transform.is_changed();
+    if !camera.is_active || moved {
+        *projection = main_projection.clone();
+    }
+    let sub_view = SubCameraView {
+        full_size,
+        offset: region.offset,
+        size: UVec2::splat(REGION),
+    };
+    if !camera.is_active || camera.sub_camera_view != Some(sub_view) {
+        camera.is_active = true;
+        camera.sub_camera_view = Some(sub_view);
+    }
+    if probe.region != Some(region) || moved {
+        probe.region = Some(region);
+        probe.stale = READBACK_LATENCY;
+        probe.sample = None;
+    }
+    if probe.sample.is_none()
+        && let Some(activity) = activity.as_mut()
+    {
+        activity.remaining_frames = activity.remaining_frames.max(1);
+    }
+}
+
+fn read_depth_region(
+    trigger: Trigger<ReadbackComplete>,
+    probe: Option<ResMut<DepthProbe>>,
+    mapper: Res<CoordinateMapper>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera3D>>,
+) {
+    let Some(mut probe) = probe else {
+        return;
+    };
+    if probe.stale > 0 {
+        probe.stale -= 1;
+        return;
+    }
+    let (Some(region), None) = (probe.region, probe.sample) else {
+        return;
+    };
+    let data = trigger.event().0.as_slice();
+    // Texture readbacks may pad each row; infer the stride from the total.
+    let stride = data.len() / REGION as usize;
+    if stride < REGION as usize * 4 {
+        return;
+    }
+    // The probed pixel, else the covered pixel nearest to it.
+    let nearest = (0..REGION)
+        .flat_map(|y| (0..REGION).map(move |x| UVec2::new(x, y)))
+        .filter_map(|pixel| {
+            let at = pixel.y as usize * stride + pixel.x as usize * 4;
+            let &[r, g, b, a] = &data[at..at + 4] else {
+                return None;
+            };
+            let fixed = (r as u32) << 16 | (g as u32) << 8 | b as u32;
+            let depth = fixed as f32 / 0xFF_FFFF as f32 * MAX_DEPTH;
+            (a > 0).then_some((pixel, depth))
+        })
+        .min_by_key(|(pixel, _)| {
+            let d = pixel.as_ivec2() - region.center.as_ivec2();
+            d.length_squared()
+        });
+
+    let hit = nearest.and_then(|(pixel, depth)| {
+        let (camera, transform) = cameras.single().ok()?;
+        let screen = region.viewport_min + region.offset + pixel.as_vec2() + 0.5;
+        let ray = mapper.viewport_ray(camera, transform, screen)?;
+        // Walk the ray to the view-space depth read back for its pixel.
+        let forward = transform.forward().as_vec3();
+        let facing = ray.direction.dot(forward);
+        if facing <= f32::EPSILON {
+            return None;
+        }
+        let eye = transform.translation();
+        let t = (depth - (ray.origin - eye).dot(forward)) / facing;
+        let position = ray.get_point(t);
+        Some(DepthHit {
+            position,
+            distance: position.distance(eye),
+        })
+    });
+    probe.sample = Some(hit);
+}
+
+/// Surface point under `point` (window px) from the depth probe, if it has settled
+/// there on a surface.
+pub(crate) fn probed_surface_point(world: &World, point: Vec2) -> Option<Vec3> {
+    let hit = world.get_resource::<DepthProbe>()?.sample_at(point)??;
+    Some(hit.position)
+}
+
+/// Probe the depth under canvas position (`x`, `y`) in CSS px, like `mouse_move`.
+/// Returns JSON `{ status, position, distance }`: status "hit" with the world-space
+/// `position` and its `distance` from the camera, "miss" (no surface, or outside the
+/// 3D viewer), or "pending" while the readback for a new point is in flight.
+#[wasm_bindgen]
+pub fn sample_depth_at(ptr: u64, x: f32, y: f32) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let window = app.window;
+    let point = match app.world().get_resource::<CoordinateMapper>() {
+        Some(mapper) => mapper.host_to_window(window, Vec2::new(x, y)),
+        None => Vec2::new(x, y) * app.scale_factor,
+    };
+    let world = app.world_mut();
+    let at = world
+        .get_resource::<Time>()
+        .map_or(0.0, Time::elapsed_secs_f64);
+    world.insert_resource(DepthProbeRequest { point, at });
+
+    let sample = world
+        .get_resource::<DepthProbe>()
+        .and_then(|probe| probe.sample_at(point));
+    if sample.is_none()
+        && let Some(mut active_info) = world.get_resource_mut::<ActivityControl>()
+    {
+        active_info.remaining_frames = 10;
+    }
+    match sample {
+        Some(Some(hit)) => serde_json::json!({
+            "status": "hit",
+            "position": hit.position.to_array(),
+            "distance": hit.distance,
+        }),
+        Some(None) => serde_json::json!({ "status": "miss" }),
+        None => serde_json::json!({ "status": "pending" }),
+    }
+    .to_string()
+}
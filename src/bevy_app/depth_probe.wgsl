@@ -0,0 +1,30 @@
+// Fragment stage for `DepthProbeMaterial`: writes the fragment's view-space depth as a
+// 24-bit fixed-point number in the rgb bytes of an `Rgba8UnormSrgb` target (alpha 1
+// marks a surface; the clear color has alpha 0).
+
+#import bevy_pbr::{
+    forward_io::VertexOutput,
+    mesh_view_bindings::view,
+}
+
+// Must match `MAX_DEPTH` in depth_probe.rs.
+const MAX_DEPTH: f32 = 4096.0;
+
+// The target re-encodes to sRGB on write; pre-decode so the stored bytes are exact.
+fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+    let low = c / 12.92;
+    let high = pow((c + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(high, low, c <= vec3<f32>(0.04045));
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let depth = -(view.view_from_world * vec4<f32>(in.world_position.xyz, 1.0)).z;
+    let fixed = u32(round(clamp(depth / MAX_DEPTH, 0.0, 1.0) * 16777215.0));
+    let bytes = vec3<f32>(
+        f32((fixed >> 16u) & 255u),
+        f32((fixed >> 8u) & 255u),
+        f32(fixed & 255u),
+    ) / 255.0;
+    return vec4<f32>(srgb_to_linear(bytes), 1.0);
+}
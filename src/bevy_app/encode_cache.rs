@@ -0,0 +1,112 @@
+//! Change-driven Vello re-encoding.
+//!
+//! Scene renderers hash the inputs that affect their output (panel rect, shape state,
+//! animation phase) into an `EncodeKey` and only `reset()` + re-encode when the key
+//! changes. Batched scenes additionally keep one `ShapeFragment` per shape, encoded in
+//! overlay-world coordinates, so a change to one shape re-encodes only that shape and
+//! the batch is rebuilt by appending cached fragments.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use bevy::prelude::*;
+use bevy_vello::prelude::*;
+
+use crate::panels::PanelRect;
+
+/// Last content key a scene (or fragment) was encoded with.
+#[derive(Component, Debug, Default)]
+pub(crate) struct EncodeKey(Option<u64>);
+
+impl EncodeKey {
+    /// Record `key`; true when it differs from the previous one (scene must be re-encoded).
+    pub fn update(&mut self, key: u64) -> bool {
+        if self.0 == Some(key) {
+            return false;
+        }
+        self.0 = Some(key);
+        true
+    }
+}
+
+/// Cached encoding of a single shape, appended into its batch scene.
+#[derive(Component, Default)]
+pub(crate) struct ShapeFragment {
+    pub key: EncodeKey,
+    pub scene: vello::Scene,
+    /// Overlay-world bounds of the encoded content (see `overlay_damage`).
+    pub bounds: Option<kurbo::Rect>,
+}
+
+impl ShapeFragment {
+    /// Record the bounds of a fresh encoding; returns the area it changed, the old
+    /// bounds united with the new.
+    pub fn rebound(&mut self, bounds: kurbo::Rect) -> kurbo::Rect {
+        let old = self.bounds.replace(bounds);
+        old.map_or(bounds, |old| old.union(bounds))
+    }
+}
+
+/// Small hashing helper for float-heavy render inputs (hashes bit patterns).
+#[derive(Default)]
+pub(crate) struct ContentHasher(DefaultHasher);
+
+impl ContentHasher {
+    pub fn f32(mut self, v: f32) -> Self {
+        v.to_bits().hash(&mut self.0);
+        self
+    }
+
+    pub fn f64(mut self, v: f64) -> Self {
+        v.to_bits().hash(&mut self.0);
+        self
+    }
+
+    pub fn vec2(self, v: Vec2) -> Self {
+        self.f32(v.x).f32(v.y)
+    }
+
+    pub fn u32(mut self, v: u32) -> Self {
+        v.hash(&mut self.0);
+        self
+    }
+
+    pub fn bool(mut self, v: bool) -> Self {
+        v.hash(&mut self.0);
+        self
+    }
+
+    /// Which variant an enum value is (not its fields).
+    pub fn variant<T>(mut self, v: &T) -> Self {
+        std::mem::discriminant(v).hash(&mut self.0);
+        self
+    }
+
+    pub fn rect(self, rect: Option<PanelRect>) -> Self {
+        match rect {
+            Some(r) => self.bool(true).f32(r.x).f32(r.y).f32(r.w).f32(r.h),
+            None => self.bool(false),
+        }
+    }
+
+    pub fn finish(self) -> u64 {
+        self.0.finish()
+    }
+}
+
+/// Animation clock for the demo overlay. Only advances while `auto_animate` is on, so
+/// the animated scenes stop re-encoding when the app is idle instead of following
+/// `Time` on every input-triggered frame.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct OverlayClock {
+    pub elapsed: f32,
+}
+
+pub(crate) fn advance_overlay_clock(
+    mut clock: ResMut<OverlayClock>,
+    activity: Res<crate::ActivityControl>,
+    time: Res<Time>,
+) {
+    if activity.auto_animate {
+        clock.elapsed += time.delta_secs();
+    }
+}
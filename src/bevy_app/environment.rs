@@ -0,0 +1,378 @@
+//! Image-based environment lighting for the 3D viewer.
+//!
+//! `set_environment(ptr, url_or_preset)` puts a `Skybox` and `EnvironmentMapLight` on
+//! the main camera. Sources:
+//! - presets `studio` / `sky`: procedural cubemaps generated on the CPU; `none` removes
+//!   the environment.
+//! - `.hdr` (equirectangular) URLs: resampled into a cubemap with a box-filtered mip
+//!   chain for specular and a cosine-convolved low-res cubemap for diffuse.
+//! - `.ktx2` cubemap URLs: used as-is for skybox and specular. Following Bevy's asset
+//!   naming, a `specular` in the file name is swapped for `diffuse` to find the
+//!   matching irradiance map; otherwise the same map is used for both.
+//!
+//! Relative paths resolve against the app's asset folder, `http(s)://` URLs go
+//! through the web asset source.
+
+use std::f32::consts::PI;
+
+use bevy::asset::LoadState;
+use bevy::core_pipeline::Skybox;
+use bevy::image::ImageSampler;
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{
+    Extent3d, TextureDimension, TextureFormat, TextureViewDescriptor, TextureViewDimension,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::scene3d::MainCamera3D;
+use crate::{ActivityControl, WorkerApp};
+
+/// Skybox brightness / light intensity (cd/m²) for unit radiance, which the default
+/// camera exposure maps to roughly 1.0 on screen.
+pub(crate) const ENVIRONMENT_BRIGHTNESS: f32 = 1000.0;
+const PRESET_SPECULAR_SIZE: u32 = 128;
+const HDR_SPECULAR_SIZE: u32 = 256;
+const DIFFUSE_SIZE: u32 = 8;
+/// Directions the diffuse convolution integrates over.
+const IRRADIANCE_SAMPLES: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvironmentPreset {
+    Studio,
+    Sky,
+}
+
+impl EnvironmentPreset {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "studio" => Some(Self::Studio),
+            "sky" => Some(Self::Sky),
+            _ => None,
+        }
+    }
+
+    /// Linear radiance seen along `dir` (y-up).
+    fn radiance(self, dir: Vec3) -> Vec3 {
+        let y = dir.y;
+        match self {
+            Self::Studio => {
+                let floor = Vec3::splat(0.18);
+                let walls = Vec3::splat(0.45);
+                // Soft overhead box light plus a dimmer key from the front-left.
+                let top = smoothstep(0.75, 0.9, y) * 3.0;
+                let key = smoothstep(0.85, 0.95, dir.dot(Vec3::new(-0.6, 0.4, 0.7).normalize()));
+                floor.lerp(walls, smoothstep(-0.2, 0.1, y)) + Vec3::splat(top + key * 1.5)
+            }
+            Self::Sky => {
+                let ground = Vec3::new(0.22, 0.2, 0.18);
+                let horizon = Vec3::new(0.85, 0.9, 0.95);
+                let zenith = Vec3::new(0.25, 0.45, 0.85);
+                let sun_dir = Vec3::new(0.4, 0.6, 0.3).normalize();
+                let sun = smoothstep(0.995, 0.999, dir.dot(sun_dir)) * 40.0;
+                if y < 0.0 {
+                    horizon.lerp(ground, smoothstep(0.0, 0.1, -y))
+                } else {
+                    horizon.lerp(zenith, y.sqrt()) + Vec3::splat(sun)
+                }
+            }
+        }
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Environment map load waiting for its assets.
+#[derive(Debug)]
+struct PendingEnvironment {
+    url: String,
+    specular: Handle<Image>,
+    diffuse: Option<Handle<Image>>,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct EnvironmentState {
+    pending: Option<PendingEnvironment>,
+}
+
+/// World-space direction through texel (`u`, `v` in [-1, 1]) of cube face `face`
+/// (+X, -X, +Y, -Y, +Z, -Z).
+fn cube_direction(face: usize, u: f32, v: f32) -> Vec3 {
+    match face {
+        0 => Vec3::new(1.0, -v, -u),
+        1 => Vec3::new(-1.0, -v, u),
+        2 => Vec3::new(u, 1.0, v),
+        3 => Vec3::new(u, -1.0, -v),
+        4 => Vec3::new(u, -v, 1.0),
+        _ => Vec3::new(-u, -v, -1.0),
+    }
+    .normalize()
+}
+
+/// Evaluate `radiance` over each cube face at `size` px.
+pub(crate) fn render_faces(size: u32, radiance: impl Fn(Vec3) -> Vec3) -> Vec<Vec<Vec3>> {
+    (0..6)
+        .map(|face| {
+            (0..size * size)
+                .map(|i| {
+                    let to_uv = |p: u32| (p as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+                    radiance(cube_direction(face, to_uv(i % size), to_uv(i / size)))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Cosine-weighted irradiance (normalized to radiance units) at `size` px per face.
+fn irradiance_faces(size: u32, radiance: impl Fn(Vec3) -> Vec3) -> Vec<Vec<Vec3>> {
+    // Fibonacci sphere: evenly spread sample directions.
+    let samples: Vec<(Vec3, Vec3)> = (0..IRRADIANCE_SAMPLES)
+        .map(|i| {
+            let y = 1.0 - 2.0 * (i as f32 + 0.5) / IRRADIANCE_SAMPLES as f32;
+            let r = (1.0 - y * y).sqrt();
+            let phi = i as f32 * PI * (3.0 - 5.0_f32.sqrt());
+            let dir = Vec3::new(r * phi.cos(), y, r * phi.sin());
+            (dir, radiance(dir))
+        })
+        .collect();
+    render_faces(size, |normal| {
+        let (sum, weight) = samples
+            .iter()
+            .map(|(dir, l)| (*l, normal.dot(*dir).max(0.0)))
+            .fold((Vec3::ZERO, 0.0), |(sum, w), (l, c)| (sum + l * c, w + c));
+        sum / weight.max(f32::EPSILON)
+    })
+}
+
+/// Rgba16Float cubemap with a 2x2 box-filtered mip chain down to 1 px.
+pub(crate) fn cubemap_image(faces: Vec<Vec<Vec3>>, size: u32) -> Image {
+    let mip_count = size.ilog2() + 1;
+    let mut data = Vec::new();
+    for mut texels in faces {
+        let mut mip_size = size;
+        loop {
+            for c in &texels {
+                for v in [c.x, c.y, c.z, 1.0] {
+                    data.extend_from_slice(&half::f16::from_f32(v).to_le_bytes());
+                }
+            }
+            if mip_size == 1 {
+                break;
+            }
+            let next = mip_size / 2;
+            texels = (0..next * next)
+                .map(|i| {
+                    let (x, y) = (i % next * 2, i / next * 2);
+                    let at = |x: u32, y: u32| texels[(y * mip_size + x) as usize];
+                    (at(x, y) + at(x + 1, y) + at(x, y + 1) + at(x + 1, y + 1)) * 0.25
+                })
+                .collect();
+            mip_size = next;
+        }
+    }
+    let mut image = Image::new_uninit(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 6,
+        },
+        TextureDimension::D2,
+        TextureFormat::Rgba16Float,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.data = Some(data);
+    image.texture_descriptor.mip_level_count = mip_count;
+    image.texture_view_descriptor = Some(TextureViewDescriptor {
+        dimension: Some(TextureViewDimension::Cube),
+        ..default()
+    });
+    image.sampler = ImageSampler::linear();
+    image
+}
+
+/// Bilinear lookup into an equirectangular (2:1 lat-long) image.
+fn equirect_sampler(image: &Image) -> Option<impl Fn(Vec3) -> Vec3 + '_> {
+    let (w, h) = (image.width(), image.height());
+    image.get_color_at(0, 0).ok()?;
+    let texel = move |x: i64, y: i64| {
+        let x = x.rem_euclid(w as i64) as u32;
+        let y = y.clamp(0, h as i64 - 1) as u32;
+        image
+            .get_color_at(x, y)
+            .map(|c| Vec3::from_slice(&c.to_linear().to_f32_array()[..3]))
+            .unwrap_or(Vec3::ZERO)
+    };
+    Some(move |dir: Vec3| {
+        let u = 0.5 + dir.z.atan2(dir.x) / (2.0 * PI);
+        let v = dir.y.clamp(-1.0, 1.0).acos() / PI;
+        let (fx, fy) = (u * w as f32 - 0.5, v * h as f32 - 0.5);
+        let (x0, y0) = (fx.floor() as i64, fy.floor() as i64);
+        let (tx, ty) = (fx.fract().abs(), fy.fract().abs());
+        let top = texel(x0, y0).lerp(texel(x0 + 1, y0), tx);
+        let bottom = texel(x0, y0 + 1).lerp(texel(x0 + 1, y0 + 1), tx);
+        top.lerp(bottom, ty)
+    })
+}
+
+fn insert_environment(
+    world: &mut World,
+    specular: Handle<Image>,
+    diffuse: Handle<Image>,
+) -> Option<()> {
+    let camera = world
+        .query_filtered::<Entity, With<MainCamera3D>>()
+        .single(world)
+        .ok()?;
+    world.entity_mut(camera).insert((
+        Skybox {
+            image: specular.clone(),
+            brightness: ENVIRONMENT_BRIGHTNESS,
+            ..default()
+        },
+        EnvironmentMapLight {
+            diffuse_map: diffuse,
+            specular_map: specular,
+            intensity: ENVIRONMENT_BRIGHTNESS,
+            ..default()
+        },
+    ));
+    Some(())
+}
+
+/// Finish a URL environment once its images are loaded.
+pub(crate) fn apply_pending_environment(world: &mut World) {
+    let Some(pending) = world
+        .get_resource::<EnvironmentState>()
+        .and_then(|state| state.pending.as_ref())
+    else {
+        return;
+    };
+    let asset_server = world.resource::<AssetServer>();
+    let handles = std::iter::once(&pending.specular).chain(pending.diffuse.as_ref());
+    let mut loaded = true;
+    for handle in handles {
+        match asset_server.get_load_state(handle) {
+            Some(LoadState::Loaded) => {}
+            Some(LoadState::Failed(e)) => {
+                warn!("Failed to load environment {}: {e}", pending.url);
+                world.resource_mut::<EnvironmentState>().pending = None;
+                return;
+            }
+            _ => loaded = false,
+        }
+    }
+    if !loaded {
+        return;
+    }
+    let Some(pending) = world.resource_mut::<EnvironmentState>().pending.take() else {
+        return;
+    };
+
+    let mut images = world.resource_mut::<Assets<Image>>();
+    let Some(source) = images.get_mut(&pending.specular) else {
+        return;
+    };
+    let (specular, diffuse) = if source.texture_descriptor.size.depth_or_array_layers == 6 {
+        // KTX2 cubemap; make sure it is viewed as a cube.
+        source.texture_view_descriptor = Some(TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::Cube),
+            ..default()
+        });
+        let diffuse = pending.diffuse.unwrap_or_else(|| pending.specular.clone());
+        (pending.specular, diffuse)
+    } else {
+        let cubemaps = equirect_sampler(source).map(|sample| {
+            (
+                cubemap_image(render_faces(HDR_SPECULAR_SIZE, &sample), HDR_SPECULAR_SIZE),
+                cubemap_image(irradiance_faces(DIFFUSE_SIZE, &sample), DIFFUSE_SIZE),
+            )
+        });
+        let Some((specular, diffuse)) = cubemaps else {
+            warn!(
+                "Environment {} is not a cubemap or a readable equirect image",
+                pending.url
+            );
+            return;
+        };
+        (images.add(specular), images.add(diffuse))
+    };
+    if insert_environment(world, specular, diffuse).is_some() {
+        info!("Environment {} applied", pending.url);
+    }
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+}
+
+/// Set the viewer environment: `none`, a preset (`studio`, `sky`) or an `.hdr` /
+/// `.ktx2` URL. URL loads finish asynchronously; returns false for an unsupported
+/// source.
+#[wasm_bindgen]
+pub fn set_environment(ptr: u64, url_or_preset: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    let source = url_or_preset.trim();
+    if !world.contains_resource::<EnvironmentState>() {
+        return false;
+    }
+    world.resource_mut::<EnvironmentState>().pending = None;
+
+    if source == "none" {
+        let mut cameras = world.query_filtered::<Entity, With<MainCamera3D>>();
+        let Ok(camera) = cameras.single(world) else {
+            return false;
+        };
+        world
+            .entity_mut(camera)
+            .remove::<(Skybox, EnvironmentMapLight)>();
+    } else if let Some(preset) = EnvironmentPreset::parse(source) {
+        let radiance = |dir| preset.radiance(dir);
+        let specular = cubemap_image(
+            render_faces(PRESET_SPECULAR_SIZE, radiance),
+            PRESET_SPECULAR_SIZE,
+        );
+        let diffuse = cubemap_image(irradiance_faces(DIFFUSE_SIZE, radiance), DIFFUSE_SIZE);
+        let mut images = world.resource_mut::<Assets<Image>>();
+        let handles = (images.add(specular), images.add(diffuse));
+        if insert_environment(world, handles.0, handles.1).is_none() {
+            return false;
+        }
+    } else {
+        let lower = source.to_ascii_lowercase();
+        let asset_server = world.resource::<AssetServer>().clone();
+        let pending = if lower.ends_with(".hdr") {
+            PendingEnvironment {
+                url: source.to_string(),
+                specular: asset_server.load(source.to_string()),
+                diffuse: None,
+            }
+        } else if lower.ends_with(".ktx2") {
+            let (dir, file) = source.rsplit_once('/').unwrap_or(("", source));
+            let diffuse = file.contains("specular").then(|| {
+                let file = file.replace("specular", "diffuse");
+                let path = if dir.is_empty() {
+                    file
+                } else {
+                    format!("{dir}/{file}")
+                };
+                asset_server.load(path)
+            });
+            PendingEnvironment {
+                url: source.to_string(),
+                specular: asset_server.load(source.to_string()),
+                diffuse,
+            }
+        } else {
+            return false;
+        };
+        world.resource_mut::<EnvironmentState>().pending = Some(pending);
+    }
+
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
@@ -0,0 +1,195 @@
+//! Gamepads bridged from the browser Gamepad API.
+//!
+//! The Gamepad API only exists on the main thread, so the host polls
+//! `navigator.getGamepads()` and forwards each pad as
+//! `gamepad_update(ptr, { index, id, connected, buttons, axes })`, with `buttons` as
+//! `{ value }` objects or plain numbers. Changed values become Bevy
+//! `RawGamepadEvent`s (through the `InputQueue`); Bevy's gamepad processing then
+//! emits the filtered `GamepadEvent`s and keeps the `Gamepad` components current.
+//! Buttons and axes follow the W3C "standard" mapping; browser stick Y points down
+//! and is flipped to Bevy's up-positive convention.
+
+use bevy::input::gamepad::{
+    GamepadConnection, GamepadConnectionEvent, RawGamepadAxisChangedEvent,
+    RawGamepadButtonChangedEvent, RawGamepadEvent,
+};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::input_queue::InputQueue;
+use crate::{ActivityControl, WorkerApp};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(untagged)]
+enum ButtonJson {
+    Value(f32),
+    Object { value: f32 },
+}
+
+impl ButtonJson {
+    fn value(self) -> f32 {
+        match self {
+            ButtonJson::Value(value) | ButtonJson::Object { value } => value,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GamepadJson {
+    index: u32,
+    #[serde(default)]
+    id: String,
+    #[serde(default = "connected_default")]
+    connected: bool,
+    #[serde(default)]
+    buttons: Vec<ButtonJson>,
+    #[serde(default)]
+    axes: Vec<f32>,
+}
+
+fn connected_default() -> bool {
+    true
+}
+
+#[derive(Default)]
+struct HostGamepad {
+    entity: Option<Entity>,
+    connected: bool,
+    buttons: Vec<f32>,
+    axes: Vec<f32>,
+}
+
+/// Browser gamepad index -> Bevy gamepad entity plus the last values sent.
+#[derive(Resource, Default)]
+pub struct HostGamepads {
+    pads: HashMap<u32, HostGamepad>,
+}
+
+fn standard_button(index: usize) -> GamepadButton {
+    match index {
+        0 => GamepadButton::South,
+        1 => GamepadButton::East,
+        2 => GamepadButton::West,
+        3 => GamepadButton::North,
+        4 => GamepadButton::LeftTrigger,
+        5 => GamepadButton::RightTrigger,
+        6 => GamepadButton::LeftTrigger2,
+        7 => GamepadButton::RightTrigger2,
+        8 => GamepadButton::Select,
+        9 => GamepadButton::Start,
+        10 => GamepadButton::LeftThumb,
+        11 => GamepadButton::RightThumb,
+        12 => GamepadButton::DPadUp,
+        13 => GamepadButton::DPadDown,
+        14 => GamepadButton::DPadLeft,
+        15 => GamepadButton::DPadRight,
+        16 => GamepadButton::Mode,
+        n => GamepadButton::Other(n.min(u8::MAX as usize) as u8),
+    }
+}
+
+fn standard_axis(index: usize, value: f32) -> (GamepadAxis, f32) {
+    match index {
+        0 => (GamepadAxis::LeftStickX, value),
+        1 => (GamepadAxis::LeftStickY, -value),
+        2 => (GamepadAxis::RightStickX, value),
+        3 => (GamepadAxis::RightStickY, -value),
+        n => (GamepadAxis::Other(n.min(u8::MAX as usize) as u8), value),
+    }
+}
+
+/// Changed values of `current` against `last`.
+fn changed(last: &[f32], current: &[f32]) -> Vec<(usize, f32)> {
+    current
+        .iter()
+        .enumerate()
+        .filter(|(i, value)| last.get(*i).is_none_or(|prev| prev != *value))
+        .map(|(i, value)| (i, *value))
+        .collect()
+}
+
+/// Raw events turning `pad` into `snapshot`; updates `pad` to match.
+fn snapshot_events(
+    pad: &mut HostGamepad,
+    entity: Entity,
+    snapshot: GamepadJson,
+) -> Vec<RawGamepadEvent> {
+    let mut events = Vec::new();
+    if !snapshot.connected {
+        if pad.connected {
+            events.push(RawGamepadEvent::Connection(GamepadConnectionEvent::new(
+                entity,
+                GamepadConnection::Disconnected,
+            )));
+        }
+        *pad = HostGamepad {
+            entity: Some(entity),
+            ..default()
+        };
+        return events;
+    }
+    if !pad.connected {
+        events.push(RawGamepadEvent::Connection(GamepadConnectionEvent::new(
+            entity,
+            GamepadConnection::Connected {
+                name: snapshot.id,
+                vendor_id: None,
+                product_id: None,
+            },
+        )));
+        pad.connected = true;
+    }
+    let buttons: Vec<f32> = snapshot.buttons.iter().map(|b| b.value()).collect();
+    for (i, value) in changed(&pad.buttons, &buttons) {
+        events.push(RawGamepadEvent::Button(RawGamepadButtonChangedEvent::new(
+            entity,
+            standard_button(i),
+            value,
+        )));
+    }
+    for (i, value) in changed(&pad.axes, &snapshot.axes) {
+        let (axis, value) = standard_axis(i, value);
+        events.push(RawGamepadEvent::Axis(RawGamepadAxisChangedEvent::new(
+            entity, axis, value,
+        )));
+    }
+    pad.entity = Some(entity);
+    pad.buttons = buttons;
+    pad.axes = snapshot.axes;
+    events
+}
+
+/// Apply one browser gamepad snapshot (JSON, see module docs); `connected: false`
+/// disconnects it. Returns false for invalid JSON.
+#[wasm_bindgen]
+pub fn gamepad_update(ptr: u64, gamepad_json: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Ok(snapshot) = serde_json::from_str::<GamepadJson>(gamepad_json) else {
+        return false;
+    };
+    let world = app.world_mut();
+    if !world.contains_resource::<HostGamepads>() || !world.contains_resource::<InputQueue>() {
+        return false;
+    }
+    let known = world
+        .resource::<HostGamepads>()
+        .pads
+        .get(&snapshot.index)
+        .and_then(|pad| pad.entity);
+    // Like the gilrs backend: one entity per pad, kept across reconnects.
+    let entity = known.unwrap_or_else(|| world.spawn_empty().id());
+    let mut gamepads = world.resource_mut::<HostGamepads>();
+    let pad = gamepads.pads.entry(snapshot.index).or_default();
+    let events = snapshot_events(pad, entity, snapshot);
+
+    let mut queue = world.resource_mut::<InputQueue>();
+    for event in events {
+        queue.push(event);
+    }
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
@@ -0,0 +1,197 @@
+//! Importing glTF animation clips into timeline tracks.
+//!
+//! `get_gltf_animations(ptr, scene_entity)` lists the clips of the glTF file a
+//! `SceneRoot` entity was spawned from; `import_gltf_animation(ptr, scene_entity,
+//! clip_index)` turns one clip into keyframed tracks on the spawned hierarchy, one per
+//! animated `Transform` component (`translation.x`, `rotation.w`, ...), so it can be
+//! retimed in the curve editor like any other track.
+//!
+//! Curves are evaluated through Bevy's own curve evaluators against a scratch entity,
+//! which keeps the glTF interpolation modes (step, linear, cubic spline) without
+//! depending on the concrete curve types the loader builds. Each node is sampled at
+//! `SAMPLE_RATE` and samples a straight line through their neighbours reproduces are
+//! dropped. Rotations are keyed per quaternion component, which is close enough at this
+//! rate; morph weight curves are skipped.
+
+use bevy::animation::graph::AnimationNodeIndex;
+use bevy::animation::{
+    AnimationClip, AnimationEntityMut, AnimationTarget, AnimationTargetId, VariableCurve,
+};
+use bevy::gltf::Gltf;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+
+use crate::WorkerApp;
+use crate::bevy_app::keyframes;
+use crate::bevy_app::timeline::TimelineState;
+use crate::entity_id::live_entity_or_warn;
+
+/// Samples per second of clip time.
+const SAMPLE_RATE: f32 = 30.0;
+
+/// `Transform` components a clip can animate, as track property paths.
+const CHANNELS: [(&str, fn(&Transform) -> f32); 10] = [
+    ("translation.x", |t| t.translation.x),
+    ("translation.y", |t| t.translation.y),
+    ("translation.z", |t| t.translation.z),
+    ("rotation.x", |t| t.rotation.x),
+    ("rotation.y", |t| t.rotation.y),
+    ("rotation.z", |t| t.rotation.z),
+    ("rotation.w", |t| t.rotation.w),
+    ("scale.x", |t| t.scale.x),
+    ("scale.y", |t| t.scale.y),
+    ("scale.z", |t| t.scale.z),
+];
+
+/// The glTF asset `scene_entity`'s `SceneRoot` was loaded from.
+fn source_gltf(world: &World, scene_entity: Entity) -> Option<&Gltf> {
+    let scene = world.get::<SceneRoot>(scene_entity)?;
+    let server = world.get_resource::<AssetServer>()?;
+    let path = server.get_path(scene.0.id())?.without_label().into_owned();
+    let id = server
+        .get_path_ids(path)
+        .into_iter()
+        .find_map(|id| id.try_typed::<Gltf>().ok())?;
+    world.get_resource::<Assets<Gltf>>()?.get(id)
+}
+
+/// Entities under `root` (inclusive) by the animation target id the glTF loader gave them.
+fn animation_targets(world: &World, root: Entity) -> HashMap<AnimationTargetId, Entity> {
+    let mut targets = HashMap::default();
+    let mut stack = vec![root];
+    while let Some(entity) = stack.pop() {
+        if let Some(target) = world.get::<AnimationTarget>(entity) {
+            targets.insert(target.id, entity);
+        }
+        if let Some(children) = world.get::<Children>(entity) {
+            stack.extend(children.iter());
+        }
+    }
+    targets
+}
+
+/// `Transform` of an entity starting at `rest` with `curves` applied, at each of `times`.
+fn sample_curves(
+    world: &mut World,
+    rest: Transform,
+    curves: &[VariableCurve],
+    times: &[f32],
+) -> Vec<Transform> {
+    let scratch = world.spawn(rest).id();
+    let mut query = world.query::<AnimationEntityMut>();
+    let node = AnimationNodeIndex::new(0);
+    let mut samples = Vec::with_capacity(times.len());
+    for &time in times {
+        for curve in curves {
+            let mut evaluator = curve.0.create_evaluator();
+            let t = curve.0.domain().clamp(time);
+            if let Err(err) = curve.0.apply(evaluator.as_mut(), t, 1.0, node) {
+                warn_once!("Skipping glTF animation curve: {err:?}");
+                continue;
+            }
+            if let Ok(entity) = query.get_mut(world, scratch) {
+                // Curves for other components (morph weights) fail here; ignore them.
+                evaluator.commit(entity).ok();
+            }
+        }
+        samples.push(world.get::<Transform>(scratch).copied().unwrap_or(rest));
+    }
+    world.despawn(scratch);
+    samples
+}
+
+/// Add the tracks of `clip` to the entities under `scene_entity`; returns how many.
+fn import_clip(world: &mut World, scene_entity: Entity, clip: &AnimationClip) -> usize {
+    let duration = clip.duration().max(0.0);
+    let count = (duration * SAMPLE_RATE).ceil() as usize;
+    let times = (0..=count)
+        .map(|i| (i as f32 / SAMPLE_RATE).min(duration))
+        .collect::<Vec<_>>();
+    let targets = animation_targets(world, scene_entity);
+
+    let mut tracks = Vec::new();
+    for (target_id, curves) in clip.curves() {
+        let Some(&entity) = targets.get(target_id) else {
+            continue;
+        };
+        let rest = world.get::<Transform>(entity).copied().unwrap_or_default();
+        let samples = sample_curves(world, rest, curves, &times);
+        for (property, read) in CHANNELS {
+            let values = samples.iter().map(read).collect::<Vec<_>>();
+            // Components the clip leaves alone stay at the rest value.
+            if values.iter().all(|&v| v == read(&rest)) {
+                continue;
+            }
+            tracks.push((entity, property, keyframes::keys_from_samples(&times, &values)));
+        }
+    }
+
+    let Some(mut timeline) = world.get_resource_mut::<TimelineState>() else {
+        return 0;
+    };
+    timeline.duration = timeline.duration.max(duration as f64);
+    let imported = tracks.len();
+    for (entity, property, keys) in tracks {
+        timeline.set_property_track(entity, property, keys);
+    }
+    imported
+}
+
+/// Clips of the glTF `scene_entity` was spawned from:
+/// `[{ "index", "name", "duration" }]` (`name` is null for unnamed clips), or `[]` while
+/// the file is loading or for an entity that is not a glTF scene root.
+#[wasm_bindgen]
+pub fn get_gltf_animations(ptr: u64, scene_entity: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world();
+    let Some(entity) = live_entity_or_warn(world, scene_entity, "get_gltf_animations") else {
+        return "[]".to_string();
+    };
+    let Some(gltf) = source_gltf(world, entity) else {
+        return "[]".to_string();
+    };
+    let clips = world.resource::<Assets<AnimationClip>>();
+    let list = gltf
+        .animations
+        .iter()
+        .enumerate()
+        .map(|(index, handle)| {
+            let name = gltf
+                .named_animations
+                .iter()
+                .find(|(_, named)| named.id() == handle.id())
+                .map(|(name, _)| name.to_string());
+            let duration = clips.get(handle).map(AnimationClip::duration);
+            json!({ "index": index, "name": name, "duration": duration })
+        })
+        .collect::<Vec<_>>();
+    serde_json::Value::from(list).to_string()
+}
+
+/// Import animation `clip_index` of the glTF `scene_entity` was spawned from as timeline
+/// tracks on the spawned nodes, replacing their tracks for the same properties. Returns
+/// the number of tracks written; 0 if the clip isn't loaded or animates nothing.
+#[wasm_bindgen]
+pub fn import_gltf_animation(ptr: u64, scene_entity: u64, clip_index: usize) -> u32 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(entity) = live_entity_or_warn(app.world(), scene_entity, "import_gltf_animation")
+    else {
+        return 0;
+    };
+    let clip = source_gltf(app.world(), entity)
+        .and_then(|gltf| gltf.animations.get(clip_index))
+        .and_then(|handle| app.world().resource::<Assets<AnimationClip>>().get(handle))
+        .cloned();
+    let Some(clip) = clip else {
+        warn!("import_gltf_animation: clip {clip_index} of {entity} is not loaded");
+        return 0;
+    };
+    let imported = import_clip(app.world_mut(), entity, &clip);
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<crate::ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    imported as u32
+}
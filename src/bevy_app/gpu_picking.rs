@@ -0,0 +1,362 @@
+//! GPU picking: an alternative 3D picking backend that reads entity ids from the GPU.
+//!
+//! The default `raycast` backend tests the pointer ray against bounding boxes, which is
+//! coarse for irregular shapes and wrong for skinned meshes. With the `gpu` backend
+//! (`set_picking_backend(ptr, "gpu")`):
+//!
+//! - Every mesh the main camera renders gets a `PickingProxy` child: the same mesh (and
+//!   skin) with an unlit material whose sRGB color encodes a 24-bit picking id, on
+//!   `PICKING_LAYER` only.
+//! - An id camera, a child of the main camera with its projection, renders that layer
+//!   into a 1×1 `Rgba8UnormSrgb` image through a `SubCameraView` of the single pixel
+//!   under the cursor. Tonemapping and dithering are off so colors survive exactly.
+//! - A `Readback` copies the pixel back each frame; `ReadbackComplete` arrives a frame
+//!   or two later and `pick_world_3d_gpu_system` reports the decoded entity as the
+//!   pointer hit (id 0, the clear color, is no hit).
+//!
+//! Switching back to `raycast` despawns the proxies, camera and readback.
+
+use bevy::core_pipeline::tonemapping::{DebandDither, Tonemapping};
+use bevy::pbr::NotShadowCaster;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy::render::camera::{RenderTarget, SubCameraView};
+use bevy::render::gpu_readback::{Readback, ReadbackComplete};
+use bevy::render::mesh::skinning::SkinnedMesh;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
+use bevy::render::view::RenderLayers;
+use bevy_remote_inspector::InspectorHidden;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::CoordinateMapper;
+use crate::bevy_app::scene3d::MainCamera3D;
+use crate::{ActivityControl, WorkerApp};
+
+/// Render layer of the picking proxies; only the id camera renders it.
+pub const PICKING_LAYER: usize = 31;
+
+/// Largest id that fits the 24 color bits.
+const MAX_PICKING_ID: u32 = 0xFF_FFFF;
+
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PickingBackend {
+    /// Pointer ray against bounding boxes.
+    #[default]
+    Raycast,
+    /// Entity ids rendered under the cursor and read back from the GPU.
+    Gpu,
+}
+
+pub(crate) fn picking_backend_is(
+    backend: PickingBackend,
+) -> impl Fn(Res<PickingBackend>) -> bool + Clone {
+    move |current: Res<PickingBackend>| *current == backend
+}
+
+/// Id-colored copy of a mesh entity, rendered only by the id camera.
+#[derive(Component, Debug)]
+#[require(InspectorHidden)]
+pub struct PickingProxy {
+    pub source: Entity,
+}
+
+#[derive(Component)]
+struct PickingCamera;
+
+/// State of the `gpu` backend; present only while it is selected.
+#[derive(Resource)]
+pub(crate) struct GpuPicking {
+    image: Handle<Image>,
+    camera: Entity,
+    readback: Entity,
+    /// Source entity → (picking id, proxy).
+    proxies: HashMap<Entity, (u32, Entity)>,
+    entities: HashMap<u32, Entity>,
+    next_id: u32,
+    /// Cursor the id camera is rendering, and the pick read back for it.
+    cursor: Option<Vec2>,
+    hit: Option<Entity>,
+}
+
+impl GpuPicking {
+    fn allocate_id(&mut self, source: Entity) -> Option<u32> {
+        if self.next_id > MAX_PICKING_ID {
+            // Reuse ids freed by despawned entities before giving up.
+            self.next_id = 1;
+            while self.entities.contains_key(&self.next_id) {
+                self.next_id += 1;
+                if self.next_id > MAX_PICKING_ID {
+                    return None;
+                }
+            }
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entities.insert(id, source);
+        Some(id)
+    }
+}
+
+fn id_color(id: u32) -> Color {
+    Color::srgb_u8((id >> 16) as u8, (id >> 8) as u8, id as u8)
+}
+
+pub struct GpuPickingPlugin;
+
+impl Plugin for GpuPickingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PickingBackend>().add_systems(
+            PostUpdate,
+            (
+                apply_picking_backend,
+                (sync_picking_proxies, update_picking_camera).run_if(resource_exists::<GpuPicking>),
+            )
+                .chain()
+                .after(bevy::transform::TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// Create or tear down the `gpu` backend's entities when the backend changes.
+fn apply_picking_backend(
+    mut commands: Commands,
+    backend: Res<PickingBackend>,
+    picking: Option<Res<GpuPicking>>,
+    main_camera: Query<Entity, With<MainCamera3D>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    match (*backend, picking) {
+        (PickingBackend::Gpu, None) => {
+            let Ok(main_camera) = main_camera.single() else {
+                return;
+            };
+            let mut image = Image::new_fill(
+                Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                &[0; 4],
+                TextureFormat::Rgba8UnormSrgb,
+                RenderAssetUsages::RENDER_WORLD,
+            );
+            image.texture_descriptor.usage |=
+                TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC;
+            let image = images.add(image);
+
+            let camera = commands
+                .spawn((
+                    PickingCamera,
+                    Camera3d::default(),
+                    Camera {
+                        // Before the viewer camera; it draws to its own target anyway.
+                        order: -1,
+                        target: RenderTarget::Image(image.clone().into()),
+                        clear_color: ClearColorConfig::Custom(Color::NONE),
+                        is_active: false,
+                        ..default()
+                    },
+                    Tonemapping::None,
+                    DebandDither::Disabled,
+                    Msaa::Off,
+                    RenderLayers::layer(PICKING_LAYER),
+                    Transform::IDENTITY,
+                    ChildOf(main_camera),
+                ))
+                .id();
+            let readback = commands
+                .spawn((Readback::texture(image.clone()), InspectorHidden))
+                .observe(read_picked_pixel)
+                .id();
+            commands.insert_resource(GpuPicking {
+                image,
+                camera,
+                readback,
+                proxies: HashMap::default(),
+                entities: HashMap::default(),
+                next_id: 1,
+                cursor: None,
+                hit: None,
+            });
+        }
+        (PickingBackend::Raycast, Some(picking)) => {
+            commands.entity(picking.camera).despawn();
+            commands.entity(picking.readback).despawn();
+            for (_, proxy) in picking.proxies.values() {
+                if let Ok(mut proxy) = commands.get_entity(*proxy) {
+                    proxy.despawn();
+                }
+            }
+            images.remove(&picking.image);
+            commands.remove_resource::<GpuPicking>();
+        }
+        _ => {}
+    }
+}
+
+type PickableMesh<'a> = (
+    Entity,
+    Ref<'a, Mesh3d>,
+    Option<&'a RenderLayers>,
+    Option<&'a SkinnedMesh>,
+);
+
+/// Spawn proxies for new meshes, follow mesh swaps and forget despawned sources.
+fn sync_picking_proxies(
+    mut commands: Commands,
+    mut picking: ResMut<GpuPicking>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    meshes: Query<PickableMesh, Without<PickingProxy>>,
+    mut proxy_meshes: Query<&mut Mesh3d, With<PickingProxy>>,
+) {
+    let picking = &mut *picking;
+    picking.proxies.retain(|source, (id, _)| {
+        let alive = meshes.contains(*source);
+        if !alive {
+            picking.entities.remove(id);
+        }
+        alive
+    });
+
+    let main_layer = RenderLayers::default();
+    for (entity, mesh, layers, skin) in &meshes {
+        if !layers.unwrap_or(&main_layer).intersects(&main_layer) {
+            continue;
+        }
+        if let Some((_, proxy)) = picking.proxies.get(&entity) {
+            if mesh.is_changed()
+                && let Ok(mut proxy_mesh) = proxy_meshes.get_mut(*proxy)
+            {
+                proxy_mesh.0 = mesh.0.clone();
+            }
+            continue;
+        }
+        let Some(id) = picking.allocate_id(entity) else {
+            continue;
+        };
+        let material = materials.add(StandardMaterial {
+            base_color: id_color(id),
+            unlit: true,
+            ..default()
+        });
+        let mut proxy = commands.spawn((
+            PickingProxy { source: entity },
+            Mesh3d(mesh.0.clone()),
+            MeshMaterial3d(material),
+            RenderLayers::layer(PICKING_LAYER),
+            NotShadowCaster,
+            Transform::IDENTITY,
+            ChildOf(entity),
+        ));
+        if let Some(skin) = skin {
+            proxy.insert(skin.clone());
+        }
+        picking.proxies.insert(entity, (id, proxy.id()));
+    }
+}
+
+type MainCameraView<'a> = (&'a Camera, Ref<'a, Projection>);
+
+/// Point the id camera at the pixel under the cursor, or switch it off when the cursor
+/// is outside the viewer viewport.
+fn update_picking_camera(
+    pointer: Res<crate::PointerState>,
+    mapper: Res<CoordinateMapper>,
+    mut picking: ResMut<GpuPicking>,
+    main_camera: Query<MainCameraView, (With<MainCamera3D>, Without<PickingCamera>)>,
+    mut id_camera: Query<(&mut Camera, &mut Projection), With<PickingCamera>>,
+    mut activity: Option<ResMut<ActivityControl>>,
+) {
+    let (Ok((main, main_projection)), Ok((mut camera, mut projection))) =
+        (main_camera.single(), id_camera.single_mut())
+    else {
+        return;
+    };
+    let cursor = mapper.viewport_rect(main).and_then(|rect| {
+        let local = pointer.screen - rect.min;
+        let inside = !pointer.excluded
+            && main.is_active
+            && local.cmpge(Vec2::ZERO).all()
+            && local.cmplt(rect.size()).all();
+        inside.then_some((local.floor(), rect.size().as_uvec2()))
+    });
+
+    let Some((local, full_size)) = cursor else {
+        if camera.is_active {
+            camera.is_active = false;
+        }
+        picking.cursor = None;
+        picking.hit = None;
+        return;
+    };
+    let sub_view = SubCameraView {
+        full_size,
+        offset: local,
+        size: UVec2::ONE,
+    };
+    if !camera.is_active || main_projection.is_changed() {
+        *projection = main_projection.clone();
+    }
+    if !camera.is_active || camera.sub_camera_view != Some(sub_view) {
+        camera.is_active = true;
+        camera.sub_camera_view = Some(sub_view);
+        // The readback lands a couple of frames after the render.
+        if let Some(activity) = activity.as_mut() {
+            activity.remaining_frames = activity.remaining_frames.max(3);
+        }
+    }
+    picking.cursor = Some(local);
+}
+
+fn read_picked_pixel(trigger: Trigger<ReadbackComplete>, picking: Option<ResMut<GpuPicking>>) {
+    let (Some(mut picking), [r, g, b, ..]) = (picking, trigger.event().0.as_slice()) else {
+        return;
+    };
+    if picking.cursor.is_none() {
+        return;
+    }
+    let id = (*r as u32) << 16 | (*g as u32) << 8 | *b as u32;
+    picking.hit = picking.entities.get(&id).copied();
+}
+
+/// `pick_world_3d_system` for the `gpu` backend: the read-back entity, at its distance
+/// from the camera.
+pub(crate) fn pick_world_3d_gpu_system(
+    picking: Option<Res<GpuPicking>>,
+    cameras: Query<&GlobalTransform, With<MainCamera3D>>,
+    transforms: Query<&GlobalTransform>,
+    mut hits: ResMut<crate::PointerHits>,
+) {
+    hits.world3d.clear();
+    let Some(entity) = picking.and_then(|picking| picking.hit) else {
+        return;
+    };
+    let distance = cameras
+        .single()
+        .ok()
+        .zip(transforms.get(entity).ok())
+        .map_or(0.0, |(camera, transform)| {
+            camera.translation().distance(transform.translation())
+        });
+    hits.world3d.push(crate::Hit3D { entity, distance });
+}
+
+/// Select the 3D picking backend: "raycast" (bounding boxes, the default) or "gpu"
+/// (exact, via an id buffer; see module docs). Returns false for an unknown name.
+#[wasm_bindgen]
+pub fn set_picking_backend(ptr: u64, backend: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let backend = match backend {
+        "raycast" => PickingBackend::Raycast,
+        "gpu" => PickingBackend::Gpu,
+        _ => return false,
+    };
+    app.world_mut().insert_resource(backend);
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
@@ -0,0 +1,684 @@
+//! Parameter graph: small dataflow graphs for procedural animation.
+//!
+//! Node values are floats with 1–4 components. Source nodes read the clock and the
+//! pointer, math nodes combine them (scalars broadcast against vectors), and each frame
+//! every `output` node writes its input into a field of an entity, addressed by
+//! reflection path: a component field (component type path plus e.g. `translation.y`)
+//! or a field of the entity's `StandardMaterial` (e.g. `base_color`).
+//! Fields can be `f32`, `Vec2`/`Vec3`/`Vec4`, `[f32; 3]`/`[f32; 4]`, `Color` (sRGB) or
+//! `LinearRgba`.
+//!
+//! Output entities are stored by `PersistentId` so graphs survive a reload; they are
+//! saved with the project. The host edits graphs over FFI (`add_graph_node`, ...), where
+//! output entities may also be given as entity bits.
+
+use anyhow::{Context, anyhow, bail};
+use bevy::platform::collections::{HashMap, HashSet};
+use bevy::prelude::*;
+use bevy::reflect::ReflectPath;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::persistent_id::{PersistentId, PersistentIndex};
+use crate::bevy_app::timeline::TimelineState;
+use crate::entity_id::live_entity;
+use crate::{ActivityControl, PointerState, WorkerApp};
+
+/// A node value: the first `len` components are meaningful.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GraphValue {
+    components: [f32; 4],
+    len: usize,
+}
+
+impl GraphValue {
+    pub const ZERO: Self = Self::scalar(0.0);
+
+    pub const fn scalar(x: f32) -> Self {
+        Self {
+            components: [x, 0.0, 0.0, 0.0],
+            len: 1,
+        }
+    }
+
+    fn from_slice(values: &[f32]) -> Self {
+        let mut components = [0.0; 4];
+        let len = values.len().clamp(1, 4);
+        components[..values.len().min(4)].copy_from_slice(&values[..values.len().min(4)]);
+        Self { components, len }
+    }
+
+    /// Component `i`; a scalar broadcasts, missing components are 0.
+    pub fn get(&self, i: usize) -> f32 {
+        if self.len == 1 {
+            self.components[0]
+        } else {
+            self.components[i]
+        }
+    }
+
+    pub fn as_slice(&self) -> &[f32] {
+        &self.components[..self.len]
+    }
+
+    fn map(self, f: impl Fn(f32) -> f32) -> Self {
+        let mut out = self;
+        for x in &mut out.components[..self.len] {
+            *x = f(*x);
+        }
+        out
+    }
+
+    fn zip(self, other: Self, f: impl Fn(f32, f32) -> f32) -> Self {
+        let len = self.len.max(other.len);
+        let mut components = [0.0; 4];
+        for (i, x) in components[..len].iter_mut().enumerate() {
+            *x = f(self.get(i), other.get(i));
+        }
+        Self { components, len }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeSource {
+    /// Seconds since the app started.
+    #[default]
+    App,
+    /// The timeline playhead, in seconds.
+    Timeline,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PointerSpace {
+    /// Canvas pixels.
+    #[default]
+    Screen,
+    /// Overlay-world units; the last known position while the pointer is elsewhere.
+    Overlay,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MathOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Min,
+    Max,
+    /// `a` raised to `b`.
+    Power,
+    Sin,
+    Cos,
+    Abs,
+    Fract,
+}
+
+impl MathOp {
+    fn apply(self, a: GraphValue, b: GraphValue) -> GraphValue {
+        match self {
+            MathOp::Add => a.zip(b, |a, b| a + b),
+            MathOp::Subtract => a.zip(b, |a, b| a - b),
+            MathOp::Multiply => a.zip(b, |a, b| a * b),
+            MathOp::Divide => a.zip(b, |a, b| if b == 0.0 { 0.0 } else { a / b }),
+            MathOp::Min => a.zip(b, f32::min),
+            MathOp::Max => a.zip(b, f32::max),
+            MathOp::Power => a.zip(b, f32::powf),
+            MathOp::Sin => a.map(f32::sin),
+            MathOp::Cos => a.map(f32::cos),
+            MathOp::Abs => a.map(f32::abs),
+            MathOp::Fract => a.map(|x| x - x.floor()),
+        }
+    }
+}
+
+/// An output's entity: a `PersistentId`, or entity bits as sent by the host (replaced by
+/// the entity's `PersistentId` when the node is stored).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EntityRef {
+    Id(Uuid),
+    Bits(u64),
+}
+
+/// Where an output node writes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Binding {
+    /// `field` (reflect path, empty for the whole value) of the component with
+    /// type path `component`.
+    Component { component: String, field: String },
+    /// `field` of the entity's `StandardMaterial`, which is shared by every entity
+    /// using the same material handle.
+    Material { field: String },
+}
+
+/// Node kinds. Inputs are node ids; an unconnected input reads 0.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NodeKind {
+    Time {
+        #[serde(default)]
+        source: TimeSource,
+    },
+    Pointer {
+        #[serde(default)]
+        space: PointerSpace,
+    },
+    Constant {
+        value: Vec<f32>,
+    },
+    /// Unary ops ignore `b`.
+    Math {
+        op: MathOp,
+        a: Option<u32>,
+        b: Option<u32>,
+    },
+    /// A vector from the first component of each input.
+    Combine {
+        inputs: Vec<Option<u32>>,
+    },
+    /// One component of `input`.
+    Extract {
+        input: Option<u32>,
+        index: usize,
+    },
+    /// sRGB from hue (degrees), saturation and lightness (0..1).
+    Hsl {
+        hue: Option<u32>,
+        saturation: Option<u32>,
+        lightness: Option<u32>,
+    },
+    Output {
+        input: Option<u32>,
+        entity: EntityRef,
+        binding: Binding,
+    },
+}
+
+impl NodeKind {
+    fn inputs(&self) -> Vec<u32> {
+        let inputs: Vec<Option<u32>> = match self {
+            NodeKind::Time { .. } | NodeKind::Pointer { .. } | NodeKind::Constant { .. } => {
+                vec![]
+            }
+            NodeKind::Math { a, b, .. } => vec![*a, *b],
+            NodeKind::Combine { inputs } => inputs.clone(),
+            NodeKind::Extract { input, .. } | NodeKind::Output { input, .. } => vec![*input],
+            NodeKind::Hsl {
+                hue,
+                saturation,
+                lightness,
+            } => vec![*hue, *saturation, *lightness],
+        };
+        inputs.into_iter().flatten().collect()
+    }
+
+    fn disconnect(&mut self, id: u32) {
+        let inputs: Vec<&mut Option<u32>> = match self {
+            NodeKind::Time { .. } | NodeKind::Pointer { .. } | NodeKind::Constant { .. } => {
+                vec![]
+            }
+            NodeKind::Math { a, b, .. } => vec![a, b],
+            NodeKind::Combine { inputs } => inputs.iter_mut().collect(),
+            NodeKind::Extract { input, .. } | NodeKind::Output { input, .. } => vec![input],
+            NodeKind::Hsl {
+                hue,
+                saturation,
+                lightness,
+            } => vec![hue, saturation, lightness],
+        };
+        for input in inputs {
+            if *input == Some(id) {
+                *input = None;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphNode {
+    /// Assigned by `add_graph_node`; 0 in requests that create a node.
+    #[serde(default)]
+    pub id: u32,
+    #[serde(flatten)]
+    pub kind: NodeKind,
+    /// Editor placement, stored for the host UI.
+    #[serde(default)]
+    pub position: [f32; 2],
+}
+
+/// The project's parameter graph and the values of its last evaluation.
+#[derive(Resource, Debug, Default)]
+pub struct ParameterGraph {
+    nodes: Vec<GraphNode>,
+    values: HashMap<u32, GraphValue>,
+    /// Outputs that failed to apply; warned about once until the graph changes.
+    failed: HashSet<u32>,
+}
+
+impl ParameterGraph {
+    pub fn nodes(&self) -> &[GraphNode] {
+        &self.nodes
+    }
+
+    pub fn value(&self, id: u32) -> Option<GraphValue> {
+        self.values.get(&id).copied()
+    }
+
+    /// Replace all nodes, leaving the graph untouched if `nodes` is invalid.
+    pub fn replace(&mut self, nodes: Vec<GraphNode>) -> anyhow::Result<()> {
+        validate(&nodes)?;
+        self.nodes = nodes;
+        self.changed();
+        Ok(())
+    }
+
+    /// Add a node with a fresh id and return it.
+    pub fn add(&mut self, kind: NodeKind, position: [f32; 2]) -> anyhow::Result<u32> {
+        let id = self.nodes.iter().map(|node| node.id).max().unwrap_or(0) + 1;
+        let mut nodes = self.nodes.clone();
+        nodes.push(GraphNode { id, kind, position });
+        self.replace(nodes)?;
+        Ok(id)
+    }
+
+    pub fn update(&mut self, id: u32, kind: NodeKind, position: [f32; 2]) -> anyhow::Result<()> {
+        let mut nodes = self.nodes.clone();
+        let node = nodes
+            .iter_mut()
+            .find(|node| node.id == id)
+            .ok_or_else(|| anyhow!("No node {id}"))?;
+        node.kind = kind;
+        node.position = position;
+        self.replace(nodes)
+    }
+
+    /// Remove a node and disconnect everything reading from it.
+    pub fn remove(&mut self, id: u32) -> bool {
+        let before = self.nodes.len();
+        self.nodes.retain(|node| node.id != id);
+        if self.nodes.len() == before {
+            return false;
+        }
+        for node in &mut self.nodes {
+            node.kind.disconnect(id);
+        }
+        self.changed();
+        true
+    }
+
+    fn changed(&mut self) {
+        self.values.clear();
+        self.failed.clear();
+    }
+}
+
+/// Ids must be unique and non-zero, inputs must exist, and there must be no cycles.
+fn validate(nodes: &[GraphNode]) -> anyhow::Result<()> {
+    let mut inputs = HashMap::new();
+    for node in nodes {
+        if node.id == 0 {
+            bail!("Node id 0 is reserved");
+        }
+        if inputs.insert(node.id, node.kind.inputs()).is_some() {
+            bail!("Duplicate node id {}", node.id);
+        }
+    }
+    for (id, node_inputs) in &inputs {
+        if let Some(missing) = node_inputs
+            .iter()
+            .find(|input| !inputs.contains_key(*input))
+        {
+            bail!("Node {id} reads from missing node {missing}");
+        }
+    }
+
+    // Depth-first search; `Some(false)` is on the current path, `Some(true)` is done.
+    fn visit(id: u32, inputs: &HashMap<u32, Vec<u32>>, state: &mut HashMap<u32, bool>) -> bool {
+        match state.get(&id) {
+            Some(done) => return *done,
+            None => state.insert(id, false),
+        };
+        let acyclic = inputs[&id].iter().all(|input| visit(*input, inputs, state));
+        state.insert(id, true);
+        acyclic
+    }
+    let mut state = HashMap::new();
+    if let Some(id) = inputs.keys().find(|id| !visit(**id, &inputs, &mut state)) {
+        bail!("Node {id} is part of a cycle");
+    }
+    Ok(())
+}
+
+/// Per-frame source values.
+struct Sources {
+    app_time: f32,
+    timeline_time: f32,
+    pointer_screen: Vec2,
+    pointer_overlay: Vec2,
+}
+
+fn evaluate_node(
+    id: Option<u32>,
+    nodes: &HashMap<u32, &GraphNode>,
+    sources: &Sources,
+    values: &mut HashMap<u32, GraphValue>,
+) -> GraphValue {
+    let Some(node) = id.and_then(|id| nodes.get(&id)) else {
+        return GraphValue::ZERO;
+    };
+    if let Some(value) = values.get(&node.id) {
+        return *value;
+    }
+    let mut input = |id: Option<u32>| evaluate_node(id, nodes, sources, values);
+    let value = match &node.kind {
+        NodeKind::Time {
+            source: TimeSource::App,
+        } => GraphValue::scalar(sources.app_time),
+        NodeKind::Time {
+            source: TimeSource::Timeline,
+        } => GraphValue::scalar(sources.timeline_time),
+        NodeKind::Pointer { space } => {
+            let position = match space {
+                PointerSpace::Screen => sources.pointer_screen,
+                PointerSpace::Overlay => sources.pointer_overlay,
+            };
+            GraphValue::from_slice(&position.to_array())
+        }
+        NodeKind::Constant { value } => GraphValue::from_slice(value),
+        NodeKind::Math { op, a, b } => {
+            let a = input(*a);
+            op.apply(a, input(*b))
+        }
+        NodeKind::Combine { inputs } => {
+            let components: Vec<f32> = inputs.iter().map(|id| input(*id).get(0)).collect();
+            GraphValue::from_slice(&components)
+        }
+        NodeKind::Extract { input: id, index } => {
+            GraphValue::scalar(input(*id).as_slice().get(*index).copied().unwrap_or(0.0))
+        }
+        NodeKind::Hsl {
+            hue,
+            saturation,
+            lightness,
+        } => {
+            let color = Color::hsl(
+                input(*hue).get(0).rem_euclid(360.0),
+                input(*saturation).get(0).clamp(0.0, 1.0),
+                input(*lightness).get(0).clamp(0.0, 1.0),
+            )
+            .to_srgba();
+            GraphValue::from_slice(&[color.red, color.green, color.blue])
+        }
+        NodeKind::Output { input: id, .. } => input(*id),
+    };
+    values.insert(node.id, value);
+    value
+}
+
+/// Write `value` into the field at `path` under `root`.
+fn write_field(root: &mut dyn PartialReflect, path: &str, value: GraphValue) -> anyhow::Result<()> {
+    let field = if path.is_empty() {
+        root
+    } else {
+        path.reflect_element_mut(root)
+            .map_err(|e| anyhow!("{path}: {e}"))?
+    };
+    let [x, y, z, w] = [0, 1, 2, 3].map(|i| value.get(i));
+    let alpha = if value.len == 4 { w } else { 1.0 };
+    if let Some(field) = field.try_downcast_mut::<f32>() {
+        *field = x;
+    } else if let Some(field) = field.try_downcast_mut::<Vec2>() {
+        *field = Vec2::new(x, y);
+    } else if let Some(field) = field.try_downcast_mut::<Vec3>() {
+        *field = Vec3::new(x, y, z);
+    } else if let Some(field) = field.try_downcast_mut::<Vec4>() {
+        *field = Vec4::new(x, y, z, w);
+    } else if let Some(field) = field.try_downcast_mut::<[f32; 3]>() {
+        *field = [x, y, z];
+    } else if let Some(field) = field.try_downcast_mut::<[f32; 4]>() {
+        *field = [x, y, z, alpha];
+    } else if let Some(field) = field.try_downcast_mut::<Color>() {
+        *field = Color::srgba(x, y, z, alpha);
+    } else if let Some(field) = field.try_downcast_mut::<LinearRgba>() {
+        *field = LinearRgba::new(x, y, z, alpha);
+    } else {
+        bail!(
+            "{path} is a {}, not a float, vector or color",
+            field.reflect_type_path()
+        );
+    }
+    Ok(())
+}
+
+fn apply_output(
+    world: &mut World,
+    entity: Entity,
+    binding: &Binding,
+    value: GraphValue,
+) -> anyhow::Result<()> {
+    match binding {
+        Binding::Component { component, field } => {
+            let registry = world.resource::<AppTypeRegistry>().clone();
+            let registry = registry.read();
+            let reflect_component = registry
+                .get_with_type_path(component)
+                .and_then(|registration| registration.data::<ReflectComponent>())
+                .ok_or_else(|| anyhow!("{component} is not a reflected component"))?;
+            let mut entity = world.get_entity_mut(entity)?;
+            let mut reflected = reflect_component
+                .reflect_mut(&mut entity)
+                .ok_or_else(|| anyhow!("Entity has no {component}"))?;
+            write_field(reflected.as_partial_reflect_mut(), field, value)
+        }
+        Binding::Material { field } => {
+            let handle = world
+                .get_entity(entity)?
+                .get::<MeshMaterial3d<StandardMaterial>>()
+                .context("Entity has no StandardMaterial")?
+                .0
+                .clone();
+            let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+            let material = materials
+                .get_mut(&handle)
+                .context("Material is not loaded")?;
+            write_field(material, field, value)
+        }
+    }
+}
+
+/// Evaluate the graph and apply its outputs.
+pub(crate) fn evaluate_parameter_graph(world: &mut World) {
+    if world
+        .get_resource::<ParameterGraph>()
+        .is_none_or(|graph| graph.nodes.is_empty())
+    {
+        return;
+    }
+    let pointer = world.get_resource::<PointerState>();
+    let sources = Sources {
+        app_time: world
+            .get_resource::<Time>()
+            .map_or(0.0, |time| time.elapsed_secs()),
+        timeline_time: world
+            .get_resource::<TimelineState>()
+            .map_or(0.0, |timeline| timeline.current_time as f32),
+        pointer_screen: pointer.map_or(Vec2::ZERO, |pointer| pointer.screen),
+        pointer_overlay: pointer
+            .and_then(|pointer| pointer.overlay_world)
+            .unwrap_or_default(),
+    };
+
+    world.resource_scope(|world, mut graph: Mut<ParameterGraph>| {
+        let graph = &mut *graph;
+        let nodes: HashMap<u32, &GraphNode> =
+            graph.nodes.iter().map(|node| (node.id, node)).collect();
+        let mut values = HashMap::new();
+        for node in &graph.nodes {
+            evaluate_node(Some(node.id), &nodes, &sources, &mut values);
+        }
+
+        let mut has_outputs = false;
+        for node in &graph.nodes {
+            let NodeKind::Output {
+                entity, binding, ..
+            } = &node.kind
+            else {
+                continue;
+            };
+            has_outputs = true;
+            let entity = match entity {
+                EntityRef::Id(id) => world
+                    .get_resource::<PersistentIndex>()
+                    .and_then(|index| index.entity(*id)),
+                EntityRef::Bits(bits) => live_entity(world, *bits).ok(),
+            };
+            // Entities missing from the scene (not loaded yet) are skipped quietly.
+            let Some(entity) = entity else {
+                continue;
+            };
+            if let Err(e) = apply_output(world, entity, binding, values[&node.id])
+                && graph.failed.insert(node.id)
+            {
+                warn!("Graph output {} failed: {e}", node.id);
+            }
+        }
+        graph.values = values;
+
+        if has_outputs && let Some(mut activity) = world.get_resource_mut::<ActivityControl>() {
+            activity.remaining_frames = activity.remaining_frames.max(1);
+        }
+    });
+}
+
+/// Replace entity bits in output nodes by the entity's `PersistentId`, giving it one if
+/// it has none.
+fn persist_entity_refs(world: &mut World, kind: &mut NodeKind) -> anyhow::Result<()> {
+    let NodeKind::Output { entity, .. } = kind else {
+        return Ok(());
+    };
+    let EntityRef::Bits(bits) = *entity else {
+        return Ok(());
+    };
+    let target = live_entity(world, bits)?;
+    let id = match world.get::<PersistentId>(target) {
+        Some(id) => *id,
+        None => {
+            let id = PersistentId::new();
+            world.entity_mut(target).insert(id);
+            id
+        }
+    };
+    *entity = EntityRef::Id(id.0);
+    Ok(())
+}
+
+fn edit_graph(
+    app: &mut WorkerApp,
+    edit: impl FnOnce(&mut World, &mut ParameterGraph) -> anyhow::Result<u32>,
+) -> anyhow::Result<u32> {
+    let world = app.world_mut();
+    if !world.contains_resource::<ParameterGraph>() {
+        bail!("No parameter graph");
+    }
+    let result =
+        world.resource_scope(|world, mut graph: Mut<ParameterGraph>| edit(world, &mut graph));
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    result
+}
+
+/// The graph's nodes as a JSON array.
+#[wasm_bindgen]
+pub fn get_parameter_graph(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    app.world()
+        .get_resource::<ParameterGraph>()
+        .and_then(|graph| serde_json::to_string(graph.nodes()).ok())
+        .unwrap_or_else(|| "[]".to_string())
+}
+
+/// Replace the whole graph with a JSON array of nodes. Returns false (and keeps the
+/// current graph) if it is invalid: duplicate ids, missing inputs or cycles.
+#[wasm_bindgen]
+pub fn set_parameter_graph(ptr: u64, nodes_json: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let result = edit_graph(app, |world, graph| {
+        let mut nodes: Vec<GraphNode> = serde_json::from_str(nodes_json)?;
+        for node in &mut nodes {
+            persist_entity_refs(world, &mut node.kind)?;
+        }
+        graph.replace(nodes)?;
+        Ok(0)
+    });
+    result
+        .inspect_err(|e| warn!("set_parameter_graph: {e}"))
+        .is_ok()
+}
+
+/// Add a node, e.g. `{ "type": "time" }` or `{ "type": "output", "input": 3, "entity":
+/// <bits>, "binding": { "kind": "material", "field": "base_color" } }`. Returns its id,
+/// or 0 if the node is invalid.
+#[wasm_bindgen]
+pub fn add_graph_node(ptr: u64, node_json: &str) -> u32 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let result = edit_graph(app, |world, graph| {
+        let mut node: GraphNode = serde_json::from_str(node_json)?;
+        persist_entity_refs(world, &mut node.kind)?;
+        graph.add(node.kind, node.position)
+    });
+    result
+        .inspect_err(|e| warn!("add_graph_node: {e}"))
+        .unwrap_or(0)
+}
+
+/// Replace node `id`'s kind, inputs and position. Returns false if the node is missing
+/// or the change would make the graph invalid.
+#[wasm_bindgen]
+pub fn update_graph_node(ptr: u64, id: u32, node_json: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let result = edit_graph(app, |world, graph| {
+        let mut node: GraphNode = serde_json::from_str(node_json)?;
+        persist_entity_refs(world, &mut node.kind)?;
+        graph.update(id, node.kind, node.position)?;
+        Ok(id)
+    });
+    result
+        .inspect_err(|e| warn!("update_graph_node: {e}"))
+        .is_ok()
+}
+
+/// Remove node `id`, disconnecting nodes that read from it.
+#[wasm_bindgen]
+pub fn remove_graph_node(ptr: u64, id: u32) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    edit_graph(app, |_, graph| {
+        if graph.remove(id) {
+            Ok(id)
+        } else {
+            bail!("No node {id}")
+        }
+    })
+    .is_ok()
+}
+
+/// Values of the last evaluation as JSON `{ "<id>": [components] }`, for previews.
+#[wasm_bindgen]
+pub fn get_graph_values(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(graph) = app.world().get_resource::<ParameterGraph>() else {
+        return "{}".to_string();
+    };
+    let values: serde_json::Map<String, serde_json::Value> = graph
+        .values
+        .iter()
+        .map(|(id, value)| (id.to_string(), value.as_slice().into()))
+        .collect();
+    serde_json::Value::Object(values).to_string()
+}
@@ -0,0 +1,504 @@
+//! Rulers and guide lines.
+//!
+//! Rulers are thin tick strips drawn inside the top/left edges of the viewer panel
+//! (overlay-world units) and along the top of the timeline panel (seconds). Guides are
+//! horizontal/vertical lines in overlay-world space stored in the `Guides` resource:
+//! drag out of a ruler to create one, drag an existing guide to move it, drop it back
+//! onto a ruler to delete it. Dragged overlay shapes snap to guides. The host UI can
+//! manage guides over FFI (see `web_ffi`), and they are saved with the project.
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy_vello::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::bevy_app::CoordinateMapper;
+use crate::bevy_app::encode_cache::{ContentHasher, EncodeKey};
+use crate::bevy_app::overlay2d::SimpleMouseState;
+use crate::bevy_app::theme::Theme;
+use crate::bevy_app::timeline::TimelineState;
+use crate::panels::{PanelRect, Panels, TIMELINE_PANEL, VIEWER_PANEL};
+
+/// Ruler strip thickness (px).
+pub const RULER_SIZE: f32 = 18.0;
+/// Pointer distance (px) within which a guide can be grabbed.
+const GUIDE_GRAB_DISTANCE: f32 = 4.0;
+/// Distance (overlay-world units == px) within which dragged shapes snap to a guide.
+pub const GUIDE_SNAP_DISTANCE: f32 = 6.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuideAxis {
+    /// Line of constant overlay-world y.
+    Horizontal,
+    /// Line of constant overlay-world x.
+    Vertical,
+}
+
+impl GuideAxis {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "horizontal" | "h" => Some(GuideAxis::Horizontal),
+            "vertical" | "v" => Some(GuideAxis::Vertical),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Guide {
+    pub id: u32,
+    pub axis: GuideAxis,
+    /// Overlay-world coordinate (y for horizontal guides, x for vertical ones).
+    pub position: f32,
+}
+
+#[derive(Resource, Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Guides {
+    pub guides: Vec<Guide>,
+    next_id: u32,
+}
+
+impl Guides {
+    pub fn add(&mut self, axis: GuideAxis, position: f32) -> u32 {
+        self.next_id = self.next_id.max(1);
+        let id = self.next_id;
+        self.next_id += 1;
+        self.guides.push(Guide { id, axis, position });
+        id
+    }
+
+    pub fn set_position(&mut self, id: u32, position: f32) -> bool {
+        match self.guides.iter_mut().find(|g| g.id == id) {
+            Some(guide) => {
+                guide.position = position;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove(&mut self, id: u32) -> bool {
+        let len = self.guides.len();
+        self.guides.retain(|g| g.id != id);
+        self.guides.len() != len
+    }
+
+    pub fn clear(&mut self) {
+        self.guides.clear();
+    }
+
+    /// Replace all guides (e.g. on project load), keeping ids unique afterwards.
+    pub fn replace(&mut self, guides: Vec<Guide>) {
+        self.next_id = guides.iter().map(|g| g.id + 1).max().unwrap_or(1);
+        self.guides = guides;
+    }
+
+    /// Offset that moves `point` onto the nearest guide per axis (zero when none is
+    /// within `GUIDE_SNAP_DISTANCE`).
+    pub fn snap_offset(&self, point: Vec2) -> Vec2 {
+        let mut best = Vec2::splat(f32::INFINITY);
+        for guide in &self.guides {
+            match guide.axis {
+                GuideAxis::Vertical => {
+                    let d = guide.position - point.x;
+                    if d.abs() < best.x.abs() {
+                        best.x = d;
+                    }
+                }
+                GuideAxis::Horizontal => {
+                    let d = guide.position - point.y;
+                    if d.abs() < best.y.abs() {
+                        best.y = d;
+                    }
+                }
+            }
+        }
+        Vec2::new(
+            if best.x.abs() <= GUIDE_SNAP_DISTANCE {
+                best.x
+            } else {
+                0.0
+            },
+            if best.y.abs() <= GUIDE_SNAP_DISTANCE {
+                best.y
+            } else {
+                0.0
+            },
+        )
+    }
+}
+
+/// In-progress guide drag (pointer-driven).
+#[derive(Resource, Debug, Default)]
+pub(crate) struct GuideDrag {
+    dragging: Option<u32>,
+    hovered: Option<u32>,
+    last_cursor: Option<Vec2>,
+}
+
+#[derive(Component)]
+pub(crate) struct RulerScene;
+
+#[derive(Component)]
+pub(crate) struct GuideScene;
+
+pub(crate) fn setup_guides(mut commands: Commands) {
+    commands.spawn((
+        VelloScene::new(),
+        VelloScreenSpace,
+        RenderLayers::layer(1),
+        RulerScene,
+        EncodeKey::default(),
+    ));
+    commands.spawn((
+        VelloScene::new(),
+        VelloScreenSpace,
+        RenderLayers::layer(1),
+        GuideScene,
+        EncodeKey::default(),
+    ));
+}
+
+fn top_ruler(rect: PanelRect) -> PanelRect {
+    PanelRect {
+        h: RULER_SIZE.min(rect.h),
+        ..rect
+    }
+}
+
+fn left_ruler(rect: PanelRect) -> PanelRect {
+    PanelRect {
+        w: RULER_SIZE.min(rect.w),
+        ..rect
+    }
+}
+
+/// Screen-space distance from `screen` to a guide line.
+fn guide_distance(
+    mapper: &CoordinateMapper,
+    rect: PanelRect,
+    guide: &Guide,
+    screen: Vec2,
+) -> f32 {
+    let on_line = mapper.window_from_overlay(rect, Vec2::splat(guide.position));
+    match guide.axis {
+        GuideAxis::Horizontal => (on_line.y - screen.y).abs(),
+        GuideAxis::Vertical => (on_line.x - screen.x).abs(),
+    }
+}
+
+/// Create / move / delete guides with the left button. Runs before the overlay shape
+/// systems and marks the press as captured so they ignore it.
+pub(crate) fn guide_interaction_system(
+    mut drag: ResMut<GuideDrag>,
+    mut guides: ResMut<Guides>,
+    mut mouse: ResMut<SimpleMouseState>,
+    mut cursor_events: EventReader<CursorMoved>,
+    panels: Res<Panels>,
+    mapper: Res<CoordinateMapper>,
+) {
+    if let Some(last) = cursor_events.read().last() {
+        drag.last_cursor = Some(last.position);
+    }
+    let (Some(rect), Some(screen)) = (panels.rect(VIEWER_PANEL), drag.last_cursor) else {
+        return;
+    };
+    let world = mapper.overlay_world(rect, screen);
+
+    let hovered = guides
+        .guides
+        .iter()
+        .filter(|g| guide_distance(&mapper, rect, g, screen) <= GUIDE_GRAB_DISTANCE)
+        .map(|g| g.id)
+        .next();
+    drag.hovered = hovered;
+
+    if mouse.just_pressed && rect.contains(screen) {
+        if top_ruler(rect).contains(screen) {
+            drag.dragging = Some(guides.add(GuideAxis::Horizontal, world.y));
+        } else if left_ruler(rect).contains(screen) {
+            drag.dragging = Some(guides.add(GuideAxis::Vertical, world.x));
+        } else if let Some(id) = hovered {
+            drag.dragging = Some(id);
+        }
+        if drag.dragging.is_some() {
+            mouse.captured = true;
+        }
+    }
+
+    let Some(id) = drag.dragging else {
+        return;
+    };
+    if mouse.left_pressed {
+        let Some(axis) = guides.guides.iter().find(|g| g.id == id).map(|g| g.axis) else {
+            drag.dragging = None;
+            return;
+        };
+        let position = match axis {
+            GuideAxis::Horizontal => world.y,
+            GuideAxis::Vertical => world.x,
+        };
+        guides.set_position(id, position);
+    } else {
+        // Dropped back onto a ruler (or outside the panel): delete.
+        if !rect.contains(screen)
+            || top_ruler(rect).contains(screen)
+            || left_ruler(rect).contains(screen)
+        {
+            guides.remove(id);
+        }
+        drag.dragging = None;
+    }
+}
+
+/// Draw the viewer rulers and the timeline time ruler (when the timeline is enabled).
+pub(crate) fn render_rulers(
+    mut q_scene: Query<(&mut VelloScene, &mut EncodeKey), With<RulerScene>>,
+    panels: Res<Panels>,
+    mapper: Res<CoordinateMapper>,
+    theme: Res<Theme>,
+    timeline: Option<Res<TimelineState>>,
+) {
+    let Ok((mut scene, mut key)) = q_scene.single_mut() else {
+        return;
+    };
+    let viewer = panels.rect(VIEWER_PANEL);
+    let duration = timeline.map_or(0.0, |timeline| timeline.duration);
+    let timeline_rect = panels.rect(TIMELINE_PANEL);
+    let content_key = ContentHasher::default()
+        .rect(viewer)
+        .rect(timeline_rect)
+        .u32(theme.revision)
+        .f64(duration)
+        .finish();
+    if !key.update(content_key) {
+        return;
+    }
+    scene.reset();
+
+    let colors = &theme.ruler;
+
+    if let Some(rect) = viewer {
+        let top = top_ruler(rect);
+        let left = left_ruler(rect);
+        scene.push_layer(
+            peniko::Mix::Clip,
+            1.0,
+            kurbo::Affine::IDENTITY,
+            &rect.to_kurbo(),
+        );
+        for strip in [top, left] {
+            scene.fill(
+                peniko::Fill::NonZero,
+                kurbo::Affine::IDENTITY,
+                Theme::faded(colors.strip, 1.0),
+                None,
+                &strip.to_kurbo(),
+            );
+        }
+
+        // Ticks every 10 world units; taller every 50 / 100.
+        let mut path = kurbo::BezPath::new();
+        let tick_len = |v: i32| -> f64 {
+            if v % 100 == 0 {
+                RULER_SIZE as f64
+            } else if v % 50 == 0 {
+                RULER_SIZE as f64 * 0.6
+            } else {
+                RULER_SIZE as f64 * 0.3
+            }
+        };
+        let a = mapper.overlay_world(rect, Vec2::new(rect.x, rect.y));
+        let b = mapper.overlay_world(rect, Vec2::new(rect.x + rect.w, rect.y + rect.h));
+        let (min, max) = (a.min(b), a.max(b));
+        let ticks = |from: f32, to: f32| (from as i32 / 10 * 10..=to as i32).step_by(10);
+        for v in ticks(min.x, max.x) {
+            let x = mapper.window_from_overlay(rect, Vec2::new(v as f32, 0.0)).x as f64;
+            let y0 = (top.y + top.h) as f64;
+            path.move_to((x, y0));
+            path.line_to((x, y0 - tick_len(v)));
+        }
+        for v in ticks(min.y, max.y) {
+            let y = mapper.window_from_overlay(rect, Vec2::new(0.0, v as f32)).y as f64;
+            let x0 = (left.x + left.w) as f64;
+            path.move_to((x0, y));
+            path.line_to((x0 - tick_len(v), y));
+        }
+        scene.stroke(
+            &kurbo::Stroke::new(1.0),
+            kurbo::Affine::IDENTITY,
+            Theme::faded(colors.tick, 1.0),
+            None,
+            &path,
+        );
+        scene.pop_layer();
+    }
+
+    if let Some(rect) = timeline_rect.filter(|r| r.w > 0.0 && duration > 0.0) {
+        let strip = top_ruler(rect);
+        scene.push_layer(
+            peniko::Mix::Clip,
+            1.0,
+            kurbo::Affine::IDENTITY,
+            &rect.to_kurbo(),
+        );
+        scene.fill(
+            peniko::Fill::NonZero,
+            kurbo::Affine::IDENTITY,
+            Theme::faded(colors.time_strip, 1.0),
+            None,
+            &strip.to_kurbo(),
+        );
+        // Tenth-second ticks, full height on whole seconds.
+        let px_per_sec = rect.w as f64 / duration;
+        let bottom = (strip.y + strip.h) as f64;
+        let mut path = kurbo::BezPath::new();
+        let steps = (duration * 10.0).round() as i64;
+        for i in 0..=steps {
+            let x = rect.x as f64 + i as f64 * 0.1 * px_per_sec;
+            let len = if i % 10 == 0 {
+                strip.h as f64
+            } else if i % 5 == 0 {
+                strip.h as f64 * 0.6
+            } else {
+                strip.h as f64 * 0.3
+            };
+            path.move_to((x, bottom));
+            path.line_to((x, bottom - len));
+        }
+        scene.stroke(
+            &kurbo::Stroke::new(1.0),
+            kurbo::Affine::IDENTITY,
+            Theme::faded(colors.time_tick, 1.0),
+            None,
+            &path,
+        );
+        scene.pop_layer();
+    }
+}
+
+/// Draw guide lines across the viewer panel.
+pub(crate) fn render_guides(
+    mut q_scene: Query<(&mut VelloScene, &mut EncodeKey), With<GuideScene>>,
+    guides: Res<Guides>,
+    drag: Res<GuideDrag>,
+    panels: Res<Panels>,
+    mapper: Res<CoordinateMapper>,
+    theme: Res<Theme>,
+) {
+    let Ok((mut scene, mut key)) = q_scene.single_mut() else {
+        return;
+    };
+    let rect = panels.rect(VIEWER_PANEL);
+    let mut hasher = ContentHasher::default()
+        .rect(rect)
+        .u32(theme.revision)
+        .u32(drag.hovered.unwrap_or_default())
+        .u32(drag.dragging.unwrap_or_default());
+    for guide in &guides.guides {
+        hasher = hasher
+            .u32(guide.id)
+            .bool(guide.axis == GuideAxis::Horizontal)
+            .f32(guide.position);
+    }
+    if !key.update(hasher.finish()) {
+        return;
+    }
+    scene.reset();
+    let Some(rect) = rect else {
+        return;
+    };
+
+    let [r, g, b] = theme.gizmo.guide;
+    scene.push_layer(
+        peniko::Mix::Clip,
+        1.0,
+        kurbo::Affine::IDENTITY,
+        &rect.to_kurbo(),
+    );
+    for guide in &guides.guides {
+        // Same mapping as `guide_distance`, so the line is where it can be grabbed.
+        let on_line = mapper.window_from_overlay(rect, Vec2::splat(guide.position));
+        let line = match guide.axis {
+            GuideAxis::Horizontal => {
+                let y = on_line.y as f64;
+                kurbo::Line::new((rect.x as f64, y), ((rect.x + rect.w) as f64, y))
+            }
+            GuideAxis::Vertical => {
+                let x = on_line.x as f64;
+                kurbo::Line::new((x, rect.y as f64), (x, (rect.y + rect.h) as f64))
+            }
+        };
+        let active = drag.hovered == Some(guide.id) || drag.dragging == Some(guide.id);
+        let (width, alpha) = if active { (2.0, 1.0) } else { (1.0, 0.8) };
+        scene.stroke(
+            &kurbo::Stroke::new(width),
+            kurbo::Affine::IDENTITY,
+            Theme::faded([r, g, b, 1.0], alpha),
+            None,
+            &line,
+        );
+    }
+    scene.pop_layer();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snap_offset_moves_onto_the_nearest_guide_per_axis() {
+        let mut guides = Guides::default();
+        guides.add(GuideAxis::Vertical, 100.0);
+        guides.add(GuideAxis::Vertical, 104.0);
+        guides.add(GuideAxis::Horizontal, 50.0);
+        assert_eq!(
+            guides.snap_offset(Vec2::new(101.0, 47.0)),
+            Vec2::new(-1.0, 3.0)
+        );
+        assert_eq!(
+            guides.snap_offset(Vec2::new(103.0, 56.0)),
+            Vec2::new(1.0, -6.0)
+        );
+        // Only the axis with a guide in range snaps.
+        assert_eq!(
+            guides.snap_offset(Vec2::new(111.0, 52.0)),
+            Vec2::new(0.0, -2.0)
+        );
+        assert_eq!(
+            guides.snap_offset(Vec2::new(95.0, 80.0)),
+            Vec2::new(5.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn snap_offset_is_zero_without_guides_in_range() {
+        let mut guides = Guides::default();
+        assert_eq!(guides.snap_offset(Vec2::new(3.0, 4.0)), Vec2::ZERO);
+        guides.add(GuideAxis::Horizontal, 0.0);
+        assert_eq!(
+            guides.snap_offset(Vec2::new(0.0, GUIDE_SNAP_DISTANCE + 0.5)),
+            Vec2::ZERO
+        );
+    }
+
+    #[test]
+    fn ids_stay_unique_across_remove_and_replace() {
+        let mut guides = Guides::default();
+        let a = guides.add(GuideAxis::Vertical, 0.0);
+        let b = guides.add(GuideAxis::Horizontal, 0.0);
+        assert_ne!(a, b);
+        assert!(guides.remove(a));
+        assert!(!guides.remove(a));
+        assert!(!guides.set_position(a, 1.0));
+        assert_ne!(guides.add(GuideAxis::Vertical, 0.0), a);
+
+        guides.replace(vec![Guide {
+            id: 9,
+            axis: GuideAxis::Vertical,
+            position: 0.0,
+        }]);
+        assert_eq!(guides.add(GuideAxis::Vertical, 1.0), 10);
+    }
+}
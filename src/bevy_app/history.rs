@@ -0,0 +1,490 @@
+//! Undo / redo history.
+//!
+//! Interactive edits push a `HistoryEntry` once the gesture completes (pointer
+//! release), holding the before/after value of everything it touched. Undo applies
+//! the `before` side, redo the `after` side; pushing a new entry clears the redo stack.
+//!
+//! Global settings are covered too: commands that edit a resource (theme, atmosphere,
+//! guides, timeline rate / snap / events / tracks) go through `edit_resource`, so undo
+//! steps back through entity and setting changes alike, in the order they were made.
+//!
+//! A transaction (`begin` .. `commit`) folds everything pushed in between into one
+//! entry, e.g. a paste of many entities; `revert_pending` rolls part of it back.
+//!
+//! Structural inspector commands (spawn, despawn, component insert / remove / toggle)
+//! are recorded too. Undoing a spawn or redoing a despawn saves the entity as an
+//! `EntitySnapshot` before despawning it, and the other direction respawns it from the
+//! snapshot under a new id; `History` maps the old id to the new one so the entry's
+//! other ops (and older entries) still reach it.
+
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy_remote_inspector::{
+    InspectorContext,
+    command::{
+        Execute, InsertComponent, ReadComponent, RemoveComponent, ToggleComponent, ToggleVisibity,
+        UpdateComponent,
+    },
+};
+use serde_json::Value;
+
+/// Oldest entries are dropped beyond this depth.
+const MAX_UNDO_DEPTH: usize = 256;
+
+#[derive(Debug, Clone)]
+pub enum HistoryOp {
+    Transform {
+        entity: Entity,
+        before: Transform,
+        after: Transform,
+    },
+    /// Reflected component value edited through the inspector (serialized JSON).
+    Component {
+        entity: Entity,
+        component: usize,
+        before: Value,
+        after: Value,
+    },
+    /// Part of a resource edited by a command (see `edit_resource`).
+    Resource {
+        resource: &'static str,
+        change: Box<dyn ResourceChange>,
+    },
+    /// Component inserted, replaced or removed by a command; None while absent.
+    Presence {
+        entity: Entity,
+        component: usize,
+        before: Option<Value>,
+        after: Option<Value>,
+    },
+    /// Component disabled or enabled by the inspector; toggling again reverts it.
+    ToggleComponent { entity: Entity, component: usize },
+    /// Visibility toggled by the inspector; toggling again restores the saved one.
+    ToggleVisibility { entity: Entity },
+    /// Entity (with its descendants) spawned, or despawned when `spawned` is false.
+    Lifetime { entity: Entity, spawned: bool },
+}
+
+/// Serialized components of an entity and its descendants, enough to respawn it.
+/// Components the inspector can't serialize are left out.
+#[derive(Debug, Clone)]
+pub struct EntitySnapshot {
+    entity: Entity,
+    parent: Option<Entity>,
+    components: Vec<(usize, Value)>,
+    children: Vec<EntitySnapshot>,
+}
+
+impl EntitySnapshot {
+    pub fn capture(world: &mut World, entity: Entity) -> Option<Self> {
+        let entity_ref = world.get_entity(entity).ok()?;
+        let parent = entity_ref.get::<ChildOf>().map(ChildOf::parent);
+        let children: Vec<Entity> = entity_ref
+            .get::<Children>()
+            .map(|children| children.to_vec())
+            .unwrap_or_default();
+        // The hierarchy is rebuilt from the snapshot tree, not from these.
+        let skipped = [
+            world.component_id::<ChildOf>(),
+            world.component_id::<Children>(),
+        ];
+        let ids: Vec<usize> = entity_ref
+            .archetype()
+            .components()
+            .filter(|id| !skipped.contains(&Some(*id)))
+            .map(|id| id.index())
+            .collect();
+        let components = ids
+            .into_iter()
+            .filter_map(|component| Some((component, read(world, entity, component)?)))
+            .collect();
+        let children = children
+            .into_iter()
+            .filter_map(|child| Self::capture(world, child))
+            .collect();
+        Some(Self {
+            entity,
+            parent,
+            components,
+            children,
+        })
+    }
+
+    /// Spawn the snapshot again (under its parent, if that is alive) and map the old
+    /// ids to the new ones.
+    fn respawn(&self, world: &mut World, parent: Option<Entity>) -> Entity {
+        let entity = world.spawn_empty().id();
+        if let Some(parent) = parent.filter(|parent| world.get_entity(*parent).is_ok()) {
+            world.entity_mut(parent).add_child(entity);
+        }
+        for (component, value) in &self.components {
+            let command = InsertComponent {
+                entity,
+                component: *component,
+                value: value.clone(),
+            };
+            if let Err(e) = InspectorContext::run(world, |ctx, world| command.execute(ctx, world)) {
+                warn!("Failed to restore component {component} on {entity}: {e}");
+            }
+        }
+        if let Some(mut history) = world.get_resource_mut::<History>() {
+            history.aliases.insert(self.entity, entity);
+        }
+        for child in &self.children {
+            child.respawn(world, Some(entity));
+        }
+        entity
+    }
+}
+
+fn read(world: &mut World, entity: Entity, component: usize) -> Option<Value> {
+    let read = ReadComponent { entity, component };
+    InspectorContext::run(world, |ctx, world| read.execute(ctx, world)).ok()
+}
+
+/// Current id of an entity recorded as `entity`, which undo / redo may have respawned.
+fn resolve(world: &World, entity: Entity) -> Entity {
+    let Some(history) = world.get_resource::<History>() else {
+        return entity;
+    };
+    let mut entity = entity;
+    while let Some(next) = history.aliases.get(&entity) {
+        entity = *next;
+    }
+    entity
+}
+
+/// Save `entity` for a later respawn and despawn it.
+fn stash_and_despawn(world: &mut World, recorded: Entity) {
+    let entity = resolve(world, recorded);
+    let Some(snapshot) = EntitySnapshot::capture(world, entity) else {
+        return;
+    };
+    if let Some(mut history) = world.get_resource_mut::<History>() {
+        history.snapshots.insert(recorded, snapshot);
+    }
+    world.despawn(entity);
+}
+
+fn respawn_stashed(world: &mut World, recorded: Entity) {
+    let Some(snapshot) = world
+        .get_resource::<History>()
+        .and_then(|history| history.snapshots.get(&recorded).cloned())
+    else {
+        warn!("Can't respawn {recorded}: no snapshot");
+        return;
+    };
+    let parent = snapshot.parent.map(|parent| resolve(world, parent));
+    snapshot.respawn(world, parent);
+}
+
+fn run(world: &mut World, what: &str, command: impl Execute) {
+    if let Err(e) = InspectorContext::run(world, |ctx, world| command.execute(ctx, world)) {
+        warn!("Failed to restore {what}: {e}");
+    }
+}
+
+/// Before / after value of the edited part of one resource type. Type-erased so
+/// `HistoryOp` can hold edits to any resource.
+pub trait ResourceChange: Send + Sync + 'static {
+    fn apply(&self, world: &mut World, undo: bool);
+    fn clone_box(&self) -> Box<dyn ResourceChange>;
+}
+
+impl Clone for Box<dyn ResourceChange> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+impl std::fmt::Debug for dyn ResourceChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ResourceChange")
+    }
+}
+
+struct FieldChange<R, V> {
+    before: V,
+    after: V,
+    /// Writes one side back into the resource.
+    set: fn(&mut R, V),
+}
+
+impl<R: Resource, V: Clone + Send + Sync + 'static> ResourceChange for FieldChange<R, V> {
+    fn apply(&self, world: &mut World, undo: bool) {
+        if let Some(mut resource) = world.get_resource_mut::<R>() {
+            let value = if undo { &self.before } else { &self.after };
+            (self.set)(&mut *resource, value.clone());
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn ResourceChange> {
+        Box::new(FieldChange {
+            before: self.before.clone(),
+            after: self.after.clone(),
+            set: self.set,
+        })
+    }
+}
+
+impl HistoryOp {
+    fn apply(&self, world: &mut World, undo: bool) {
+        match self {
+            HistoryOp::Transform {
+                entity,
+                before,
+                after,
+            } => {
+                let entity = resolve(world, *entity);
+                if let Some(mut transform) = world.get_mut::<Transform>(entity) {
+                    *transform = if undo { *before } else { *after };
+                }
+            }
+            HistoryOp::Component {
+                entity,
+                component,
+                before,
+                after,
+            } => {
+                let entity = resolve(world, *entity);
+                let command = UpdateComponent {
+                    entity,
+                    component: *component,
+                    value: if undo { before } else { after }.clone(),
+                };
+                if let Err(e) =
+                    InspectorContext::run(world, |ctx, world| command.execute(ctx, world))
+                {
+                    warn!("Failed to restore component {component} on {entity}: {e}");
+                }
+            }
+            HistoryOp::Resource { change, .. } => change.apply(world, undo),
+            HistoryOp::Presence {
+                entity,
+                component,
+                before,
+                after,
+            } => {
+                let entity = resolve(world, *entity);
+                let component = *component;
+                let present = read(world, entity, component).is_some();
+                match (if undo { before } else { after }).clone() {
+                    Some(value) if present => run(
+                        world,
+                        "component",
+                        UpdateComponent {
+                            entity,
+                            component,
+                            value,
+                        },
+                    ),
+                    Some(value) => run(
+                        world,
+                        "component",
+                        InsertComponent {
+                            entity,
+                            component,
+                            value,
+                        },
+                    ),
+                    None if present => {
+                        run(world, "component", RemoveComponent { entity, component })
+                    }
+                    None => {}
+                }
+            }
+            HistoryOp::ToggleComponent { entity, component } => {
+                let entity = resolve(world, *entity);
+                let component = *component;
+                run(world, "component", ToggleComponent { entity, component });
+            }
+            HistoryOp::ToggleVisibility { entity } => {
+                let entity = resolve(world, *entity);
+                run(world, "visibility", ToggleVisibity { entity });
+            }
+            HistoryOp::Lifetime { entity, spawned } => {
+                if undo == *spawned {
+                    stash_and_despawn(world, *entity);
+                } else {
+                    respawn_stashed(world, *entity);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub label: String,
+    pub ops: Vec<HistoryOp>,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct History {
+    undo: Vec<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+    /// Open transaction: pushed ops collect here until `commit`.
+    open: Option<HistoryEntry>,
+    /// Despawned entities by recorded id, for `HistoryOp::Lifetime`.
+    snapshots: HashMap<Entity, EntitySnapshot>,
+    /// Recorded id -> id of the entity respawned in its place.
+    aliases: HashMap<Entity, Entity>,
+}
+
+impl History {
+    /// Record a completed edit. Entries without ops are ignored. Inside a transaction
+    /// the ops join the transaction's entry instead.
+    pub fn push(&mut self, entry: HistoryEntry) {
+        if entry.ops.is_empty() {
+            return;
+        }
+        if let Some(open) = &mut self.open {
+            open.ops.extend(entry.ops);
+            return;
+        }
+        self.undo.push(entry);
+        if self.undo.len() > MAX_UNDO_DEPTH {
+            let dropped = self.undo.remove(0);
+            self.forget(&dropped);
+        }
+        for dropped in std::mem::take(&mut self.redo) {
+            self.forget(&dropped);
+        }
+    }
+
+    /// Drop the snapshots only `entry` could respawn.
+    fn forget(&mut self, entry: &HistoryEntry) {
+        for op in &entry.ops {
+            if let HistoryOp::Lifetime { entity, .. } = op {
+                self.snapshots.remove(entity);
+            }
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Start a transaction: everything pushed until `commit` becomes one entry named
+    /// `label`. False if one is already open.
+    pub fn begin(&mut self, label: String) -> bool {
+        if self.open.is_some() {
+            return false;
+        }
+        self.open = Some(HistoryEntry {
+            label,
+            ops: Vec::new(),
+        });
+        true
+    }
+
+    pub fn in_transaction(&self) -> bool {
+        self.open.is_some()
+    }
+
+    /// Keep `snapshot` for respawning its entity when a despawn is undone.
+    pub fn save_snapshot(&mut self, snapshot: EntitySnapshot) {
+        self.snapshots.insert(snapshot.entity, snapshot);
+    }
+
+    /// Close the open transaction and push its entry. False when none is open.
+    pub fn commit(&mut self) -> bool {
+        match self.open.take() {
+            Some(entry) => {
+                self.push(entry);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Revert the open transaction's ops past the first `keep`, newest first, and drop
+/// them from it.
+pub fn revert_pending(world: &mut World, keep: usize) {
+    let Some(ops) = world
+        .get_resource_mut::<History>()
+        .and_then(|mut history| {
+            let ops = &mut history.open.as_mut()?.ops;
+            Some(ops.split_off(keep.min(ops.len())))
+        })
+    else {
+        return;
+    };
+    for op in ops.iter().rev() {
+        op.apply(world, true);
+    }
+}
+
+/// Run `edit` on resource `R` and record the change to the part `get` reads (and `set`
+/// writes back) as the undoable entry `label`. When that part is unchanged nothing is
+/// recorded and `R` is not marked changed, so `edit` must only touch that part.
+/// Returns `edit`'s result, or None without the resource.
+pub fn edit_resource<R, V, T>(
+    world: &mut World,
+    label: &str,
+    get: fn(&R) -> V,
+    set: fn(&mut R, V),
+    edit: impl FnOnce(&mut R) -> T,
+) -> Option<T>
+where
+    R: Resource,
+    V: Clone + PartialEq + Send + Sync + 'static,
+{
+    let mut resource = world.get_resource_mut::<R>()?;
+    let before = get(&*resource);
+    let result = edit(resource.bypass_change_detection());
+    let after = get(&*resource);
+    if before == after {
+        return Some(result);
+    }
+    resource.set_changed();
+    if let Some(mut history) = world.get_resource_mut::<History>() {
+        history.push(HistoryEntry {
+            label: label.to_string(),
+            ops: vec![HistoryOp::Resource {
+                resource: std::any::type_name::<R>(),
+                change: Box::new(FieldChange { before, after, set }),
+            }],
+        });
+    }
+    Some(result)
+}
+
+/// Revert the most recent entry. Returns false when there is nothing to undo or a
+/// transaction is open.
+pub fn undo(world: &mut World) -> bool {
+    let Some(entry) = world
+        .get_resource_mut::<History>()
+        .filter(|history| !history.in_transaction())
+        .and_then(|mut history| history.undo.pop())
+    else {
+        return false;
+    };
+    for op in entry.ops.iter().rev() {
+        op.apply(world, true);
+    }
+    world.resource_mut::<History>().redo.push(entry);
+    true
+}
+
+/// Re-apply the most recently undone entry. Returns false when there is nothing to redo
+/// or a transaction is open.
+pub fn redo(world: &mut World) -> bool {
+    let Some(entry) = world
+        .get_resource_mut::<History>()
+        .filter(|history| !history.in_transaction())
+        .and_then(|mut history| history.redo.pop())
+    else {
+        return false;
+    };
+    for op in &entry.ops {
+        op.apply(world, false);
+    }
+    world.resource_mut::<History>().undo.push(entry);
+    true
+}
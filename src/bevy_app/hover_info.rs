@@ -0,0 +1,194 @@
+//! Hover tooltip data: what is under the pointer, for the host page to describe.
+//!
+//! Off by default; `set_hover_info(ptr, true)` turns it on. While on, `export_hover_info`
+//! re-resolves the hover after the pointer or the camera moves, at most every
+//! `REFRESH_SECS`, and pushes it through `send_hover_info_from_worker(json)` when it
+//! differs from the last push. Overlay shapes are checked first, as they draw on top:
+//!
+//! - `{ kind: "overlay", entity, name, position: [x, y], local: [x, y], screen }` for a
+//!   `VectorNode` (overlay-world and path-local coordinates),
+//! - `{ kind: "world", entity, name, position: [x, y, z], normal: [x, y, z], distance,
+//!   screen }` for the nearest mesh surface under the pointer,
+//! - `{ kind: "none", screen }` otherwise.
+//!
+//! `name` is the nearest `Name` on the entity or its ancestors (so a glTF mesh reports
+//! its node or scene name), or null. `screen` is the pointer in canvas physical px.
+
+use bevy::picking::mesh_picking::ray_cast::{MeshRayCast, MeshRayCastSettings};
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy_vello::prelude::*;
+use kurbo::{ParamCurveNearest, Shape as _};
+use serde_json::{Value, json};
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::CoordinateMapper;
+use crate::bevy_app::scene3d::MainCamera3D;
+use crate::bevy_app::vector::{VectorNode, transform_affine};
+use crate::{ActivityControl, PointerState, WorkerApp};
+
+/// Minimum time between two hover pushes, seconds.
+const REFRESH_SECS: f32 = 0.05;
+
+/// Pointer distance (overlay px) from a path's outline that still counts as a hit.
+const OUTLINE_TOLERANCE: f64 = 4.0;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_hover_info_from_worker(info_json: &str);
+}
+
+#[derive(Resource, Default)]
+pub struct HoverInfo {
+    pub enabled: bool,
+    /// The pointer or camera moved since the last push.
+    pending: bool,
+    since_refresh: f32,
+    last_screen: Vec2,
+    last_sent: String,
+}
+
+/// Nearest `Name` on `entity` or its ancestors.
+fn owner_name(entity: Entity, names: &Query<&Name>, parents: &Query<&ChildOf>) -> Option<String> {
+    std::iter::once(entity)
+        .chain(parents.iter_ancestors(entity))
+        .find_map(|entity| names.get(entity).ok())
+        .map(|name| name.to_string())
+}
+
+/// Topmost `VectorNode` under `position` (overlay-world), with the path-local point.
+fn hover_overlay(
+    position: Vec2,
+    paths: &Query<(Entity, &Transform, &VectorNode)>,
+) -> Option<(Entity, Vec2)> {
+    let world = kurbo::Point::new(position.x as f64, position.y as f64);
+    paths
+        .iter()
+        .filter_map(|(entity, transform, node)| {
+            let local = transform_affine(transform).inverse() * world;
+            let path = node.bez_path();
+            let filled = node.closed && node.fill.is_some() && path.contains(local);
+            let scale = transform
+                .scale
+                .x
+                .abs()
+                .max(transform.scale.y.abs())
+                .max(1e-6) as f64;
+            let tolerance = (node.stroke_width as f64 * 0.5).max(OUTLINE_TOLERANCE) / scale;
+            let on_outline = || {
+                path.segments()
+                    .any(|seg| seg.nearest(local, 1e-3).distance_sq <= tolerance * tolerance)
+            };
+            (filled || on_outline()).then_some((
+                transform.translation.z,
+                entity,
+                Vec2::new(local.x as f32, local.y as f32),
+            ))
+        })
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, entity, local)| (entity, local))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn export_hover_info(
+    time: Res<Time>,
+    mut hover: ResMut<HoverInfo>,
+    activity: Option<ResMut<ActivityControl>>,
+    pointer: Res<PointerState>,
+    mapper: Res<CoordinateMapper>,
+    cameras: Query<(&Camera, Ref<GlobalTransform>), With<MainCamera3D>>,
+    paths: Query<(Entity, &Transform, &VectorNode)>,
+    layers: Query<&RenderLayers>,
+    names: Query<&Name>,
+    parents: Query<&ChildOf>,
+    mut ray_cast: MeshRayCast,
+) {
+    if !hover.enabled {
+        return;
+    }
+    let camera_moved = cameras.iter().any(|(_, transform)| transform.is_changed());
+    hover.pending |= camera_moved || pointer.screen != hover.last_screen;
+    hover.since_refresh += time.delta_secs();
+    if !hover.pending {
+        return;
+    }
+    if hover.since_refresh < REFRESH_SECS {
+        // Keep frames coming until the throttled push goes out.
+        if let Some(mut activity) = activity {
+            activity.remaining_frames = activity.remaining_frames.max(1);
+        }
+        return;
+    }
+    hover.pending = false;
+    hover.since_refresh = 0.0;
+    hover.last_screen = pointer.screen;
+
+    let screen = pointer.screen.to_array();
+    let overlay = pointer
+        .overlay_world
+        .and_then(|position| Some((position, hover_overlay(position, &paths)?)));
+    let info: Value = if let Some((position, (entity, local))) = overlay {
+        json!({
+            "kind": "overlay",
+            "entity": entity.to_bits(),
+            "name": owner_name(entity, &names, &parents),
+            "position": position.to_array(),
+            "local": local.to_array(),
+            "screen": screen,
+        })
+    } else {
+        let ray = (!pointer.excluded)
+            .then(|| cameras.single().ok())
+            .flatten()
+            .and_then(|(camera, transform)| {
+                mapper.viewport_ray(camera, &transform, pointer.screen)
+            });
+        // Only what the viewer camera renders (layer 0), e.g. not GPU picking proxies.
+        let main_layer = RenderLayers::default();
+        let on_main_layer = |entity: Entity| {
+            layers
+                .get(entity)
+                .ok()
+                .is_none_or(|layers| layers.intersects(&main_layer))
+        };
+        let settings = MeshRayCastSettings::default().with_filter(&on_main_layer);
+        let hit = ray.and_then(|ray| ray_cast.cast_ray(ray, &settings).first().cloned());
+        match hit {
+            Some((entity, hit)) => json!({
+                "kind": "world",
+                "entity": entity.to_bits(),
+                "name": owner_name(entity, &names, &parents),
+                "position": hit.point.to_array(),
+                "normal": hit.normal.to_array(),
+                "distance": hit.distance,
+                "screen": screen,
+            }),
+            None => json!({ "kind": "none", "screen": screen }),
+        }
+    };
+
+    let info = info.to_string();
+    if info != hover.last_sent {
+        send_hover_info_from_worker(&info);
+        hover.last_sent = info;
+    }
+}
+
+/// Turn hover info streaming on or off. Turning it on pushes the current hover on the
+/// next frame.
+#[wasm_bindgen]
+pub fn set_hover_info(ptr: u64, enabled: bool) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(mut hover) = app.world_mut().get_resource_mut::<HoverInfo>() else {
+        return false;
+    };
+    hover.enabled = enabled;
+    hover.pending = true;
+    hover.since_refresh = REFRESH_SECS;
+    hover.last_sent.clear();
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
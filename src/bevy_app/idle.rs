@@ -0,0 +1,137 @@
+//! Idle detection: tell the host when the app has settled so it can stop its RAF loop
+//! entirely instead of ticking `enter_frame` just to have it return early.
+//!
+//! Off by default; `set_idle_detection(ptr, frames)` turns it on. A frame is quiet when
+//! it skipped its update (nothing animating, no frames requested) or ran one that
+//! changed no entity, left no frames requested (`remaining_frames`) and no asset
+//! loading. After `frames` quiet frames in a row `send_idle_from_worker(true)` goes out;
+//! the first busy frame after that sends `false`. UI nodes don't count as changes, since
+//! the FPS HUD rewrites its text on every update. Pending loads are found by walking
+//! reflected components for handles, as in the world audit, and are only checked when
+//! nothing else kept the frame busy.
+
+use bevy::asset::{LoadState, RecursiveDependencyLoadState};
+use bevy::prelude::*;
+use wasm_bindgen::prelude::*;
+
+use super::asset_graph::component_handles;
+use crate::{ActivityControl, WorkerApp};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_idle_from_worker(is_idle: bool);
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct IdleState {
+    /// Quiet frames before reporting idle; 0 disables detection.
+    threshold: u32,
+    quiet: u32,
+    idle: bool,
+    /// Set by `detect_frame_activity` when this frame's update did something.
+    busy: bool,
+}
+
+impl IdleState {
+    pub fn is_idle(&self) -> bool {
+        self.idle
+    }
+}
+
+/// Whether an entity changed this frame, ignoring UI nodes and text spans.
+fn world_changed(world: &World) -> bool {
+    let (last_run, this_run) = (world.last_change_tick(), world.read_change_tick());
+    world
+        .iter_entities()
+        .filter(|entity| !entity.contains::<Node>() && !entity.contains::<TextSpan>())
+        .any(|entity| {
+            entity.archetype().components().any(|id| {
+                entity
+                    .get_change_ticks_by_id(id)
+                    .is_some_and(|ticks| ticks.is_changed(last_run, this_run))
+            })
+        })
+}
+
+/// Whether a reflected component holds a handle to an asset (or dependency) still loading.
+fn assets_loading(world: &World) -> bool {
+    let Some(server) = world.get_resource::<AssetServer>() else {
+        return false;
+    };
+    let Some(type_registry) = world.get_resource::<AppTypeRegistry>() else {
+        return false;
+    };
+    let registry = type_registry.read();
+    world.iter_entities().any(|entity_ref| {
+        let mut loading = false;
+        component_handles(world, &registry, entity_ref, |_, handles| {
+            loading |= handles.into_iter().any(|id| {
+                matches!(server.get_load_state(id), Some(LoadState::Loading))
+                    || matches!(
+                        server.get_recursive_dependency_load_state(id),
+                        Some(RecursiveDependencyLoadState::Loading)
+                    )
+            });
+        });
+        loading
+    })
+}
+
+/// End of an update, in `Last`: note whether the frame did anything.
+pub(crate) fn detect_frame_activity(world: &mut World) {
+    if !world
+        .get_resource::<IdleState>()
+        .is_some_and(|state| state.threshold > 0)
+    {
+        return;
+    }
+    let frames_requested = world
+        .get_resource::<ActivityControl>()
+        .is_some_and(|activity| activity.remaining_frames > 0);
+    let busy = frames_requested || world_changed(world) || assets_loading(world);
+    world.resource_mut::<IdleState>().busy = busy;
+}
+
+/// Count one `enter_frame` tick (`updated`: whether it ran an update) and report
+/// transitions to the host.
+pub(crate) fn note_frame(world: &mut World, updated: bool) {
+    let Some(mut state) = world.get_resource_mut::<IdleState>() else {
+        return;
+    };
+    if state.threshold == 0 {
+        return;
+    }
+    let busy = std::mem::take(&mut state.busy) && updated;
+    if busy {
+        state.quiet = 0;
+        if state.idle {
+            state.idle = false;
+            send_idle_from_worker(false);
+        }
+    } else {
+        state.quiet = state.quiet.saturating_add(1);
+        if !state.idle && state.quiet >= state.threshold {
+            state.idle = true;
+            send_idle_from_worker(true);
+        }
+    }
+}
+
+/// Report idle after `frames` quiet frames in a row; 0 turns detection off (reporting
+/// `false` first if the app was idle, so the host resumes its loop).
+#[wasm_bindgen]
+pub fn set_idle_detection(ptr: u64, frames: u32) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(mut state) = app.world_mut().get_resource_mut::<IdleState>() else {
+        return false;
+    };
+    if frames == 0 && state.idle {
+        send_idle_from_worker(false);
+    }
+    *state = IdleState {
+        threshold: frames,
+        ..default()
+    };
+    true
+}
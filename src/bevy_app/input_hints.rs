@@ -0,0 +1,402 @@
+//! Screencast-style input hints: the keys and mouse buttons held right now, and the
+//! last few shortcuts, in the lower-left corner of the 3D viewer.
+//!
+//! Off by default; `set_input_hints(ptr, true)` shows them. They read Bevy's
+//! `ButtonInput<KeyCode>` / `ButtonInput<MouseButton>`, i.e. input after the host's key
+//! mapping, so a key that never shows up here never made it through the FFI. Key caps
+//! and the mouse are drawn in a vello scene; their labels are UI text laid over the
+//! caps (the vello overlay has no glyph renderer).
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy_remote_inspector::InspectorHidden;
+use bevy_vello::prelude::*;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::encode_cache::{ContentHasher, EncodeKey};
+use crate::bevy_app::render_timing::VelloEncodeSet;
+use crate::bevy_app::theme::Theme;
+use crate::panels::{Panels, VIEWER_PANEL};
+use crate::{ActivityControl, WorkerApp};
+
+/// Shortcuts listed at most, oldest dropped first.
+const MAX_RECENT: usize = 5;
+/// How long a shortcut stays listed, seconds.
+const RECENT_SECS: f64 = 3.0;
+/// Height of a row of caps, px.
+const ROW_HEIGHT: f32 = 28.0;
+const ROW_GAP: f32 = 6.0;
+/// Space left of the held keys for the mouse, px.
+const MOUSE_SPACE: f32 = 32.0;
+const FONT_SIZE: f32 = 16.0;
+const CAP_RADIUS: f64 = 5.0;
+
+const MODIFIERS: [(&str, [KeyCode; 2]); 4] = [
+    ("Ctrl", [KeyCode::ControlLeft, KeyCode::ControlRight]),
+    ("Alt", [KeyCode::AltLeft, KeyCode::AltRight]),
+    ("Shift", [KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+    ("Meta", [KeyCode::SuperLeft, KeyCode::SuperRight]),
+];
+
+#[derive(Resource, Debug, Default, PartialEq)]
+pub struct InputHints {
+    pub enabled: bool,
+    /// Held keys, in the order they were pressed.
+    held: Vec<KeyCode>,
+    /// Left, right, middle.
+    mouse: [bool; 3],
+    /// Recent shortcuts ("Ctrl+Z") and when they were pressed, oldest first.
+    recent: VecDeque<(String, f64)>,
+}
+
+/// Cap label for `key`: "A", "1", "Shift", "Esc", ...
+fn key_label(key: KeyCode) -> String {
+    if let Some((name, _)) = MODIFIERS.iter().find(|(_, keys)| keys.contains(&key)) {
+        return name.to_string();
+    }
+    let label = match key {
+        KeyCode::Escape => "Esc",
+        KeyCode::ArrowLeft => "←",
+        KeyCode::ArrowRight => "→",
+        KeyCode::ArrowUp => "↑",
+        KeyCode::ArrowDown => "↓",
+        KeyCode::Backspace => "⌫",
+        _ => {
+            let name = format!("{key:?}");
+            return ["Key", "Digit"]
+                .iter()
+                .find_map(|prefix| name.strip_prefix(prefix))
+                .map_or(name.clone(), str::to_string);
+        }
+    };
+    label.to_string()
+}
+
+fn is_modifier(key: KeyCode) -> bool {
+    MODIFIERS.iter().any(|(_, keys)| keys.contains(&key))
+}
+
+#[derive(Component)]
+#[require(InspectorHidden)]
+struct InputHintsRoot;
+
+/// Row of held keys; the mouse is drawn left of it.
+#[derive(Component)]
+struct HeldRow;
+
+#[derive(Component)]
+struct RecentRow;
+
+/// A labelled cap; recent shortcuts are drawn dimmer than held keys.
+#[derive(Component)]
+struct KeyCap {
+    held: bool,
+}
+
+#[derive(Component)]
+struct InputHintsScene;
+
+pub struct InputHintsPlugin;
+
+impl Plugin for InputHintsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputHints>()
+            .add_systems(Startup, setup_input_hints)
+            .add_systems(
+                Update,
+                (
+                    track_input_hints,
+                    sync_input_hint_caps.after(track_input_hints),
+                    position_input_hints,
+                    render_input_hints.in_set(VelloEncodeSet),
+                ),
+            );
+    }
+}
+
+fn setup_input_hints(mut commands: Commands) {
+    commands.spawn((
+        VelloScene::new(),
+        VelloScreenSpace,
+        RenderLayers::layer(1),
+        InputHintsScene,
+        EncodeKey::default(),
+    ));
+    let row = || Node {
+        height: Val::Px(ROW_HEIGHT),
+        flex_direction: FlexDirection::Row,
+        align_items: AlignItems::Center,
+        column_gap: Val::Px(ROW_GAP),
+        ..default()
+    };
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(ROW_GAP),
+                ..default()
+            },
+            Visibility::Hidden,
+            InputHintsRoot,
+        ))
+        .with_children(|root| {
+            root.spawn((row(), RecentRow));
+            root.spawn((
+                Node {
+                    margin: UiRect::left(Val::Px(MOUSE_SPACE)),
+                    ..row()
+                },
+                HeldRow,
+            ));
+        });
+}
+
+/// Follow held keys and buttons and record shortcuts.
+fn track_input_hints(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut hints: ResMut<InputHints>,
+    mut activity: Option<ResMut<ActivityControl>>,
+) {
+    if !hints.enabled {
+        return;
+    }
+    let now = time.elapsed_secs_f64();
+    let mut held: Vec<KeyCode> = hints
+        .held
+        .iter()
+        .copied()
+        .filter(|key| keys.pressed(*key))
+        .collect();
+    for key in keys.get_pressed() {
+        if !held.contains(key) {
+            held.push(*key);
+        }
+    }
+
+    let mut recent = hints.recent.clone();
+    for key in keys.get_just_pressed().filter(|key| !is_modifier(**key)) {
+        let mut parts: Vec<String> = MODIFIERS
+            .iter()
+            .filter(|(_, keys_of)| keys.any_pressed(*keys_of))
+            .map(|(name, _)| name.to_string())
+            .collect();
+        parts.push(key_label(*key));
+        recent.push_back((parts.join("+"), now));
+        if recent.len() > MAX_RECENT {
+            recent.pop_front();
+        }
+    }
+    while recent.front().is_some_and(|(_, t)| now - t > RECENT_SECS) {
+        recent.pop_front();
+    }
+
+    let mouse = [MouseButton::Left, MouseButton::Right, MouseButton::Middle]
+        .map(|button| buttons.pressed(button));
+    // Frames keep coming until the last shortcut has expired.
+    if !recent.is_empty()
+        && let Some(activity) = activity.as_mut()
+    {
+        activity.remaining_frames = activity.remaining_frames.max(1);
+    }
+    hints.set_if_neq(InputHints {
+        enabled: true,
+        held,
+        mouse,
+        recent,
+    });
+}
+
+/// Rebuild the cap labels when the held keys or shortcuts change.
+fn sync_input_hint_caps(
+    mut commands: Commands,
+    hints: Res<InputHints>,
+    theme: Res<Theme>,
+    mut roots: Query<&mut Visibility, With<InputHintsRoot>>,
+    held_rows: Query<Entity, With<HeldRow>>,
+    recent_rows: Query<Entity, With<RecentRow>>,
+) {
+    if !hints.is_changed() && !theme.is_changed() {
+        return;
+    }
+    for mut visibility in &mut roots {
+        visibility.set_if_neq(if hints.enabled {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+    let cap = |label: String, held: bool| {
+        (
+            Text::new(label),
+            TextFont {
+                font_size: FONT_SIZE,
+                ..default()
+            },
+            TextColor(Theme::color(theme.hud.label)),
+            Node {
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(2.0)),
+                ..default()
+            },
+            KeyCap { held },
+        )
+    };
+    for row in &held_rows {
+        commands.entity(row).despawn_related::<Children>();
+        commands.entity(row).with_children(|row| {
+            for key in &hints.held {
+                row.spawn(cap(key_label(*key), true));
+            }
+        });
+    }
+    for row in &recent_rows {
+        commands.entity(row).despawn_related::<Children>();
+        commands.entity(row).with_children(|row| {
+            for (shortcut, _) in &hints.recent {
+                row.spawn(cap(shortcut.clone(), false));
+            }
+        });
+    }
+}
+
+/// Keep the hints in the lower-left corner of the viewer panel.
+fn position_input_hints(panels: Res<Panels>, mut roots: Query<&mut Node, With<InputHintsRoot>>) {
+    if !panels.is_changed() {
+        return;
+    }
+    let Some(rect) = panels.rect(VIEWER_PANEL) else {
+        return;
+    };
+    for mut node in &mut roots {
+        node.left = Val::Px(rect.x + 8.0);
+        node.top = Val::Px(rect.y + rect.h - 8.0 - 2.0 * ROW_HEIGHT - ROW_GAP);
+    }
+}
+
+/// Window-space rect of a laid-out UI node.
+fn node_rect(node: &ComputedNode, transform: &GlobalTransform) -> kurbo::Rect {
+    let center = transform.translation().truncate();
+    let half = node.size() * 0.5;
+    kurbo::Rect::new(
+        (center.x - half.x) as f64,
+        (center.y - half.y) as f64,
+        (center.x + half.x) as f64,
+        (center.y + half.y) as f64,
+    )
+}
+
+/// Draw the caps behind their labels, and the mouse with its pressed buttons.
+fn render_input_hints(
+    mut scenes: Query<(&mut VelloScene, &mut EncodeKey), With<InputHintsScene>>,
+    hints: Res<InputHints>,
+    theme: Res<Theme>,
+    caps: Query<(&ComputedNode, &GlobalTransform, &KeyCap)>,
+    held_rows: Query<(&ComputedNode, &GlobalTransform), With<HeldRow>>,
+) {
+    let Ok((mut scene, mut key)) = scenes.single_mut() else {
+        return;
+    };
+    let rects: Vec<(kurbo::Rect, bool)> = caps
+        .iter()
+        .map(|(node, transform, cap)| (node_rect(node, transform), cap.held))
+        .collect();
+    let held_row = held_rows
+        .single()
+        .ok()
+        .map(|(node, transform)| node_rect(node, transform));
+    let mut hasher = ContentHasher::default()
+        .bool(hints.enabled)
+        .u32(theme.revision);
+    for &(rect, held) in rects.iter().chain(held_row.map(|row| (row, true)).as_ref()) {
+        hasher = hasher
+            .f64(rect.x0)
+            .f64(rect.y0)
+            .f64(rect.x1)
+            .f64(rect.y1)
+            .bool(held);
+    }
+    for pressed in hints.mouse {
+        hasher = hasher.bool(pressed);
+    }
+    if !key.update(hasher.finish()) {
+        return;
+    }
+    scene.reset();
+    if !hints.enabled {
+        return;
+    }
+
+    let background = Theme::faded(theme.hud.background, 1.0);
+    let accent = Theme::faded(theme.hud.value, 1.0);
+    let dim = Theme::faded(theme.hud.label, 0.4);
+    let outline = kurbo::Stroke::new(1.5);
+    for (rect, held) in &rects {
+        let cap = rect.to_rounded_rect(CAP_RADIUS);
+        scene.fill(
+            peniko::Fill::NonZero,
+            kurbo::Affine::IDENTITY,
+            background,
+            None,
+            &cap,
+        );
+        let edge = if *held { accent } else { dim };
+        scene.stroke(&outline, kurbo::Affine::IDENTITY, edge, None, &cap);
+    }
+
+    let Some(row) = held_row else {
+        return;
+    };
+    // Mouse body centered in the space left of the held keys; buttons split its top.
+    let center = kurbo::Point::new(row.x0 - MOUSE_SPACE as f64 * 0.5, row.center().y);
+    let body = kurbo::Rect::from_center_size(center, (18.0, 26.0));
+    let split = body.y0 + body.height() * 0.4;
+    let buttons = [
+        kurbo::Rect::new(body.x0, body.y0, center.x, split),
+        kurbo::Rect::new(center.x, body.y0, body.x1, split),
+        kurbo::Rect::from_center_size((center.x, body.y0 + 5.0), (3.0, 6.0)),
+    ];
+    let body_shape = body.to_rounded_rect(8.0);
+    scene.fill(
+        peniko::Fill::NonZero,
+        kurbo::Affine::IDENTITY,
+        background,
+        None,
+        &body_shape,
+    );
+    scene.push_layer(peniko::Mix::Clip, 1.0, kurbo::Affine::IDENTITY, &body_shape);
+    for (button, pressed) in buttons.iter().zip(hints.mouse) {
+        if pressed {
+            scene.fill(peniko::Fill::NonZero, kurbo::Affine::IDENTITY, accent, None, button);
+        }
+    }
+    scene.pop_layer();
+    let mut seams = kurbo::BezPath::new();
+    seams.move_to((body.x0, split));
+    seams.line_to((body.x1, split));
+    seams.move_to((center.x, body.y0));
+    seams.line_to((center.x, split));
+    scene.stroke(&outline, kurbo::Affine::IDENTITY, dim, None, &seams);
+    scene.stroke(&outline, kurbo::Affine::IDENTITY, dim, None, &body_shape);
+}
+
+/// Show or hide the input hints.
+#[wasm_bindgen]
+pub fn set_input_hints(ptr: u64, enabled: bool) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(mut hints) = app.world_mut().get_resource_mut::<InputHints>() else {
+        return false;
+    };
+    *hints = InputHints {
+        enabled,
+        ..default()
+    };
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
@@ -0,0 +1,105 @@
+//! Input-to-update latency, for tuning the worker / RAF frame loop.
+//!
+//! The pointer FFI calls (`mouse_move`, `left_bt_down`, `mouse_wheel`, ...) take the
+//! host event's timestamp: `performance.now()` in the context that runs the app (the
+//! worker in worker mode), NaN when unknown. `drain_input_queue` hands the timestamps of
+//! each frame's input to `InputLatency`, `PointerState::timestamp_ms` carries the newest
+//! pointer timestamp through picking, and the interaction systems note when a selection
+//! or drag was applied from it. At the end of the frame two diagnostics are recorded:
+//!
+//! - `INPUT_LATENCY`: oldest input drained this frame to the end of its update,
+//! - `INPUT_APPLY_LATENCY`: pointer event to the selection / drag it produced.
+//!
+//! Both land in `DiagnosticsStore`, so the FPS HUD and the inspector's `stats` event
+//! show them, and captured frame traces carry the per-frame value.
+
+use bevy::diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic};
+use bevy::prelude::*;
+use wasm_bindgen::prelude::*;
+
+pub const INPUT_LATENCY: DiagnosticPath = DiagnosticPath::const_new("input/latency_ms");
+pub const INPUT_APPLY_LATENCY: DiagnosticPath =
+    DiagnosticPath::const_new("input/apply_latency_ms");
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = performance, js_name = now)]
+    fn performance_now() -> f64;
+}
+
+/// `performance.now()` of the context running the app, ms; the clock input
+/// timestamps are given in.
+pub(crate) fn host_now() -> f64 {
+    performance_now()
+}
+
+/// A host input timestamp, or None for NaN / non-positive values (unknown).
+pub(crate) fn host_timestamp(ms: f64) -> Option<f64> {
+    (ms.is_finite() && ms > 0.0).then_some(ms)
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct InputLatency {
+    /// Oldest timestamp among the inputs drained this frame.
+    oldest: Option<f64>,
+    /// Newest pointer input timestamp drained so far.
+    pointer: Option<f64>,
+    /// Pointer timestamp the last selection / drag was applied from.
+    last_applied: Option<f64>,
+    /// Event-to-application latency of this frame's selection / drag, ms.
+    applied: Option<f64>,
+}
+
+impl InputLatency {
+    /// Note an input with host timestamp `stamp` drained this frame.
+    pub(crate) fn drained(&mut self, stamp: f64, pointer: bool) {
+        self.oldest = Some(self.oldest.map_or(stamp, |oldest| oldest.min(stamp)));
+        if pointer {
+            self.pointer = Some(self.pointer.map_or(stamp, |newest| newest.max(stamp)));
+        }
+    }
+
+    /// Timestamp of the newest pointer input drained so far.
+    pub fn pointer_timestamp(&self) -> Option<f64> {
+        self.pointer
+    }
+
+    /// Note that a selection or drag was applied from the pointer input stamped
+    /// `stamp`. Later frames applying the same input (a drag holding still) don't count.
+    pub fn applied(&mut self, stamp: Option<f64>) {
+        let Some(stamp) = stamp.filter(|&stamp| self.last_applied != Some(stamp)) else {
+            return;
+        };
+        self.last_applied = Some(stamp);
+        self.applied = Some((host_now() - stamp).max(0.0));
+    }
+}
+
+pub struct InputLatencyPlugin;
+
+impl Plugin for InputLatencyPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_diagnostic(Diagnostic::new(INPUT_LATENCY).with_suffix("ms"))
+            .register_diagnostic(Diagnostic::new(INPUT_APPLY_LATENCY).with_suffix("ms"))
+            .init_resource::<InputLatency>()
+            .add_systems(Last, record_input_latency.before(crate::end_frame_trace));
+    }
+}
+
+/// End of the frame: record the latencies of the input it consumed.
+fn record_input_latency(
+    mut latency: ResMut<InputLatency>,
+    mut diagnostics: Diagnostics,
+    trace: Option<ResMut<crate::FrameTrace>>,
+) {
+    let frame = latency.oldest.take().map(|oldest| (host_now() - oldest).max(0.0));
+    if let Some(ms) = frame {
+        diagnostics.add_measurement(&INPUT_LATENCY, || ms);
+        if let Some(mut trace) = trace {
+            trace.input_latency(ms);
+        }
+    }
+    if let Some(ms) = latency.applied.take() {
+        diagnostics.add_measurement(&INPUT_APPLY_LATENCY, || ms);
+    }
+}
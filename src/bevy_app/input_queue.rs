@@ -0,0 +1,148 @@
+//! Single entry point for host input.
+//!
+//! The FFI input functions (`mouse_move`, `mouse_button`, `key_down`,
+//! `gamepad_update`, `text_input`, ...) run between frames, possibly several per frame and
+//! interleaved with `enter_frame`. Instead of writing Bevy events directly they append
+//! to `InputQueue`; `drain_input_queue` sends everything in sequence order at one point
+//! in `First`, right after the event buffers swap, so every system sees the same
+//! ordered batch and a press/release pair that arrives within one frame is never split
+//! across the event clear. Pointer inputs carry the host event timestamp, which the
+//! drain hands to `InputLatency`.
+
+use bevy::input::gamepad::RawGamepadEvent;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::mouse::{MouseButtonInput, MouseMotion, MouseWheel};
+use bevy::prelude::*;
+
+use super::input_latency::{InputLatency, host_timestamp};
+
+#[derive(Debug, Clone)]
+pub enum QueuedInput {
+    CursorMoved(CursorMoved),
+    MouseMotion(MouseMotion),
+    MouseButton(MouseButtonInput),
+    MouseWheel(MouseWheel),
+    Keyboard(KeyboardInput),
+    Gamepad(RawGamepadEvent),
+    Ime(Ime),
+}
+
+macro_rules! queued_input_from {
+    ($($event:ident => $variant:ident),* $(,)?) => {
+        $(impl From<$event> for QueuedInput {
+            fn from(event: $event) -> Self {
+                QueuedInput::$variant(event)
+            }
+        })*
+    };
+}
+
+queued_input_from!(
+    CursorMoved => CursorMoved,
+    MouseMotion => MouseMotion,
+    MouseButtonInput => MouseButton,
+    MouseWheel => MouseWheel,
+    KeyboardInput => Keyboard,
+    RawGamepadEvent => Gamepad,
+    Ime => Ime,
+);
+
+impl QueuedInput {
+    /// Short name for traces.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            QueuedInput::CursorMoved(_) => "cursor_moved",
+            QueuedInput::MouseMotion(_) => "mouse_motion",
+            QueuedInput::MouseButton(_) => "mouse_button",
+            QueuedInput::MouseWheel(_) => "mouse_wheel",
+            QueuedInput::Keyboard(_) => "keyboard",
+            QueuedInput::Gamepad(_) => "gamepad",
+            QueuedInput::Ime(_) => "ime",
+        }
+    }
+
+    fn is_pointer(&self) -> bool {
+        matches!(
+            self,
+            QueuedInput::CursorMoved(_)
+                | QueuedInput::MouseMotion(_)
+                | QueuedInput::MouseButton(_)
+                | QueuedInput::MouseWheel(_)
+        )
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct InputQueue {
+    next_seq: u64,
+    /// Sequence number, input, host timestamp (ms, `performance.now()`).
+    pending: Vec<(u64, QueuedInput, Option<f64>)>,
+}
+
+impl InputQueue {
+    /// Append an input; returns its sequence number.
+    pub fn push(&mut self, input: impl Into<QueuedInput>) -> u64 {
+        self.push_at(input, None)
+    }
+
+    /// Append an input with the host event's timestamp (`performance.now()` ms; NaN
+    /// when unknown); returns its sequence number.
+    pub fn push_stamped(&mut self, input: impl Into<QueuedInput>, timestamp: f64) -> u64 {
+        self.push_at(input, host_timestamp(timestamp))
+    }
+
+    fn push_at(&mut self, input: impl Into<QueuedInput>, timestamp: Option<f64>) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.pending.push((seq, input.into(), timestamp));
+        seq
+    }
+}
+
+pub(crate) fn drain_input_queue(world: &mut World) {
+    let Some(mut queue) = world.get_resource_mut::<InputQueue>() else {
+        return;
+    };
+    if queue.pending.is_empty() {
+        return;
+    }
+    let mut pending = std::mem::take(&mut queue.pending);
+    pending.sort_by_key(|(seq, ..)| *seq);
+    if let Some(mut trace) = world.get_resource_mut::<crate::FrameTrace>() {
+        for (_, input, _) in &pending {
+            trace.input(input.kind());
+        }
+    }
+    if let Some(mut latency) = world.get_resource_mut::<InputLatency>() {
+        for (_, input, timestamp) in &pending {
+            if let Some(timestamp) = timestamp {
+                latency.drained(*timestamp, input.is_pointer());
+            }
+        }
+    }
+    for (_, input, _) in pending {
+        match input {
+            QueuedInput::CursorMoved(event) => {
+                world.send_event(event);
+            }
+            QueuedInput::MouseMotion(event) => {
+                world.send_event(event);
+            }
+            QueuedInput::MouseButton(event) => {
+                world.send_event(event);
+            }
+            QueuedInput::MouseWheel(event) => {
+                world.send_event(event);
+            }
+            QueuedInput::Keyboard(event) => {
+                world.send_event(event);
+            }
+            QueuedInput::Gamepad(event) => {
+                world.send_event(event);
+            }
+            QueuedInput::Ime(event) => {
+                world.send_event(event);
+            }
+        }
+    }
+}
@@ -1,13 +1,20 @@
 use bevy::prelude::*;
 
-use crate::bevy_app::scene3d::ActiveState;
+use crate::bevy_app::cross_drag::CrossWindowDrag;
+use crate::bevy_app::input_latency::InputLatency;
+use crate::bevy_app::proportional_edit::{ProportionalDrag, ProportionalEdit};
+use crate::bevy_app::scene3d::{ActiveState, CurrentVolume};
 
 // Decide drag start/stop and update selection based on pointer hits.
+#[allow(clippy::too_many_arguments)]
 pub fn interaction_decide_system(
     pointer: Res<crate::PointerState>,
     hits: Res<crate::PointerHits>,
     mut drag: ResMut<crate::DragState>,
     mut selection: ResMut<crate::SelectionState>,
+    mut latency: ResMut<InputLatency>,
+    tool: Res<crate::bevy_app::tools::ToolState>,
+    mapper: Res<crate::bevy_app::CoordinateMapper>,
     cameras: Query<(&Camera, &GlobalTransform), With<crate::bevy_app::scene3d::MainCamera3D>>,
     transforms: Query<&GlobalTransform>,
 ) {
@@ -17,14 +24,15 @@ pub fn interaction_decide_system(
         drag.kind = None;
     }
 
-    // Drag begin or click selection start
-    if pointer.just_pressed_left {
+    // Drag begin or click selection start (other tools own the left button)
+    if pointer.just_pressed_left && tool.active.picks_3d() {
         if let Some(primary) = hits.primary {
             selection.selected.clear();
             selection.selected.insert(primary, ());
             selection.last_primary = Some(primary);
             drag.target = Some(primary);
             drag.kind = Some(crate::DragKind::World3D);
+            latency.applied(pointer.timestamp_ms);
 
             // Establish drag plane for 3D: if ctrl held -> fixed XZ plane (normal Y).
             // Otherwise plane passes through object and is camera-facing (normal = camera forward).
@@ -44,9 +52,7 @@ pub fn interaction_decide_system(
                     drag.plane_origin = cam_tf.translation();
                 }
                 // Compute grab offset: intersection point - entity translation
-                if let Some(ray) =
-                    crate::bevy_app::picking::camera_ray_from_window_px(camera, cam_tf, pointer.screen)
-                {
+                if let Some(ray) = mapper.viewport_ray(camera, cam_tf, pointer.screen) {
                     if let Some(hit_pos) =
                         intersect_ray_plane(ray, drag.plane_origin, drag.plane_normal)
                     {
@@ -64,20 +70,34 @@ pub fn interaction_decide_system(
         } else {
             selection.selected.clear();
             selection.last_primary = None;
+            latency.applied(pointer.timestamp_ms);
         }
     }
 }
 
-// Apply drag translation for 3D entities (simple XY plane move by screen delta * scalar)
+// Apply drag translation for 3D entities (simple XY plane move by screen delta * scalar).
+// With proportional editing on, neighbours follow by their falloff share of the motion.
+#[allow(clippy::too_many_arguments)]
 pub fn drag_apply_system(
     pointer: Res<crate::PointerState>,
     drag: Res<crate::DragState>,
+    mapper: Res<crate::bevy_app::CoordinateMapper>,
+    mut latency: ResMut<InputLatency>,
+    cross: Option<Res<CrossWindowDrag>>,
+    edit: Res<ProportionalEdit>,
+    mut proportional: ResMut<ProportionalDrag>,
+    volumes: Query<(Entity, &CurrentVolume)>,
     mut query: Query<&mut Transform>,
     cameras: Query<(&Camera, &GlobalTransform), With<crate::bevy_app::scene3d::MainCamera3D>>,
 ) {
     let Some(entity) = drag.target else {
+        proportional.clear();
         return;
     };
+    // Hold the entity still while it is being dropped onto the timeline.
+    if cross.is_some_and(|cross| cross.over_timeline()) {
+        return;
+    }
     match drag.kind {
         Some(crate::DragKind::World3D) => {}
         _ => return,
@@ -86,14 +106,30 @@ pub fn drag_apply_system(
     let Ok((camera, cam_tf)) = cameras.single() else {
         return;
     };
-    let Some(ray) =
-        crate::bevy_app::picking::camera_ray_from_window_px(camera, cam_tf, pointer.screen)
-    else {
+    let Some(ray) = mapper.viewport_ray(camera, cam_tf, pointer.screen) else {
         return;
     };
+    // Neighbours are captured once per drag, before the grabbed entity moves.
+    if proportional.target != Some(entity) {
+        if edit.enabled {
+            proportional.capture(entity, &volumes, &query);
+        } else {
+            proportional.clear();
+        }
+    }
     if let Some(hit_pos) = intersect_ray_plane(ray, drag.plane_origin, drag.plane_normal) {
         if let Ok(mut transform) = query.get_mut(entity) {
             transform.translation = hit_pos + drag.grab_offset_world;
+            latency.applied(pointer.timestamp_ms);
+        }
+        let offset = hit_pos + drag.grab_offset_world - proportional.origin;
+        for &(member, start, distance) in &proportional.members {
+            let translation = start + offset * edit.weight(distance);
+            if let Ok(mut transform) = query.get_mut(member)
+                && transform.translation != translation
+            {
+                transform.translation = translation;
+            }
         }
     }
 }
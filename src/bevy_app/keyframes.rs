@@ -0,0 +1,586 @@
+//! Keyframed scalar curves on timeline tracks.
+//!
+//! A track animates one `f32` property of its entity: a `Transform` field named by a
+//! reflect path (`"translation.y"`, `"scale.x"`, ...), or a 2D shape property such as
+//! `"opacity"`, `"fill.a"` or `"morph"` (see `TrackProperty`). Keys are kept sorted by
+//! time; each key's `easing` shapes the segment to the next key: one of the usual in/out
+//! curves, a CSS-style `cubic_bezier`, `steps`, or `Bezier`, which uses the keys' tangent
+//! handles (offsets in (seconds, value) like the handles of a vector path anchor).
+//! Tracks on the same entity and property are layers with a weight and blend mode;
+//! `apply_track_curves` writes the resolved values whenever the timeline changes.
+
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy::reflect::GetPath;
+use serde::{Deserialize, Serialize};
+
+use crate::bevy_app::overlay2d::z_angle;
+use crate::bevy_app::path_morph::PathMorph;
+use crate::bevy_app::timeline::{TimelineState, TimelineTrack, TrackBlend};
+use crate::bevy_app::vector::VectorNode;
+
+/// Shape of the segment from a key to the next. Serialized as the snake_case name, or
+/// `{ "cubic_bezier": { x1, y1, x2, y2 } }` / `{ "steps": { count } }` for the
+/// parameterised modes.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    /// Hold this key's value until the next key.
+    Constant,
+    #[default]
+    Linear,
+    /// Quadratic.
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    /// Overshoots backwards before leaving / past the target before settling.
+    BackIn,
+    BackOut,
+    BackInOut,
+    /// Cubic through this key's `handle_out` and the next key's `handle_in`.
+    Bezier,
+    /// CSS-style `cubic-bezier(x1, y1, x2, y2)` on the unit segment; `x1`, `x2` are
+    /// clamped to [0, 1].
+    CubicBezier { x1: f32, y1: f32, x2: f32, y2: f32 },
+    /// `count` equal jumps, the last one landing on the next key.
+    Steps { count: u32 },
+}
+
+/// Overshoot of the `Back*` modes (the usual ~10%).
+const BACK_OVERSHOOT: f32 = 1.701_58;
+
+impl Easing {
+    /// Parse a mode name (`"ease_in_out"`), `"cubic_bezier(x1, y1, x2, y2)"`,
+    /// `"steps(n)"`, or the serialized JSON form.
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim();
+        if let Some(args) = call_args(name, "cubic_bezier") {
+            let [x1, y1, x2, y2] = args.as_slice() else {
+                return None;
+            };
+            return Some(Self::CubicBezier {
+                x1: *x1,
+                y1: *y1,
+                x2: *x2,
+                y2: *y2,
+            });
+        }
+        if let Some(args) = call_args(name, "steps") {
+            let [count] = args.as_slice() else {
+                return None;
+            };
+            return (*count >= 1.0).then(|| Self::Steps {
+                count: *count as u32,
+            });
+        }
+        match name {
+            "constant" => Some(Self::Constant),
+            "linear" => Some(Self::Linear),
+            "ease_in" => Some(Self::EaseIn),
+            "ease_out" => Some(Self::EaseOut),
+            "ease_in_out" => Some(Self::EaseInOut),
+            "cubic_in" => Some(Self::CubicIn),
+            "cubic_out" => Some(Self::CubicOut),
+            "cubic_in_out" => Some(Self::CubicInOut),
+            "sine_in" => Some(Self::SineIn),
+            "sine_out" => Some(Self::SineOut),
+            "sine_in_out" => Some(Self::SineInOut),
+            "back_in" => Some(Self::BackIn),
+            "back_out" => Some(Self::BackOut),
+            "back_in_out" => Some(Self::BackInOut),
+            "bezier" => Some(Self::Bezier),
+            _ => serde_json::from_str(name).ok(),
+        }
+    }
+
+    /// Eased fraction for the non-`Bezier` modes.
+    pub fn ease(self, t: f32) -> f32 {
+        use std::f32::consts::FRAC_PI_2;
+        let c = BACK_OVERSHOOT;
+        match self {
+            Self::Constant => 0.0,
+            Self::Linear | Self::Bezier => t,
+            Self::EaseIn => t * t,
+            Self::EaseOut => t * (2.0 - t),
+            Self::EaseInOut => t * t * (3.0 - 2.0 * t),
+            Self::CubicIn => t * t * t,
+            Self::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Self::CubicInOut if t < 0.5 => 4.0 * t * t * t,
+            Self::CubicInOut => 1.0 - (2.0 - 2.0 * t).powi(3) / 2.0,
+            Self::SineIn => 1.0 - (t * FRAC_PI_2).cos(),
+            Self::SineOut => (t * FRAC_PI_2).sin(),
+            Self::SineInOut => (1.0 - (t * 2.0 * FRAC_PI_2).cos()) / 2.0,
+            Self::BackIn => t * t * ((c + 1.0) * t - c),
+            Self::BackOut => {
+                let u = t - 1.0;
+                1.0 + u * u * ((c + 1.0) * u + c)
+            }
+            Self::BackInOut => {
+                let c = c * 1.525;
+                if t < 0.5 {
+                    let u = 2.0 * t;
+                    u * u * ((c + 1.0) * u - c) / 2.0
+                } else {
+                    let u = 2.0 * t - 2.0;
+                    (u * u * ((c + 1.0) * u + c) + 2.0) / 2.0
+                }
+            }
+            Self::CubicBezier { x1, y1, x2, y2 } => {
+                let xs = [0.0, x1.clamp(0.0, 1.0), x2.clamp(0.0, 1.0), 1.0];
+                cubic([0.0, y1, y2, 1.0], solve_cubic(xs, t))
+            }
+            Self::Steps { count } => {
+                let count = count.max(1) as f32;
+                (t * count).floor() / count
+            }
+        }
+    }
+}
+
+/// Numeric arguments of `name(a, b, ...)`; `-` is accepted for `_` in the name.
+fn call_args(text: &str, name: &str) -> Option<Vec<f32>> {
+    let (head, rest) = text.split_once('(')?;
+    if head.trim().replace('-', "_") != name {
+        return None;
+    }
+    rest.strip_suffix(')')?
+        .split(',')
+        .map(|arg| arg.trim().parse().ok())
+        .collect()
+}
+
+/// Error allowed when reducing samples to keys, relative to the channel's value range.
+const SAMPLE_TOLERANCE: f32 = 1e-3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keyframe {
+    /// Seconds.
+    pub time: f64,
+    pub value: f32,
+    /// Incoming tangent handle, (seconds, value) relative to the key.
+    pub handle_in: Vec2,
+    /// Outgoing tangent handle, (seconds, value) relative to the key.
+    pub handle_out: Vec2,
+    /// Interpolation from this key to the next.
+    pub easing: Easing,
+}
+
+impl Default for Keyframe {
+    fn default() -> Self {
+        Self {
+            time: 0.0,
+            value: 0.0,
+            handle_in: Vec2::new(-0.5, 0.0),
+            handle_out: Vec2::new(0.5, 0.0),
+            easing: Easing::Linear,
+        }
+    }
+}
+
+impl Keyframe {
+    fn point(&self) -> Vec2 {
+        Vec2::new(self.time as f32, self.value)
+    }
+}
+
+/// Linear keys for a sampled channel: the samples a straight line through the kept
+/// neighbours can't reproduce within `SAMPLE_TOLERANCE`, plus the end points.
+pub fn keys_from_samples(times: &[f32], values: &[f32]) -> Vec<Keyframe> {
+    let lo = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let hi = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let tolerance = SAMPLE_TOLERANCE * (hi - lo).max(1e-3);
+    let key = |i: usize| Keyframe {
+        time: times[i] as f64,
+        value: values[i],
+        ..default()
+    };
+    let Some(last) = values.len().checked_sub(1) else {
+        return Vec::new();
+    };
+    let mut keys = vec![key(0)];
+    let mut anchor = 0;
+    for next in 2..=last {
+        let span = times[next] - times[anchor];
+        let fits = (anchor + 1..next).all(|i| {
+            let s = (times[i] - times[anchor]) / span.max(f32::EPSILON);
+            let line = values[anchor] + (values[next] - values[anchor]) * s;
+            (line - values[i]).abs() <= tolerance
+        });
+        if !fits {
+            anchor = next - 1;
+            keys.push(key(anchor));
+        }
+    }
+    if last > 0 {
+        keys.push(key(last));
+    }
+    keys
+}
+
+/// Sort keys by time (stable, so keys dragged onto the same time keep their order).
+pub fn sort_keys(keys: &mut [Keyframe]) {
+    keys.sort_by(|a, b| a.time.total_cmp(&b.time));
+}
+
+/// Value of the curve at `time`; flat before the first and after the last key.
+pub fn sample(keys: &[Keyframe], time: f64) -> Option<f32> {
+    let first = keys.first()?;
+    let last = keys.last()?;
+    if time <= first.time {
+        return Some(first.value);
+    }
+    if time >= last.time {
+        return Some(last.value);
+    }
+    let i = keys.partition_point(|k| k.time <= time) - 1;
+    let (a, b) = (&keys[i], &keys[i + 1]);
+    let span = (b.time - a.time).max(f64::EPSILON);
+    let t = ((time - a.time) / span) as f32;
+    Some(match a.easing {
+        Easing::Bezier => bezier_value(a, b, time as f32),
+        easing => a.value + (b.value - a.value) * easing.ease(t),
+    })
+}
+
+/// Control points of the Bezier segment from `a` to `b`, handle times clamped into the
+/// segment so the curve stays a function of time.
+pub fn bezier_points(a: &Keyframe, b: &Keyframe) -> [Vec2; 4] {
+    let (p0, p3) = (a.point(), b.point());
+    let span = p3.x - p0.x;
+    let mut p1 = p0 + a.handle_out;
+    let mut p2 = p3 + b.handle_in;
+    p1.x = p1.x.clamp(p0.x, p0.x + span);
+    p2.x = p2.x.clamp(p3.x - span, p3.x);
+    [p0, p1, p2, p3]
+}
+
+fn cubic(p: [f32; 4], s: f32) -> f32 {
+    let r = 1.0 - s;
+    r * r * r * p[0] + 3.0 * r * r * s * p[1] + 3.0 * r * s * s * p[2] + s * s * s * p[3]
+}
+
+/// Curve parameter at which the cubic with (monotonic) control values `xs` reaches `x`,
+/// by bisection.
+fn solve_cubic(xs: [f32; 4], x: f32) -> f32 {
+    let (mut lo, mut hi) = (0.0_f32, 1.0_f32);
+    for _ in 0..24 {
+        let mid = 0.5 * (lo + hi);
+        if cubic(xs, mid) < x {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    0.5 * (lo + hi)
+}
+
+/// Value of the Bezier segment at `time`: find the curve parameter whose time matches
+/// (monotonic, given the clamped handles), then evaluate the value there.
+fn bezier_value(a: &Keyframe, b: &Keyframe, time: f32) -> f32 {
+    let [p0, p1, p2, p3] = bezier_points(a, b);
+    let s = solve_cubic([p0.x, p1.x, p2.x, p3.x], time);
+    cubic([p0.y, p1.y, p2.y, p3.y], s)
+}
+
+/// Combine the layers of one property at `time`, in track order, starting from `base`:
+/// an override layer blends toward its value by its weight, an additive layer adds its
+/// weighted value. `None` when no layer has keys.
+pub fn resolve_layers<'a>(
+    base: f32,
+    time: f64,
+    layers: impl IntoIterator<Item = &'a TimelineTrack>,
+) -> Option<f32> {
+    let mut resolved = None;
+    for track in layers {
+        let Some(value) = sample(&track.keyframes, time) else {
+            continue;
+        };
+        let below = resolved.unwrap_or(base);
+        resolved = Some(match track.blend {
+            TrackBlend::Override => below + (value - below) * track.weight,
+            TrackBlend::Additive => below + value * track.weight,
+        });
+    }
+    resolved
+}
+
+/// What a track's `property` string animates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackProperty<'a> {
+    /// An `f32` field of `Transform` by reflect path (`"translation.x"`, `"scale.y"`).
+    Transform(&'a str),
+    /// `"rotation_deg"`: rotation about z, degrees (2D shapes).
+    RotationDeg,
+    /// `"opacity"` of a `VectorNode`.
+    Opacity,
+    /// `"fill.r"` .. `"fill.a"` of a `VectorNode`; an unfilled node starts from
+    /// transparent black.
+    Fill(usize),
+    /// `"stroke.r"` .. `"stroke.a"` of a `VectorNode`.
+    Stroke(usize),
+    /// `"stroke_width"` of a `VectorNode`.
+    StrokeWidth,
+    /// `"morph"`: the `PathMorph` amount of a `VectorNode` (see `set_path_morph`).
+    Morph,
+}
+
+impl<'a> TrackProperty<'a> {
+    pub fn parse(property: &'a str) -> Self {
+        match property {
+            "rotation_deg" => Self::RotationDeg,
+            "opacity" => Self::Opacity,
+            "stroke_width" => Self::StrokeWidth,
+            "morph" => Self::Morph,
+            _ => {
+                let channel = |name: &str| ["r", "g", "b", "a"].iter().position(|c| *c == name);
+                let color = property
+                    .split_once('.')
+                    .and_then(|(field, name)| Some((field, channel(name)?)));
+                match color {
+                    Some(("fill", i)) => Self::Fill(i),
+                    Some(("stroke", i)) => Self::Stroke(i),
+                    _ => Self::Transform(property),
+                }
+            }
+        }
+    }
+
+    pub(crate) fn read(
+        self,
+        transform: &Transform,
+        node: Option<&VectorNode>,
+        morph: Option<&PathMorph>,
+    ) -> Result<f32, String> {
+        let node = || node.ok_or("no VectorNode");
+        Ok(match self {
+            Self::Transform(path) => *transform.path::<f32>(path).map_err(|e| e.to_string())?,
+            Self::RotationDeg => z_angle(transform).to_degrees(),
+            Self::Opacity => node()?.opacity,
+            Self::Fill(i) => node()?.fill.map_or(0.0, |fill| fill[i]),
+            Self::Stroke(i) => node()?.stroke[i],
+            Self::StrokeWidth => node()?.stroke_width,
+            Self::Morph => {
+                node()?;
+                morph.ok_or("no PathMorph")?.amount
+            }
+        })
+    }
+
+    fn write(
+        self,
+        value: f32,
+        transform: &mut Transform,
+        node: Option<&mut VectorNode>,
+        morph: Option<&mut PathMorph>,
+    ) {
+        match (self, node) {
+            (Self::Transform(path), _) => {
+                if let Ok(field) = transform.path_mut::<f32>(path) {
+                    *field = value;
+                }
+            }
+            (Self::RotationDeg, _) => {
+                transform.rotation = Quat::from_rotation_z(value.to_radians());
+            }
+            (Self::Opacity, Some(node)) => node.opacity = value,
+            (Self::Fill(i), Some(node)) => node.fill.get_or_insert([0.0; 4])[i] = value,
+            (Self::Stroke(i), Some(node)) => node.stroke[i] = value,
+            (Self::StrokeWidth, Some(node)) => node.stroke_width = value.max(0.0),
+            (Self::Morph, Some(node)) => {
+                if let Some(morph) = morph {
+                    morph.amount = value;
+                    node.anchors = morph.anchors();
+                }
+            }
+            (_, None) => {}
+        }
+    }
+}
+
+/// Write each animated property's resolved value at the playhead into its entity.
+/// `rest` keeps each property's value from before it was first animated, the base the
+/// layers resolve on.
+pub(crate) fn apply_track_curves(
+    timeline: Res<TimelineState>,
+    mut targets: Query<(
+        &mut Transform,
+        Option<&mut VectorNode>,
+        Option<&mut PathMorph>,
+    )>,
+    mut rest: Local<HashMap<(Entity, String), f32>>,
+) {
+    if !timeline.is_changed() {
+        return;
+    }
+    let mut layers = HashMap::<(Entity, &str), Vec<&TimelineTrack>>::default();
+    for track in &timeline.tracks {
+        layers
+            .entry((track.entity, track.property.as_str()))
+            .or_default()
+            .push(track);
+    }
+    rest.retain(|(entity, property), _| layers.contains_key(&(*entity, property.as_str())));
+
+    for ((entity, property), tracks) in layers {
+        let Ok((mut transform, mut node, mut morph)) = targets.get_mut(entity) else {
+            continue;
+        };
+        let target = TrackProperty::parse(property);
+        let current = match target.read(&transform, node.as_deref(), morph.as_deref()) {
+            Ok(current) => current,
+            Err(err) => {
+                warn_once!("timeline track property {property:?}: {err}");
+                continue;
+            }
+        };
+        let base = *rest.entry((entity, property.to_string())).or_insert(current);
+        let Some(value) = resolve_layers(base, timeline.current_time, tracks) else {
+            continue;
+        };
+        if value == current {
+            continue;
+        }
+        target.write(
+            value,
+            &mut transform,
+            node.as_deref_mut(),
+            morph.as_deref_mut(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURVES: [Easing; 14] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+        Easing::CubicIn,
+        Easing::CubicOut,
+        Easing::CubicInOut,
+        Easing::SineIn,
+        Easing::SineOut,
+        Easing::SineInOut,
+        Easing::BackIn,
+        Easing::BackOut,
+        Easing::BackInOut,
+        Easing::CubicBezier {
+            x1: 0.42,
+            y1: 0.0,
+            x2: 0.58,
+            y2: 1.0,
+        },
+    ];
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn curves_start_at_zero_and_end_at_one() {
+        for easing in CURVES {
+            assert!(close(easing.ease(0.0), 0.0), "{easing:?} at 0");
+            assert!(close(easing.ease(1.0), 1.0), "{easing:?} at 1");
+        }
+    }
+
+    #[test]
+    fn in_out_curves_are_symmetric() {
+        for easing in [
+            Easing::EaseInOut,
+            Easing::CubicInOut,
+            Easing::SineInOut,
+            Easing::BackInOut,
+        ] {
+            assert!(close(easing.ease(0.5), 0.5), "{easing:?}");
+            for t in [0.1, 0.25, 0.4] {
+                assert!(
+                    close(easing.ease(t), 1.0 - easing.ease(1.0 - t)),
+                    "{easing:?}"
+                );
+            }
+        }
+        assert!(Easing::EaseIn.ease(0.5) < 0.5);
+        assert!(Easing::EaseOut.ease(0.5) > 0.5);
+    }
+
+    #[test]
+    fn back_curves_overshoot() {
+        assert!(Easing::BackIn.ease(0.2) < 0.0);
+        assert!(Easing::BackOut.ease(0.8) > 1.0);
+    }
+
+    #[test]
+    fn cubic_bezier_with_diagonal_handles_is_linear() {
+        let linear = Easing::CubicBezier {
+            x1: 0.25,
+            y1: 0.25,
+            x2: 0.75,
+            y2: 0.75,
+        };
+        for t in [0.1, 0.3, 0.5, 0.9] {
+            assert!(close(linear.ease(t), t));
+        }
+    }
+
+    #[test]
+    fn steps_jump_at_equal_intervals() {
+        let steps = Easing::Steps { count: 4 };
+        assert_eq!(steps.ease(0.0), 0.0);
+        assert_eq!(steps.ease(0.3), 0.25);
+        assert_eq!(steps.ease(0.99), 0.75);
+        assert_eq!(steps.ease(1.0), 1.0);
+    }
+
+    #[test]
+    fn parse_names_calls_and_json() {
+        assert_eq!(Easing::parse(" ease_in_out "), Some(Easing::EaseInOut));
+        assert_eq!(
+            Easing::parse("cubic-bezier(0.42, 0, 0.58, 1)"),
+            Some(Easing::CubicBezier {
+                x1: 0.42,
+                y1: 0.0,
+                x2: 0.58,
+                y2: 1.0
+            })
+        );
+        assert_eq!(Easing::parse("steps(3)"), Some(Easing::Steps { count: 3 }));
+        assert_eq!(
+            Easing::parse(r#"{ "steps": { "count": 2 } }"#),
+            Some(Easing::Steps { count: 2 })
+        );
+        assert_eq!(Easing::parse("steps(0)"), None);
+        assert_eq!(Easing::parse("cubic_bezier(1, 2)"), None);
+        assert_eq!(Easing::parse("bounce"), None);
+    }
+
+    #[test]
+    fn sample_applies_the_segment_easing() {
+        let key = |time, value, easing| Keyframe {
+            time,
+            value,
+            easing,
+            ..default()
+        };
+        let keys = [
+            key(0.0, 0.0, Easing::Linear),
+            key(1.0, 10.0, Easing::Constant),
+            key(2.0, 20.0, Easing::Linear),
+        ];
+        assert_eq!(sample(&keys, -1.0), Some(0.0));
+        assert_eq!(sample(&keys, 0.5), Some(5.0));
+        assert_eq!(sample(&keys, 1.5), Some(10.0));
+        assert_eq!(sample(&keys, 3.0), Some(20.0));
+        assert_eq!(sample(&[], 0.0), None);
+    }
+}
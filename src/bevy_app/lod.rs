@@ -0,0 +1,124 @@
+//! Level of detail: swap an entity's `Mesh3d` by its distance to the main 3D camera.
+//!
+//! `Lod` lists meshes from most to least detailed, each used up to its `max_distance`;
+//! beyond the last threshold the last mesh stays. A relative `hysteresis` band around
+//! each threshold keeps an entity near a boundary from flickering between levels.
+//! `Lod` is reflected with a default, so thresholds can be edited from the inspector;
+//! `set_lod_thresholds` sets them all at once.
+
+use bevy::prelude::*;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::scene3d::MainCamera3D;
+use crate::entity_id::live_entity_or_warn;
+use crate::{ActivityControl, WorkerApp};
+
+#[derive(Debug, Clone, Default, Reflect)]
+pub struct LodLevel {
+    pub mesh: Handle<Mesh>,
+    /// Camera distance (world units) up to which this level is used.
+    pub max_distance: f32,
+}
+
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+pub struct Lod {
+    /// Ordered from most to least detailed, by increasing `max_distance`.
+    pub levels: Vec<LodLevel>,
+    /// Fraction of a threshold the distance must pass it by before switching.
+    pub hysteresis: f32,
+    /// Index of the level in use.
+    #[reflect(ignore)]
+    current: Option<usize>,
+}
+
+impl Default for Lod {
+    fn default() -> Self {
+        Self {
+            levels: Vec::new(),
+            hysteresis: 0.1,
+            current: None,
+        }
+    }
+}
+
+impl Lod {
+    pub fn new(levels: Vec<LodLevel>) -> Self {
+        Self {
+            levels,
+            ..default()
+        }
+    }
+
+    /// Level for `distance`, staying at the current one within the hysteresis band.
+    fn level_for(&self, distance: f32) -> usize {
+        let last = self.levels.len().saturating_sub(1);
+        let target = self
+            .levels
+            .iter()
+            .position(|level| distance <= level.max_distance)
+            .unwrap_or(last);
+        let Some(current) = self.current.filter(|current| *current <= last) else {
+            return target;
+        };
+        let band = self.hysteresis.max(0.0);
+        if target > current && distance <= self.levels[current].max_distance * (1.0 + band) {
+            return current;
+        }
+        if target < current && distance > self.levels[target].max_distance * (1.0 - band) {
+            return current;
+        }
+        target
+    }
+}
+
+pub(crate) fn update_lod(
+    camera: Query<&GlobalTransform, With<MainCamera3D>>,
+    mut entities: Query<(&mut Lod, &GlobalTransform, &mut Mesh3d)>,
+) {
+    let Ok(camera) = camera.single() else {
+        return;
+    };
+    let eye = camera.translation();
+    for (mut lod, transform, mut mesh) in &mut entities {
+        if lod.levels.is_empty() {
+            continue;
+        }
+        let level = lod.level_for(eye.distance(transform.translation()));
+        if lod.current != Some(level) {
+            lod.bypass_change_detection().current = Some(level);
+        }
+        let handle = &lod.levels[level].mesh;
+        if mesh.0 != *handle {
+            mesh.0 = handle.clone();
+        }
+    }
+}
+
+/// Set the thresholds of `entity`'s `Lod` from a JSON array of distances, one per
+/// level in order. Returns false if the entity has no `Lod`, the JSON is invalid or
+/// the count doesn't match its levels.
+#[wasm_bindgen]
+pub fn set_lod_thresholds(ptr: u64, entity: u64, thresholds_json: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Ok(thresholds) = serde_json::from_str::<Vec<f32>>(thresholds_json) else {
+        return false;
+    };
+    let Some(entity) = live_entity_or_warn(app.world(), entity, "set_lod_thresholds") else {
+        return false;
+    };
+    let Some(mut lod) = app.world_mut().get_mut::<Lod>(entity) else {
+        return false;
+    };
+    if thresholds.len() != lod.levels.len() {
+        return false;
+    }
+    for (level, max_distance) in lod.levels.iter_mut().zip(thresholds) {
+        level.max_distance = max_distance;
+    }
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
@@ -1,45 +1,110 @@
 //! Bevy app module
 //! Splits 3D scene setup, 2D overlay, and shared types/systems into submodules.
 
+pub mod a11y;
+pub mod annotations;
+pub mod asset_graph;
+pub mod atmosphere;
+pub mod camera_track;
+pub mod constraints;
+pub mod coordinates;
+pub mod cross_drag;
+pub mod cursor_effects;
+pub mod curve_editor;
+pub mod demo_scene;
+pub mod depth_probe;
+mod encode_cache;
+pub mod environment;
+pub mod gamepad;
+pub mod gltf_animation;
+pub mod gpu_picking;
+pub mod graph;
+pub mod guides;
+pub mod history;
+pub mod hover_info;
+pub mod idle;
 mod input_accum;
+pub mod input_hints;
+pub mod input_latency;
+pub mod input_queue;
 mod interaction;
+pub mod keyframes;
+pub mod lod;
+pub mod motion_path;
+pub mod options;
 mod overlay2d;
+pub mod overlay_damage;
+pub mod particles;
+pub mod path_morph;
+mod pen_tool;
+pub mod persistent_id;
 mod picking;
+pub mod placement;
 mod pointer;
-mod scene3d;
-mod timeline;
+pub mod proportional_edit;
+pub mod recording;
+#[cfg(feature = "render_debug")]
+pub mod render_debug;
+pub mod render_quality;
+pub mod render_timing;
+pub mod scatter;
+pub(crate) mod scene3d;
+pub mod screen_projection;
+pub mod selection_sets;
+pub mod shape_templates;
+pub mod shortcuts;
+pub mod text_edit;
+pub mod theme;
+pub mod timecode;
+pub(crate) mod timeline;
+pub mod timeline_events;
+pub mod tools;
+pub mod track_layers;
+mod transform_handles;
 mod ui_panels;
+mod vector;
+pub mod visibility_debug;
+pub mod world_audit;
 
 use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
 use bevy::prelude::*;
 use bevy::render::view::RenderLayers;
 use bevy_vello::{VelloPlugin, prelude::*};
 
+pub(crate) use coordinates::CoordinateMapper;
+use encode_cache::{OverlayClock, advance_overlay_clock};
 pub use input_accum::*;
 // Bring required items into scope from submodules
 use interaction::{
     drag_apply_system, interaction_decide_system, outbound_hover_system, outbound_selection_system,
     selection_reflect_system,
 };
+pub(crate) use overlay2d::OverlayLayer;
 use overlay2d::{
     DraggableSquare, SimpleMouseState, animate_2d_overlay, render_draggable_square,
     setup_2d_overlay, simple_mouse_state_system, update_draggable_square_state,
     update_mini_square_entities, render_mini_squares, render_selection_marquee
 };
+pub(crate) use picking::{RayHit, raycast_world};
 use picking::{pick_overlay_2d_system, pick_world_3d_system, resolve_primary_hit_system};
 use pointer::pointer_collect_system;
 use scene3d::{render_active_shapes, rotate_3d_shapes, setup_3d_scene, update_aabbes};
+use options::{AppFeature, AppOptions, Capabilities};
+use render_timing::VelloEncodeSet;
 use timeline::TimelinePlugin;
 
 use crate::{
     WorkerApp,
     asset_reader::WebAssetPlugin,
     camera_controller::CameraControllerPlugin,
-    ffi_inspector_bridge::{InspectorStreamingState, inspector_continuous_streaming_system},
+    ffi_inspector_bridge::{
+        HOST_INSPECTOR_CLIENT, InspectorPreview, InspectorStreamingState, InspectorTransaction,
+        PendingComponentUpdates, PostMessageTransport, flush_component_updates_system, inspector_continuous_streaming_system,
+        sync_streaming_focus_system,
+    },
     fps_overlay::FPSOverlayPlugin,
-    // tracking_circle::TrackingCircle,
 };
-use bevy_remote_inspector::RemoteInspectorPlugin;
+use bevy_remote_inspector::{InspectorHidden, RemoteInspectorPlugin};
 
 const MAX_HISTORY_LENGTH: usize = 200;
 
@@ -50,14 +115,17 @@ pub const VARIANT_NO_LOG: u32 = 1 << 0;
 pub const VARIANT_MIN_PLUGINS: u32 = 1 << 1;
 pub const VARIANT_EMPTY: u32 = 1 << 2;
 
-pub(crate) fn init_app(variant_flags: u32) -> WorkerApp {
+pub(crate) fn init_app(variant_flags: u32, options: &AppOptions) -> WorkerApp {
     let no_log = variant_flags & VARIANT_NO_LOG != 0;
     let min_plugins = variant_flags & VARIANT_MIN_PLUGINS != 0;
     let empty = variant_flags & VARIANT_EMPTY != 0;
 
     let mut app = App::new();
+    app.insert_resource(Capabilities::new(options));
+    app.insert_resource(options.clone());
 
-    app.insert_resource(ClearColor(Color::srgb(0.97, 0.97, 0.97)));
+    app.insert_resource(ClearColor(Color::srgb_from_array(options.clear_color)));
+    app.insert_resource(theme::Theme::with_background(options.clear_color));
 
     if min_plugins {
         // Perf-grid cell B3 (`?bevy=min`): the smallest plugin set that can boot a
@@ -78,14 +146,16 @@ pub(crate) fn init_app(variant_flags: u32) -> WorkerApp {
             },
             bevy::a11y::AccessibilityPlugin,
             bevy::asset::AssetPlugin::default(),
-            bevy::render::RenderPlugin::default(),
+            options.render_plugin(),
             ImagePlugin::default_nearest(),
         ));
         init_shared_resources(&mut app);
         return WorkerApp::new(app);
     }
 
-    let mut default_plugins = DefaultPlugins.set(ImagePlugin::default_nearest());
+    let mut default_plugins = DefaultPlugins
+        .set(ImagePlugin::default_nearest())
+        .set(options.render_plugin());
 
     // By default, a primary window gets spawned by `WindowPlugin`, contained in `DefaultPlugins`
     // Do NOT create an implicit primary window; all windows are created explicitly
@@ -102,6 +172,11 @@ pub(crate) fn init_app(variant_flags: u32) -> WorkerApp {
         .build()
         .disable::<bevy::winit::WinitPlugin>();
 
+    default_plugins = default_plugins.set(bevy::log::LogPlugin {
+        custom_layer: crate::log_bridge::log_bridge_layer,
+        ..default()
+    });
+
     // Perf-grid cell B2 (`?bevy=nolog`): LogPlugin installs tracing-wasm on the web,
     // which emits performance.mark/measure for every system span every frame.
     if no_log {
@@ -117,23 +192,53 @@ pub(crate) fn init_app(variant_flags: u32) -> WorkerApp {
     }
 
     app.add_plugins((
-        // WebAssetPlugin::default(),
+        WebAssetPlugin,
         default_plugins,
-        // TrackingCircle,
-        VelloPlugin {
-            canvas_render_layers: RenderLayers::layer(1),
-            use_cpu: false,
-            antialiasing: vello::AaConfig::Area,
-        },
-        FPSOverlayPlugin,
+        cursor_effects::CursorEffectsPlugin,
+        input_hints::InputHintsPlugin,
+        overlay_damage::OverlayDamagePlugin,
+        annotations::AnnotationsPlugin,
         FrameTimeDiagnosticsPlugin {
             max_history_length: MAX_HISTORY_LENGTH,
             smoothing_factor: 2.0 / (MAX_HISTORY_LENGTH as f64 + 1.0),
         },
-        CameraControllerPlugin,
         RemoteInspectorPlugin,
-        TimelinePlugin,
     ));
+    // Bevy implements `Plugins` for tuples of up to 15.
+    app.add_plugins((
+        render_quality::RenderQualityPlugin,
+        render_timing::RenderTimingPlugin,
+        input_latency::InputLatencyPlugin,
+        crate::MemoryBudgetPlugin,
+        crate::ImagePreviewPlugin,
+        gpu_picking::GpuPickingPlugin,
+        depth_probe::DepthProbePlugin,
+        particles::ParticlesPlugin,
+    ));
+    app.world_mut()
+        .resource_mut::<bevy_remote_inspector::RemoteInspectorCore>()
+        .connect(HOST_INSPECTOR_CLIENT, PostMessageTransport);
+    // Cameras and overlay scenes are plumbing, not content: keep them out of the
+    // inspector hierarchy (`set_inspector_reveal_hidden` shows them).
+    app.register_required_components::<Camera, InspectorHidden>()
+        .register_required_components::<VelloScene, InspectorHidden>();
+    if options.has_feature(AppFeature::FpsOverlay) {
+        app.add_plugins(FPSOverlayPlugin);
+    }
+    if options.has_feature(AppFeature::CameraController) {
+        app.add_plugins(CameraControllerPlugin);
+    }
+    if options.timeline {
+        app.add_plugins(TimelinePlugin);
+    }
+    // Vello needs compute shaders; without it the vello scenes simply aren't rendered.
+    if options.vello_enabled() {
+        app.add_plugins(VelloPlugin {
+            canvas_render_layers: RenderLayers::layer(1),
+            use_cpu: false,
+            antialiasing: vello::AaConfig::Area,
+        });
+    }
 
     init_shared_resources(&mut app);
 
@@ -160,38 +265,179 @@ pub(crate) fn init_app(variant_flags: u32) -> WorkerApp {
     // --- STEP 3: 3D scene + viewport camera ----------------------------------
     // MainCamera3D (viewport-scoped, driven by the "viewer" panel rect) + meshes.
     app.add_systems(Startup, setup_3d_scene);
+    if options.demo_scene {
+        app.add_plugins(demo_scene::DemoScenePlugin);
+    }
     app.add_systems(Update, (
         apply_viewer_viewport, 
         rotate_3d_shapes, 
-        update_aabbes
+        update_aabbes,
+        theme::apply_theme.run_if(resource_changed::<theme::Theme>),
     ));
+    // Cameras of canvases the host paused stay off whatever switched them on this frame.
+    app.add_systems(Last, crate::canvas_view::hold_paused_cameras);
 
     // --- STEP 4: 2D overlay + UI panels + remaining Update systems -----------
-    app.add_systems(Startup, (setup_2d_overlay, ui_panels::setup_ui_panels));
+    app.add_systems(
+        Startup,
+        (
+            setup_2d_overlay,
+            ui_panels::setup_ui_panels,
+            guides::setup_guides,
+            transform_handles::setup_transform_handles,
+            vector::setup_vector_nodes,
+            pen_tool::setup_pen_tool,
+            tools::setup_measure_tool,
+        ),
+    );
     app.add_systems(
         Update,
         (
-            ui_panels::render_ui_panels,
+            ui_panels::render_ui_panels.in_set(VelloEncodeSet),
+            sync_streaming_focus_system.before(inspector_continuous_streaming_system),
             inspector_continuous_streaming_system,
-            animate_2d_overlay, // TODO: refactor overlay interaction to new picking path
+            advance_overlay_clock.before(animate_2d_overlay),
+            animate_2d_overlay.in_set(VelloEncodeSet), // TODO: refactor overlay interaction to new picking path
             simple_mouse_state_system,
             update_draggable_square_state,
-            render_draggable_square,
+            render_draggable_square.in_set(VelloEncodeSet),
             update_mini_square_entities,
-            render_mini_squares,
-            render_selection_marquee
+            render_mini_squares.in_set(VelloEncodeSet),
+            render_selection_marquee.in_set(VelloEncodeSet),
+            guides::guide_interaction_system
+                .after(simple_mouse_state_system)
+                .before(update_draggable_square_state)
+                .before(update_mini_square_entities),
+            guides::render_rulers.in_set(VelloEncodeSet),
+            guides::render_guides.in_set(VelloEncodeSet),
+            transform_handles::transform_handles_system
+                .after(guides::guide_interaction_system)
+                .before(update_mini_square_entities),
+            transform_handles::render_transform_handles
+                .after(transform_handles::transform_handles_system)
+                .in_set(VelloEncodeSet),
+            pen_tool::pen_tool_system
+                .after(guides::guide_interaction_system)
+                .before(transform_handles::transform_handles_system),
+            pen_tool::render_pen_preview
+                .after(pen_tool::pen_tool_system)
+                .in_set(VelloEncodeSet),
+            vector::render_vector_nodes.in_set(VelloEncodeSet),
+            persistent_id::assign_persistent_ids,
+        ),
+    );
+    app.add_plugins(shortcuts::ShortcutsPlugin);
+    app.add_systems(
+        Update,
+        (
+            tools::tool_shortcut_system.before(tools::tool_pointer_routing_system),
+            tools::tool_pointer_routing_system
+                .after(simple_mouse_state_system)
+                .before(guides::guide_interaction_system)
+                .before(update_draggable_square_state),
+            tools::outbound_tool_system.after(tools::tool_shortcut_system),
+            tools::pan_tool_system.after(simple_mouse_state_system),
+            tools::measure_tool_system.after(simple_mouse_state_system),
+            tools::render_measure
+                .after(tools::measure_tool_system)
+                .in_set(VelloEncodeSet),
         ),
     );
 
+    #[cfg(feature = "render_debug")]
+    {
+        #[cfg(feature = "wireframe_plugin")]
+        app.add_plugins(bevy::pbr::wireframe::WireframePlugin::default());
+        app.register_type::<render_debug::RenderDebugMode>();
+        app.add_systems(
+            Update,
+            (
+                render_debug::attach_render_debug_mode,
+                render_debug::setup_render_debug
+                    .run_if(render_debug::debug_materials_missing)
+                    .before(render_debug::apply_debug_materials),
+                render_debug::apply_debug_materials,
+                render_debug::apply_debug_gizmo_config,
+                render_debug::draw_debug_lines,
+            ),
+        );
+    }
+
+    #[cfg(feature = "scripting")]
+    app.add_plugins(crate::ScriptingPlugin);
+
+    app.register_type::<text_edit::VectorText>();
+    app.register_type::<vector::VectorNode>();
+    app.add_systems(Update, text_edit::text_edit_system);
+
+    app.add_systems(
+        Update,
+        graph::evaluate_parameter_graph.after(timeline::update_timeline_view),
+    );
+
+    app.register_type::<motion_path::FollowPath>();
+    app.add_systems(
+        Update,
+        motion_path::follow_paths.after(timeline::update_timeline_view),
+    );
+
+    app.register_type::<constraints::LookAt>();
+    app.register_type::<constraints::CopyPosition>();
+    app.register_type::<constraints::LimitDistance>();
+    app.add_systems(
+        PostUpdate,
+        constraints::apply_constraints
+            .before(bevy::transform::TransformSystem::TransformPropagate),
+    );
+
+    app.register_type::<lod::Lod>();
+    app.register_type::<lod::LodLevel>();
+    app.add_systems(
+        PostUpdate,
+        lod::update_lod.after(bevy::transform::TransformSystem::TransformPropagate),
+    );
+
+    app.register_type::<atmosphere::Atmosphere>();
+    app.add_systems(
+        Update,
+        (
+            environment::apply_pending_environment,
+            atmosphere::apply_atmosphere.after(environment::apply_pending_environment),
+        ),
+    );
+
+    app.add_systems(
+        PostUpdate,
+        a11y::export_a11y_tree.after(bevy::transform::TransformSystem::TransformPropagate),
+    );
+    app.add_systems(
+        PostUpdate,
+        hover_info::export_hover_info.after(bevy::transform::TransformSystem::TransformPropagate),
+    );
+    app.add_systems(
+        PostUpdate,
+        screen_projection::push_tracked_projections
+            .after(bevy::transform::TransformSystem::TransformPropagate),
+    );
+
+    // Coalesced `inspector_update_component` edits land before this frame's systems run.
+    app.add_systems(PreUpdate, flush_component_updates_system);
+
     // --- STEP 5: input/picking/interaction pipelines --------------------------
     app.add_systems(
         PreUpdate,
         (
             accumulate_cursor_delta_system,
             accumulate_custom_scroll_system,
+            proportional_edit::adjust_proportional_radius.after(accumulate_custom_scroll_system),
             pointer_collect_system,
             pick_overlay_2d_system,
-            pick_world_3d_system,
+            pick_world_3d_system.run_if(gpu_picking::picking_backend_is(
+                gpu_picking::PickingBackend::Raycast,
+            )),
+            gpu_picking::pick_world_3d_gpu_system.run_if(gpu_picking::picking_backend_is(
+                gpu_picking::PickingBackend::Gpu,
+            )),
             resolve_primary_hit_system,
         ),
     );
@@ -200,6 +446,14 @@ pub(crate) fn init_app(variant_flags: u32) -> WorkerApp {
         (
             interaction_decide_system,
             drag_apply_system,
+            persistent_id::update_persistent_index
+                .before(persistent_id::reconcile_selection_system),
+            crate::project::resolve_pending_tracks.after(persistent_id::update_persistent_index),
+            persistent_id::reconcile_selection_system
+                .after(interaction_decide_system)
+                .before(selection_reflect_system)
+                .before(outbound_hover_system)
+                .before(outbound_selection_system),
             selection_reflect_system,
             outbound_hover_system,
             outbound_selection_system,
@@ -217,16 +471,54 @@ fn init_shared_resources(app: &mut App) {
     app.init_resource::<AccumulatedCursorDelta>();
     app.init_resource::<AccumulatedScroll>();
     app.init_resource::<InspectorStreamingState>();
+    app.init_resource::<InspectorPreview>();
+    app.init_resource::<PendingComponentUpdates>();
+    app.init_resource::<InspectorTransaction>();
     app.init_resource::<crate::panels::Panels>();
+    app.init_resource::<crate::canvas_view::PausedCanvases>();
+    app.init_resource::<crate::panels::UiExclusionRects>();
+    app.init_resource::<CoordinateMapper>();
     // New interaction resources
     app.insert_resource(crate::ActivityControl::new());
     app.init_resource::<crate::PointerState>();
     app.init_resource::<crate::PointerHits>();
     app.init_resource::<crate::SelectionState>();
     app.init_resource::<crate::DragState>();
+    app.init_resource::<proportional_edit::ProportionalEdit>();
+    app.init_resource::<proportional_edit::ProportionalDrag>();
     // Overlay interaction resources
     app.init_resource::<DraggableSquare>();
     app.init_resource::<SimpleMouseState>();
+    app.init_resource::<OverlayClock>();
+    app.init_resource::<guides::Guides>();
+    app.init_resource::<guides::GuideDrag>();
+    app.init_resource::<graph::ParameterGraph>();
+    app.init_resource::<history::History>();
+    app.init_resource::<transform_handles::TransformHandles>();
+    app.init_resource::<tools::ToolState>();
+    app.init_resource::<tools::MeasureState>();
+    app.init_resource::<persistent_id::PersistentIndex>();
+    app.init_resource::<selection_sets::SelectionSets>();
+    app.init_resource::<crate::project::PendingTracks>();
+    app.init_resource::<pen_tool::PenState>();
+    app.init_resource::<environment::EnvironmentState>();
+    app.init_resource::<screen_projection::ProjectionTracking>();
+    // Host input reaches Bevy only through the queue, so every variant drains it.
+    app.init_resource::<input_queue::InputQueue>();
+    app.init_resource::<gamepad::HostGamepads>();
+    app.init_resource::<a11y::A11yExport>();
+    app.init_resource::<hover_info::HoverInfo>();
+    app.init_resource::<crate::Locale>();
+    app.init_resource::<crate::FrameTrace>();
+    app.init_resource::<crate::OpLog>();
+    app.init_resource::<idle::IdleState>();
+    app.add_systems(
+        First,
+        (crate::begin_frame_trace, input_queue::drain_input_queue)
+            .chain()
+            .after(bevy::ecs::event::EventUpdates),
+    );
+    app.add_systems(Last, (crate::end_frame_trace, idle::detect_frame_activity));
 }
 
 /// Full-window helper cameras for the single-canvas architecture, split so the
@@ -245,7 +537,8 @@ fn setup_background_camera(mut commands: Commands) {
         bevy::render::view::Msaa::Off,
         Camera {
             order: -10,
-            clear_color: ClearColorConfig::Custom(Color::srgb(0.97, 0.97, 0.97)),
+            // `ClearColor`, set from `AppOptions::clear_color`.
+            clear_color: ClearColorConfig::Default,
             ..default()
         },
         RenderLayers::none(),
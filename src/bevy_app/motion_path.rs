@@ -0,0 +1,222 @@
+//! Motion paths: place entities along Bézier paths by arclength.
+//!
+//! `ArcLengthPath` caches per-segment lengths of a `kurbo::BezPath` so it can be sampled
+//! or cut at a distance along the path. `FollowPath` binds an entity (a 3D entity or a
+//! 2D overlay shape) to an authored `VectorNode` path, or to the demo overlay path, and
+//! `follow_paths` moves it there every frame, either at a constant speed or in step
+//! with the timeline playhead.
+
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy_vello::prelude::kurbo::{self, ParamCurve, ParamCurveArclen, ParamCurveDeriv};
+
+use crate::bevy_app::overlay2d::AnimatedBezierPath;
+use crate::bevy_app::timeline::TimelineState;
+use crate::bevy_app::vector::VectorNode;
+
+/// Accuracy (in path units) of arclength computations.
+const ARCLEN_ACCURACY: f64 = 0.5;
+
+/// A `BezPath` with its segment arclengths.
+#[derive(Debug, Clone)]
+pub struct ArcLengthPath {
+    pub path: kurbo::BezPath,
+    pub seg_lengths: Vec<f64>,
+    pub total_length: f64,
+}
+
+impl ArcLengthPath {
+    pub fn new(path: kurbo::BezPath) -> Self {
+        let seg_lengths: Vec<f64> = path
+            .segments()
+            .map(|seg| seg.arclen(ARCLEN_ACCURACY))
+            .collect();
+        Self {
+            total_length: seg_lengths.iter().sum(),
+            path,
+            seg_lengths,
+        }
+    }
+
+    /// Index of the segment containing `distance` (clamped to the path), the segment
+    /// and the curve parameter there. None for an empty path.
+    fn locate(&self, distance: f64) -> Option<(usize, kurbo::PathSeg, f64)> {
+        let mut remaining = distance.clamp(0.0, self.total_length);
+        let segments = self.path.segments().zip(&self.seg_lengths);
+        let count = self.seg_lengths.len();
+        for (index, (seg, length)) in segments.enumerate() {
+            if remaining <= *length || index + 1 == count {
+                let t = if *length > 0.0 {
+                    seg.inv_arclen(remaining.min(*length), ARCLEN_ACCURACY)
+                } else {
+                    0.0
+                };
+                return Some((index, seg, t));
+            }
+            remaining -= length;
+        }
+        None
+    }
+
+    /// Point and unit tangent at `distance` along the path (clamped to its ends).
+    pub fn sample(&self, distance: f64) -> Option<(Vec2, Vec2)> {
+        let (_, seg, t) = self.locate(distance)?;
+        let point = seg.eval(t);
+        let derivative = match seg {
+            kurbo::PathSeg::Line(line) => line.p1 - line.p0,
+            kurbo::PathSeg::Quad(quad) => quad.deriv().eval(t).to_vec2(),
+            kurbo::PathSeg::Cubic(cubic) => cubic.deriv().eval(t).to_vec2(),
+        };
+        let tangent = Vec2::new(derivative.x as f32, derivative.y as f32).normalize_or(Vec2::X);
+        Some((Vec2::new(point.x as f32, point.y as f32), tangent))
+    }
+
+    /// The path from its start up to `distance`.
+    pub fn prefix(&self, distance: f64) -> kurbo::BezPath {
+        let mut partial = kurbo::BezPath::new();
+        let Some((end, _, end_t)) = self.locate(distance) else {
+            return partial;
+        };
+        for (index, seg) in self.path.segments().enumerate() {
+            if index == 0 {
+                partial.move_to(seg.start());
+            }
+            if index == end {
+                partial.push(seg.subsegment(0.0..end_t).as_path_el());
+                break;
+            }
+            partial.push(seg.as_path_el());
+        }
+        partial
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+pub enum PathProgress {
+    /// Advance `FollowPath::speed` units per second.
+    #[default]
+    Speed,
+    /// Map the timeline playhead (0..duration) onto the whole path.
+    Timeline,
+}
+
+/// Which axes the path's (x, y) drive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+pub enum PathPlane {
+    /// Translation x / y, rotation about Z: overlay shapes, or 3D entities facing the
+    /// camera.
+    #[default]
+    Xy,
+    /// Translation x / -z, rotation about Y: 3D entities moving over the ground.
+    Xz,
+}
+
+/// Move the entity along a path each frame.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+pub struct FollowPath {
+    /// Entity whose `VectorNode` is the path (placed by its `Transform`);
+    /// `Entity::PLACEHOLDER` follows the animated demo path.
+    pub path: Entity,
+    pub progress: PathProgress,
+    /// Units per second, for `PathProgress::Speed`.
+    pub speed: f32,
+    /// Distance added along the path, in path units.
+    pub offset: f32,
+    /// Wrap around at the end instead of stopping there.
+    pub looping: bool,
+    /// Rotate the entity to face along the path.
+    pub orient: bool,
+    pub plane: PathPlane,
+    /// World units per path unit (overlay paths are in pixels).
+    pub scale: f32,
+    #[reflect(ignore)]
+    traveled: f32,
+}
+
+impl Default for FollowPath {
+    fn default() -> Self {
+        Self {
+            path: Entity::PLACEHOLDER,
+            progress: PathProgress::Speed,
+            speed: 100.0,
+            offset: 0.0,
+            looping: true,
+            orient: false,
+            plane: PathPlane::Xy,
+            scale: 1.0,
+            traveled: 0.0,
+        }
+    }
+}
+
+pub(crate) fn follow_paths(
+    time: Res<Time>,
+    timeline: Option<Res<TimelineState>>,
+    demo_path: Option<Res<AnimatedBezierPath>>,
+    paths: Query<(Ref<VectorNode>, &Transform), Without<FollowPath>>,
+    mut followers: Query<(&mut FollowPath, &mut Transform)>,
+    mut cache: Local<HashMap<Entity, ArcLengthPath>>,
+) {
+    cache.retain(|entity, _| paths.contains(*entity));
+    for (mut follow, mut transform) in &mut followers {
+        let (arc, placement) = if follow.path == Entity::PLACEHOLDER {
+            let Some(demo_path) = demo_path.as_ref() else {
+                continue;
+            };
+            (&demo_path.arc, Transform::IDENTITY)
+        } else {
+            let Ok((node, placement)) = paths.get(follow.path) else {
+                continue;
+            };
+            if node.is_changed() || !cache.contains_key(&follow.path) {
+                cache.insert(follow.path, ArcLengthPath::new(node.bez_path()));
+            }
+            (&cache[&follow.path], *placement)
+        };
+        let length = arc.total_length as f32;
+        if length <= 0.0 {
+            continue;
+        }
+
+        let distance = match follow.progress {
+            PathProgress::Speed => {
+                follow.traveled += follow.speed * time.delta_secs();
+                follow.traveled
+            }
+            PathProgress::Timeline => timeline.as_ref().map_or(0.0, |timeline| {
+                (timeline.current_time / timeline.duration.max(f64::EPSILON)) as f32 * length
+            }),
+        } + follow.offset;
+        let distance = if follow.looping {
+            distance.rem_euclid(length)
+        } else {
+            distance.clamp(0.0, length)
+        };
+        let Some((point, tangent)) = arc.sample(distance as f64) else {
+            continue;
+        };
+
+        // Path-local (x, y) through the path entity's placement, then into the plane.
+        let point = placement.transform_point(point.extend(0.0)).truncate() * follow.scale;
+        let tangent = (placement.rotation * tangent.extend(0.0)).truncate();
+        let angle = tangent.y.atan2(tangent.x);
+        match follow.plane {
+            PathPlane::Xy => {
+                transform.translation.x = point.x;
+                transform.translation.y = point.y;
+                if follow.orient {
+                    transform.rotation = Quat::from_rotation_z(angle);
+                }
+            }
+            PathPlane::Xz => {
+                transform.translation.x = point.x;
+                transform.translation.z = -point.y;
+                if follow.orient {
+                    // Local -Z (forward) along the path.
+                    transform.rotation = Quat::from_rotation_y(angle - std::f32::consts::FRAC_PI_2);
+                }
+            }
+        }
+    }
+}
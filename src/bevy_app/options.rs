@@ -0,0 +1,211 @@
+//! Host-supplied app options (`init_bevy_app_with_options(options_json)`) and the
+//! capability flags the frontend reads back with `get_capabilities`.
+//!
+//! Every field is optional; `{}` gives the same app as `init_bevy_app(0)`. Example:
+//! `{"demo_scene": false, "clear_color": [0.1, 0.1, 0.12], "vsync": "on",
+//! "power_preference": "low_power", "features": ["camera_controller"]}`.
+//!
+//! `backend: "webgl2"` is for browsers without WebGPU. A build renders with exactly one
+//! backend, chosen by the exclusive `webgpu` (default) and `webgl2` cargo features, and
+//! `init_bevy_app_with_options` rejects options asking for the other one. `backend`
+//! defaults to whichever was compiled in. Vello rasterizes with compute shaders, which
+//! WebGL2 lacks, so the Vello renderer is left out on that backend and the 2D overlay,
+//! timeline and FPS text don't draw.
+
+use bevy::prelude::*;
+use bevy::render::RenderPlugin;
+use bevy::render::renderer::RenderAdapterInfo;
+use bevy::render::settings::{
+    Backends, PowerPreference as WgpuPowerPreference, RenderCreation, WgpuSettings,
+    WgpuSettingsPriority,
+};
+use bevy::window::PresentMode;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+
+use crate::WorkerApp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphicsBackend {
+    Webgpu,
+    Webgl2,
+}
+
+impl GraphicsBackend {
+    /// The backend this build was compiled for.
+    pub const fn compiled() -> Self {
+        if cfg!(feature = "webgl2") {
+            GraphicsBackend::Webgl2
+        } else {
+            GraphicsBackend::Webgpu
+        }
+    }
+}
+
+impl Default for GraphicsBackend {
+    fn default() -> Self {
+        Self::compiled()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VsyncMode {
+    #[default]
+    Off,
+    On,
+}
+
+impl VsyncMode {
+    pub fn present_mode(self) -> PresentMode {
+        match self {
+            VsyncMode::Off => PresentMode::AutoNoVsync,
+            VsyncMode::On => PresentMode::AutoVsync,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerPreference {
+    #[default]
+    HighPerformance,
+    LowPower,
+}
+
+impl From<PowerPreference> for WgpuPowerPreference {
+    fn from(preference: PowerPreference) -> Self {
+        match preference {
+            PowerPreference::HighPerformance => WgpuPowerPreference::HighPerformance,
+            PowerPreference::LowPower => WgpuPowerPreference::LowPower,
+        }
+    }
+}
+
+/// Optional plugins; all are on unless `features` lists a subset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppFeature {
+    FpsOverlay,
+    CameraController,
+}
+
+#[derive(Resource, Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AppOptions {
+    pub backend: GraphicsBackend,
+    /// Spawn the default demo (`DemoScenePlugin`): torus, ground and lights.
+    pub demo_scene: bool,
+    pub timeline: bool,
+    /// sRGB background behind the panels.
+    pub clear_color: [f32; 3],
+    pub vsync: VsyncMode,
+    pub power_preference: PowerPreference,
+    pub features: Vec<AppFeature>,
+}
+
+impl Default for AppOptions {
+    fn default() -> Self {
+        Self {
+            backend: GraphicsBackend::default(),
+            demo_scene: true,
+            timeline: true,
+            clear_color: [0.97, 0.97, 0.97],
+            vsync: VsyncMode::default(),
+            power_preference: PowerPreference::default(),
+            features: vec![AppFeature::FpsOverlay, AppFeature::CameraController],
+        }
+    }
+}
+
+impl AppOptions {
+    /// Err if the options ask for a backend this build was not compiled for.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.backend == GraphicsBackend::compiled() {
+            return Ok(());
+        }
+        let feature = match self.backend {
+            GraphicsBackend::Webgpu => "webgpu",
+            GraphicsBackend::Webgl2 => "webgl2",
+        };
+        Err(format!(
+            "backend \"{feature}\" requested but this build lacks the `{feature}` feature"
+        ))
+    }
+
+    /// Backend actually used: always the compiled one. Differs from `backend` only for
+    /// options that skipped `validate` (e.g. from `IronfellAppBuilder::options`).
+    pub fn backend(&self) -> GraphicsBackend {
+        GraphicsBackend::compiled()
+    }
+
+    pub fn has_feature(&self, feature: AppFeature) -> bool {
+        self.features.contains(&feature)
+    }
+
+    pub fn vello_enabled(&self) -> bool {
+        self.backend() == GraphicsBackend::Webgpu
+    }
+
+    pub(crate) fn render_plugin(&self) -> RenderPlugin {
+        let mut settings = match self.backend() {
+            GraphicsBackend::Webgpu => WgpuSettings::default(),
+            GraphicsBackend::Webgl2 => WgpuSettings {
+                backends: Some(Backends::GL),
+                priority: WgpuSettingsPriority::WebGL2,
+                ..default()
+            },
+        };
+        settings.power_preference = self.power_preference.into();
+        RenderPlugin {
+            render_creation: RenderCreation::Automatic(settings),
+            ..default()
+        }
+    }
+}
+
+/// What this app instance was built with; see `get_capabilities`.
+#[derive(Resource, Debug, Clone)]
+pub struct Capabilities {
+    pub requested_backend: GraphicsBackend,
+    pub backend: GraphicsBackend,
+    pub vello: bool,
+}
+
+impl Capabilities {
+    pub fn new(options: &AppOptions) -> Self {
+        if let Err(e) = options.validate() {
+            warn!("{e}; using {:?}", options.backend());
+        }
+        Self {
+            requested_backend: options.backend,
+            backend: options.backend(),
+            vello: options.vello_enabled(),
+        }
+    }
+}
+
+/// JSON: `{ requested_backend, backend, webgl2_supported, vello, compute, adapter_backend }`.
+/// `adapter_backend` is wgpu's backend name once the renderer is up, else null.
+#[wasm_bindgen]
+pub fn get_capabilities(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world();
+    let Some(capabilities) = world.get_resource::<Capabilities>() else {
+        return "{}".to_string();
+    };
+    let adapter_backend = world
+        .get_resource::<RenderAdapterInfo>()
+        .map(|info| format!("{:?}", info.backend));
+    json!({
+        "requested_backend": capabilities.requested_backend,
+        "backend": capabilities.backend,
+        "webgl2_supported": cfg!(feature = "webgl2"),
+        "vello": capabilities.vello,
+        "compute": capabilities.backend == GraphicsBackend::Webgpu,
+        "adapter_backend": adapter_backend,
+    })
+    .to_string()
+}
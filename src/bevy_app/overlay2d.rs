@@ -2,11 +2,15 @@ use bevy::input::mouse::MouseButtonInput; // added for button event reader
 use bevy::prelude::*;
 use bevy::render::view::RenderLayers;
 use bevy_vello::prelude::*;
-// Bring kurbo trait methods into scope for PathSeg operations (arclen, inv_arclen, etc.)
-use bevy_vello::prelude::kurbo::{ParamCurve, ParamCurveArclen};
 use bevy_vello::prelude::VelloScreenSpace;
 
-use crate::panels::{Panels, VIEWER_PANEL, overlay_affine, overlay_world_from_screen};
+use crate::bevy_app::CoordinateMapper;
+use crate::bevy_app::encode_cache::{ContentHasher, EncodeKey, OverlayClock, ShapeFragment};
+use crate::bevy_app::guides::Guides;
+use crate::bevy_app::motion_path::ArcLengthPath;
+use crate::bevy_app::overlay_damage::OverlayDamage;
+use crate::bevy_app::theme::Theme;
+use crate::panels::{Panels, VIEWER_PANEL, overlay_affine};
 
 // -------------------------------------------------------------------------------------------------
 // Overlay 2D content, drawn in "overlay world" coordinates (viewer panel center origin,
@@ -38,6 +42,11 @@ impl Default for DraggableSquare {
     }
 }
 
+/// Marker shared by every scene that belongs to the viewer's 2D overlay document
+/// (as opposed to timeline / UI panel scenes), e.g. for off-screen export.
+#[derive(Component)]
+pub(crate) struct OverlayLayer;
+
 #[derive(Component)]
 pub(crate) struct DraggableOverlayScene; // Separate Vello scene so it isn't affected by the animated transform
 
@@ -49,9 +58,7 @@ pub(crate) struct AnimatedBezierStrokeScene; // Marker for animated bezier strok
 
 #[derive(Resource)]
 pub(crate) struct AnimatedBezierPath {
-    pub path: kurbo::BezPath,
-    pub seg_lengths: Vec<f64>,
-    pub total_length: f64,
+    pub arc: ArcLengthPath,
     pub stroke_width: f32,
 }
 
@@ -81,20 +88,10 @@ impl AnimatedBezierPath {
             );
             x += w;
         }
-        // Precompute lengths
-        let mut seg_lengths = Vec::new();
-        let mut total = 0.0;
-        for seg in path.segments() {
-            let len = seg.arclen(0.5); // moderate accuracy
-            seg_lengths.push(len);
-            total += len;
-        }
-
-        info!("number of segments: {}", seg_lengths.len());
+        let arc = ArcLengthPath::new(path);
+        info!("number of segments: {}", arc.seg_lengths.len());
         Self {
-            path,
-            seg_lengths,
-            total_length: total,
+            arc,
             stroke_width: 25.0,
         }
     }
@@ -105,6 +102,9 @@ pub(crate) struct SimpleMouseState {
     pub left_pressed: bool,
     pub just_pressed: bool,
     pub just_released: bool,
+    /// Set when an overlay tool (e.g. guide dragging) claimed the current press;
+    /// shape selection/drag ignores captured presses. Cleared on the next press.
+    pub captured: bool,
 }
 
 
@@ -115,13 +115,45 @@ pub(crate) struct SimpleMouseState {
 
 #[derive(Component)]
 pub(crate) struct MiniSquare {
-    size: f32,
+    pub size: f32,
     base_color: [f32; 3], // precomputed linear components
 }
 
+impl MiniSquare {
+    /// Half extents in overlay-world units after the entity's (x, y) scale.
+    pub fn half_extents(&self, tr: &Transform) -> Vec2 {
+        tr.scale.truncate() * self.size * 0.5
+    }
+
+    /// Corners in overlay-world space (honours translation, z rotation and scale).
+    pub fn corners(&self, tr: &Transform) -> [Vec2; 4] {
+        let center = tr.translation.truncate();
+        let rot = Vec2::from_angle(z_angle(tr));
+        let h = self.half_extents(tr);
+        [
+            Vec2::new(-h.x, -h.y),
+            Vec2::new(h.x, -h.y),
+            Vec2::new(h.x, h.y),
+            Vec2::new(-h.x, h.y),
+        ]
+        .map(|c| center + rot.rotate(c))
+    }
+
+    pub fn contains(&self, tr: &Transform, p: Vec2) -> bool {
+        let local = Vec2::from_angle(-z_angle(tr)).rotate(p - tr.translation.truncate());
+        let h = self.half_extents(tr).abs();
+        local.x.abs() <= h.x && local.y.abs() <= h.y
+    }
+}
+
+/// Rotation about the overlay's z axis (radians, counter-clockwise in y-up space).
+pub(crate) fn z_angle(tr: &Transform) -> f32 {
+    tr.rotation.to_euler(EulerRot::ZYX).0
+}
+
 #[derive(Component)]
 pub(crate) struct MiniSquareState {
-    selected: bool,
+    pub selected: bool,
     hovered: bool,
     dragging: bool,
     drag_offset: Vec2,
@@ -159,10 +191,10 @@ pub(crate) struct SelectionMarqueeScene;
 // Notes:
 // We leverage Bevy's built-in Input<MouseButton> resource as a lightweight "state machine" for
 // mouse buttons normally, but in this environment (custom event injection without winit) we
-// instead maintain a minimal `SimpleMouseState` from `MouseButtonInput` events. This reproduces the
-// tracking approach used in `tracking_circle.rs`, reacting only to the latest cursor position.
+// instead maintain a minimal `SimpleMouseState` from `MouseButtonInput` events, reacting only to
+// the latest cursor position.
 // Cursor position comes from CursorMoved events and is converted to overlay world space via the
-// overlay camera (similar to the tracking circle implementation). This pattern is typical in Bevy
+// overlay camera. This pattern is typical in Bevy
 // apps: input state is queried each frame rather than building an explicit FSM, unless more complex
 // gesture / multi-button / modal behavior is required.
 // -------------------------------------------------------------------------------------------------
@@ -179,6 +211,7 @@ pub(crate) fn simple_mouse_state_system(
             if ev.state.is_pressed() {
                 if !mouse.left_pressed {
                     mouse.just_pressed = true;
+                    mouse.captured = false;
                 }
                 mouse.left_pressed = true;
             } else {
@@ -206,6 +239,8 @@ pub(crate) fn setup_2d_overlay(
         AnimatedOverlayScene,
         VelloScreenSpace,
         RenderLayers::layer(1),
+        OverlayLayer,
+        EncodeKey::default(),
     ));
 
     // Static scene for draggable square (unaffected by animated transform changes)
@@ -214,6 +249,8 @@ pub(crate) fn setup_2d_overlay(
         DraggableOverlayScene,
         VelloScreenSpace,
         RenderLayers::layer(1),
+        OverlayLayer,
+        EncodeKey::default(),
     ));
 
     // Animated bezier stroke scene
@@ -225,6 +262,7 @@ pub(crate) fn setup_2d_overlay(
         AnimatedBezierStrokeScene,
         VelloScreenSpace,
         RenderLayers::layer(1),
+        OverlayLayer,
     ));
 
     // SPAWN many mini square entities (NO per-entity VelloScene now)
@@ -250,6 +288,7 @@ pub(crate) fn setup_2d_overlay(
                 final_color: [r, g, b, 1.0],
                 ..Default::default()
             },
+            ShapeFragment::default(),
         ));
     }
 
@@ -259,6 +298,8 @@ pub(crate) fn setup_2d_overlay(
         MiniSquaresScene,
         VelloScreenSpace,
         RenderLayers::layer(1),
+        OverlayLayer,
+        OverlayDamage::default(),
     ));
     commands.insert_resource(MiniSquaresDirty(true));
 
@@ -269,11 +310,14 @@ pub(crate) fn setup_2d_overlay(
         SelectionMarqueeScene,
         VelloScreenSpace,
         RenderLayers::layer(1),
+        OverlayLayer,
     ));
 }
 
+type AnimatedSceneFilter = (With<AnimatedOverlayScene>, Without<AnimatedBezierStrokeScene>);
+
 pub(crate) fn animate_2d_overlay(
-    mut query_scene: Query<&mut VelloScene, (With<AnimatedOverlayScene>, Without<AnimatedBezierStrokeScene>)>,
+    mut query_scene: Query<(&mut VelloScene, &mut EncodeKey), AnimatedSceneFilter>,
     mut bezier_scene: Query<
         &mut VelloScene,
         (
@@ -282,16 +326,28 @@ pub(crate) fn animate_2d_overlay(
         ),
     >,
     bezier: Option<Res<AnimatedBezierPath>>,
-    time: Res<Time>,
+    clock: Res<OverlayClock>,
     panels: Res<Panels>,
 ) {
-    let Ok(mut scene) = query_scene.single_mut() else {
+    let Ok((mut scene, mut key)) = query_scene.single_mut() else {
         return;
     }; // not ready yet
-    let sin_time = time.elapsed_secs().sin().mul_add(0.5, 0.5);
+
+    // Both scenes are a pure function of the panel rect and the animation phase; skip
+    // re-encoding entirely while neither changes (paused animation, idle input frames).
+    let rect = panels.rect(VIEWER_PANEL);
+    let content_key = ContentHasher::default()
+        .rect(rect)
+        .f32(clock.elapsed)
+        .finish();
+    if !key.update(content_key) {
+        return;
+    }
+
+    let sin_time = clock.elapsed.sin().mul_add(0.5, 0.5);
     scene.reset();
 
-    let Some(rect) = panels.rect(VIEWER_PANEL) else {
+    let Some(rect) = rect else {
         if let Ok(mut scene_stroke) = bezier_scene.single_mut() {
             scene_stroke.reset();
         }
@@ -329,66 +385,25 @@ pub(crate) fn animate_2d_overlay(
     // Animate progressive bezier stroke reveal
     if let (Ok(mut scene_stroke), Some(bezier)) = (bezier_scene.single_mut(), bezier) {
         scene_stroke.reset();
-        let progress = (time.elapsed_secs() / 6.0).fract().clamp(0.0, 1.0);
-        let target_len = bezier.total_length * (progress as f64);
+        let progress = (clock.elapsed / 6.0).fract().clamp(0.0, 1.0);
+        let target_len = bezier.arc.total_length * (progress as f64);
         if target_len <= 0.0 {
             return;
         }
         scene_stroke.push_layer(peniko::Mix::Clip, 1.0, kurbo::Affine::IDENTITY, &clip);
-        if (target_len - bezier.total_length).abs() < f64::EPSILON {
+        if (target_len - bezier.arc.total_length).abs() < f64::EPSILON {
             let stroke_style = kurbo::Stroke::new(bezier.stroke_width as f64);
             scene_stroke.stroke(
                 &stroke_style,
                 base,
                 peniko::Color::new([0.0, 0.6, 1.0, 1.0]),
                 None,
-                &bezier.path,
+                &bezier.arc.path,
             );
             scene_stroke.pop_layer();
             return;
         }
-        let mut partial = kurbo::BezPath::new();
-        let mut remaining = target_len;
-        let mut idx = 0usize;
-        let mut segs = bezier.path.segments();
-        if let Some(first) = segs.next() {
-            let first_start = match first {
-                // extract start point manually
-                kurbo::PathSeg::Line(l) => l.p0,
-                kurbo::PathSeg::Quad(q) => q.p0,
-                kurbo::PathSeg::Cubic(c) => c.p0,
-            };
-            partial.move_to(first_start);
-            let take_seg = |seg: kurbo::PathSeg,
-                            partial: &mut kurbo::BezPath,
-                            remaining: &mut f64,
-                            idx: usize| {
-                let seg_len = bezier.seg_lengths[idx];
-                if *remaining >= seg_len {
-                    partial.push(seg.as_path_el());
-                    *remaining -= seg_len;
-                    true
-                } else {
-                    let t = seg.inv_arclen(*remaining, 0.5);
-                    let sub = seg.subsegment(0.0..t);
-                    partial.push(sub.as_path_el());
-                    *remaining = 0.0;
-                    false
-                }
-            };
-            take_seg(first, &mut partial, &mut remaining, idx);
-            idx += 1;
-            for seg in segs {
-                if remaining <= 0.0 {
-                    break;
-                }
-                let cont = take_seg(seg, &mut partial, &mut remaining, idx);
-                idx += 1;
-                if !cont {
-                    break;
-                }
-            }
-        }
+        let partial = bezier.arc.prefix(target_len);
         let stroke_style = kurbo::Stroke::new(bezier.stroke_width as f64);
         scene_stroke.stroke(
             &stroke_style,
@@ -424,8 +439,10 @@ pub(crate) fn update_draggable_square_state(
     mut cursor_events: EventReader<CursorMoved>,
     mouse: Res<SimpleMouseState>,
     panels: Res<Panels>,
+    mapper: Res<CoordinateMapper>,
+    guides: Res<Guides>,
 ) {
-    // Follow the pattern in tracking_circle.rs: only act if we have cursor movement events this frame.
+    // Only act if we have cursor movement events this frame.
     if cursor_events.is_empty() {
         // Still need to handle drag end even without movement.
         if state.dragging && !mouse.left_pressed {
@@ -440,7 +457,7 @@ pub(crate) fn update_draggable_square_state(
     let Some(rect) = panels.rect(VIEWER_PANEL) else {
         return;
     };
-    let world_pos = overlay_world_from_screen(rect, last_pos);
+    let world_pos = mapper.overlay_world(rect, last_pos);
 
     // Hover test (AABB of the square)
     let half = state.size * 0.5;
@@ -450,7 +467,7 @@ pub(crate) fn update_draggable_square_state(
                  && (world_pos.y <= state.position.y + half.y);
 
     // Drag start
-    if !state.dragging && state.hovered && mouse.left_pressed {
+    if !state.dragging && state.hovered && mouse.left_pressed && !mouse.captured {
         state.dragging = true;
         state.drag_offset = world_pos - state.position;
     }
@@ -462,21 +479,30 @@ pub(crate) fn update_draggable_square_state(
 
     // Drag move
     if state.dragging && mouse.left_pressed {
-        state.position = world_pos - state.drag_offset;
+        let position = world_pos - state.drag_offset;
+        state.position = position + guides.snap_offset(position);
     }
 }
 
 // -------------------------------------------------------------------------------------------------
 // Multi-square update system
 // -------------------------------------------------------------------------------------------------
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn update_mini_square_entities(
     mut q_squares: Query<(&mut Transform, &MiniSquare, &mut MiniSquareState)>,
     mut marquee_res: ResMut<SelectionMarquee>,
     mut cursor_events: EventReader<CursorMoved>,
     mouse: Res<SimpleMouseState>,
     panels: Res<Panels>,
+    mapper: Res<CoordinateMapper>,
+    guides: Res<Guides>,
     mut dirty: ResMut<MiniSquaresDirty>,
 ) {
+    // A press claimed by another overlay tool (guide drag) is invisible to the squares.
+    if mouse.captured && (mouse.left_pressed || mouse.just_released) {
+        cursor_events.clear();
+        return;
+    }
     if cursor_events.is_empty() && !mouse.just_pressed && !mouse.just_released {
         if mouse.just_released {
             for (_, _, mut st) in q_squares.iter_mut() {
@@ -494,7 +520,7 @@ pub(crate) fn update_mini_square_entities(
     let mut world_pos_opt = None;
     if let Some(screen) = cursor_events.read().last().map(|e| e.position) {
         if let Some(rect) = panels.rect(VIEWER_PANEL) {
-            world_pos_opt = Some(overlay_world_from_screen(rect, screen));
+            world_pos_opt = Some(mapper.overlay_world(rect, screen));
         }
     }
     let Some(world_pos) = world_pos_opt else { return; };
@@ -503,12 +529,7 @@ pub(crate) fn update_mini_square_entities(
     let mut any_hovered = false;
     let mut any_hovered_selected = false;
     for (tr, ms, mut st) in q_squares.iter_mut() {
-        let center = tr.translation.truncate();
-        let half = ms.size * 0.5;
-        let new_hovered = world_pos.x >= center.x - half
-            && world_pos.x <= center.x + half
-            && world_pos.y >= center.y - half
-            && world_pos.y <= center.y + half;
+        let new_hovered = ms.contains(&tr, world_pos);
         if new_hovered != st.hovered {
             st.hovered = new_hovered;
             dirty.0 = true;
@@ -568,10 +589,9 @@ pub(crate) fn update_mini_square_entities(
             let min = start.min(world_pos);
             let max = start.max(world_pos);
             for (tr, ms, mut st) in q_squares.iter_mut() {
-                let center = tr.translation.truncate();
-                let half = ms.size * 0.5;
-                let a_min = center - Vec2::splat(half);
-                let a_max = center + Vec2::splat(half);
+                let corners = ms.corners(&tr);
+                let a_min = corners.iter().fold(Vec2::INFINITY, |m, c| m.min(*c));
+                let a_max = corners.iter().fold(Vec2::NEG_INFINITY, |m, c| m.max(*c));
                 let intersects = !(a_max.x < min.x || a_min.x > max.x || a_max.y < min.y || a_min.y > max.y);
                 if intersects != st.selected {
                     st.selected = intersects;
@@ -583,11 +603,18 @@ pub(crate) fn update_mini_square_entities(
 
     // Drag move
     if mouse.left_pressed {
+        // Snap the group by the offset that puts the first dragged square on a guide,
+        // so relative layout inside the selection is preserved.
+        let snap = q_squares
+            .iter()
+            .find(|(_, _, st)| st.dragging)
+            .map(|(_, _, st)| guides.snap_offset(world_pos + st.drag_offset))
+            .unwrap_or_default();
         let mut moved_any = false;
         for (mut tr, _, mut st) in q_squares.iter_mut() {
             if st.dragging {
-                let new_x = world_pos.x + st.drag_offset.x;
-                let new_y = world_pos.y + st.drag_offset.y;
+                let new_x = world_pos.x + st.drag_offset.x + snap.x;
+                let new_y = world_pos.y + st.drag_offset.y + snap.y;
                 if tr.translation.x != new_x || tr.translation.y != new_y {
                     tr.translation.x = new_x;
                     tr.translation.y = new_y;
@@ -638,13 +665,24 @@ pub(crate) fn update_mini_square_entities(
 // -------------------------------------------------------------------------------------------------
 
 pub(crate) fn render_draggable_square(
-    mut scenes: Query<&mut VelloScene, With<DraggableOverlayScene>>,
+    mut scenes: Query<(&mut VelloScene, &mut EncodeKey), With<DraggableOverlayScene>>,
     state: Res<DraggableSquare>,
     panels: Res<Panels>,
 ) {
-    let Ok(mut scene) = scenes.single_mut() else { return; };
+    let Ok((mut scene, mut key)) = scenes.single_mut() else { return; };
+    let panel_rect = panels.rect(VIEWER_PANEL);
+    let content_key = ContentHasher::default()
+        .rect(panel_rect)
+        .vec2(state.position)
+        .vec2(state.size)
+        .bool(state.dragging)
+        .bool(state.hovered)
+        .finish();
+    if !key.update(content_key) {
+        return;
+    }
     scene.reset();
-    let Some(panel_rect) = panels.rect(VIEWER_PANEL) else { return; };
+    let Some(panel_rect) = panel_rect else { return; };
     let base = overlay_affine(panel_rect);
 
     // Choose color based on state
@@ -676,42 +714,64 @@ pub(crate) fn render_draggable_square(
 
 pub(crate) fn render_mini_squares(
     mut dirty: ResMut<MiniSquaresDirty>,
-    mut q_scene: Query<&mut VelloScene, With<MiniSquaresScene>>,
-    q_squares: Query<(&Transform, &MiniSquare, &MiniSquareState)>,
+    mut q_scene: Query<(&mut VelloScene, &mut OverlayDamage), With<MiniSquaresScene>>,
+    mut q_squares: Query<(&Transform, &MiniSquare, &MiniSquareState, &mut ShapeFragment)>,
+    moved: Query<(), (With<MiniSquare>, Changed<Transform>)>,
     panels: Res<Panels>,
 ) {
-    // Panel layout changes move the whole batch, so they dirty the scene too.
-    if panels.is_changed() {
+    // Panel layout changes move the whole batch, so they dirty the scene too; so do
+    // transforms edited outside the drag path (handles, undo, inspector).
+    if panels.is_changed() || !moved.is_empty() {
         dirty.0 = true;
     }
     if !dirty.0 {
         return;
     }
-    let Ok(mut scene) = q_scene.single_mut() else { return; };
+    let Ok((mut scene, mut damage)) = q_scene.single_mut() else { return; };
     scene.reset();
     let Some(panel_rect) = panels.rect(VIEWER_PANEL) else { return; };
     let base = overlay_affine(panel_rect);
+    if panels.is_changed() {
+        damage.add(panel_rect.to_kurbo());
+    }
 
     // Canonical unit rect
     const UNIT_RECT: kurbo::Rect = kurbo::Rect::new(0.0, 0.0, 1.0, 1.0);
 
     scene.push_layer(peniko::Mix::Clip, 1.0, kurbo::Affine::IDENTITY, &panel_rect.to_kurbo());
-    for (tr, sq, st) in q_squares.iter() {
+    for (tr, sq, st, mut fragment) in q_squares.iter_mut() {
+        // Fragments live in overlay-world space (panel affine applied on append), so
+        // only shapes whose own geometry/color changed are re-encoded here.
         let center = tr.translation.truncate();
-        let half = sq.size * 0.5;
-        let affine = base
-            * kurbo::Affine::scale(sq.size as f64).then_translate((
-                (center.x - half) as f64,
-                (center.y - half) as f64,
-            ).into());
-
-        scene.fill(
-            peniko::Fill::NonZero,
-            affine,
-            peniko::Color::new(st.final_color),
-            None,
-            &UNIT_RECT,
-        );
+        let angle = z_angle(tr);
+        let content_key = ContentHasher::default()
+            .vec2(center)
+            .f32(angle)
+            .vec2(tr.scale.truncate())
+            .f32(sq.size)
+            .f32(st.final_color[0])
+            .f32(st.final_color[1])
+            .f32(st.final_color[2])
+            .f32(st.final_color[3])
+            .finish();
+        if fragment.key.update(content_key) {
+            let size = tr.scale.truncate() * sq.size;
+            let affine = kurbo::Affine::translate((-0.5, -0.5))
+                .then_scale_non_uniform(size.x as f64, size.y as f64)
+                .then_rotate(angle as f64)
+                .then_translate((center.x as f64, center.y as f64).into());
+            let bounds = affine.transform_rect_bbox(UNIT_RECT);
+            damage.add(base.transform_rect_bbox(fragment.rebound(bounds)));
+            fragment.scene.reset();
+            fragment.scene.fill(
+                peniko::Fill::NonZero,
+                affine,
+                peniko::Color::new(st.final_color),
+                None,
+                &UNIT_RECT,
+            );
+        }
+        scene.append(&fragment.scene, Some(base));
     }
     scene.pop_layer();
 
@@ -722,8 +782,9 @@ pub(crate) fn render_selection_marquee(
     marquee_res: Res<SelectionMarquee>,
     mut q_scene: Query<&mut VelloScene, With<SelectionMarqueeScene>>,
     panels: Res<Panels>,
+    theme: Res<Theme>,
 ) {
-    if marquee_res.is_changed() || panels.is_changed() {
+    if marquee_res.is_changed() || panels.is_changed() || theme.is_changed() {
         if let Ok(mut scene) = q_scene.single_mut() {
             scene.reset();
             let Some(panel_rect) = panels.rect(VIEWER_PANEL) else { return; };
@@ -736,7 +797,7 @@ pub(crate) fn render_selection_marquee(
                 scene.fill(
                     peniko::Fill::NonZero,
                     base,
-                    peniko::Color::new([0.1, 0.4, 1.0, 0.15]),
+                    Theme::faded(theme.selection, 0.15),
                     None,
                     &rect,
                 );
@@ -744,7 +805,7 @@ pub(crate) fn render_selection_marquee(
                 scene.stroke(
                     &stroke,
                     base,
-                    peniko::Color::new([0.1, 0.4, 1.0, 0.9]),
+                    Theme::faded(theme.selection, 0.9),
                     None,
                     &rect,
                 );
@@ -0,0 +1,211 @@
+//! Damage tracking and visualization for the batched overlay scenes (vector nodes,
+//! mini squares).
+//!
+//! Each re-encoded shape fragment reports its old and new screen bounds into its
+//! scene's `OverlayDamage`, so a frame's damage is the union of the shapes that
+//! changed (the whole panel on layout changes and removals).
+//!
+//! Scope: this module tracks and visualizes damage only; the overlay is still redrawn
+//! and presented in full every rendered frame. Scissored partial redraw is not
+//! supported by any backend here. bevy_vello rasterizes every scene into its whole
+//! target texture in a render-world system whose target and scene affines are private
+//! to the crate, so there is no region to restrict it to, and a WebGPU canvas has no
+//! partial present or preserved back buffer. Partial redraw needs render-target
+//! control upstream in bevy_vello; until then the numbers here show what it would save,
+//! and `get_overlay_damage` reports `partial_redraw: false`.
+//!
+//! `get_overlay_damage(ptr)` reports the latest damage; `set_overlay_damage_debug(ptr,
+//! true)` flashes each frame's damage rect over the overlay.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy_vello::prelude::*;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::encode_cache::{ContentHasher, EncodeKey};
+use crate::bevy_app::render_timing::VelloEncodeSet;
+use crate::bevy_app::theme::Theme;
+use crate::panels::{Panels, VIEWER_PANEL};
+use crate::{ActivityControl, WorkerApp};
+
+/// How long a damage flash fades, seconds.
+const FLASH_SECS: f64 = 0.4;
+/// Anti-aliasing margin around shape bounds, px.
+const AA_MARGIN: f64 = 1.0;
+
+/// Screen area (window px) a batched overlay scene re-encoded this frame.
+#[derive(Component, Debug, Default)]
+pub(crate) struct OverlayDamage(Option<kurbo::Rect>);
+
+impl OverlayDamage {
+    pub fn add(&mut self, rect: kurbo::Rect) {
+        let rect = rect.inflate(AA_MARGIN, AA_MARGIN);
+        self.0 = Some(self.0.map_or(rect, |damage| damage.union(rect)));
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct DamageDebug {
+    pub enabled: bool,
+    /// Latest non-empty damage across the overlay scenes.
+    last: Option<kurbo::Rect>,
+    /// Damage rects being flashed and when they happened, oldest first.
+    flashes: VecDeque<(kurbo::Rect, f64)>,
+}
+
+#[derive(Component)]
+struct DamageDebugScene;
+
+pub struct OverlayDamagePlugin;
+
+impl Plugin for OverlayDamagePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DamageDebug>()
+            .add_systems(Startup, setup_damage_debug)
+            .add_systems(
+                Update,
+                (
+                    clear_overlay_damage.before(VelloEncodeSet),
+                    (collect_overlay_damage, render_damage_debug)
+                        .chain()
+                        .after(VelloEncodeSet),
+                ),
+            );
+    }
+}
+
+fn setup_damage_debug(mut commands: Commands) {
+    commands.spawn((
+        VelloScene::new(),
+        VelloScreenSpace,
+        RenderLayers::layer(1),
+        DamageDebugScene,
+        EncodeKey::default(),
+    ));
+}
+
+fn clear_overlay_damage(mut damage: Query<&mut OverlayDamage>) {
+    for mut damage in &mut damage {
+        if damage.0.is_some() {
+            damage.0 = None;
+        }
+    }
+}
+
+fn collect_overlay_damage(
+    damage: Query<&OverlayDamage>,
+    mut debug: ResMut<DamageDebug>,
+    time: Res<Time>,
+) {
+    let Some(frame) = damage
+        .iter()
+        .filter_map(|damage| damage.0)
+        .reduce(|a, b| a.union(b))
+    else {
+        return;
+    };
+    debug.last = Some(frame);
+    if debug.enabled {
+        debug.flashes.push_back((frame, time.elapsed_secs_f64()));
+    }
+}
+
+fn render_damage_debug(
+    mut scenes: Query<(&mut VelloScene, &mut EncodeKey), With<DamageDebugScene>>,
+    mut debug: ResMut<DamageDebug>,
+    mut activity: ResMut<ActivityControl>,
+    time: Res<Time>,
+    theme: Res<Theme>,
+) {
+    let Ok((mut scene, mut key)) = scenes.single_mut() else {
+        return;
+    };
+    let now = time.elapsed_secs_f64();
+    let enabled = debug.enabled;
+    while debug
+        .flashes
+        .front()
+        .is_some_and(|(_, at)| !enabled || now - at > FLASH_SECS)
+    {
+        debug.flashes.pop_front();
+    }
+    let mut hasher = ContentHasher::default().u32(theme.revision);
+    for (rect, at) in &debug.flashes {
+        hasher = hasher
+            .f64(rect.x0)
+            .f64(rect.y0)
+            .f64(rect.x1)
+            .f64(rect.y1)
+            .f64(now - at);
+    }
+    if !key.update(hasher.finish()) {
+        return;
+    }
+    scene.reset();
+    for (rect, at) in &debug.flashes {
+        let alpha = (1.0 - (now - at) / FLASH_SECS).clamp(0.0, 1.0) as f32;
+        scene.fill(
+            peniko::Fill::NonZero,
+            kurbo::Affine::IDENTITY,
+            Theme::faded(theme.hover, 0.15 * alpha),
+            None,
+            rect,
+        );
+        scene.stroke(
+            &kurbo::Stroke::new(1.0),
+            kurbo::Affine::IDENTITY,
+            Theme::faded(theme.hover, alpha),
+            None,
+            rect,
+        );
+    }
+    if !debug.flashes.is_empty() {
+        activity.remaining_frames = activity.remaining_frames.max(1);
+    }
+}
+
+/// Flash each frame's overlay damage rect. Returns false before the app is set up.
+#[wasm_bindgen]
+pub fn set_overlay_damage_debug(ptr: u64, enabled: bool) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(mut debug) = app.world_mut().get_resource_mut::<DamageDebug>() else {
+        return false;
+    };
+    debug.enabled = enabled;
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
+
+/// Latest overlay damage as JSON `{ rect: [x, y, w, h], fraction, partial_redraw }`
+/// (window px; `fraction` of the viewer panel's area; `partial_redraw` is whether the
+/// damage limited the redraw, always false for now), or `null` before anything was
+/// drawn.
+#[wasm_bindgen]
+pub fn get_overlay_damage(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world();
+    let Some(rect) = world
+        .get_resource::<DamageDebug>()
+        .and_then(|debug| debug.last)
+    else {
+        return "null".to_string();
+    };
+    let panel = world
+        .get_resource::<Panels>()
+        .and_then(|panels| panels.rect(VIEWER_PANEL))
+        .map(|panel| panel.to_kurbo());
+    let fraction = panel
+        .filter(|panel| panel.area() > 0.0)
+        .map_or(0.0, |panel| rect.intersect(panel).area() / panel.area());
+    serde_json::json!({
+        "rect": [rect.x0, rect.y0, rect.width(), rect.height()],
+        "fraction": fraction,
+        "partial_redraw": false,
+    })
+    .to_string()
+}
@@ -0,0 +1,237 @@
+//! Instanced particle field, as a rendering capability and a streaming stress scene.
+//!
+//! Every particle shares one mesh and one `ParticleMaterial`, so Bevy batches the whole
+//! field into instanced draws. Each particle carries a `MeshTag` (its index); the
+//! material's vertex shader (`particles.wgsl`) derives an orbit and a color from the
+//! tag and animates it from the global time, so the CPU does no per-frame work. With
+//! `cpu_animate` the particles' `Transform`s are moved every frame as well, to load the
+//! inspector's change streaming with thousands of updates.
+//!
+//! `spawn_particles(ptr, params_json)` replaces the current field; `clear_particles`
+//! removes it. The `particles` demo scene spawns a field with the default params.
+
+use bevy::asset::{load_internal_asset, weak_handle};
+use bevy::pbr::{ExtendedMaterial, MaterialExtension, NotShadowCaster};
+use bevy::prelude::*;
+use bevy::render::mesh::MeshTag;
+use bevy::render::render_resource::{AsBindGroup, ShaderRef};
+use bevy::render::view::RenderLayers;
+use rand::Rng;
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{ActivityControl, WorkerApp};
+
+const PARTICLE_SHADER_HANDLE: Handle<Shader> = weak_handle!("5d0c7c55-4b8e-4f55-9d7a-3f1a62d1c8e4");
+
+pub type ParticleMaterial = ExtendedMaterial<StandardMaterial, ParticleMotion>;
+
+/// Motion shared by the whole field, read by the vertex shader.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+pub struct ParticleMotion {
+    /// x: orbit radius around each rest position, y: orbit speed (radians per
+    /// second), z: ratio of the vertical to the horizontal orbit frequency,
+    /// w: phase spread (0 moves all particles in phase, 1 spreads them over a turn).
+    #[uniform(100)]
+    pub motion: Vec4,
+}
+
+impl MaterialExtension for ParticleMotion {
+    fn vertex_shader() -> ShaderRef {
+        PARTICLE_SHADER_HANDLE.into()
+    }
+}
+
+/// Root of a particle field; its `Transform` places the whole field.
+#[derive(Component, Debug)]
+pub struct ParticleField {
+    cpu_animate: bool,
+}
+
+/// A particle's rest position, for `cpu_animate`.
+#[derive(Component, Debug)]
+pub struct Particle {
+    rest: Vec3,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ParticleParams {
+    pub count: u32,
+    /// Radius of the sphere the particles are scattered in.
+    pub radius: f32,
+    /// Particle sphere radius.
+    pub size: f32,
+    pub amplitude: f32,
+    pub speed: f32,
+    pub frequency: f32,
+    pub phase_spread: f32,
+    /// Also move every particle's `Transform` each frame (see module docs).
+    pub cpu_animate: bool,
+}
+
+impl Default for ParticleParams {
+    fn default() -> Self {
+        Self {
+            count: 5000,
+            radius: 6.0,
+            size: 0.05,
+            amplitude: 0.3,
+            speed: 1.0,
+            frequency: 1.3,
+            phase_spread: 1.0,
+            cpu_animate: false,
+        }
+    }
+}
+
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            PARTICLE_SHADER_HANDLE,
+            "particles.wgsl",
+            Shader::from_wgsl
+        );
+        app.add_plugins(MaterialPlugin::<ParticleMaterial>::default())
+            .add_systems(Update, animate_particles);
+    }
+}
+
+/// Despawn any particle field and spawn a new one at the origin. Returns its root.
+pub(crate) fn spawn_particle_field(world: &mut World, params: &ParticleParams) -> Entity {
+    despawn_particle_fields(world);
+
+    // A white vertex color attribute lets the shader tint each instance.
+    let mut mesh = Sphere::new(params.size).mesh().uv(8, 6);
+    let vertex_count = mesh.count_vertices();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![[1.0f32; 4]; vertex_count]);
+    let mesh = world.resource_mut::<Assets<Mesh>>().add(mesh);
+    let material = world
+        .resource_mut::<Assets<ParticleMaterial>>()
+        .add(ParticleMaterial {
+            base: StandardMaterial {
+                perceptual_roughness: 0.6,
+                ..default()
+            },
+            extension: ParticleMotion {
+                motion: Vec4::new(
+                    params.amplitude,
+                    params.speed,
+                    params.frequency,
+                    params.phase_spread,
+                ),
+            },
+        });
+
+    let root = world
+        .spawn((
+            Name::new("Particles"),
+            ParticleField {
+                cpu_animate: params.cpu_animate,
+            },
+            Transform::default(),
+            Visibility::default(),
+        ))
+        .id();
+    let mut rng = rand::thread_rng();
+    let particles: Vec<_> = (0..params.count)
+        .map(|index| {
+            // Uniform in the ball: random direction, radius by cube root.
+            let direction = Vec3::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            )
+            .normalize_or(Vec3::Y);
+            let rest = direction * params.radius * rng.r#gen::<f32>().cbrt();
+            (
+                Particle { rest },
+                Mesh3d(mesh.clone()),
+                MeshMaterial3d(material.clone()),
+                MeshTag(index),
+                Transform::from_translation(rest),
+                NotShadowCaster,
+                RenderLayers::layer(0),
+                ChildOf(root),
+            )
+        })
+        .collect();
+    world.spawn_batch(particles);
+    root
+}
+
+fn despawn_particle_fields(world: &mut World) -> bool {
+    let fields: Vec<Entity> = world
+        .query_filtered::<Entity, With<ParticleField>>()
+        .iter(world)
+        .collect();
+    for field in &fields {
+        world.despawn(*field);
+    }
+    !fields.is_empty()
+}
+
+/// Keep frames coming while a field exists (its animation runs on the GPU clock) and
+/// move the particles on the CPU for fields spawned with `cpu_animate`.
+fn animate_particles(
+    time: Res<Time>,
+    fields: Query<&ParticleField>,
+    mut particles: Query<(&Particle, &ChildOf, &mut Transform)>,
+    mut activity: Option<ResMut<ActivityControl>>,
+) {
+    if fields.is_empty() {
+        return;
+    }
+    if let Some(activity) = activity.as_mut() {
+        activity.remaining_frames = activity.remaining_frames.max(1);
+    }
+    if !fields.iter().any(|field| field.cpu_animate) {
+        return;
+    }
+    let t = time.elapsed_secs();
+    for (particle, child_of, mut transform) in &mut particles {
+        if fields
+            .get(child_of.parent())
+            .is_ok_and(|field| field.cpu_animate)
+        {
+            let phase = particle.rest.x + particle.rest.z;
+            transform.translation = particle.rest + Vec3::Y * (t + phase).sin() * 0.1;
+        }
+    }
+}
+
+/// Replace the particle field with one built from `params_json` (a `ParticleParams`
+/// object, e.g. `{ "count": 20000, "cpu_animate": true }`; omitted fields use the
+/// defaults). Returns the field's root entity, or 0 for invalid JSON.
+#[wasm_bindgen]
+pub fn spawn_particles(ptr: u64, params_json: &str) -> u64 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let params: ParticleParams = match serde_json::from_str(params_json) {
+        Ok(params) => params,
+        Err(e) => {
+            warn!("spawn_particles: {e}");
+            return 0;
+        }
+    };
+    let root = spawn_particle_field(app.world_mut(), &params);
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    root.to_bits()
+}
+
+/// Despawn the particle field. Returns false if there was none.
+#[wasm_bindgen]
+pub fn clear_particles(ptr: u64) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let cleared = despawn_particle_fields(app.world_mut());
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    cleared
+}
@@ -0,0 +1,84 @@
+// Vertex stage for `ParticleMaterial`: the standard mesh vertex shader plus a per-instance
+// orbit and color derived from the instance's `MeshTag`, so a particle field animates
+// without any per-frame CPU work. The fragment stage is the regular PBR one.
+
+#import bevy_pbr::{
+    mesh_functions,
+    mesh_view_bindings::globals,
+    forward_io::{Vertex, VertexOutput},
+    view_transformations::position_world_to_clip,
+}
+
+// x: amplitude, y: speed, z: frequency ratio, w: phase spread (see `ParticleMotion`).
+@group(2) @binding(100) var<uniform> motion: vec4<f32>;
+
+const TAU: f32 = 6.28318530718;
+
+// PCG hash of the tag, mapped to 0..1.
+fn hash(n: u32) -> f32 {
+    let state = n * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return f32((word >> 22u) ^ word) / 4294967295.0;
+}
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+
+    let tag = mesh_functions::get_tag(vertex.instance_index);
+    let seed = hash(tag);
+    let t = globals.time * motion.y + seed * TAU * motion.w;
+    let offset = motion.x * vec3<f32>(
+        sin(t),
+        sin(t * motion.z + 1.7),
+        cos(t * 0.7 + seed * TAU),
+    );
+
+    let world_from_local = mesh_functions::get_world_from_local(vertex.instance_index);
+    out.world_position = mesh_functions::mesh_position_local_to_world(
+        world_from_local,
+        vec4<f32>(vertex.position, 1.0),
+    ) + vec4<f32>(offset, 0.0);
+    out.position = position_world_to_clip(out.world_position.xyz);
+
+#ifdef VERTEX_NORMALS
+    out.world_normal = mesh_functions::mesh_normal_local_to_world(
+        vertex.normal,
+        vertex.instance_index,
+    );
+#endif
+
+#ifdef VERTEX_UVS_A
+    out.uv = vertex.uv;
+#endif
+#ifdef VERTEX_UVS_B
+    out.uv_b = vertex.uv_b;
+#endif
+
+#ifdef VERTEX_TANGENTS
+    out.world_tangent = mesh_functions::mesh_tangent_local_to_world(
+        world_from_local,
+        vertex.tangent,
+        vertex.instance_index,
+    );
+#endif
+
+#ifdef VERTEX_COLORS
+    // Rainbow by tag, multiplied with the base color in the PBR fragment stage.
+    let hue = seed + vec3<f32>(0.0, 0.33, 0.67);
+    out.color = vec4<f32>(0.5 + 0.5 * cos(TAU * hue), 1.0) * vertex.color;
+#endif
+
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    out.instance_index = vertex.instance_index;
+#endif
+
+#ifdef VISIBILITY_RANGE_DITHER
+    out.visibility_range_dither = mesh_functions::get_visibility_range_dither_level(
+        vertex.instance_index,
+        world_from_local[3],
+    );
+#endif
+
+    return out;
+}
@@ -0,0 +1,91 @@
+//! Path morphs: a `VectorNode` blending between two compatible paths.
+//!
+//! `set_path_morph(ptr, entity, target)` records the entity's current anchors and those
+//! of `target`'s node; a timeline track on the `"morph"` property then drives `amount`
+//! (0 = own path, 1 = target path), see `keyframes::TrackProperty`. Paths are compatible
+//! when they have the same anchor count and are both open or both closed; anchors are
+//! matched by index.
+
+use bevy::prelude::*;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::vector::{PathAnchor, VectorNode};
+use crate::entity_id::live_entity_or_warn;
+use crate::{ActivityControl, WorkerApp};
+
+#[derive(Component, Debug, Clone)]
+pub struct PathMorph {
+    pub from: Vec<PathAnchor>,
+    pub to: Vec<PathAnchor>,
+    pub amount: f32,
+}
+
+impl PathMorph {
+    /// Morph from `node`'s path to `target`'s, or `None` if they are not compatible.
+    pub fn between(node: &VectorNode, target: &VectorNode) -> Option<Self> {
+        if node.anchors.len() != target.anchors.len() || node.closed != target.closed {
+            return None;
+        }
+        Some(Self {
+            from: node.anchors.clone(),
+            to: target.anchors.clone(),
+            amount: 0.0,
+        })
+    }
+
+    /// Anchors at `amount` (not clamped, so overshooting easings overshoot the shape).
+    pub fn anchors(&self) -> Vec<PathAnchor> {
+        let t = self.amount;
+        self.from
+            .iter()
+            .zip(&self.to)
+            .map(|(a, b)| PathAnchor {
+                position: a.position.lerp(b.position, t),
+                handle_in: a.handle_in.lerp(b.handle_in, t),
+                handle_out: a.handle_out.lerp(b.handle_out, t),
+            })
+            .collect()
+    }
+}
+
+/// Make `entity_id`'s path morphable into `target_id`'s path; 0 removes the morph and
+/// restores the entity's own path. Returns false if either entity has no `VectorNode`
+/// or the paths are not compatible.
+#[wasm_bindgen]
+pub fn set_path_morph(ptr: u64, entity_id: u64, target_id: u64) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(entity) = live_entity_or_warn(app.world(), entity_id, "set_path_morph") else {
+        return false;
+    };
+    let world = app.world_mut();
+    // Start from the entity's own path, not a blend left by an earlier morph.
+    let previous = world.entity_mut(entity).take::<PathMorph>();
+    if let Some(previous) = &previous
+        && let Some(mut node) = world.get_mut::<VectorNode>(entity)
+    {
+        node.anchors = previous.from.clone();
+    }
+    if target_id == 0 {
+        if previous.is_none() {
+            return false;
+        }
+    } else {
+        let Some(target) = live_entity_or_warn(world, target_id, "set_path_morph") else {
+            return false;
+        };
+        let morph = match (world.get::<VectorNode>(entity), world.get::<VectorNode>(target)) {
+            (Some(node), Some(target)) => PathMorph::between(node, target),
+            _ => None,
+        };
+        let Some(morph) = morph else {
+            warn!("set_path_morph: {entity} and {target} are not compatible paths");
+            return false;
+        };
+        world.entity_mut(entity).insert(morph);
+    }
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
@@ -0,0 +1,254 @@
+//! Pen tool: draw new `VectorNode` paths in the viewer overlay.
+//!
+//! While `Tool::Pen` is active, a click adds a corner anchor and a click-drag pulls out
+//! symmetric control handles (smooth anchor). Clicking the first anchor closes the
+//! path; Enter or a double-click finishes it open; Escape discards it. The finished
+//! path is spawned as a `VectorNode` entity and becomes the selection. Switching to
+//! another tool finishes a pending path.
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy_vello::prelude::*;
+
+use crate::bevy_app::encode_cache::{ContentHasher, EncodeKey, ShapeFragment};
+use crate::bevy_app::overlay2d::SimpleMouseState;
+use crate::bevy_app::persistent_id::PersistentId;
+use crate::bevy_app::theme::Theme;
+use crate::bevy_app::tools::{Tool, ToolState};
+use crate::bevy_app::vector::{PathAnchor, VectorNode, bez_path};
+use crate::panels::{Panels, VIEWER_PANEL, overlay_affine};
+use crate::{PointerState, SelectionState};
+
+/// Pointer distance (px) to the first anchor that closes the path.
+const CLOSE_DISTANCE: f32 = 8.0;
+/// Drag distance (px) before a press turns into a smooth anchor.
+const SMOOTH_DRAG_THRESHOLD: f32 = 2.0;
+const DOUBLE_CLICK_SECS: f32 = 0.35;
+const DOUBLE_CLICK_DISTANCE: f32 = 4.0;
+const ANCHOR_SIZE: f64 = 6.0;
+
+/// Path under construction (anchors in overlay-world space).
+#[derive(Resource, Debug, Default)]
+pub(crate) struct PenState {
+    anchors: Vec<PathAnchor>,
+    /// The last anchor's handles follow the pointer while the button is held.
+    dragging: bool,
+    last_click: Option<(f32, Vec2)>,
+}
+
+#[derive(Component)]
+pub(crate) struct PenPreviewScene;
+
+pub(crate) fn setup_pen_tool(mut commands: Commands) {
+    // Editor chrome: not an `OverlayLayer`, so exports skip the preview.
+    commands.spawn((
+        VelloScene::new(),
+        VelloScreenSpace,
+        RenderLayers::layer(1),
+        PenPreviewScene,
+        EncodeKey::default(),
+    ));
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn pen_tool_system(
+    mut commands: Commands,
+    mut pen: ResMut<PenState>,
+    mut mouse: ResMut<SimpleMouseState>,
+    mut selection: ResMut<SelectionState>,
+    tool: Res<ToolState>,
+    pointer: Res<PointerState>,
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+) {
+    if tool.active != Tool::Pen {
+        if !pen.anchors.is_empty() {
+            finish_path(&mut commands, &mut pen, &mut selection, false);
+        }
+        return;
+    }
+    if keys.just_pressed(KeyCode::Escape) {
+        *pen = PenState::default();
+        return;
+    }
+    if keys.just_pressed(KeyCode::Enter) {
+        finish_path(&mut commands, &mut pen, &mut selection, false);
+        return;
+    }
+    let Some(p) = pointer.overlay_world else {
+        return;
+    };
+
+    if mouse.just_pressed && !mouse.captured {
+        mouse.captured = true;
+        let now = time.elapsed_secs();
+        let double_click = pen.last_click.is_some_and(|(t, at)| {
+            now - t <= DOUBLE_CLICK_SECS && at.distance(p) <= DOUBLE_CLICK_DISTANCE
+        });
+        pen.last_click = Some((now, p));
+        let closes = pen.anchors.len() > 2 && pen.anchors[0].position.distance(p) <= CLOSE_DISTANCE;
+        if closes {
+            finish_path(&mut commands, &mut pen, &mut selection, true);
+        } else if double_click {
+            finish_path(&mut commands, &mut pen, &mut selection, false);
+        } else {
+            pen.anchors.push(PathAnchor::corner(p));
+            pen.dragging = true;
+        }
+        return;
+    }
+
+    if pen.dragging {
+        if !mouse.left_pressed {
+            pen.dragging = false;
+        } else if let Some(last) = pen.anchors.last_mut() {
+            let pull = p - last.position;
+            let (handle_in, handle_out) = if pull.length() > SMOOTH_DRAG_THRESHOLD {
+                (-pull, pull)
+            } else {
+                (Vec2::ZERO, Vec2::ZERO)
+            };
+            if last.handle_out != handle_out {
+                last.handle_in = handle_in;
+                last.handle_out = handle_out;
+            }
+        }
+    }
+}
+
+/// Spawn the pending path (if it has at least two anchors) and select it.
+fn finish_path(
+    commands: &mut Commands,
+    pen: &mut PenState,
+    selection: &mut SelectionState,
+    closed: bool,
+) {
+    let anchors = std::mem::take(&mut pen.anchors);
+    pen.dragging = false;
+    pen.last_click = None;
+    if anchors.len() < 2 {
+        return;
+    }
+    // The first anchor becomes the node origin; anchors are stored relative to it.
+    let origin = anchors[0].position;
+    let node = VectorNode {
+        anchors: anchors
+            .into_iter()
+            .map(|a| PathAnchor {
+                position: a.position - origin,
+                ..a
+            })
+            .collect(),
+        closed,
+        ..default()
+    };
+    let entity = commands
+        .spawn((
+            Name::new("Path"),
+            Transform::from_translation(origin.extend(0.0)),
+            GlobalTransform::default(),
+            node,
+            ShapeFragment::default(),
+            PersistentId::new(),
+        ))
+        .id();
+    selection.selected.clear();
+    selection.selected.insert(entity, ());
+    selection.last_primary = Some(entity);
+}
+
+pub(crate) fn render_pen_preview(
+    mut q_scene: Query<(&mut VelloScene, &mut EncodeKey), With<PenPreviewScene>>,
+    pen: Res<PenState>,
+    pointer: Res<PointerState>,
+    tool: Res<ToolState>,
+    panels: Res<Panels>,
+    theme: Res<Theme>,
+) {
+    let Ok((mut scene, mut key)) = q_scene.single_mut() else {
+        return;
+    };
+    let panel_rect = panels.rect(VIEWER_PANEL);
+    let cursor = pointer.overlay_world.filter(|_| tool.active == Tool::Pen);
+    let mut hasher = ContentHasher::default()
+        .rect(panel_rect)
+        .u32(theme.revision)
+        .bool(pen.dragging)
+        .u32(pen.anchors.len() as u32);
+    for a in &pen.anchors {
+        hasher = hasher.vec2(a.position).vec2(a.handle_in).vec2(a.handle_out);
+    }
+    // The rubber band only follows the pointer once a path is started.
+    if let Some(c) = cursor.filter(|_| !pen.anchors.is_empty()) {
+        hasher = hasher.vec2(c);
+    }
+    if !key.update(hasher.finish()) {
+        return;
+    }
+    scene.reset();
+    let Some(panel_rect) = panel_rect else {
+        return;
+    };
+    if pen.anchors.is_empty() {
+        return;
+    }
+    let base = overlay_affine(panel_rect);
+    let accent = peniko::Color::new(theme.selection);
+    let white = peniko::Color::new(theme.gizmo.handle);
+    let thin = kurbo::Stroke::new(1.0);
+
+    scene.push_layer(
+        peniko::Mix::Clip,
+        1.0,
+        kurbo::Affine::IDENTITY,
+        &panel_rect.to_kurbo(),
+    );
+
+    let mut preview = pen.anchors.clone();
+    if let Some(c) = cursor.filter(|_| !pen.dragging) {
+        preview.push(PathAnchor::corner(c));
+    }
+    scene.stroke(
+        &kurbo::Stroke::new(2.0),
+        base,
+        accent,
+        None,
+        &bez_path(&preview, false),
+    );
+
+    let to_screen = |v: Vec2| base * kurbo::Point::new(v.x as f64, v.y as f64);
+    for a in &pen.anchors {
+        let c = to_screen(a.position);
+        for handle in [a.handle_in, a.handle_out] {
+            if handle == Vec2::ZERO {
+                continue;
+            }
+            let h = to_screen(a.position + handle);
+            scene.stroke(
+                &thin,
+                kurbo::Affine::IDENTITY,
+                accent,
+                None,
+                &kurbo::Line::new(c, h),
+            );
+            let dot = kurbo::Circle::new(h, ANCHOR_SIZE * 0.5);
+            scene.fill(
+                peniko::Fill::NonZero,
+                kurbo::Affine::IDENTITY,
+                accent,
+                None,
+                &dot,
+            );
+        }
+        let square = kurbo::Rect::from_center_size(c, (ANCHOR_SIZE, ANCHOR_SIZE));
+        scene.fill(
+            peniko::Fill::NonZero,
+            kurbo::Affine::IDENTITY,
+            white,
+            None,
+            &square,
+        );
+        scene.stroke(&thin, kurbo::Affine::IDENTITY, accent, None, &square);
+    }
+    scene.pop_layer();
+}
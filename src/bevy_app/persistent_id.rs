@@ -0,0 +1,138 @@
+//! Stable entity identity across despawn / respawn.
+//!
+//! `Entity` bits are recycled when a scene is reloaded, so anything that must survive
+//! a reload (selection, saved references) is keyed by `PersistentId` instead. Loaders
+//! respawn entities with their saved id; authorable entities spawned without one get a
+//! fresh id. `reconcile_selection_system` keeps `SelectionState` pointing at live
+//! entities: dead entities are dropped, and when an entity carrying a selected id
+//! reappears it is selected again, which re-emits the corrected set to JS.
+
+use bevy::ecs::entity::Entities;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::SelectionState;
+use crate::bevy_app::scene3d::ActiveState;
+use crate::bevy_app::vector::VectorNode;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PersistentId(pub Uuid);
+
+impl PersistentId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+impl Default for PersistentId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Live entity for every `PersistentId`. Updated once per frame, so an entity
+/// despawned since still maps to its id.
+#[derive(Resource, Debug, Default)]
+pub struct PersistentIndex {
+    by_id: HashMap<Uuid, Entity>,
+    by_entity: HashMap<Entity, Uuid>,
+}
+
+impl PersistentIndex {
+    pub fn entity(&self, id: Uuid) -> Option<Entity> {
+        self.by_id.get(&id).copied()
+    }
+
+    pub fn id(&self, entity: Entity) -> Option<Uuid> {
+        self.by_entity.get(&entity).copied()
+    }
+}
+
+type NeedsPersistentId = (
+    Without<PersistentId>,
+    Or<(With<ActiveState>, With<VectorNode>)>,
+);
+
+/// Give selectable entities spawned without an id a fresh one.
+pub(crate) fn assign_persistent_ids(
+    mut commands: Commands,
+    query: Query<Entity, NeedsPersistentId>,
+) {
+    for entity in &query {
+        commands.entity(entity).insert(PersistentId::new());
+    }
+}
+
+pub(crate) fn update_persistent_index(
+    mut index: ResMut<PersistentIndex>,
+    added: Query<(Entity, &PersistentId), Added<PersistentId>>,
+    mut removed: RemovedComponents<PersistentId>,
+) {
+    for entity in removed.read() {
+        if let Some(id) = index.by_entity.remove(&entity) {
+            index.by_id.remove(&id);
+        }
+    }
+    for (entity, id) in &added {
+        index.by_id.insert(id.0, entity);
+        if let Some(old) = index.by_entity.insert(entity, id.0)
+            && old != id.0
+        {
+            index.by_id.remove(&old);
+        }
+    }
+}
+
+/// Remap selection / hover after despawn + respawn (see module docs).
+pub(crate) fn reconcile_selection_system(
+    mut selection: ResMut<SelectionState>,
+    index: Res<PersistentIndex>,
+    ids: Query<&PersistentId>,
+    added: Query<Entity, Added<PersistentId>>,
+    entities: &Entities,
+    mut last_seen: Local<HashMap<Entity, ()>>,
+) {
+    // A selection change made elsewhere redefines the persistent selection, and so does
+    // a selected entity getting its id after it was selected (e.g. a path the pen tool
+    // just finished).
+    if selection.selected != *last_seen
+        || added.iter().any(|e| selection.selected.contains_key(&e))
+    {
+        let selected_ids = selection
+            .selected
+            .keys()
+            .filter_map(|e| ids.get(*e).ok())
+            .map(|id| (id.0, ()))
+            .collect();
+        selection.bypass_change_detection().selected_ids = selected_ids;
+    }
+
+    let dead = |e: &Entity| !entities.contains(*e);
+    let stale_selected = selection.selected.keys().any(dead);
+    let stale_hovered = selection.hovered.keys().any(dead);
+    let missing: Vec<Entity> = selection
+        .selected_ids
+        .keys()
+        .filter_map(|id| index.entity(*id))
+        .filter(|e| !selection.selected.contains_key(e))
+        .collect();
+
+    if stale_selected || stale_hovered || !missing.is_empty() {
+        let selection = &mut *selection;
+        selection.selected.retain(|e, _| entities.contains(*e));
+        selection.hovered.retain(|e, _| entities.contains(*e));
+        if selection
+            .last_primary
+            .is_some_and(|e| !entities.contains(e))
+        {
+            selection.last_primary = None;
+        }
+        for entity in missing {
+            selection.selected.insert(entity, ());
+            selection.last_primary.get_or_insert(entity);
+        }
+    }
+    *last_seen = selection.selected.clone();
+}
@@ -1,41 +1,10 @@
 use bevy::math::bounding::RayCast3d;
 use bevy::prelude::*;
 
+use crate::bevy_app::CoordinateMapper;
 use crate::bevy_app::overlay2d::DraggableSquare;
 use crate::bevy_app::scene3d::{CurrentVolume, MainCamera3D};
 
-/// Build a world ray from a window-space cursor position (physical px).
-/// The camera renders into a viewport sub-rect, so the position is translated to
-/// viewport-local coordinates first; returns None when the cursor is outside the
-/// viewport (e.g. over the timeline or an HTML panel).
-pub fn camera_ray_from_window_px(
-    camera: &Camera,
-    cam_transform: &GlobalTransform,
-    screen: Vec2,
-) -> Option<Ray3d> {
-    // Reject clicks outside the camera's viewport sub-rect (e.g. over the timeline
-    // or an HTML panel). `screen` and the viewport are both window/canvas-relative
-    // physical px.
-    if let Some(vp) = &camera.viewport {
-        let origin = vp.physical_position.as_vec2();
-        let size = vp.physical_size.as_vec2();
-        let p = screen - origin;
-        if p.x < 0.0 || p.y < 0.0 || p.x > size.x || p.y > size.y {
-            return None;
-        }
-    }
-    // NOTE: `viewport_to_world` expects the position in WINDOW space, not
-    // viewport-local space — it subtracts the viewport offset itself via
-    // `logical_viewport_rect().min`. So pass the full-window `screen`, not
-    // `screen - viewport.origin`; pre-subtracting double-counts the offset and
-    // throws the ray off by the viewport's top-left (the left panel width). The
-    // window scale factor is forced to 1.0, so physical `screen` == logical.
-    camera
-        .viewport_to_world(cam_transform, screen)
-        .ok()
-        .map(Ray3d::from)
-}
-
 // Overlay 2D placeholder: treat draggable square as a hit if pointer over its AABB.
 pub fn pick_overlay_2d_system(
     pointer: Res<crate::PointerState>,
@@ -66,16 +35,20 @@ pub fn pick_overlay_2d_system(
 // 3D picking using AABB intersection along view ray.
 pub fn pick_world_3d_system(
     pointer: Res<crate::PointerState>,
+    mapper: Res<CoordinateMapper>,
     cameras: Query<(&Camera, &GlobalTransform), With<MainCamera3D>>,
     query: Query<(Entity, &CurrentVolume)>,
     mut hits: ResMut<crate::PointerHits>,
 ) {
     hits.world3d.clear();
+    if pointer.excluded {
+        return;
+    }
     let Ok((camera, cam_transform)) = cameras.single() else {
         return;
     };
     // Build ray from pointer screen pos (viewport-aware)
-    let Some(ray) = camera_ray_from_window_px(camera, cam_transform, pointer.screen) else {
+    let Some(ray) = mapper.viewport_ray(camera, cam_transform, pointer.screen) else {
         return;
     };
     let ray_cast = RayCast3d::from_ray(ray, 10_000.0);
@@ -99,3 +72,48 @@ pub fn pick_world_3d_system(
 pub fn resolve_primary_hit_system(mut hits: ResMut<crate::PointerHits>) {
     hits.primary = hits.world3d.first().map(|h| h.entity);
 }
+
+/// One hit from `raycast_world`: distance along the ray and the world-space point.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RayHit {
+    pub entity: Entity,
+    pub distance: f32,
+    pub point: Vec3,
+}
+
+/// Every visible mesh whose bounding box the ray crosses within `max_dist`, nearest
+/// first. Boxes are tested in each mesh's local space (so they rotate with it), the
+/// same box-level precision the pointer picking uses.
+pub(crate) fn raycast_world(world: &mut World, ray: Ray3d, max_dist: f32) -> Vec<RayHit> {
+    let mut query = world.query_filtered::<(
+        Entity,
+        &bevy::render::primitives::Aabb,
+        &GlobalTransform,
+        Option<&InheritedVisibility>,
+    ), (
+        With<Mesh3d>,
+        Without<super::gpu_picking::PickingProxy>,
+        Without<super::depth_probe::DepthProxy>,
+    )>();
+    let mut hits: Vec<RayHit> = query
+        .iter(world)
+        .filter(|(.., visibility)| visibility.is_none_or(|v| v.get()))
+        .filter_map(|(entity, aabb, transform, _)| {
+            let to_local = transform.affine().inverse();
+            let origin = to_local.transform_point3(ray.origin);
+            let direction = Dir3::new(to_local.transform_vector3(*ray.direction)).ok()?;
+            let local_box = bevy::math::bounding::Aabb3d::new(aabb.center, aabb.half_extents);
+            let local_dist =
+                RayCast3d::new(origin, direction, f32::MAX).aabb_intersection_at(&local_box)?;
+            let point = transform.transform_point(origin + *direction * local_dist);
+            let distance = point.distance(ray.origin);
+            (distance <= max_dist).then_some(RayHit {
+                entity,
+                distance,
+                point,
+            })
+        })
+        .collect();
+    hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+    hits
+}
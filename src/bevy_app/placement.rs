@@ -0,0 +1,115 @@
+//! Click-to-place: object templates for the 3D viewer and where the pointer puts them.
+//!
+//! `SpawnObject { kind, position }` spawns a pickable cube, sphere or torus, the same
+//! kind of entity the `shapes_grid` demo is made of. `spawn_at_cursor(ptr, template)`
+//! (in the inspector bridge, beside the other spawn commands) drops one onto the first
+//! surface under the pointer, or onto the ground plane (y = 0) when the ray hits
+//! nothing; overlay shape templates land at the pointer's overlay position instead.
+//! When the host keeps the depth probe on the pointer (`sample_depth_at`), the surface
+//! comes from the rendered depth rather than from bounding boxes.
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy_remote_inspector::{InspectorContext, command::Execute};
+
+use crate::bevy_app::CoordinateMapper;
+use crate::bevy_app::depth_probe::probed_surface_point;
+use crate::bevy_app::picking::raycast_world;
+use crate::bevy_app::scene3d::{ActiveState, MainCamera3D, Shape};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    Cube,
+    Sphere,
+    Torus,
+}
+
+impl ObjectKind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "cube" => Some(Self::Cube),
+            "sphere" => Some(Self::Sphere),
+            "torus" => Some(Self::Torus),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Cube => "Cube",
+            Self::Sphere => "Sphere",
+            Self::Torus => "Torus",
+        }
+    }
+
+    /// Bounding box size, for picking and for resting the object on a surface.
+    pub fn bounds(self) -> Vec3 {
+        match self {
+            Self::Cube | Self::Sphere => Vec3::splat(1.0),
+            Self::Torus => Vec3::new(1.0, 0.5, 1.0),
+        }
+    }
+
+    fn mesh(self) -> Mesh {
+        match self {
+            Self::Cube => Mesh::from(Cuboid::default()),
+            Self::Sphere => Sphere::new(0.5).mesh().ico(3).unwrap(),
+            Self::Torus => Mesh::from(Torus::new(0.25, 0.5)),
+        }
+    }
+}
+
+/// Spawn a 3D object from a template with its center at `position`; returns the
+/// entity bits.
+#[derive(Debug)]
+pub struct SpawnObject {
+    pub kind: ObjectKind,
+    pub position: Vec3,
+}
+
+impl Execute for SpawnObject {
+    type Output = u64;
+
+    fn execute(
+        self,
+        _ctx: &mut InspectorContext,
+        world: &mut World,
+    ) -> anyhow::Result<Self::Output> {
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(self.kind.mesh());
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(Color::srgb(0.6, 0.62, 0.66));
+        let entity = world
+            .spawn((
+                Name::new(self.kind.name()),
+                Mesh3d(mesh),
+                MeshMaterial3d(material),
+                Transform::from_translation(self.position),
+                Shape::Box(Cuboid::from_size(self.kind.bounds())),
+                ActiveState::default(),
+                RenderLayers::layer(0),
+            ))
+            .id();
+        Ok(entity.to_bits())
+    }
+}
+
+/// World point under the pointer: the depth probe's surface if it has settled there,
+/// else the nearest surface the viewer camera's ray hits, else where it meets the
+/// ground plane. None outside the viewer or when the ray points away from the ground.
+pub(crate) fn cursor_surface_point(world: &mut World) -> Option<Vec3> {
+    let screen = world.get_resource::<crate::PointerState>()?.screen;
+    if let Some(point) = probed_surface_point(world, screen) {
+        return Some(point);
+    }
+    let mut cameras = world.query_filtered::<(&Camera, &GlobalTransform), With<MainCamera3D>>();
+    let (camera, transform) = cameras.single(world).ok()?;
+    let ray = world
+        .get_resource::<CoordinateMapper>()?
+        .viewport_ray(camera, transform, screen)?;
+    if let Some(hit) = raycast_world(world, ray, f32::MAX).first() {
+        return Some(hit.point);
+    }
+    let distance = ray.intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Y))?;
+    Some(ray.get_point(distance))
+}
@@ -1,14 +1,21 @@
 use bevy::input::mouse::MouseButtonInput;
 use bevy::prelude::*;
 
-use crate::bevy_app::AccumulatedCursorDelta;
+use crate::bevy_app::input_latency::InputLatency;
+use crate::bevy_app::{AccumulatedCursorDelta, CoordinateMapper};
+use crate::panels::{Panels, UiExclusionRects, VIEWER_PANEL};
 
 // Collect pointer state from input events and accumulated deltas.
+#[allow(clippy::too_many_arguments)]
 pub fn pointer_collect_system(
     mut cursor_events: EventReader<CursorMoved>,
     mut button_events: EventReader<MouseButtonInput>,
     keys: Res<ButtonInput<KeyCode>>,
     accumulated: Res<AccumulatedCursorDelta>,
+    panels: Res<Panels>,
+    exclusions: Res<UiExclusionRects>,
+    mapper: Res<CoordinateMapper>,
+    latency: Res<InputLatency>,
     mut pointer: ResMut<crate::PointerState>,
 ) {
     // Update position from the last cursor event this frame (if any)
@@ -16,6 +23,13 @@ pub fn pointer_collect_system(
         // reads & drains for this system only
         pointer.screen = last.position;
     }
+    pointer.timestamp_ms = latency.pointer_timestamp();
+    pointer.excluded = exclusions.contains(pointer.screen);
+    // Overlay-world position while the pointer is over the viewer panel.
+    pointer.overlay_world = panels
+        .rect(VIEWER_PANEL)
+        .filter(|rect| !pointer.excluded && rect.contains(pointer.screen))
+        .map(|rect| mapper.overlay_world(rect, pointer.screen));
 
     // Apply accumulated delta (already zeroed if no movement this frame)
     pointer.delta = accumulated.delta;
@@ -25,7 +39,10 @@ pub fn pointer_collect_system(
 
     // Process button events for edge detection
     for ev in button_events.read() {
-        // independent reader
+        // independent reader; releases always pass so nothing stays held
+        if pointer.excluded && ev.state.is_pressed() {
+            continue;
+        }
         match ev.button {
             MouseButton::Left => pointer.buttons.left = ev.state.is_pressed(),
             MouseButton::Right => pointer.buttons.right = ev.state.is_pressed(),
@@ -0,0 +1,186 @@
+//! Proportional editing: dragging an object in the viewer also moves the objects
+//! around it, each by a share of the motion that falls off with distance.
+//!
+//! Off by default; the host turns it on with
+//! `set_proportional_edit(ptr, enabled, radius, falloff)`. Neighbours are the objects
+//! whose `CurrentVolume` center lies within `radius` of the grabbed object's center
+//! when the drag starts (`drag_apply_system` moves them). While such a drag is held
+//! the mouse wheel grows or shrinks the radius instead of reaching the camera;
+//! objects that fall outside it go back to where they started.
+
+use bevy::math::bounding::BoundingVolume;
+use bevy::prelude::*;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::AccumulatedScroll;
+use crate::bevy_app::scene3d::CurrentVolume;
+use crate::{ActivityControl, DragKind, DragState, WorkerApp};
+
+const MIN_RADIUS: f32 = 0.1;
+const MAX_RADIUS: f32 = 1000.0;
+/// Radius growth per wheel line.
+const WHEEL_STEP: f32 = 0.1;
+
+/// How a neighbour's share of the motion drops from 1 at the grabbed object to 0 at
+/// the radius.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Falloff {
+    #[default]
+    Smooth,
+    Linear,
+    Sharp,
+    Constant,
+}
+
+impl Falloff {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "smooth" => Some(Self::Smooth),
+            "linear" => Some(Self::Linear),
+            "sharp" => Some(Self::Sharp),
+            "constant" => Some(Self::Constant),
+            _ => None,
+        }
+    }
+
+    /// Share of the motion at `t` = distance / radius; 0 from `t = 1` on.
+    pub fn weight(self, t: f32) -> f32 {
+        if t >= 1.0 {
+            return 0.0;
+        }
+        let s = 1.0 - t.max(0.0);
+        match self {
+            Self::Smooth => s * s * (3.0 - 2.0 * s),
+            Self::Linear => s,
+            Self::Sharp => s * s,
+            Self::Constant => 1.0,
+        }
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct ProportionalEdit {
+    pub enabled: bool,
+    /// World units.
+    pub radius: f32,
+    pub falloff: Falloff,
+}
+
+impl Default for ProportionalEdit {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            radius: 2.0,
+            falloff: Falloff::Smooth,
+        }
+    }
+}
+
+impl ProportionalEdit {
+    /// Share of the grabbed object's motion for a neighbour `distance` away.
+    pub fn weight(&self, distance: f32) -> f32 {
+        if !self.enabled {
+            return 0.0;
+        }
+        self.falloff.weight(distance / self.radius)
+    }
+}
+
+/// Neighbours of the current drag, captured when it starts.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct ProportionalDrag {
+    pub(crate) target: Option<Entity>,
+    /// Grabbed object's translation at drag start.
+    pub(crate) origin: Vec3,
+    /// (entity, translation at drag start, center distance from the grabbed object)
+    pub(crate) members: Vec<(Entity, Vec3, f32)>,
+}
+
+impl ProportionalDrag {
+    /// Capture everything that could come within `MAX_RADIUS` of `target`, so the
+    /// radius can grow mid-drag without re-capturing moved objects.
+    pub(crate) fn capture(
+        &mut self,
+        target: Entity,
+        volumes: &Query<(Entity, &CurrentVolume)>,
+        transforms: &Query<&mut Transform>,
+    ) {
+        self.target = Some(target);
+        self.origin = transforms
+            .get(target)
+            .map(|transform| transform.translation)
+            .unwrap_or_default();
+        self.members.clear();
+        let Ok((_, grabbed)) = volumes.get(target) else {
+            return;
+        };
+        let center = grabbed.center();
+        for (entity, volume) in volumes.iter() {
+            let distance = volume.center().distance(center);
+            if entity == target || distance >= MAX_RADIUS {
+                continue;
+            }
+            if let Ok(transform) = transforms.get(entity) {
+                self.members.push((entity, transform.translation, distance));
+            }
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.target = None;
+        self.members.clear();
+    }
+}
+
+/// While a proportional drag is held, the wheel resizes the radius. Consumes the
+/// accumulated scroll so the camera controller doesn't also act on it.
+pub(crate) fn adjust_proportional_radius(
+    drag: Res<DragState>,
+    mut edit: ResMut<ProportionalEdit>,
+    mut scroll: ResMut<AccumulatedScroll>,
+    mut activity: ResMut<ActivityControl>,
+) {
+    if !edit.enabled || drag.target.is_none() || !matches!(drag.kind, Some(DragKind::World3D)) {
+        return;
+    }
+    let lines = match scroll.unit {
+        bevy::input::mouse::MouseScrollUnit::Line => scroll.delta.y,
+        bevy::input::mouse::MouseScrollUnit::Pixel => scroll.delta.y / 16.0,
+    };
+    if lines == 0.0 {
+        return;
+    }
+    scroll.delta = Vec2::ZERO;
+    let radius = edit.radius * (1.0 + WHEEL_STEP).powf(lines);
+    edit.radius = radius.clamp(MIN_RADIUS, MAX_RADIUS);
+    activity.remaining_frames = activity.remaining_frames.max(1);
+}
+
+/// Configure proportional editing. `falloff` is "smooth", "linear", "sharp" or
+/// "constant"; `radius` is clamped to a sane range. Returns false (and changes
+/// nothing) for an unknown falloff.
+#[wasm_bindgen]
+pub fn set_proportional_edit(ptr: u64, enabled: bool, radius: f32, falloff: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(falloff) = Falloff::parse(falloff) else {
+        warn!("set_proportional_edit: unknown falloff {falloff:?}");
+        return false;
+    };
+    let Some(mut edit) = app.world_mut().get_resource_mut::<ProportionalEdit>() else {
+        return false;
+    };
+    *edit = ProportionalEdit {
+        enabled,
+        radius: if radius.is_finite() {
+            radius.clamp(MIN_RADIUS, MAX_RADIUS)
+        } else {
+            edit.radius
+        },
+        falloff,
+    };
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
@@ -0,0 +1,219 @@
+//! Recording live interaction into timeline tracks.
+//!
+//! `timeline_record(ptr, entity_ids, component_ids, true)` starts sampling the chosen
+//! components of the chosen entities every frame (e.g. while the user drags an object
+//! around); `timeline_record(ptr, [], [], false)` stops and bakes every property that
+//! changed into keyframes starting at the playhead, one track per property, replacing
+//! existing tracks for the same properties. Recording runs on its own real-time clock
+//! and leaves the timeline alone, so existing tracks don't fight the interaction.
+//!
+//! Recordable components are those tracks can drive (see `keyframes::TrackProperty`):
+//! `Transform` (translation, rotation and scale components) and `VectorNode` (opacity,
+//! stroke width, fill and stroke channels). Other component ids are skipped.
+
+use std::any::TypeId;
+
+use bevy::ecs::component::ComponentId;
+use bevy::prelude::*;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::history;
+use crate::bevy_app::keyframes::{self, TrackProperty};
+use crate::bevy_app::timeline::TimelineState;
+use crate::bevy_app::vector::VectorNode;
+use crate::entity_id::live_entity_or_warn;
+use crate::{ActivityControl, WorkerApp};
+
+const TRANSFORM_PROPERTIES: [&str; 10] = [
+    "translation.x",
+    "translation.y",
+    "translation.z",
+    "rotation.x",
+    "rotation.y",
+    "rotation.z",
+    "rotation.w",
+    "scale.x",
+    "scale.y",
+    "scale.z",
+];
+
+const VECTOR_NODE_PROPERTIES: [&str; 10] = [
+    "opacity",
+    "stroke_width",
+    "fill.r",
+    "fill.g",
+    "fill.b",
+    "fill.a",
+    "stroke.r",
+    "stroke.g",
+    "stroke.b",
+    "stroke.a",
+];
+
+#[derive(Debug)]
+struct RecordedChannel {
+    entity: Entity,
+    property: &'static str,
+    /// Seconds since the recording started.
+    times: Vec<f32>,
+    values: Vec<f32>,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct TimelineRecorder {
+    channels: Vec<RecordedChannel>,
+    /// Timeline time the recording is baked from.
+    start: f64,
+    /// Recording clock, seconds; `None` when not recording.
+    clock: Option<f64>,
+}
+
+impl TimelineRecorder {
+    pub fn is_recording(&self) -> bool {
+        self.clock.is_some()
+    }
+
+    /// Turn the samples into tracks; returns how many properties changed.
+    fn bake(self, timeline: &mut TimelineState) -> usize {
+        let mut baked = 0;
+        for channel in self.channels {
+            let Some(&first) = channel.values.first() else {
+                continue;
+            };
+            if channel.values.iter().all(|&v| v == first) {
+                continue;
+            }
+            let times = channel
+                .times
+                .iter()
+                .map(|&t| (self.start + t as f64) as f32)
+                .collect::<Vec<_>>();
+            let keys = keyframes::keys_from_samples(&times, &channel.values);
+            if let Some(end) = keys.last() {
+                timeline.duration = timeline.duration.max(end.time);
+            }
+            timeline.set_property_track(channel.entity, channel.property, keys);
+            baked += 1;
+        }
+        baked
+    }
+}
+
+/// Sample the recorded properties; runs after the frame's interaction has moved things.
+pub(crate) fn record_samples(
+    time: Res<Time>,
+    mut recorder: ResMut<TimelineRecorder>,
+    targets: Query<(&Transform, Option<&VectorNode>)>,
+) {
+    let Some(clock) = recorder.clock else {
+        return;
+    };
+    // The first frame is t = 0; its delta covers time before the recording started.
+    let started = recorder.channels.iter().any(|c| !c.times.is_empty());
+    let now = if started {
+        clock + time.delta_secs_f64()
+    } else {
+        0.0
+    };
+    recorder.clock = Some(now);
+    for channel in &mut recorder.channels {
+        let Ok((transform, node)) = targets.get(channel.entity) else {
+            continue;
+        };
+        if let Ok(value) = TrackProperty::parse(channel.property).read(transform, node, None) {
+            channel.times.push(now as f32);
+            channel.values.push(value);
+        }
+    }
+}
+
+/// Start recording the components `component_ids` (inspector component ids) of
+/// `entity_ids`, restarting any recording in progress; returns the number of recorded
+/// properties. With `start` false, stop and bake the recording into tracks; returns
+/// the number of tracks written.
+#[wasm_bindgen]
+pub fn timeline_record(
+    ptr: u64,
+    entity_ids: Vec<u64>,
+    component_ids: Vec<usize>,
+    start: bool,
+) -> u32 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    let count = if start {
+        start_recording(world, &entity_ids, &component_ids)
+    } else {
+        let recorder = world
+            .get_resource_mut::<TimelineRecorder>()
+            .map(|mut recorder| std::mem::take(&mut *recorder));
+        match recorder {
+            Some(recorder) if recorder.is_recording() => history::edit_resource(
+                world,
+                "Record keyframes",
+                |timeline: &TimelineState| (timeline.tracks.clone(), timeline.duration),
+                |timeline, (tracks, duration)| {
+                    timeline.tracks = tracks;
+                    timeline.duration = duration;
+                },
+                |timeline| recorder.bake(timeline),
+            )
+            .unwrap_or(0),
+            _ => 0,
+        }
+    };
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    count as u32
+}
+
+fn start_recording(world: &mut World, entity_ids: &[u64], component_ids: &[usize]) -> usize {
+    let mut properties = Vec::new();
+    for &id in component_ids {
+        let type_id = world
+            .components()
+            .get_info(ComponentId::new(id))
+            .and_then(|info| info.type_id());
+        match type_id {
+            Some(t) if t == TypeId::of::<Transform>() => properties.extend(TRANSFORM_PROPERTIES),
+            Some(t) if t == TypeId::of::<VectorNode>() => {
+                properties.extend(VECTOR_NODE_PROPERTIES);
+            }
+            _ => warn!("timeline_record: component {id} can't be keyframed; skipping"),
+        }
+    }
+
+    let mut channels = Vec::new();
+    for &bits in entity_ids {
+        let Some(entity) = live_entity_or_warn(world, bits, "timeline_record") else {
+            continue;
+        };
+        let Some(transform) = world.get::<Transform>(entity) else {
+            continue;
+        };
+        let node = world.get::<VectorNode>(entity);
+        for &property in &properties {
+            // Skips properties of components the entity doesn't have.
+            if TrackProperty::parse(property).read(transform, node, None).is_ok() {
+                channels.push(RecordedChannel {
+                    entity,
+                    property,
+                    times: Vec::new(),
+                    values: Vec::new(),
+                });
+            }
+        }
+    }
+
+    let start = world
+        .get_resource::<TimelineState>()
+        .map_or(0.0, |timeline| timeline.current_time);
+    let count = channels.len();
+    world.insert_resource(TimelineRecorder {
+        channels,
+        start,
+        clock: (count > 0).then_some(0.0),
+    });
+    count
+}
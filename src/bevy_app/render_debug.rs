@@ -0,0 +1,256 @@
+//! Debug render modes for the 3D viewer (`render_debug` feature).
+//!
+//! The active mode is a reflected component on the main camera, so the inspector
+//! streams it and can edit it like any other field; `set_render_debug_mode` sets it
+//! from the host. Modes:
+//! - `wireframe`: triangle edges. Uses Bevy's `WireframePlugin` with the
+//!   `wireframe_plugin` feature (needs `POLYGON_MODE_LINE`, which WebGPU lacks);
+//!   otherwise edges are drawn as gizmo lines.
+//! - `normals`: vertex normals as gizmo lines.
+//! - `uv_checker` / `overdraw`: every `StandardMaterial` mesh is switched to a shared
+//!   debug material (checker texture / unlit additive tint, so stacked surfaces get
+//!   brighter); the original material is restored when the mode changes. The debug
+//!   materials are created on first use and can be released again while no override
+//!   mode is active (`unload_unused_assets`).
+//! - `aabb`: Bevy's AABB gizmos for all meshes.
+
+use bevy::gizmos::aabb::AabbGizmoConfigGroup;
+use bevy::prelude::*;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::scene3d::{MainCamera3D, uv_debug_texture};
+use crate::{ActivityControl, WorkerApp};
+
+/// Gizmo debug lines stop after this many vertices per frame.
+const MAX_DEBUG_VERTICES: usize = 50_000;
+const NORMAL_LENGTH: f32 = 0.1;
+
+#[derive(Component, Reflect, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[reflect(Component)]
+pub enum RenderDebugMode {
+    #[default]
+    Off,
+    Wireframe,
+    Normals,
+    UvChecker,
+    Overdraw,
+    Aabb,
+}
+
+impl RenderDebugMode {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "off" => Some(Self::Off),
+            "wireframe" => Some(Self::Wireframe),
+            "normals" => Some(Self::Normals),
+            "uv_checker" => Some(Self::UvChecker),
+            "overdraw" => Some(Self::Overdraw),
+            "aabb" => Some(Self::Aabb),
+            _ => None,
+        }
+    }
+
+    fn overrides_materials(self) -> bool {
+        matches!(self, Self::UvChecker | Self::Overdraw)
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct DebugMaterials {
+    uv_checker: Handle<StandardMaterial>,
+    overdraw: Handle<StandardMaterial>,
+}
+
+/// Material a mesh had before a debug material replaced it.
+#[derive(Component)]
+pub(crate) struct OriginalMaterial(Handle<StandardMaterial>);
+
+/// Run condition: an override mode is active and its materials are not created yet.
+pub(crate) fn debug_materials_missing(
+    cameras: Query<&RenderDebugMode, With<MainCamera3D>>,
+    debug_materials: Option<Res<DebugMaterials>>,
+) -> bool {
+    debug_materials.is_none() && active_mode(&cameras).overrides_materials()
+}
+
+/// Drop the debug materials unless an override mode uses them. Returns whether they
+/// were released.
+pub(crate) fn release_debug_materials(world: &mut World) -> bool {
+    let in_use = world
+        .query_filtered::<&RenderDebugMode, With<MainCamera3D>>()
+        .iter(world)
+        .any(|mode| mode.overrides_materials());
+    !in_use && world.remove_resource::<DebugMaterials>().is_some()
+}
+
+pub(crate) fn setup_render_debug(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let uv_checker = materials.add(StandardMaterial {
+        base_color_texture: Some(images.add(uv_debug_texture())),
+        unlit: true,
+        ..default()
+    });
+    let overdraw = materials.add(StandardMaterial {
+        base_color: Color::linear_rgb(0.12, 0.04, 0.01),
+        alpha_mode: AlphaMode::Add,
+        unlit: true,
+        cull_mode: None,
+        ..default()
+    });
+    commands.insert_resource(DebugMaterials {
+        uv_checker,
+        overdraw,
+    });
+}
+
+pub(crate) fn attach_render_debug_mode(
+    mut commands: Commands,
+    cameras: Query<Entity, (With<MainCamera3D>, Without<RenderDebugMode>)>,
+) {
+    for camera in &cameras {
+        commands.entity(camera).insert(RenderDebugMode::default());
+    }
+}
+
+fn active_mode(cameras: &Query<&RenderDebugMode, With<MainCamera3D>>) -> RenderDebugMode {
+    cameras.single().copied().unwrap_or_default()
+}
+
+type NotOverridden = (With<Mesh3d>, Without<OriginalMaterial>);
+
+/// Swap materials for the material-override modes, including meshes spawned while
+/// the mode is on, and restore them otherwise.
+pub(crate) fn apply_debug_materials(
+    mut commands: Commands,
+    cameras: Query<&RenderDebugMode, With<MainCamera3D>>,
+    debug_materials: Option<Res<DebugMaterials>>,
+    mut plain: Query<(Entity, &mut MeshMaterial3d<StandardMaterial>), NotOverridden>,
+    mut overridden: Query<(
+        Entity,
+        &mut MeshMaterial3d<StandardMaterial>,
+        &OriginalMaterial,
+    )>,
+) {
+    let debug = match (active_mode(&cameras), &debug_materials) {
+        (RenderDebugMode::UvChecker, Some(debug_materials)) => Some(&debug_materials.uv_checker),
+        (RenderDebugMode::Overdraw, Some(debug_materials)) => Some(&debug_materials.overdraw),
+        _ => None,
+    };
+    match debug {
+        Some(debug) => {
+            for (entity, mut material) in &mut plain {
+                let original = std::mem::replace(&mut material.0, debug.clone());
+                commands.entity(entity).insert(OriginalMaterial(original));
+            }
+            for (_, mut material, _) in &mut overridden {
+                if material.0 != *debug {
+                    material.0 = debug.clone();
+                }
+            }
+        }
+        None => {
+            for (entity, mut material, original) in &mut overridden {
+                material.0 = original.0.clone();
+                commands.entity(entity).remove::<OriginalMaterial>();
+            }
+        }
+    }
+}
+
+pub(crate) fn apply_debug_gizmo_config(
+    cameras: Query<&RenderDebugMode, (With<MainCamera3D>, Changed<RenderDebugMode>)>,
+    mut config_store: ResMut<GizmoConfigStore>,
+    #[cfg(feature = "wireframe_plugin")] mut wireframe: ResMut<
+        bevy::pbr::wireframe::WireframeConfig,
+    >,
+) {
+    let Ok(mode) = cameras.single() else {
+        return;
+    };
+    config_store.config_mut::<AabbGizmoConfigGroup>().1.draw_all = *mode == RenderDebugMode::Aabb;
+    #[cfg(feature = "wireframe_plugin")]
+    {
+        wireframe.global = *mode == RenderDebugMode::Wireframe;
+    }
+}
+
+/// Gizmo-line modes: normals, and wireframe when the wireframe plugin is unavailable.
+pub(crate) fn draw_debug_lines(
+    cameras: Query<&RenderDebugMode, With<MainCamera3D>>,
+    meshes: Res<Assets<Mesh>>,
+    query: Query<(&Mesh3d, &GlobalTransform, &ViewVisibility)>,
+    mut gizmos: Gizmos,
+) {
+    let mode = active_mode(&cameras);
+    let draw_edges = mode == RenderDebugMode::Wireframe && !cfg!(feature = "wireframe_plugin");
+    if !(draw_edges || mode == RenderDebugMode::Normals) {
+        return;
+    }
+    let mut budget = MAX_DEBUG_VERTICES;
+    for (mesh, transform, visibility) in &query {
+        if !visibility.get() {
+            continue;
+        }
+        let Some(mesh) = meshes.get(&mesh.0) else {
+            continue;
+        };
+        let Some(positions) = mesh
+            .attribute(Mesh::ATTRIBUTE_POSITION)
+            .and_then(|p| p.as_float3())
+        else {
+            continue;
+        };
+        if positions.len() > budget {
+            break;
+        }
+        budget -= positions.len();
+        let world = |p: [f32; 3]| transform.transform_point(Vec3::from(p));
+
+        if draw_edges {
+            let indices: Vec<usize> = match mesh.indices() {
+                Some(indices) => indices.iter().collect(),
+                None => (0..positions.len()).collect(),
+            };
+            for tri in indices.chunks_exact(3) {
+                let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| world(positions[i]));
+                gizmos.linestrip([a, b, c, a], Color::linear_rgb(0.0, 0.0, 0.0));
+            }
+        } else if let Some(normals) = mesh
+            .attribute(Mesh::ATTRIBUTE_NORMAL)
+            .and_then(|n| n.as_float3())
+        {
+            for (p, n) in positions.iter().zip(normals) {
+                let start = world(*p);
+                let dir = transform.affine().transform_vector3(Vec3::from(*n));
+                gizmos.line(
+                    start,
+                    start + dir.normalize_or_zero() * NORMAL_LENGTH,
+                    Color::linear_rgb(0.1, 0.4, 1.0),
+                );
+            }
+        }
+    }
+}
+
+/// Switch the debug render mode: `off`, `wireframe`, `normals`, `uv_checker`,
+/// `overdraw` or `aabb`. Returns false for an unknown mode.
+#[wasm_bindgen]
+pub fn set_render_debug_mode(ptr: u64, mode: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(mode) = RenderDebugMode::parse(mode) else {
+        return false;
+    };
+    let world = app.world_mut();
+    let mut cameras = world.query_filtered::<&mut RenderDebugMode, With<MainCamera3D>>();
+    let Ok(mut current) = cameras.single_mut(world) else {
+        return false;
+    };
+    current.set_if_neq(mode);
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
@@ -0,0 +1,234 @@
+//! Render quality settings the host can change at runtime (e.g. a quality dropdown
+//! for low-end devices).
+//!
+//! `set_render_quality(ptr, json)` merges the given fields into the `RenderQuality`
+//! resource; `apply_render_quality` pushes it into the 3D camera (MSAA), lights and
+//! shadow maps, and image samplers, and an extract system copies the Vello AA mode
+//! into the render world (the renderer is built with every AA mode supported).
+
+use bevy::image::{ImageFilterMode, ImageSampler, ImageSamplerDescriptor};
+use bevy::pbr::{DirectionalLightShadowMap, PointLightShadowMap};
+use bevy::prelude::*;
+use bevy::render::view::Msaa;
+use bevy::render::{Extract, RenderApp};
+use bevy_vello::render::VelloRenderSettings;
+use bevy_vello::vello::AaConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::scene3d::MainCamera3D;
+use crate::{ActivityControl, WorkerApp};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VelloAa {
+    Area,
+    Msaa8,
+    Msaa16,
+}
+
+impl From<VelloAa> for AaConfig {
+    fn from(aa: VelloAa) -> Self {
+        match aa {
+            VelloAa::Area => AaConfig::Area,
+            VelloAa::Msaa8 => AaConfig::Msaa8,
+            VelloAa::Msaa16 => AaConfig::Msaa16,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextureFiltering {
+    Nearest,
+    Linear,
+}
+
+impl From<TextureFiltering> for ImageFilterMode {
+    fn from(filtering: TextureFiltering) -> Self {
+        match filtering {
+            TextureFiltering::Nearest => ImageFilterMode::Nearest,
+            TextureFiltering::Linear => ImageFilterMode::Linear,
+        }
+    }
+}
+
+/// Defaults match the app's startup configuration.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderQuality {
+    /// 3D viewer MSAA samples: 1 (off) or 4, the counts WebGPU supports.
+    pub msaa_samples: u32,
+    pub vello_aa: VelloAa,
+    pub shadows: bool,
+    /// Shadow map size (px) for directional cascades and point light cube faces.
+    pub shadow_resolution: u32,
+    pub texture_filtering: TextureFiltering,
+}
+
+impl Default for RenderQuality {
+    fn default() -> Self {
+        Self {
+            msaa_samples: 1,
+            vello_aa: VelloAa::Area,
+            shadows: false,
+            shadow_resolution: 2048,
+            texture_filtering: TextureFiltering::Nearest,
+        }
+    }
+}
+
+impl RenderQuality {
+    fn is_valid(&self) -> bool {
+        matches!(self.msaa_samples, 1 | 4)
+            && self.shadow_resolution.is_power_of_two()
+            && (256..=8192).contains(&self.shadow_resolution)
+    }
+}
+
+pub struct RenderQualityPlugin;
+
+impl Plugin for RenderQualityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RenderQuality>()
+            .add_systems(Update, (apply_render_quality, apply_texture_filtering));
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.add_systems(ExtractSchedule, extract_vello_aa);
+        }
+    }
+}
+
+fn apply_render_quality(
+    mut commands: Commands,
+    quality: Res<RenderQuality>,
+    cameras: Query<Entity, With<MainCamera3D>>,
+    mut point_lights: Query<&mut PointLight>,
+    mut directional_lights: Query<&mut DirectionalLight>,
+    mut spot_lights: Query<&mut SpotLight>,
+) {
+    if !quality.is_changed() {
+        return;
+    }
+    for camera in &cameras {
+        commands
+            .entity(camera)
+            .insert(Msaa::from_samples(quality.msaa_samples));
+    }
+    for mut light in &mut point_lights {
+        light.shadows_enabled = quality.shadows;
+    }
+    for mut light in &mut directional_lights {
+        light.shadows_enabled = quality.shadows;
+    }
+    for mut light in &mut spot_lights {
+        light.shadows_enabled = quality.shadows;
+    }
+    let size = quality.shadow_resolution as usize;
+    commands.insert_resource(DirectionalLightShadowMap { size });
+    commands.insert_resource(PointLightShadowMap { size: size / 2 });
+}
+
+/// Set min/mag/mip filters on every image; images added later pick up the current
+/// setting as they arrive.
+fn apply_texture_filtering(
+    quality: Res<RenderQuality>,
+    mut images: ResMut<Assets<Image>>,
+    mut events: EventReader<AssetEvent<Image>>,
+    mut applied: Local<Option<TextureFiltering>>,
+) {
+    let filtering = quality.texture_filtering;
+    let set_filter = |image: &mut Image| {
+        let mode = ImageFilterMode::from(filtering);
+        if !matches!(image.sampler, ImageSampler::Descriptor(_)) {
+            image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor::default());
+        }
+        let ImageSampler::Descriptor(descriptor) = &mut image.sampler else {
+            return;
+        };
+        descriptor.mag_filter = mode;
+        descriptor.min_filter = mode;
+        descriptor.mipmap_filter = mode;
+    };
+
+    if *applied != Some(filtering) {
+        // The startup default (ImagePlugin::default_nearest) needs no rewrite.
+        if applied.is_some() || filtering != TextureFiltering::Nearest {
+            for (_, image) in images.iter_mut() {
+                set_filter(image);
+            }
+        }
+        *applied = Some(filtering);
+        events.clear();
+        return;
+    }
+    if filtering == TextureFiltering::Nearest {
+        events.clear();
+        return;
+    }
+    let added: Vec<AssetId<Image>> = events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Added { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    for id in added {
+        if let Some(image) = images.get_mut(id) {
+            set_filter(image);
+        }
+    }
+}
+
+fn extract_vello_aa(
+    quality: Extract<Res<RenderQuality>>,
+    settings: Option<ResMut<VelloRenderSettings>>,
+) {
+    // Absent when Vello is disabled (WebGL2 backend).
+    let Some(mut settings) = settings else {
+        return;
+    };
+    let aa = AaConfig::from(quality.vello_aa);
+    if settings.antialiasing != aa {
+        settings.antialiasing = aa;
+    }
+}
+
+/// Merge quality fields (`msaa_samples`, `vello_aa`, `shadows`, `shadow_resolution`,
+/// `texture_filtering`) into the current settings. Returns false on invalid JSON or
+/// out-of-range values, leaving the settings unchanged.
+#[wasm_bindgen]
+pub fn set_render_quality(ptr: u64, json: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Ok(Value::Object(fields)) = serde_json::from_str::<Value>(json) else {
+        return false;
+    };
+    let Some(mut quality) = app.world_mut().get_resource_mut::<RenderQuality>() else {
+        return false;
+    };
+    let Ok(Value::Object(mut merged)) = serde_json::to_value(&*quality) else {
+        return false;
+    };
+    merged.extend(fields);
+    let Ok(updated) = serde_json::from_value::<RenderQuality>(Value::Object(merged)) else {
+        return false;
+    };
+    if !updated.is_valid() {
+        return false;
+    }
+    quality.set_if_neq(updated);
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
+
+/// Current settings as JSON.
+#[wasm_bindgen]
+pub fn get_render_quality(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    app.world()
+        .get_resource::<RenderQuality>()
+        .and_then(|quality| serde_json::to_string(quality).ok())
+        .unwrap_or_else(|| "{}".to_string())
+}
@@ -0,0 +1,91 @@
+//! Frame cost diagnostics for telling CPU-bound from GPU-bound slowdowns.
+//!
+//! - `RenderDiagnosticsPlugin` records per-pass `render/<pass>/elapsed_cpu`, plus
+//!   `elapsed_gpu` where the device supports timestamp queries inside encoders
+//!   (native Vulkan / DX12; browsers currently report CPU time only).
+//! - `GPU_FRAME_TIME` / `RENDER_CPU_TIME` sum the top-level passes of each kind.
+//! - `VELLO_ENCODE_TIME` is the wall time of the systems in `VelloEncodeSet`, which
+//!   rebuild `VelloScene`s for the overlay, panels and tools.
+//!
+//! All of them land in `DiagnosticsStore`, so the FPS HUD and the inspector's `stats`
+//! event pick them up like any other diagnostic.
+
+use bevy::diagnostic::{
+    Diagnostic, DiagnosticPath, Diagnostics, DiagnosticsStore, RegisterDiagnostic,
+};
+use bevy::platform::time::Instant;
+use bevy::prelude::*;
+use bevy::render::diagnostic::RenderDiagnosticsPlugin;
+
+pub const GPU_FRAME_TIME: DiagnosticPath = DiagnosticPath::const_new("frame/gpu_ms");
+pub const RENDER_CPU_TIME: DiagnosticPath = DiagnosticPath::const_new("frame/render_cpu_ms");
+pub const VELLO_ENCODE_TIME: DiagnosticPath = DiagnosticPath::const_new("vello/encode_ms");
+
+/// Update systems that reset and re-encode Vello scenes.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VelloEncodeSet;
+
+#[derive(Resource, Default)]
+struct EncodeTimer(Option<Instant>);
+
+pub struct RenderTimingPlugin;
+
+impl Plugin for RenderTimingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RenderDiagnosticsPlugin)
+            .register_diagnostic(Diagnostic::new(GPU_FRAME_TIME).with_suffix("ms"))
+            .register_diagnostic(Diagnostic::new(RENDER_CPU_TIME).with_suffix("ms"))
+            .register_diagnostic(Diagnostic::new(VELLO_ENCODE_TIME).with_suffix("ms"))
+            .init_resource::<EncodeTimer>()
+            .add_systems(
+                Update,
+                (
+                    start_encode_timer.before(VelloEncodeSet),
+                    stop_encode_timer.after(VelloEncodeSet),
+                    sum_render_pass_timings,
+                ),
+            );
+    }
+}
+
+fn start_encode_timer(mut timer: ResMut<EncodeTimer>) {
+    timer.0 = Some(Instant::now());
+}
+
+fn stop_encode_timer(mut timer: ResMut<EncodeTimer>, mut diagnostics: Diagnostics) {
+    if let Some(start) = timer.0.take() {
+        diagnostics.add_measurement(&VELLO_ENCODE_TIME, || {
+            start.elapsed().as_secs_f64() * 1000.0
+        });
+    }
+}
+
+/// Sum the latest `render/<pass>/elapsed_*` values; nested passes are already
+/// included in their parent's span.
+fn sum_render_pass_timings(store: Res<DiagnosticsStore>, mut diagnostics: Diagnostics) {
+    let mut gpu = None;
+    let mut cpu = None;
+    for diagnostic in store.iter() {
+        let components: Vec<&str> = diagnostic.path().components().collect();
+        let [_, _, field] = components[..] else {
+            continue;
+        };
+        if components[0] != "render" {
+            continue;
+        }
+        let total = match field {
+            "elapsed_gpu" => &mut gpu,
+            "elapsed_cpu" => &mut cpu,
+            _ => continue,
+        };
+        if let Some(value) = diagnostic.value() {
+            *total.get_or_insert(0.0) += value;
+        }
+    }
+    if let Some(gpu) = gpu {
+        diagnostics.add_measurement(&GPU_FRAME_TIME, || gpu);
+    }
+    if let Some(cpu) = cpu {
+        diagnostics.add_measurement(&RENDER_CPU_TIME, || cpu);
+    }
+}
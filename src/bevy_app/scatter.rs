@@ -0,0 +1,208 @@
+//! Scatter: jitter the position, rotation and scale of a set of entities with seeded
+//! randomness, e.g. to break up a row of duplicates into a natural-looking cluster.
+//!
+//! `ScatterEntities` draws each entity's jitter from one `seed` in entity order, so the
+//! same seed over the same entities always gives the same arrangement, and records the
+//! whole scatter as one undo entry.
+
+use anyhow::bail;
+use bevy::prelude::*;
+use bevy_remote_inspector::{InspectorContext, command::Execute};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Deserialize;
+
+use crate::Locale;
+use crate::bevy_app::history::{History, HistoryEntry, HistoryOp};
+
+/// Upper bound for `scale_jitter`, so no entity is scaled to zero or flipped.
+const MAX_SCALE_JITTER: f32 = 0.95;
+
+/// Perturb `entities`' transforms. Each jitter is the largest change in either
+/// direction: `position_jitter` per axis in world units, `rotation_jitter` per axis in
+/// degrees (applied on top of the current rotation) and `scale_jitter` as a fraction
+/// of the current uniform scale. Params deserialize from JSON, every field optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ScatterEntities {
+    #[serde(skip)]
+    pub entities: Vec<Entity>,
+    pub seed: u64,
+    pub position_jitter: Vec3,
+    pub rotation_jitter: Vec3,
+    pub scale_jitter: f32,
+}
+
+impl Execute for ScatterEntities {
+    /// Number of entities moved.
+    type Output = u32;
+
+    fn execute(
+        self,
+        _ctx: &mut InspectorContext,
+        world: &mut World,
+    ) -> anyhow::Result<Self::Output> {
+        let mut entities = self.entities;
+        entities.sort();
+        entities.dedup();
+        let position_jitter = self.position_jitter.abs();
+        let rotation_jitter = self.rotation_jitter.abs().map(f32::to_radians);
+        let scale_jitter = self.scale_jitter.abs().min(MAX_SCALE_JITTER);
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut ops = Vec::new();
+        for entity in entities {
+            // Draw before the lookup so a skipped entity doesn't shift everyone else's.
+            let offset = unit_vec3(&mut rng) * position_jitter;
+            let angles = unit_vec3(&mut rng) * rotation_jitter;
+            let scale = 1.0 + rng.gen_range(-1.0..=1.0) * scale_jitter;
+            let Some(mut transform) = world.get_mut::<Transform>(entity) else {
+                continue;
+            };
+            let before = *transform;
+            transform.translation += offset;
+            transform.rotation =
+                Quat::from_euler(EulerRot::XYZ, angles.x, angles.y, angles.z) * transform.rotation;
+            transform.scale *= scale;
+            ops.push(HistoryOp::Transform {
+                entity,
+                before,
+                after: *transform,
+            });
+        }
+        if ops.is_empty() {
+            bail!("No entity to scatter has a Transform");
+        }
+
+        let count = ops.len() as u32;
+        let label = world.get_resource::<Locale>().map_or_else(
+            || Locale::english("history.scatter").to_string(),
+            |locale| locale.get("history.scatter").to_string(),
+        );
+        if let Some(mut history) = world.get_resource_mut::<History>() {
+            history.push(HistoryEntry { label, ops });
+        }
+        Ok(count)
+    }
+}
+
+/// Each component uniform in [-1, 1].
+fn unit_vec3(rng: &mut StdRng) -> Vec3 {
+    Vec3::new(
+        rng.gen_range(-1.0..=1.0),
+        rng.gen_range(-1.0..=1.0),
+        rng.gen_range(-1.0..=1.0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_remote_inspector::RemoteInspectorPlugin;
+
+    use super::*;
+    use crate::bevy_app::history;
+
+    /// A world with one entity per entry, with a `Transform` at x = index where `true`.
+    fn world_with(transforms: &[bool]) -> (App, Vec<Entity>) {
+        let mut app = App::new();
+        app.add_plugins(RemoteInspectorPlugin)
+            .init_resource::<History>();
+        let entities = transforms
+            .iter()
+            .enumerate()
+            .map(|(i, has_transform)| {
+                let mut entity = app.world_mut().spawn_empty();
+                if *has_transform {
+                    entity.insert(Transform::from_xyz(i as f32, 0.0, 0.0));
+                }
+                entity.id()
+            })
+            .collect();
+        (app, entities)
+    }
+
+    fn scatter(world: &mut World, entities: &[Entity], seed: u64) -> anyhow::Result<u32> {
+        let command = ScatterEntities {
+            entities: entities.to_vec(),
+            seed,
+            position_jitter: Vec3::splat(2.0),
+            rotation_jitter: Vec3::new(0.0, 0.0, 45.0),
+            scale_jitter: 0.5,
+        };
+        InspectorContext::run(world, |ctx, world| command.execute(ctx, world))
+    }
+
+    fn transforms(world: &World, entities: &[Entity]) -> Vec<Option<Transform>> {
+        entities
+            .iter()
+            .map(|entity| world.get::<Transform>(*entity).copied())
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_gives_same_arrangement() {
+        let scattered = |seed, order: &[usize]| {
+            let (mut app, entities) = world_with(&[true; 3]);
+            let ordered: Vec<_> = order.iter().map(|i| entities[*i]).collect();
+            assert_eq!(scatter(app.world_mut(), &ordered, seed).ok(), Some(3));
+            transforms(app.world(), &entities)
+        };
+        // Entities are drawn for in entity order, not the order they were passed in.
+        assert_eq!(scattered(7, &[0, 1, 2]), scattered(7, &[2, 0, 1, 0]));
+        assert_ne!(scattered(7, &[0, 1, 2]), scattered(8, &[0, 1, 2]));
+    }
+
+    #[test]
+    fn skipped_entities_do_not_shift_the_rest() {
+        let (mut full, entities) = world_with(&[true, true, true]);
+        let (mut gap, _) = world_with(&[true, false, true]);
+        assert_eq!(scatter(full.world_mut(), &entities, 3).ok(), Some(3));
+        assert_eq!(scatter(gap.world_mut(), &entities, 3).ok(), Some(2));
+        let full = transforms(full.world(), &entities);
+        let gap = transforms(gap.world(), &entities);
+        assert_eq!((gap[0], gap[1], gap[2]), (full[0], None, full[2]));
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let (mut app, entities) = world_with(&[true; 16]);
+        let command = ScatterEntities {
+            entities: entities.clone(),
+            seed: 11,
+            position_jitter: Vec3::new(-1.0, 0.5, 0.0),
+            rotation_jitter: Vec3::ZERO,
+            scale_jitter: 4.0,
+        };
+        let world = app.world_mut();
+        InspectorContext::run(world, |ctx, world| command.execute(ctx, world)).unwrap();
+        for (i, transform) in transforms(world, &entities).into_iter().enumerate() {
+            let transform = transform.unwrap();
+            let offset = transform.translation - Vec3::new(i as f32, 0.0, 0.0);
+            assert!(offset.abs().cmple(Vec3::new(1.0, 0.5, 0.0) + 1e-5).all());
+            assert_eq!(transform.rotation, Quat::IDENTITY);
+            // A negative jitter counts as its magnitude; the scale one is capped.
+            let scale = transform.scale.x;
+            assert!((1.0 - MAX_SCALE_JITTER..=1.0 + MAX_SCALE_JITTER).contains(&scale));
+            assert_eq!(transform.scale, Vec3::splat(scale));
+        }
+    }
+
+    #[test]
+    fn one_undo_restores_every_entity() {
+        let (mut app, entities) = world_with(&[true; 4]);
+        let world = app.world_mut();
+        let before = transforms(world, &entities);
+        scatter(world, &entities, 5).unwrap();
+        assert_ne!(transforms(world, &entities), before);
+        assert!(history::undo(world));
+        assert_eq!(transforms(world, &entities), before);
+        assert!(!world.resource::<History>().can_undo());
+    }
+
+    #[test]
+    fn nothing_to_scatter_is_an_error() {
+        let (mut app, entities) = world_with(&[false, false]);
+        assert!(scatter(app.world_mut(), &entities, 1).is_err());
+        assert!(!app.world().resource::<History>().can_undo());
+    }
+}
@@ -1,5 +1,8 @@
 use crate::ActivityControl;
+use crate::bevy_app::atmosphere::Atmosphere;
+use crate::bevy_app::theme::Theme;
 use crate::camera_controller::CameraController;
+use bevy::core_pipeline::tonemapping::Tonemapping;
 use bevy::math::bounding::{Aabb3d, Bounded3d};
 use bevy::prelude::*;
 use bevy::render::view::RenderLayers;
@@ -8,7 +11,6 @@ use bevy::render::{
     render_resource::{Extent3d, TextureDimension, TextureFormat},
 };
 use std::ops::Deref;
-use bevy::core_pipeline::tonemapping::Tonemapping;
 
 // Marker for 3D main camera
 #[derive(Component)]
@@ -43,61 +45,7 @@ impl Deref for CurrentVolume {
     }
 }
 
-pub(crate) fn setup_3d_scene(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut images: ResMut<Assets<Image>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    _asset_server: Res<AssetServer>,
-) {
-    let debug_material = materials.add(StandardMaterial {
-        base_color_texture: Some(images.add(uv_debug_texture())),
-        ..default()
-    });
-
-    let meshe_handles = [meshes.add(
-        Torus::default()
-            .mesh()
-            .major_resolution(8)
-            .minor_resolution(6),
-    )];
-    let shape = Shape::Box(Cuboid::from_size(Vec3::new(1.75, 0.52, 1.75)));
-
-    commands.spawn((
-        Mesh3d(meshe_handles[0].to_owned()),
-        MeshMaterial3d(debug_material.clone()),
-        Transform::from_xyz(0.0, 1.5, 0.0),
-        shape,
-        ActiveState::default(),
-        RenderLayers::layer(0),
-    ));
-
-    // Lights
-    commands.spawn((
-        PointLight {
-            shadows_enabled: false,
-            intensity: 07_000_000.,
-            range: 100.0,
-            ..default()
-        },
-        Transform::from_xyz(8.0, 9.0, 16.0),
-    ));
-    commands.spawn((
-        PointLight {
-            shadows_enabled: false,
-            intensity: 2_000_000.,
-            range: 100.0,
-            ..default()
-        },
-        Transform::from_xyz(-8.0, 9.0, -10.0),
-    ));
-
-    // Ground
-    commands.spawn((
-        Mesh3d(meshes.add(Plane3d::default().mesh().size(50.0, 50.0).subdivisions(10))),
-        MeshMaterial3d(materials.add(Color::srgb(0.75, 0.75, 0.75))),
-    ));
-
+pub(crate) fn setup_3d_scene(mut commands: Commands) {
     // Camera renders into the "viewer" panel's viewport rect; it stays inactive
     // until JS posts that panel (see apply_viewer_viewport in bevy_app/mod.rs).
     let camera = Camera {
@@ -122,6 +70,9 @@ pub(crate) fn setup_3d_scene(
         }),
         Transform::from_xyz(0.0, 18., 18.).looking_at(Vec3::new(0., 0., 0.), Vec3::Y),
     ));
+
+    // Fog and gradient sky; both start disabled (see `set_atmosphere`).
+    commands.insert_resource(Atmosphere::default());
 }
 
 pub(crate) fn rotate_3d_shapes(
@@ -139,19 +90,18 @@ pub(crate) fn rotate_3d_shapes(
 
 pub(crate) fn render_active_shapes(
     mut gizmos: Gizmos,
+    theme: Res<Theme>,
     query: Query<(&Shape, &Transform, &ActiveState)>,
 ) {
-    use bevy::color::palettes::css::BLANCHED_ALMOND;
-    use bevy::color::palettes::tailwind::BLUE_400;
     for (shape, transform, active_state) in query.iter() {
         if !active_state.is_active() {
             continue;
         }
-        let color = if active_state.selected {
-            BLUE_400
+        let color = Theme::color(if active_state.selected {
+            theme.selection
         } else {
-            BLANCHED_ALMOND
-        };
+            theme.hover
+        });
         let translation = transform.translation.xyz();
         match shape {
             Shape::Box(cuboid) => {
@@ -167,12 +117,8 @@ pub(crate) fn render_active_shapes(
 
 pub(crate) fn update_aabbes(
     mut commands: Commands,
-    mut config_store: ResMut<bevy::gizmos::config::GizmoConfigStore>,
     query: Query<(Entity, &Shape, &Transform), Or<(Changed<Shape>, Changed<Transform>)>>,
 ) {
-    for (_, config, _) in config_store.iter_mut() {
-        config.line.width = 3.;
-    }
     for (entity, shape, transform) in query.iter() {
         let translation = transform.translation;
         let rotation = transform.rotation;
@@ -183,7 +129,7 @@ pub(crate) fn update_aabbes(
     }
 }
 
-fn uv_debug_texture() -> Image {
+pub(crate) fn uv_debug_texture() -> Image {
     const TEXTURE_SIZE: usize = 8;
     let mut palette: [u8; 32] = [
         255, 102, 159, 255, 255, 159, 102, 255, 236, 255, 102, 255, 121, 255, 102, 255, 102, 255,
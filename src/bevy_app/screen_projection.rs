@@ -0,0 +1,194 @@
+//! World-to-screen projection for pinning HTML overlays to 3D entities.
+//!
+//! `project_entities(ptr, ids_json)` answers once; `track_entity_projections` keeps a
+//! set of entities whose projections are pushed through
+//! `send_projections_from_worker` whenever they move on screen (entity or camera
+//! motion, viewport changes). Positions are canvas-relative CSS px in the host's input
+//! frame (see `CoordinateMapper`), matching the coordinates the host sends in.
+
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::CoordinateMapper;
+use crate::bevy_app::scene3d::MainCamera3D;
+use crate::web_ffi::parse_entity_ids;
+use crate::{ActivityControl, WorkerApp};
+
+/// Tracked projections are re-sent once they move by more than this (CSS px).
+const MOVE_THRESHOLD: f32 = 0.25;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_projections_from_worker(projections_json: &str);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreenStatus {
+    /// Inside the viewer viewport.
+    OnScreen,
+    /// In front of the camera but outside the viewport; x/y are still given.
+    OffScreen,
+    /// Behind the camera (or past the far plane); no usable x/y.
+    Behind,
+    /// Hidden through `Visibility`.
+    Hidden,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ScreenProjection {
+    pub entity: u64,
+    pub x: Option<f32>,
+    pub y: Option<f32>,
+    /// Distance from the camera, world units.
+    pub depth: f32,
+    pub status: ScreenStatus,
+}
+
+impl ScreenProjection {
+    fn moved_from(&self, other: &Self) -> bool {
+        let delta = |a: Option<f32>, b: Option<f32>| match (a, b) {
+            (Some(a), Some(b)) => (a - b).abs() > MOVE_THRESHOLD,
+            (a, b) => a.is_some() != b.is_some(),
+        };
+        self.status != other.status || delta(self.x, other.x) || delta(self.y, other.y)
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ProjectionTracking {
+    entities: Vec<Entity>,
+    window: Option<Entity>,
+    last_sent: HashMap<Entity, ScreenProjection>,
+}
+
+fn project(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    entity: Entity,
+    transform: &GlobalTransform,
+    visible: bool,
+    to_host: impl Fn(Vec2) -> Vec2,
+) -> ScreenProjection {
+    let position = transform.translation();
+    let depth = position.distance(camera_transform.translation());
+    // Errors mean past the near / far plane (or no viewport yet).
+    let viewport_position = camera.world_to_viewport(camera_transform, position).ok();
+    let on_screen = viewport_position.is_some_and(|p| {
+        camera
+            .logical_viewport_rect()
+            .is_some_and(|rect| rect.contains(p))
+    });
+    let status = match (visible, viewport_position, on_screen) {
+        (false, ..) => ScreenStatus::Hidden,
+        (true, None, _) => ScreenStatus::Behind,
+        (true, Some(_), true) => ScreenStatus::OnScreen,
+        (true, Some(_), false) => ScreenStatus::OffScreen,
+    };
+    let css = viewport_position.map(to_host);
+    ScreenProjection {
+        entity: entity.to_bits(),
+        x: css.map(|p| p.x),
+        y: css.map(|p| p.y),
+        depth,
+        status,
+    }
+}
+
+fn project_all(world: &mut World, entities: &[Entity], window: Entity) -> Vec<ScreenProjection> {
+    let mut cameras = world.query_filtered::<(&Camera, &GlobalTransform), With<MainCamera3D>>();
+    let Ok((camera, camera_transform)) = cameras.single(world) else {
+        return Vec::new();
+    };
+    let Some(mapper) = world.get_resource::<CoordinateMapper>() else {
+        return Vec::new();
+    };
+    entities
+        .iter()
+        .filter_map(|&entity| {
+            let transform = world.get::<GlobalTransform>(entity)?;
+            let visible = world
+                .get::<InheritedVisibility>(entity)
+                .is_none_or(|v| v.get());
+            Some(project(
+                camera,
+                camera_transform,
+                entity,
+                transform,
+                visible,
+                |p| mapper.window_to_host(window, p),
+            ))
+        })
+        .collect()
+}
+
+/// Push tracked projections that moved since they were last sent.
+pub(crate) fn push_tracked_projections(world: &mut World) {
+    let Some(tracking) = world.get_resource::<ProjectionTracking>() else {
+        return;
+    };
+    let Some(window) = tracking.window.filter(|_| !tracking.entities.is_empty()) else {
+        return;
+    };
+    let entities = tracking.entities.clone();
+    let projections = project_all(world, &entities, window);
+    let mut tracking = world.resource_mut::<ProjectionTracking>();
+    let changed: Vec<ScreenProjection> = projections
+        .into_iter()
+        .filter(|projection| {
+            let entity = Entity::from_bits(projection.entity);
+            let moved = tracking
+                .last_sent
+                .get(&entity)
+                .is_none_or(|last| projection.moved_from(last));
+            if moved {
+                tracking.last_sent.insert(entity, *projection);
+            }
+            moved
+        })
+        .collect();
+    if changed.is_empty() {
+        return;
+    }
+    if let Ok(json) = serde_json::to_string(&changed) {
+        send_projections_from_worker(&json);
+    }
+}
+
+/// Project entities (JSON array of ids) through the 3D viewer camera. Returns a JSON
+/// array of `{ entity, x, y, depth, status }` (`status`: `on_screen`, `off_screen`,
+/// `behind` or `hidden`); unknown ids are skipped.
+#[wasm_bindgen]
+pub fn project_entities(ptr: u64, ids_json: &str) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let window = app.window;
+    let Some(entities) = parse_entity_ids(app.world(), ids_json, "project_entities") else {
+        return "[]".to_string();
+    };
+    let projections = project_all(app.world_mut(), &entities, window);
+    serde_json::to_string(&projections).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Continuously push projections for these entities (JSON array of ids) whenever they
+/// move on screen; `[]` stops tracking. The current projections are sent right away.
+#[wasm_bindgen]
+pub fn track_entity_projections(ptr: u64, ids_json: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let window = app.window;
+    let Some(entities) = parse_entity_ids(app.world(), ids_json, "track_entity_projections") else {
+        return false;
+    };
+    let Some(mut tracking) = app.world_mut().get_resource_mut::<ProjectionTracking>() else {
+        return false;
+    };
+    tracking.entities = entities;
+    tracking.window = Some(window);
+    tracking.last_sent.clear();
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
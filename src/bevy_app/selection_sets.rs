@@ -0,0 +1,100 @@
+//! Named selections: working sets of objects the user can jump between.
+//!
+//! `save_selection_set(ptr, name)` stores the current selection under `name` by
+//! `PersistentId`, so a set survives scene reloads and is saved with the project;
+//! `restore_selection_set(ptr, name)` selects its members that are currently alive.
+//! Selected entities without a persistent id (helpers, gizmos) are left out.
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::persistent_id::{PersistentId, PersistentIndex};
+use crate::web_ffi::apply_selection;
+use crate::{ActivityControl, SelectionState, WorkerApp};
+
+/// Members of each named selection, by name.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelectionSets(pub BTreeMap<String, Vec<Uuid>>);
+
+/// Save the current selection as `name`, replacing a set of that name. Returns the
+/// number of members saved; saving an empty selection deletes the set.
+#[wasm_bindgen]
+pub fn save_selection_set(ptr: u64, name: &str) -> u32 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    let Some(selection) = world.get_resource::<SelectionState>() else {
+        return 0;
+    };
+    let mut members: Vec<Uuid> = selection
+        .selected
+        .keys()
+        .filter_map(|entity| world.get::<PersistentId>(*entity))
+        .map(|id| id.0)
+        .collect();
+    members.sort();
+    let count = members.len();
+    let Some(mut sets) = world.get_resource_mut::<SelectionSets>() else {
+        return 0;
+    };
+    if members.is_empty() {
+        sets.0.remove(name);
+    } else {
+        sets.0.insert(name.to_string(), members);
+    }
+    count as u32
+}
+
+/// Select the live members of set `name` (`mode` as in `set_selection`: "replace",
+/// "add" or "remove"). Returns how many members are alive, or 0 for an unknown set.
+#[wasm_bindgen]
+pub fn restore_selection_set(ptr: u64, name: &str, mode: &str) -> u32 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    let (Some(sets), Some(index)) = (
+        world.get_resource::<SelectionSets>(),
+        world.get_resource::<PersistentIndex>(),
+    ) else {
+        return 0;
+    };
+    let Some(members) = sets.0.get(name) else {
+        warn!("restore_selection_set: no set named {name:?}");
+        return 0;
+    };
+    let entities: Vec<Entity> = members.iter().filter_map(|id| index.entity(*id)).collect();
+    if !apply_selection(world, &entities, mode) {
+        return 0;
+    }
+
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    entities.len() as u32
+}
+
+/// Delete set `name`. Returns false if there was none.
+#[wasm_bindgen]
+pub fn delete_selection_set(ptr: u64, name: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    app.world_mut()
+        .get_resource_mut::<SelectionSets>()
+        .is_some_and(|mut sets| sets.0.remove(name).is_some())
+}
+
+/// JSON object of set name -> member count, names sorted.
+#[wasm_bindgen]
+pub fn list_selection_sets(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(sets) = app.world().get_resource::<SelectionSets>() else {
+        return "{}".to_string();
+    };
+    let counts: BTreeMap<&str, usize> = sets
+        .0
+        .iter()
+        .map(|(name, members)| (name.as_str(), members.len()))
+        .collect();
+    serde_json::to_string(&counts).unwrap_or_else(|_| "{}".to_string())
+}
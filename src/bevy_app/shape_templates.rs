@@ -0,0 +1,207 @@
+//! Overlay shape templates for the inspector: `SpawnShape { kind, params }` spawns a
+//! rect, ellipse, line, arrow or text label into the viewer overlay.
+//!
+//! Paths become ordinary `VectorNode` entities (the same thing the pen tool makes), so
+//! they show up in the entity stream with an editable, reflected `VectorNode` and
+//! `Transform`, and render / pick like authored paths. Text becomes a `VectorText`.
+//! Positions are overlay-world px.
+
+use anyhow::bail;
+use bevy::prelude::*;
+use bevy_remote_inspector::{InspectorContext, command::Execute};
+use serde::Deserialize;
+
+use crate::bevy_app::encode_cache::ShapeFragment;
+use crate::bevy_app::text_edit::VectorText;
+use crate::bevy_app::vector::{PathAnchor, VectorNode};
+
+/// Control-handle length of a quarter-ellipse cubic, as a fraction of the radius.
+const KAPPA: f32 = 0.552_284_8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShapeKind {
+    Rect,
+    Ellipse,
+    Line,
+    Arrow,
+    Text,
+}
+
+impl ShapeKind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "rect" => Some(Self::Rect),
+            "ellipse" => Some(Self::Ellipse),
+            "line" => Some(Self::Line),
+            "arrow" => Some(Self::Arrow),
+            "text" => Some(Self::Text),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Rect => "Rect",
+            Self::Ellipse => "Ellipse",
+            Self::Line => "Line",
+            Self::Arrow => "Arrow",
+            Self::Text => "Text",
+        }
+    }
+}
+
+/// Template parameters; every field is optional.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ShapeParams {
+    /// Shape origin: the center of a rect / ellipse, the start of a line / arrow, the
+    /// anchor of a text label.
+    pub position: Vec2,
+    pub rotation_deg: f32,
+    /// Rect / ellipse extent.
+    pub size: Vec2,
+    /// Line / arrow end, relative to `position`.
+    pub end: Vec2,
+    pub fill: Option<[f32; 4]>,
+    pub stroke: Option<[f32; 4]>,
+    pub stroke_width: Option<f32>,
+    pub text: String,
+    pub font_size: Option<f32>,
+    pub name: Option<String>,
+}
+
+impl Default for ShapeParams {
+    fn default() -> Self {
+        Self {
+            position: Vec2::ZERO,
+            rotation_deg: 0.0,
+            size: Vec2::new(120.0, 80.0),
+            end: Vec2::new(120.0, 0.0),
+            fill: None,
+            stroke: None,
+            stroke_width: None,
+            text: "Text".to_string(),
+            font_size: None,
+            name: None,
+        }
+    }
+}
+
+impl ShapeParams {
+    fn vector_node(&self, kind: ShapeKind) -> VectorNode {
+        let defaults = VectorNode::default();
+        let stroke_width = self.stroke_width.unwrap_or(defaults.stroke_width);
+        let (anchors, closed) = match kind {
+            ShapeKind::Rect => {
+                let h = self.size / 2.0;
+                let corners = [(-h.x, -h.y), (h.x, -h.y), (h.x, h.y), (-h.x, h.y)];
+                let anchors = corners
+                    .into_iter()
+                    .map(|(x, y)| PathAnchor::corner(Vec2::new(x, y)))
+                    .collect();
+                (anchors, true)
+            }
+            ShapeKind::Ellipse => {
+                let r = self.size / 2.0;
+                let anchors = [Vec2::X, Vec2::Y, Vec2::NEG_X, Vec2::NEG_Y]
+                    .into_iter()
+                    .map(|dir| {
+                        // Counter-clockwise tangent at this axis point.
+                        let tangent = dir.perp() * r * KAPPA;
+                        PathAnchor {
+                            position: dir * r,
+                            handle_in: -tangent,
+                            handle_out: tangent,
+                        }
+                    })
+                    .collect();
+                (anchors, true)
+            }
+            ShapeKind::Line => (
+                vec![PathAnchor::corner(Vec2::ZERO), PathAnchor::corner(self.end)],
+                false,
+            ),
+            ShapeKind::Arrow => {
+                // Shaft, then the head drawn as a polyline through the tip.
+                let dir = self.end.try_normalize().unwrap_or(Vec2::X);
+                let head = (stroke_width * 4.0).max(10.0);
+                let back = self.end - dir * head;
+                let side = dir.perp() * head * 0.5;
+                let points = [Vec2::ZERO, self.end, back + side, self.end, back - side];
+                let anchors = points.into_iter().map(PathAnchor::corner).collect();
+                (anchors, false)
+            }
+            ShapeKind::Text => (Vec::new(), false),
+        };
+        VectorNode {
+            anchors,
+            closed,
+            fill: self.fill.filter(|_| closed),
+            stroke: self.stroke.unwrap_or(defaults.stroke),
+            stroke_width,
+            opacity: 1.0,
+        }
+    }
+
+    fn transform(&self) -> Transform {
+        Transform::from_translation(self.position.extend(0.0))
+            .with_rotation(Quat::from_rotation_z(self.rotation_deg.to_radians()))
+    }
+}
+
+/// Spawn an overlay shape from a template; returns the entity bits.
+#[derive(Debug)]
+pub struct SpawnShape {
+    pub kind: ShapeKind,
+    pub params: ShapeParams,
+    pub parent: Option<Entity>,
+}
+
+impl Execute for SpawnShape {
+    type Output = u64;
+
+    fn execute(
+        self,
+        _ctx: &mut InspectorContext,
+        world: &mut World,
+    ) -> anyhow::Result<Self::Output> {
+        if let Some(parent) = self.parent {
+            if world.get_entity(parent).is_err() {
+                bail!("Parent entity does not exist");
+            }
+        }
+        let name = Name::new(
+            self.params
+                .name
+                .clone()
+                .unwrap_or_else(|| self.kind.name().to_string()),
+        );
+        let transform = self.params.transform();
+        let entity = match self.kind {
+            ShapeKind::Text => {
+                let defaults = VectorText::default();
+                let text = VectorText {
+                    text: self.params.text.clone(),
+                    font_size: self.params.font_size.unwrap_or(defaults.font_size),
+                    color: self.params.fill.unwrap_or(defaults.color),
+                };
+                world
+                    .spawn((name, transform, GlobalTransform::default(), text))
+                    .id()
+            }
+            kind => world
+                .spawn((
+                    name,
+                    transform,
+                    GlobalTransform::default(),
+                    self.params.vector_node(kind),
+                    ShapeFragment::default(),
+                ))
+                .id(),
+        };
+        if let Some(parent) = self.parent {
+            world.entity_mut(parent).add_child(entity);
+        }
+        Ok(entity.to_bits())
+    }
+}
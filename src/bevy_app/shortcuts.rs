@@ -0,0 +1,486 @@
+//! Keyboard shortcut registry: chords (`ctrl+z`, `shift+d`, `v`) bound to named actions.
+//!
+//! Bindings are scoped. The scope under the pointer decides which bindings are live:
+//! `overlay` over an overlay shape, `viewport` elsewhere in the viewer panel, `timeline`
+//! over the timeline, `global` everywhere. A chord resolves in the innermost live scope
+//! first (overlay, then viewport, then global), so a scoped binding shadows a global one.
+//! Nothing fires while a text label is being edited.
+//!
+//! A resolved chord sends a `ShortcutTriggered` event and `send_shortcut_from_worker`
+//! to the host. Rust handles `tool.<name>` (see `tools`), `history.undo` and
+//! `history.redo`; any other action name is the host's to handle. Held keys (the camera
+//! controller's WASD) and the pen / text editing Enter and Escape keys stay with their
+//! systems.
+//!
+//! The host replaces the bindings with `set_shortcuts(ptr, json)` and reads them back
+//! with `get_shortcuts(ptr)`. Whenever they change, the conflicts (two actions on one
+//! chord in one scope, or a scoped binding shadowing a broader one) are sent through
+//! `send_shortcut_conflicts_from_worker`.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::history;
+use crate::bevy_app::text_edit::TextEditing;
+use crate::bevy_app::tools::tool_shortcut_system;
+use crate::ffi_inspector_bridge::flush_component_updates_system;
+use crate::panels::{Panels, TIMELINE_PANEL, VIEWER_PANEL};
+use crate::web_ffi::map_key_str_to_bevy_key;
+use crate::{ActivityControl, PointerHits, PointerState, WorkerApp};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_shortcut_from_worker(action: &str, scope: &str);
+    /// JSON array of `ShortcutConflict`.
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_shortcut_conflicts_from_worker(conflicts_json: &str);
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortcutScope {
+    #[default]
+    Global,
+    Viewport,
+    Timeline,
+    Overlay,
+}
+
+impl ShortcutScope {
+    /// The next broader scope, whose bindings apply here too.
+    fn parent(self) -> Option<Self> {
+        match self {
+            ShortcutScope::Global => None,
+            ShortcutScope::Viewport | ShortcutScope::Timeline => Some(ShortcutScope::Global),
+            ShortcutScope::Overlay => Some(ShortcutScope::Viewport),
+        }
+    }
+
+    /// Whether bindings in `self` are live wherever `inner` is.
+    fn encloses(self, inner: Self) -> bool {
+        std::iter::successors(Some(inner), |scope| scope.parent()).any(|scope| scope == self)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ShortcutScope::Global => "global",
+            ShortcutScope::Viewport => "viewport",
+            ShortcutScope::Timeline => "timeline",
+            ShortcutScope::Overlay => "overlay",
+        }
+    }
+}
+
+/// A key with the exact set of modifiers held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chord {
+    pub key: KeyCode,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub meta: bool,
+}
+
+impl Chord {
+    /// Parse `ctrl+shift+z`-style text: modifiers (`ctrl`/`control`, `shift`,
+    /// `alt`/`option`, `meta`/`cmd`/`super`) joined by `+`, then one key named as the
+    /// host names it to `key_down`. Case-insensitive.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts: Vec<String> = text.split('+').map(|p| p.trim().to_lowercase()).collect();
+        let key = parts.pop()?;
+        let (key, _) = map_key_str_to_bevy_key(&key)?;
+        if is_modifier(key) {
+            return None;
+        }
+        let mut chord = Chord {
+            key,
+            ctrl: false,
+            shift: false,
+            alt: false,
+            meta: false,
+        };
+        for part in &parts {
+            match part.as_str() {
+                "ctrl" | "control" => chord.ctrl = true,
+                "shift" => chord.shift = true,
+                "alt" | "option" => chord.alt = true,
+                "meta" | "cmd" | "super" => chord.meta = true,
+                _ => return None,
+            }
+        }
+        Some(chord)
+    }
+}
+
+fn is_modifier(key: KeyCode) -> bool {
+    use KeyCode::*;
+    matches!(
+        key,
+        ShiftLeft | ShiftRight | ControlLeft | ControlRight | AltLeft | AltRight | SuperLeft
+            | SuperRight
+    )
+}
+
+/// One binding as the host sends it: `{ chord, action, scope }`, scope defaulting to
+/// `global`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Shortcut {
+    pub chord: String,
+    pub action: String,
+    #[serde(default)]
+    pub scope: ShortcutScope,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// Both bindings are in the same scope; the first one wins.
+    Duplicate,
+    /// `first` is in a scope inside `second`'s, and hides it there.
+    Shadowed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ShortcutConflict {
+    pub kind: ConflictKind,
+    pub first: Shortcut,
+    pub second: Shortcut,
+}
+
+#[derive(Resource, Debug)]
+pub struct ShortcutRegistry {
+    bindings: Vec<(Chord, Shortcut)>,
+}
+
+impl Default for ShortcutRegistry {
+    fn default() -> Self {
+        let defaults = [
+            ("v", "tool.select"),
+            ("h", "tool.pan"),
+            ("p", "tool.pen"),
+            ("m", "tool.measure"),
+            ("g", "tool.gizmo"),
+            ("ctrl+z", "history.undo"),
+            ("meta+z", "history.undo"),
+            ("ctrl+shift+z", "history.redo"),
+            ("meta+shift+z", "history.redo"),
+            ("ctrl+y", "history.redo"),
+        ];
+        let shortcuts = defaults.map(|(chord, action)| Shortcut {
+            chord: chord.to_string(),
+            action: action.to_string(),
+            scope: ShortcutScope::Global,
+        });
+        Self::from_shortcuts(shortcuts.into()).expect("default shortcuts parse")
+    }
+}
+
+impl ShortcutRegistry {
+    /// Err with the first chord that does not parse.
+    pub fn from_shortcuts(shortcuts: Vec<Shortcut>) -> Result<Self, String> {
+        let bindings = shortcuts
+            .into_iter()
+            .map(|shortcut| match Chord::parse(&shortcut.chord) {
+                Some(chord) => Ok((chord, shortcut)),
+                None => Err(shortcut.chord),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { bindings })
+    }
+
+    pub fn shortcuts(&self) -> impl Iterator<Item = &Shortcut> {
+        self.bindings.iter().map(|(_, shortcut)| shortcut)
+    }
+
+    /// The binding `chord` triggers with the pointer in `scope`: the first one in the
+    /// innermost live scope.
+    pub fn resolve(&self, chord: Chord, scope: ShortcutScope) -> Option<&Shortcut> {
+        std::iter::successors(Some(scope), |scope| scope.parent()).find_map(|scope| {
+            self.bindings
+                .iter()
+                .find(|(c, shortcut)| *c == chord && shortcut.scope == scope)
+                .map(|(_, shortcut)| shortcut)
+        })
+    }
+
+    /// Pairs of bindings on the same chord whose scopes overlap, in binding order.
+    /// Rebinding a chord to the same action is not a conflict.
+    pub fn conflicts(&self) -> Vec<ShortcutConflict> {
+        let mut conflicts = Vec::new();
+        for (i, (chord_a, a)) in self.bindings.iter().enumerate() {
+            for (chord_b, b) in &self.bindings[i + 1..] {
+                if chord_a != chord_b || a.action == b.action {
+                    continue;
+                }
+                let (kind, first, second) = if a.scope == b.scope {
+                    (ConflictKind::Duplicate, a, b)
+                } else if b.scope.encloses(a.scope) {
+                    (ConflictKind::Shadowed, a, b)
+                } else if a.scope.encloses(b.scope) {
+                    (ConflictKind::Shadowed, b, a)
+                } else {
+                    continue;
+                };
+                conflicts.push(ShortcutConflict {
+                    kind,
+                    first: first.clone(),
+                    second: second.clone(),
+                });
+            }
+        }
+        conflicts
+    }
+}
+
+/// A shortcut fired this frame.
+#[derive(Event, Debug, Clone)]
+pub struct ShortcutTriggered {
+    pub action: String,
+    pub scope: ShortcutScope,
+}
+
+pub struct ShortcutsPlugin;
+
+impl Plugin for ShortcutsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShortcutRegistry>()
+            .add_event::<ShortcutTriggered>()
+            .add_systems(
+                Update,
+                (
+                    dispatch_shortcuts.before(tool_shortcut_system),
+                    run_history_shortcuts.after(dispatch_shortcuts),
+                    report_shortcut_conflicts.run_if(resource_changed::<ShortcutRegistry>),
+                ),
+            );
+    }
+}
+
+/// The innermost scope under the pointer.
+fn pointer_scope(pointer: &PointerState, hits: &PointerHits, panels: &Panels) -> ShortcutScope {
+    let over = |id| panels.rect(id).is_some_and(|rect| rect.contains(pointer.screen));
+    if pointer.excluded {
+        ShortcutScope::Global
+    } else if over(VIEWER_PANEL) && !hits.overlay.is_empty() {
+        ShortcutScope::Overlay
+    } else if over(VIEWER_PANEL) {
+        ShortcutScope::Viewport
+    } else if over(TIMELINE_PANEL) {
+        ShortcutScope::Timeline
+    } else {
+        ShortcutScope::Global
+    }
+}
+
+/// Resolve this frame's key presses into `ShortcutTriggered` events.
+fn dispatch_shortcuts(
+    keys: Res<ButtonInput<KeyCode>>,
+    pointer: Res<PointerState>,
+    hits: Res<PointerHits>,
+    panels: Res<Panels>,
+    registry: Res<ShortcutRegistry>,
+    editing: Query<(), With<TextEditing>>,
+    mut triggered: EventWriter<ShortcutTriggered>,
+) {
+    if !editing.is_empty() {
+        return;
+    }
+    let scope = pointer_scope(&pointer, &hits, &panels);
+    let modifiers = pointer.modifiers;
+    for key in keys.get_just_pressed().filter(|key| !is_modifier(**key)) {
+        let chord = Chord {
+            key: *key,
+            ctrl: modifiers.ctrl,
+            shift: modifiers.shift,
+            alt: modifiers.alt,
+            meta: modifiers.meta,
+        };
+        let Some(shortcut) = registry.resolve(chord, scope) else {
+            continue;
+        };
+        send_shortcut_from_worker(&shortcut.action, shortcut.scope.name());
+        triggered.write(ShortcutTriggered {
+            action: shortcut.action.clone(),
+            scope: shortcut.scope,
+        });
+    }
+}
+
+/// `history.undo` / `history.redo`.
+fn run_history_shortcuts(mut triggered: EventReader<ShortcutTriggered>, mut commands: Commands) {
+    for shortcut in triggered.read() {
+        match shortcut.action.as_str() {
+            "history.undo" => commands.queue(|world: &mut World| {
+                flush_component_updates_system(world);
+                history::undo(world);
+            }),
+            "history.redo" => commands.queue(|world: &mut World| {
+                flush_component_updates_system(world);
+                history::redo(world);
+            }),
+            _ => {}
+        }
+    }
+}
+
+fn report_shortcut_conflicts(registry: Res<ShortcutRegistry>) {
+    let conflicts = registry.conflicts();
+    for conflict in &conflicts {
+        warn!(
+            "Shortcut {} is bound to both {} ({}) and {} ({})",
+            conflict.first.chord,
+            conflict.first.action,
+            conflict.first.scope.name(),
+            conflict.second.action,
+            conflict.second.scope.name()
+        );
+    }
+    if let Ok(json) = serde_json::to_string(&conflicts) {
+        send_shortcut_conflicts_from_worker(&json);
+    }
+}
+
+/// Replace every binding with `[{ chord, action, scope }]` (see `Chord::parse` for the
+/// chord syntax; scope is `global`, `viewport`, `timeline` or `overlay`). `"default"`
+/// restores the built-in bindings. False, with the bindings unchanged, on invalid JSON
+/// or an unparseable chord.
+#[wasm_bindgen]
+pub fn set_shortcuts(ptr: u64, shortcuts_json: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let registry = if shortcuts_json == "default" {
+        ShortcutRegistry::default()
+    } else {
+        let parsed = serde_json::from_str::<Vec<Shortcut>>(shortcuts_json)
+            .map_err(|e| e.to_string())
+            .and_then(|shortcuts| {
+                ShortcutRegistry::from_shortcuts(shortcuts).map_err(|c| format!("bad chord {c:?}"))
+            });
+        match parsed {
+            Ok(registry) => registry,
+            Err(e) => {
+                warn!("set_shortcuts: {e}");
+                return false;
+            }
+        }
+    };
+    app.world_mut().insert_resource(registry);
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
+
+/// The bindings as `[{ chord, action, scope }]`, in resolution order.
+#[wasm_bindgen]
+pub fn get_shortcuts(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    app.world()
+        .get_resource::<ShortcutRegistry>()
+        .and_then(|registry| serde_json::to_string(&registry.shortcuts().collect::<Vec<_>>()).ok())
+        .unwrap_or_else(|| "[]".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry(bindings: &[(&str, &str, ShortcutScope)]) -> ShortcutRegistry {
+        let shortcuts = bindings
+            .iter()
+            .map(|(chord, action, scope)| Shortcut {
+                chord: chord.to_string(),
+                action: action.to_string(),
+                scope: *scope,
+            })
+            .collect();
+        ShortcutRegistry::from_shortcuts(shortcuts).unwrap()
+    }
+
+    fn kinds(registry: &ShortcutRegistry) -> Vec<(ConflictKind, String, String)> {
+        registry
+            .conflicts()
+            .into_iter()
+            .map(|c| (c.kind, c.first.action, c.second.action))
+            .collect()
+    }
+
+    #[test]
+    fn chords_parse_modifiers_in_any_order() {
+        assert_eq!(
+            Chord::parse("Ctrl+Shift+Z"),
+            Chord::parse("shift + ctrl + z")
+        );
+        assert_ne!(Chord::parse("ctrl+z"), Chord::parse("ctrl+shift+z"));
+        assert_eq!(Chord::parse("cmd+z"), Chord::parse("meta+z"));
+        assert_eq!(Chord::parse("shift"), None);
+        assert_eq!(Chord::parse("hyper+z"), None);
+        assert_eq!(Chord::parse("ctrl+"), None);
+    }
+
+    #[test]
+    fn default_bindings_do_not_conflict() {
+        assert!(ShortcutRegistry::default().conflicts().is_empty());
+    }
+
+    #[test]
+    fn same_scope_different_action_is_a_duplicate() {
+        let registry = registry(&[
+            ("d", "duplicate", ShortcutScope::Global),
+            ("D", "delete", ShortcutScope::Global),
+            ("d", "duplicate", ShortcutScope::Global),
+        ]);
+        assert_eq!(
+            kinds(&registry),
+            [
+                (ConflictKind::Duplicate, "duplicate".into(), "delete".into()),
+                (ConflictKind::Duplicate, "delete".into(), "duplicate".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn inner_scope_shadows_enclosing_ones_only() {
+        let registry = registry(&[
+            ("d", "global.d", ShortcutScope::Global),
+            ("d", "overlay.d", ShortcutScope::Overlay),
+            ("d", "timeline.d", ShortcutScope::Timeline),
+        ]);
+        // The inner binding comes first whatever the binding order; the timeline and
+        // overlay scopes never overlap.
+        assert_eq!(
+            kinds(&registry),
+            [
+                (
+                    ConflictKind::Shadowed,
+                    "overlay.d".into(),
+                    "global.d".into()
+                ),
+                (
+                    ConflictKind::Shadowed,
+                    "timeline.d".into(),
+                    "global.d".into()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_prefers_the_innermost_scope() {
+        let registry = registry(&[
+            ("d", "global.d", ShortcutScope::Global),
+            ("d", "viewport.d", ShortcutScope::Viewport),
+        ]);
+        let d = Chord::parse("d").unwrap();
+        let action = |scope| registry.resolve(d, scope).map(|s| s.action.as_str());
+        assert_eq!(action(ShortcutScope::Overlay), Some("viewport.d"));
+        assert_eq!(action(ShortcutScope::Viewport), Some("viewport.d"));
+        assert_eq!(action(ShortcutScope::Timeline), Some("global.d"));
+        assert_eq!(
+            registry.resolve(Chord::parse("e").unwrap(), ShortcutScope::Global),
+            None
+        );
+    }
+}
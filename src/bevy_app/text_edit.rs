@@ -0,0 +1,252 @@
+//! In-canvas text editing for overlay `VectorText` labels, IME included.
+//!
+//! Browsers only deliver IME composition to a focused DOM element, so editing is
+//! driven by the host: `begin_text_edit(ptr, entity)` focuses a `VectorText` (adds
+//! `TextEditing`) and reports it through `send_text_edit_from_worker`; the host then
+//! focuses a hidden textarea and forwards `text_input(ptr, text)` for committed text,
+//! `ime_composition(ptr, text, start, end)` for the in-progress composition and
+//! `key_down` / `key_up` for Backspace, Delete, arrows, Home, End, Enter and Escape.
+//! Text and composition become Bevy `Ime` events (through the `InputQueue`), so
+//! `text_edit_system` sees them in order with the keys. Enter and Escape end editing.
+//!
+//! `VectorText` is data only for now (no glyph renderer in the overlay yet); the
+//! inspector and the outbound edit reports show its content.
+
+use bevy::input::ButtonState;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::prelude::*;
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::input_queue::InputQueue;
+use crate::entity_id::live_entity_or_warn;
+use crate::{ActivityControl, WorkerApp};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_text_edit_from_worker(edit_json: &str);
+}
+
+/// A text label in the viewer overlay, placed by the entity `Transform`.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct VectorText {
+    pub text: String,
+    pub font_size: f32,
+    pub color: [f32; 4],
+}
+
+impl Default for VectorText {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            font_size: 16.0,
+            color: [0.1, 0.1, 0.12, 1.0],
+        }
+    }
+}
+
+/// Present on the one `VectorText` being edited.
+#[derive(Component, Debug, Clone, Default)]
+pub struct TextEditing {
+    /// Caret, a byte offset on a char boundary of `VectorText::text`.
+    pub cursor: usize,
+    /// Uncommitted IME composition shown at the caret.
+    pub preedit: String,
+    /// Selected range inside `preedit` (byte offsets), if the IME reports one.
+    pub preedit_cursor: Option<(usize, usize)>,
+}
+
+impl TextEditing {
+    /// The text as it should be shown: `text` with the composition at the caret.
+    pub fn display_text(&self, text: &str) -> String {
+        let (before, after) = text.split_at(self.cursor.min(text.len()));
+        format!("{before}{}{after}", self.preedit)
+    }
+}
+
+fn report(entity: Entity, text: &VectorText, editing: Option<&TextEditing>) {
+    let edit = json!({
+        "entity": entity.to_bits(),
+        "editing": editing.is_some(),
+        "text": text.text,
+        "cursor": editing.map(|e| e.cursor),
+        "preedit": editing.map(|e| e.preedit.as_str()),
+    });
+    send_text_edit_from_worker(&edit.to_string());
+}
+
+fn prev_boundary(text: &str, cursor: usize) -> usize {
+    text[..cursor].char_indices().last().map_or(0, |(i, _)| i)
+}
+
+fn next_boundary(text: &str, cursor: usize) -> usize {
+    text[cursor..]
+        .chars()
+        .next()
+        .map_or(cursor, |c| cursor + c.len_utf8())
+}
+
+/// Byte offset of a UTF-16 index (what JS string offsets count) into `s`.
+fn utf16_to_byte(s: &str, index: u32) -> usize {
+    let mut units = 0;
+    for (byte, c) in s.char_indices() {
+        if units >= index as usize {
+            return byte;
+        }
+        units += c.len_utf16();
+    }
+    s.len()
+}
+
+pub(crate) fn text_edit_system(
+    mut commands: Commands,
+    mut ime_events: EventReader<Ime>,
+    mut key_events: EventReader<KeyboardInput>,
+    mut editing: Query<(Entity, &mut VectorText, &mut TextEditing)>,
+) {
+    let Ok((entity, mut text, mut edit)) = editing.single_mut() else {
+        ime_events.clear();
+        key_events.clear();
+        return;
+    };
+    let mut changed = false;
+    for event in ime_events.read() {
+        match event {
+            Ime::Preedit { value, cursor, .. } => {
+                edit.preedit = value.clone();
+                edit.preedit_cursor = *cursor;
+            }
+            Ime::Commit { value, .. } => {
+                let at = edit.cursor.min(text.text.len());
+                text.text.insert_str(at, value);
+                edit.cursor = at + value.len();
+                edit.preedit.clear();
+                edit.preedit_cursor = None;
+            }
+            Ime::Enabled { .. } | Ime::Disabled { .. } => continue,
+        }
+        changed = true;
+    }
+    for event in key_events.read() {
+        if event.state != ButtonState::Pressed || !edit.preedit.is_empty() {
+            continue;
+        }
+        let cursor = edit.cursor.min(text.text.len());
+        match event.key_code {
+            KeyCode::Backspace if cursor > 0 => {
+                let start = prev_boundary(&text.text, cursor);
+                text.text.replace_range(start..cursor, "");
+                edit.cursor = start;
+            }
+            KeyCode::Delete if cursor < text.text.len() => {
+                let end = next_boundary(&text.text, cursor);
+                text.text.replace_range(cursor..end, "");
+            }
+            KeyCode::ArrowLeft => edit.cursor = prev_boundary(&text.text, cursor),
+            KeyCode::ArrowRight => edit.cursor = next_boundary(&text.text, cursor),
+            KeyCode::Home => edit.cursor = 0,
+            KeyCode::End => edit.cursor = text.text.len(),
+            KeyCode::Enter | KeyCode::Escape => {
+                commands.entity(entity).remove::<TextEditing>();
+                report(entity, &text, None);
+                return;
+            }
+            _ => continue,
+        }
+        changed = true;
+    }
+    if changed {
+        report(entity, &text, Some(&edit));
+    }
+}
+
+fn end_editing(world: &mut World) {
+    let editing: Vec<Entity> = world
+        .query_filtered::<Entity, With<TextEditing>>()
+        .iter(world)
+        .collect();
+    for entity in editing {
+        world.entity_mut(entity).remove::<TextEditing>();
+        if let Some(text) = world.get::<VectorText>(entity) {
+            report(entity, text, None);
+        }
+    }
+}
+
+fn queue_ime(app: &mut WorkerApp, ime: Ime) {
+    let world = app.world_mut();
+    if let Some(mut queue) = world.get_resource_mut::<InputQueue>() {
+        queue.push(ime);
+    }
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+}
+
+/// Start editing a `VectorText` entity (caret at the end), ending any other edit.
+/// Returns false if the entity has no `VectorText`.
+#[wasm_bindgen]
+pub fn begin_text_edit(ptr: u64, id: u64) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let window = app.window;
+    let world = app.world_mut();
+    let Some((entity, cursor)) = live_entity_or_warn(world, id, "begin_text_edit")
+        .and_then(|e| world.get::<VectorText>(e).map(|text| (e, text.text.len())))
+    else {
+        return false;
+    };
+    end_editing(world);
+    let edit = TextEditing {
+        cursor,
+        ..default()
+    };
+    if let Some(text) = world.get::<VectorText>(entity) {
+        report(entity, text, Some(&edit));
+    }
+    world.entity_mut(entity).insert(edit);
+    queue_ime(app, Ime::Enabled { window });
+    true
+}
+
+/// Stop editing (drops any uncommitted composition).
+#[wasm_bindgen]
+pub fn end_text_edit(ptr: u64) {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let window = app.window;
+    end_editing(app.world_mut());
+    queue_ime(app, Ime::Disabled { window });
+}
+
+/// Committed text (typed characters or a finished IME composition) at the caret.
+#[wasm_bindgen]
+pub fn text_input(ptr: u64, text: &str) {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let window = app.window;
+    let value = text.to_string();
+    queue_ime(app, Ime::Commit { window, value });
+}
+
+/// In-progress IME composition; `start` / `end` select inside `text` in JS string
+/// (UTF-16) offsets, negative for none. Empty `text` clears the composition.
+#[wasm_bindgen]
+pub fn ime_composition(ptr: u64, text: &str, start: i32, end: i32) {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let window = app.window;
+    let cursor = (start >= 0 && end >= 0).then(|| {
+        (
+            utf16_to_byte(text, start as u32),
+            utf16_to_byte(text, end as u32),
+        )
+    });
+    let value = text.to_string();
+    queue_ime(
+        app,
+        Ime::Preedit {
+            window,
+            value,
+            cursor,
+        },
+    );
+}
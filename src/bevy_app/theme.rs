@@ -0,0 +1,230 @@
+//! Canvas colors that follow the host page's light / dark theme.
+//!
+//! `Theme` is inserted by `init_app` with the `AppOptions::clear_color` background and
+//! the built-in dark palette, and edited with `set_theme(ptr, json)`, which merges the
+//! given fields like `set_atmosphere`. `apply_theme` pushes a change into `ClearColor`,
+//! the overlay (vello) camera's clear color and the gizmo config; the FPS HUD restyles
+//! itself, and the cached vello scenes fold `Theme::revision` into their content keys so
+//! they re-encode. Colors are sRGB; four-component ones carry alpha.
+
+use bevy::gizmos::aabb::AabbGizmoConfigGroup;
+use bevy::gizmos::config::GizmoConfigStore;
+use bevy::prelude::*;
+use bevy_vello::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::atmosphere::merge_json;
+use crate::bevy_app::history;
+use crate::{ActivityControl, WorkerApp};
+
+/// Timeline panel background and grid lines.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GridColors {
+    pub background: [f32; 4],
+    pub major: [f32; 4],
+    pub minor: [f32; 4],
+}
+
+impl Default for GridColors {
+    fn default() -> Self {
+        Self {
+            background: [0.145, 0.145, 0.152, 1.0],
+            major: [0.5, 0.5, 0.5, 1.0],
+            minor: [0.3, 0.3, 0.3, 1.0],
+        }
+    }
+}
+
+/// Gizmos and 2D handles.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GizmoPalette {
+    /// Line width of every gizmo config group, px.
+    pub line_width: f32,
+    /// Fill of idle transform / pen handles.
+    pub handle: [f32; 4],
+    /// Ruler guides; drawn at reduced alpha unless hovered or dragged.
+    pub guide: [f32; 3],
+    /// AABB debug boxes; `None` colors each entity by its id.
+    pub aabb: Option<[f32; 3]>,
+}
+
+impl Default for GizmoPalette {
+    fn default() -> Self {
+        Self {
+            line_width: 3.0,
+            handle: [1.0, 1.0, 1.0, 1.0],
+            guide: [0.0, 0.7, 0.9],
+            aabb: None,
+        }
+    }
+}
+
+/// Viewer rulers and the timeline time ruler.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RulerColors {
+    pub strip: [f32; 4],
+    pub tick: [f32; 4],
+    pub time_strip: [f32; 4],
+    pub time_tick: [f32; 4],
+}
+
+impl Default for RulerColors {
+    fn default() -> Self {
+        Self {
+            strip: [0.9, 0.9, 0.92, 0.95],
+            tick: [0.35, 0.35, 0.38, 1.0],
+            time_strip: [0.2, 0.2, 0.21, 1.0],
+            time_tick: [0.7, 0.7, 0.72, 1.0],
+        }
+    }
+}
+
+/// FPS HUD panel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HudStyle {
+    pub background: [f32; 4],
+    pub label: [f32; 4],
+    pub value: [f32; 4],
+}
+
+impl Default for HudStyle {
+    fn default() -> Self {
+        Self {
+            background: [0.0, 0.0, 0.0, 0.75],
+            label: [1.0, 1.0, 1.0, 1.0],
+            value: [0.0, 1.0, 1.0, 1.0],
+        }
+    }
+}
+
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// Window clear color (`ClearColor`), also behind the 3D viewer.
+    pub background: [f32; 3],
+    /// Clear color of the full-window overlay camera; `None` keeps it transparent so
+    /// the background and 3D viewer show through.
+    pub overlay_clear: Option<[f32; 4]>,
+    pub grid: GridColors,
+    /// Selected shapes, vector outlines, the marquee and transform / pen handles.
+    pub selection: [f32; 4],
+    /// Hovered shapes.
+    pub hover: [f32; 4],
+    pub gizmo: GizmoPalette,
+    pub ruler: RulerColors,
+    pub hud: HudStyle,
+    /// Bumped on every change; hashed into vello content keys.
+    #[serde(skip)]
+    pub revision: u32,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            background: [0.2, 0.2, 0.2],
+            overlay_clear: None,
+            grid: GridColors::default(),
+            selection: [0.1, 0.4, 1.0, 1.0],
+            hover: [1.0, 0.92, 0.8, 1.0],
+            gizmo: GizmoPalette::default(),
+            ruler: RulerColors::default(),
+            hud: HudStyle::default(),
+            revision: 0,
+        }
+    }
+}
+
+impl Theme {
+    pub fn with_background(background: [f32; 3]) -> Self {
+        Self {
+            background,
+            ..default()
+        }
+    }
+
+    /// Take `updated`'s fields, bumping `revision` if anything changed.
+    pub fn replace(&mut self, mut updated: Theme) {
+        updated.revision = self.revision;
+        if *self != updated {
+            updated.revision = updated.revision.wrapping_add(1);
+            *self = updated;
+        }
+    }
+
+    /// `color` as a Bevy color.
+    pub fn color(color: [f32; 4]) -> Color {
+        Srgba::from_f32_array(color).into()
+    }
+
+    /// `color` with its alpha scaled by `alpha`, for vello.
+    pub fn faded(color: [f32; 4], alpha: f32) -> peniko::Color {
+        let [r, g, b, a] = color;
+        peniko::Color::new([r, g, b, a * alpha])
+    }
+}
+
+/// Push theme changes into the clear colors and gizmo config.
+pub(crate) fn apply_theme(
+    theme: Res<Theme>,
+    mut clear_color: ResMut<ClearColor>,
+    mut overlay_cameras: Query<&mut Camera, With<VelloView>>,
+    gizmo_config: Option<ResMut<GizmoConfigStore>>,
+) {
+    clear_color.0 = Color::srgb_from_array(theme.background);
+    for mut camera in overlay_cameras.iter_mut() {
+        camera.clear_color = match theme.overlay_clear {
+            Some(color) => ClearColorConfig::Custom(Theme::color(color)),
+            None => ClearColorConfig::None,
+        };
+    }
+    if let Some(mut store) = gizmo_config {
+        for (_, config, _) in store.iter_mut() {
+            config.line.width = theme.gizmo.line_width;
+        }
+        store.config_mut::<AabbGizmoConfigGroup>().1.default_color =
+            theme.gizmo.aabb.map(Color::srgb_from_array);
+    }
+}
+
+/// Merge theme fields (`{"background": [r, g, b], "hud": {...}, ...}`, any subset) into
+/// the current theme. Returns false on invalid JSON.
+#[wasm_bindgen]
+pub fn set_theme(ptr: u64, json: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Ok(patch) = serde_json::from_str::<Value>(json) else {
+        return false;
+    };
+    let Some(theme) = app.world().get_resource::<Theme>() else {
+        return false;
+    };
+    let Ok(mut merged) = serde_json::to_value(theme) else {
+        return false;
+    };
+    merge_json(&mut merged, patch);
+    let Ok(updated) = serde_json::from_value::<Theme>(merged) else {
+        return false;
+    };
+    history::edit_resource(app.world_mut(), "Theme", Theme::clone, Theme::replace, |theme| {
+        theme.replace(updated)
+    });
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
+
+/// Current theme as JSON.
+#[wasm_bindgen]
+pub fn get_theme(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    app.world()
+        .get_resource::<Theme>()
+        .and_then(|theme| serde_json::to_string(theme).ok())
+        .unwrap_or_else(|| "{}".to_string())
+}
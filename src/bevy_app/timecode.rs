@@ -0,0 +1,183 @@
+//! Frame rate, frame snapping and SMPTE time display for the timeline.
+//!
+//! `timeline_set_fps(ptr, fps)` picks the display rate (24, 30 or 60) and
+//! `timeline_set_frame_snap(ptr, enabled)` whether the paused playhead and dragged or
+//! dropped keys land on whole frames. The host gets `send_timeline_state_from_worker`
+//! whenever the displayed frame, rate, duration or play state changes:
+//! `{ time, frame, fps, timecode: "HH:MM:SS:FF", duration, duration_frames, playing,
+//! snap }`.
+
+use bevy::prelude::*;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::history;
+use crate::bevy_app::timeline::TimelineState;
+use crate::{ActivityControl, WorkerApp};
+
+/// Display rates offered by the host.
+pub const SUPPORTED_FPS: [u32; 3] = [24, 30, 60];
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_timeline_state_from_worker(state_json: &str);
+}
+
+/// Non-drop SMPTE timecode of frame `frame` at `fps`.
+pub fn timecode(frame: u64, fps: u32) -> String {
+    let fps = u64::from(fps.max(1));
+    let seconds = frame / fps;
+    format!(
+        "{:02}:{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        frame % fps
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct TimelineStatus {
+    time: f64,
+    frame: u64,
+    fps: u32,
+    timecode: String,
+    duration: f64,
+    duration_frames: u64,
+    playing: bool,
+    snap: bool,
+}
+
+impl TimelineStatus {
+    fn of(timeline: &TimelineState) -> Self {
+        let frame = timeline.frame_at(timeline.current_time);
+        Self {
+            time: timeline.current_time,
+            frame,
+            fps: timeline.fps,
+            timecode: timecode(frame, timeline.fps),
+            duration: timeline.duration,
+            duration_frames: timeline.frame_at(timeline.duration),
+            playing: timeline.playing,
+            snap: timeline.snap_to_frames,
+        }
+    }
+
+    /// Same display, ignoring sub-frame time.
+    fn same_display(&self, other: &Self) -> bool {
+        Self {
+            time: other.time,
+            ..self.clone()
+        } == *other
+    }
+}
+
+/// Report the timeline's display state when it changes.
+pub(crate) fn send_timeline_state(
+    timeline: Res<TimelineState>,
+    mut last: Local<Option<TimelineStatus>>,
+) {
+    if !timeline.is_changed() {
+        return;
+    }
+    let status = TimelineStatus::of(&timeline);
+    if last.as_ref().is_some_and(|last| last.same_display(&status)) {
+        return;
+    }
+    if let Ok(json) = serde_json::to_string(&status) {
+        send_timeline_state_from_worker(&json);
+    }
+    *last = Some(status);
+}
+
+fn request_frames(app: &mut WorkerApp) {
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+}
+
+/// Set the timeline display rate; false for a rate not in `SUPPORTED_FPS`.
+#[wasm_bindgen]
+pub fn timeline_set_fps(ptr: u64, fps: u32) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    if !SUPPORTED_FPS.contains(&fps) {
+        warn!("timeline_set_fps: unsupported rate {fps}");
+        return false;
+    }
+    let recorded = history::edit_resource(
+        app.world_mut(),
+        "Timeline frame rate",
+        |timeline: &TimelineState| timeline.fps,
+        |timeline, fps| timeline.fps = fps,
+        |timeline| timeline.fps = fps,
+    );
+    if recorded.is_none() {
+        return false;
+    }
+    request_frames(app);
+    true
+}
+
+/// Snap the paused playhead and edited keys to whole frames.
+#[wasm_bindgen]
+pub fn timeline_set_frame_snap(ptr: u64, enabled: bool) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let recorded = history::edit_resource(
+        app.world_mut(),
+        "Frame snapping",
+        |timeline: &TimelineState| timeline.snap_to_frames,
+        |timeline, enabled| timeline.snap_to_frames = enabled,
+        |timeline| timeline.snap_to_frames = enabled,
+    );
+    if recorded.is_none() {
+        return false;
+    }
+    request_frames(app);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timecode_formats_hours_minutes_seconds_frames() {
+        assert_eq!(timecode(0, 24), "00:00:00:00");
+        assert_eq!(timecode(23, 24), "00:00:00:23");
+        assert_eq!(timecode(24, 24), "00:00:01:00");
+        assert_eq!(timecode(30 * 61 + 5, 30), "00:01:01:05");
+        assert_eq!(timecode(60 * 3600 + 59, 60), "01:00:00:59");
+        // A zero rate counts frames as seconds instead of dividing by zero.
+        assert_eq!(timecode(3, 0), "00:00:03:00");
+    }
+
+    #[test]
+    fn frame_at_counts_whole_frames() {
+        let timeline = TimelineState {
+            fps: 30,
+            ..default()
+        };
+        assert_eq!(timeline.frame_at(-1.0), 0);
+        assert_eq!(timeline.frame_at(0.0), 0);
+        assert_eq!(timeline.frame_at(1.0 / 30.0), 1);
+        assert_eq!(timeline.frame_at(0.999), 29);
+        // Exact frame times computed as frame / fps don't land a frame early.
+        assert_eq!(timeline.frame_at(7.0 / 30.0), 7);
+    }
+
+    #[test]
+    fn snap_rounds_to_frames_only_when_enabled() {
+        let mut timeline = TimelineState {
+            fps: 24,
+            duration: 2.0,
+            ..default()
+        };
+        assert_eq!(timeline.snap(0.51), 0.51);
+        timeline.snap_to_frames = true;
+        assert_eq!(timeline.snap(0.51), 12.0 / 24.0);
+        assert_eq!(timeline.snap(0.53), 13.0 / 24.0);
+        assert_eq!(timeline.snap(5.0), 2.0);
+        assert_eq!(timeline.snap(-0.1), 0.0);
+    }
+}
@@ -1,8 +1,29 @@
 use bevy::prelude::*;
 use bevy::render::view::RenderLayers;
 use bevy_vello::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::panels::{Panels, TIMELINE_PANEL};
+use crate::bevy_app::encode_cache::{ContentHasher, EncodeKey};
+use crate::bevy_app::keyframes::Keyframe;
+use crate::bevy_app::render_timing::VelloEncodeSet;
+use crate::bevy_app::theme::Theme;
+use crate::bevy_app::cross_drag::{CrossWindowDrag, cross_window_drag_system};
+use crate::bevy_app::curve_editor::{
+    CurveEditor, curve_editor_system, render_curve_editor, setup_curve_editor,
+};
+use crate::bevy_app::camera_track::{CinematicPlayback, apply_camera_track};
+use crate::bevy_app::keyframes::apply_track_curves;
+use crate::bevy_app::recording::{TimelineRecorder, record_samples};
+use crate::bevy_app::timecode::send_timeline_state;
+use crate::bevy_app::timeline_events::{TimelineEvent, fire_timeline_events};
+use crate::panels::{PanelRect, Panels, TIMELINE_PANEL};
+
+/// Height of a track row, px.
+const TRACK_HEIGHT: f64 = 18.0;
+/// Gap between track rows and above the first one (clear of the playhead handle), px.
+const TRACK_GAP: f64 = 4.0;
+/// Half-size of an event marker diamond, px.
+const EVENT_MARKER_SIZE: f64 = 5.0;
 
 /// Timeline plugin: draws the timeline into its panel rect (screen space, clipped).
 /// No dedicated camera/window — the shared full-window vello camera presents it.
@@ -11,8 +32,35 @@ pub struct TimelinePlugin;
 impl Plugin for TimelinePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<TimelineState>()
-            .add_systems(Startup, setup_timeline_scenes)
-            .add_systems(Update, (update_timeline_view, render_timeline_grid));
+            .init_resource::<CrossWindowDrag>()
+            .init_resource::<CurveEditor>()
+            .init_resource::<CinematicPlayback>()
+            .init_resource::<TimelineRecorder>()
+            .add_systems(Startup, (setup_timeline_scenes, setup_curve_editor).chain())
+            // After the frame's drags and gizmo edits have moved things.
+            .add_systems(PostUpdate, record_samples)
+            .add_systems(
+                Update,
+                (
+                    update_timeline_view,
+                    cross_window_drag_system.before(render_timeline_grid),
+                    curve_editor_system
+                        .after(update_timeline_view)
+                        .before(render_curve_editor),
+                    apply_track_curves
+                        .after(update_timeline_view)
+                        .after(curve_editor_system),
+                    render_timeline_grid.in_set(VelloEncodeSet),
+                    render_curve_editor
+                        .after(render_timeline_grid)
+                        .in_set(VelloEncodeSet),
+                    apply_camera_track.after(update_timeline_view),
+                    fire_timeline_events.after(update_timeline_view),
+                    send_timeline_state
+                        .after(update_timeline_view)
+                        .after(curve_editor_system),
+                ),
+            );
     }
 }
 
@@ -24,6 +72,120 @@ pub struct TimelineState {
     pub duration: f64,
     pub current_time: f64,
     pub playing: bool,
+    /// Display frame rate (see `bevy_app::timecode`).
+    pub fps: u32,
+    /// Keep the paused playhead and edited keys on whole frames.
+    pub snap_to_frames: bool,
+    /// Track rows, top to bottom. Tracks on the same entity and property are layers,
+    /// combined in this order (see `keyframes::resolve_layers`).
+    pub tracks: Vec<TimelineTrack>,
+    /// Event track, sorted by time (see `bevy_app::timeline_events`).
+    pub events: Vec<TimelineEvent>,
+}
+
+/// How a track combines with the layers above it on the same property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackBlend {
+    /// Blend from the value so far toward this track's value by its weight.
+    #[default]
+    Override,
+    /// Add this track's value, times its weight, to the value so far.
+    Additive,
+}
+
+impl TrackBlend {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "override" => Some(Self::Override),
+            "additive" => Some(Self::Additive),
+            _ => None,
+        }
+    }
+}
+
+/// A timeline row for an entity, created by dragging the entity from the viewer onto
+/// the timeline panel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineTrack {
+    pub entity: Entity,
+    /// Time the track starts at, seconds (where it was dropped).
+    pub start: f64,
+    /// `Transform` field the keyframes animate, as a reflect path.
+    pub property: String,
+    /// Sorted by time; see `bevy_app::keyframes`.
+    pub keyframes: Vec<Keyframe>,
+    /// Influence of the track, 0..=1.
+    pub weight: f32,
+    pub blend: TrackBlend,
+}
+
+impl TimelineState {
+    /// Time under window x position `x` in the timeline panel `rect`, clamped to the
+    /// duration.
+    pub fn time_at(&self, rect: PanelRect, x: f32) -> f64 {
+        let t = ((x - rect.x) / rect.w.max(1.0)) as f64;
+        self.snap((t * self.duration).clamp(0.0, self.duration))
+    }
+
+    /// Frame shown at `time`.
+    pub fn frame_at(&self, time: f64) -> u64 {
+        // Nudge so times computed as frame / fps don't land a frame early.
+        (time.max(0.0) * self.fps as f64 + 1e-6).floor() as u64
+    }
+
+    /// `time` rounded to the nearest frame when frame snapping is on.
+    pub fn snap(&self, time: f64) -> f64 {
+        if !self.snap_to_frames || self.fps == 0 {
+            return time;
+        }
+        let fps = self.fps as f64;
+        ((time * fps).round() / fps).clamp(0.0, self.duration)
+    }
+
+    pub fn track_mut(&mut self, entity: Entity) -> Option<&mut TimelineTrack> {
+        self.tracks.iter_mut().find(|track| track.entity == entity)
+    }
+
+    /// Add a track for `entity` starting at `start`, or move its existing track there.
+    pub fn add_track(&mut self, entity: Entity, start: f64) {
+        match self.tracks.iter_mut().find(|track| track.entity == entity) {
+            Some(track) => track.start = start,
+            None => self.tracks.push(TimelineTrack {
+                entity,
+                start,
+                property: "translation.y".to_string(),
+                keyframes: Vec::new(),
+                weight: 1.0,
+                blend: TrackBlend::Override,
+            }),
+        }
+    }
+
+    /// Replace the keyframes of `entity`'s track for `property`, adding a track (starting
+    /// at the first key) if the entity has none for that property.
+    pub fn set_property_track(
+        &mut self,
+        entity: Entity,
+        property: &str,
+        keyframes: Vec<Keyframe>,
+    ) {
+        let existing = self
+            .tracks
+            .iter_mut()
+            .find(|track| track.entity == entity && track.property == property);
+        match existing {
+            Some(track) => track.keyframes = keyframes,
+            None => self.tracks.push(TimelineTrack {
+                entity,
+                start: keyframes.first().map_or(0.0, |k| k.time),
+                property: property.to_string(),
+                keyframes,
+                weight: 1.0,
+                blend: TrackBlend::Override,
+            }),
+        }
+    }
 }
 
 impl Default for TimelineState {
@@ -34,6 +196,10 @@ impl Default for TimelineState {
             duration: 30.0, // 30 seconds default
             current_time: 0.0,
             playing: false,
+            fps: 30,
+            snap_to_frames: false,
+            tracks: Vec::new(),
+            events: Vec::new(),
         }
     }
 }
@@ -57,6 +223,7 @@ fn setup_timeline_scenes(mut commands: Commands) {
         VelloScreenSpace,
         RenderLayers::layer(1),
         TimelineBackgroundScene,
+        EncodeKey::default(),
     ));
     commands.spawn((
         VelloScene::new(),
@@ -69,6 +236,7 @@ fn setup_timeline_scenes(mut commands: Commands) {
         VelloScreenSpace,
         RenderLayers::layer(1),
         TimelinePlayheadScene,
+        EncodeKey::default(),
     ));
 }
 
@@ -81,40 +249,87 @@ pub fn update_timeline_view(mut timeline: ResMut<TimelineState>, time: Res<Time>
             timeline.current_time = timeline.duration;
             timeline.playing = false; // Stop at end
         }
+    } else {
+        let snapped = timeline.snap(timeline.current_time);
+        if snapped != timeline.current_time {
+            timeline.current_time = snapped;
+        }
     }
 }
 
+type BackgroundSceneFilter = (
+    With<TimelineBackgroundScene>,
+    Without<TimelineGridScene>,
+    Without<TimelinePlayheadScene>,
+);
+type GridSceneFilter = (
+    With<TimelineGridScene>,
+    Without<TimelinePlayheadScene>,
+    Without<TimelineBackgroundScene>,
+);
+type PlayheadSceneFilter = (
+    With<TimelinePlayheadScene>,
+    Without<TimelineGridScene>,
+    Without<TimelineBackgroundScene>,
+);
+
 /// Render the timeline background, grid and playhead into the timeline panel rect.
+///
+/// Background + grid only depend on the panel rect and duration, the playhead on the
+/// current time as well; each group is re-encoded only when its inputs change (the
+/// background scene's `EncodeKey` gates both background and grid).
 pub fn render_timeline_grid(
-    mut bg_scene: Query<
-        &mut VelloScene,
-        (
-            With<TimelineBackgroundScene>,
-            Without<TimelineGridScene>,
-            Without<TimelinePlayheadScene>,
-        ),
-    >,
-    mut grid_scene: Query<
-        &mut VelloScene,
-        (With<TimelineGridScene>, Without<TimelinePlayheadScene>),
-    >,
-    mut playhead_scene: Query<
-        &mut VelloScene,
-        (With<TimelinePlayheadScene>, Without<TimelineGridScene>),
-    >,
+    mut bg_scene: Query<(&mut VelloScene, &mut EncodeKey), BackgroundSceneFilter>,
+    mut grid_scene: Query<&mut VelloScene, GridSceneFilter>,
+    mut playhead_scene: Query<(&mut VelloScene, &mut EncodeKey), PlayheadSceneFilter>,
     timeline: Res<TimelineState>,
     panels: Res<Panels>,
+    theme: Res<Theme>,
 ) {
     let rect = panels.rect(TIMELINE_PANEL);
 
+    let mut static_hasher = ContentHasher::default()
+        .rect(rect)
+        .f64(timeline.duration)
+        .u32(theme.revision)
+        .u32(timeline.tracks.len() as u32);
+    for track in &timeline.tracks {
+        static_hasher = static_hasher
+            .u32(track.entity.index())
+            .f64(track.start)
+            .f32(track.weight)
+            .variant(&track.blend);
+    }
+    static_hasher = static_hasher.u32(timeline.events.len() as u32);
+    for event in &timeline.events {
+        static_hasher = static_hasher.f64(event.time);
+    }
+    let static_key = static_hasher.finish();
+    let static_dirty = match bg_scene.single_mut() {
+        Ok((_, mut key)) => key.update(static_key),
+        Err(_) => false,
+    };
+    let playhead_key = ContentHasher::default()
+        .rect(rect)
+        .f64(timeline.duration)
+        .f64(timeline.current_time)
+        .finish();
+    let playhead_dirty = match playhead_scene.single_mut() {
+        Ok((_, mut key)) => key.update(playhead_key),
+        Err(_) => false,
+    };
+    if !static_dirty && !playhead_dirty {
+        return;
+    }
+
     // Background (replaces the old timeline camera's clear color)
-    if let Ok(mut scene) = bg_scene.single_mut() {
+    if let Some((mut scene, _)) = bg_scene.single_mut().ok().filter(|_| static_dirty) {
         scene.reset();
         if let Some(rect) = rect {
             scene.fill(
                 peniko::Fill::NonZero,
                 kurbo::Affine::IDENTITY,
-                peniko::Color::new([0.145, 0.145, 0.152, 1.0]),
+                peniko::Color::new(theme.grid.background),
                 None,
                 &rect.to_kurbo(),
             );
@@ -125,7 +340,7 @@ pub fn render_timeline_grid(
         for mut scene in grid_scene.iter_mut() {
             scene.reset();
         }
-        for mut scene in playhead_scene.iter_mut() {
+        for (mut scene, _) in playhead_scene.iter_mut() {
             scene.reset();
         }
         return;
@@ -138,7 +353,7 @@ pub fn render_timeline_grid(
     let width = rect.w as f64;
 
     // Render grid
-    if let Ok(mut scene) = grid_scene.single_mut() {
+    if let Some(mut scene) = grid_scene.single_mut().ok().filter(|_| static_dirty) {
         scene.reset();
         scene.push_layer(peniko::Mix::Clip, 1.0, kurbo::Affine::IDENTITY, &clip);
 
@@ -157,7 +372,7 @@ pub fn render_timeline_grid(
                 scene.stroke(
                     &kurbo::Stroke::new(2.0),
                     kurbo::Affine::IDENTITY,
-                    peniko::Color::new([0.5, 0.5, 0.5, 1.0]),
+                    peniko::Color::new(theme.grid.major),
                     None,
                     &line,
                 );
@@ -166,7 +381,7 @@ pub fn render_timeline_grid(
                 scene.stroke(
                     &kurbo::Stroke::new(1.0),
                     kurbo::Affine::IDENTITY,
-                    peniko::Color::new([0.3, 0.3, 0.3, 1.0]),
+                    peniko::Color::new(theme.grid.minor),
                     None,
                     &line,
                 );
@@ -174,11 +389,48 @@ pub fn render_timeline_grid(
 
             time += 0.5; // Check every 0.5 seconds for grid lines
         }
+
+        // Track rows: a bar from each track's start to the end of the timeline, fainter
+        // for lighter layers; additive layers use the hover color.
+        for (row, track) in timeline.tracks.iter().enumerate() {
+            let y = top + TRACK_GAP + row as f64 * (TRACK_HEIGHT + TRACK_GAP);
+            let x = left + track.start / time_per_pixel;
+            let color = match track.blend {
+                TrackBlend::Override => theme.selection,
+                TrackBlend::Additive => theme.hover,
+            };
+            scene.fill(
+                peniko::Fill::NonZero,
+                kurbo::Affine::IDENTITY,
+                Theme::faded(color, 0.6 * track.weight.clamp(0.25, 1.0)),
+                None,
+                &kurbo::RoundedRect::new(x, y, left + width, y + TRACK_HEIGHT, 3.0),
+            );
+        }
+
+        // Event markers: diamonds along the bottom edge
+        for event in &timeline.events {
+            let x = left + event.time / time_per_pixel;
+            let y = bottom - EVENT_MARKER_SIZE - 2.0;
+            let mut diamond = kurbo::BezPath::new();
+            diamond.move_to((x, y - EVENT_MARKER_SIZE));
+            diamond.line_to((x + EVENT_MARKER_SIZE, y));
+            diamond.line_to((x, y + EVENT_MARKER_SIZE));
+            diamond.line_to((x - EVENT_MARKER_SIZE, y));
+            diamond.close_path();
+            scene.fill(
+                peniko::Fill::NonZero,
+                kurbo::Affine::IDENTITY,
+                peniko::Color::new(theme.hover),
+                None,
+                &diamond,
+            );
+        }
         scene.pop_layer();
     }
 
     // Render playhead
-    if let Ok(mut scene) = playhead_scene.single_mut() {
+    if let Some((mut scene, _)) = playhead_scene.single_mut().ok().filter(|_| playhead_dirty) {
         scene.reset();
         scene.push_layer(peniko::Mix::Clip, 1.0, kurbo::Affine::IDENTITY, &clip);
 
@@ -0,0 +1,145 @@
+//! Event track: timeline keys carrying arbitrary JSON payloads for the host.
+//!
+//! When the playhead crosses an event during playback, the host gets
+//! `send_timeline_event_from_worker(time, payload_json)`, to sync UI, sound or analytics
+//! to the animation. Crossing means the event lies in (previous time, current time] of
+//! a played frame, or exactly at the time playback started from. Scrubbing and jumps
+//! while paused fire nothing. Events show as markers along the bottom of the timeline.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::history;
+use crate::bevy_app::timeline::TimelineState;
+use crate::{ActivityControl, WorkerApp};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_timeline_event_from_worker(time: f64, payload_json: &str);
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineEvent {
+    /// Seconds.
+    pub time: f64,
+    #[serde(default)]
+    pub payload: serde_json::Value,
+}
+
+/// Sort events by time (stable, so events at the same time fire in insertion order).
+pub fn sort_events(events: &mut [TimelineEvent]) {
+    events.sort_by(|a, b| a.time.total_cmp(&b.time));
+}
+
+/// Timeline time and play state as of the previous frame.
+#[derive(Default)]
+pub(crate) struct PlaybackCursor {
+    playing: bool,
+    time: f64,
+}
+
+/// Fire the events the playhead crossed since the previous frame of playback.
+pub(crate) fn fire_timeline_events(
+    timeline: Res<TimelineState>,
+    mut cursor: Local<PlaybackCursor>,
+) {
+    let (from, now) = (cursor.time, timeline.current_time);
+    // Playback stops on the frame that reaches the end, so that frame still counts.
+    let advancing = timeline.playing || cursor.playing;
+    let started = timeline.playing && !cursor.playing;
+    *cursor = PlaybackCursor {
+        playing: timeline.playing,
+        time: now,
+    };
+    if !advancing {
+        return;
+    }
+    let crossed = |time: f64| (time > from || (started && time == from)) && time <= now;
+    for event in timeline.events.iter().filter(|event| crossed(event.time)) {
+        let payload = serde_json::to_string(&event.payload).unwrap_or_default();
+        send_timeline_event_from_worker(event.time, &payload);
+    }
+}
+
+/// Edit the event track as one undoable step.
+fn edit_events<T>(
+    app: &mut WorkerApp,
+    label: &str,
+    edit: impl FnOnce(&mut TimelineState) -> T,
+) -> Option<T> {
+    history::edit_resource(
+        app.world_mut(),
+        label,
+        |timeline: &TimelineState| timeline.events.clone(),
+        |timeline, events| timeline.events = events,
+        edit,
+    )
+}
+
+fn request_frames(app: &mut WorkerApp) {
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+}
+
+/// Add an event at `time` with a JSON payload; returns its index after sorting, or -1
+/// on invalid JSON.
+#[wasm_bindgen]
+pub fn timeline_add_event(ptr: u64, time: f64, payload_json: &str) -> i32 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Ok(payload) = serde_json::from_str::<serde_json::Value>(payload_json) else {
+        return -1;
+    };
+    let added = edit_events(app, "Add timeline event", |timeline| {
+        let time = timeline.snap(time.clamp(0.0, timeline.duration));
+        let index = timeline.events.partition_point(|e| e.time <= time);
+        timeline.events.insert(index, TimelineEvent { time, payload });
+        index
+    });
+    let Some(index) = added else {
+        return -1;
+    };
+    request_frames(app);
+    index as i32
+}
+
+/// Replace all events with a JSON array of `{ time, payload }`. False on invalid JSON.
+#[wasm_bindgen]
+pub fn timeline_set_events(ptr: u64, events_json: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Ok(mut events) = serde_json::from_str::<Vec<TimelineEvent>>(events_json) else {
+        return false;
+    };
+    sort_events(&mut events);
+    if edit_events(app, "Set timeline events", |timeline| timeline.events = events).is_none() {
+        return false;
+    }
+    request_frames(app);
+    true
+}
+
+/// All events as a JSON array of `{ time, payload }`, sorted by time.
+#[wasm_bindgen]
+pub fn timeline_get_events(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    app.world()
+        .get_resource::<TimelineState>()
+        .and_then(|timeline| serde_json::to_string(&timeline.events).ok())
+        .unwrap_or_else(|| "[]".to_string())
+}
+
+/// Remove event `index`.
+#[wasm_bindgen]
+pub fn timeline_remove_event(ptr: u64, index: usize) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let removed = edit_events(app, "Remove timeline event", |timeline| {
+        (index < timeline.events.len()).then(|| timeline.events.remove(index))
+    });
+    if removed.flatten().is_none() {
+        return false;
+    }
+    request_frames(app);
+    true
+}
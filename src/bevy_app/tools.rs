@@ -0,0 +1,263 @@
+//! Tool mode state machine shared between Rust and the host UI.
+//!
+//! Exactly one tool is active; it decides where the left button goes:
+//! - `Select`: overlay shape selection / drag / marquee / transform handles, guides and
+//!   3D picking + drag.
+//! - `Pan`: left-drag pans the 3D viewer camera.
+//! - `Pen`: draws new vector paths (see `pen_tool`).
+//! - `Measure`: left-drag measures a distance in overlay-world units.
+//! - `Gizmo`: 3D picking + drag only; overlay shapes ignore the pointer.
+//!
+//! The host switches tools with `set_tool(ptr, name)` or the `tool.<name>` shortcuts
+//! (V/H/P/M/G by default, see `shortcuts`); every change is echoed through
+//! `send_tool_changed_from_worker(name)` and the matching CSS cursor through
+//! `send_cursor_from_worker(cursor)`.
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy_vello::prelude::*;
+use wasm_bindgen::prelude::*;
+
+use crate::PointerState;
+use crate::bevy_app::encode_cache::{ContentHasher, EncodeKey};
+use crate::bevy_app::overlay2d::SimpleMouseState;
+use crate::bevy_app::scene3d::MainCamera3D;
+use crate::bevy_app::shortcuts::ShortcutTriggered;
+use crate::panels::{Panels, VIEWER_PANEL, overlay_affine};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_tool_changed_from_worker(name: &str);
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_cursor_from_worker(cursor: &str);
+    /// Current measurement (overlay-world units, degrees counter-clockwise from +x).
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_measurement_from_worker(length: f32, angle_deg: f32);
+}
+
+/// World units the camera moves per pointer pixel while panning.
+const PAN_SPEED: f32 = 0.01;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Tool {
+    #[default]
+    Select,
+    Pan,
+    Pen,
+    Measure,
+    Gizmo,
+}
+
+impl Tool {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "select" => Some(Tool::Select),
+            "pan" => Some(Tool::Pan),
+            "pen" => Some(Tool::Pen),
+            "measure" => Some(Tool::Measure),
+            "gizmo" => Some(Tool::Gizmo),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Tool::Select => "select",
+            Tool::Pan => "pan",
+            Tool::Pen => "pen",
+            Tool::Measure => "measure",
+            Tool::Gizmo => "gizmo",
+        }
+    }
+
+    /// CSS cursor for the canvas; `dragging` is true while the left button is held.
+    fn cursor(self, dragging: bool) -> &'static str {
+        match self {
+            Tool::Select => "default",
+            Tool::Pan if dragging => "grabbing",
+            Tool::Pan => "grab",
+            Tool::Pen | Tool::Measure => "crosshair",
+            Tool::Gizmo => "move",
+        }
+    }
+
+    /// Whether 3D picking / drag reacts to the left button.
+    pub fn picks_3d(self) -> bool {
+        matches!(self, Tool::Select | Tool::Gizmo)
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct ToolState {
+    pub active: Tool,
+}
+
+/// Switch tools from `tool.<name>` shortcuts.
+pub(crate) fn tool_shortcut_system(
+    mut shortcuts: EventReader<ShortcutTriggered>,
+    mut tools: ResMut<ToolState>,
+) {
+    let tool = shortcuts
+        .read()
+        .filter_map(|s| s.action.strip_prefix("tool."))
+        .filter_map(Tool::parse)
+        .last();
+    if let Some(tool) = tool.filter(|t| *t != tools.active) {
+        tools.active = tool;
+    }
+}
+
+/// Claim left presses for tools that do not use the overlay select path, so guides and
+/// overlay shapes ignore them. Runs right after `simple_mouse_state_system`; the pen
+/// claims its own presses.
+pub(crate) fn tool_pointer_routing_system(
+    tools: Res<ToolState>,
+    mut mouse: ResMut<SimpleMouseState>,
+) {
+    if mouse.just_pressed && matches!(tools.active, Tool::Pan | Tool::Measure | Tool::Gizmo) {
+        mouse.captured = true;
+    }
+}
+
+/// Echo tool and cursor changes to the host.
+pub(crate) fn outbound_tool_system(
+    tools: Res<ToolState>,
+    mouse: Res<SimpleMouseState>,
+    mut last_tool: Local<Option<Tool>>,
+    mut last_cursor: Local<Option<&'static str>>,
+) {
+    if *last_tool != Some(tools.active) {
+        *last_tool = Some(tools.active);
+        send_tool_changed_from_worker(tools.active.name());
+    }
+    let cursor = tools.active.cursor(mouse.left_pressed);
+    if *last_cursor != Some(cursor) {
+        *last_cursor = Some(cursor);
+        send_cursor_from_worker(cursor);
+    }
+}
+
+/// Pan tool: left-drag slides the viewer camera in its view plane.
+pub(crate) fn pan_tool_system(
+    tools: Res<ToolState>,
+    mouse: Res<SimpleMouseState>,
+    pointer: Res<PointerState>,
+    mut cameras: Query<&mut Transform, With<MainCamera3D>>,
+) {
+    if tools.active != Tool::Pan || !mouse.left_pressed || pointer.delta == Vec2::ZERO {
+        return;
+    }
+    let Ok(mut transform) = cameras.single_mut() else {
+        return;
+    };
+    let right = transform.right().as_vec3();
+    let up = transform.up().as_vec3();
+    // Screen y grows downwards; drag the scene with the pointer.
+    transform.translation += (-right * pointer.delta.x + up * pointer.delta.y) * PAN_SPEED;
+}
+
+/// Measure tool: segment from press point to the pointer (overlay-world units).
+#[derive(Resource, Debug, Default)]
+pub(crate) struct MeasureState {
+    start: Option<Vec2>,
+    end: Option<Vec2>,
+}
+
+#[derive(Component)]
+pub(crate) struct MeasureScene;
+
+pub(crate) fn setup_measure_tool(mut commands: Commands) {
+    // Editor chrome: not an `OverlayLayer`, so exports skip it.
+    commands.spawn((
+        VelloScene::new(),
+        VelloScreenSpace,
+        RenderLayers::layer(1),
+        MeasureScene,
+        EncodeKey::default(),
+    ));
+}
+
+pub(crate) fn measure_tool_system(
+    tools: Res<ToolState>,
+    mouse: Res<SimpleMouseState>,
+    pointer: Res<PointerState>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut measure: ResMut<MeasureState>,
+) {
+    if tools.active != Tool::Measure || keys.just_pressed(KeyCode::Escape) {
+        if measure.start.is_some() {
+            *measure = MeasureState::default();
+        }
+        return;
+    }
+    let Some(p) = pointer.overlay_world else {
+        return;
+    };
+    if mouse.just_pressed {
+        measure.start = Some(p);
+        measure.end = Some(p);
+    } else if mouse.left_pressed && measure.start.is_some() && measure.end != Some(p) {
+        measure.end = Some(p);
+    } else {
+        return;
+    }
+    if let (Some(a), Some(b)) = (measure.start, measure.end) {
+        let d = b - a;
+        send_measurement_from_worker(d.length(), d.to_angle().to_degrees());
+    }
+}
+
+pub(crate) fn render_measure(
+    mut q_scene: Query<(&mut VelloScene, &mut EncodeKey), With<MeasureScene>>,
+    measure: Res<MeasureState>,
+    panels: Res<Panels>,
+) {
+    let Ok((mut scene, mut key)) = q_scene.single_mut() else {
+        return;
+    };
+    let panel_rect = panels.rect(VIEWER_PANEL);
+    let segment = measure.start.zip(measure.end);
+    let mut hasher = ContentHasher::default().rect(panel_rect);
+    if let Some((a, b)) = segment {
+        hasher = hasher.vec2(a).vec2(b);
+    }
+    if !key.update(hasher.finish()) {
+        return;
+    }
+    scene.reset();
+    let (Some(panel_rect), Some((a, b))) = (panel_rect, segment) else {
+        return;
+    };
+    let base = overlay_affine(panel_rect);
+    let to_screen = |v: Vec2| base * kurbo::Point::new(v.x as f64, v.y as f64);
+    let (sa, sb) = (to_screen(a), to_screen(b));
+    let color = peniko::Color::new([0.95, 0.35, 0.1, 1.0]);
+
+    let mut path = kurbo::BezPath::new();
+    path.move_to(sa);
+    path.line_to(sb);
+    // End ticks perpendicular to the segment.
+    let dir = (sb - sa).normalize();
+    if dir.is_finite() {
+        let n = kurbo::Vec2::new(-dir.y, dir.x) * 6.0;
+        for p in [sa, sb] {
+            path.move_to(p - n);
+            path.line_to(p + n);
+        }
+    }
+    scene.push_layer(
+        peniko::Mix::Clip,
+        1.0,
+        kurbo::Affine::IDENTITY,
+        &panel_rect.to_kurbo(),
+    );
+    scene.stroke(
+        &kurbo::Stroke::new(1.5),
+        kurbo::Affine::IDENTITY,
+        color,
+        None,
+        &path,
+    );
+    scene.pop_layer();
+}
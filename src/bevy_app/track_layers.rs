@@ -0,0 +1,119 @@
+//! FFI for layered timeline tracks.
+//!
+//! Several tracks may animate the same entity property; `keyframes::apply_track_curves`
+//! resolves them top to bottom by blend mode and weight. Layers are addressed by their
+//! row index in `TimelineState::tracks`, as listed by `get_timeline_tracks`.
+
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::keyframes::{self, Keyframe};
+use crate::bevy_app::timeline::{TimelineState, TimelineTrack, TrackBlend};
+use crate::entity_id::live_entity_or_warn;
+use crate::{ActivityControl, WorkerApp};
+
+fn request_frames(app: &mut WorkerApp) {
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+}
+
+/// All tracks: `[{ "index", "entity", "property", "start", "weight", "blend", "keys" }]`,
+/// `keys` being the keyframe count.
+#[wasm_bindgen]
+pub fn get_timeline_tracks(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(timeline) = app.world().get_resource::<TimelineState>() else {
+        return "[]".to_string();
+    };
+    let tracks = timeline
+        .tracks
+        .iter()
+        .enumerate()
+        .map(|(index, track)| {
+            json!({
+                "index": index,
+                "entity": track.entity.to_bits(),
+                "property": track.property,
+                "start": track.start,
+                "weight": track.weight,
+                "blend": track.blend,
+                "keys": track.keyframes.len(),
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::Value::from(tracks).to_string()
+}
+
+/// Add a layer on `entity_id`'s `property` ("override" or "additive", weight 0..=1)
+/// below its existing tracks, with keyframes from a JSON array of `Keyframe`. Returns
+/// the new track's index, or -1 on invalid input.
+#[wasm_bindgen]
+pub fn add_track_layer(
+    ptr: u64,
+    entity_id: u64,
+    property: &str,
+    blend: &str,
+    weight: f32,
+    keyframes_json: &str,
+) -> i32 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(entity) = live_entity_or_warn(app.world(), entity_id, "add_track_layer") else {
+        return -1;
+    };
+    let Some(blend) = TrackBlend::parse(blend) else {
+        return -1;
+    };
+    let Ok(mut keys) = serde_json::from_str::<Vec<Keyframe>>(keyframes_json) else {
+        return -1;
+    };
+    keyframes::sort_keys(&mut keys);
+    let Some(mut timeline) = app.world_mut().get_resource_mut::<TimelineState>() else {
+        return -1;
+    };
+    timeline.tracks.push(TimelineTrack {
+        entity,
+        start: keys.first().map_or(0.0, |k| k.time),
+        property: property.to_string(),
+        keyframes: keys,
+        weight: weight.clamp(0.0, 1.0),
+        blend,
+    });
+    let index = timeline.tracks.len() - 1;
+    request_frames(app);
+    index as i32
+}
+
+/// Set the blend mode ("override" or "additive") and weight (0..=1) of track `index`.
+#[wasm_bindgen]
+pub fn set_track_blend(ptr: u64, index: usize, blend: &str, weight: f32) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(blend) = TrackBlend::parse(blend) else {
+        return false;
+    };
+    let Some(mut timeline) = app.world_mut().get_resource_mut::<TimelineState>() else {
+        return false;
+    };
+    let Some(track) = timeline.tracks.get_mut(index) else {
+        return false;
+    };
+    track.blend = blend;
+    track.weight = weight.clamp(0.0, 1.0);
+    request_frames(app);
+    true
+}
+
+/// Remove track `index`.
+#[wasm_bindgen]
+pub fn remove_track(ptr: u64, index: usize) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(mut timeline) = app.world_mut().get_resource_mut::<TimelineState>() else {
+        return false;
+    };
+    if index >= timeline.tracks.len() {
+        return false;
+    }
+    timeline.tracks.remove(index);
+    request_frames(app);
+    true
+}
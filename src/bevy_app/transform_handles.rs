@@ -0,0 +1,388 @@
+//! Scale / rotate handles for selected overlay shapes.
+//!
+//! Eight handles sit on the selection box: corners scale both axes (shift = uniform),
+//! edges scale one axis, each relative to the opposite side. A ninth handle above the
+//! top edge rotates the selection around the box center (shift = 15° steps). The box is
+//! the shape's own oriented rectangle for a single selection and the overlay-axis
+//! bounds of all selected shapes otherwise. Gestures read the pointer from
+//! `PointerState`, claim the press via `SimpleMouseState::captured` so the shape
+//! drag/marquee ignores it, and push one `History` entry on release.
+
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy_vello::prelude::*;
+
+use crate::PointerState;
+use crate::bevy_app::encode_cache::{ContentHasher, EncodeKey};
+use crate::bevy_app::history::{History, HistoryEntry, HistoryOp};
+use crate::bevy_app::overlay2d::{MiniSquare, MiniSquareState, SimpleMouseState, z_angle};
+use crate::bevy_app::theme::Theme;
+use crate::bevy_app::tools::{Tool, ToolState};
+use crate::panels::{Panels, VIEWER_PANEL, overlay_affine};
+
+/// Handle square size (px; overlay-world units map 1:1 to px).
+const HANDLE_SIZE: f64 = 8.0;
+/// Pointer distance within which a handle can be grabbed.
+const HANDLE_GRAB_DISTANCE: f32 = 7.0;
+/// Distance of the rotation handle above the top edge.
+const ROTATE_HANDLE_OFFSET: f32 = 24.0;
+const ROTATE_SNAP: f32 = PI / 12.0;
+/// Scale factors are clamped here so a gesture can never collapse a shape.
+const MIN_SCALE_FACTOR: f32 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Handle {
+    /// Scale handle at box-local position (`sx`, `sy`) ∈ {-1, 0, 1}² (not both zero).
+    Scale {
+        sx: i8,
+        sy: i8,
+    },
+    Rotate,
+}
+
+/// Hit-test priority order: rotation, corners, edges.
+const HANDLES: [Handle; 9] = [
+    Handle::Rotate,
+    Handle::Scale { sx: -1, sy: -1 },
+    Handle::Scale { sx: 1, sy: -1 },
+    Handle::Scale { sx: 1, sy: 1 },
+    Handle::Scale { sx: -1, sy: 1 },
+    Handle::Scale { sx: 0, sy: -1 },
+    Handle::Scale { sx: 1, sy: 0 },
+    Handle::Scale { sx: 0, sy: 1 },
+    Handle::Scale { sx: -1, sy: 0 },
+];
+
+/// Oriented selection rectangle in overlay-world space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SelectionBox {
+    center: Vec2,
+    half: Vec2,
+    angle: f32,
+}
+
+impl SelectionBox {
+    fn to_world(self, local: Vec2) -> Vec2 {
+        self.center + Vec2::from_angle(self.angle).rotate(local)
+    }
+
+    fn to_local(self, world: Vec2) -> Vec2 {
+        Vec2::from_angle(-self.angle).rotate(world - self.center)
+    }
+
+    fn handle_position(self, handle: Handle) -> Vec2 {
+        let local = match handle {
+            Handle::Scale { sx, sy } => Vec2::new(sx as f32, sy as f32) * self.half,
+            Handle::Rotate => Vec2::new(0.0, self.half.y + ROTATE_HANDLE_OFFSET),
+        };
+        self.to_world(local)
+    }
+
+    fn hit(self, p: Vec2) -> Option<Handle> {
+        HANDLES
+            .into_iter()
+            .find(|h| self.handle_position(*h).distance(p) <= HANDLE_GRAB_DISTANCE)
+    }
+}
+
+fn selection_box<'a>(
+    shapes: impl Iterator<Item = (&'a Transform, &'a MiniSquare)>,
+) -> Option<SelectionBox> {
+    let shapes: Vec<_> = shapes.collect();
+    match shapes.as_slice() {
+        [] => None,
+        [(tr, sq)] => Some(SelectionBox {
+            center: tr.translation.truncate(),
+            half: sq.half_extents(tr).abs(),
+            angle: z_angle(tr),
+        }),
+        many => {
+            let (min, max) = many
+                .iter()
+                .flat_map(|(tr, sq)| sq.corners(tr))
+                .fold((Vec2::INFINITY, Vec2::NEG_INFINITY), |(lo, hi), c| {
+                    (lo.min(c), hi.max(c))
+                });
+            Some(SelectionBox {
+                center: (min + max) * 0.5,
+                half: (max - min) * 0.5,
+                angle: 0.0,
+            })
+        }
+    }
+}
+
+struct HandleGesture {
+    handle: Handle,
+    start_box: SelectionBox,
+    start_pointer: Vec2,
+    start: Vec<(Entity, Transform)>,
+}
+
+#[derive(Resource, Default)]
+pub(crate) struct TransformHandles {
+    hovered: Option<Handle>,
+    gesture: Option<HandleGesture>,
+}
+
+#[derive(Component)]
+pub(crate) struct TransformHandlesScene;
+
+pub(crate) fn setup_transform_handles(mut commands: Commands) {
+    // Editor chrome: deliberately not an `OverlayLayer`, so exports skip it.
+    commands.spawn((
+        VelloScene::new(),
+        VelloScreenSpace,
+        RenderLayers::layer(1),
+        TransformHandlesScene,
+        EncodeKey::default(),
+    ));
+}
+
+type HandleShapeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut Transform,
+        &'static MiniSquare,
+        &'static MiniSquareState,
+    ),
+>;
+
+/// Start, drive and commit handle gestures. Runs after guide interaction (guides win
+/// the press) and before the shape drag/marquee system.
+pub(crate) fn transform_handles_system(
+    mut handles: ResMut<TransformHandles>,
+    mut mouse: ResMut<SimpleMouseState>,
+    mut history: ResMut<History>,
+    pointer: Res<PointerState>,
+    tool: Res<ToolState>,
+    locale: Res<crate::Locale>,
+    mut q_shapes: HandleShapeQuery,
+) {
+    if let Some(gesture) = &handles.gesture {
+        if mouse.left_pressed {
+            if let Some(p) = pointer.overlay_world {
+                apply_gesture(gesture, p, pointer.modifiers.shift, &mut q_shapes);
+            }
+            return;
+        }
+        let label = match gesture.handle {
+            Handle::Rotate => locale.get("history.rotate"),
+            Handle::Scale { .. } => locale.get("history.scale"),
+        };
+        let ops = gesture
+            .start
+            .iter()
+            .filter_map(|(entity, before)| {
+                let (_, after, _, _) = q_shapes.get(*entity).ok()?;
+                (*after != *before).then_some(HistoryOp::Transform {
+                    entity: *entity,
+                    before: *before,
+                    after: *after,
+                })
+            })
+            .collect();
+        history.push(HistoryEntry {
+            label: label.to_string(),
+            ops,
+        });
+        handles.gesture = None;
+        return;
+    }
+
+    let bbox = selection_box(
+        q_shapes
+            .iter()
+            .filter(|(_, _, _, st)| st.selected)
+            .map(|(_, tr, sq, _)| (tr, sq)),
+    )
+    .filter(|_| tool.active == Tool::Select);
+    let hovered = bbox.zip(pointer.overlay_world).and_then(|(b, p)| b.hit(p));
+    if handles.hovered != hovered {
+        handles.hovered = hovered;
+    }
+
+    if !mouse.just_pressed || mouse.captured {
+        return;
+    }
+    let (Some(handle), Some(start_box), Some(start_pointer)) =
+        (hovered, bbox, pointer.overlay_world)
+    else {
+        return;
+    };
+    mouse.captured = true;
+    handles.gesture = Some(HandleGesture {
+        handle,
+        start_box,
+        start_pointer,
+        start: q_shapes
+            .iter()
+            .filter(|(_, _, _, st)| st.selected)
+            .map(|(entity, tr, _, _)| (entity, *tr))
+            .collect(),
+    });
+}
+
+fn apply_gesture(gesture: &HandleGesture, p: Vec2, shift: bool, q_shapes: &mut HandleShapeQuery) {
+    let b = gesture.start_box;
+    match gesture.handle {
+        Handle::Rotate => {
+            let mut delta =
+                (p - b.center).to_angle() - (gesture.start_pointer - b.center).to_angle();
+            if shift {
+                delta = (delta / ROTATE_SNAP).round() * ROTATE_SNAP;
+            }
+            let rot = Vec2::from_angle(delta);
+            for (entity, start) in &gesture.start {
+                let Ok((_, mut tr, _, _)) = q_shapes.get_mut(*entity) else {
+                    continue;
+                };
+                let pos = b.center + rot.rotate(start.translation.truncate() - b.center);
+                tr.translation = pos.extend(start.translation.z);
+                tr.rotation = Quat::from_rotation_z(delta) * start.rotation;
+            }
+        }
+        Handle::Scale { sx, sy } => {
+            let handle = Vec2::new(sx as f32, sy as f32) * b.half;
+            // Opposite side stays fixed; for edge handles the other axis is untouched.
+            let anchor = -handle;
+            let span = handle - anchor;
+            let local = b.to_local(p);
+            let mut factor = Vec2::ONE;
+            if sx != 0 && span.x.abs() > f32::EPSILON {
+                factor.x = (local.x - anchor.x) / span.x;
+            }
+            if sy != 0 && span.y.abs() > f32::EPSILON {
+                factor.y = (local.y - anchor.y) / span.y;
+            }
+            if shift && sx != 0 && sy != 0 {
+                factor = Vec2::splat((local - anchor).dot(span) / span.length_squared());
+            }
+            let factor = factor.max(Vec2::splat(MIN_SCALE_FACTOR));
+            for (entity, start) in &gesture.start {
+                let Ok((_, mut tr, _, _)) = q_shapes.get_mut(*entity) else {
+                    continue;
+                };
+                let offset = b.to_local(start.translation.truncate());
+                let pos = b.to_world(anchor + (offset - anchor) * factor);
+                tr.translation = pos.extend(start.translation.z);
+                // Scales along the shape's own axes: exact when they match the box
+                // (always for a single selection), approximate for rotated group members.
+                tr.scale = (start.scale.truncate() * factor).extend(start.scale.z);
+            }
+        }
+    }
+}
+
+pub(crate) fn render_transform_handles(
+    mut q_scene: Query<(&mut VelloScene, &mut EncodeKey), With<TransformHandlesScene>>,
+    q_shapes: Query<(&Transform, &MiniSquare, &MiniSquareState)>,
+    handles: Res<TransformHandles>,
+    panels: Res<Panels>,
+    theme: Res<Theme>,
+) {
+    let Ok((mut scene, mut key)) = q_scene.single_mut() else {
+        return;
+    };
+    let panel_rect = panels.rect(VIEWER_PANEL);
+    let bbox = selection_box(
+        q_shapes
+            .iter()
+            .filter(|(_, _, st)| st.selected)
+            .map(|(tr, sq, _)| (tr, sq)),
+    );
+    let mut hasher = ContentHasher::default()
+        .rect(panel_rect)
+        .u32(theme.revision)
+        .bool(handles.gesture.is_some());
+    if let Some(b) = bbox {
+        hasher = hasher.vec2(b.center).vec2(b.half).f32(b.angle);
+    }
+    hasher = match handles.hovered {
+        Some(Handle::Scale { sx, sy }) => hasher.u32(1).f32(sx as f32).f32(sy as f32),
+        Some(Handle::Rotate) => hasher.u32(2),
+        None => hasher.u32(0),
+    };
+    if !key.update(hasher.finish()) {
+        return;
+    }
+    scene.reset();
+    let (Some(panel_rect), Some(b)) = (panel_rect, bbox) else {
+        return;
+    };
+    let base = overlay_affine(panel_rect);
+    let to_screen = |p: Vec2| base * kurbo::Point::new(p.x as f64, p.y as f64);
+
+    let accent = peniko::Color::new(theme.selection);
+    let white = peniko::Color::new(theme.gizmo.handle);
+    let stroke = kurbo::Stroke::new(1.0);
+
+    scene.push_layer(
+        peniko::Mix::Clip,
+        1.0,
+        kurbo::Affine::IDENTITY,
+        &panel_rect.to_kurbo(),
+    );
+
+    let mut outline = kurbo::BezPath::new();
+    for (i, corner) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+        .into_iter()
+        .enumerate()
+    {
+        let p = to_screen(b.to_world(Vec2::new(corner.0, corner.1) * b.half));
+        if i == 0 {
+            outline.move_to(p);
+        } else {
+            outline.line_to(p);
+        }
+    }
+    outline.close_path();
+    let top = to_screen(b.handle_position(Handle::Scale { sx: 0, sy: 1 }));
+    let rotate = to_screen(b.handle_position(Handle::Rotate));
+    outline.move_to(top);
+    outline.line_to(rotate);
+    scene.stroke(&stroke, kurbo::Affine::IDENTITY, accent, None, &outline);
+
+    // Hide the handles mid-gesture except the one being dragged.
+    let active = handles.gesture.as_ref().map(|g| g.handle);
+    for handle in HANDLES {
+        if active.is_some_and(|a| a != handle) {
+            continue;
+        }
+        let c = to_screen(b.handle_position(handle));
+        let fill = if handles.hovered == Some(handle) || active == Some(handle) {
+            accent
+        } else {
+            white
+        };
+        match handle {
+            Handle::Rotate => {
+                let circle = kurbo::Circle::new(c, HANDLE_SIZE * 0.5);
+                scene.fill(
+                    peniko::Fill::NonZero,
+                    kurbo::Affine::IDENTITY,
+                    fill,
+                    None,
+                    &circle,
+                );
+                scene.stroke(&stroke, kurbo::Affine::IDENTITY, accent, None, &circle);
+            }
+            Handle::Scale { .. } => {
+                let square = kurbo::Rect::from_center_size(c, (HANDLE_SIZE, HANDLE_SIZE));
+                scene.fill(
+                    peniko::Fill::NonZero,
+                    kurbo::Affine::IDENTITY,
+                    fill,
+                    None,
+                    &square,
+                );
+                scene.stroke(&stroke, kurbo::Affine::IDENTITY, accent, None, &square);
+            }
+        }
+    }
+    scene.pop_layer();
+}
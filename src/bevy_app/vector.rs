@@ -0,0 +1,229 @@
+//! Authored vector paths in the viewer overlay.
+//!
+//! A `VectorNode` is a cubic Bézier path stored as anchors with in/out control handles
+//! (relative to the anchor), in the entity's local space; the entity `Transform` places
+//! it in overlay-world space. All nodes are drawn into one batched scene from cached
+//! per-node fragments, like the mini squares.
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy_vello::prelude::kurbo::Shape as _;
+use bevy_vello::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::SelectionState;
+use crate::bevy_app::encode_cache::{ContentHasher, ShapeFragment};
+use crate::bevy_app::overlay2d::{OverlayLayer, z_angle};
+use crate::bevy_app::overlay_damage::OverlayDamage;
+use crate::bevy_app::theme::Theme;
+use crate::panels::{Panels, VIEWER_PANEL, overlay_affine};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, Reflect)]
+pub struct PathAnchor {
+    pub position: Vec2,
+    /// Incoming control point, relative to `position` (zero = corner).
+    pub handle_in: Vec2,
+    /// Outgoing control point, relative to `position` (zero = corner).
+    pub handle_out: Vec2,
+}
+
+impl PathAnchor {
+    pub fn corner(position: Vec2) -> Self {
+        Self {
+            position,
+            ..default()
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Serialize, Deserialize, Reflect)]
+#[reflect(Component)]
+pub struct VectorNode {
+    pub anchors: Vec<PathAnchor>,
+    pub closed: bool,
+    pub fill: Option<[f32; 4]>,
+    pub stroke: [f32; 4],
+    pub stroke_width: f32,
+    /// Multiplies the fill and stroke alpha.
+    #[serde(default = "full_opacity")]
+    pub opacity: f32,
+}
+
+fn full_opacity() -> f32 {
+    1.0
+}
+
+impl Default for VectorNode {
+    fn default() -> Self {
+        Self {
+            anchors: Vec::new(),
+            closed: false,
+            fill: None,
+            stroke: [0.1, 0.1, 0.12, 1.0],
+            stroke_width: 2.0,
+            opacity: 1.0,
+        }
+    }
+}
+
+impl VectorNode {
+    /// Path in local space (a straight segment where both handles are zero).
+    pub fn bez_path(&self) -> kurbo::BezPath {
+        bez_path(&self.anchors, self.closed)
+    }
+
+    fn content_key(&self, hasher: ContentHasher) -> ContentHasher {
+        let mut hasher = hasher
+            .bool(self.closed)
+            .f32(self.stroke_width)
+            .f32(self.opacity)
+            .u32(self.anchors.len() as u32);
+        for c in self.stroke.iter().chain(self.fill.iter().flatten()) {
+            hasher = hasher.f32(*c);
+        }
+        for a in &self.anchors {
+            hasher = hasher.vec2(a.position).vec2(a.handle_in).vec2(a.handle_out);
+        }
+        hasher
+    }
+}
+
+fn point(v: Vec2) -> kurbo::Point {
+    kurbo::Point::new(v.x as f64, v.y as f64)
+}
+
+pub(crate) fn bez_path(anchors: &[PathAnchor], closed: bool) -> kurbo::BezPath {
+    let mut path = kurbo::BezPath::new();
+    let Some(first) = anchors.first() else {
+        return path;
+    };
+    path.move_to(point(first.position));
+    let segment = |path: &mut kurbo::BezPath, a: &PathAnchor, b: &PathAnchor| {
+        if a.handle_out == Vec2::ZERO && b.handle_in == Vec2::ZERO {
+            path.line_to(point(b.position));
+        } else {
+            path.curve_to(
+                point(a.position + a.handle_out),
+                point(b.position + b.handle_in),
+                point(b.position),
+            );
+        }
+    };
+    for pair in anchors.windows(2) {
+        segment(&mut path, &pair[0], &pair[1]);
+    }
+    if closed && anchors.len() > 2 {
+        segment(&mut path, &anchors[anchors.len() - 1], first);
+        path.close_path();
+    }
+    path
+}
+
+/// Local -> overlay-world affine for a 2D entity transform (translation, z rotation, scale).
+pub(crate) fn transform_affine(tr: &Transform) -> kurbo::Affine {
+    kurbo::Affine::scale_non_uniform(tr.scale.x as f64, tr.scale.y as f64)
+        .then_rotate(z_angle(tr) as f64)
+        .then_translate((tr.translation.x as f64, tr.translation.y as f64).into())
+}
+
+#[derive(Component)]
+pub(crate) struct VectorNodesScene;
+
+pub(crate) fn setup_vector_nodes(mut commands: Commands) {
+    commands.spawn((
+        VelloScene::new(),
+        VectorNodesScene,
+        VelloScreenSpace,
+        RenderLayers::layer(1),
+        OverlayLayer,
+        OverlayDamage::default(),
+    ));
+}
+
+type VectorNodeChanged = Or<(Changed<VectorNode>, Changed<Transform>)>;
+
+pub(crate) fn render_vector_nodes(
+    mut q_scene: Query<(&mut VelloScene, &mut OverlayDamage), With<VectorNodesScene>>,
+    mut q_nodes: Query<(Entity, &Transform, &VectorNode, &mut ShapeFragment)>,
+    changed: Query<(), (With<VectorNode>, VectorNodeChanged)>,
+    mut removed: RemovedComponents<VectorNode>,
+    selection: Res<SelectionState>,
+    panels: Res<Panels>,
+    theme: Res<Theme>,
+) {
+    let any_removed = removed.read().count() > 0;
+    if !(panels.is_changed()
+        || selection.is_changed()
+        || theme.is_changed()
+        || any_removed
+        || !changed.is_empty())
+    {
+        return;
+    }
+    let Ok((mut scene, mut damage)) = q_scene.single_mut() else {
+        return;
+    };
+    scene.reset();
+    let Some(panel_rect) = panels.rect(VIEWER_PANEL) else {
+        return;
+    };
+    let base = overlay_affine(panel_rect);
+    // Removed nodes leave no fragment to report their bounds.
+    if panels.is_changed() || any_removed {
+        damage.add(panel_rect.to_kurbo());
+    }
+
+    scene.push_layer(
+        peniko::Mix::Clip,
+        1.0,
+        kurbo::Affine::IDENTITY,
+        &panel_rect.to_kurbo(),
+    );
+    for (entity, tr, node, mut fragment) in q_nodes.iter_mut() {
+        let selected = selection.selected.contains_key(&entity);
+        let content_key = node
+            .content_key(ContentHasher::default())
+            .vec2(tr.translation.truncate())
+            .f32(z_angle(tr))
+            .vec2(tr.scale.truncate())
+            .bool(selected)
+            .u32(theme.revision)
+            .finish();
+        if fragment.key.update(content_key) {
+            let affine = transform_affine(tr);
+            let path = node.bez_path();
+            let half_width = node.stroke_width.max(1.0) as f64 / 2.0;
+            let local = path.bounding_box().inflate(half_width, half_width);
+            let bounds = affine.transform_rect_bbox(local);
+            damage.add(base.transform_rect_bbox(fragment.rebound(bounds)));
+            fragment.scene.reset();
+            if let Some(fill) = node.fill.filter(|_| node.closed) {
+                fragment.scene.fill(
+                    peniko::Fill::NonZero,
+                    affine,
+                    Theme::faded(fill, node.opacity),
+                    None,
+                    &path,
+                );
+            }
+            fragment.scene.stroke(
+                &kurbo::Stroke::new(node.stroke_width as f64),
+                affine,
+                Theme::faded(node.stroke, node.opacity),
+                None,
+                &path,
+            );
+            if selected {
+                fragment.scene.stroke(
+                    &kurbo::Stroke::new(1.0),
+                    affine,
+                    peniko::Color::new(theme.selection),
+                    None,
+                    &path,
+                );
+            }
+        }
+        scene.append(&fragment.scene, Some(base));
+    }
+    scene.pop_layer();
+}
@@ -0,0 +1,251 @@
+//! Visibility diagnostics: why an entity isn't on screen, and culling counts.
+//!
+//! `explain_visibility` walks the same checks Bevy's visibility systems make, in order:
+//! the entity's own `Visibility`, hidden ancestors, render layers against each camera,
+//! the camera being active, frustum culling, and later cameras on the same target that
+//! clear over it. Results reflect the last rendered frame (`ViewVisibility` is computed
+//! in `PostUpdate`). `get_visibility_stats` counts meshes by outcome.
+
+use bevy::ecs::query::QueryFilter;
+use bevy::prelude::*;
+use bevy::render::camera::NormalizedRenderTarget;
+use bevy::render::primitives::{Aabb, Frustum};
+use bevy::render::view::{NoFrustumCulling, RenderLayers};
+use bevy::window::PrimaryWindow;
+use serde_json::{Value, json};
+use wasm_bindgen::prelude::*;
+
+use crate::WorkerApp;
+use crate::entity_id::live_entity;
+
+fn reason(code: &str, message: String) -> Value {
+    json!({ "code": code, "message": message })
+}
+
+fn label(world: &World, entity: Entity) -> String {
+    match world.get::<Name>(entity) {
+        Some(name) => format!("{name} ({entity})"),
+        None => entity.to_string(),
+    }
+}
+
+fn first<F: QueryFilter>(world: &mut World) -> Option<Entity> {
+    world.query_filtered::<Entity, F>().iter(world).next()
+}
+
+/// Nearest ancestor with `Visibility::Hidden`.
+fn hidden_ancestor(world: &World, entity: Entity) -> Option<Entity> {
+    let mut current = entity;
+    while let Some(child_of) = world.get::<ChildOf>(current) {
+        current = child_of.parent();
+        if world.get::<Visibility>(current) == Some(&Visibility::Hidden) {
+            return Some(current);
+        }
+    }
+    None
+}
+
+struct CameraInfo {
+    entity: Entity,
+    order: isize,
+    active: bool,
+    clears: bool,
+    target: Option<NormalizedRenderTarget>,
+    layers: RenderLayers,
+}
+
+/// Explain why `entity_id` is or isn't visible. Returns
+/// `{ "entity", "visible", "reasons": [{ "code", "message" }], "cameras": [...] }`;
+/// `reasons` is empty for a visible entity. Codes: "not_found" / "invalid_bits" /
+/// "stale_generation", "no_visibility", "hidden", "hidden_ancestor", "render_layers",
+/// "camera_inactive", "frustum", "camera_order". Each camera entry has `entity`,
+/// `name`, `order`, `active`, `layers_match`, `in_frustum` (null without bounds) and
+/// `overdrawn_by` (a later camera that clears the same target, or null).
+#[wasm_bindgen]
+pub fn explain_visibility(ptr: u64, entity_id: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    let entity = match live_entity(world, entity_id) {
+        Ok(entity) => entity,
+        Err(e) => {
+            return json!({
+                "entity": entity_id,
+                "visible": false,
+                "reasons": [reason(e.code(), e.to_string())],
+                "cameras": [],
+            })
+            .to_string();
+        }
+    };
+
+    let mut reasons = Vec::new();
+    let Some(view_visibility) = world.get::<ViewVisibility>(entity).copied() else {
+        reasons.push(reason(
+            "no_visibility",
+            format!(
+                "{} has no Visibility component, so it is never rendered",
+                label(world, entity)
+            ),
+        ));
+        return json!({
+            "entity": entity_id,
+            "visible": false,
+            "reasons": reasons,
+            "cameras": [],
+        })
+        .to_string();
+    };
+
+    if world.get::<Visibility>(entity) == Some(&Visibility::Hidden) {
+        reasons.push(reason("hidden", "its Visibility is Hidden".to_string()));
+    } else if let Some(ancestor) = hidden_ancestor(world, entity) {
+        reasons.push(reason(
+            "hidden_ancestor",
+            format!("its ancestor {} is Hidden", label(world, ancestor)),
+        ));
+    }
+
+    let primary_window = first::<With<PrimaryWindow>>(world);
+    let cameras: Vec<CameraInfo> = world
+        .query::<(Entity, &Camera, Option<&RenderLayers>)>()
+        .iter(world)
+        .map(|(entity, camera, layers)| CameraInfo {
+            entity,
+            order: camera.order,
+            active: camera.is_active,
+            clears: !matches!(camera.clear_color, ClearColorConfig::None),
+            target: camera.target.normalize(primary_window),
+            layers: layers.cloned().unwrap_or_default(),
+        })
+        .collect();
+
+    let layers = world
+        .get::<RenderLayers>(entity)
+        .cloned()
+        .unwrap_or_default();
+    let no_culling = world.get::<NoFrustumCulling>(entity).is_some();
+    let bounds = world
+        .get::<Aabb>(entity)
+        .copied()
+        .zip(world.get::<GlobalTransform>(entity).copied());
+
+    let mut camera_reports = Vec::new();
+    let (mut matching, mut active, mut in_any_frustum) = (0, 0, false);
+    let mut overdrawn = Vec::new();
+    for camera in &cameras {
+        let layers_match = camera.layers.intersects(&layers);
+        let in_frustum = match (bounds, world.get::<Frustum>(camera.entity)) {
+            _ if no_culling => Some(true),
+            (Some((aabb, transform)), Some(frustum)) => {
+                Some(frustum.intersects_obb(&aabb, &transform.affine(), true, false))
+            }
+            _ => None,
+        };
+        let overdrawn_by = cameras
+            .iter()
+            .filter(|other| {
+                other.active
+                    && other.clears
+                    && other.order > camera.order
+                    && other.target.is_some()
+                    && other.target == camera.target
+            })
+            .max_by_key(|other| other.order)
+            .map(|other| other.entity);
+
+        if layers_match {
+            matching += 1;
+            if camera.active {
+                active += 1;
+                in_any_frustum |= in_frustum != Some(false);
+                if let Some(other) = overdrawn_by {
+                    overdrawn.push((camera.entity, other));
+                }
+            }
+        }
+        camera_reports.push(json!({
+            "entity": camera.entity.to_bits(),
+            "name": world.get::<Name>(camera.entity).map(|name| name.as_str()),
+            "order": camera.order,
+            "active": camera.active,
+            "layers_match": layers_match,
+            "in_frustum": in_frustum,
+            "overdrawn_by": overdrawn_by.map(|other| other.to_bits()),
+        }));
+    }
+
+    if matching == 0 {
+        reasons.push(reason(
+            "render_layers",
+            format!(
+                "no camera renders its layers {:?}",
+                layers.iter().collect::<Vec<_>>()
+            ),
+        ));
+    } else if active == 0 {
+        reasons.push(reason(
+            "camera_inactive",
+            "every camera that renders its layers is inactive".to_string(),
+        ));
+    } else if !in_any_frustum {
+        reasons.push(reason(
+            "frustum",
+            "its bounds are outside the view frustum of every camera that renders it".to_string(),
+        ));
+    }
+    for (camera, other) in overdrawn {
+        reasons.push(reason(
+            "camera_order",
+            format!(
+                "camera {} is drawn over by {}, which has a higher order and clears the same target",
+                label(world, camera),
+                label(world, other)
+            ),
+        ));
+    }
+
+    json!({
+        "entity": entity_id,
+        "visible": view_visibility.get(),
+        "reasons": reasons,
+        "cameras": camera_reports,
+    })
+    .to_string()
+}
+
+/// Count 3D meshes by visibility outcome in the last frame:
+/// `{ "meshes", "visible", "hidden", "culled", "no_culling" }`. `hidden` meshes have
+/// themselves or an ancestor hidden; `culled` ones are visible in the hierarchy but
+/// outside every view (or not on any camera's layers).
+#[wasm_bindgen]
+pub fn get_visibility_stats(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    let (mut meshes, mut visible, mut hidden, mut culled, mut no_culling) = (0, 0, 0, 0, 0);
+    let mut query = world.query_filtered::<(
+        &InheritedVisibility,
+        &ViewVisibility,
+        Has<NoFrustumCulling>,
+    ), With<Mesh3d>>();
+    for (inherited, view, uncullable) in query.iter(world) {
+        meshes += 1;
+        if view.get() {
+            visible += 1;
+        } else if !inherited.get() {
+            hidden += 1;
+        } else {
+            culled += 1;
+        }
+        if uncullable {
+            no_culling += 1;
+        }
+    }
+    json!({
+        "meshes": meshes,
+        "visible": visible,
+        "hidden": hidden,
+        "culled": culled,
+        "no_culling": no_culling,
+    })
+    .to_string()
+}
@@ -0,0 +1,105 @@
+//! World health check: leftovers that usually mean something went wrong.
+//!
+//! `inspector_audit_world` reports entities with no components, entities whose
+//! `ChildOf` parent no longer exists, reflected components holding handles to assets
+//! that are neither loaded nor loading (never added, removed, or failed), and windows
+//! no active camera renders to. Handles are found by walking each reflected component,
+//! so unreflected components are not checked.
+
+use bevy::asset::{LoadState, ReflectAsset, UntypedAssetId};
+use bevy::prelude::*;
+use bevy::reflect::TypeRegistry;
+use bevy::render::camera::NormalizedRenderTarget;
+use bevy::window::PrimaryWindow;
+use serde_json::{Value, json};
+use wasm_bindgen::prelude::*;
+
+use super::asset_graph::{asset_ref, component_handles};
+use crate::WorkerApp;
+
+/// Why the asset `id` is unusable, or None if it is loaded or loading.
+fn missing_asset(world: &World, registry: &TypeRegistry, id: UntypedAssetId) -> Option<Value> {
+    let reflect_asset = registry.get_type_data::<ReflectAsset>(id.type_id())?;
+    if reflect_asset.get(world, UntypedHandle::Weak(id)).is_some() {
+        return None;
+    }
+    let state = world
+        .get_resource::<AssetServer>()
+        .and_then(|server| server.get_load_state(id));
+    let reason = match state {
+        Some(LoadState::Loading) => return None,
+        Some(LoadState::Failed(error)) => json!({ "failed": error.to_string() }),
+        _ => json!("missing"),
+    };
+    let mut missing = asset_ref(registry, id);
+    missing["reason"] = reason;
+    Some(missing)
+}
+
+/// Audit the world for leftovers. Returns
+/// `{ "empty_entities": [id], "orphans": [{ "entity", "parent" }],
+///    "missing_assets": [{ "entity", "component", "asset_type", "asset", "reason" }],
+///    "windows_without_cameras": [{ "entity", "title" }] }`,
+/// where `reason` is "missing" or `{ "failed": message }`.
+#[wasm_bindgen]
+pub fn inspector_audit_world(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+
+    let primary_window = world
+        .query_filtered::<Entity, With<PrimaryWindow>>()
+        .iter(world)
+        .next();
+    let targets: Vec<NormalizedRenderTarget> = world
+        .query::<&Camera>()
+        .iter(world)
+        .filter(|camera| camera.is_active)
+        .filter_map(|camera| camera.target.normalize(primary_window))
+        .collect();
+    let windows_without_cameras: Vec<Value> = world
+        .query::<(Entity, &Window)>()
+        .iter(world)
+        .filter(|(entity, _)| {
+            !targets.iter().any(|target| {
+                matches!(target, NormalizedRenderTarget::Window(window) if window.entity() == *entity)
+            })
+        })
+        .map(|(entity, window)| json!({ "entity": entity.to_bits(), "title": window.title }))
+        .collect();
+
+    let type_registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = type_registry.read();
+    let world = &*world;
+    let mut empty_entities = Vec::new();
+    let mut orphans = Vec::new();
+    let mut missing_assets = Vec::new();
+    for entity_ref in world.iter_entities() {
+        let entity = entity_ref.id();
+        let archetype = entity_ref.archetype();
+        if archetype.component_count() == 0 {
+            empty_entities.push(entity.to_bits());
+            continue;
+        }
+        let parent = entity_ref.get::<ChildOf>().map(ChildOf::parent);
+        if let Some(parent) = parent.filter(|parent| world.get_entity(*parent).is_err()) {
+            orphans.push(json!({ "entity": entity.to_bits(), "parent": parent.to_bits() }));
+        }
+        component_handles(world, &registry, entity_ref, |info, handles| {
+            for id in handles {
+                if let Some(mut missing) = missing_asset(world, &registry, id) {
+                    missing["entity"] = json!(entity.to_bits());
+                    missing["component"] = json!(info.name());
+                    missing_assets.push(missing);
+                }
+            }
+        });
+    }
+
+    json!({
+        "empty_entities": empty_entities,
+        "orphans": orphans,
+        "missing_assets": missing_assets,
+        "windows_without_cameras": windows_without_cameras,
+    })
+    .to_string()
+}
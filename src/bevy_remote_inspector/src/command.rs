@@ -3,12 +3,12 @@ use bevy::{
     ecs::component::ComponentId,
     prelude::*,
     ptr::OwningPtr,
-    reflect::{serde::TypedReflectDeserializer, ReflectFromPtr},
+    reflect::{serde::TypedReflectDeserializer, ParsedPath, PartialReflect, ReflectFromPtr},
 };
 use serde::{de::DeserializeSeed, Serialize};
 use serde_json::Value;
 
-use crate::InspectorContext;
+use crate::{component::serialize_component, InspectorContext};
 
 pub trait Execute {
     type Output: Serialize;
@@ -19,7 +19,9 @@ pub trait Execute {
 
 #[derive(Debug)]
 pub enum Command {
+    ReadComponent(ReadComponent),
     UpdateComponent(UpdateComponent),
+    AdjustComponentField(AdjustComponentField),
     ToggleComponent(ToggleComponent),
     RemoveComponent(RemoveComponent),
     InsertComponent(InsertComponent),
@@ -36,7 +38,11 @@ impl Command {
         }
 
         let result = match self {
+            Command::ReadComponent(command) => command.execute(ctx, world).and_then(map_result),
             Command::UpdateComponent(command) => command.execute(ctx, world).and_then(map_result),
+            Command::AdjustComponentField(command) => {
+                command.execute(ctx, world).and_then(map_result)
+            }
             Command::ToggleComponent(command) => command.execute(ctx, world).and_then(map_result),
             Command::RemoveComponent(command) => command.execute(ctx, world).and_then(map_result),
             Command::InsertComponent(command) => command.execute(ctx, world).and_then(map_result),
@@ -49,6 +55,34 @@ impl Command {
     }
 }
 
+/// Serialize the current value of a component, in the same format `UpdateComponent` accepts.
+#[derive(Debug)]
+pub struct ReadComponent {
+    pub entity: Entity,
+    pub component: usize,
+}
+
+impl Execute for ReadComponent {
+    type Output = Value;
+
+    fn execute(
+        self,
+        _ctx: &mut InspectorContext,
+        world: &mut World,
+    ) -> anyhow::Result<Self::Output> {
+        let component_id = ComponentId::new(self.component);
+        let registry = world.resource::<AppTypeRegistry>().read();
+        let info = world
+            .components()
+            .get_info(component_id)
+            .ok_or(anyhow!("Component not found"))?;
+        let entity = world.get_entity(self.entity)?;
+
+        serialize_component(component_id, &entity, &registry, info, None)
+            .ok_or(anyhow!("Component is not serializable"))
+    }
+}
+
 #[derive(Debug)]
 pub struct UpdateComponent {
     pub entity: Entity,
@@ -102,6 +136,96 @@ impl Execute for UpdateComponent {
     }
 }
 
+/// How `AdjustComponentField` combines the field's current value with `amount`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+pub enum AdjustOp {
+    Add,
+    Mul,
+    Set,
+}
+
+/// Change one numeric field of a component relative to its current value, in place
+/// (e.g. "+= 1" spinner arrows, "*= 2" expressions), so the client never writes back a
+/// value it read earlier. `path` is a reflect path into the component (`"translation.x"`,
+/// `".0"`; empty for a numeric component itself). Integer fields round and saturate.
+/// Returns the field's new value.
+#[derive(Debug)]
+pub struct AdjustComponentField {
+    pub entity: Entity,
+    pub component: usize,
+    pub path: String,
+    pub op: AdjustOp,
+    pub amount: f64,
+}
+
+impl AdjustOp {
+    fn apply(self, current: f64, amount: f64) -> f64 {
+        match self {
+            AdjustOp::Add => current + amount,
+            AdjustOp::Mul => current * amount,
+            AdjustOp::Set => amount,
+        }
+    }
+}
+
+/// Apply `op` to a numeric reflected value; None if it is not a number.
+fn adjust_number(field: &mut dyn PartialReflect, op: AdjustOp, amount: f64) -> Option<f64> {
+    macro_rules! adjust {
+        ($($ty:ty),*) => {
+            $(
+                if let Some(value) = field.try_downcast_mut::<$ty>() {
+                    *value = op.apply(*value as f64, amount).round() as $ty;
+                    return Some(*value as f64);
+                }
+            )*
+        };
+    }
+    if let Some(value) = field.try_downcast_mut::<f32>() {
+        *value = op.apply(*value as f64, amount) as f32;
+        return Some(*value as f64);
+    }
+    if let Some(value) = field.try_downcast_mut::<f64>() {
+        *value = op.apply(*value, amount);
+        return Some(*value);
+    }
+    adjust!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+    None
+}
+
+impl Execute for AdjustComponentField {
+    type Output = f64;
+
+    fn execute(
+        self,
+        _ctx: &mut InspectorContext,
+        world: &mut World,
+    ) -> anyhow::Result<Self::Output> {
+        world.resource_scope(|world, registry: Mut<AppTypeRegistry>| {
+            let registry = registry.read();
+            let component_id = ComponentId::new(self.component);
+            let type_id = world
+                .components()
+                .get_info(component_id)
+                .and_then(|info| info.type_id())
+                .ok_or(anyhow!("Component not found"))?;
+            let reflect_from_ptr = registry
+                .get_type_data::<ReflectFromPtr>(type_id)
+                .ok_or(anyhow!("Component does not implement ReflectFromPtr"))?;
+            let path = ParsedPath::parse(&self.path)
+                .map_err(|e| anyhow!("Invalid field path {:?}: {e}", self.path))?;
+
+            let mut entity = world.get_entity_mut(self.entity)?;
+            let mut component_ptr = entity.get_mut_by_id(component_id)?;
+            let reflect_mut = unsafe { reflect_from_ptr.as_reflect_mut(component_ptr.as_mut()) };
+            let field = path
+                .reflect_element_mut(reflect_mut.as_partial_reflect_mut())
+                .map_err(|e| anyhow!("Invalid field path {:?}: {e}", self.path))?;
+            adjust_number(field, self.op, self.amount)
+                .ok_or_else(|| anyhow!("Field {:?} is not a number", self.path))
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct ToggleComponent {
     pub entity: Entity,
@@ -450,6 +574,12 @@ mod test {
     #[derive(Component, Reflect, Default)]
     struct ComponentReflectNothing(usize);
 
+    #[derive(Component, Reflect, Default)]
+    struct ComponentNumbers {
+        speed: f32,
+        count: u32,
+    }
+
     fn create_world() -> World {
         let mut world = World::default();
         let mut type_registry = TypeRegistry::default();
@@ -458,6 +588,7 @@ mod test {
         type_registry.register::<ComponentReflectDeserialize>();
         type_registry.register::<ComponentReflectBoth>();
         type_registry.register::<ComponentReflectNothing>();
+        type_registry.register::<ComponentNumbers>();
 
         world.insert_resource(DisabledComponents::default());
         world.insert_resource(DeepCompareComponents::default());
@@ -534,4 +665,50 @@ mod test {
         insert_component::<ComponentReflectBoth>();
         // insert_component::<ComponentReflectNothing>();
     }
+
+    #[test]
+    fn test_adjust_component_field() {
+        let mut world = create_world();
+        let entity = world
+            .spawn(ComponentNumbers {
+                speed: 1.0,
+                count: 3,
+            })
+            .id();
+        let component = world.register_component::<ComponentNumbers>().index();
+        let adjust = |world: &mut World, path: &str, op: AdjustOp, amount: f64| {
+            let command = AdjustComponentField {
+                entity,
+                component,
+                path: path.to_string(),
+                op,
+                amount,
+            };
+            InspectorContext::run(world, |ctx, world| command.execute(ctx, world))
+        };
+
+        assert_eq!(
+            adjust(&mut world, "speed", AdjustOp::Add, 1.5).ok(),
+            Some(2.5)
+        );
+        assert_eq!(
+            adjust(&mut world, "speed", AdjustOp::Mul, 2.0).ok(),
+            Some(5.0)
+        );
+        assert_eq!(
+            adjust(&mut world, "count", AdjustOp::Set, 7.4).ok(),
+            Some(7.0)
+        );
+        // Unsigned fields saturate instead of wrapping.
+        assert_eq!(
+            adjust(&mut world, "count", AdjustOp::Add, -10.0).ok(),
+            Some(0.0)
+        );
+
+        let numbers = world.entity(entity).get::<ComponentNumbers>().unwrap();
+        assert_eq!((numbers.speed, numbers.count), (5.0, 0));
+
+        assert!(adjust(&mut world, "missing", AdjustOp::Add, 1.0).is_err());
+        assert!(adjust(&mut world, "", AdjustOp::Add, 1.0).is_err());
+    }
 }
@@ -4,9 +4,9 @@ use bevy::{
     reflect::{serde::TypedReflectSerializer, ReflectFromPtr, TypeRegistry},
 };
 use serde::Serialize;
-use serde_json::Value;
+use serde_json::{json, Value};
 
-use crate::{InspectorEvent, TrackedData};
+use crate::{policy::StreamingPolicy, InspectorEvent, TrackedData};
 
 impl TrackedData {
     pub fn track_components(
@@ -80,12 +80,18 @@ impl InspectorComponentInfo {
     }
 }
 
+/// Serialize a component value; with a `policy` (streaming), disallowed components
+/// give None and oversized values a truncation marker.
 pub fn serialize_component(
     component_id: ComponentId,
     entity_ref: &EntityRef,
     type_registry: &TypeRegistry,
     component_info: &ComponentInfo,
+    policy: Option<&StreamingPolicy>,
 ) -> Option<Value> {
+    if policy.is_some_and(|policy| !policy.allows(component_info)) {
+        return None;
+    }
     let component_ptr = entity_ref.get_by_id(component_id).ok()?;
     let type_id = component_info.type_id()?;
 
@@ -103,5 +109,45 @@ pub fn serialize_component(
 
     let ret = serde_json::to_value(serializer).ok();
 
-    ret
+    match policy {
+        Some(policy) => ret.map(|value| policy.limit(value)),
+        None => ret,
+    }
+}
+
+/// Stand-in value for a component that has no serialized value (not reflected, not
+/// serializable or withheld by the `StreamingPolicy`), so clients can still list it:
+/// `{ "$opaque": { short_name, size, reflectable } }`.
+pub fn opaque_component(component_info: &ComponentInfo, type_registry: &TypeRegistry) -> Value {
+    let reflectable = component_info.type_id().is_some_and(|type_id| {
+        type_registry
+            .get_type_data::<ReflectFromPtr>(type_id)
+            .is_some()
+    });
+    json!({
+        "$opaque": {
+            "short_name": short_type_name(component_info.name()),
+            "size": component_info.layout().size(),
+            "reflectable": reflectable,
+        }
+    })
+}
+
+/// `a::b::Foo<c::Bar>` -> `Foo<Bar>`.
+fn short_type_name(name: &str) -> String {
+    let mut short = String::with_capacity(name.len());
+    let mut segment_start = 0;
+    for (index, c) in name.char_indices() {
+        if matches!(c, '<' | '>' | ',' | '(' | ')' | '[' | ']' | ';' | '&' | ' ') {
+            short.push_str(path_tail(&name[segment_start..index]));
+            short.push(c);
+            segment_start = index + c.len_utf8();
+        }
+    }
+    short.push_str(path_tail(&name[segment_start..]));
+    short
+}
+
+fn path_tail(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path)
 }
@@ -1,17 +1,91 @@
 use bevy::{
-    ecs::component::ComponentId,
+    ecs::{component::ComponentId, event::EventCursor, removal_detection::RemovedComponentEntity},
     prelude::*,
     reflect::{serde::TypedReflectSerializer, TypeRegistry},
 };
 use serde::Serialize;
 use serde_json::Value;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::{
-    component::serialize_component, type_registry::ZeroSizedTypes, InspectorContext,
-    InspectorEvent, TrackedData,
+    component::{opaque_component, serialize_component},
+    policy::{StreamingFocus, StreamingPolicy},
+    type_registry::ZeroSizedTypes,
+    InspectorContext, InspectorEvent, InspectorHidden, SuppressedChanges, TrackedData,
+    TrackedDatas,
 };
 
+/// Read positions in `RemovedComponentEvents`, one per component id that has had a
+/// removal.
+#[derive(Resource, Default)]
+pub struct RemovalCursors(HashMap<ComponentId, EventCursor<RemovedComponentEntity>>);
+
+/// Queue removals of tracked components for every client. Runs each frame in `Last`
+/// (removal events only live for two frames, and flushes may be throttled) and before
+/// each flush.
+pub fn collect_removed_components(world: &mut World) {
+    if !world.contains_resource::<TrackedDatas>() || !world.contains_resource::<RemovalCursors>() {
+        return;
+    }
+    world.resource_scope(|world, mut cursors: Mut<RemovalCursors>| {
+        let mut tracked_datas = world.remove_resource::<TrackedDatas>().unwrap();
+        for (&component_id, events) in world.removed_components().iter() {
+            let cursor = cursors.0.entry(component_id).or_default();
+            for entity in cursor
+                .read(events)
+                .map(|removed| Entity::from(removed.clone()))
+            {
+                for tracked in tracked_datas.values_mut() {
+                    let is_tracked = tracked
+                        .entities
+                        .get(&entity)
+                        .is_some_and(|ids| ids.contains(&component_id));
+                    if is_tracked {
+                        tracked
+                            .pending_removals
+                            .entry(entity)
+                            .or_default()
+                            .insert(component_id);
+                    }
+                }
+            }
+        }
+        world.insert_resource(tracked_datas);
+    });
+}
+
+/// Whether the cameras culled `entity` last frame: it takes part in visibility
+/// checks (has bounds) but no view saw it.
+fn is_culled(entity: &EntityRef) -> bool {
+    #[cfg(feature = "bevy_render")]
+    {
+        entity.contains::<bevy::render::primitives::Aabb>()
+            && entity
+                .get::<ViewVisibility>()
+                .is_some_and(|visibility| !visibility.get())
+    }
+    #[cfg(not(feature = "bevy_render"))]
+    {
+        let _ = entity;
+        false
+    }
+}
+
+/// Whether `entity` or one of its ancestors is `InspectorHidden`.
+fn is_hidden(world: &World, entity: &EntityRef) -> bool {
+    if entity.contains::<InspectorHidden>() {
+        return true;
+    }
+    let mut parent = entity.get::<ChildOf>().map(ChildOf::parent);
+    while let Some(ancestor) = parent.and_then(|parent| world.get_entity(parent).ok()) {
+        if ancestor.contains::<InspectorHidden>() {
+            return true;
+        }
+        parent = ancestor.get::<ChildOf>().map(ChildOf::parent);
+    }
+    false
+}
+
 #[derive(Serialize)]
 #[serde(rename_all(serialize = "snake_case"))]
 #[serde(tag = "kind")]
@@ -24,6 +98,14 @@ pub enum EntityMutation {
     },
 }
 
+/// One entity of a `snapshot` event: `[entity, changes]`, the changes as in
+/// `EntityMutation::Change` of a newly seen entity.
+#[derive(Serialize)]
+pub struct SnapshotEntity(
+    #[serde(serialize_with = "crate::serialize_entity")] Entity,
+    Vec<EntityMutationChange>,
+);
+
 #[derive(Serialize)]
 pub struct EntityMutationChange(
     usize,
@@ -48,7 +130,7 @@ impl TrackedData {
             .copied()
             .filter(|k| world.get_entity(*k).is_err())
             .collect();
-        
+
         for entity in invalid_entities {
             ctx.disabled_components.0.remove(&entity);
         }
@@ -60,9 +142,12 @@ impl TrackedData {
             .filter(|(k, _)| world.get_entity(**k).is_err())
             .map(|(k, v)| (*k, v.clone()))
             .collect();
-        
+
         for (entity, _) in &removed_entities {
             self.entities.remove(entity);
+            self.pending_removals.remove(entity);
+            self.deferred.remove(entity);
+            self.watched.remove(entity);
         }
 
         events.reserve(removed_entities.len());
@@ -75,40 +160,87 @@ impl TrackedData {
             });
         }
 
-        let this_run = world.change_tick();
+        // A client that tracks nothing yet gets every entity in one `snapshot` event
+        // rather than one event each; later flushes are incremental.
+        let mut snapshot = self.entities.is_empty().then(Vec::new);
+
+        // Bump the tick so changes made after this flush, even within the same system,
+        // are newer than `last_streamed`.
+        let this_run = world.increment_change_tick();
+        let last_streamed = self.last_streamed.unwrap_or(world.last_change_tick());
+        let policy = world.get_resource::<StreamingPolicy>();
+        let focus = world.get_resource::<StreamingFocus>();
+        let suppressed = world.get_resource::<SuppressedChanges>();
+        self.flushes += 1;
+        let offscreen_flush = policy.is_some_and(|policy| {
+            policy.offscreen_interval > 0
+                && self
+                    .flushes
+                    .is_multiple_of(policy.offscreen_interval as u64)
+        });
+        let viewport_priority =
+            policy.is_some_and(|policy| policy.viewport_priority) && !offscreen_flush;
+        // In outline mode, unwatched entities only stream these.
+        let outline_components = [
+            world.components().component_id::<Name>(),
+            world.components().component_id::<ChildOf>(),
+        ];
         for entity_ref in world.iter_entities() {
             let id = entity_ref.id();
             let entity_disbled_components = ctx.disabled_components.0.get_mut(&entity_ref.id());
+            let in_outline = !self.outline || self.watched.contains(&id);
+            let streamed = |component_id: &ComponentId| {
+                in_outline || outline_components.contains(&Some(*component_id))
+            };
+            if !self.reveal_hidden && is_hidden(world, &entity_ref) {
+                // Hidden after it was sent, or reveal turned off: the client drops it.
+                if self.entities.remove(&id).is_some() {
+                    self.pending_removals.remove(&id);
+                    self.deferred.remove(&id);
+                    events.push(InspectorEvent::Entity {
+                        entity: id,
+                        mutation: EntityMutation::Remove,
+                    });
+                }
+                continue;
+            }
             if let Some(component_ids) = self.entities.get_mut(&id) {
+                // Changes since the last send; earlier than the last flush if held back.
+                let since = self
+                    .deferred
+                    .get(&id)
+                    .copied()
+                    .unwrap_or(last_streamed);
+                if viewport_priority
+                    && is_culled(&entity_ref)
+                    && focus.is_none_or(|focus| !focus.contains(&id))
+                {
+                    self.deferred.insert(id, since);
+                    continue;
+                }
+                self.deferred.remove(&id);
                 let mut changes: Vec<EntityMutationChange> = vec![];
                 let archetype = entity_ref.archetype();
-                
-                // Find removed components and collect them
-                let removed_component_ids: Vec<_> = component_ids
-                    .iter()
-                    .filter(|&id| {
-                        archetype
-                            .components()
-                            .find(|component_id| component_id == id)
-                            .is_none()
-                    })
+
+                // Removals queued by `collect_removed_components`; a component removed
+                // and re-added since the last flush is a change, not a removal.
+                let removed_component_ids: Vec<_> = self
+                    .pending_removals
+                    .remove(&id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|id| !archetype.contains(*id) && component_ids.remove(id))
                     .map(|id| {
                         let is_disabled = entity_disbled_components
                             .as_ref()
-                            .map(|disabled| disabled.contains_key(id))
+                            .map(|disabled| disabled.contains_key(&id))
                             .unwrap_or_default();
 
                         (id.index(), is_disabled)
                     })
                     .collect();
-                
-                // Remove the components from tracking
-                for (component_index, _) in &removed_component_ids {
-                    let component_id = ComponentId::new(*component_index);
-                    component_ids.remove(&component_id);
-                }
 
-                for component_id in entity_ref.archetype().components() {
+                for component_id in entity_ref.archetype().components().filter(streamed) {
                     let Some(ticks) = entity_ref.get_change_ticks_by_id(component_id) else {
                         continue;
                     };
@@ -117,7 +249,10 @@ impl TrackedData {
                         continue;
                     };
 
-                    if !ticks.is_changed(world.last_change_tick(), this_run) {
+                    if !ticks.is_changed(since, this_run)
+                        || suppressed
+                            .is_some_and(|suppressed| suppressed.contains(id, component_id))
+                    {
                         continue;
                     }
 
@@ -143,6 +278,7 @@ impl TrackedData {
                             &entity_ref,
                             &type_registry,
                             component_info,
+                            policy,
                         );
 
                         if !is_tracked {
@@ -164,10 +300,12 @@ impl TrackedData {
                                 _ => {}
                             }
 
+                            let value = serialized
+                                .unwrap_or_else(|| opaque_component(component_info, type_registry));
                             changes.push(EntityMutationChange(
                                 component_id.index(),
                                 is_disabled,
-                                serialized,
+                                Some(value),
                             ));
                         }
                     }
@@ -183,43 +321,70 @@ impl TrackedData {
                 }
             } else {
                 // Untracked entity, serialize all component
-                self.entities
-                    .insert(id, entity_ref.archetype().components().collect());
+                self.entities.insert(
+                    id,
+                    entity_ref
+                        .archetype()
+                        .components()
+                        .filter(streamed)
+                        .collect(),
+                );
                 let disabled_componentsi = entity_disbled_components.map(|components| {
-                    let iter = components.iter().map(|(component_id, value)| {
-                        let serialized = {
-                            let reflect: &dyn PartialReflect = value.as_partial_reflect();
-                            let serializer = TypedReflectSerializer::new(reflect, &type_registry);
+                    let iter = components
+                        .iter()
+                        .filter(|(component_id, _)| streamed(component_id))
+                        .map(|(component_id, value)| {
+                            let allowed = policy.is_none_or(|policy| {
+                                world
+                                    .components()
+                                    .get_info(*component_id)
+                                    .is_none_or(|info| policy.allows(info))
+                            });
+                            let serialized = allowed.then(|| {
+                                let reflect: &dyn PartialReflect = value.as_partial_reflect();
+                                let serializer =
+                                    TypedReflectSerializer::new(reflect, &type_registry);
 
-                            let ret = serde_json::to_value(serializer).ok();
+                                let ret = serde_json::to_value(serializer).ok();
 
-                            ret
-                        };
-                        EntityMutationChange(component_id.index(), true, serialized)
-                    });
+                                match policy {
+                                    Some(policy) => ret.map(|value| policy.limit(value)),
+                                    None => ret,
+                                }
+                            });
+                            EntityMutationChange(component_id.index(), true, serialized.flatten())
+                        });
 
                     return Box::new(iter) as Box<dyn Iterator<Item = EntityMutationChange>>;
                 });
 
-                let changes = entity_ref.archetype().components().map(|component_id| {
-                    let component_info = world.components().get_info(component_id).unwrap();
-                    let serialized = serialize_component(
-                        component_id,
-                        &entity_ref,
-                        &type_registry,
-                        component_info,
-                    );
-
-                    if let Some(serialized) = serialized.as_ref() {
-                        ctx.deep_compare_components
-                            .values
-                            .entry(entity_ref.id())
-                            .or_default()
-                            .insert(component_id, serialized.clone());
-                    }
+                let changes =
+                    entity_ref
+                        .archetype()
+                        .components()
+                        .filter(streamed)
+                        .map(|component_id| {
+                            let component_info = world.components().get_info(component_id).unwrap();
+                            let serialized = serialize_component(
+                                component_id,
+                                &entity_ref,
+                                &type_registry,
+                                component_info,
+                                policy,
+                            );
 
-                    EntityMutationChange(component_id.index(), false, serialized)
-                });
+                            if let Some(serialized) = serialized.as_ref() {
+                                ctx.deep_compare_components
+                                    .values
+                                    .entry(entity_ref.id())
+                                    .or_default()
+                                    .insert(component_id, serialized.clone());
+                            }
+
+                            let value = serialized
+                                .unwrap_or_else(|| opaque_component(component_info, type_registry));
+                            EntityMutationChange(component_id.index(), false, Some(value))
+                        });
 
                 let changes = if let Some(disabled_components) = disabled_componentsi {
                     changes.chain(disabled_components).collect::<Vec<_>>()
@@ -227,14 +392,23 @@ impl TrackedData {
                     changes.collect()
                 };
 
-                events.push(InspectorEvent::Entity {
-                    entity: id,
-                    mutation: EntityMutation::Change {
-                        changes,
-                        removes: vec![],
-                    },
-                });
+                if let Some(snapshot) = snapshot.as_mut() {
+                    snapshot.push(SnapshotEntity(id, changes));
+                } else {
+                    events.push(InspectorEvent::Entity {
+                        entity: id,
+                        mutation: EntityMutation::Change {
+                            changes,
+                            removes: vec![],
+                        },
+                    });
+                }
             }
         }
+
+        if let Some(entities) = snapshot.filter(|entities| !entities.is_empty()) {
+            events.push(InspectorEvent::Snapshot { entities });
+        }
+        self.last_streamed = Some(this_run);
     }
 }
@@ -3,19 +3,34 @@
 pub mod command;
 mod component;
 mod entity;
+pub mod lifecycle;
+pub mod policy;
+pub mod relations;
 mod schedule;
+pub mod stats;
+pub mod transport;
 pub mod type_registry;
 
 use bevy::{
-    ecs::{component::ComponentId, entity::EntityHashMap},
+    ecs::{
+        component::{ComponentId, Tick},
+        entity::{EntityHashMap, EntityHashSet},
+    },
     prelude::*,
 };
 use component::InspectorComponentInfo;
-use entity::EntityMutation;
+use entity::{collect_removed_components, EntityMutation, RemovalCursors, SnapshotEntity};
+use lifecycle::{watch_lifecycle, LifecycleLog, LifecycleRecord};
+use relations::Relation;
 use schedule::{ScheduleInfo, SchedulesPlugin};
 use serde::Serialize;
 use serde_json::Value;
-use std::collections::{HashMap, HashSet};
+use stats::DiagnosticStat;
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+use transport::InspectorTransport;
 use type_registry::ZeroSizedTypes;
 
 pub struct RemoteInspectorPlugin;
@@ -35,21 +50,232 @@ impl Plugin for RemoteInspectorPlugin {
             .init_resource::<DisabledComponents>()
             .init_resource::<EntityVisibilities>()
             .init_resource::<TrackedDatas>()
+            .init_resource::<RemoteInspectorCore>()
+            .init_resource::<RemovalCursors>()
+            .init_resource::<policy::StreamingPolicy>()
+            .init_resource::<policy::StreamingFocus>()
+            .init_resource::<LifecycleLog>()
+            .init_resource::<SuppressedChanges>()
+            .add_systems(Last, (collect_removed_components, watch_lifecycle))
             .insert_resource(deep_compare_components);
     }
 }
 
+/// Keeps an entity and its descendants out of inspector streams (cameras, overlay
+/// scenes and other internals of the host app), unless the client asked to see them
+/// with `set_reveal_hidden`.
+#[derive(Component, Default, Clone, Copy)]
+pub struct InspectorHidden;
+
+/// Components whose changes no client is sent for now, e.g. a value the host is
+/// scrubbing as a preview; see `suppress_component_changes`.
+#[derive(Resource, Default)]
+pub struct SuppressedChanges(HashSet<(Entity, ComponentId)>);
+
+impl SuppressedChanges {
+    pub fn contains(&self, entity: Entity, component: ComponentId) -> bool {
+        self.0.contains(&(entity, component))
+    }
+}
+
+/// Leave changes to `entity`'s `component` out of every client's stream (or stop).
+/// The component keeps its change ticks, so systems still see the edits; lifting the
+/// suppression marks it changed, and the next flush sends whatever value it ends at.
+pub fn suppress_component_changes(
+    world: &mut World,
+    entity: Entity,
+    component: ComponentId,
+    suppressed: bool,
+) {
+    let Some(mut suppressions) = world.get_resource_mut::<SuppressedChanges>() else {
+        return;
+    };
+    let changed = if suppressed {
+        suppressions.0.insert((entity, component))
+    } else {
+        suppressions.0.remove(&(entity, component))
+    };
+    if changed && !suppressed {
+        if let Ok(mut entity) = world.get_entity_mut(entity) {
+            if let Ok(mut value) = entity.get_mut_by_id(component) {
+                value.set_changed();
+            }
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct TrackedData {
     pub type_registry: bool,
     pub components: HashSet<ComponentId>,
     pub entities: EntityHashMap<HashSet<ComponentId>>,
+    /// Tracked components removed since the last flush, filled by
+    /// `collect_removed_components`.
+    pub pending_removals: EntityHashMap<HashSet<ComponentId>>,
+    /// Off-screen entities with changes held back by `StreamingPolicy::viewport_priority`,
+    /// and the change tick they were last sent up to.
+    pub deferred: EntityHashMap<Tick>,
+    /// Change tick entity changes were last sent up to; None before the first flush.
+    /// Kept per client so throttled or infrequent flushes still see every change made
+    /// since, not just those since the world's last `clear_trackers`.
+    pub last_streamed: Option<Tick>,
+    /// Flushes so far, for `StreamingPolicy::offscreen_interval`.
+    pub flushes: u64,
+    /// Send only names and parent links, except for `watched` entities (see
+    /// `set_outline_mode`).
+    pub outline: bool,
+    pub watched: EntityHashSet,
+    /// Also stream `InspectorHidden` entities, for debugging the host app itself.
+    pub reveal_hidden: bool,
+    /// First `LifecycleLog` record not yet sent; None until the first flush, which
+    /// starts from the present (the snapshot already reflects earlier history).
+    pub lifecycle_seq: Option<u64>,
+    /// Relation edges last sent.
+    pub relations: HashSet<Relation>,
+    /// Diagnostic values last sent, by path.
+    pub stats: HashMap<String, f64>,
     pub schedules: bool,
+    /// `SystemRuns` generation and time of the last schedules sent, for refreshes.
+    pub schedule_runs: u64,
+    pub schedules_sent_at: Duration,
 }
 
 #[derive(Resource, Default, Deref, DerefMut)]
 pub struct TrackedDatas(HashMap<u32, TrackedData>); // Using u32 as a simple client ID
 
+/// Connected clients and their transports. What each client has already been sent
+/// lives in `TrackedDatas` under the same id, so event generation does not depend on
+/// how events travel.
+#[derive(Resource, Default)]
+pub struct RemoteInspectorCore {
+    clients: HashMap<u32, InspectorClient>,
+    next_client_id: u32,
+}
+
+struct InspectorClient {
+    transport: Box<dyn InspectorTransport>,
+    /// Gzip batches holding a `snapshot` event, if the transport accepts gzip.
+    compress_snapshot: bool,
+}
+
+impl RemoteInspectorCore {
+    /// Attach `transport` to `client_id`, replacing any previous one. The client keeps
+    /// its tracked state, so a reconnect only receives what changed.
+    pub fn connect(&mut self, client_id: u32, transport: impl InspectorTransport) {
+        self.clients.insert(
+            client_id,
+            InspectorClient {
+                transport: Box::new(transport),
+                compress_snapshot: false,
+            },
+        );
+    }
+
+    /// Attach `transport` under a fresh client id. Ids start at 1, leaving 0 for a
+    /// default client attached with `connect`.
+    pub fn connect_new(&mut self, transport: impl InspectorTransport) -> u32 {
+        let mut client_id = self.next_client_id.max(1);
+        while self.clients.contains_key(&client_id) {
+            client_id = client_id.wrapping_add(1).max(1);
+        }
+        self.next_client_id = client_id.wrapping_add(1);
+        self.connect(client_id, transport);
+        client_id
+    }
+
+    pub fn is_connected(&self, client_id: u32) -> bool {
+        self.clients.contains_key(&client_id)
+    }
+
+    /// Send this client's snapshot batches gzip-compressed (when its transport accepts
+    /// gzip). Returns false if the client is not connected.
+    pub fn set_compress_snapshot(&mut self, client_id: u32, enabled: bool) -> bool {
+        let Some(client) = self.clients.get_mut(&client_id) else {
+            return false;
+        };
+        client.compress_snapshot = enabled;
+        true
+    }
+
+    pub fn client_ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.clients.keys().copied()
+    }
+}
+
+/// Drop a client's transport and tracked state.
+pub fn disconnect_client(world: &mut World, client_id: u32) -> bool {
+    let removed = world
+        .get_resource_mut::<RemoteInspectorCore>()
+        .is_some_and(|mut core| core.clients.remove(&client_id).is_some());
+    if let Some(mut tracked_datas) = world.get_resource_mut::<TrackedDatas>() {
+        tracked_datas.remove(&client_id);
+    }
+    removed
+}
+
+/// Switch a client between full streaming and outline mode, where entities carry only
+/// their `Name` and `ChildOf` unless watched with `watch_entity` (for tree views that
+/// expand entities on demand). The client is re-sent a snapshot in the new mode.
+/// Returns false if the client is not connected.
+pub fn set_outline_mode(world: &mut World, client_id: u32, enabled: bool) -> bool {
+    if !world
+        .get_resource::<RemoteInspectorCore>()
+        .is_some_and(|core| core.is_connected(client_id))
+    {
+        return false;
+    }
+    let mut tracked_datas = world.resource_mut::<TrackedDatas>();
+    let tracked = tracked_datas.entry(client_id).or_default();
+    if tracked.outline != enabled {
+        tracked.outline = enabled;
+        tracked.entities.clear();
+        tracked.pending_removals.clear();
+        tracked.deferred.clear();
+    }
+    true
+}
+
+/// Include `InspectorHidden` entities in a client's stream (or leave them out again;
+/// the client then gets a `remove` for each). Returns false if the client is not
+/// connected.
+pub fn set_reveal_hidden(world: &mut World, client_id: u32, enabled: bool) -> bool {
+    if !world
+        .get_resource::<RemoteInspectorCore>()
+        .is_some_and(|core| core.is_connected(client_id))
+    {
+        return false;
+    }
+    let mut tracked_datas = world.resource_mut::<TrackedDatas>();
+    tracked_datas.entry(client_id).or_default().reveal_hidden = enabled;
+    true
+}
+
+/// Stream all of `entity`'s components to an outline-mode client (or stop). Watching
+/// sends the entity in full on the next flush; unwatching stops its component updates
+/// beyond the outline. Returns false if the client is not connected.
+pub fn watch_entity(world: &mut World, client_id: u32, entity: Entity, enabled: bool) -> bool {
+    if !world
+        .get_resource::<RemoteInspectorCore>()
+        .is_some_and(|core| core.is_connected(client_id))
+    {
+        return false;
+    }
+    let mut tracked_datas = world.resource_mut::<TrackedDatas>();
+    let tracked = tracked_datas.entry(client_id).or_default();
+    let changed = if enabled {
+        tracked.watched.insert(entity)
+    } else {
+        tracked.watched.remove(&entity)
+    };
+    if changed && enabled {
+        // Untracked again, so the next flush sends every component.
+        tracked.entities.remove(&entity);
+        tracked.pending_removals.remove(&entity);
+        tracked.deferred.remove(&entity);
+    }
+    true
+}
+
 #[derive(Serialize)]
 #[serde(rename_all(serialize = "snake_case"))]
 #[serde(tag = "kind")]
@@ -68,6 +294,23 @@ pub enum InspectorEvent {
     Schedules {
         schedules: Vec<ScheduleInfo>,
     },
+    /// Every entity at once, the first time a client is flushed.
+    Snapshot {
+        entities: Vec<SnapshotEntity>,
+    },
+    /// Component adds / replaces / removes since the last flush, in order.
+    Lifecycle {
+        records: Vec<LifecycleRecord>,
+    },
+    /// Typed entity -> entity / asset references that appeared or went away.
+    Relations {
+        added: Vec<Relation>,
+        removed: Vec<Relation>,
+    },
+    /// Bevy diagnostics (frame, render pass and encode timings) that changed.
+    Stats {
+        stats: Vec<DiagnosticStat>,
+    },
 }
 
 fn serialize_entity<S>(entity: &Entity, serializer: S) -> Result<S::Ok, S::Error>
@@ -146,10 +389,13 @@ impl<'a> InspectorContext<'a> {
     }
 }
 
-/// Get inspector events for streaming updates
+/// Events `client_id` has not seen yet, updating its tracked state. Transport-free;
+/// `stream_inspector_events` delivers to connected clients.
 pub fn get_inspector_events(world: &mut World, client_id: u32) -> Vec<InspectorEvent> {
     let mut events = Vec::new();
     let mut zsts = ZeroSizedTypes::default();
+    // Removals made since `Last` ran (e.g. by an inspector command) are not collected yet.
+    collect_removed_components(world);
 
     world.resource_scope(|world, mut tracked_datas: Mut<TrackedDatas>| {
         InspectorContext::run(world, |ctx, world| {
@@ -160,10 +406,66 @@ pub fn get_inspector_events(world: &mut World, client_id: u32) -> Vec<InspectorE
                 tracked.track_type_registry(&mut events, &mut zsts, &type_registry);
                 tracked.track_components(&mut events, world, &type_registry);
                 tracked.track_entities(&mut events, world, &type_registry, ctx, &zsts);
+                tracked.track_relations(&mut events, world);
+                tracked.track_stats(&mut events, world);
                 tracked.track_schedules(&mut events, world, &type_registry);
+                if let Some(log) = world.get_resource::<LifecycleLog>() {
+                    tracked.track_lifecycle(&mut events, log);
+                }
             });
         });
     });
 
     events
 }
+
+/// Build each connected client's pending events and hand them to its transport.
+/// Returns the number of clients that were sent something.
+pub fn stream_inspector_events(world: &mut World) -> usize {
+    if !world.contains_resource::<TrackedDatas>()
+        || !world.contains_resource::<RemoteInspectorCore>()
+    {
+        return 0;
+    }
+    world.resource_scope(|world, mut core: Mut<RemoteInspectorCore>| {
+        let mut sent = 0;
+        for (&client_id, client) in core.clients.iter_mut() {
+            let events = get_inspector_events(world, client_id);
+            if events.is_empty() {
+                continue;
+            }
+            let json = match serde_json::to_string(&events) {
+                Ok(json) => json,
+                Err(e) => {
+                    error!("Failed to serialize inspector events: {}", e);
+                    continue;
+                }
+            };
+            let has_snapshot = events
+                .iter()
+                .any(|event| matches!(event, InspectorEvent::Snapshot { .. }));
+            let compress =
+                has_snapshot && client.compress_snapshot && client.transport.accepts_gzip();
+            let gzip = compress.then(|| gzip(json.as_bytes())).and_then(|result| {
+                result
+                    .inspect_err(|e| error!("Failed to compress inspector snapshot: {}", e))
+                    .ok()
+            });
+            match gzip {
+                Some(bytes) => client.transport.send_gzip(client_id, &bytes),
+                None => client.transport.send(client_id, &json),
+            }
+            sent += 1;
+        }
+        sent
+    })
+}
+
+fn gzip(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+    encoder.write_all(bytes)?;
+    encoder.finish()
+}
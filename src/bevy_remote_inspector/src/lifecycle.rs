@@ -0,0 +1,195 @@
+use bevy::{
+    ecs::{
+        component::{ComponentId, Tick},
+        observer::ObserverState,
+    },
+    prelude::*,
+};
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+
+use crate::{InspectorEvent, InspectorHidden, TrackedData, TrackedDatas};
+
+/// Records kept for clients that have not been flushed yet; older ones are dropped.
+const MAX_RECORDS: usize = 4096;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all(serialize = "snake_case"))]
+pub enum LifecycleKind {
+    Add,
+    Replace,
+    Remove,
+}
+
+/// One component add / replace / remove, in the order the observers saw them.
+/// `seq` orders records across flushes.
+#[derive(Debug, Clone, Serialize)]
+pub struct LifecycleRecord {
+    seq: u64,
+    #[serde(serialize_with = "crate::serialize_entity")]
+    entity: Entity,
+    component: usize,
+    kind: LifecycleKind,
+}
+
+/// Component lifecycle records from `OnAdd` / `OnReplace` / `OnRemove` observers on
+/// the tracked component types, drained into each client's `lifecycle` event. Value
+/// changes made through `Mut` trigger no observer and still come from change ticks.
+#[derive(Resource, Default)]
+pub struct LifecycleLog {
+    records: VecDeque<LifecycleRecord>,
+    next_seq: u64,
+    watched: HashSet<ComponentId>,
+    /// The add / replace / remove observers, once the first components are watched.
+    observers: Option<[Entity; 3]>,
+    /// Change tick of the last `watch_lifecycle`; adds after it that happened before
+    /// their component was watched are backfilled.
+    last_watch: Option<Tick>,
+}
+
+impl LifecycleLog {
+    fn push(&mut self, entity: Entity, components: &[ComponentId], kind: LifecycleKind) {
+        for component in components {
+            if self.records.len() == MAX_RECORDS {
+                self.records.pop_front();
+            }
+            self.records.push_back(LifecycleRecord {
+                seq: self.next_seq,
+                entity,
+                component: component.index(),
+                kind,
+            });
+            self.next_seq += 1;
+        }
+    }
+}
+
+fn on_add(trigger: Trigger<OnAdd>, mut log: ResMut<LifecycleLog>) {
+    log.push(trigger.target(), trigger.components(), LifecycleKind::Add);
+}
+
+fn on_replace(trigger: Trigger<OnReplace>, mut log: ResMut<LifecycleLog>) {
+    log.push(
+        trigger.target(),
+        trigger.components(),
+        LifecycleKind::Replace,
+    );
+}
+
+fn on_remove(trigger: Trigger<OnRemove>, mut log: ResMut<LifecycleLog>) {
+    log.push(
+        trigger.target(),
+        trigger.components(),
+        LifecycleKind::Remove,
+    );
+}
+
+/// Extend the lifecycle observers to component types tracked since the last call. Runs
+/// in `Last`; does nothing until a client has been flushed. A type is only tracked
+/// once an entity has it, so that first add predates its observer and is backfilled
+/// from the component's added tick.
+pub fn watch_lifecycle(world: &mut World) {
+    let (Some(tracked_datas), Some(log)) = (
+        world.get_resource::<TrackedDatas>(),
+        world.get_resource::<LifecycleLog>(),
+    ) else {
+        return;
+    };
+    let new_components: Vec<ComponentId> = tracked_datas
+        .values()
+        .flat_map(|tracked| tracked.components.iter().copied())
+        .filter(|id| !log.watched.contains(id))
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let since = log.last_watch;
+    // Bump the tick so adds made after this pass, even between frames, are newer.
+    let now = world.increment_change_tick();
+    world.resource_mut::<LifecycleLog>().last_watch = Some(now);
+    if new_components.is_empty() {
+        return;
+    }
+
+    let backfill: Vec<(Entity, ComponentId)> = since
+        .map(|since| {
+            world
+                .archetypes()
+                .iter()
+                .flat_map(|archetype| {
+                    new_components
+                        .iter()
+                        .filter(|id| archetype.contains(**id))
+                        .flat_map(move |id| archetype.entities().iter().map(|e| (e.id(), *id)))
+                })
+                .filter(|(entity, id)| {
+                    world
+                        .entity(*entity)
+                        .get_change_ticks_by_id(*id)
+                        .is_some_and(|ticks| ticks.is_added(since, now))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut log = world.resource_mut::<LifecycleLog>();
+    for (entity, id) in backfill {
+        log.push(entity, &[id], LifecycleKind::Add);
+    }
+    log.watched.extend(new_components);
+    let watched: Vec<ComponentId> = log.watched.iter().copied().collect();
+    let observers = log.observers;
+
+    let watch = |observer: Observer| {
+        watched
+            .iter()
+            .fold(observer, |observer, id| observer.with_component(*id))
+    };
+    let kinds = [
+        (watch(Observer::new(on_add)), "Inspector lifecycle (add)"),
+        (
+            watch(Observer::new(on_replace)),
+            "Inspector lifecycle (replace)",
+        ),
+        (
+            watch(Observer::new(on_remove)),
+            "Inspector lifecycle (remove)",
+        ),
+    ];
+    match observers {
+        // A registered observer's components are fixed; re-adding it re-registers it
+        // with the full set.
+        Some(entities) => {
+            for (entity, (observer, _)) in entities.into_iter().zip(kinds) {
+                world
+                    .entity_mut(entity)
+                    .remove::<(Observer, ObserverState)>()
+                    .insert(observer);
+            }
+        }
+        None => {
+            let entities = kinds.map(|(observer, name)| {
+                world
+                    .spawn((observer, Name::new(name), InspectorHidden))
+                    .id()
+            });
+            world.resource_mut::<LifecycleLog>().observers = Some(entities);
+        }
+    }
+}
+
+impl TrackedData {
+    pub fn track_lifecycle(&mut self, events: &mut Vec<InspectorEvent>, log: &LifecycleLog) {
+        let Some(next) = self.lifecycle_seq.replace(log.next_seq) else {
+            return;
+        };
+        let records: Vec<LifecycleRecord> = log
+            .records
+            .iter()
+            .filter(|record| record.seq >= next)
+            .cloned()
+            .collect();
+        if !records.is_empty() {
+            events.push(InspectorEvent::Lifecycle { records });
+        }
+    }
+}
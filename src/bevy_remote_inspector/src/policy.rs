@@ -0,0 +1,155 @@
+use bevy::{
+    ecs::{component::ComponentInfo, entity::EntityHashSet},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Which component values are streamed to inspector clients, and how large they may
+/// be. Components that are not allowed stream like unreflected ones (opaque entries).
+/// Reads for commands (e.g. undo snapshots) ignore the policy.
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StreamingPolicy {
+    /// Type path globs (`*` matches any run of characters, `::` included). When not
+    /// empty, only matching components are serialized.
+    pub include: Vec<String>,
+    /// Type path globs that are never serialized, checked after `include`.
+    pub exclude: Vec<String>,
+    /// Values whose JSON is larger than this many bytes are replaced by a
+    /// `{ "$truncated": { "bytes": n } }` marker. 0 means no limit.
+    pub max_value_bytes: usize,
+    /// Hold back changes to entities the cameras cull (frustum-culled or hidden
+    /// meshes, sprites, ...) until they come into view, are in `StreamingFocus`, or
+    /// `offscreen_interval` flushes have passed. Nothing is lost, only delayed.
+    pub viewport_priority: bool,
+    /// With `viewport_priority`, send held-back changes every this many flushes;
+    /// 0 waits for visibility or focus.
+    pub offscreen_interval: u32,
+}
+
+impl StreamingPolicy {
+    pub fn allows(&self, component_info: &ComponentInfo) -> bool {
+        let name = component_info.name();
+        (self.include.is_empty() || self.include.iter().any(|glob| glob_match(glob, name)))
+            && !self.exclude.iter().any(|glob| glob_match(glob, name))
+    }
+
+    /// `value`, or the truncation marker if it is over `max_value_bytes`.
+    pub fn limit(&self, value: Value) -> Value {
+        if self.max_value_bytes == 0 {
+            return value;
+        }
+        let bytes = serde_json::to_vec(&value).map_or(0, |json| json.len());
+        if bytes <= self.max_value_bytes {
+            return value;
+        }
+        json!({ "$truncated": { "bytes": bytes } })
+    }
+}
+
+/// Entities streamed without delay regardless of `StreamingPolicy::viewport_priority`
+/// (e.g. the selection).
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
+pub struct StreamingFocus(pub EntityHashSet);
+
+/// Glob match where `*` matches any (possibly empty) run of characters.
+fn glob_match(glob: &str, text: &str) -> bool {
+    let mut parts = glob.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts = parts.peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            return rest.len() >= part.len() && rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.is_empty()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn glob_matches_literals_and_stars() {
+        assert!(glob_match(
+            "bevy_transform::Transform",
+            "bevy_transform::Transform"
+        ));
+        assert!(!glob_match(
+            "bevy_transform::Transform",
+            "bevy_transform::TransformX"
+        ));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*", "anything::at::all"));
+        assert!(glob_match("bevy_*", "bevy_render::view::Visibility"));
+        assert!(glob_match(
+            "*::Transform",
+            "bevy_transform::components::Transform"
+        ));
+        assert!(!glob_match(
+            "*::Transform",
+            "bevy_transform::GlobalTransform"
+        ));
+        assert!(glob_match(
+            "bevy_*::*Visibility",
+            "bevy_render::view::ViewVisibility"
+        ));
+        assert!(glob_match("a**b", "ab"));
+        assert!(!glob_match("game::*", "bevy::game::Player"));
+        // The last part must fit after the earlier ones, not overlap them.
+        assert!(!glob_match("a*a", "a"));
+        assert!(glob_match("a*a", "aa"));
+    }
+
+    #[test]
+    fn allows_applies_include_then_exclude() {
+        let mut world = World::new();
+        let transform = world.register_component::<Transform>();
+        let visibility = world.register_component::<Visibility>();
+        let info = |id| world.components().get_info(id).unwrap();
+
+        let policy = StreamingPolicy::default();
+        assert!(policy.allows(info(transform)));
+
+        let policy = StreamingPolicy {
+            include: vec!["bevy_transform::*".to_string()],
+            ..Default::default()
+        };
+        assert!(policy.allows(info(transform)));
+        assert!(!policy.allows(info(visibility)));
+
+        let policy = StreamingPolicy {
+            exclude: vec!["*Transform".to_string()],
+            ..Default::default()
+        };
+        assert!(!policy.allows(info(transform)));
+        assert!(policy.allows(info(visibility)));
+    }
+
+    #[test]
+    fn limit_truncates_large_values() {
+        let value = json!({ "name": "a long enough string" });
+        let bytes = serde_json::to_vec(&value).unwrap().len();
+        let policy = StreamingPolicy {
+            max_value_bytes: bytes,
+            ..Default::default()
+        };
+        assert_eq!(policy.limit(value.clone()), value);
+        let policy = StreamingPolicy {
+            max_value_bytes: bytes - 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            policy.limit(value),
+            json!({ "$truncated": { "bytes": bytes } })
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use bevy::{
+    asset::{Asset, AssetId},
+    pbr::NotShadowCaster,
+    prelude::*,
+    render::camera::RenderTarget,
+    window::{PrimaryWindow, WindowRef},
+};
+use serde::{Serialize, Serializer};
+use std::collections::HashSet;
+
+use crate::{InspectorEvent, TrackedData};
+
+/// A typed reference from an entity to another entity or an asset, beyond the
+/// parent / child hierarchy the entity stream already carries.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct Relation {
+    #[serde(serialize_with = "crate::serialize_entity")]
+    source: Entity,
+    kind: RelationKind,
+    target: RelationTarget,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all(serialize = "snake_case"))]
+pub enum RelationKind {
+    /// Camera -> the window it renders to, or its target image.
+    RenderTarget,
+    /// `Mesh3d` -> mesh asset.
+    Mesh,
+    /// `MeshMaterial3d<StandardMaterial>` -> material asset.
+    Material,
+    /// Shadow-casting light -> a mesh entity that casts shadows.
+    ShadowCaster,
+}
+
+/// `{ "entity": bits }` or `{ "asset": { "type_name", "id" } }`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all(serialize = "snake_case"))]
+pub enum RelationTarget {
+    Entity(#[serde(serialize_with = "crate::serialize_entity")] Entity),
+    Asset {
+        type_name: &'static str,
+        #[serde(serialize_with = "serialize_asset_id")]
+        id: AssetKey,
+    },
+}
+
+/// `AssetId` without the type parameter: index bits or UUID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssetKey {
+    Index(u64),
+    Uuid(u128),
+}
+
+fn serialize_asset_id<S: Serializer>(id: &AssetKey, serializer: S) -> Result<S::Ok, S::Error> {
+    match id {
+        AssetKey::Index(bits) => serializer.collect_str(bits),
+        AssetKey::Uuid(uuid) => serializer.collect_str(&format_args!("{uuid:032x}")),
+    }
+}
+
+fn asset_target<A: Asset>(type_name: &'static str, id: AssetId<A>) -> RelationTarget {
+    let id = match id {
+        AssetId::Index { index, .. } => AssetKey::Index(index.to_bits()),
+        AssetId::Uuid { uuid } => AssetKey::Uuid(uuid.as_u128()),
+    };
+    RelationTarget::Asset { type_name, id }
+}
+
+fn collect_relations(world: &mut World) -> HashSet<Relation> {
+    let mut relations = HashSet::new();
+    let primary_window = world
+        .query_filtered::<Entity, With<PrimaryWindow>>()
+        .iter(world)
+        .next();
+
+    for (source, camera) in world.query::<(Entity, &Camera)>().iter(world) {
+        let target = match &camera.target {
+            RenderTarget::Window(WindowRef::Primary) => primary_window.map(RelationTarget::Entity),
+            RenderTarget::Window(WindowRef::Entity(window)) => {
+                Some(RelationTarget::Entity(*window))
+            }
+            RenderTarget::Image(image) => Some(asset_target("Image", image.handle.id())),
+            RenderTarget::TextureView(_) => None,
+        };
+        if let Some(target) = target {
+            relations.insert(Relation {
+                source,
+                kind: RelationKind::RenderTarget,
+                target,
+            });
+        }
+    }
+
+    for (source, mesh) in world.query::<(Entity, &Mesh3d)>().iter(world) {
+        relations.insert(Relation {
+            source,
+            kind: RelationKind::Mesh,
+            target: asset_target("Mesh", mesh.id()),
+        });
+    }
+
+    for (source, material) in world
+        .query::<(Entity, &MeshMaterial3d<StandardMaterial>)>()
+        .iter(world)
+    {
+        relations.insert(Relation {
+            source,
+            kind: RelationKind::Material,
+            target: asset_target("StandardMaterial", material.id()),
+        });
+    }
+
+    let lights: Vec<Entity> = world
+        .query::<(
+            Entity,
+            Option<&DirectionalLight>,
+            Option<&PointLight>,
+            Option<&SpotLight>,
+        )>()
+        .iter(world)
+        .filter(|(_, directional, point, spot)| {
+            directional.is_some_and(|light| light.shadows_enabled)
+                || point.is_some_and(|light| light.shadows_enabled)
+                || spot.is_some_and(|light| light.shadows_enabled)
+        })
+        .map(|(entity, ..)| entity)
+        .collect();
+    if !lights.is_empty() {
+        let casters: Vec<Entity> = world
+            .query_filtered::<Entity, (With<Mesh3d>, Without<NotShadowCaster>)>()
+            .iter(world)
+            .collect();
+        for &source in &lights {
+            relations.extend(casters.iter().map(|&caster| Relation {
+                source,
+                kind: RelationKind::ShadowCaster,
+                target: RelationTarget::Entity(caster),
+            }));
+        }
+    }
+
+    relations
+}
+
+impl TrackedData {
+    /// Relation edges added and removed since this client's last flush.
+    pub fn track_relations(&mut self, events: &mut Vec<InspectorEvent>, world: &mut World) {
+        let current = collect_relations(world);
+        let added: Vec<Relation> = current.difference(&self.relations).cloned().collect();
+        let removed: Vec<Relation> = self.relations.difference(&current).cloned().collect();
+        self.relations = current;
+        if !added.is_empty() || !removed.is_empty() {
+            events.push(InspectorEvent::Relations { added, removed });
+        }
+    }
+}
@@ -1,6 +1,12 @@
+use std::time::Duration;
+
 use bevy::{
     app::{FixedMainScheduleOrder, MainScheduleOrder},
-    ecs::schedule::{InternedScheduleLabel, NodeId, ScheduleLabel, graph::Direction as BevyDirection},
+    ecs::{
+        component::{Components, Tick},
+        schedule::{InternedScheduleLabel, NodeId, ScheduleLabel, graph::Direction as BevyDirection},
+    },
+    platform::collections::HashMap,
     prelude::*,
     reflect::TypeRegistry,
 };
@@ -13,7 +19,12 @@ pub struct SchedulesPlugin;
 impl Plugin for SchedulesPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<UpdateSchedule>()
-            .add_systems(PostUpdate, collect_update_schedule);
+            .init_resource::<SystemRuns>()
+            .add_systems(PostUpdate, collect_update_schedule)
+            // A running schedule is out of `Schedules`, so each point records the ones it
+            // can see: `First` covers `Last`, `Last` covers the rest.
+            .add_systems(First, record_system_runs)
+            .add_systems(Last, record_system_runs);
     }
 
     fn finish(&self, app: &mut App) {
@@ -34,7 +45,22 @@ struct ClonedMainScheduleOrder {
     labels: Vec<InternedScheduleLabel>,
 }
 
-fn collect_update_schedule(mut update_schedule: ResMut<UpdateSchedule>, schedules: Res<Schedules>) {
+/// How often schedules are re-sent while their run state keeps changing.
+const SCHEDULE_REFRESH: Duration = Duration::from_millis(500);
+
+/// Which systems ran during the last frame, by schedule name and system id.
+#[derive(Resource, Default)]
+struct SystemRuns {
+    schedules: HashMap<String, HashMap<String, bool>>,
+    /// Bumped whenever `schedules` changes.
+    generation: u64,
+}
+
+fn collect_update_schedule(
+    mut update_schedule: ResMut<UpdateSchedule>,
+    schedules: Res<Schedules>,
+    components: &Components,
+) {
     if update_schedule.initialized {
         return;
     }
@@ -44,10 +70,36 @@ fn collect_update_schedule(mut update_schedule: ResMut<UpdateSchedule>, schedule
     let schedule = schedules.get(Update);
 
     if let Some(sche) = schedule {
-        update_schedule.info = ScheduleInfo::from_schedule(sche, ScheduleKind::Main);
+        update_schedule.info = ScheduleInfo::from_schedule(sche, ScheduleKind::Main, components);
     }
 }
 
+/// Record which systems of the schedules in `Schedules` ran since this system last
+/// ran, i.e. over one frame.
+fn record_system_runs(world: &mut World, mut since: Local<Option<Tick>>) {
+    let now = world.change_tick();
+    let Some(last) = since.replace(now) else {
+        return;
+    };
+
+    world.resource_scope(|world, mut runs: Mut<SystemRuns>| {
+        let schedules = world.resource::<Schedules>();
+        for (label, schedule) in schedules.iter() {
+            let Ok(systems) = schedule.systems() else {
+                continue;
+            };
+            let ran: HashMap<_, _> = systems
+                .map(|(id, sys)| (get_node_id(&id), sys.get_last_run().is_newer_than(last, now)))
+                .collect();
+            let name = format!("{:?}", label);
+            if runs.schedules.get(&name) != Some(&ran) {
+                runs.schedules.insert(name, ran);
+                runs.generation += 1;
+            }
+        }
+    });
+}
+
 fn clone_main_schedule_order(world: &mut World) {
     let main_schedule_order = world.resource::<MainScheduleOrder>();
     let my_main_schedule_order = ClonedMainScheduleOrder {
@@ -62,6 +114,22 @@ fn clone_main_schedule_order(world: &mut World) {
 pub struct SystemInfo {
     id: String,
     name: String,
+    /// Whether the system ran during the last frame.
+    ran: bool,
+    /// Whether the system's run conditions passed, inferred from whether it ran while
+    /// others in its schedule did. None when the schedule did not run at all.
+    condition_met: Option<bool>,
+    /// Systems with conflicting access and no ordering relative to this one.
+    ambiguities: Vec<AmbiguityInfo>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct AmbiguityInfo {
+    /// Id of the other system.
+    with: String,
+    /// Names of the conflicting components and resources; empty when the conflict is
+    /// over the whole world (e.g. exclusive systems).
+    components: Vec<String>,
 }
 
 #[derive(Serialize, Clone)]
@@ -89,16 +157,34 @@ pub struct ScheduleInfo {
 }
 
 impl ScheduleInfo {
-    pub fn from_schedule(schedule: &Schedule, kind: ScheduleKind) -> Self {
+    pub fn from_schedule(schedule: &Schedule, kind: ScheduleKind, components: &Components) -> Self {
+        let g = schedule.graph();
+        let mut ambiguities: HashMap<NodeId, Vec<AmbiguityInfo>> = HashMap::default();
+        for (a, b, conflicts) in g.conflicting_systems() {
+            let components = conflicts
+                .iter()
+                .filter_map(|id| components.get_name(*id))
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>();
+            for (system, other) in [(a, b), (b, a)] {
+                ambiguities.entry(*system).or_default().push(AmbiguityInfo {
+                    with: get_node_id(other),
+                    components: components.clone(),
+                });
+            }
+        }
+
         let systems = schedule
             .systems()
             .unwrap()
             .map(|(id, sys)| SystemInfo {
                 id: get_node_id(&id),
                 name: sys.name().to_string(),
+                ran: false,
+                condition_met: None,
+                ambiguities: ambiguities.remove(&id).unwrap_or_default(),
             })
             .collect();
-        let g = schedule.graph();
         let sets = g
             .system_sets()
             .filter_map(|(id, name, _)| {
@@ -174,6 +260,17 @@ impl ScheduleInfo {
             dependencies,
         }
     }
+
+    fn apply_runs(&mut self, runs: &SystemRuns) {
+        let Some(ran) = runs.schedules.get(&self.name) else {
+            return;
+        };
+        let schedule_ran = ran.values().any(|ran| *ran);
+        for system in &mut self.systems {
+            system.ran = ran.get(&system.id).copied().unwrap_or_default();
+            system.condition_met = schedule_ran.then_some(system.ran);
+        }
+    }
 }
 
 impl TrackedData {
@@ -184,12 +281,25 @@ impl TrackedData {
         _type_registry: &TypeRegistry,
     ) {
         let update_schedule = world.resource::<UpdateSchedule>();
+        let runs = world.resource::<SystemRuns>();
+        let now = world
+            .get_resource::<Time<Real>>()
+            .map(|time| time.elapsed())
+            .unwrap_or_default();
 
-        if !update_schedule.initialized || self.schedules {
+        if !update_schedule.initialized {
+            return;
+        }
+        if self.schedules
+            && (self.schedule_runs == runs.generation
+                || now.saturating_sub(self.schedules_sent_at) < SCHEDULE_REFRESH)
+        {
             return;
         }
 
         self.schedules = true;
+        self.schedule_runs = runs.generation;
+        self.schedules_sent_at = now;
 
         let main_order = world.resource::<ClonedMainScheduleOrder>();
         let fixed_main_order = world.resource::<FixedMainScheduleOrder>();
@@ -200,7 +310,11 @@ impl TrackedData {
             let Some(schedule) = schedules.get(*label) else {
                 continue;
             };
-            schedule_infos.push(ScheduleInfo::from_schedule(schedule, ScheduleKind::Startup));
+            schedule_infos.push(ScheduleInfo::from_schedule(
+                schedule,
+                ScheduleKind::Startup,
+                world.components(),
+            ));
         }
 
         for label in main_order.labels.iter() {
@@ -212,18 +326,27 @@ impl TrackedData {
                     schedule_infos.push(ScheduleInfo::from_schedule(
                         schedule,
                         ScheduleKind::FixedMain,
+                        world.components(),
                     ));
                 }
             } else {
                 let schedule = schedules.get(*label);
                 if let Some(schedule) = schedule {
-                    schedule_infos.push(ScheduleInfo::from_schedule(schedule, ScheduleKind::Main));
+                    schedule_infos.push(ScheduleInfo::from_schedule(
+                        schedule,
+                        ScheduleKind::Main,
+                        world.components(),
+                    ));
                 } else if label.0.as_dyn_eq().dyn_eq(Update.as_dyn_eq()) {
                     schedule_infos.push(update_schedule.info.clone());
                 }
             }
         }
 
+        for info in &mut schedule_infos {
+            info.apply_runs(runs);
+        }
+
         events.push(InspectorEvent::Schedules {
             schedules: schedule_infos,
         });
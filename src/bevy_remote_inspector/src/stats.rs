@@ -0,0 +1,42 @@
+use bevy::{diagnostic::DiagnosticsStore, prelude::*};
+use serde::Serialize;
+
+use crate::{InspectorEvent, TrackedData};
+
+/// The smoothed value of one registered Bevy diagnostic (frame time, render pass
+/// timings, entity counts, ...).
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticStat {
+    path: String,
+    value: f64,
+    suffix: String,
+}
+
+impl TrackedData {
+    /// Diagnostics whose value changed since this client's last flush.
+    pub fn track_stats(&mut self, events: &mut Vec<InspectorEvent>, world: &World) {
+        let Some(store) = world.get_resource::<DiagnosticsStore>() else {
+            return;
+        };
+        let stats: Vec<DiagnosticStat> = store
+            .iter()
+            .filter(|diagnostic| diagnostic.is_enabled)
+            .filter_map(|diagnostic| {
+                let path = diagnostic.path().as_str();
+                let value = diagnostic.smoothed()?;
+                if self.stats.get(path) == Some(&value) {
+                    return None;
+                }
+                self.stats.insert(path.to_string(), value);
+                Some(DiagnosticStat {
+                    path: path.to_string(),
+                    value,
+                    suffix: diagnostic.suffix.to_string(),
+                })
+            })
+            .collect();
+        if !stats.is_empty() {
+            events.push(InspectorEvent::Stats { stats });
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use std::io::Read;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use bevy::log::error;
+use flate2::read::GzDecoder;
+
+/// Carries serialized inspector events to one connected client.
+///
+/// `RemoteInspectorCore` owns event tracking; a transport only delivers. The wasm
+/// host implements this over its postMessage bridge and over a WebSocket per
+/// connection; `ChannelTransport` keeps batches in-process.
+pub trait InspectorTransport: Send + Sync + 'static {
+    /// Deliver one batch: a JSON array of `InspectorEvent`s, never empty.
+    fn send(&mut self, client_id: u32, events_json: &str);
+
+    /// Whether the transport carries gzip batches as they are. Clients that opt into
+    /// compressed snapshots only get them over transports that accept gzip.
+    fn accepts_gzip(&self) -> bool {
+        false
+    }
+
+    /// Deliver one batch as gzip-compressed JSON (same content as `send`). The default
+    /// decompresses it and hands the JSON to `send`.
+    fn send_gzip(&mut self, client_id: u32, events_gzip: &[u8]) {
+        let mut events_json = String::new();
+        match GzDecoder::new(events_gzip).read_to_string(&mut events_json) {
+            Ok(_) => self.send(client_id, &events_json),
+            Err(e) => error!("Failed to decompress inspector batch: {}", e),
+        }
+    }
+}
+
+/// In-process transport over a std channel, for tests and native tools.
+pub struct ChannelTransport(Sender<(u32, String)>);
+
+impl ChannelTransport {
+    /// The transport plus the receiving end, yielding `(client_id, events_json)`.
+    pub fn new() -> (Self, Receiver<(u32, String)>) {
+        let (sender, receiver) = channel();
+        (Self(sender), receiver)
+    }
+}
+
+impl InspectorTransport for ChannelTransport {
+    fn send(&mut self, client_id: u32, events_json: &str) {
+        // A dropped receiver just means nobody is listening any more.
+        let _ = self.0.send((client_id, events_json.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::*;
+
+    #[test]
+    fn send_gzip_falls_back_to_send() {
+        let (mut transport, batches) = ChannelTransport::new();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(br#"[{"kind":"snapshot"}]"#).unwrap();
+        transport.send_gzip(3, &encoder.finish().unwrap());
+
+        assert!(!transport.accepts_gzip());
+        assert_eq!(
+            batches.try_recv().unwrap(),
+            (3, r#"[{"kind":"snapshot"}]"#.to_string())
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! Golden-file tests of the inspector wire format.
+//!
+//! Each test drives a world through scripted steps, flushes the stream to a
+//! `ChannelTransport` after each one and compares the batches with
+//! `tests/golden/<test>.json`. Run with `UPDATE_GOLDEN=1` to rewrite the files after an
+//! intended protocol change.
+//!
+//! Entity bits, component ids and lifecycle sequence numbers depend on everything Bevy
+//! spawned and registered before the fixture ran, so they are normalized: entities
+//! become `@label`, component ids become type names and `seq` counts from 0 in each
+//! event. Only the fixture's own entities, components and types are kept.
+
+use std::{collections::HashMap, env, fs, path::PathBuf, sync::mpsc::Receiver};
+
+use bevy::{ecs::component::ComponentId, prelude::*};
+use bevy_remote_inspector::{
+    lifecycle::watch_lifecycle, stream_inspector_events, transport::ChannelTransport,
+    RemoteInspectorCore, RemoteInspectorPlugin,
+};
+use serde_json::{json, Value};
+
+const CLIENT: u32 = 1;
+/// Module path of the fixture types (this test crate).
+const FIXTURE_PATH: &str = "protocol::";
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component, Default)]
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component, Default)]
+struct Health(u32);
+
+/// Not reflected, so streamed as an `$opaque` stand-in.
+#[derive(Component)]
+struct Opaque(#[allow(dead_code)] u8);
+
+struct Fixture {
+    app: App,
+    batches: Receiver<(u32, String)>,
+    labels: HashMap<u64, String>,
+    steps: Vec<Value>,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let mut app = App::new();
+        app.add_plugins(RemoteInspectorPlugin);
+        // Only primitives and the fixture types, rather than everything `App` registers.
+        let registry = AppTypeRegistry::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<Position>();
+            registry.register::<Health>();
+        }
+        app.insert_resource(registry);
+
+        let (transport, batches) = ChannelTransport::new();
+        app.world_mut()
+            .resource_mut::<RemoteInspectorCore>()
+            .connect(CLIENT, transport);
+        Self {
+            app,
+            batches,
+            labels: HashMap::new(),
+            steps: Vec::new(),
+        }
+    }
+
+    fn world(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    fn spawn(&mut self, label: &str, bundle: impl Bundle) -> Entity {
+        let entity = self.world().spawn(bundle).id();
+        self.labels.insert(entity.to_bits(), format!("@{label}"));
+        entity
+    }
+
+    /// End a frame: the inspector's `Last` work, then Bevy's tracker reset.
+    fn frame(&mut self) {
+        let world = self.world();
+        watch_lifecycle(world);
+        // Register the observers queued by their hooks before anything else happens.
+        world.flush();
+        world.clear_trackers();
+    }
+
+    /// Flush the stream and record the normalized batch as step `name`.
+    fn flush(&mut self, name: &str) {
+        stream_inspector_events(self.world());
+        let events: Vec<Value> = self
+            .batches
+            .try_iter()
+            .flat_map(|(client_id, json)| {
+                assert_eq!(client_id, CLIENT);
+                serde_json::from_str::<Vec<Value>>(&json).expect("batch is a JSON array")
+            })
+            .collect();
+        let events = self.normalize(events);
+        self.steps.push(json!({ "step": name, "events": events }));
+    }
+
+    fn assert_golden(self, test: &str) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join(format!("{test}.json"));
+        let actual = Value::Array(self.steps);
+        if env::var_os("UPDATE_GOLDEN").is_some() {
+            let json = serde_json::to_string_pretty(&actual).unwrap();
+            fs::write(&path, json + "\n").unwrap();
+            return;
+        }
+        let expected = fs::read_to_string(&path)
+            .unwrap_or_else(|_| panic!("no {}; run with UPDATE_GOLDEN=1", path.display()));
+        let expected: Value = serde_json::from_str(&expected).unwrap();
+        assert!(
+            actual == expected,
+            "{test}: stream differs from {} (UPDATE_GOLDEN=1 to accept)\nactual:\n{}",
+            path.display(),
+            serde_json::to_string_pretty(&actual).unwrap()
+        );
+    }
+
+    fn entity(&self, bits: &Value) -> Option<Value> {
+        let label = self.labels.get(&bits.as_u64()?)?;
+        Some(json!(label))
+    }
+
+    fn component(&self, id: &Value) -> Option<Value> {
+        let id = ComponentId::new(id.as_u64()? as usize);
+        let name = self.app.world().components().get_info(id)?.name();
+        name.starts_with(FIXTURE_PATH).then(|| json!(name))
+    }
+
+    /// `[component, ...]` tuples (changes, removes) with component names, sorted.
+    fn component_tuples(&self, tuples: &mut Value) {
+        let Some(tuples) = tuples.as_array_mut() else {
+            return;
+        };
+        tuples.retain_mut(|tuple| match self.component(&tuple[0]) {
+            Some(name) => {
+                tuple[0] = name;
+                true
+            }
+            None => false,
+        });
+        tuples.sort_by_key(|tuple| tuple[0].to_string());
+    }
+
+    fn normalize(&self, events: Vec<Value>) -> Vec<Value> {
+        let mut events: Vec<Value> = events
+            .into_iter()
+            .filter_map(|event| self.normalize_event(event))
+            .collect();
+        // Entity events come in archetype order; sort them by label in their slots.
+        let slots: Vec<usize> = (0..events.len())
+            .filter(|&i| events[i]["kind"] == "entity")
+            .collect();
+        let mut entity_events: Vec<Value> = slots.iter().map(|&i| events[i].take()).collect();
+        entity_events.sort_by_key(|event| event["entity"].to_string());
+        for (i, event) in slots.into_iter().zip(entity_events) {
+            events[i] = event;
+        }
+        events
+    }
+
+    fn normalize_event(&self, mut event: Value) -> Option<Value> {
+        match event["kind"].as_str()? {
+            "type_registry" => {
+                let types = event["types"].as_array_mut()?;
+                types.retain(|item| {
+                    item[0]
+                        .as_str()
+                        .is_some_and(|name| name.starts_with(FIXTURE_PATH))
+                });
+                types.sort_by_key(|item| item[0].to_string());
+            }
+            "component" => {
+                let components = event["components"].as_array_mut()?;
+                components.retain_mut(|info| {
+                    let Some(name) = self.component(&info["id"]) else {
+                        return false;
+                    };
+                    info["id"] = name;
+                    let required = info["required_components"].as_array_mut();
+                    if let Some(required) = required {
+                        *required = required
+                            .iter()
+                            .filter_map(|id| self.component(id))
+                            .collect();
+                        required.sort_by_key(|name| name.to_string());
+                    }
+                    true
+                });
+                components.sort_by_key(|info| info["name"].to_string());
+                if components.is_empty() {
+                    return None;
+                }
+            }
+            "snapshot" => {
+                let entities = event["entities"].as_array_mut()?;
+                entities.retain_mut(|entity| {
+                    let Some(label) = self.entity(&entity[0]) else {
+                        return false;
+                    };
+                    entity[0] = label;
+                    self.component_tuples(&mut entity[1]);
+                    true
+                });
+                entities.sort_by_key(|entity| entity[0].to_string());
+            }
+            "entity" => {
+                event["entity"] = self.entity(&event["entity"])?;
+                let mutation = &mut event["mutation"];
+                if mutation["kind"] == "change" {
+                    self.component_tuples(&mut mutation["changes"]);
+                    self.component_tuples(&mut mutation["removes"]);
+                }
+            }
+            "lifecycle" => {
+                let records = event["records"].as_array_mut()?;
+                records.retain_mut(|record| {
+                    match (self.entity(&record["entity"]), self.component(&record["component"])) {
+                        (Some(label), Some(name)) => {
+                            record["entity"] = label;
+                            record["component"] = name;
+                            true
+                        }
+                        _ => false,
+                    }
+                });
+                for (seq, record) in records.iter_mut().enumerate() {
+                    record["seq"] = json!(seq);
+                }
+                if records.is_empty() {
+                    return None;
+                }
+            }
+            _ => {}
+        }
+        Some(event)
+    }
+}
+
+#[test]
+fn entity_stream() {
+    let mut fixture = Fixture::new();
+    let player = fixture.spawn("player", (Position { x: 1.0, y: 2.0 }, Health(100)));
+    let chest = fixture.spawn("chest", (Position::default(), Opaque(7)));
+    let spark = fixture.spawn("spark", Position { x: 3.0, y: 0.5 });
+    fixture.flush("initial");
+
+    fixture.frame();
+    fixture.world().get_mut::<Position>(player).unwrap().x = 5.0;
+    fixture
+        .world()
+        .entity_mut(chest)
+        .insert(Health(3))
+        .remove::<Opaque>();
+    fixture.flush("mutate");
+
+    fixture.frame();
+    fixture.world().despawn(spark);
+    fixture.flush("despawn");
+
+    fixture.assert_golden("entity_stream");
+}
+
+#[test]
+fn throttled_flushes() {
+    let mut fixture = Fixture::new();
+    let player = fixture.spawn("player", (Position::default(), Health(100)));
+    fixture.flush("initial");
+
+    // A change several frames before the flush is still sent.
+    fixture.frame();
+    fixture.world().get_mut::<Health>(player).unwrap().0 = 90;
+    for _ in 0..3 {
+        fixture.frame();
+    }
+    fixture.flush("after three frames");
+
+    fixture.assert_golden("throttled_flushes");
+}
@@ -17,10 +17,18 @@
 //! - **Space**: Move the camera up.
 //! - **Shift**: Move the camera down.
 //! - **Control**: Toggle run mode for faster movement.
-//! - **Mouse Movement**: Look around.
+//! - **Mouse Movement**: Look around. While the cursor is grabbed the host is asked to
+//!   lock the pointer (`request_pointer_lock`); raw `movementX/Y` deltas from
+//!   `mouse_raw_delta` are then preferred over cursor positions, so looking is not
+//!   stopped by the canvas edges.
 //! - **Right Mouse Button**: Grab/Release the cursor.
+//! - **Middle Mouse Drag**: Orbit around a point in front of the camera.
+//! - **Shift + Middle Mouse Drag**: Pan in the view plane.
 //! - **F**: Toggle cursor grab mode.
-//! - **Scroll Wheel**: Adjust movement speed.
+//! - **Gamepad** (`gamepad_update` FFI): left stick moves, right stick looks, right /
+//!   left shoulder buttons move up / down, left stick press runs.
+//! - **Scroll Wheel**: Adjust movement speed, or dolly towards the point under the
+//!   cursor when `zoom_to_cursor` is enabled (`set_zoom_to_cursor` FFI).
 //!
 //! # Configuration
 //! The camera controller can be configured by modifying the `CameraController`
@@ -34,15 +42,23 @@
 //!     CameraController::default(),
 //! ));
 use bevy::{
-    input::mouse::{MouseButton, MouseScrollUnit}, // Removed AccumulatedMouseScroll
+    input::mouse::{AccumulatedMouseMotion, MouseButton, MouseScrollUnit}, // Removed AccumulatedMouseScroll
     prelude::*,
     window::{CursorGrabMode, CursorMoved}, // Added CursorMoved
 };
 use std::{f32::consts::*, fmt};
+use wasm_bindgen::prelude::*;
 
 // Import your custom accumulator resource for cursor delta from bevy_app
 use crate::bevy_app::AccumulatedScroll; // Removed AccumulatedCursorDelta
 
+#[wasm_bindgen]
+extern "C" {
+    /// Ask the host to lock (or release) the pointer on the canvas.
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn request_pointer_lock(locked: bool);
+}
+
 const RADIANS_PER_DOT: f32 = 0.35 / 180.0;
 
 /// A component for controlling a camera with free-look and movement.
@@ -67,6 +83,17 @@ pub struct CameraController {
     pub key_run: KeyCode,
     pub mouse_key_cursor_grab: MouseButton,
     pub keyboard_key_toggle_cursor_grab: KeyCode,
+    pub mouse_key_orbit: MouseButton,
+    /// Distance in front of the camera of the middle-drag orbit pivot.
+    pub orbit_distance: f32,
+    /// World units per pointer pixel for shift + middle-drag panning.
+    pub pan_speed: f32,
+    /// Wheel dollies along the cursor ray instead of adjusting movement speed.
+    pub zoom_to_cursor: bool,
+    /// Fraction of the distance to the point under the cursor covered per wheel line.
+    pub zoom_factor: f32,
+    /// Right-stick look rate at full deflection, radians per second.
+    pub gamepad_look_speed: f32,
 }
 
 impl Default for CameraController {
@@ -91,6 +118,12 @@ impl Default for CameraController {
             key_run: KeyCode::ShiftLeft,
             mouse_key_cursor_grab: MouseButton::Right,
             keyboard_key_toggle_cursor_grab: KeyCode::KeyF,
+            mouse_key_orbit: MouseButton::Middle,
+            orbit_distance: 10.0,
+            pan_speed: 0.01,
+            zoom_to_cursor: false,
+            zoom_factor: 0.1,
+            gamepad_look_speed: 2.5,
         }
     }
 }
@@ -111,20 +144,26 @@ pub struct CameraControllerPlugin;
 
 impl Plugin for CameraControllerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, run_camera_controller);
+        app.add_systems(Update, (run_camera_controller, zoom_to_cursor));
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn run_camera_controller(
     time: Res<Time>,
     mut windows: Query<&mut Window>,
     mut cursor_moved_events: EventReader<CursorMoved>, // Added
     accumulated_scroll: Res<AccumulatedScroll>,
+    raw_motion: Res<AccumulatedMouseMotion>,
     mouse_button_input: Res<ButtonInput<MouseButton>>,
     key_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    pointer: Res<crate::PointerState>,
     mut toggle_cursor_grab: Local<bool>,
     mut mouse_cursor_grab: Local<bool>,
     mut last_mouse_position: Local<Option<Vec2>>, // Added to track mouse delta
+    mut pressed_over_ui: Local<bool>,             // grab/orbit press landed on host UI
+    mut raw_look: Local<bool>,                    // raw deltas arrived during this grab
     mut query: Query<(&mut Transform, &mut CameraController), With<Camera>>,
 ) {
     let dt = time.delta_secs();
@@ -144,16 +183,10 @@ fn run_camera_controller(
         return;
     }
 
-    let mut scroll_input_amount = 0.0;
-    // Use AccumulatedScroll directly
-    if accumulated_scroll.delta.y.abs() > 0.0 {
-        scroll_input_amount = match accumulated_scroll.unit {
-            MouseScrollUnit::Line => accumulated_scroll.delta.y,
-            MouseScrollUnit::Pixel => accumulated_scroll.delta.y / 16.0, // Adjust divisor as needed
-        };
-    }
+    // Use AccumulatedScroll directly (the wheel belongs to `zoom_to_cursor` when enabled)
+    let scroll_input_amount = scroll_lines(&accumulated_scroll);
 
-    if scroll_input_amount.abs() > 0.0 {
+    if scroll_input_amount.abs() > 0.0 && !controller.zoom_to_cursor && !pointer.excluded {
         let current_speed = if key_input.pressed(controller.key_run) {
             controller.run_speed
         } else {
@@ -195,6 +228,32 @@ fn run_camera_controller(
         axis_input.y -= 1.0;
     }
 
+    // Presses over host UI (`set_ui_exclusion_rects`) don't grab or orbit until released.
+    let grab_or_orbit = [controller.mouse_key_cursor_grab, controller.mouse_key_orbit];
+    if pointer.excluded && mouse_button_input.any_just_pressed(grab_or_orbit) {
+        *pressed_over_ui = true;
+    }
+    if !mouse_button_input.any_pressed(grab_or_orbit) {
+        *pressed_over_ui = false;
+    }
+
+    // Sticks add to the keys; analog deflection scales the speed below.
+    let mut gamepad_run = false;
+    let mut gamepad_look = Vec2::ZERO;
+    for gamepad in &gamepads {
+        let stick = gamepad.left_stick();
+        axis_input.x += stick.x;
+        axis_input.z += stick.y;
+        if gamepad.pressed(GamepadButton::RightTrigger) {
+            axis_input.y += 1.0;
+        }
+        if gamepad.pressed(GamepadButton::LeftTrigger) {
+            axis_input.y -= 1.0;
+        }
+        gamepad_run |= gamepad.pressed(GamepadButton::LeftThumb);
+        gamepad_look += gamepad.right_stick();
+    }
+
     let mut cursor_grab_change = false;
     let prev_cursor_grab = *mouse_cursor_grab || *toggle_cursor_grab;
 
@@ -205,7 +264,7 @@ fn run_camera_controller(
         *toggle_cursor_grab = !*toggle_cursor_grab;
         cursor_grab_change = true;
     }
-    if mouse_button_input.just_pressed(controller.mouse_key_cursor_grab) {
+    if mouse_button_input.just_pressed(controller.mouse_key_cursor_grab) && !*pressed_over_ui {
         *mouse_cursor_grab = true;
         cursor_grab_change = true;
     }
@@ -219,15 +278,23 @@ fn run_camera_controller(
         // Just grabbed the cursor, invalidate last_mouse_position to prevent jump
         *last_mouse_position = None;
     }
+    if cursor_grab_change && cursor_grab != prev_cursor_grab {
+        request_pointer_lock(cursor_grab);
+    }
+    if !cursor_grab {
+        *raw_look = false;
+    } else if raw_motion.delta != Vec2::ZERO {
+        *raw_look = true;
+    }
 
     // Apply movement update
     if axis_input != Vec3::ZERO {
-        let max_speed = if key_input.pressed(controller.key_run) {
+        let max_speed = if key_input.pressed(controller.key_run) || gamepad_run {
             controller.run_speed
         } else {
             controller.walk_speed
         };
-        controller.velocity = axis_input.normalize() * max_speed;
+        controller.velocity = axis_input.clamp_length_max(1.0) * max_speed;
     } else {
         let friction = controller.friction.clamp(0.0, 1.0);
         controller.velocity *= 1.0 - friction;
@@ -241,6 +308,13 @@ fn run_camera_controller(
         + controller.velocity.y * dt * Vec3::Y
         + controller.velocity.z * dt * forward;
 
+    if gamepad_look != Vec2::ZERO {
+        let look = gamepad_look * controller.gamepad_look_speed * dt;
+        controller.pitch = (controller.pitch + look.y).clamp(-PI / 2., PI / 2.);
+        controller.yaw -= look.x;
+        transform.rotation = Quat::from_euler(EulerRot::ZYX, 0.0, controller.yaw, controller.pitch);
+    }
+
     // Handle cursor grab
     // Note: Directly manipulating window.cursor_options might need to be
     // handled via JavaScript calls in a WASM/FFI context if this doesn't work as expected.
@@ -262,9 +336,10 @@ fn run_camera_controller(
         }
     }
 
-    // Handle mouse input for rotation
+    // Handle mouse input for rotation (grabbed cursor) and middle-drag orbit / pan
+    let orbiting = mouse_button_input.pressed(controller.mouse_key_orbit) && !*pressed_over_ui;
     let mut mouse_movement_delta = Vec2::ZERO;
-    if cursor_grab {
+    if cursor_grab || orbiting {
         for event in cursor_moved_events.read() {
             if let Some(last_pos) = *last_mouse_position {
                 mouse_movement_delta += event.position - last_pos;
@@ -275,6 +350,31 @@ fn run_camera_controller(
         *last_mouse_position = None; // Clear last position if not grabbed
         cursor_moved_events.clear(); // Consume events if not grabbed to prevent buildup
     }
+    if cursor_grab && *raw_look {
+        // Pointer-locked: positions are frozen at the lock point, use raw deltas.
+        mouse_movement_delta = raw_motion.delta;
+    }
+
+    if mouse_movement_delta != Vec2::ZERO && orbiting && !cursor_grab {
+        let shift = key_input.pressed(KeyCode::ShiftLeft) || key_input.pressed(KeyCode::ShiftRight);
+        if shift {
+            let right = *transform.right();
+            let up = *transform.up();
+            transform.translation += (-right * mouse_movement_delta.x
+                + up * mouse_movement_delta.y)
+                * controller.pan_speed;
+        } else {
+            let pivot = transform.translation + *transform.forward() * controller.orbit_distance;
+            controller.pitch = (controller.pitch
+                - mouse_movement_delta.y * RADIANS_PER_DOT * controller.sensitivity)
+                .clamp(-PI / 2., PI / 2.);
+            controller.yaw -= mouse_movement_delta.x * RADIANS_PER_DOT * controller.sensitivity;
+            transform.rotation =
+                Quat::from_euler(EulerRot::ZYX, 0.0, controller.yaw, controller.pitch);
+            transform.translation = pivot - *transform.forward() * controller.orbit_distance;
+        }
+        return;
+    }
 
     if mouse_movement_delta != Vec2::ZERO && cursor_grab {
         // Apply look update
@@ -285,3 +385,41 @@ fn run_camera_controller(
         transform.rotation = Quat::from_euler(EulerRot::ZYX, 0.0, controller.yaw, controller.pitch);
     }
 }
+
+fn scroll_lines(scroll: &AccumulatedScroll) -> f32 {
+    match scroll.unit {
+        MouseScrollUnit::Line => scroll.delta.y,
+        MouseScrollUnit::Pixel => scroll.delta.y / 16.0, // Adjust divisor as needed
+    }
+}
+
+/// Dolly the camera along the ray under the cursor. The step is a fraction of the
+/// distance to the ground plane (y = 0) hit by that ray, falling back to the orbit
+/// distance when the ray misses it, and never crosses the target point.
+fn zoom_to_cursor(
+    accumulated_scroll: Res<AccumulatedScroll>,
+    pointer: Res<crate::PointerState>,
+    mapper: Res<crate::bevy_app::CoordinateMapper>,
+    mut query: Query<(&mut Transform, &GlobalTransform, &Camera, &CameraController)>,
+) {
+    let Ok((mut transform, global, camera, controller)) = query.single_mut() else {
+        return;
+    };
+    if !controller.enabled || !controller.zoom_to_cursor || pointer.excluded {
+        return;
+    }
+    let lines = scroll_lines(&accumulated_scroll);
+    if lines == 0.0 {
+        return;
+    }
+    let Some(ray) = mapper.viewport_ray(camera, global, pointer.screen) else {
+        return;
+    };
+    let distance = (ray.direction.y.abs() > 1e-5)
+        .then(|| -ray.origin.y / ray.direction.y)
+        .filter(|t| *t > 0.0)
+        .unwrap_or(controller.orbit_distance);
+    // Wheel up (negative delta) zooms in.
+    let step = (-lines * controller.zoom_factor * distance).min(distance * 0.9);
+    transform.translation += *ray.direction * step;
+}
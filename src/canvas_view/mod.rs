@@ -4,14 +4,41 @@
 //! OffscreenCanvas in a worker) into a Bevy `Window` + `RawHandleWrapper`.
 //! There is exactly one window; panel subdivision happens via camera viewports
 //! (see `crate::panels`).
+//!
+//! Loss recovery: wgpu's device-lost callback and its uncaptured-error handler (for
+//! errors raised by the surface, e.g. a canvas whose context went away) flip
+//! `SurfaceHealth`, frames stop (Bevy panics on an unusable swap chain) and the host
+//! is told via `send_canvas_status_from_worker`. A replacement canvas is attached with
+//! `replace_canvas_surface`, which swaps the window's raw handle so the render world
+//! rebuilds its surface. Bevy cannot recreate its `RenderDevice` in place, so after a
+//! real device loss `reinit_canvas` rebuilds the whole app on a new device instead
+//! (see `web_ffi::recover_lost_device`).
+//!
+//! Pausing: `set_canvas_active(ptr, canvas_id, false)` stops a hidden canvas from
+//! rendering. Its window loses its raw handle (so the render world drops the surface
+//! and presents nothing to it) and every camera targeting it is switched off and held
+//! off by `hold_paused_cameras`; reactivating restores both. App logic keeps running.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use app_surface::{CanvasWrapper, OffscreenCanvasWrapper};
 use bevy::ecs::system::SystemState;
+use bevy::platform::collections::HashMap;
 use bevy::prelude::*;
+use bevy::render::camera::NormalizedRenderTarget;
+use bevy::render::renderer::RenderDevice;
 use bevy::window::{
     PresentMode, PrimaryWindow, RawHandleWrapper, Window, WindowCreated, WindowResized,
     WindowWrapper,
 };
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+
+use crate::Locale;
+use crate::bevy_app::CoordinateMapper;
+use crate::bevy_app::options::AppOptions;
+use wgpu::DeviceLostReason;
 
 pub(crate) use app_surface::{Canvas, OffscreenCanvas};
 
@@ -40,6 +67,17 @@ impl ViewObj {
     }
 }
 
+#[wasm_bindgen]
+extern "C" {
+    /// `{ status: "device_lost" | "surface_lost" | "reinitializing" | "ready" | "failed", ... }`
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_canvas_status_from_worker(status_json: &str);
+}
+
+pub(crate) fn send_canvas_status(status: serde_json::Value) {
+    send_canvas_status_from_worker(&status.to_string());
+}
+
 /// NonSend resource keeping the canvas wrapper (and thus the raw handles) alive
 /// for the lifetime of the app.
 pub struct ActiveCanvas {
@@ -47,32 +85,278 @@ pub struct ActiveCanvas {
     pub window: Entity,
 }
 
+fn raw_handle(view: &ViewObj) -> Option<RawHandleWrapper> {
+    match view {
+        ViewObj::Canvas(wrapper) => RawHandleWrapper::new(wrapper),
+        ViewObj::Offscreen(wrapper) => RawHandleWrapper::new(wrapper),
+    }
+    .ok()
+}
+
+/// Shared with the device-lost callback and the uncaptured-error handler, which run
+/// outside any system.
+#[derive(Resource, Clone, Default)]
+pub struct SurfaceHealth {
+    device_lost: Arc<AtomicBool>,
+    surface_error: Arc<AtomicBool>,
+}
+
+impl SurfaceHealth {
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::Acquire)
+    }
+
+    /// The surface reported an error (lost or outdated context, failed configure);
+    /// cleared when `replace_canvas_surface` attaches a new canvas.
+    pub fn is_surface_lost(&self) -> bool {
+        self.surface_error.load(Ordering::Acquire)
+    }
+}
+
+/// Whether an uncaptured wgpu error came from the canvas surface rather than, say, a
+/// bad pipeline. wgpu only hands over the message, so this goes by its wording.
+fn is_surface_error(error: &wgpu::Error) -> bool {
+    let message = error.to_string().to_ascii_lowercase();
+    ["surface", "swap chain", "swapchain", "canvas"]
+        .iter()
+        .any(|needle| message.contains(needle))
+}
+
+/// Install the device-lost callback and the uncaptured-error handler once the renderer
+/// exists (main-world `RenderDevice` is only inserted when the plugins finish).
+pub fn watch_device_loss(app: &mut App) {
+    if app.world().contains_resource::<SurfaceHealth>() {
+        return;
+    }
+    let Some(device) = app.world().get_resource::<RenderDevice>() else {
+        return;
+    };
+    let health = SurfaceHealth::default();
+    let device_lost = health.device_lost.clone();
+    device
+        .wgpu_device()
+        .set_device_lost_callback(move |reason, message| {
+            // `Destroyed` is our own teardown in `release_app`.
+            if reason == DeviceLostReason::Destroyed {
+                return;
+            }
+            device_lost.store(true, Ordering::Release);
+            send_canvas_status(json!({
+                "status": "device_lost",
+                "reason": format!("{reason:?}"),
+                "message": message,
+            }));
+        });
+    // Replaces wgpu's default handler, which panics on every uncaptured error.
+    let surface_error = health.surface_error.clone();
+    device
+        .wgpu_device()
+        .on_uncaptured_error(Box::new(move |error| {
+            if !is_surface_error(&error) {
+                error!("Uncaptured GPU error: {error}");
+                return;
+            }
+            if !surface_error.swap(true, Ordering::AcqRel) {
+                send_canvas_status(json!({
+                    "status": "surface_lost",
+                    "message": error.to_string(),
+                }));
+            }
+        }));
+    app.insert_resource(health);
+}
+
+/// True once the GPU device is gone; frames must not run after that.
+pub fn is_device_lost(app: &App) -> bool {
+    app.world()
+        .get_resource::<SurfaceHealth>()
+        .is_some_and(SurfaceHealth::is_device_lost)
+}
+
+/// True while the canvas surface is unusable; frames stop until `reinit_canvas`
+/// attaches a new canvas.
+pub fn is_surface_lost(app: &App) -> bool {
+    app.world()
+        .get_resource::<SurfaceHealth>()
+        .is_some_and(SurfaceHealth::is_surface_lost)
+}
+
+/// Canvas windows paused with `pause_canvas`, with what was switched off for each.
+#[derive(Resource, Default)]
+pub struct PausedCanvases {
+    windows: HashMap<Entity, PausedCanvas>,
+}
+
+struct PausedCanvas {
+    /// The window's raw handle, put back on resume.
+    handle: Option<RawHandleWrapper>,
+    /// Cameras that were rendering to the window.
+    cameras: Vec<Entity>,
+}
+
+impl PausedCanvases {
+    pub fn is_paused(&self, window: Entity) -> bool {
+        self.windows.contains_key(&window)
+    }
+}
+
+/// Stop rendering to `window`: its surface is dropped and its cameras go inactive
+/// (see `hold_paused_cameras`). No-op if it is already paused.
+pub fn pause_canvas(world: &mut World, window: Entity) {
+    if world
+        .get_resource::<PausedCanvases>()
+        .is_none_or(|paused| paused.is_paused(window))
+    {
+        return;
+    }
+    let handle = world
+        .get_entity_mut(window)
+        .ok()
+        .and_then(|mut entity| entity.take::<RawHandleWrapper>());
+    world.resource_mut::<PausedCanvases>().windows.insert(
+        window,
+        PausedCanvas {
+            handle,
+            cameras: Vec::new(),
+        },
+    );
+}
+
+/// Undo `pause_canvas`: the cameras it switched off come back on and the window gets
+/// a fresh surface at the next extract. No-op if `window` isn't paused.
+pub fn resume_canvas(world: &mut World, window: Entity) {
+    let Some(paused) = world
+        .get_resource_mut::<PausedCanvases>()
+        .and_then(|mut paused| paused.windows.remove(&window))
+    else {
+        return;
+    };
+    for camera in paused.cameras {
+        if let Some(mut camera) = world.get_mut::<Camera>(camera) {
+            camera.is_active = true;
+        }
+    }
+    if let Some(handle) = paused.handle
+        && let Ok(mut entity) = world.get_entity_mut(window)
+    {
+        entity.insert(handle);
+    }
+}
+
+/// Keep cameras targeting a paused canvas off, including ones another system switched
+/// back on this frame, and remember them for the resume. Runs in `Last`, after every
+/// system that toggles cameras.
+pub fn hold_paused_cameras(
+    mut paused: ResMut<PausedCanvases>,
+    primary: Query<Entity, With<PrimaryWindow>>,
+    mut cameras: Query<(Entity, &mut Camera)>,
+) {
+    if paused.windows.is_empty() {
+        return;
+    }
+    let primary = primary.single().ok();
+    for (entity, mut camera) in &mut cameras {
+        if !camera.is_active {
+            continue;
+        }
+        let Some(NormalizedRenderTarget::Window(window)) = camera.target.normalize(primary)
+        else {
+            continue;
+        };
+        let Some(canvas) = paused.windows.get_mut(&window.entity()) else {
+            continue;
+        };
+        camera.is_active = false;
+        if !canvas.cameras.contains(&entity) {
+            canvas.cameras.push(entity);
+        }
+    }
+}
+
+/// Point the existing window at a new canvas (e.g. after the browser dropped the old
+/// one's context). Keeping the window entity keeps cameras and panel viewports
+/// targeting it valid. Returns the window, or None if there is none yet or the canvas
+/// handle can't be wrapped.
+pub fn replace_canvas_surface(app: &mut App, view: ViewObj) -> Option<Entity> {
+    let window = app.world().get_non_send_resource::<ActiveCanvas>()?.window;
+    let raw_handle = raw_handle(&view)?;
+    let paused = app
+        .world()
+        .get_resource::<PausedCanvases>()
+        .is_some_and(|paused| paused.is_paused(window));
+    if paused {
+        // A paused canvas gets the new handle on resume.
+        let mut paused = app.world_mut().resource_mut::<PausedCanvases>();
+        if let Some(canvas) = paused.windows.get_mut(&window) {
+            canvas.handle = Some(raw_handle);
+        }
+    } else {
+        // The removal makes the render world drop the old surface at the next extract;
+        // the new handle gets a fresh surface on the frame after.
+        app.world_mut()
+            .get_entity_mut(window)
+            .ok()?
+            .remove::<RawHandleWrapper>()
+            .insert(raw_handle);
+    }
+    app.insert_non_send_resource(ActiveCanvas { view, window });
+    update_canvas_window(app);
+    reupload_gpu_assets(app.world_mut());
+    if let Some(health) = app.world().get_resource::<SurfaceHealth>() {
+        health.surface_error.store(false, Ordering::Release);
+    }
+    Some(window)
+}
+
+/// Mark every image and mesh modified so the render world re-prepares them from their
+/// main-world copies. Assets that only live in the render world can't be restored.
+fn reupload_gpu_assets(world: &mut World) {
+    let mut images = world.resource_mut::<Assets<Image>>();
+    let ids: Vec<_> = images.ids().collect();
+    for id in ids {
+        images.get_mut(id);
+    }
+    let mut meshes = world.resource_mut::<Assets<Mesh>>();
+    let ids: Vec<_> = meshes.ids().collect();
+    for id in ids {
+        meshes.get_mut(id);
+    }
+}
+
 /// Spawn the single primary window for the provided canvas and wire up its raw handle.
 pub fn create_canvas_window(app: &mut App, view: ViewObj) -> Entity {
     let (width, height) = view.physical_resolution();
 
+    let present_mode = app
+        .world()
+        .get_resource::<AppOptions>()
+        .map_or(PresentMode::AutoNoVsync, |options| {
+            options.vsync.present_mode()
+        });
+    let title = app.world().get_resource::<Locale>().map_or_else(
+        || Locale::english("window.title").to_owned(),
+        |locale| locale.get("window.title").to_owned(),
+    );
     let mut window = Window {
-        title: "Ironfell".to_owned(),
-        present_mode: PresentMode::AutoNoVsync,
+        title,
+        present_mode,
         ..default()
     };
     // Scale is handled JS-side; everything Rust-side is physical pixels.
     window.resolution.set_scale_factor(1.0);
     window.resolution.set(width as f32, height as f32);
 
-    let raw_handle = match &view {
-        ViewObj::Canvas(wrapper) => RawHandleWrapper::new(wrapper),
-        ViewObj::Offscreen(wrapper) => RawHandleWrapper::new(wrapper),
-    }
-    .expect("failed to wrap canvas window handle");
+    let raw_handle = raw_handle(&view).expect("failed to wrap canvas window handle");
 
     let entity = app
         .world_mut()
         .spawn((window, PrimaryWindow, raw_handle))
         .id();
 
-    app.world_mut()
-        .send_event(WindowCreated { window: entity });
+    if let Some(mut mapper) = app.world_mut().get_resource_mut::<CoordinateMapper>() {
+        mapper.resize(entity, Vec2::new(width as f32, height as f32));
+    }
+    app.world_mut().send_event(WindowCreated { window: entity });
     app.insert_non_send_resource(ActiveCanvas {
         view,
         window: entity,
@@ -93,13 +377,19 @@ pub fn update_canvas_window(app: &mut App) {
         return;
     };
 
-    let mut system_state: SystemState<(Query<(Entity, &mut Window)>, EventWriter<WindowResized>)> =
-        SystemState::new(app.world_mut());
-    let (mut windows, mut resize_events) = system_state.get_mut(app.world_mut());
+    let mut system_state: SystemState<(
+        Query<(Entity, &mut Window)>,
+        EventWriter<WindowResized>,
+        Option<ResMut<CoordinateMapper>>,
+    )> = SystemState::new(app.world_mut());
+    let (mut windows, mut resize_events, mut mapper) = system_state.get_mut(app.world_mut());
 
     for (entity, mut window) in windows.iter_mut() {
         window.resolution.set_scale_factor(1.0);
         window.resolution.set(width as f32, height as f32);
+        if let Some(mapper) = mapper.as_mut() {
+            mapper.resize(entity, Vec2::new(width as f32, height as f32));
+        }
         resize_events.write(WindowResized {
             window: entity,
             width: width as f32,
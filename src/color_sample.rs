@@ -0,0 +1,84 @@
+//! Eyedropper: the color of a rendered canvas pixel, for the host's color picker.
+//!
+//! `sample_color_at(ptr, canvas_id, x, y)` captures the canvas as presented (3D viewer,
+//! overlay and panels together) and answers a frame or two later through
+//! `send_color_sample_from_worker(request_id, color_json)`. Bevy only reads back whole
+//! surfaces, so each sample copies the full canvas once; fine per click, not per frame.
+
+use bevy::prelude::*;
+use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured};
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::CoordinateMapper;
+use crate::{ActivityControl, WorkerApp, canvas_window};
+
+#[wasm_bindgen]
+extern "C" {
+    /// Deliver a `sample_color_at` result: `{ rgba: [r, g, b, a], hex }` (sRGB, 0..1),
+    /// or `null` when the capture failed or the point was off the canvas.
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_color_sample_from_worker(request_id: u32, color_json: &str);
+}
+
+#[derive(Resource, Default)]
+struct ColorSampleRequests {
+    next_id: u32,
+}
+
+/// Sample the rendered color at canvas position (`x`, `y`) in CSS px, like
+/// `mouse_move`. `canvas_id` is the window id as for `reinit_canvas` (0 for the
+/// primary window). Returns the request id the answer will carry, or 0 for an unknown
+/// canvas.
+#[wasm_bindgen]
+pub fn sample_color_at(ptr: u64, canvas_id: u64, x: f32, y: f32) -> u32 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(window) = canvas_window(app, canvas_id) else {
+        return 0;
+    };
+    let position = match app.world().get_resource::<CoordinateMapper>() {
+        Some(mapper) => mapper.host_to_window(window, Vec2::new(x, y)),
+        None => Vec2::new(x, y) * app.scale_factor,
+    };
+
+    let world = app.world_mut();
+    let mut requests = world.get_resource_or_init::<ColorSampleRequests>();
+    requests.next_id += 1;
+    let request_id = requests.next_id;
+    world
+        .spawn(Screenshot::window(window))
+        .observe(deliver_color_sample(request_id, position.floor()));
+
+    // The capture lands after the next render.
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    request_id
+}
+
+/// Observer reading the pixel at `position` (window px) out of the capture.
+fn deliver_color_sample(
+    request_id: u32,
+    position: Vec2,
+) -> impl FnMut(Trigger<ScreenshotCaptured>) {
+    move |trigger| {
+        let image = &trigger.event().0;
+        let in_bounds = position.cmpge(Vec2::ZERO).all()
+            && position.x < image.width() as f32
+            && position.y < image.height() as f32;
+        let color = in_bounds
+            .then(|| image.get_color_at(position.x as u32, position.y as u32))
+            .and_then(|color| {
+                color
+                    .inspect_err(|e| warn!("sample_color_at {request_id}: {e}"))
+                    .ok()
+            });
+        let json = match color.map(|color| color.to_srgba()) {
+            Some(srgba) => serde_json::json!({
+                "rgba": srgba.to_f32_array(),
+                "hex": srgba.to_hex(),
+            }),
+            None => serde_json::Value::Null,
+        };
+        send_color_sample_from_worker(request_id, &json.to_string());
+    }
+}
@@ -0,0 +1,113 @@
+//! Validation of entity ids that arrive from JS as raw `u64` bits.
+//!
+//! `Entity::from_bits` panics on bits that do not decode to an entity, and a decoded
+//! entity may be dead or an older generation of a reused index. FFI entry points go
+//! through `live_entity` instead; `validate_entity_id` reports why an id is rejected.
+//! FFI functions that reject an id (returning false, 0 or undefined) also send the
+//! structured error to the host through `send_entity_id_error_from_worker`.
+
+use bevy::prelude::*;
+use serde_json::{Value, json};
+use wasm_bindgen::prelude::*;
+
+use crate::WorkerApp;
+
+#[wasm_bindgen]
+extern "C" {
+    /// `{ caller, id, error, message, live }` for an id an FFI function rejected
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_entity_id_error_from_worker(error_json: &str);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityIdError {
+    /// The bits do not decode to an entity (e.g. a zero generation).
+    InvalidBits(u64),
+    /// No entity lives at that index.
+    NotFound(Entity),
+    /// The index was reused; `live` is the entity there now.
+    StaleGeneration { entity: Entity, live: Entity },
+}
+
+impl EntityIdError {
+    /// Stable identifier for the host.
+    pub fn code(&self) -> &'static str {
+        match self {
+            EntityIdError::InvalidBits(_) => "invalid_bits",
+            EntityIdError::NotFound(_) => "not_found",
+            EntityIdError::StaleGeneration { .. } => "stale_generation",
+        }
+    }
+
+    /// `{ "error": code, "message": text, "live": bits | null }`.
+    pub fn to_json(&self) -> Value {
+        let live = match self {
+            EntityIdError::StaleGeneration { live, .. } => Some(live.to_bits()),
+            _ => None,
+        };
+        json!({ "error": self.code(), "message": self.to_string(), "live": live })
+    }
+}
+
+impl std::fmt::Display for EntityIdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EntityIdError::InvalidBits(bits) => write!(f, "{bits} is not a valid entity id"),
+            EntityIdError::NotFound(entity) => write!(f, "entity {entity} does not exist"),
+            EntityIdError::StaleGeneration { entity, live } => {
+                write!(
+                    f,
+                    "entity {entity} was despawned; its index now holds {live}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for EntityIdError {}
+
+/// Decode `bits` and check they name a live entity of the current generation.
+pub fn live_entity(world: &World, bits: u64) -> Result<Entity, EntityIdError> {
+    let entity = Entity::try_from_bits(bits).map_err(|_| EntityIdError::InvalidBits(bits))?;
+    if world.get_entity(entity).is_ok() {
+        return Ok(entity);
+    }
+    match world.entities().resolve_from_id(entity.index()) {
+        Some(live) if live != entity && world.get_entity(live).is_ok() => {
+            Err(EntityIdError::StaleGeneration { entity, live })
+        }
+        _ => Err(EntityIdError::NotFound(entity)),
+    }
+}
+
+/// `live_entity`, logging the rejection on behalf of the FFI function `caller` and
+/// sending it to the host as `{ caller, id, error, message, live }`.
+pub(crate) fn live_entity_or_warn(world: &World, bits: u64, caller: &str) -> Option<Entity> {
+    live_entity(world, bits)
+        .inspect_err(|e| {
+            warn!("{caller}: {e}");
+            let mut error = e.to_json();
+            error["caller"] = json!(caller);
+            error["id"] = json!(bits);
+            send_entity_id_error_from_worker(&error.to_string());
+        })
+        .ok()
+}
+
+/// Check an entity id from JS. Returns `{ "ok": true }` or
+/// `{ "ok": false, "error": code, "message": text, "live": bits | null }`, `code` being
+/// "invalid_bits", "not_found" or "stale_generation" (`live` is the entity that now
+/// holds the index).
+#[wasm_bindgen]
+pub fn validate_entity_id(ptr: u64, id: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let result = match live_entity(app.world(), id) {
+        Ok(_) => json!({ "ok": true }),
+        Err(e) => {
+            let mut result = e.to_json();
+            result["ok"] = json!(false);
+            result
+        }
+    };
+    result.to_string()
+}
@@ -1,14 +1,29 @@
-use crate::WorkerApp;
+use crate::bevy_app::history::{self, EntitySnapshot, History, HistoryEntry, HistoryOp};
+use crate::bevy_app::placement::{self, ObjectKind, SpawnObject};
+use crate::bevy_app::scatter::ScatterEntities;
+use crate::bevy_app::shape_templates::{ShapeKind, ShapeParams, SpawnShape};
+use crate::entity_id::live_entity_or_warn;
+use crate::op_log;
+use crate::{ActivityControl, Locale, PointerState, SelectionState, WorkerApp, trace_command};
+use bevy::ecs::component::ComponentId;
 use bevy::prelude::*;
 use bevy_remote_inspector::{
-    InspectorContext, TrackedDatas,
+    InspectorContext, RemoteInspectorCore, TrackedDatas,
     command::{
-        DespawnEntity, Execute, InsertComponent, RemoveComponent, ReparentEntity, SpawnEntity,
-        ToggleComponent, ToggleVisibity, UpdateComponent,
+        AdjustComponentField, AdjustOp, DespawnEntity, DespawnEntityKind, Execute, InsertComponent,
+        ReadComponent, RemoveComponent, ReparentEntity, SpawnEntity, ToggleComponent,
+        ToggleVisibity, UpdateComponent,
     },
-    get_inspector_events,
+    disconnect_client,
+    policy::{StreamingFocus, StreamingPolicy},
+    set_outline_mode, set_reveal_hidden, stream_inspector_events, suppress_component_changes,
+    transport::InspectorTransport,
+    watch_entity,
 };
+use send_wrapper::SendWrapper;
 use serde_json::Value;
+use std::cell::RefCell;
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -16,9 +31,226 @@ extern "C" {
     /// Send inspector updates from worker to main thread
     #[wasm_bindgen(js_namespace = rustBridge)]
     pub(crate) fn send_inspector_update_from_worker(update_json: &str);
+    /// Send inspector updates for a client opened with `inspector_connect`
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_inspector_client_update_from_worker(client_id: u32, update_json: &str);
+    /// Send a gzip-compressed inspector update (a client's snapshot) for any client
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_inspector_gzip_update_from_worker(client_id: u32, update_gzip: &[u8]);
+}
+
+/// Inspector client id of the host page, connected at startup.
+pub const HOST_INSPECTOR_CLIENT: u32 = 0;
+
+/// Delivers inspector events to the host page over the worker postMessage bridge.
+/// Updates for the default client keep their untagged message; other clients' carry
+/// the client id so the host can route them to the right view.
+pub struct PostMessageTransport;
+
+impl InspectorTransport for PostMessageTransport {
+    fn send(&mut self, client_id: u32, events_json: &str) {
+        if client_id == HOST_INSPECTOR_CLIENT {
+            send_inspector_update_from_worker(events_json);
+        } else {
+            send_inspector_client_update_from_worker(client_id, events_json);
+        }
+    }
+
+    fn accepts_gzip(&self) -> bool {
+        true
+    }
+
+    fn send_gzip(&mut self, client_id: u32, events_gzip: &[u8]) {
+        send_inspector_gzip_update_from_worker(client_id, events_gzip);
+    }
+}
+
+/// One queued WebSocket message.
+enum SocketFrame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+fn send_frame(socket: &web_sys::WebSocket, frame: &SocketFrame) {
+    let result = match frame {
+        SocketFrame::Text(json) => socket.send_with_str(json),
+        SocketFrame::Binary(bytes) => socket.send_with_u8_array(bytes),
+    };
+    if let Err(e) = result {
+        warn!("Inspector WebSocket send failed: {e:?}");
+    }
+}
+
+/// Delivers inspector events over a WebSocket, e.g. to an inspector UI outside the
+/// page. Batches go out as text frames, gzip snapshots as binary frames. Batches sent
+/// while the socket is still connecting (the snapshot, usually) are queued and flushed
+/// when it opens; once it closes, batches are dropped. Edits still come in through the
+/// FFI functions. JS objects only live on the thread that made them; `SendWrapper`
+/// satisfies `InspectorTransport`'s bounds and checks that.
+pub struct WebSocketTransport {
+    socket: SendWrapper<web_sys::WebSocket>,
+    pending: SendWrapper<Rc<RefCell<Vec<SocketFrame>>>>,
+    _on_open: SendWrapper<Closure<dyn FnMut()>>,
+}
+
+impl WebSocketTransport {
+    /// Start connecting to `url` (`ws://` or `wss://`).
+    pub fn connect(url: &str) -> Result<Self, JsValue> {
+        let socket = web_sys::WebSocket::new(url)?;
+        socket.set_binary_type(web_sys::BinaryType::Arraybuffer);
+        let pending: Rc<RefCell<Vec<SocketFrame>>> = Rc::default();
+        let on_open = {
+            let socket = socket.clone();
+            let pending = pending.clone();
+            Closure::<dyn FnMut()>::new(move || {
+                for frame in pending.borrow_mut().drain(..) {
+                    send_frame(&socket, &frame);
+                }
+            })
+        };
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        Ok(Self {
+            socket: SendWrapper::new(socket),
+            pending: SendWrapper::new(pending),
+            _on_open: SendWrapper::new(on_open),
+        })
+    }
+
+    fn deliver(&mut self, frame: SocketFrame) {
+        match self.socket.ready_state() {
+            web_sys::WebSocket::CONNECTING => self.pending.borrow_mut().push(frame),
+            web_sys::WebSocket::OPEN => send_frame(&self.socket, &frame),
+            // Closing or closed: nobody is listening any more.
+            _ => {}
+        }
+    }
+}
+
+impl InspectorTransport for WebSocketTransport {
+    fn send(&mut self, _client_id: u32, events_json: &str) {
+        self.deliver(SocketFrame::Text(events_json.to_string()));
+    }
+
+    fn accepts_gzip(&self) -> bool {
+        true
+    }
+
+    fn send_gzip(&mut self, _client_id: u32, events_gzip: &[u8]) {
+        self.deliver(SocketFrame::Binary(events_gzip.to_vec()));
+    }
+}
+
+impl Drop for WebSocketTransport {
+    fn drop(&mut self) {
+        self.socket.set_onopen(None);
+        let _ = self.socket.close();
+    }
 }
 
-/// Update a component on an entity
+/// Open an inspector client that streams to a WebSocket at `url`, with its own tracked
+/// state like `inspector_connect`; `inspector_disconnect` closes the socket. Returns
+/// the client id, or 0 if the inspector is not running or `url` is not a valid
+/// WebSocket URL.
+#[wasm_bindgen]
+pub fn inspector_connect_websocket(ptr: u64, url: &str) -> u32 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(mut core) = app.world_mut().get_resource_mut::<RemoteInspectorCore>() else {
+        return HOST_INSPECTOR_CLIENT;
+    };
+    match WebSocketTransport::connect(url) {
+        Ok(transport) => core.connect_new(transport),
+        Err(e) => {
+            warn!("inspector_connect_websocket: can't open {url}: {e:?}");
+            HOST_INSPECTOR_CLIENT
+        }
+    }
+}
+
+/// Open another inspector client with its own tracked state (e.g. a detail panel next
+/// to the hierarchy). Its full snapshot goes out with the next stream, so the host can
+/// register the id first and then `force_inspector_update`. Returns the client id, or
+/// 0 (the host client) if the inspector is not running.
+#[wasm_bindgen]
+pub fn inspector_connect(ptr: u64) -> u32 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    match app.world_mut().get_resource_mut::<RemoteInspectorCore>() {
+        Some(mut core) => core.connect_new(PostMessageTransport),
+        None => HOST_INSPECTOR_CLIENT,
+    }
+}
+
+/// Stream only entity ids, names and parent links to `client_id`, plus full component
+/// data for entities it watches with `inspector_watch_entity` (or go back to full
+/// streaming). The client receives a new snapshot in the chosen mode. Returns false if
+/// the client is not connected.
+#[wasm_bindgen]
+pub fn set_inspector_outline_mode(ptr: u64, client_id: u32, enabled: bool) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    if !set_outline_mode(world, client_id, enabled) {
+        return false;
+    }
+    trigger_inspector_streaming(world);
+    true
+}
+
+/// Subscribe an outline-mode client to all of an entity's components (e.g. when its
+/// tree row is expanded), or unsubscribe. Returns false if the client is not connected
+/// or the entity does not exist.
+#[wasm_bindgen]
+pub fn inspector_watch_entity(ptr: u64, client_id: u32, entity_id: u64, enabled: bool) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(entity) = live_entity_or_warn(app.world(), entity_id, "inspector_watch_entity") else {
+        return false;
+    };
+    let world = app.world_mut();
+    if !watch_entity(world, client_id, entity, enabled) {
+        return false;
+    }
+    trigger_inspector_streaming(world);
+    true
+}
+
+/// Stream `InspectorHidden` internals (cameras, vello scenes, the FPS overlay) to
+/// `client_id` too, for debugging the app itself; turning it off removes them from the
+/// client again. Returns false if the client is not connected.
+#[wasm_bindgen]
+pub fn set_inspector_reveal_hidden(ptr: u64, client_id: u32, enabled: bool) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    if !set_reveal_hidden(world, client_id, enabled) {
+        return false;
+    }
+    trigger_inspector_streaming(world);
+    true
+}
+
+/// Gzip-compress a client's snapshot batch (its first update, or the first after
+/// `inspector_reset_streaming_state`); the host decompresses it. Returns false if the
+/// client is not connected.
+#[wasm_bindgen]
+pub fn set_inspector_snapshot_compression(ptr: u64, client_id: u32, enabled: bool) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    app.world_mut()
+        .get_resource_mut::<RemoteInspectorCore>()
+        .is_some_and(|mut core| core.set_compress_snapshot(client_id, enabled))
+}
+
+/// Close a client opened with `inspector_connect`, dropping its tracked state.
+/// Returns false if it was not connected or is the host client.
+#[wasm_bindgen]
+pub fn inspector_disconnect(ptr: u64, client_id: u32) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    client_id != HOST_INSPECTOR_CLIENT && disconnect_client(app.world_mut(), client_id)
+}
+
+/// Queue a component update on an entity. Successive updates to the same
+/// (entity, component) before the next frame collapse into the last value; the queue
+/// is applied, recorded as one undo step and streamed once per frame by
+/// `flush_component_updates_system`. Inside a transaction the update applies at once
+/// instead, as part of the transaction's undo step. Returns true once the update is
+/// queued (or applied, in a transaction); false if the JSON is invalid, the entity does
+/// not exist or the component can't be read.
 #[wasm_bindgen]
 pub fn inspector_update_component(
     ptr: u64,
@@ -28,19 +260,531 @@ pub fn inspector_update_component(
 ) -> bool {
     let app = unsafe { &mut *(ptr as *mut WorkerApp) };
 
-    let entity = Entity::from_bits(entity_id);
+    let Some(entity) = live_entity_or_warn(app.world(), entity_id, "inspector_update_component")
+    else {
+        return false;
+    };
+    let value: Value = match serde_json::from_str(value_json) {
+        Ok(v) => v,
+        Err(_) => return false,
+    };
+    let world = app.world_mut();
+    let command = UpdateComponent {
+        entity,
+        component: component_id,
+        value,
+    };
+    if in_transaction(world) {
+        return update_component_in_transaction(world, command);
+    }
+    let Some(queued) = world
+        .get_resource::<PendingComponentUpdates>()
+        .map(|pending| pending.contains(entity, component_id))
+    else {
+        return false;
+    };
+    // The undo `before` value is the one from before the first queued update.
+    let before = if queued {
+        None
+    } else {
+        match read_component(world, entity, component_id) {
+            Some(before) => Some(before),
+            None => {
+                warn!(
+                    "inspector_update_component: can't read component {component_id} on {entity}"
+                );
+                return false;
+            }
+        }
+    };
+    world
+        .resource_mut::<PendingComponentUpdates>()
+        .push(command, before);
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
+
+/// Apply `command` now and record it in the open transaction's undo step.
+fn update_component_in_transaction(world: &mut World, command: UpdateComponent) -> bool {
+    if transaction_failed(world) {
+        warn!("inspector_update_component: skipped, the open transaction was rolled back");
+        return false;
+    }
+    let (entity, component) = (command.entity, command.component);
+    let read = || ReadComponent { entity, component };
+    let before = InspectorContext::run(world, |ctx, world| read().execute(ctx, world)).ok();
+    let result = op_log::logged(world, |world| {
+        InspectorContext::run(world, |ctx, world| command.execute(ctx, world))
+    });
+    if let Err(e) = result {
+        roll_back_transaction(world, "inspector_update_component", &e);
+        return false;
+    }
+    trace_command(world, "inspector_update_component");
+    let after = InspectorContext::run(world, |ctx, world| read().execute(ctx, world)).ok();
+    if let (Some(before), Some(after)) = (before, after)
+        && before != after
+        && let Some(mut history) = world.get_resource_mut::<History>()
+    {
+        // The transaction's label replaces this one on commit.
+        history.push(HistoryEntry {
+            label: String::new(),
+            ops: vec![HistoryOp::Component {
+                entity,
+                component,
+                before,
+                after,
+            }],
+        });
+    }
+    true
+}
+
+/// Apply one component value to many entities (e.g. the selection) as a single edit:
+/// one undo entry covering every entity that changed, and one stream flush.
+/// `entity_ids_json` is a JSON array of entity ids. With `merge_mode` "replace",
+/// `value_json` is the whole component value; with "merge" it is a partial object
+/// merged into each entity's current value (nested objects merge, anything else
+/// replaces), so e.g. `{ "scale": [2, 2, 2] }` rescales Transforms without moving them.
+/// Entities that are gone or lack the component are skipped with a warning. Returns
+/// how many entities were updated; 0 for invalid JSON or an unknown mode.
+#[wasm_bindgen]
+pub fn inspector_update_component_multi(
+    ptr: u64,
+    entity_ids_json: &str,
+    component_id: usize,
+    value_json: &str,
+    merge_mode: &str,
+) -> u32 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+
+    let merge = match merge_mode {
+        "replace" => false,
+        "merge" => true,
+        _ => return 0,
+    };
+    let (Ok(ids), Ok(value)) = (
+        serde_json::from_str::<Vec<u64>>(entity_ids_json),
+        serde_json::from_str::<Value>(value_json),
+    ) else {
+        return 0;
+    };
+    let world = app.world_mut();
+    // A live preview may target one of these entities; settle it first.
+    commit_preview(world);
+
+    let (ops, updated) = op_log::logged(world, |world| {
+        let mut ops = Vec::new();
+        let mut updated = 0;
+        for id in ids {
+            let Some(entity) = live_entity_or_warn(world, id, "inspector_update_component_multi")
+            else {
+                continue;
+            };
+            let read = ReadComponent {
+                entity,
+                component: component_id,
+            };
+            let before = match InspectorContext::run(world, |ctx, world| read.execute(ctx, world)) {
+                Ok(before) => before,
+                Err(e) => {
+                    warn!("Failed to read component {component_id} on {entity}: {e}");
+                    continue;
+                }
+            };
+            let after = if merge {
+                let mut merged = before.clone();
+                merge_json(&mut merged, &value);
+                merged
+            } else {
+                value.clone()
+            };
+            let command = UpdateComponent {
+                entity,
+                component: component_id,
+                value: after,
+            };
+            if let Err(e) = InspectorContext::run(world, |ctx, world| command.execute(ctx, world)) {
+                warn!("Failed to update component {component_id} on {entity}: {e}");
+                continue;
+            }
+            updated += 1;
+            // Record what the component now serializes to, not the raw input.
+            let read = ReadComponent {
+                entity,
+                component: component_id,
+            };
+            if let Ok(after) = InspectorContext::run(world, |ctx, world| read.execute(ctx, world))
+                && after != before
+            {
+                ops.push(HistoryOp::Component {
+                    entity,
+                    component: component_id,
+                    before,
+                    after,
+                });
+            }
+        }
+        (ops, updated)
+    });
+
+    let label = world.get_resource::<Locale>().map_or_else(
+        || Locale::english("history.edit_components").to_string(),
+        |locale| locale.get("history.edit_components").to_string(),
+    );
+    if let Some(mut history) = world.get_resource_mut::<History>() {
+        history.push(HistoryEntry { label, ops });
+    }
+    if updated > 0 {
+        trace_command(world, "inspector_update_component_multi");
+        trigger_inspector_streaming(world);
+    }
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    updated
+}
+
+/// Change a numeric component field relative to its current value, applied directly
+/// rather than through the update queue so it composes with whatever the animation
+/// systems wrote this frame. `path` is a reflect path into the component (e.g.
+/// "translation.y"), `op` is "add", "mul" or "set". Records one undo entry. Returns the
+/// field's new value, or undefined if the entity, path or op is invalid or the field is
+/// not numeric.
+#[wasm_bindgen]
+pub fn inspector_adjust_component_field(
+    ptr: u64,
+    entity_id: u64,
+    component_id: usize,
+    path: &str,
+    op: &str,
+    amount: f64,
+) -> Option<f64> {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+
+    let op = match op {
+        "add" => AdjustOp::Add,
+        "mul" => AdjustOp::Mul,
+        "set" => AdjustOp::Set,
+        _ => return None,
+    };
+    let entity = live_entity_or_warn(app.world(), entity_id, "inspector_adjust_component_field")?;
+    let world = app.world_mut();
+    if transaction_failed(world) {
+        warn!("inspector_adjust_component_field: skipped, the open transaction was rolled back");
+        return None;
+    }
+    let read = || ReadComponent {
+        entity,
+        component: component_id,
+    };
+    let before = InspectorContext::run(world, |ctx, world| read().execute(ctx, world)).ok();
+    let command = AdjustComponentField {
+        entity,
+        component: component_id,
+        path: path.to_string(),
+        op,
+        amount,
+    };
+    let result = op_log::logged(world, |world| {
+        InspectorContext::run(world, |ctx, world| command.execute(ctx, world))
+    });
+    let value = match result {
+        Ok(value) => value,
+        Err(e) => {
+            warn!("Failed to adjust component {component_id} on {entity}: {e}");
+            roll_back_transaction(world, "inspector_adjust_component_field", &e);
+            return None;
+        }
+    };
+
+    let after = InspectorContext::run(world, |ctx, world| read().execute(ctx, world)).ok();
+    if let (Some(before), Some(after)) = (before, after)
+        && before != after
+    {
+        let label = world.get_resource::<Locale>().map_or_else(
+            || Locale::english("history.edit_component").to_string(),
+            |locale| locale.get("history.edit_component").to_string(),
+        );
+        if let Some(mut history) = world.get_resource_mut::<History>() {
+            history.push(HistoryEntry {
+                label,
+                ops: vec![HistoryOp::Component {
+                    entity,
+                    component: component_id,
+                    before,
+                    after,
+                }],
+            });
+        }
+    }
+    trace_command(world, "inspector_adjust_component_field");
+    trigger_inspector_streaming(world);
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    Some(value)
+}
+
+/// Merge `patch` into `target`: objects merge key by key, anything else replaces.
+fn merge_json(target: &mut Value, patch: &Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                match target.get_mut(key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        target.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (target, patch) => *target = patch.clone(),
+    }
+}
+
+/// Component updates waiting for the next frame, in first-arrival order, each with the
+/// component's value from before the first of them.
+#[derive(Resource, Default)]
+pub struct PendingComponentUpdates {
+    updates: Vec<(UpdateComponent, Value)>,
+}
+
+impl PendingComponentUpdates {
+    fn contains(&self, entity: Entity, component: usize) -> bool {
+        self.updates
+            .iter()
+            .any(|(u, _)| u.entity == entity && u.component == component)
+    }
+
+    /// Replace a queued update for the same (entity, component), keeping its position
+    /// and `before` value; otherwise queue it with `before`.
+    fn push(&mut self, update: UpdateComponent, before: Option<Value>) {
+        match self
+            .updates
+            .iter_mut()
+            .find(|(u, _)| u.entity == update.entity && u.component == update.component)
+        {
+            Some((queued, _)) => queued.value = update.value,
+            None => {
+                if let Some(before) = before {
+                    self.updates.push((update, before));
+                }
+            }
+        }
+    }
+}
+
+/// Apply the coalesced component updates, record them as one undo step, then stream
+/// once.
+pub fn flush_component_updates_system(world: &mut World) {
+    let updates = match world.get_resource_mut::<PendingComponentUpdates>() {
+        Some(mut pending) if !pending.updates.is_empty() => std::mem::take(&mut pending.updates),
+        _ => return,
+    };
+    let (applied, ops) = op_log::logged(world, |world| {
+        InspectorContext::run(world, |ctx, world| {
+            let mut applied = 0;
+            let mut ops = Vec::new();
+            for (command, before) in updates {
+                let (entity, component) = (command.entity, command.component);
+                if let Err(e) = command.execute(ctx, world) {
+                    warn!("Failed to update component {component} on {entity}: {e}");
+                    continue;
+                }
+                applied += 1;
+                let read = ReadComponent { entity, component };
+                if let Ok(after) = read.execute(ctx, world)
+                    && after != before
+                {
+                    ops.push(HistoryOp::Component {
+                        entity,
+                        component,
+                        before,
+                        after,
+                    });
+                }
+            }
+            (applied, ops)
+        })
+    });
+    for _ in 0..applied {
+        trace_command(world, "inspector_update_component");
+    }
+    let key = if ops.len() > 1 {
+        "history.edit_components"
+    } else {
+        "history.edit_component"
+    };
+    let label = world.get_resource::<Locale>().map_or_else(
+        || Locale::english(key).to_string(),
+        |locale| locale.get(key).to_string(),
+    );
+    if let Some(mut history) = world.get_resource_mut::<History>() {
+        history.push(HistoryEntry { label, ops });
+    }
+    if applied > 0 {
+        trigger_inspector_streaming(world);
+    }
+}
+
+/// Component value being scrubbed; `original` is restored on cancel and becomes the
+/// undo `before` value on commit.
+struct PreviewTarget {
+    entity: Entity,
+    component: usize,
+    original: Value,
+}
+
+/// At most one preview is live at a time.
+#[derive(Resource, Default)]
+pub struct InspectorPreview {
+    active: Option<PreviewTarget>,
+}
+
+/// Apply a tentative component value (e.g. while dragging a slider). Previews are not
+/// streamed back to the inspector and do not touch undo history until
+/// `inspector_commit_preview`; previewing a different field commits the previous one.
+/// The component is still marked changed for the app's own systems (transform
+/// propagation has to see a scrubbed `Transform`), but inspector streams skip it until
+/// the preview is committed or cancelled.
+#[wasm_bindgen]
+pub fn inspector_preview_component(
+    ptr: u64,
+    entity_id: u64,
+    component_id: usize,
+    value_json: &str,
+) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+
+    let Some(entity) = live_entity_or_warn(app.world(), entity_id, "inspector_preview_component")
+    else {
+        return false;
+    };
     let value: Value = match serde_json::from_str(value_json) {
         Ok(v) => v,
         Err(_) => return false,
     };
+    let world = app.world_mut();
+    let same_target = world
+        .get_resource::<InspectorPreview>()
+        .and_then(|preview| preview.active.as_ref())
+        .map(|target| target.entity == entity && target.component == component_id);
+    if same_target == Some(false) {
+        commit_preview(world);
+    }
+    if same_target != Some(true) {
+        let read = ReadComponent {
+            entity,
+            component: component_id,
+        };
+        let Ok(original) = InspectorContext::run(world, |ctx, world| read.execute(ctx, world))
+        else {
+            return false;
+        };
+        let Some(mut preview) = world.get_resource_mut::<InspectorPreview>() else {
+            return false;
+        };
+        preview.active = Some(PreviewTarget {
+            entity,
+            component: component_id,
+            original,
+        });
+        suppress_component_changes(world, entity, ComponentId::new(component_id), true);
+    }
 
     let command = UpdateComponent {
         entity,
         component: component_id,
         value,
     };
+    let applied = InspectorContext::run(world, |ctx, world| command.execute(ctx, world)).is_ok();
+    trace_command(world, "inspector_preview_component");
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    applied
+}
 
-    execute_inspector_command(app, |ctx, world| command.execute(ctx, world))
+/// Keep the previewed value: record one undo entry (original -> current) and stream it.
+/// Returns false when no preview is active.
+#[wasm_bindgen]
+pub fn inspector_commit_preview(ptr: u64) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let committed = commit_preview(app.world_mut());
+    if committed {
+        trace_command(app.world_mut(), "inspector_commit_preview");
+        trigger_inspector_streaming(app.world_mut());
+    }
+    committed
+}
+
+/// Restore the value the preview started from. Returns false when no preview is active.
+#[wasm_bindgen]
+pub fn inspector_cancel_preview(ptr: u64) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    let Some(target) = world
+        .get_resource_mut::<InspectorPreview>()
+        .and_then(|mut preview| preview.active.take())
+    else {
+        return false;
+    };
+    let component = ComponentId::new(target.component);
+    suppress_component_changes(world, target.entity, component, false);
+    let command = UpdateComponent {
+        entity: target.entity,
+        component: target.component,
+        value: target.original,
+    };
+    let restored = InspectorContext::run(world, |ctx, world| command.execute(ctx, world)).is_ok();
+    trace_command(world, "inspector_cancel_preview");
+    trigger_inspector_streaming(world);
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    restored
+}
+
+fn commit_preview(world: &mut World) -> bool {
+    let Some(target) = world
+        .get_resource_mut::<InspectorPreview>()
+        .and_then(|mut preview| preview.active.take())
+    else {
+        return false;
+    };
+    // Streams pick up the committed value with their next flush.
+    let component = ComponentId::new(target.component);
+    suppress_component_changes(world, target.entity, component, false);
+    let read = ReadComponent {
+        entity: target.entity,
+        component: target.component,
+    };
+    let label = world.get_resource::<Locale>().map_or_else(
+        || Locale::english("history.edit_component").to_string(),
+        |locale| locale.get("history.edit_component").to_string(),
+    );
+    // The entity or component may have gone away mid-scrub; nothing to record then.
+    if let Ok(current) = InspectorContext::run(world, |ctx, world| read.execute(ctx, world))
+        && current != target.original
+    {
+        // Previews apply unlogged; the op log gets the committed value.
+        op_log::log_component(world, target.entity, target.component);
+        if let Some(mut history) = world.get_resource_mut::<History>() {
+            history.push(HistoryEntry {
+                label,
+                ops: vec![HistoryOp::Component {
+                    entity: target.entity,
+                    component: target.component,
+                    before: target.original,
+                    after: current,
+                }],
+            });
+        }
+    }
+    true
 }
 
 /// Toggle a component on an entity (add if missing, remove if present)
@@ -48,13 +792,27 @@ pub fn inspector_update_component(
 pub fn inspector_toggle_component(ptr: u64, entity_id: u64, component_id: usize) -> bool {
     let app = unsafe { &mut *(ptr as *mut WorkerApp) };
 
-    let entity = Entity::from_bits(entity_id);
+    let Some(entity) = live_entity_or_warn(app.world(), entity_id, "inspector_toggle_component")
+    else {
+        return false;
+    };
     let command = ToggleComponent {
         entity,
         component: component_id,
     };
+    // Toggling again restores the component, stashed value and all.
+    let op = HistoryOp::ToggleComponent {
+        entity,
+        component: component_id,
+    };
 
-    execute_inspector_command(app, |ctx, world| command.execute(ctx, world))
+    execute_inspector_command(
+        app,
+        "inspector_toggle_component",
+        "history.toggle_component",
+        |_| vec![op],
+        |ctx, world| command.execute(ctx, world),
+    )
 }
 
 /// Remove a component from an entity
@@ -62,13 +820,23 @@ pub fn inspector_toggle_component(ptr: u64, entity_id: u64, component_id: usize)
 pub fn inspector_remove_component(ptr: u64, entity_id: u64, component_id: usize) -> bool {
     let app = unsafe { &mut *(ptr as *mut WorkerApp) };
 
-    let entity = Entity::from_bits(entity_id);
+    let Some(entity) = live_entity_or_warn(app.world(), entity_id, "inspector_remove_component")
+    else {
+        return false;
+    };
     let command = RemoveComponent {
         entity,
         component: component_id,
     };
+    let before = read_component(app.world_mut(), entity, component_id);
 
-    execute_inspector_command(app, |ctx, world| command.execute(ctx, world))
+    execute_inspector_command(
+        app,
+        "inspector_remove_component",
+        "history.remove_component",
+        |world| presence_change(world, entity, component_id, before),
+        |ctx, world| command.execute(ctx, world),
+    )
 }
 
 /// Insert a component on an entity
@@ -81,7 +849,10 @@ pub fn inspector_insert_component(
 ) -> bool {
     let app = unsafe { &mut *(ptr as *mut WorkerApp) };
 
-    let entity = Entity::from_bits(entity_id);
+    let Some(entity) = live_entity_or_warn(app.world(), entity_id, "inspector_insert_component")
+    else {
+        return false;
+    };
     let value: Value = match serde_json::from_str(value_json) {
         Ok(v) => v,
         Err(_) => return false,
@@ -92,8 +863,15 @@ pub fn inspector_insert_component(
         component: component_id,
         value,
     };
+    let before = read_component(app.world_mut(), entity, component_id);
 
-    execute_inspector_command(app, |ctx, world| command.execute(ctx, world))
+    execute_inspector_command(
+        app,
+        "inspector_insert_component",
+        "history.insert_component",
+        |world| presence_change(world, entity, component_id, before),
+        |ctx, world| command.execute(ctx, world),
+    )
 }
 
 /// Despawn an entity
@@ -101,10 +879,13 @@ pub fn inspector_insert_component(
 pub fn inspector_despawn_entity(ptr: u64, entity_id: u64, kind: &str) -> bool {
     let app = unsafe { &mut *(ptr as *mut WorkerApp) };
 
-    let entity = Entity::from_bits(entity_id);
+    let Some(entity) = live_entity_or_warn(app.world(), entity_id, "inspector_despawn_entity")
+    else {
+        return false;
+    };
     let despawn_kind = match kind {
-        "recursive" => bevy_remote_inspector::command::DespawnEntityKind::Recursive,
-        "descendant" => bevy_remote_inspector::command::DespawnEntityKind::Descendant,
+        "recursive" => DespawnEntityKind::Recursive,
+        "descendant" => DespawnEntityKind::Descendant,
         _ => return false,
     };
 
@@ -112,8 +893,27 @@ pub fn inspector_despawn_entity(ptr: u64, entity_id: u64, kind: &str) -> bool {
         entity,
         kind: despawn_kind,
     };
+    // Both kinds despawn the whole tree (see `DespawnEntity`), so save all of it.
+    let snapshot = EntitySnapshot::capture(app.world_mut(), entity);
 
-    execute_inspector_command(app, |ctx, world| command.execute(ctx, world))
+    execute_inspector_command(
+        app,
+        "inspector_despawn_entity",
+        "history.despawn",
+        |world| {
+            let (Some(snapshot), Some(mut history)) =
+                (snapshot, world.get_resource_mut::<History>())
+            else {
+                return Vec::new();
+            };
+            history.save_snapshot(snapshot);
+            vec![HistoryOp::Lifetime {
+                entity,
+                spawned: false,
+            }]
+        },
+        |ctx, world| command.execute(ctx, world),
+    )
 }
 
 /// Toggle visibility of an entity
@@ -123,10 +923,20 @@ pub fn inspector_toggle_visibility(ptr: u64, entity_id: u64) -> bool {
 
     info!("type of entity_id: {}", entity_id);
 
-    let entity = Entity::from_bits(entity_id);
+    let Some(entity) = live_entity_or_warn(app.world(), entity_id, "inspector_toggle_visibility")
+    else {
+        return false;
+    };
     let command = ToggleVisibity { entity };
 
-    execute_inspector_command(app, |ctx, world| command.execute(ctx, world))
+    // A second toggle restores the saved visibility.
+    execute_inspector_command(
+        app,
+        "inspector_toggle_visibility",
+        "history.toggle_visibility",
+        |_| vec![HistoryOp::ToggleVisibility { entity }],
+        |ctx, world| command.execute(ctx, world),
+    )
 }
 
 /// Reparent an entity
@@ -134,12 +944,35 @@ pub fn inspector_toggle_visibility(ptr: u64, entity_id: u64) -> bool {
 pub fn inspector_reparent_entity(ptr: u64, entity_id: u64, parent_id: Option<u64>) -> bool {
     let app = unsafe { &mut *(ptr as *mut WorkerApp) };
 
-    let entity = Entity::from_bits(entity_id);
-    let parent = parent_id.map(Entity::from_bits);
+    let Some(entity) = live_entity_or_warn(app.world(), entity_id, "inspector_reparent_entity")
+    else {
+        return false;
+    };
+    let parent = match parent_id {
+        Some(bits) => match live_entity_or_warn(app.world(), bits, "inspector_reparent_entity") {
+            Some(parent) => Some(parent),
+            None => return false,
+        },
+        None => None,
+    };
 
     let command = ReparentEntity { entity, parent };
+    let child_of = app
+        .world()
+        .component_id::<ChildOf>()
+        .map(ComponentId::index);
+    let before = child_of.and_then(|id| read_component(app.world_mut(), entity, id));
 
-    execute_inspector_command(app, |ctx, world| command.execute(ctx, world))
+    execute_inspector_command(
+        app,
+        "inspector_reparent_entity",
+        "history.reparent",
+        |world| match child_of {
+            Some(id) => presence_change(world, entity, id, before),
+            None => Vec::new(),
+        },
+        |ctx, world| command.execute(ctx, world),
+    )
 }
 
 /// Spawn a new entity
@@ -148,15 +981,200 @@ pub fn inspector_spawn_entity(ptr: u64, parent_id: Option<u64>) -> u64 {
     info!("Spawning entity with parent: {:?}", parent_id);
     let app = unsafe { &mut *(ptr as *mut WorkerApp) };
 
-    let parent = parent_id.map(Entity::from_bits);
+    let parent = match parent_id {
+        Some(bits) => match live_entity_or_warn(app.world(), bits, "inspector_spawn_entity") {
+            Some(parent) => Some(parent),
+            None => return 0,
+        },
+        None => None,
+    };
     let command = SpawnEntity { parent };
 
-    match execute_inspector_command_with_result(app, |ctx, world| command.execute(ctx, world)) {
+    let spawned = execute_inspector_command_with_result(
+        app,
+        "inspector_spawn_entity",
+        "history.spawn",
+        record_spawn,
+        |ctx, world| command.execute(ctx, world),
+    );
+    match spawned {
         Some(entity_bits) => entity_bits,
         None => 0, // Return 0 for error/invalid entity
     }
 }
 
+/// Spawn a 2D overlay shape from a template (`kind`: "rect", "ellipse", "line",
+/// "arrow" or "text"; `params_json`: `ShapeParams`, any subset). Returns the new
+/// entity's bits, or 0 on an unknown kind, invalid params or a missing parent.
+#[wasm_bindgen]
+pub fn inspector_spawn_shape(
+    ptr: u64,
+    kind: &str,
+    params_json: &str,
+    parent_id: Option<u64>,
+) -> u64 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+
+    let Some(kind) = ShapeKind::parse(kind) else {
+        warn!("inspector_spawn_shape: unknown shape kind {kind:?}");
+        return 0;
+    };
+    let params = match serde_json::from_str::<ShapeParams>(params_json) {
+        Ok(params) => params,
+        Err(e) => {
+            warn!("inspector_spawn_shape: invalid params: {e}");
+            return 0;
+        }
+    };
+    let parent = match parent_id {
+        Some(bits) => match live_entity_or_warn(app.world(), bits, "inspector_spawn_shape") {
+            Some(parent) => Some(parent),
+            None => return 0,
+        },
+        None => None,
+    };
+    let command = SpawnShape {
+        kind,
+        params,
+        parent,
+    };
+
+    let entity_bits = execute_inspector_command_with_result(
+        app,
+        "inspector_spawn_shape",
+        "history.spawn",
+        record_spawn,
+        |ctx, world| command.execute(ctx, world),
+    )
+    .unwrap_or(0);
+    if entity_bits != 0 {
+        if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+            active_info.remaining_frames = 10;
+        }
+    }
+    entity_bits
+}
+
+/// Click-to-place: spawn `template_name` under the pointer and select it. 3D object
+/// templates ("cube", "sphere", "torus") rest on the first surface under the pointer,
+/// or on the ground plane; overlay shape templates (see `inspector_spawn_shape`) are
+/// placed at the pointer's overlay position. Returns the new entity's bits, or 0 for
+/// an unknown template or a pointer outside the viewer.
+#[wasm_bindgen]
+pub fn spawn_at_cursor(ptr: u64, template_name: &str) -> u64 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+
+    let spawned = if let Some(kind) = ObjectKind::parse(template_name) {
+        let Some(point) = placement::cursor_surface_point(app.world_mut()) else {
+            warn!("spawn_at_cursor: pointer is not over the viewer");
+            return 0;
+        };
+        let command = SpawnObject {
+            kind,
+            position: point + Vec3::Y * kind.bounds().y / 2.0,
+        };
+        execute_inspector_command_with_result(
+            app,
+            "spawn_at_cursor",
+            "history.spawn",
+            record_spawn,
+            |ctx, world| command.execute(ctx, world),
+        )
+    } else if let Some(kind) = ShapeKind::parse(template_name) {
+        let Some(position) = app
+            .world()
+            .get_resource::<PointerState>()
+            .and_then(|pointer| pointer.overlay_world)
+        else {
+            warn!("spawn_at_cursor: pointer is not over the viewer");
+            return 0;
+        };
+        let command = SpawnShape {
+            kind,
+            params: ShapeParams {
+                position,
+                ..default()
+            },
+            parent: None,
+        };
+        execute_inspector_command_with_result(
+            app,
+            "spawn_at_cursor",
+            "history.spawn",
+            record_spawn,
+            |ctx, world| command.execute(ctx, world),
+        )
+    } else {
+        warn!("spawn_at_cursor: unknown template {template_name:?}");
+        return 0;
+    };
+    let Some(entity_bits) = spawned else {
+        return 0;
+    };
+    crate::web_ffi::apply_selection(app.world_mut(), &[Entity::from_bits(entity_bits)], "replace");
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    entity_bits
+}
+
+/// Jitter entities' transforms with seeded randomness as one undo step.
+/// `entity_ids_json` is a JSON array of entity ids, `[]` for the current selection;
+/// `params_json` is `{ seed, position_jitter: [x, y, z], rotation_jitter: [x, y, z]
+/// (degrees), scale_jitter }`, any subset. Returns how many entities moved, 0 for
+/// invalid JSON or when none of them has a Transform.
+#[wasm_bindgen]
+pub fn inspector_scatter_entities(ptr: u64, entity_ids_json: &str, params_json: &str) -> u32 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+
+    let Ok(ids) = serde_json::from_str::<Vec<u64>>(entity_ids_json) else {
+        warn!("inspector_scatter_entities: invalid entity ids");
+        return 0;
+    };
+    let mut command = match serde_json::from_str::<ScatterEntities>(params_json) {
+        Ok(command) => command,
+        Err(e) => {
+            warn!("inspector_scatter_entities: invalid params: {e}");
+            return 0;
+        }
+    };
+    let world = app.world_mut();
+    if transaction_failed(world) {
+        warn!("inspector_scatter_entities: skipped, the open transaction was rolled back");
+        return 0;
+    }
+    command.entities = if ids.is_empty() {
+        world
+            .get_resource::<SelectionState>()
+            .map(|selection| selection.selected.keys().copied().collect())
+            .unwrap_or_default()
+    } else {
+        ids.into_iter()
+            .filter_map(|id| live_entity_or_warn(world, id, "inspector_scatter_entities"))
+            .collect()
+    };
+
+    // Undone through the history entry it records, like `inspector_adjust_component_field`.
+    let result = op_log::logged(world, |world| {
+        InspectorContext::run(world, |ctx, world| command.execute(ctx, world))
+    });
+    let moved = match result {
+        Ok(moved) => moved,
+        Err(e) => {
+            warn!("inspector_scatter_entities: {e}");
+            roll_back_transaction(world, "inspector_scatter_entities", &e);
+            return 0;
+        }
+    };
+    trace_command(world, "inspector_scatter_entities");
+    trigger_inspector_streaming(world);
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    moved
+}
+
 /// Resource to track streaming state
 #[derive(Resource)]
 pub struct InspectorStreamingState {
@@ -175,18 +1193,24 @@ impl Default for InspectorStreamingState {
     }
 }
 
-/// Trigger inspector streaming immediately (called after commands)
-fn trigger_inspector_streaming(world: &mut World) {
-    let events = get_inspector_events(world, 0);
-    if !events.is_empty() {
-        match serde_json::to_string(&events) {
-            Ok(json) => {
-                send_inspector_update_from_worker(&json);
-            }
-            Err(e) => {
-                error!("Failed to serialize inspector events: {}", e);
-            }
-        }
+/// Trigger inspector streaming immediately (called after commands). Held back while a
+/// transaction is open; the commit streams once.
+pub(crate) fn trigger_inspector_streaming(world: &mut World) {
+    if in_transaction(world) {
+        return;
+    }
+    stream_inspector_events(world);
+}
+
+/// Stream the selection without delay under `StreamingPolicy::viewport_priority`.
+pub fn sync_streaming_focus_system(
+    selection: Res<SelectionState>,
+    focus: Option<ResMut<StreamingFocus>>,
+) {
+    if let Some(mut focus) = focus
+        && selection.is_changed()
+    {
+        focus.0 = selection.selected.keys().copied().collect();
     }
 }
 
@@ -230,35 +1254,178 @@ pub fn inspector_continuous_streaming_system(world: &mut World) {
     trigger_inspector_streaming(world);
 }
 
-/// Helper function to execute inspector commands
-fn execute_inspector_command<F, T>(app: &mut WorkerApp, f: F) -> bool
+/// Helper function to execute inspector commands. On success `record` returns the
+/// history ops undoing the command, which become an undo step named by the locale key
+/// `label` (or join the open transaction's).
+fn execute_inspector_command<F, T>(
+    app: &mut WorkerApp,
+    name: &'static str,
+    label: &str,
+    record: impl FnOnce(&mut World) -> Vec<HistoryOp>,
+    f: F,
+) -> bool
 where
     F: FnOnce(&mut InspectorContext, &mut World) -> anyhow::Result<T>,
 {
-    let result = InspectorContext::run(app.world_mut(), f);
-    let success = result.is_ok();
-
-    // Trigger immediate streaming update after successful command execution
-    if success {
-        trigger_inspector_streaming(app.world_mut());
-    }
-
-    success
+    execute_inspector_command_with_result(app, name, label, |world, _| record(world), f).is_some()
 }
 
-/// Helper function to execute inspector commands that return a value
-fn execute_inspector_command_with_result<F, T>(app: &mut WorkerApp, f: F) -> Option<T>
+/// Helper function to execute inspector commands that return a value; `record` gets
+/// it too (see `execute_inspector_command`).
+fn execute_inspector_command_with_result<F, T>(
+    app: &mut WorkerApp,
+    name: &'static str,
+    label: &str,
+    record: impl FnOnce(&mut World, &T) -> Vec<HistoryOp>,
+    f: F,
+) -> Option<T>
 where
     F: FnOnce(&mut InspectorContext, &mut World) -> anyhow::Result<T>,
 {
-    let result = InspectorContext::run(app.world_mut(), f);
+    let world = app.world_mut();
+    if transaction_failed(world) {
+        warn!("{name}: skipped, the open transaction was rolled back");
+        return None;
+    }
+    let result = op_log::logged(world, |world| InspectorContext::run(world, f));
+    trace_command(world, name);
 
-    // Trigger immediate streaming update after successful command execution
-    if result.is_ok() {
-        trigger_inspector_streaming(app.world_mut());
+    match result {
+        Ok(value) => {
+            let ops = record(world, &value);
+            let label = world.get_resource::<Locale>().map_or_else(
+                || Locale::english(label).to_string(),
+                |locale| locale.get(label).to_string(),
+            );
+            if let Some(mut history) = world.get_resource_mut::<History>() {
+                history.push(HistoryEntry { label, ops });
+            }
+            // Trigger immediate streaming update after successful command execution
+            trigger_inspector_streaming(world);
+            Some(value)
+        }
+        Err(e) => {
+            roll_back_transaction(world, name, &e);
+            None
+        }
+    }
+}
+
+fn read_component(world: &mut World, entity: Entity, component: usize) -> Option<Value> {
+    let read = ReadComponent { entity, component };
+    InspectorContext::run(world, |ctx, world| read.execute(ctx, world)).ok()
+}
+
+/// History op for a command that put or took `component` on `entity`, given its value
+/// before (None while absent). Nothing when the value didn't change.
+fn presence_change(
+    world: &mut World,
+    entity: Entity,
+    component: usize,
+    before: Option<Value>,
+) -> Vec<HistoryOp> {
+    let after = read_component(world, entity, component);
+    if before == after {
+        return Vec::new();
+    }
+    vec![HistoryOp::Presence {
+        entity,
+        component,
+        before,
+        after,
+    }]
+}
+
+fn record_spawn(_: &mut World, entity_bits: &u64) -> Vec<HistoryOp> {
+    vec![HistoryOp::Lifetime {
+        entity: Entity::from_bits(*entity_bits),
+        spawned: true,
+    }]
+}
+
+/// Commands bracketed by `inspector_begin_transaction` / `inspector_commit_transaction`.
+#[derive(Resource, Default)]
+pub struct InspectorTransaction {
+    open: bool,
+    /// A command failed and the transaction was rolled back; later commands are
+    /// refused until the commit.
+    failed: bool,
+}
+
+fn in_transaction(world: &World) -> bool {
+    world
+        .get_resource::<InspectorTransaction>()
+        .is_some_and(|transaction| transaction.open)
+}
+
+fn transaction_failed(world: &World) -> bool {
+    world
+        .get_resource::<InspectorTransaction>()
+        .is_some_and(|transaction| transaction.open && transaction.failed)
+}
+
+/// After `name` failed inside a transaction, undo everything the transaction recorded
+/// so far, newest first.
+fn roll_back_transaction(world: &mut World, name: &str, error: &anyhow::Error) {
+    match world.get_resource_mut::<InspectorTransaction>() {
+        Some(mut transaction) if transaction.open && !transaction.failed => {
+            transaction.failed = true;
+        }
+        _ => return,
+    }
+    warn!("{name} failed ({error}); rolling back the transaction");
+    // Peers already have the transaction's ops; they get the rollback as further ops.
+    op_log::logged(world, |world| history::revert_pending(world, 0));
+}
+
+/// Start a transaction named `label`: the inspector commands and edits until
+/// `inspector_commit_transaction` form one undo step and stream in one flush. If a
+/// command fails, everything before it is rolled back and the remaining commands are
+/// refused. Returns false if a transaction is already open.
+#[wasm_bindgen]
+pub fn inspector_begin_transaction(ptr: u64, label: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    if in_transaction(world) {
+        warn!("inspector_begin_transaction: {label:?} while a transaction is open");
+        return false;
+    }
+    // A live preview belongs to whatever came before.
+    commit_preview(world);
+    flush_component_updates_system(world);
+    if !world.contains_resource::<History>() || !world.contains_resource::<InspectorTransaction>() {
+        return false;
     }
+    world.resource_mut::<History>().begin(label.to_string());
+    *world.resource_mut::<InspectorTransaction>() = InspectorTransaction {
+        open: true,
+        ..default()
+    };
+    true
+}
 
-    result.ok()
+/// Close the open transaction: record its undo step and stream its changes. Returns
+/// false when none was open or it was rolled back.
+#[wasm_bindgen]
+pub fn inspector_commit_transaction(ptr: u64) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    let Some(transaction) = world
+        .get_resource_mut::<InspectorTransaction>()
+        .filter(|transaction| transaction.open)
+        .map(|mut transaction| std::mem::take(&mut *transaction))
+    else {
+        return false;
+    };
+    if let Some(mut history) = world.get_resource_mut::<History>() {
+        history.commit();
+    }
+    trace_command(world, "inspector_commit_transaction");
+    trigger_inspector_streaming(world);
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    !transaction.failed
 }
 
 /// Enable continuous inspector streaming (for animations/automatic updates)
@@ -326,6 +1493,19 @@ pub fn inspector_reset_streaming_state(ptr: u64, client_id: u32) -> bool {
     }
 }
 
+/// Replace the streaming policy with `{ include, exclude, max_value_bytes,
+/// viewport_priority, offscreen_interval }` (all optional; see `StreamingPolicy`). Values serialized from now on follow it; already
+/// streamed values are not resent. Returns false for invalid JSON.
+#[wasm_bindgen]
+pub fn set_inspector_streaming_policy(ptr: u64, policy_json: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Ok(policy) = serde_json::from_str::<StreamingPolicy>(policy_json) else {
+        return false;
+    };
+    app.world_mut().insert_resource(policy);
+    true
+}
+
 /// Export the type registry schema for dynamic UI generation
 #[wasm_bindgen]
 pub fn get_type_registry_schema(ptr: u64) -> String {
@@ -1,16 +1,37 @@
 use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin}; // removed LogDiagnosticsPlugin
 
-use bevy::{
-    color::palettes::basic::{AQUA, LIME, WHITE},
-    prelude::*,
-};
+use bevy::prelude::*;
+use bevy_remote_inspector::InspectorHidden;
+
+use crate::Locale;
+use crate::bevy_app::theme::Theme;
+use crate::bevy_app::input_latency::INPUT_LATENCY;
+use crate::bevy_app::render_timing::{GPU_FRAME_TIME, RENDER_CPU_TIME, VELLO_ENCODE_TIME};
+
+/// Label spans of the FPS text (the value spans sit between them), with their keys.
+/// The GPU line shows render CPU time instead when the device has no GPU timings.
+const FPS_LABELS: [(usize, &str); 6] = [
+    (1, "fps.raw"),
+    (3, "fps.sma"),
+    (5, "fps.ema"),
+    (7, "fps.gpu"),
+    (9, "fps.vello_encode"),
+    (11, "fps.input_latency"),
+];
 
 pub(crate) struct FPSOverlayPlugin;
 
 impl Plugin for FPSOverlayPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_ui)
-            .add_systems(Update, (update_fps_display, position_fps_overlay));
+        app.add_systems(Startup, setup_ui).add_systems(
+            Update,
+            (
+                update_fps_display,
+                position_fps_overlay,
+                localize_fps_labels.run_if(resource_changed::<Locale>),
+                style_fps_overlay.run_if(resource_changed::<Theme>),
+            ),
+        );
     }
 }
 
@@ -18,11 +39,17 @@ impl Plugin for FPSOverlayPlugin {
 #[derive(Component)]
 struct FpsText;
 
-/// Root node of the overlay, repositioned to track the viewer panel.
+/// Value span of the FPS text (the others are labels).
 #[derive(Component)]
+struct FpsValue;
+
+/// Root node of the overlay, repositioned to track the viewer panel. Hidden from the
+/// inspector along with its text nodes.
+#[derive(Component)]
+#[require(InspectorHidden)]
 struct FpsRoot;
 
-fn setup_ui(mut commands: Commands) {
+fn setup_ui(mut commands: Commands, locale: Res<Locale>, theme: Res<Theme>) {
     let font = TextFont {
         font_size: 30.0,
 
@@ -36,31 +63,26 @@ fn setup_ui(mut commands: Commands) {
                 padding: UiRect::all(Val::Px(20.0)),
                 ..default()
             },
-            BackgroundColor(Color::BLACK.with_alpha(0.75)),
+            BackgroundColor(Theme::color(theme.hud.background)),
             GlobalZIndex(i32::MAX),
             FpsRoot,
         ))
         .with_children(|p| {
             p.spawn((Text::default(), FpsText, Name::new("FPS Text")))
                 .with_children(|p| {
-                    p.spawn((
-                        TextSpan::new("\nFPS (raw): "),
-                        font.clone(),
-                        TextColor(WHITE.into()),
-                    ));
-                    p.spawn((TextSpan::new(""), font.clone(), TextColor(AQUA.into())));
-                    p.spawn((
-                        TextSpan::new("\nFPS (SMA): "),
-                        font.clone(),
-                        TextColor(WHITE.into()),
-                    ));
-                    p.spawn((TextSpan::new(""), font.clone(), TextColor(AQUA.into())));
-                    p.spawn((
-                        TextSpan::new("\nFPS (EMA): "),
-                        font.clone(),
-                        TextColor(WHITE.into()),
-                    ));
-                    p.spawn((TextSpan::new(""), font.clone(), TextColor(AQUA.into())));
+                    for (_, key) in FPS_LABELS {
+                        p.spawn((
+                            TextSpan::new(format!("\n{}", locale.get(key))),
+                            font.clone(),
+                            TextColor(Theme::color(theme.hud.label)),
+                        ));
+                        p.spawn((
+                            TextSpan::new(""),
+                            font.clone(),
+                            TextColor(Theme::color(theme.hud.value)),
+                            FpsValue,
+                        ));
+                    }
                 });
         });
 }
@@ -84,8 +106,37 @@ fn position_fps_overlay(
     }
 }
 
+/// Recolor the panel and text spans from the theme's HUD style.
+fn style_fps_overlay(
+    theme: Res<Theme>,
+    mut roots: Query<&mut BackgroundColor, With<FpsRoot>>,
+    mut spans: Query<(&mut TextColor, Has<FpsValue>), With<TextSpan>>,
+    texts: Query<&Children, With<FpsText>>,
+) {
+    for mut background in roots.iter_mut() {
+        background.0 = Theme::color(theme.hud.background);
+    }
+    for children in texts.iter() {
+        let mut iter = spans.iter_many_mut(children);
+        while let Some((mut color, value)) = iter.fetch_next() {
+            color.0 = Theme::color(if value { theme.hud.value } else { theme.hud.label });
+        }
+    }
+}
+
+fn localize_fps_labels(
+    locale: Res<Locale>,
+    query: Single<Entity, With<FpsText>>,
+    mut writer: TextUiWriter,
+) {
+    for (index, key) in FPS_LABELS {
+        *writer.text(*query, index) = format!("\n{}", locale.get(key));
+    }
+}
+
 fn update_fps_display(
     diagnostics: Res<DiagnosticsStore>,
+    locale: Res<Locale>,
     query: Single<Entity, With<FpsText>>,
     mut writer: TextUiWriter,
 ) {
@@ -103,4 +154,27 @@ fn update_fps_display(
             *writer.text(text_entity, 6) = format!("{ema:.2}");
         }
     }
+
+    let gpu = [
+        ("fps.gpu", GPU_FRAME_TIME),
+        ("fps.render_cpu", RENDER_CPU_TIME),
+    ]
+    .into_iter()
+    .find_map(|(key, path)| Some((key, diagnostics.get(&path)?.smoothed()?)));
+    if let Some((key, ms)) = gpu {
+        *writer.text(text_entity, 7) = format!("\n{}", locale.get(key));
+        *writer.text(text_entity, 8) = format!("{ms:.2}");
+    }
+    if let Some(ms) = diagnostics
+        .get(&VELLO_ENCODE_TIME)
+        .and_then(|encode| encode.smoothed())
+    {
+        *writer.text(text_entity, 10) = format!("{ms:.2}");
+    }
+    if let Some(ms) = diagnostics
+        .get(&INPUT_LATENCY)
+        .and_then(|latency| latency.smoothed())
+    {
+        *writer.text(text_entity, 12) = format!("{ms:.2}");
+    }
 }
@@ -0,0 +1,205 @@
+//! Per-frame trace capture for debugging stalls and input / command ordering.
+//!
+//! While capturing, every frame records the inspector commands executed since the
+//! previous frame, the host input drained in `First` (and its input-to-update latency,
+//! see `input_latency`), how many entities changed and how long the main schedules
+//! took. The last N frames are kept;
+//! `export_trace(ptr)` returns them in Chrome trace-event JSON, which loads as-is into
+//! Perfetto or chrome://tracing.
+
+use bevy::platform::time::Instant;
+use bevy::prelude::*;
+use serde_json::{Value, json};
+use std::collections::VecDeque;
+use wasm_bindgen::prelude::*;
+
+use crate::WorkerApp;
+
+/// A command or input seen during a frame; `at` is microseconds since capture began.
+struct TraceMark {
+    at: f64,
+    name: &'static str,
+}
+
+#[derive(Default)]
+struct FrameRecord {
+    frame: u32,
+    start: f64,
+    duration: f64,
+    delta_ms: f64,
+    changed_entities: usize,
+    input_latency_ms: Option<f64>,
+    commands: Vec<TraceMark>,
+    inputs: Vec<TraceMark>,
+}
+
+/// Ring buffer of recent frames. Capture is off (and costs nothing) until
+/// `set_frame_trace_capture` sets a frame count.
+#[derive(Resource, Default)]
+pub struct FrameTrace {
+    capacity: usize,
+    origin: Option<Instant>,
+    frames: VecDeque<FrameRecord>,
+    /// The frame being recorded; commands executed between frames land here too.
+    current: FrameRecord,
+}
+
+impl FrameTrace {
+    pub fn is_capturing(&self) -> bool {
+        self.capacity > 0
+    }
+
+    fn now(&self) -> f64 {
+        self.origin
+            .map_or(0.0, |origin| origin.elapsed().as_secs_f64() * 1_000_000.0)
+    }
+
+    /// Note an executed inspector command (by FFI name) in the current frame.
+    pub fn command(&mut self, name: &'static str) {
+        if self.is_capturing() {
+            let at = self.now();
+            self.current.commands.push(TraceMark { at, name });
+        }
+    }
+
+    /// Note a host input drained this frame.
+    pub fn input(&mut self, name: &'static str) {
+        if self.is_capturing() {
+            let at = self.now();
+            self.current.inputs.push(TraceMark { at, name });
+        }
+    }
+
+    /// Note the input-to-update latency of the current frame.
+    pub fn input_latency(&mut self, ms: f64) {
+        if self.is_capturing() {
+            self.current.input_latency_ms = Some(ms);
+        }
+    }
+
+    fn to_chrome_trace(&self) -> Value {
+        let mut events = vec![json!({
+            "name": "thread_name", "ph": "M", "pid": 1, "tid": 1,
+            "args": { "name": "main schedules" },
+        })];
+        for frame in &self.frames {
+            events.push(json!({
+                "name": format!("frame {}", frame.frame),
+                "cat": "frame",
+                "ph": "X",
+                "ts": frame.start,
+                "dur": frame.duration,
+                "pid": 1,
+                "tid": 1,
+                "args": {
+                    "delta_ms": frame.delta_ms,
+                    "changed_entities": frame.changed_entities,
+                    "commands": frame.commands.len(),
+                    "inputs": frame.inputs.len(),
+                    "input_latency_ms": frame.input_latency_ms,
+                },
+            }));
+            events.push(json!({
+                "name": "changed entities",
+                "ph": "C",
+                "ts": frame.start,
+                "pid": 1,
+                "args": { "count": frame.changed_entities },
+            }));
+            let marks = [("command", &frame.commands), ("input", &frame.inputs)];
+            for (category, marks) in marks {
+                events.extend(marks.iter().map(|mark| {
+                    json!({
+                        "name": mark.name,
+                        "cat": category,
+                        "ph": "i",
+                        "s": "t",
+                        "ts": mark.at,
+                        "pid": 1,
+                        "tid": 1,
+                    })
+                }));
+            }
+        }
+        json!({ "traceEvents": events, "displayTimeUnit": "ms" })
+    }
+}
+
+/// Note an executed inspector command if a trace is being captured.
+pub(crate) fn trace_command(world: &mut World, name: &'static str) {
+    if let Some(mut trace) = world.get_resource_mut::<FrameTrace>() {
+        trace.command(name);
+    }
+}
+
+/// Start of the frame, in `First` before the input queue drains.
+pub(crate) fn begin_frame_trace(mut trace: ResMut<FrameTrace>, time: Res<Time<Real>>) {
+    if !trace.is_capturing() {
+        return;
+    }
+    trace.current.start = trace.now();
+    trace.current.delta_ms = time.delta_secs_f64() * 1000.0;
+}
+
+/// End of the frame, in `Last`: count changed entities and push the record.
+pub(crate) fn end_frame_trace(world: &mut World) {
+    if !world
+        .get_resource::<FrameTrace>()
+        .is_some_and(FrameTrace::is_capturing)
+    {
+        return;
+    }
+    // Same test as inspector change tracking: any component changed since this
+    // system last ran, i.e. during this frame.
+    let (last_run, this_run) = (world.last_change_tick(), world.change_tick());
+    let changed_entities = world
+        .iter_entities()
+        .filter(|entity| {
+            entity.archetype().components().any(|id| {
+                entity
+                    .get_change_ticks_by_id(id)
+                    .is_some_and(|ticks| ticks.is_changed(last_run, this_run))
+            })
+        })
+        .count();
+    let frame = world
+        .get_resource::<bevy::diagnostic::FrameCount>()
+        .map_or(0, |count| count.0);
+
+    let mut trace = world.resource_mut::<FrameTrace>();
+    let end = trace.now();
+    let mut record = std::mem::take(&mut trace.current);
+    record.frame = frame;
+    record.duration = (end - record.start).max(0.0);
+    record.changed_entities = changed_entities;
+    if trace.frames.len() == trace.capacity {
+        trace.frames.pop_front();
+    }
+    trace.frames.push_back(record);
+}
+
+/// Capture the last `frames` frames (0 stops capturing and drops the buffer).
+/// Changing the frame count restarts the capture.
+#[wasm_bindgen]
+pub fn set_frame_trace_capture(ptr: u64, frames: u32) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(mut trace) = app.world_mut().get_resource_mut::<FrameTrace>() else {
+        return false;
+    };
+    *trace = FrameTrace {
+        capacity: frames as usize,
+        origin: (frames > 0).then(Instant::now),
+        ..default()
+    };
+    true
+}
+
+/// The captured frames as Chrome trace-event JSON (`{ "traceEvents": [...] }`).
+#[wasm_bindgen]
+pub fn export_trace(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    match app.world().get_resource::<FrameTrace>() {
+        Some(trace) => trace.to_chrome_trace().to_string(),
+        None => "{}".to_string(),
+    }
+}
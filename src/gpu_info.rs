@@ -0,0 +1,85 @@
+//! GPU adapter / surface diagnostics for support triage ("black canvas on Android").
+//!
+//! Bevy creates the wgpu instance and surfaces itself; `app_surface` only wraps the
+//! canvas handles, so adapter, limits and features come from the render resources Bevy
+//! mirrors into the main world, and per-window surface state from the render world's
+//! extracted windows.
+
+use bevy::prelude::*;
+use bevy::render::RenderApp;
+use bevy::render::renderer::{RenderAdapterInfo, RenderDevice};
+use bevy::render::view::ExtractedWindows;
+use serde_json::{Value, json};
+use wasm_bindgen::prelude::*;
+
+use crate::WorkerApp;
+
+/// JSON report: `{ adapter, features, limits, surfaces }`. Fields are null until the
+/// renderer has finished initializing (see `is_preparation_completed`).
+#[wasm_bindgen]
+pub fn get_gpu_info(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world();
+
+    let adapter = world.get_resource::<RenderAdapterInfo>().map(|info| {
+        json!({
+            "name": info.name,
+            "vendor": info.vendor,
+            "device": info.device,
+            "device_type": format!("{:?}", info.device_type),
+            "driver": info.driver,
+            "driver_info": info.driver_info,
+            "backend": format!("{:?}", info.backend),
+        })
+    });
+    let device = world.get_resource::<RenderDevice>();
+    let features = device.map(|device| format!("{:?}", device.features()));
+    let limits = device.map(|device| {
+        let limits = device.limits();
+        json!({
+            "max_texture_dimension_2d": limits.max_texture_dimension_2d,
+            "max_texture_array_layers": limits.max_texture_array_layers,
+            "max_bind_groups": limits.max_bind_groups,
+            "max_samplers_per_shader_stage": limits.max_samplers_per_shader_stage,
+            "max_storage_buffers_per_shader_stage": limits.max_storage_buffers_per_shader_stage,
+            "max_uniform_buffer_binding_size": limits.max_uniform_buffer_binding_size,
+            "max_storage_buffer_binding_size": limits.max_storage_buffer_binding_size,
+            "max_buffer_size": limits.max_buffer_size,
+            "max_vertex_attributes": limits.max_vertex_attributes,
+            "max_color_attachments": limits.max_color_attachments,
+            "max_compute_workgroup_size_x": limits.max_compute_workgroup_size_x,
+            "max_compute_invocations_per_workgroup": limits.max_compute_invocations_per_workgroup,
+        })
+    });
+
+    // `swap_chain_texture_format` is the surface's preferred format as chosen by Bevy;
+    // it stays null until the first frame acquires a swap chain texture.
+    let surfaces: Vec<Value> = app
+        .get_sub_app(RenderApp)
+        .and_then(|render_app| render_app.world().get_resource::<ExtractedWindows>())
+        .map(|windows| {
+            windows
+                .windows
+                .values()
+                .map(|window| {
+                    json!({
+                        "window": window.entity.to_bits(),
+                        "physical_width": window.physical_width,
+                        "physical_height": window.physical_height,
+                        "format": window.swap_chain_texture_format.map(|f| format!("{f:?}")),
+                        "present_mode": format!("{:?}", window.present_mode),
+                        "alpha_mode": format!("{:?}", window.alpha_mode),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    json!({
+        "adapter": adapter,
+        "features": features,
+        "limits": limits,
+        "surfaces": surfaces,
+    })
+    .to_string()
+}
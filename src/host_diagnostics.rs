@@ -0,0 +1,87 @@
+//! Diagnostics registered and fed by the host application (network latency, queue
+//! depths, ...). They live in `DiagnosticsStore` next to the engine's own, so inspector
+//! clients receive them through the same `stats` event and can plot them together.
+//!
+//! `register_diagnostic` adds one under a path like `net/latency_ms`;
+//! `push_diagnostic_value` records a measurement. Only host-registered diagnostics
+//! accept pushed values, so engine metrics cannot be overwritten from JS.
+
+use bevy::diagnostic::{Diagnostic, DiagnosticMeasurement, DiagnosticPath, DiagnosticsStore};
+use bevy::platform::collections::HashSet;
+use bevy::platform::time::Instant;
+use bevy::prelude::*;
+use wasm_bindgen::prelude::*;
+
+use crate::{ActivityControl, WorkerApp};
+
+/// Paths registered through `register_diagnostic`.
+#[derive(Resource, Default)]
+struct HostDiagnostics(HashSet<DiagnosticPath>);
+
+/// Whether `name` is a valid `DiagnosticPath` (non-empty `/`-separated segments).
+fn valid_path(name: &str) -> bool {
+    !name.is_empty() && name.split('/').all(|segment| !segment.is_empty())
+}
+
+/// Register a host diagnostic under `name` with display `unit` (e.g. "ms"), or update
+/// the unit of one registered before. Returns false for an invalid path or one already
+/// used by an engine diagnostic.
+#[wasm_bindgen]
+pub fn register_diagnostic(ptr: u64, name: &str, unit: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    if !valid_path(name) {
+        warn!("register_diagnostic: invalid path {name:?}");
+        return false;
+    }
+    let world = app.world_mut();
+    let path = DiagnosticPath::new(name.to_string());
+    let host = world
+        .get_resource_or_init::<HostDiagnostics>()
+        .0
+        .contains(&path);
+    let Some(mut store) = world.get_resource_mut::<DiagnosticsStore>() else {
+        return false;
+    };
+    match store.get_mut(&path) {
+        Some(diagnostic) if host => diagnostic.suffix = unit.to_string().into(),
+        Some(_) => {
+            warn!("register_diagnostic: {name} is an engine diagnostic");
+            return false;
+        }
+        None => store.add(Diagnostic::new(path.clone()).with_suffix(unit.to_string())),
+    }
+    world.resource_mut::<HostDiagnostics>().0.insert(path);
+    true
+}
+
+/// Record `value` for the host diagnostic `name`. Returns false if it was not
+/// registered with `register_diagnostic`.
+#[wasm_bindgen]
+pub fn push_diagnostic_value(ptr: u64, name: &str, value: f64) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    if !valid_path(name) {
+        return false;
+    }
+    let path = DiagnosticPath::new(name.to_string());
+    if !world
+        .get_resource::<HostDiagnostics>()
+        .is_some_and(|host| host.0.contains(&path))
+    {
+        return false;
+    }
+    let Some(diagnostic) = world
+        .get_resource_mut::<DiagnosticsStore>()
+        .and_then(|store| store.into_inner().get_mut(&path))
+    else {
+        return false;
+    };
+    diagnostic.add_measurement(DiagnosticMeasurement {
+        time: Instant::now(),
+        value,
+    });
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
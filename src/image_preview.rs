@@ -0,0 +1,200 @@
+//! Thumbnails of loaded `Image` assets for the asset browser.
+//!
+//! `fetch_image_preview` downscales an image to fit `max_size` and delivers it as PNG
+//! through `send_image_preview_from_worker(request_id, bytes)`. Images that kept their
+//! pixels on the CPU are encoded on the next update; render-world-only images (e.g.
+//! render targets) are read back from the GPU first, which needs `COPY_SRC` usage.
+//! Compressed formats (KTX2 block formats) and 3D textures are not supported; array
+//! and cube textures show their first layer.
+
+use bevy::asset::AssetIndex;
+use bevy::image::TextureFormatPixelInfo;
+use bevy::prelude::*;
+use bevy::render::gpu_readback::{Readback, ReadbackComplete};
+use bevy::render::render_resource::{TextureDimension, TextureUsages};
+use wasm_bindgen::prelude::*;
+
+use crate::overlay_export::encode_png;
+use crate::{ActivityControl, WorkerApp};
+
+#[wasm_bindgen]
+extern "C" {
+    /// Deliver an encoded PNG for a `fetch_image_preview` request (empty on failure).
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_image_preview_from_worker(request_id: u32, png: &[u8]);
+}
+
+/// Source samples averaged per preview pixel, along each axis.
+const SAMPLES_PER_AXIS: u32 = 4;
+
+pub struct ImagePreviewPlugin;
+
+impl Plugin for ImagePreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ImagePreviews>()
+            .add_systems(Update, encode_image_previews);
+    }
+}
+
+/// Pending preview requests. Delivery waits for the next update so the callback
+/// always follows the request id returned to JS.
+#[derive(Resource)]
+struct ImagePreviews {
+    next_request_id: u32,
+    /// (request id, image, max size) with pixels on the CPU.
+    pending: Vec<(u32, AssetId<Image>, u32)>,
+}
+
+impl Default for ImagePreviews {
+    fn default() -> Self {
+        Self {
+            next_request_id: 1,
+            pending: Vec::new(),
+        }
+    }
+}
+
+/// GPU readback in flight for a preview.
+#[derive(Component)]
+struct PreviewReadback {
+    request_id: u32,
+    image: AssetId<Image>,
+    max_size: u32,
+}
+
+/// Preview of the `Image` with asset index `handle_id` (the `asset` of an Image in
+/// `get_asset_dependencies`), at most `max_size` px on its longer side. Returns the
+/// request id echoed by `send_image_preview_from_worker`, or 0 if the image is not
+/// loaded or cannot be previewed.
+#[wasm_bindgen]
+pub fn fetch_image_preview(ptr: u64, handle_id: u64, max_size: u32) -> u32 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    let id = AssetId::<Image>::from(AssetIndex::from_bits(handle_id));
+    let Some(image) = world.resource::<Assets<Image>>().get(id) else {
+        return 0;
+    };
+    if max_size == 0
+        || image.is_compressed()
+        || image.texture_descriptor.dimension != TextureDimension::D2
+    {
+        return 0;
+    }
+    let on_cpu = image.data.is_some();
+    if !on_cpu
+        && !image
+            .texture_descriptor
+            .usage
+            .contains(TextureUsages::COPY_SRC)
+    {
+        warn!("fetch_image_preview: image {handle_id} is GPU-only without COPY_SRC usage");
+        return 0;
+    }
+
+    let mut previews = world.resource_mut::<ImagePreviews>();
+    let request_id = previews.next_request_id;
+    previews.next_request_id = previews.next_request_id.wrapping_add(1).max(1);
+    if on_cpu {
+        previews.pending.push((request_id, id, max_size));
+    } else {
+        world
+            .spawn((
+                Readback::texture(Handle::Weak(id)),
+                PreviewReadback {
+                    request_id,
+                    image: id,
+                    max_size,
+                },
+            ))
+            .observe(read_preview);
+    }
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    request_id
+}
+
+fn encode_image_previews(mut previews: ResMut<ImagePreviews>, images: Res<Assets<Image>>) {
+    for (request_id, id, max_size) in previews.pending.drain(..) {
+        let png = images
+            .get(id)
+            .map(|image| preview_png(image, max_size))
+            .unwrap_or_default();
+        send_image_preview_from_worker(request_id, &png);
+    }
+}
+
+fn read_preview(
+    trigger: Trigger<ReadbackComplete>,
+    readbacks: Query<&PreviewReadback>,
+    images: Res<Assets<Image>>,
+    mut commands: Commands,
+) {
+    let entity = trigger.target();
+    let Ok(readback) = readbacks.get(entity) else {
+        return;
+    };
+    // Readback repeats every frame while the component exists.
+    commands.entity(entity).despawn();
+    let png = images
+        .get(readback.image)
+        .map(|image| {
+            let mut copy = image.clone();
+            copy.data = Some(unpad_rows(image, &trigger.event().0));
+            copy.texture_descriptor.size.depth_or_array_layers = 1;
+            preview_png(&copy, readback.max_size)
+        })
+        .unwrap_or_default();
+    send_image_preview_from_worker(readback.request_id, &png);
+}
+
+/// The first layer of `image` from readback `data`, whose rows are padded to
+/// `COPY_BYTES_PER_ROW_ALIGNMENT`.
+fn unpad_rows(image: &Image, data: &[u8]) -> Vec<u8> {
+    let size = image.texture_descriptor.size;
+    let row = (size.width * image.texture_descriptor.format.pixel_size() as u32) as usize;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as usize;
+    let padded = row.div_ceil(align) * align;
+    data.chunks(padded)
+        .take(size.height as usize)
+        .flat_map(|chunk| &chunk[..row.min(chunk.len())])
+        .copied()
+        .collect()
+}
+
+/// `image` box-filtered to fit `max_size` and encoded as PNG; empty if its format
+/// cannot be read.
+fn preview_png(image: &Image, max_size: u32) -> Vec<u8> {
+    let (width, height) = (image.width(), image.height());
+    if width == 0 || height == 0 || image.get_color_at(0, 0).is_err() {
+        return Vec::new();
+    }
+    let scale = (max_size as f32 / width.max(height) as f32).min(1.0);
+    let out_width = ((width as f32 * scale).round() as u32).max(1);
+    let out_height = ((height as f32 * scale).round() as u32).max(1);
+
+    let mut rgba = Vec::with_capacity((out_width * out_height * 4) as usize);
+    for y in 0..out_height {
+        for x in 0..out_width {
+            let mut sum = LinearRgba::NONE;
+            for sy in 0..SAMPLES_PER_AXIS {
+                for sx in 0..SAMPLES_PER_AXIS {
+                    let u =
+                        (x as f32 + (sx as f32 + 0.5) / SAMPLES_PER_AXIS as f32) / out_width as f32;
+                    let v = (y as f32 + (sy as f32 + 0.5) / SAMPLES_PER_AXIS as f32)
+                        / out_height as f32;
+                    let px = ((u * width as f32) as u32).min(width - 1);
+                    let py = ((v * height as f32) as u32).min(height - 1);
+                    let color = image.get_color_at(px, py).unwrap_or_default();
+                    sum += color.to_linear();
+                }
+            }
+            let average = sum * (1.0 / (SAMPLES_PER_AXIS * SAMPLES_PER_AXIS) as f32);
+            rgba.extend(Srgba::from(average).to_u8_array());
+        }
+    }
+    encode_png(&rgba, out_width, out_height).unwrap_or_else(|e| {
+        error!("Image preview PNG encode failed: {e}");
+        Vec::new()
+    })
+}
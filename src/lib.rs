@@ -4,6 +4,13 @@ use bevy::{
 };
 use std::ops::{Deref, DerefMut};
 
+// Bevy's `webgpu` silently overrides `webgl2`, so a build picks exactly one backend.
+#[cfg(all(feature = "webgpu", feature = "webgl2"))]
+compile_error!(
+    "features `webgpu` and `webgl2` are mutually exclusive; build WebGL2 with \
+     `--no-default-features --features webgl2`"
+);
+
 // original web ffi module
 mod web_ffi;
 pub use web_ffi::*;
@@ -16,8 +23,78 @@ pub use ffi_inspector_bridge::*;
 
 mod canvas_view;
 
+// validation of entity ids passed in from JS
+mod entity_id;
+pub use entity_id::*;
+
+// vertex / index / layout statistics and wireframe previews of loaded meshes
+mod mesh_info;
+pub use mesh_info::*;
+
+// PNG thumbnails of loaded images for the asset browser
+mod image_preview;
+pub use image_preview::*;
+
+// off-screen PNG export of the 2D overlay
+mod overlay_export;
+pub use overlay_export::*;
+
 pub mod panels;
 
+// saved/loaded editor document (guides, ...)
+mod project;
+pub use project::*;
+
+// structured tracing records forwarded to the host console panel
+mod log_bridge;
+pub use log_bridge::*;
+
+// adapter / surface diagnostics for the host UI
+mod gpu_info;
+pub use gpu_info::*;
+
+// builder for downstream crates embedding Ironfell (extra plugins, actions)
+mod app_builder;
+pub use app_builder::*;
+
+// scripted spawn / mutate / streaming stress tests
+mod bench;
+pub use bench::*;
+
+// ring-buffered per-frame trace (commands, input, changes) exported for Perfetto
+mod frame_trace;
+pub use frame_trace::*;
+
+// append-only log of inspector edits, replayed between browsers sharing a scene
+mod op_log;
+pub use op_log::*;
+
+// host-registered diagnostics streamed alongside engine metrics
+mod host_diagnostics;
+pub use host_diagnostics::*;
+
+// wasm heap / asset / GPU memory diagnostics with budget warnings
+mod memory_budget;
+pub use memory_budget::*;
+
+// scripted automation (spawn / set / select / camera / wait / screenshot steps)
+mod script;
+pub use script::*;
+
+// eyedropper: color of a rendered canvas pixel
+mod color_sample;
+pub use color_sample::*;
+
+// optional Rhai runtime for user macros (frame / event / manual triggers)
+#[cfg(feature = "scripting")]
+mod scripting;
+#[cfg(feature = "scripting")]
+pub use scripting::*;
+
+// localizable strings for built-in UI text (FPS HUD, window title, labels)
+mod locale;
+pub use locale::*;
+
 // ray_pick legacy module removed (superseded by new picking systems)
 
 pub mod bevy_app; // expose init_app and related types
@@ -25,8 +102,6 @@ pub use bevy_app::*; // re-export init_app symbols
 
 mod fps_overlay;
 
-mod tracking_circle;
-
 mod asset_reader; // kept private
 
 // mod bevy_vello;
@@ -45,6 +120,9 @@ pub struct WorkerApp {
     /// 手动包装事件需要
     pub window: Entity,
     pub scale_factor: f32,
+    /// `init_app` variant flags, kept to rebuild the app after a GPU device loss. None
+    /// for apps composed with `IronfellAppBuilder`, which can't be rebuilt.
+    pub variant_flags: Option<u32>,
 }
 
 impl Deref for WorkerApp {
@@ -67,11 +145,27 @@ impl WorkerApp {
             app,
             window: Entity::PLACEHOLDER,
             scale_factor: 1.0,
+            variant_flags: None,
         }
     }
 
-    pub fn to_physical_size(&self, x: f32, y: f32) -> Vec2 {
-        Vec2::new(x * self.scale_factor, y * self.scale_factor)
+    /// Host input position (CSS px) to window space, via the window's `CoordinateMapper`
+    /// convention.
+    pub fn to_window_px(&self, x: f32, y: f32) -> Vec2 {
+        let p = Vec2::new(x, y);
+        match self.world().get_resource::<CoordinateMapper>() {
+            Some(mapper) => mapper.host_to_window(self.window, p),
+            None => p * self.scale_factor,
+        }
+    }
+
+    /// Host movement delta (CSS px) to a window-space delta.
+    pub fn to_window_delta(&self, dx: f32, dy: f32) -> Vec2 {
+        let d = Vec2::new(dx, dy);
+        match self.world().get_resource::<CoordinateMapper>() {
+            Some(mapper) => mapper.host_delta_to_window(self.window, d),
+            None => d * self.scale_factor,
+        }
     }
 }
 
@@ -116,6 +210,11 @@ pub struct ModifierSnapshot {
 #[derive(Resource, Debug, Default)]
 pub struct PointerState {
     pub screen: Vec2,
+    /// Host timestamp (`performance.now()` ms) of the newest pointer input, for
+    /// input-latency diagnostics (see `bevy_app::input_latency`).
+    pub timestamp_ms: Option<f64>,
+    /// Pointer is over host UI (`set_ui_exclusion_rects`); presses are dropped.
+    pub excluded: bool,
     pub delta: Vec2,
     pub overlay_world: Option<Vec2>,
     pub world_ray: Option<Ray3d>,
@@ -149,6 +248,8 @@ pub struct SelectionState {
     pub selected: HashMap<Entity, ()>,
     pub hovered: HashMap<Entity, ()>,
     pub last_primary: Option<Entity>,
+    /// `PersistentId`s of the selection; survives despawn/respawn (see bevy_app::persistent_id).
+    pub selected_ids: HashMap<uuid::Uuid, ()>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -0,0 +1,91 @@
+//! Localizable strings for text Ironfell shows or reports itself.
+//!
+//! Keys map to English defaults (`DEFAULT_STRINGS`); `set_locale_strings(ptr, json)`
+//! installs overrides from a flat `{ "key": "text" }` object. Consumers look strings up
+//! with `Locale::get` when they build text, and the FPS HUD and window title follow
+//! changes to the resource. Undo history labels, accessibility names and future
+//! in-canvas text go through the same table.
+
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use wasm_bindgen::prelude::*;
+
+use crate::{ActivityControl, WorkerApp};
+
+/// Built-in English strings, also the list of known keys.
+pub const DEFAULT_STRINGS: &[(&str, &str)] = &[
+    ("window.title", "Ironfell"),
+    ("fps.raw", "FPS (raw): "),
+    ("fps.sma", "FPS (SMA): "),
+    ("fps.ema", "FPS (EMA): "),
+    ("fps.gpu", "GPU (ms): "),
+    ("fps.render_cpu", "Render CPU (ms): "),
+    ("fps.vello_encode", "Vello encode (ms): "),
+    ("fps.input_latency", "Input latency (ms): "),
+    ("history.rotate", "Rotate"),
+    ("history.scale", "Scale"),
+    ("history.edit_component", "Edit component"),
+    ("history.edit_components", "Edit components"),
+    ("history.scatter", "Scatter"),
+    ("history.toggle_component", "Toggle component"),
+    ("history.remove_component", "Remove component"),
+    ("history.insert_component", "Insert component"),
+    ("history.despawn", "Delete"),
+    ("history.toggle_visibility", "Toggle visibility"),
+    ("history.reparent", "Reparent"),
+    ("history.spawn", "Add entity"),
+    ("a11y.application", "Ironfell"),
+    ("a11y.viewer", "3D viewer"),
+    ("a11y.overlay", "Overlay"),
+    ("a11y.object", "Object"),
+    ("a11y.path", "Path"),
+    ("a11y.text", "Text"),
+];
+
+#[derive(Resource, Debug, Default)]
+pub struct Locale {
+    overrides: HashMap<String, String>,
+}
+
+impl Locale {
+    /// The override for `key`, else the English default, else the key itself.
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.overrides
+            .get(key)
+            .map_or_else(|| Self::english(key), String::as_str)
+    }
+
+    /// The built-in English string for `key` (the key itself if unknown).
+    pub fn english(key: &str) -> &str {
+        DEFAULT_STRINGS
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map_or(key, |(_, v)| *v)
+    }
+}
+
+/// Replace the string overrides with a JSON object of `key -> text`; `{}` restores
+/// English. Unknown keys are kept for in-canvas text that looks them up later.
+/// Returns false for invalid JSON.
+#[wasm_bindgen]
+pub fn set_locale_strings(ptr: u64, json: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Ok(overrides) = serde_json::from_str::<HashMap<String, String>>(json) else {
+        return false;
+    };
+    let world = app.world_mut();
+    let Some(mut locale) = world.get_resource_mut::<Locale>() else {
+        return false;
+    };
+    locale.overrides = overrides;
+    let title = locale.get("window.title").to_string();
+    let mut windows = world.query_filtered::<&mut Window, With<PrimaryWindow>>();
+    for mut window in windows.iter_mut(world) {
+        window.title.clone_from(&title);
+    }
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
@@ -0,0 +1,252 @@
+//! Structured log channel to the host console panel.
+//!
+//! A `tracing` layer installed through `LogPlugin::custom_layer` forwards every event
+//! as JSON `{ level, target, message, fields }` via `send_log_from_worker(json)`.
+//! Categories are tracing targets (module paths such as `ironfell::bevy_app::tools`).
+//! `set_log_filter(ptr, filter)` narrows what is forwarded with EnvFilter-like
+//! directives: `"warn,ironfell=debug,wgpu=off"` (longest matching target prefix
+//! wins; a bare level sets the default). Events must also pass `LogPlugin`'s own
+//! filter, so this can only narrow what Bevy already emits.
+//!
+//! All of Ironfell logs through `tracing`. Events before the first app installs the
+//! subscriber (e.g. rejected `init_bevy_app_with_options` JSON) go nowhere; those
+//! calls also report the failure in their return value.
+
+use std::sync::{Arc, RwLock};
+
+use bevy::log::BoxedLayer;
+use bevy::log::tracing::{Event, Level, Subscriber, field::Field, field::Visit};
+use bevy::log::tracing_subscriber::{Layer, filter::LevelFilter, layer::Context};
+use bevy::prelude::*;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::WorkerApp;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_log_from_worker(record_json: &str);
+}
+
+/// Parsed `set_log_filter` directives.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFilter {
+    default: LevelFilter,
+    /// (target prefix, level), longest prefix first.
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self {
+            default: LevelFilter::TRACE,
+            targets: Vec::new(),
+        }
+    }
+}
+
+impl LogFilter {
+    /// Parse comma-separated `level` / `target=level` directives. Returns None if any
+    /// level is unknown.
+    pub fn parse(filter: &str) -> Option<Self> {
+        let mut parsed = Self::default();
+        for directive in filter.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => parsed
+                    .targets
+                    .push((target.trim().to_string(), level.trim().parse().ok()?)),
+                None => parsed.default = directive.parse().ok()?,
+            }
+        }
+        parsed
+            .targets
+            .sort_by_key(|(target, _)| std::cmp::Reverse(target.len()));
+        Some(parsed)
+    }
+
+    pub fn enabled(&self, target: &str, level: &Level) -> bool {
+        let max = self
+            .targets
+            .iter()
+            .find(|(prefix, _)| {
+                target
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .map_or(self.default, |(_, level)| *level);
+        *level <= max
+    }
+}
+
+/// Filter shared between the `tracing` layer and `set_log_filter`. Only present when
+/// `LogPlugin` is enabled.
+#[derive(Resource, Clone, Default)]
+pub struct LogBridge {
+    filter: Arc<RwLock<LogFilter>>,
+}
+
+/// `LogPlugin::custom_layer` hook: forward events to JS.
+pub(crate) fn log_bridge_layer(app: &mut App) -> Option<BoxedLayer> {
+    let bridge = LogBridge::default();
+    app.insert_resource(bridge.clone());
+    Some(Box::new(JsLogLayer {
+        filter: bridge.filter,
+    }))
+}
+
+struct JsLogLayer {
+    filter: Arc<RwLock<LogFilter>>,
+}
+
+#[derive(Serialize)]
+struct LogRecord<'a> {
+    level: &'a str,
+    target: &'a str,
+    message: String,
+    fields: Map<String, Value>,
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: Map<String, Value>,
+}
+
+impl FieldVisitor {
+    fn insert(&mut self, field: &Field, value: Value) {
+        self.fields.insert(field.name().to_string(), value);
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.insert(field, value.into());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{value:?}");
+        } else {
+            self.insert(field, format!("{value:?}").into());
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for JsLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let enabled = self
+            .filter
+            .read()
+            .is_ok_and(|filter| filter.enabled(metadata.target(), metadata.level()));
+        if !enabled {
+            return;
+        }
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let record = LogRecord {
+            level: metadata.level().as_str(),
+            target: metadata.target(),
+            message: visitor.message,
+            fields: visitor.fields,
+        };
+        // No `error!` here: it would re-enter this layer.
+        if let Ok(json) = serde_json::to_string(&record) {
+            send_log_from_worker(&json);
+        }
+    }
+}
+
+/// Replace the forwarding filter. Returns false if the filter does not parse or the
+/// log plugin is disabled (`?bevy=nolog`).
+#[wasm_bindgen]
+pub fn set_log_filter(ptr: u64, filter: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let (Some(bridge), Some(parsed)) = (
+        app.world().get_resource::<LogBridge>(),
+        LogFilter::parse(filter),
+    ) else {
+        return false;
+    };
+    match bridge.filter.write() {
+        Ok(mut current) => {
+            *current = parsed;
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_forwards_everything() {
+        let filter = LogFilter::default();
+        assert_eq!(LogFilter::parse(""), Some(filter.clone()));
+        assert!(filter.enabled("ironfell", &Level::TRACE));
+        assert!(filter.enabled("wgpu_core::device", &Level::TRACE));
+    }
+
+    #[test]
+    fn bare_level_sets_default() {
+        let filter = LogFilter::parse("warn").unwrap();
+        assert!(filter.enabled("ironfell", &Level::WARN));
+        assert!(filter.enabled("ironfell", &Level::ERROR));
+        assert!(!filter.enabled("ironfell", &Level::INFO));
+    }
+
+    #[test]
+    fn target_directives() {
+        let filter = LogFilter::parse(" warn, ironfell=debug ,wgpu=off").unwrap();
+        assert!(filter.enabled("ironfell::bevy_app::tools", &Level::DEBUG));
+        assert!(!filter.enabled("ironfell::bevy_app::tools", &Level::TRACE));
+        assert!(!filter.enabled("wgpu", &Level::ERROR));
+        assert!(filter.enabled("naga", &Level::WARN));
+        assert!(!filter.enabled("naga", &Level::INFO));
+    }
+
+    #[test]
+    fn longest_prefix_wins() {
+        let filter = LogFilter::parse("ironfell=warn,ironfell::bevy_app::tools=trace").unwrap();
+        assert!(filter.enabled("ironfell::bevy_app::tools", &Level::TRACE));
+        assert!(filter.enabled("ironfell::bevy_app::tools::pen", &Level::TRACE));
+        assert!(!filter.enabled("ironfell::bevy_app::history", &Level::INFO));
+    }
+
+    #[test]
+    fn prefix_matches_whole_path_segments() {
+        let filter = LogFilter::parse("iron=off").unwrap();
+        assert!(filter.enabled("ironfell", &Level::TRACE));
+        assert!(!filter.enabled("iron::x", &Level::ERROR));
+    }
+
+    #[test]
+    fn unknown_level_is_rejected() {
+        assert_eq!(LogFilter::parse("loud"), None);
+        assert_eq!(LogFilter::parse("ironfell=loud"), None);
+    }
+}
@@ -0,0 +1,372 @@
+//! Memory use as Bevy diagnostics, with host-configurable warning thresholds.
+//!
+//! Once per `SAMPLE_INTERVAL` the plugin records (in MB):
+//! - `memory/wasm_heap_mb`: wasm linear memory size (never shrinks);
+//! - `memory/assets_mb`: CPU-side bytes of loaded `Image` and `Mesh` assets (assets
+//!   that dropped their data after upload count as 0);
+//! - `memory/gpu_buffers_mb` / `memory/gpu_textures_mb`: mesh slab buffers and
+//!   image textures the renderer holds, estimated from sizes and formats;
+//! - `memory/gpu_allocated_mb`: wgpu's allocator total, on backends that report it
+//!   (not WebGPU).
+//!
+//! They reach inspector clients through the `stats` event. `set_memory_budget` sets
+//! per-metric limits; crossing one calls `send_warning_from_worker` once, and again
+//! only after the value has dropped back under the limit.
+//!
+//! `unload_unused_assets` drops the handles the crate itself keeps alive (the render
+//! debug materials) when nothing uses them, and `unload_asset` removes the assets
+//! loaded from a path. Both process the resulting handle drops at once and report the
+//! CPU-side image and mesh bytes freed; GPU copies go over the next frames.
+
+use bevy::asset::{AssetPath, ReflectAsset, UntypedAssetId};
+use bevy::diagnostic::{
+    Diagnostic, DiagnosticPath, Diagnostics, DiagnosticsStore, RegisterDiagnostic,
+};
+use bevy::gltf::Gltf;
+use bevy::platform::collections::HashSet;
+use bevy::prelude::*;
+use bevy::render::mesh::RenderMesh;
+use bevy::render::mesh::allocator::MeshAllocator;
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::renderer::RenderDevice;
+use bevy::render::texture::GpuImage;
+use bevy::render::{Render, RenderApp, RenderSet};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::asset_graph::{asset_paths, sub_assets};
+use crate::{ActivityControl, WorkerApp};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_warning_from_worker(warning_json: &str);
+}
+
+pub const WASM_HEAP: DiagnosticPath = DiagnosticPath::const_new("memory/wasm_heap_mb");
+pub const ASSETS: DiagnosticPath = DiagnosticPath::const_new("memory/assets_mb");
+pub const GPU_BUFFERS: DiagnosticPath = DiagnosticPath::const_new("memory/gpu_buffers_mb");
+pub const GPU_TEXTURES: DiagnosticPath = DiagnosticPath::const_new("memory/gpu_textures_mb");
+pub const GPU_ALLOCATED: DiagnosticPath = DiagnosticPath::const_new("memory/gpu_allocated_mb");
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+const MB: f64 = 1024.0 * 1024.0;
+
+/// Limits in MB keyed by metric name (`wasm_heap_mb`, `assets_mb`, `gpu_buffers_mb`,
+/// `gpu_textures_mb`, `gpu_allocated_mb`); a missing limit never warns.
+#[derive(Resource, Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MemoryBudget {
+    pub wasm_heap_mb: Option<f64>,
+    pub assets_mb: Option<f64>,
+    pub gpu_buffers_mb: Option<f64>,
+    pub gpu_textures_mb: Option<f64>,
+    pub gpu_allocated_mb: Option<f64>,
+    #[serde(skip)]
+    exceeded: HashSet<&'static str>,
+}
+
+impl MemoryBudget {
+    fn limits(&self) -> [(&'static str, DiagnosticPath, Option<f64>); 5] {
+        [
+            ("wasm_heap_mb", WASM_HEAP, self.wasm_heap_mb),
+            ("assets_mb", ASSETS, self.assets_mb),
+            ("gpu_buffers_mb", GPU_BUFFERS, self.gpu_buffers_mb),
+            ("gpu_textures_mb", GPU_TEXTURES, self.gpu_textures_mb),
+            ("gpu_allocated_mb", GPU_ALLOCATED, self.gpu_allocated_mb),
+        ]
+    }
+}
+
+/// Render-world GPU totals in bytes, handed to the main world once per sample.
+#[derive(Debug, Clone, Copy, Default)]
+struct GpuMemory {
+    buffers: u64,
+    textures: u64,
+    allocated: Option<u64>,
+}
+
+/// Empty while the main world waits for a sample; the render world refills it.
+#[derive(Resource, Clone, Default)]
+struct GpuMemorySlot(Arc<Mutex<Option<GpuMemory>>>);
+
+pub struct MemoryBudgetPlugin;
+
+impl Plugin for MemoryBudgetPlugin {
+    fn build(&self, app: &mut App) {
+        let slot = GpuMemorySlot::default();
+        app.register_diagnostic(Diagnostic::new(WASM_HEAP).with_suffix("MB"))
+            .register_diagnostic(Diagnostic::new(ASSETS).with_suffix("MB"))
+            .register_diagnostic(Diagnostic::new(GPU_BUFFERS).with_suffix("MB"))
+            .register_diagnostic(Diagnostic::new(GPU_TEXTURES).with_suffix("MB"))
+            .register_diagnostic(Diagnostic::new(GPU_ALLOCATED).with_suffix("MB"))
+            .init_resource::<MemoryBudget>()
+            .insert_resource(slot.clone())
+            .add_systems(
+                Update,
+                (sample_memory, check_memory_budget.after(sample_memory)),
+            );
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .insert_resource(slot)
+                .add_systems(Render, measure_gpu_memory.in_set(RenderSet::Cleanup));
+        }
+    }
+}
+
+fn wasm_heap_bytes() -> Option<u64> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        Some(core::arch::wasm32::memory_size(0) as u64 * 65536)
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        None
+    }
+}
+
+fn asset_bytes(images: &Assets<Image>, meshes: &Assets<Mesh>) -> u64 {
+    let images: u64 = images
+        .iter()
+        .filter_map(|(_, image)| image.data.as_ref())
+        .map(|data| data.len() as u64)
+        .sum();
+    let meshes: u64 = meshes
+        .iter()
+        .map(|(_, mesh)| {
+            let vertices = mesh.count_vertices() as u64 * mesh.get_vertex_size();
+            let indices = match mesh.indices() {
+                Some(indices @ bevy::render::mesh::Indices::U16(_)) => indices.len() as u64 * 2,
+                Some(indices) => indices.len() as u64 * 4,
+                None => 0,
+            };
+            vertices + indices
+        })
+        .sum();
+    images + meshes
+}
+
+fn sample_memory(
+    time: Res<Time<Real>>,
+    mut next_sample: Local<Duration>,
+    slot: Res<GpuMemorySlot>,
+    images: Option<Res<Assets<Image>>>,
+    meshes: Option<Res<Assets<Mesh>>>,
+    mut diagnostics: Diagnostics,
+) {
+    if time.elapsed() < *next_sample {
+        return;
+    }
+    *next_sample = time.elapsed() + SAMPLE_INTERVAL;
+
+    if let Some(bytes) = wasm_heap_bytes() {
+        diagnostics.add_measurement(&WASM_HEAP, || bytes as f64 / MB);
+    }
+    if let (Some(images), Some(meshes)) = (images, meshes) {
+        let bytes = asset_bytes(&images, &meshes);
+        diagnostics.add_measurement(&ASSETS, || bytes as f64 / MB);
+    }
+    let gpu = slot.0.lock().ok().and_then(|mut gpu| gpu.take());
+    if let Some(gpu) = gpu {
+        diagnostics.add_measurement(&GPU_BUFFERS, || gpu.buffers as f64 / MB);
+        diagnostics.add_measurement(&GPU_TEXTURES, || gpu.textures as f64 / MB);
+        if let Some(allocated) = gpu.allocated {
+            diagnostics.add_measurement(&GPU_ALLOCATED, || allocated as f64 / MB);
+        }
+    }
+}
+
+/// Bytes of all mip levels of `image`'s texture.
+fn texture_bytes(image: &GpuImage) -> u64 {
+    let format = image.texture_format;
+    let (block_width, block_height) = format.block_dimensions();
+    let block_size = format.block_copy_size(None).unwrap_or(4) as u64;
+    let layers = image.size.depth_or_array_layers as u64;
+    (0..image.mip_level_count)
+        .map(|mip| {
+            let width = (image.size.width >> mip).max(1).div_ceil(block_width) as u64;
+            let height = (image.size.height >> mip).max(1).div_ceil(block_height) as u64;
+            width * height * layers * block_size
+        })
+        .sum()
+}
+
+fn measure_gpu_memory(
+    slot: Res<GpuMemorySlot>,
+    device: Res<RenderDevice>,
+    images: Res<RenderAssets<GpuImage>>,
+    meshes: Option<Res<RenderAssets<RenderMesh>>>,
+    mesh_allocator: Option<Res<MeshAllocator>>,
+) {
+    let Ok(mut gpu) = slot.0.lock() else {
+        return;
+    };
+    if gpu.is_some() {
+        return;
+    }
+
+    // Meshes share slab buffers; count each buffer once.
+    let mut seen = HashSet::new();
+    let mut buffers = 0;
+    if let (Some(meshes), Some(mesh_allocator)) = (meshes, mesh_allocator) {
+        for (id, _) in meshes.iter() {
+            let slices = [
+                mesh_allocator.mesh_vertex_slice(&id),
+                mesh_allocator.mesh_index_slice(&id),
+            ];
+            for slice in slices.into_iter().flatten() {
+                if seen.insert(slice.buffer.id()) {
+                    buffers += slice.buffer.size();
+                }
+            }
+        }
+    }
+    *gpu = Some(GpuMemory {
+        buffers,
+        textures: images.iter().map(|(_, image)| texture_bytes(image)).sum(),
+        allocated: device
+            .wgpu_device()
+            .generate_allocator_report()
+            .map(|report| report.total_allocated_bytes),
+    });
+}
+
+fn check_memory_budget(mut budget: ResMut<MemoryBudget>, store: Res<DiagnosticsStore>) {
+    for (metric, path, limit) in budget.limits() {
+        let Some(limit) = limit else {
+            continue;
+        };
+        let Some(value) = store.get(&path).and_then(|diagnostic| diagnostic.value()) else {
+            continue;
+        };
+        if value <= limit {
+            budget.exceeded.remove(metric);
+        } else if budget.exceeded.insert(metric) {
+            let warning = json!({
+                "kind": "memory_budget",
+                "metric": metric,
+                "value_mb": value,
+                "limit_mb": limit,
+                "message": format!("{metric} is {value:.1} MB, over the {limit:.1} MB budget"),
+            });
+            warn!("{}", warning["message"].as_str().unwrap_or_default());
+            send_warning_from_worker(&warning.to_string());
+        }
+    }
+}
+
+/// Set the warning thresholds, e.g. `{ "wasm_heap_mb": 1024, "gpu_textures_mb": 512 }`.
+/// Omitted metrics have no limit. Returns false on invalid JSON.
+#[wasm_bindgen]
+pub fn set_memory_budget(ptr: u64, budget_json: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Ok(budget) = serde_json::from_str::<MemoryBudget>(budget_json) else {
+        return false;
+    };
+    app.world_mut().insert_resource(budget);
+    true
+}
+
+fn world_asset_bytes(world: &World) -> u64 {
+    match (
+        world.get_resource::<Assets<Image>>(),
+        world.get_resource::<Assets<Mesh>>(),
+    ) {
+        (Some(images), Some(meshes)) => asset_bytes(images, meshes),
+        _ => 0,
+    }
+}
+
+/// Free assets whose last strong handle was dropped, now rather than at the next
+/// update. Scenes and materials go first, so the handles they hold are dropped too.
+fn collect_dropped_assets(world: &mut World) {
+    let _ = world.run_system_cached(Assets::<Scene>::track_assets);
+    let _ = world.run_system_cached(Assets::<StandardMaterial>::track_assets);
+    let _ = world.run_system_cached(Assets::<ColorMaterial>::track_assets);
+    let _ = world.run_system_cached(Assets::<Image>::track_assets);
+    let _ = world.run_system_cached(Assets::<Mesh>::track_assets);
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+}
+
+/// Remove the asset `id` regardless of the handles to it. Returns whether it existed.
+fn remove_asset(world: &mut World, id: UntypedAssetId) -> bool {
+    if let Ok(scene) = id.try_typed::<Scene>() {
+        return world
+            .resource_mut::<Assets<Scene>>()
+            .remove(scene)
+            .is_some();
+    }
+    if let Ok(gltf) = id.try_typed::<Gltf>() {
+        return world
+            .get_resource_mut::<Assets<Gltf>>()
+            .is_some_and(|mut gltfs| gltfs.remove(gltf).is_some());
+    }
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let Some(reflect_asset) = registry
+        .read()
+        .get_type_data::<ReflectAsset>(id.type_id())
+        .cloned()
+    else {
+        return false;
+    };
+    reflect_asset
+        .remove(world, UntypedHandle::Weak(id))
+        .is_some()
+}
+
+/// Release the asset handles the crate holds for itself when nothing uses them, and
+/// free what they kept alive. Returns `{ "released": n, "bytes": freed }`.
+#[wasm_bindgen]
+pub fn unload_unused_assets(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    let before = world_asset_bytes(world);
+    let mut released = 0;
+    #[cfg(feature = "render_debug")]
+    if crate::bevy_app::render_debug::release_debug_materials(world) {
+        released += 1;
+    }
+    collect_dropped_assets(world);
+    let bytes = before.saturating_sub(world_asset_bytes(world));
+    json!({ "released": released, "bytes": bytes }).to_string()
+}
+
+/// Remove the assets loaded from `path`, and for a file path (no `#label`) everything
+/// loaded from it under a label, even if entities still use them (check
+/// `get_asset_dependencies` first). Returns `{ "removed": n, "bytes": freed }`, or
+/// `{ "error": message }` for a malformed path or one with nothing loaded.
+#[wasm_bindgen]
+pub fn unload_asset(ptr: u64, path: &str) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    let Some(server) = world.get_resource::<AssetServer>() else {
+        return json!({ "error": "no asset server" }).to_string();
+    };
+    let asset_path = match AssetPath::try_parse(path) {
+        Ok(asset_path) => asset_path,
+        Err(e) => return json!({ "error": format!("invalid asset path {path}: {e}") }).to_string(),
+    };
+    let mut ids = server.get_path_ids(asset_path.clone());
+    if asset_path.label().is_none() {
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let paths = asset_paths(world, &registry.read());
+        ids.extend(sub_assets(&paths, &asset_path));
+    }
+    if ids.is_empty() {
+        return json!({ "error": format!("no asset loaded from {path}") }).to_string();
+    }
+
+    let before = world_asset_bytes(world);
+    let removed = ids
+        .into_iter()
+        .filter(|id| remove_asset(world, *id))
+        .count();
+    collect_dropped_assets(world);
+    let bytes = before.saturating_sub(world_asset_bytes(world));
+    json!({ "removed": removed, "bytes": bytes }).to_string()
+}
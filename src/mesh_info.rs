@@ -0,0 +1,170 @@
+//! Mesh statistics for the asset browser.
+//!
+//! `get_mesh_info` reports a loaded `Mesh`'s counts, vertex layout and bounds, and
+//! optionally a wireframe preview: the mesh's longest unique edges, projected with a
+//! fixed three-quarter view into the unit square, for the UI to stroke as 2D lines.
+//! Only meshes that kept their data on the CPU can be inspected.
+
+use bevy::asset::AssetIndex;
+use bevy::platform::collections::HashSet;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, MeshAabb, PrimitiveTopology, VertexAttributeValues};
+use serde_json::{Value, json};
+use wasm_bindgen::prelude::*;
+
+use crate::WorkerApp;
+
+/// Unique edges of `mesh` as vertex index pairs (lower index first).
+fn edges(mesh: &Mesh) -> Vec<(u32, u32)> {
+    let indices: Vec<u32> = match mesh.indices() {
+        Some(indices) => indices.iter().map(|i| i as u32).collect(),
+        None => (0..mesh.count_vertices() as u32).collect(),
+    };
+    let pairs: Vec<(u32, u32)> = match mesh.primitive_topology() {
+        PrimitiveTopology::TriangleList => indices
+            .chunks_exact(3)
+            .flat_map(|t| [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])])
+            .collect(),
+        PrimitiveTopology::TriangleStrip => indices
+            .windows(3)
+            .flat_map(|t| [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])])
+            .collect(),
+        PrimitiveTopology::LineList => indices.chunks_exact(2).map(|l| (l[0], l[1])).collect(),
+        PrimitiveTopology::LineStrip => indices.windows(2).map(|l| (l[0], l[1])).collect(),
+        PrimitiveTopology::PointList => Vec::new(),
+    };
+    let mut seen = HashSet::new();
+    pairs
+        .into_iter()
+        .filter(|(a, b)| a != b)
+        .map(|(a, b)| (a.min(b), a.max(b)))
+        .filter(|edge| seen.insert(*edge))
+        .collect()
+}
+
+/// Up to `max_edges` of the longest edges, as `[x0, y0, x1, y1]` in the unit square
+/// (y down) under a three-quarter view.
+fn wireframe(mesh: &Mesh, max_edges: usize) -> Vec<[f32; 4]> {
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return Vec::new();
+    };
+    let mut edges: Vec<_> = edges(mesh)
+        .into_iter()
+        .filter_map(|(a, b)| {
+            let a = Vec3::from(*positions.get(a as usize)?);
+            let b = Vec3::from(*positions.get(b as usize)?);
+            Some((a, b))
+        })
+        .collect();
+    edges.sort_by(|(a0, b0), (a1, b1)| {
+        a1.distance_squared(*b1)
+            .total_cmp(&a0.distance_squared(*b0))
+    });
+    edges.truncate(max_edges);
+
+    let view =
+        Quat::from_rotation_x(-30f32.to_radians()) * Quat::from_rotation_y(45f32.to_radians());
+    let projected: Vec<(Vec2, Vec2)> = edges
+        .iter()
+        .map(|(a, b)| ((view * *a).truncate(), (view * *b).truncate()))
+        .collect();
+    let (min, max) = projected.iter().fold(
+        (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+        |(min, max), (a, b)| (min.min(a.min(*b)), max.max(a.max(*b))),
+    );
+    // Uniform scale keeps proportions; the shorter axis is centered.
+    let extent = (max - min).max_element().max(f32::EPSILON);
+    let offset = (Vec2::splat(extent) - (max - min)) * 0.5;
+    let to_unit = |p: Vec2| {
+        let p = (p - min + offset) / extent;
+        Vec2::new(p.x, 1.0 - p.y)
+    };
+    projected
+        .into_iter()
+        .map(|(a, b)| {
+            let (a, b) = (to_unit(a), to_unit(b));
+            [a.x, a.y, b.x, b.y]
+        })
+        .collect()
+}
+
+/// Statistics of the `Mesh` with asset index `handle_id`. Returns
+/// `{ "topology", "vertices", "indices", "index_format", "primitives", "vertex_size",
+///    "bytes", "attributes": [{ "name", "id", "format", "size" }], "morph_targets",
+///    "aabb": { "min", "max" } | null, "wireframe"? }`,
+/// where `indices` and `index_format` are null for unindexed meshes and `primitives`
+/// counts triangles, lines or points. With `preview_edges` > 0, `wireframe` holds up
+/// to that many `[x0, y0, x1, y1]` lines in the unit square. Returns
+/// `{ "error": message }` for a mesh that is not loaded or has no CPU data.
+#[wasm_bindgen]
+pub fn get_mesh_info(ptr: u64, handle_id: u64, preview_edges: u32) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world();
+    let id = AssetId::<Mesh>::from(AssetIndex::from_bits(handle_id));
+    let Some(mesh) = world
+        .get_resource::<Assets<Mesh>>()
+        .and_then(|meshes| meshes.get(id))
+    else {
+        return json!({ "error": format!("mesh {handle_id} is not loaded on the CPU") })
+            .to_string();
+    };
+
+    let vertices = mesh.count_vertices();
+    let index_count = mesh.indices().map(Indices::len);
+    let elements = index_count.unwrap_or(vertices);
+    let topology = mesh.primitive_topology();
+    let primitives = match topology {
+        PrimitiveTopology::TriangleList => elements / 3,
+        PrimitiveTopology::TriangleStrip => elements.saturating_sub(2),
+        PrimitiveTopology::LineList => elements / 2,
+        PrimitiveTopology::LineStrip => elements.saturating_sub(1),
+        PrimitiveTopology::PointList => elements,
+    };
+    let index_format = match mesh.indices() {
+        Some(Indices::U16(_)) => json!("u16"),
+        Some(Indices::U32(_)) => json!("u32"),
+        None => Value::Null,
+    };
+    let index_bytes = match mesh.indices() {
+        Some(Indices::U16(indices)) => indices.len() * 2,
+        Some(Indices::U32(indices)) => indices.len() * 4,
+        None => 0,
+    };
+    let vertex_size = mesh.get_vertex_size();
+    let attributes: Vec<Value> = mesh
+        .attributes()
+        .map(|(attribute, _)| {
+            json!({
+                "name": attribute.name,
+                "id": format!("{:?}", attribute.id),
+                "format": format!("{:?}", attribute.format),
+                "size": attribute.format.size(),
+            })
+        })
+        .collect();
+    let aabb = mesh.compute_aabb().map(|aabb| {
+        json!({
+            "min": Vec3::from(aabb.min()).to_array(),
+            "max": Vec3::from(aabb.max()).to_array(),
+        })
+    });
+
+    let mut info = json!({
+        "topology": format!("{:?}", topology),
+        "vertices": vertices,
+        "indices": index_count,
+        "index_format": index_format,
+        "primitives": primitives,
+        "vertex_size": vertex_size,
+        "bytes": vertices as u64 * vertex_size + index_bytes as u64,
+        "attributes": attributes,
+        "morph_targets": mesh.has_morph_targets(),
+        "aabb": aabb,
+    });
+    if preview_edges > 0 {
+        info["wireframe"] = json!(wireframe(mesh, preview_edges as usize));
+    }
+    info.to_string()
+}
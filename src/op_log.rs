@@ -0,0 +1,600 @@
+//! Operation log: groundwork for two browsers editing the same scene.
+//!
+//! Once the host names this replica (`set_op_log_client(ptr, client_id)`), every
+//! inspector command, undo and redo appends what it changed to an append-only `OpLog`:
+//! one `Operation` per component set or removed and per entity despawned, stamped with
+//! the replica id and a vector clock. Entities are addressed by `PersistentId` and
+//! components by type name, so an op means the same thing in another browser that
+//! loaded the same scene.
+//!
+//! `get_ops_since(ptr, clock_json)` gives a peer the ops its clock hasn't covered;
+//! `apply_remote_ops(ptr, ops_json)` replays a peer's ops in causal order: an op is
+//! delivered once it is the next one from its replica and every op it depends on has
+//! been delivered, and waits in the log until then (the local clock only counts
+//! delivered ops, so peers resend what is missing). Conflicts are last-writer-wins per
+//! (entity, component): an op is dropped when the last write we know of for its target
+//! causally follows it, and concurrent writes (neither clock covers the other) go to
+//! the higher total count, then the higher replica id, so every replica keeps the same
+//! winner.
+//!
+//! Not covered yet: entities spawned after the scene loaded (their `PersistentId` is
+//! assigned after the spawning command, so peers can't address them), hierarchy
+//! changes (`ChildOf` holds local entity ids) and edits outside the inspector, such
+//! as viewport drags.
+
+use std::any::TypeId;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use anyhow::anyhow;
+use bevy::ecs::component::{ComponentId, Tick};
+use bevy::ecs::event::EventCursor;
+use bevy::ecs::removal_detection::RemovedComponentEntity;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy_remote_inspector::InspectorContext;
+use bevy_remote_inspector::command::{
+    DespawnEntity, DespawnEntityKind, Execute, ReadComponent, RemoveComponent,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use uuid::Uuid;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::persistent_id::{PersistentId, PersistentIndex};
+use crate::script::{component_id, set_component};
+use crate::{ActivityControl, WorkerApp};
+
+/// Ops seen from each replica, by replica id.
+pub type VectorClock = BTreeMap<String, u64>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum OpKind {
+    /// Component value as the inspector serializes it.
+    Set { component: String, value: Value },
+    Remove { component: String },
+    Despawn,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Operation {
+    /// Replica that made the edit.
+    pub client: String,
+    /// That replica's clock once the edit was made; `clock[client]` numbers its ops.
+    pub clock: VectorClock,
+    pub entity: Uuid,
+    #[serde(flatten)]
+    pub kind: OpKind,
+}
+
+impl Operation {
+    fn seq(&self) -> u64 {
+        self.clock.get(&self.client).copied().unwrap_or(0)
+    }
+
+    /// What last-writer-wins is decided per: a component, or the entity for despawns.
+    fn target(&self) -> (Uuid, Option<String>) {
+        let component = match &self.kind {
+            OpKind::Set { component, .. } | OpKind::Remove { component } => {
+                Some(component.clone())
+            }
+            OpKind::Despawn => None,
+        };
+        (self.entity, component)
+    }
+
+    /// Whether this op wins over `other` on the same target (see module docs).
+    fn supersedes(&self, other: &Operation) -> bool {
+        match causal_order(&self.clock, &other.clock) {
+            Some(order) => order == Ordering::Greater,
+            None => {
+                let total = |clock: &VectorClock| clock.values().sum::<u64>();
+                (total(&self.clock), &self.client) > (total(&other.clock), &other.client)
+            }
+        }
+    }
+}
+
+/// How two clocks are ordered by happened-before; None when they are concurrent.
+fn causal_order(a: &VectorClock, b: &VectorClock) -> Option<Ordering> {
+    let mut order = Ordering::Equal;
+    for client in a.keys().chain(b.keys()) {
+        let count = |clock: &VectorClock| clock.get(client).copied().unwrap_or(0);
+        match (order, count(a).cmp(&count(b))) {
+            (_, Ordering::Equal) => {}
+            (Ordering::Equal, step) => order = step,
+            (order, step) if order != step => return None,
+            _ => {}
+        }
+    }
+    Some(order)
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct OpLog {
+    /// This replica's id; nothing is logged until the host sets one.
+    client: Option<String>,
+    /// Delivered ops from each replica; every replica's ops are delivered in sequence.
+    clock: VectorClock,
+    ops: Vec<Operation>,
+    /// Remote ops received ahead of an op they depend on, in arrival order.
+    pending: Vec<Operation>,
+    /// Latest winning op per target, as an index into `ops`.
+    last_write: HashMap<(Uuid, Option<String>), usize>,
+}
+
+impl OpLog {
+    fn enabled(&self) -> bool {
+        self.client.is_some()
+    }
+
+    fn seen(&self, client: &str) -> u64 {
+        self.clock.get(client).copied().unwrap_or(0)
+    }
+
+    fn has_seen(&self, op: &Operation) -> bool {
+        op.seq() <= self.seen(&op.client)
+            || self
+                .pending
+                .iter()
+                .any(|pending| pending.client == op.client && pending.seq() == op.seq())
+    }
+
+    /// The next op from its replica, with everything it depends on delivered.
+    fn is_deliverable(&self, op: &Operation) -> bool {
+        op.seq() == self.seen(&op.client) + 1
+            && op
+                .clock
+                .iter()
+                .all(|(client, count)| *client == op.client || *count <= self.seen(client))
+    }
+
+    /// Take a pending op that can be delivered now.
+    fn next_deliverable(&mut self) -> Option<Operation> {
+        let index = self.pending.iter().position(|op| self.is_deliverable(op))?;
+        Some(self.pending.remove(index))
+    }
+
+    /// An op whose target was last written by an op it doesn't beat.
+    fn is_superseded(&self, op: &Operation) -> bool {
+        self.last_write
+            .get(&op.target())
+            .is_some_and(|last| !op.supersedes(&self.ops[*last]))
+    }
+
+    /// Deliver `op`, which must be the next one from its replica.
+    fn append(&mut self, op: Operation, wins: bool) {
+        self.clock.insert(op.client.clone(), op.seq());
+        if wins {
+            self.last_write.insert(op.target(), self.ops.len());
+        }
+        self.ops.push(op);
+    }
+
+    fn push_local(&mut self, entity: Uuid, kind: OpKind) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        *self.clock.entry(client.clone()).or_default() += 1;
+        let op = Operation {
+            client,
+            clock: self.clock.clone(),
+            entity,
+            kind,
+        };
+        self.append(op, true);
+    }
+}
+
+/// Components that are derived from others or hold local entity ids; never synced.
+fn is_synced(type_id: TypeId) -> bool {
+    ![
+        TypeId::of::<PersistentId>(),
+        TypeId::of::<ChildOf>(),
+        TypeId::of::<Children>(),
+        TypeId::of::<GlobalTransform>(),
+        TypeId::of::<InheritedVisibility>(),
+        TypeId::of::<ViewVisibility>(),
+    ]
+    .contains(&type_id)
+}
+
+fn synced_name(world: &World, component: ComponentId) -> Option<String> {
+    let info = world.components().get_info(component)?;
+    info.type_id()
+        .is_some_and(is_synced)
+        .then(|| info.name().to_string())
+}
+
+/// Where an edit starts: its writes are stamped after `since`, and its removals and
+/// despawns are the removal events past these cursors.
+struct Snapshot {
+    since: Tick,
+    removed: HashMap<ComponentId, EventCursor<RemovedComponentEntity>>,
+}
+
+fn snapshot(world: &mut World) -> Option<Snapshot> {
+    if !world.get_resource::<OpLog>().is_some_and(OpLog::enabled) {
+        return None;
+    }
+    let removed = world
+        .removed_components()
+        .iter()
+        .map(|(component, events)| (*component, events.get_cursor_current()))
+        .collect();
+    // Everything the edit writes is stamped after this tick.
+    let since = world.increment_change_tick();
+    Some(Snapshot { since, removed })
+}
+
+enum Edit {
+    Set(Entity, ComponentId, String),
+    Remove(String),
+    Despawn,
+}
+
+fn record(world: &mut World, mut snapshot: Snapshot) {
+    let this_run = world.change_tick();
+    let mut edits = Vec::new();
+
+    // Writes: change ticks are only kept per row, so this checks every persistent
+    // entity, but only reads ticks.
+    let mut query = world.query::<(EntityRef, &PersistentId)>();
+    for (entity_ref, id) in query.iter(world) {
+        for component in entity_ref.archetype().components() {
+            let changed = entity_ref
+                .get_change_ticks_by_id(component)
+                .is_some_and(|ticks| ticks.is_changed(snapshot.since, this_run));
+            if changed && let Some(name) = synced_name(world, component) {
+                edits.push((id.0, Edit::Set(entity_ref.id(), component, name)));
+            }
+        }
+    }
+
+    // Removals and despawns: only the entities the edit removed something from.
+    // `PersistentIndex` still maps despawned entities until its next update.
+    let mut despawned = Vec::new();
+    if let Some(index) = world.get_resource::<PersistentIndex>() {
+        for (component, events) in world.removed_components().iter() {
+            let mut cursor = snapshot.removed.remove(component).unwrap_or_default();
+            for removed in cursor.read(events) {
+                let entity = Entity::from(removed.clone());
+                let Some(id) = index.id(entity) else {
+                    continue;
+                };
+                let current = world.get_entity(entity).ok();
+                if current.and_then(|e| e.get::<PersistentId>()).map(|p| p.0) != Some(id) {
+                    if !despawned.contains(&id) {
+                        despawned.push(id);
+                    }
+                } else if current.is_some_and(|e| !e.contains_id(*component))
+                    && let Some(name) = synced_name(world, *component)
+                {
+                    edits.push((id, Edit::Remove(name)));
+                }
+            }
+        }
+    }
+    edits.extend(despawned.into_iter().map(|id| (id, Edit::Despawn)));
+
+    for (id, edit) in edits {
+        let kind = match edit {
+            Edit::Set(entity, component, name) => {
+                let read = ReadComponent {
+                    entity,
+                    component: component.index(),
+                };
+                // Components the inspector can't serialize can't be replayed either.
+                let Ok(value) = InspectorContext::run(world, |ctx, world| read.execute(ctx, world))
+                else {
+                    continue;
+                };
+                OpKind::Set {
+                    component: name,
+                    value,
+                }
+            }
+            Edit::Remove(name) => OpKind::Remove { component: name },
+            Edit::Despawn => OpKind::Despawn,
+        };
+        world.resource_mut::<OpLog>().push_local(id, kind);
+    }
+}
+
+/// Run an edit and log what it changed on persistent entities, if logging is on.
+pub(crate) fn logged<T>(world: &mut World, edit: impl FnOnce(&mut World) -> T) -> T {
+    let snapshot = snapshot(world);
+    let result = edit(world);
+    if let Some(snapshot) = snapshot {
+        record(world, snapshot);
+    }
+    result
+}
+
+/// Log the current value of one component, for edits applied before they were
+/// committed (inspector previews).
+pub(crate) fn log_component(world: &mut World, entity: Entity, component: usize) {
+    if !world.get_resource::<OpLog>().is_some_and(OpLog::enabled) {
+        return;
+    }
+    let Some(id) = world.get::<PersistentId>(entity).map(|id| id.0) else {
+        return;
+    };
+    let Some(name) = synced_name(world, ComponentId::new(component)) else {
+        return;
+    };
+    let read = ReadComponent { entity, component };
+    if let Ok(value) = InspectorContext::run(world, |ctx, world| read.execute(ctx, world)) {
+        world.resource_mut::<OpLog>().push_local(
+            id,
+            OpKind::Set {
+                component: name,
+                value,
+            },
+        );
+    }
+}
+
+fn apply_op(world: &mut World, op: &Operation) -> anyhow::Result<()> {
+    let entity = world
+        .get_resource::<PersistentIndex>()
+        .and_then(|index| index.entity(op.entity))
+        .ok_or_else(|| anyhow!("unknown entity {}", op.entity))?;
+    match &op.kind {
+        OpKind::Set { component, value } => set_component(world, entity, component, value.clone()),
+        OpKind::Remove { component } => {
+            let command = RemoveComponent {
+                entity,
+                component: component_id(world, component)?.index(),
+            };
+            InspectorContext::run(world, |ctx, world| command.execute(ctx, world))
+        }
+        OpKind::Despawn => {
+            let command = DespawnEntity {
+                entity,
+                kind: DespawnEntityKind::Recursive,
+            };
+            InspectorContext::run(world, |ctx, world| command.execute(ctx, world))
+        }
+    }
+}
+
+/// Name this replica and start logging edits; an empty id stops logging (the log is
+/// kept). Returns false before the app is set up.
+#[wasm_bindgen]
+pub fn set_op_log_client(ptr: u64, client_id: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(mut log) = app.world_mut().get_resource_mut::<OpLog>() else {
+        return false;
+    };
+    log.client = (!client_id.is_empty()).then(|| client_id.to_string());
+    true
+}
+
+/// Ops not covered by `clock_json` (a vector clock `{ client: count }`, `{}` for the
+/// whole log), in log order, as `{ client, clock, ops }` with this replica's id and
+/// clock. Each op is `{ client, clock, entity, op, component?, value? }`, `op` being
+/// "set", "remove" or "despawn". `null` for an invalid clock.
+#[wasm_bindgen]
+pub fn get_ops_since(ptr: u64, clock_json: &str) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let since: VectorClock = match serde_json::from_str(clock_json) {
+        Ok(clock) => clock,
+        Err(e) => {
+            warn!("get_ops_since: {e}");
+            return "null".to_string();
+        }
+    };
+    let Some(log) = app.world().get_resource::<OpLog>() else {
+        return "null".to_string();
+    };
+    let ops: Vec<&Operation> = log
+        .ops
+        .iter()
+        .filter(|op| op.seq() > since.get(&op.client).copied().unwrap_or(0))
+        .collect();
+    json!({ "client": log.client, "clock": log.clock, "ops": ops }).to_string()
+}
+
+/// Replay ops from another replica (a JSON array as `get_ops_since` returns them) in
+/// causal order, resolving conflicts last-writer-wins per (entity, component). Ops
+/// already seen or waiting are skipped; ops that arrive ahead of one they depend on
+/// wait for it; every delivered op joins the log, so it is passed on to further peers.
+/// Returns `{ applied, duplicate, superseded, failed, pending }` counts (`pending`:
+/// ops still waiting), or `null` for invalid JSON.
+#[wasm_bindgen]
+pub fn apply_remote_ops(ptr: u64, ops_json: &str) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let ops: Vec<Operation> = match serde_json::from_str(ops_json) {
+        Ok(ops) => ops,
+        Err(e) => {
+            warn!("apply_remote_ops: {e}");
+            return "null".to_string();
+        }
+    };
+    let world = app.world_mut();
+    if !world.contains_resource::<OpLog>() {
+        return "null".to_string();
+    }
+    let (mut applied, mut duplicate, mut superseded, mut failed) = (0, 0, 0, 0);
+    let mut log = world.resource_mut::<OpLog>();
+    for op in ops {
+        if log.has_seen(&op) {
+            duplicate += 1;
+        } else {
+            log.pending.push(op);
+        }
+    }
+    loop {
+        let Some(op) = world.resource_mut::<OpLog>().next_deliverable() else {
+            break;
+        };
+        let wins = !world.resource::<OpLog>().is_superseded(&op);
+        if !wins {
+            superseded += 1;
+        } else if let Err(e) = apply_op(world, &op) {
+            warn!("apply_remote_ops: {} from {}: {e}", op.seq(), op.client);
+            failed += 1;
+        } else {
+            applied += 1;
+        }
+        world.resource_mut::<OpLog>().append(op, wins);
+    }
+    let pending = world.resource::<OpLog>().pending.len();
+    if applied > 0 {
+        crate::ffi_inspector_bridge::trigger_inspector_streaming(world);
+    }
+    if let Some(mut active_info) = world.get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    json!({
+        "applied": applied,
+        "duplicate": duplicate,
+        "superseded": superseded,
+        "failed": failed,
+        "pending": pending,
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock(counts: &[(&str, u64)]) -> VectorClock {
+        counts
+            .iter()
+            .map(|(client, count)| (client.to_string(), *count))
+            .collect()
+    }
+
+    fn op(client: &str, counts: &[(&str, u64)]) -> Operation {
+        Operation {
+            client: client.to_string(),
+            clock: clock(counts),
+            entity: Uuid::nil(),
+            kind: OpKind::Set {
+                component: "Transform".to_string(),
+                value: Value::Null,
+            },
+        }
+    }
+
+    #[test]
+    fn causal_order_of_clocks() {
+        let a = clock(&[("a", 2), ("b", 1)]);
+        let b = clock(&[("a", 1), ("b", 1)]);
+        let c = clock(&[("a", 1), ("b", 2)]);
+        assert_eq!(causal_order(&a, &b), Some(Ordering::Greater));
+        assert_eq!(causal_order(&b, &a), Some(Ordering::Less));
+        assert_eq!(causal_order(&a, &a), Some(Ordering::Equal));
+        assert_eq!(causal_order(&a, &c), None);
+        // A missing replica counts as zero.
+        assert_eq!(
+            causal_order(&clock(&[("a", 1)]), &clock(&[("a", 1), ("b", 1)])),
+            Some(Ordering::Less)
+        );
+    }
+
+    #[test]
+    fn supersedes_follows_causality_then_total_then_client() {
+        let first = op("a", &[("a", 1)]);
+        let after = op("b", &[("a", 1), ("b", 1)]);
+        assert!(after.supersedes(&first));
+        assert!(!first.supersedes(&after));
+
+        let more = op("a", &[("a", 3)]);
+        let fewer = op("b", &[("b", 2)]);
+        assert!(more.supersedes(&fewer));
+        assert!(!fewer.supersedes(&more));
+
+        let lower = op("a", &[("a", 1)]);
+        let higher = op("b", &[("b", 1)]);
+        assert!(higher.supersedes(&lower));
+        assert!(!lower.supersedes(&higher));
+    }
+
+    #[test]
+    fn has_seen_only_counts_delivered_ops() {
+        let mut log = OpLog::default();
+        let first = op("b", &[("b", 1)]);
+        let second = op("b", &[("b", 2)]);
+        let third = op("b", &[("b", 3)]);
+        assert!(!log.has_seen(&third));
+
+        // An op ahead of its predecessor waits instead of advancing the clock.
+        assert!(!log.is_deliverable(&third));
+        log.pending.push(third.clone());
+        assert!(log.next_deliverable().is_none());
+        assert!(log.has_seen(&third));
+        assert!(!log.has_seen(&second));
+
+        log.append(first.clone(), true);
+        assert!(log.has_seen(&first));
+        assert!(!log.has_seen(&second));
+        log.pending.push(second.clone());
+        assert_eq!(log.next_deliverable(), Some(second.clone()));
+        log.append(second, true);
+        assert_eq!(log.next_deliverable(), Some(third.clone()));
+        log.append(third, true);
+        assert_eq!(log.seen("b"), 3);
+    }
+
+    #[test]
+    fn ops_wait_for_their_dependencies() {
+        let mut log = OpLog::default();
+        // c's op follows a's first op, which hasn't arrived.
+        let from_c = op("c", &[("a", 1), ("c", 1)]);
+        assert!(!log.is_deliverable(&from_c));
+        log.append(op("a", &[("a", 1)]), true);
+        assert!(log.is_deliverable(&from_c));
+        // Delivering c's op doesn't count a's ops it has seen.
+        log.append(from_c, true);
+        assert_eq!(log.seen("a"), 1);
+        assert_eq!(log.seen("c"), 1);
+        assert!(!log.has_seen(&op("a", &[("a", 2)])));
+    }
+
+    #[test]
+    fn logs_removals_and_despawns_of_touched_entities() {
+        use crate::bevy_app::persistent_id::update_persistent_index;
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut world = World::new();
+        world.insert_resource(OpLog {
+            client: Some("a".to_string()),
+            ..default()
+        });
+        world.init_resource::<PersistentIndex>();
+        let (kept, gone) = (PersistentId::new(), PersistentId::new());
+        let kept_entity = world.spawn((kept, Transform::default())).id();
+        let gone_entity = world.spawn((gone, Visibility::default())).id();
+        world.spawn((PersistentId::new(), Transform::default()));
+        world.run_system_once(update_persistent_index).unwrap();
+
+        logged(&mut world, |world| {
+            world.entity_mut(kept_entity).remove::<Transform>();
+            world.despawn(gone_entity);
+        });
+
+        let kinds: Vec<_> = world
+            .resource::<OpLog>()
+            .ops
+            .iter()
+            .map(|op| (op.entity, op.kind.clone()))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (
+                    kept.0,
+                    OpKind::Remove {
+                        component: std::any::type_name::<Transform>().to_string()
+                    }
+                ),
+                (gone.0, OpKind::Despawn),
+            ]
+        );
+    }
+}
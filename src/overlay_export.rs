@@ -0,0 +1,232 @@
+//! Off-screen raster export of the 2D overlay.
+//!
+//! `render_overlay_to_png` composites every overlay scene into one vello scene mapped
+//! onto a target of arbitrary size (independent of the canvas), rasterizes it with a
+//! dedicated vello renderer on Bevy's wgpu device, reads the texture back and delivers
+//! PNG bytes through `send_overlay_png_from_worker(request_id, bytes)`. The readback is
+//! asynchronous, so the FFI returns a request id that the PNG callback echoes.
+
+use bevy::prelude::*;
+use bevy::render::renderer::{RenderDevice, RenderQueue};
+use bevy_vello::prelude::*;
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+use crate::WorkerApp;
+use crate::bevy_app::OverlayLayer;
+use crate::panels::{Panels, VIEWER_PANEL};
+
+#[wasm_bindgen]
+extern "C" {
+    /// Deliver an encoded PNG for a `render_overlay_to_png` request (empty on failure).
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_overlay_png_from_worker(request_id: u32, png: &[u8]);
+}
+
+/// Source region in window coordinates (physical px, top-left origin).
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct ExportRegion {
+    x: f64,
+    y: f64,
+    w: f64,
+    h: f64,
+}
+
+/// Lazily created vello renderer used only for exports (NonSend: holds wgpu pipelines).
+struct OverlayExportRenderer {
+    renderer: vello::Renderer,
+    next_request_id: u32,
+}
+
+/// Render the overlay document into a `width` x `height` PNG.
+///
+/// `region_json` selects the source rect (`{"x","y","w","h"}` in window px); an empty
+/// string or `null` exports the viewer panel. Returns the request id echoed by
+/// `send_overlay_png_from_worker`, or 0 when the export could not be started.
+#[wasm_bindgen]
+pub fn render_overlay_to_png(ptr: u64, width: u32, height: u32, region_json: &str) -> u32 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    match start_overlay_export(app.world_mut(), width, height, region_json) {
+        Ok(request_id) => request_id,
+        Err(e) => {
+            error!("Overlay export failed: {e}");
+            0
+        }
+    }
+}
+
+fn start_overlay_export(
+    world: &mut World,
+    width: u32,
+    height: u32,
+    region_json: &str,
+) -> anyhow::Result<u32> {
+    let region = match region_json.trim() {
+        "" | "null" => {
+            let rect = world
+                .get_resource::<Panels>()
+                .and_then(|panels| panels.rect(VIEWER_PANEL))
+                .ok_or_else(|| anyhow::anyhow!("No viewer panel to export"))?;
+            ExportRegion {
+                x: rect.x as f64,
+                y: rect.y as f64,
+                w: rect.w as f64,
+                h: rect.h as f64,
+            }
+        }
+        json => serde_json::from_str(json)?,
+    };
+    if region.w <= 0.0 || region.h <= 0.0 {
+        anyhow::bail!("Export region is empty");
+    }
+
+    let device = world
+        .get_resource::<RenderDevice>()
+        .ok_or_else(|| anyhow::anyhow!("Render device not ready"))?
+        .clone();
+    let queue = world
+        .get_resource::<RenderQueue>()
+        .ok_or_else(|| anyhow::anyhow!("Render queue not ready"))?
+        .clone();
+
+    let max_dim = device.limits().max_texture_dimension_2d;
+    if width == 0 || height == 0 || width > max_dim || height > max_dim {
+        anyhow::bail!("Export size {width}x{height} outside 1..={max_dim}");
+    }
+
+    // Map the region onto the full target, then append every overlay scene through it.
+    let affine = kurbo::Affine::scale_non_uniform(width as f64 / region.w, height as f64 / region.h)
+        * kurbo::Affine::translate((-region.x, -region.y));
+    let mut composite = vello::Scene::new();
+    let mut scenes = world.query_filtered::<&VelloScene, With<OverlayLayer>>();
+    for scene in scenes.iter(world) {
+        composite.append(scene, Some(affine));
+    }
+
+    if world
+        .get_non_send_resource::<OverlayExportRenderer>()
+        .is_none()
+    {
+        let renderer = vello::Renderer::new(
+            device.wgpu_device(),
+            vello::RendererOptions {
+                use_cpu: false,
+                antialiasing_support: vello::AaSupport::area_only(),
+                num_init_threads: None,
+                pipeline_cache: None,
+            },
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to create export renderer: {e}"))?;
+        world.insert_non_send_resource(OverlayExportRenderer {
+            renderer,
+            next_request_id: 1,
+        });
+    }
+    let mut export = world.non_send_resource_mut::<OverlayExportRenderer>();
+    let request_id = export.next_request_id;
+    export.next_request_id = export.next_request_id.wrapping_add(1).max(1);
+
+    let wgpu_device = device.wgpu_device();
+    let texture = wgpu_device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("overlay_export_target"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    export
+        .renderer
+        .render_to_texture(
+            wgpu_device,
+            &queue,
+            &composite,
+            &view,
+            &vello::RenderParams {
+                base_color: peniko::Color::TRANSPARENT,
+                width,
+                height,
+                antialiasing_method: vello::AaConfig::Area,
+            },
+        )
+        .map_err(|e| anyhow::anyhow!("Overlay export render failed: {e}"))?;
+
+    // Rows must be padded to COPY_BYTES_PER_ROW_ALIGNMENT for texture -> buffer copies.
+    let unpadded_row = width * 4;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_row = unpadded_row.div_ceil(align) * align;
+    let buffer = wgpu_device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("overlay_export_readback"),
+        size: (padded_row * height) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut encoder = wgpu_device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("overlay_export_copy"),
+    });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row),
+                rows_per_image: Some(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit([encoder.finish()]);
+
+    let readback = buffer.clone();
+    buffer
+        .slice(..)
+        .map_async(wgpu::MapMode::Read, move |result| {
+            let png = match result {
+                Ok(()) => {
+                    let mapped = readback.slice(..).get_mapped_range();
+                    let pixels: Vec<u8> = mapped
+                        .chunks(padded_row as usize)
+                        .flat_map(|row| &row[..unpadded_row as usize])
+                        .copied()
+                        .collect();
+                    drop(mapped);
+                    readback.unmap();
+                    encode_png(&pixels, width, height).unwrap_or_else(|e| {
+                        error!("Overlay export PNG encode failed: {e}");
+                        Vec::new()
+                    })
+                }
+                Err(e) => {
+                    error!("Overlay export readback failed: {e}");
+                    Vec::new()
+                }
+            };
+            send_overlay_png_from_worker(request_id, &png);
+        });
+    // Native needs an explicit poll; on the web the browser resolves the mapping.
+    wgpu_device.poll(wgpu::Maintain::Poll);
+
+    Ok(request_id)
+}
+
+pub(crate) fn encode_png(rgba: &[u8], width: u32, height: u32) -> anyhow::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(rgba)?;
+    Ok(out)
+}
@@ -13,7 +13,7 @@ use bevy_vello::prelude::kurbo;
 pub const VIEWER_PANEL: &str = "viewer";
 pub const TIMELINE_PANEL: &str = "timeline";
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize)]
 pub struct PanelRect {
     /// Physical px, top-left origin of the full window canvas.
     pub x: f32,
@@ -85,16 +85,20 @@ impl Panels {
     }
 }
 
-/// Map a window-space cursor position (physical px, top-left origin) into "overlay world"
-/// coordinates: origin at the panel center, y-up. This is the coordinate space the 2D
-/// overlay content is authored in (previously provided by a per-window Camera2d).
-pub fn overlay_world_from_screen(rect: PanelRect, screen: Vec2) -> Vec2 {
-    let c = rect.center();
-    Vec2::new(screen.x - c.x, c.y - screen.y)
+/// Screen rects covered by host HTML UI (physical px, like panel rects). Pointer
+/// presses inside them are ignored by picking, tools and camera controls.
+#[derive(Resource, Debug, Default)]
+pub struct UiExclusionRects(pub Vec<PanelRect>);
+
+impl UiExclusionRects {
+    pub fn contains(&self, p: Vec2) -> bool {
+        self.0.iter().any(|rect| rect.contains(p))
+    }
 }
 
 /// Kurbo affine mapping overlay-world coords (panel-center origin, y-up) to
-/// screen-space vello coords (window top-left origin, y-down).
+/// screen-space vello coords (window top-left origin, y-down). The rendering
+/// counterpart of `CoordinateMapper::window_from_overlay`.
 pub fn overlay_affine(rect: PanelRect) -> kurbo::Affine {
     let c = rect.center();
     kurbo::Affine::new([1.0, 0.0, 0.0, -1.0, c.x as f64, c.y as f64])
@@ -0,0 +1,260 @@
+//! Project document: the editor state that is saved/loaded as a whole by the host.
+//!
+//! Only authoring state lives here (guides, the parameter graph, timeline keyframes,
+//! named selections, ...); scene entities are streamed through the inspector instead, so
+//! timeline tracks refer to their entity by `PersistentId`. A track whose entity isn't
+//! alive yet waits in `PendingTracks` until its scene is loaded. Every field is `#[serde(default)]` so older documents keep loading
+//! as new sections are added.
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use wasm_bindgen::prelude::*;
+
+use crate::WorkerApp;
+use crate::bevy_app::camera_track::{CameraKey, CameraTrack};
+use crate::bevy_app::graph::{GraphNode, ParameterGraph};
+use crate::bevy_app::guides::{Guide, Guides};
+use crate::bevy_app::keyframes::{self, Keyframe};
+use crate::bevy_app::persistent_id::{PersistentId, PersistentIndex};
+use crate::bevy_app::scene3d::MainCamera3D;
+use crate::bevy_app::selection_sets::SelectionSets;
+use crate::bevy_app::timeline::{TimelineState, TimelineTrack, TrackBlend};
+use crate::bevy_app::timeline_events::{self, TimelineEvent};
+
+pub const PROJECT_VERSION: u32 = 2;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectDocument {
+    pub version: u32,
+    pub guides: Vec<Guide>,
+    pub graph: Vec<GraphNode>,
+    pub tracks: Vec<TrackDocument>,
+    /// Keys of the main 3D camera's `CameraTrack`.
+    pub camera_keys: Vec<CameraKey>,
+    pub events: Vec<TimelineEvent>,
+    /// Named selections by `PersistentId` (see `bevy_app::selection_sets`).
+    pub selection_sets: BTreeMap<String, Vec<Uuid>>,
+}
+
+/// A `TimelineTrack` with its entity by `PersistentId`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrackDocument {
+    pub entity_id: Uuid,
+    pub start: f64,
+    pub property: String,
+    pub keyframes: Vec<Keyframe>,
+    pub weight: f32,
+    pub blend: TrackBlend,
+}
+
+impl Default for TrackDocument {
+    fn default() -> Self {
+        Self {
+            entity_id: Uuid::nil(),
+            start: 0.0,
+            property: String::new(),
+            keyframes: Vec::new(),
+            weight: 1.0,
+            blend: TrackBlend::Override,
+        }
+    }
+}
+
+impl TrackDocument {
+    fn into_track(self, entity: Entity) -> TimelineTrack {
+        let mut keyframes = self.keyframes;
+        keyframes::sort_keys(&mut keyframes);
+        TimelineTrack {
+            entity,
+            start: self.start,
+            property: self.property,
+            keyframes,
+            weight: self.weight,
+            blend: self.blend,
+        }
+    }
+}
+
+/// Loaded tracks whose entity isn't alive yet, moved onto the timeline by
+/// `resolve_pending_tracks` once an entity with their `PersistentId` appears.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct PendingTracks(Vec<TrackDocument>);
+
+pub(crate) fn resolve_pending_tracks(
+    mut pending: ResMut<PendingTracks>,
+    index: Res<PersistentIndex>,
+    timeline: Option<ResMut<TimelineState>>,
+) {
+    let Some(mut timeline) = timeline else {
+        return;
+    };
+    if pending.0.is_empty() || !index.is_changed() {
+        return;
+    }
+    for track in std::mem::take(&mut pending.0) {
+        match index.entity(track.entity_id) {
+            Some(entity) => timeline.tracks.push(track.into_track(entity)),
+            None => pending.0.push(track),
+        }
+    }
+}
+
+impl ProjectDocument {
+    /// Snapshot the project state from the world.
+    pub fn capture(world: &World) -> Self {
+        Self {
+            version: PROJECT_VERSION,
+            guides: world
+                .get_resource::<Guides>()
+                .map(|g| g.guides.clone())
+                .unwrap_or_default(),
+            graph: world
+                .get_resource::<ParameterGraph>()
+                .map(|g| g.nodes().to_vec())
+                .unwrap_or_default(),
+            // Tracks on entities without a persistent id (helpers) can't be saved.
+            tracks: world
+                .get_resource::<TimelineState>()
+                .into_iter()
+                .flat_map(|timeline| &timeline.tracks)
+                .filter_map(|track| {
+                    Some(TrackDocument {
+                        entity_id: world.get::<PersistentId>(track.entity)?.0,
+                        start: track.start,
+                        property: track.property.clone(),
+                        keyframes: track.keyframes.clone(),
+                        weight: track.weight,
+                        blend: track.blend,
+                    })
+                })
+                .chain(
+                    world
+                        .get_resource::<PendingTracks>()
+                        .into_iter()
+                        .flat_map(|pending| pending.0.iter().cloned()),
+                )
+                .collect(),
+            camera_keys: world
+                .try_query_filtered::<&CameraTrack, With<MainCamera3D>>()
+                .and_then(|mut query| query.iter(world).next().map(|track| track.keys.clone()))
+                .unwrap_or_default(),
+            events: world
+                .get_resource::<TimelineState>()
+                .map(|timeline| timeline.events.clone())
+                .unwrap_or_default(),
+            selection_sets: world
+                .get_resource::<SelectionSets>()
+                .map(|sets| sets.0.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Replace the world's project state with this document.
+    pub fn apply(self, world: &mut World) {
+        if let Some(mut guides) = world.get_resource_mut::<Guides>() {
+            guides.replace(self.guides);
+        }
+        // Members that aren't loaded yet stay in their sets until they are.
+        if let Some(mut sets) = world.get_resource_mut::<SelectionSets>() {
+            sets.0 = self.selection_sets;
+        }
+        if let Some(mut graph) = world.get_resource_mut::<ParameterGraph>()
+            && let Err(e) = graph.replace(self.graph)
+        {
+            warn!("Dropping invalid parameter graph: {e}");
+            graph.replace(Vec::new()).ok();
+        }
+        let mut tracks = Vec::new();
+        let mut pending = Vec::new();
+        let index = world.get_resource::<PersistentIndex>();
+        for track in self.tracks {
+            if track.entity_id.is_nil() {
+                warn!("Dropping timeline track without a persistent id");
+                continue;
+            }
+            match index.and_then(|index| index.entity(track.entity_id)) {
+                Some(entity) => tracks.push(track.into_track(entity)),
+                // Not loaded yet; waits in `PendingTracks`.
+                None => pending.push(track),
+            }
+        }
+        if let Some(mut pending_tracks) = world.get_resource_mut::<PendingTracks>() {
+            pending_tracks.0 = pending;
+        }
+        let mut events = self.events;
+        timeline_events::sort_events(&mut events);
+        if let Some(mut timeline) = world.get_resource_mut::<TimelineState>() {
+            timeline.tracks = tracks;
+            timeline.events = events;
+        }
+        let camera = world
+            .query_filtered::<Entity, With<MainCamera3D>>()
+            .iter(world)
+            .next();
+        if let Some(camera) = camera {
+            let mut track = CameraTrack {
+                keys: self.camera_keys,
+            };
+            track.sort();
+            world.entity_mut(camera).insert(track);
+        }
+    }
+}
+
+/// Document waiting for a rebuilt app's scene, see `restore_project_on_startup`.
+#[derive(Resource)]
+struct PendingProject(ProjectDocument);
+
+/// Apply `document` to `app` right after its startup systems have spawned the scene
+/// (used when the app is rebuilt after a GPU device loss).
+pub(crate) fn restore_project_on_startup(app: &mut App, document: ProjectDocument) {
+    app.insert_resource(PendingProject(document))
+        .add_systems(PostStartup, apply_pending_project);
+}
+
+fn apply_pending_project(world: &mut World) {
+    if let Some(PendingProject(document)) = world.remove_resource::<PendingProject>() {
+        document.apply(world);
+    }
+}
+
+/// Serialize the current project state to JSON.
+#[wasm_bindgen]
+pub fn save_project(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let document = ProjectDocument::capture(app.world());
+    serde_json::to_string(&document).unwrap_or_else(|e| {
+        error!("Failed to serialize project: {e}");
+        "{}".to_string()
+    })
+}
+
+/// Load project state previously produced by `save_project`.
+#[wasm_bindgen]
+pub fn load_project(ptr: u64, project_json: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let document: ProjectDocument = match serde_json::from_str(project_json) {
+        Ok(document) => document,
+        Err(e) => {
+            error!("Failed to parse project: {e}");
+            return false;
+        }
+    };
+    if document.version > PROJECT_VERSION {
+        warn!(
+            "Project version {} is newer than supported {PROJECT_VERSION}; loading known sections",
+            document.version
+        );
+    }
+    document.apply(app.world_mut());
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<crate::ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
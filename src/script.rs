@@ -0,0 +1,305 @@
+//! Scripted automation for integration tests and demo recordings.
+//!
+//! `run_script(ptr, script_json)` runs `{ "steps": [...] }` in order, driving
+//! `app.update()` itself for `wait`, and stops at the first failing step. Steps (`op`):
+//! - `spawn` `{ as?, parent?, name?, components?: { type_path: value } }`
+//! - `set_component` `{ entity, component: type_path, value }` (inserts if missing)
+//! - `select` `{ entities: [...], mode?: "replace" | "add" | "remove" }`
+//! - `move_camera` `{ translation?: [x, y, z], look_at?: [x, y, z], focus?: entity }`
+//! - `wait` `{ frames }`
+//! - `screenshot` `{ label? }`: PNG of the canvas, delivered later through
+//!   `send_screenshot_from_worker(request_id, png)`
+//!
+//! Entities are entity bits (number or decimal string) or `"$var"`, a variable bound
+//! by a previous `spawn` with `"as": "var"`.
+
+use anyhow::{Context, anyhow, bail};
+use bevy::ecs::component::ComponentId;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured};
+use bevy_remote_inspector::InspectorContext;
+use bevy_remote_inspector::command::{Execute, InsertComponent, SpawnEntity, UpdateComponent};
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::scene3d::MainCamera3D;
+use crate::entity_id::live_entity;
+use crate::overlay_export::encode_png;
+use crate::{ActivityControl, WorkerApp, apply_selection, focus_camera_on};
+
+#[wasm_bindgen]
+extern "C" {
+    /// Deliver a `screenshot` step's PNG (empty on failure).
+    #[wasm_bindgen(js_namespace = rustBridge)]
+    fn send_screenshot_from_worker(request_id: u32, png: &[u8]);
+}
+
+#[derive(Debug, Deserialize)]
+struct Script {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum EntityArg {
+    Bits(u64),
+    Text(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Step {
+    Spawn {
+        #[serde(rename = "as")]
+        var: Option<String>,
+        parent: Option<EntityArg>,
+        name: Option<String>,
+        #[serde(default)]
+        components: Map<String, Value>,
+    },
+    SetComponent {
+        entity: EntityArg,
+        component: String,
+        value: Value,
+    },
+    Select {
+        entities: Vec<EntityArg>,
+        #[serde(default)]
+        mode: String,
+    },
+    MoveCamera {
+        translation: Option<Vec3>,
+        look_at: Option<Vec3>,
+        focus: Option<EntityArg>,
+    },
+    Wait {
+        frames: u32,
+    },
+    Screenshot {
+        label: Option<String>,
+    },
+}
+
+#[derive(Resource, Default)]
+struct ScreenshotRequests {
+    next_id: u32,
+}
+
+/// Variables and screenshots of one run.
+#[derive(Default)]
+struct ScriptRun {
+    vars: HashMap<String, Entity>,
+    screenshots: Vec<Value>,
+}
+
+impl ScriptRun {
+    fn entity(&self, world: &World, arg: &EntityArg) -> anyhow::Result<Entity> {
+        let bits = match arg {
+            EntityArg::Bits(bits) => *bits,
+            EntityArg::Text(text) => match text.strip_prefix('$') {
+                Some(var) => {
+                    return self
+                        .vars
+                        .get(var)
+                        .copied()
+                        .ok_or_else(|| anyhow!("Unknown variable ${var}"));
+                }
+                None => text.parse().context("Invalid entity id")?,
+            },
+        };
+        Ok(live_entity(world, bits)?)
+    }
+
+    fn run(&mut self, app: &mut App, step: Step) -> anyhow::Result<()> {
+        match step {
+            Step::Spawn {
+                var,
+                parent,
+                name,
+                components,
+            } => {
+                let parent = parent
+                    .map(|parent| self.entity(app.world(), &parent))
+                    .transpose()?;
+                let bits = InspectorContext::run(app.world_mut(), |ctx, world| {
+                    SpawnEntity { parent }.execute(ctx, world)
+                })?;
+                let entity = Entity::from_bits(bits);
+                if let Some(name) = name {
+                    app.world_mut().entity_mut(entity).insert(Name::new(name));
+                }
+                for (component, value) in components {
+                    set_component(app.world_mut(), entity, &component, value)?;
+                }
+                if let Some(var) = var {
+                    self.vars.insert(var, entity);
+                }
+            }
+            Step::SetComponent {
+                entity,
+                component,
+                value,
+            } => {
+                let entity = self.entity(app.world(), &entity)?;
+                set_component(app.world_mut(), entity, &component, value)?;
+            }
+            Step::Select { entities, mode } => {
+                let entities = entities
+                    .iter()
+                    .map(|entity| self.entity(app.world(), entity))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                if !apply_selection(app.world_mut(), &entities, &mode) {
+                    bail!("No selection state");
+                }
+            }
+            Step::MoveCamera {
+                translation,
+                look_at,
+                focus,
+            } => {
+                if let Some(focus) = focus {
+                    let entity = self.entity(app.world(), &focus)?;
+                    let target = app
+                        .world()
+                        .get::<GlobalTransform>(entity)
+                        .context("Focus entity has no transform")?
+                        .translation();
+                    if !focus_camera_on(app.world_mut(), target) {
+                        bail!("No 3D camera");
+                    }
+                }
+                let world = app.world_mut();
+                let mut cameras = world.query_filtered::<&mut Transform, With<MainCamera3D>>();
+                let mut transform = cameras.single_mut(world).context("No 3D camera")?;
+                if let Some(translation) = translation {
+                    transform.translation = translation;
+                }
+                if let Some(target) = look_at {
+                    transform.look_at(target, Vec3::Y);
+                }
+            }
+            Step::Wait { frames } => {
+                for _ in 0..frames {
+                    app.update();
+                }
+            }
+            Step::Screenshot { label } => {
+                let world = app.world_mut();
+                let mut requests = world.get_resource_or_init::<ScreenshotRequests>();
+                requests.next_id += 1;
+                let request_id = requests.next_id;
+                world
+                    .spawn(Screenshot::primary_window())
+                    .observe(deliver_screenshot(request_id));
+                self.screenshots
+                    .push(json!({ "label": label, "request_id": request_id }));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Observer encoding a captured screenshot as PNG for `send_screenshot_from_worker`.
+fn deliver_screenshot(request_id: u32) -> impl FnMut(Trigger<ScreenshotCaptured>) {
+    move |trigger| {
+        let image = trigger.event().0.clone();
+        let (width, height) = (image.width(), image.height());
+        let png = image
+            .try_into_dynamic()
+            .map_err(anyhow::Error::from)
+            .and_then(|image| encode_png(&image.to_rgba8(), width, height))
+            .unwrap_or_else(|e| {
+                error!("Screenshot {request_id} failed: {e}");
+                Vec::new()
+            });
+        send_screenshot_from_worker(request_id, &png);
+    }
+}
+
+/// The id of the registered component with type path `component`.
+pub(crate) fn component_id(world: &World, component: &str) -> anyhow::Result<ComponentId> {
+    let type_id = world
+        .resource::<AppTypeRegistry>()
+        .read()
+        .get_with_type_path(component)
+        .map(|registration| registration.type_id())
+        .ok_or_else(|| anyhow!("{component} is not registered"))?;
+    world
+        .components()
+        .get_id(type_id)
+        .ok_or_else(|| anyhow!("{component} is not a component"))
+}
+
+/// Write `value` (reflect JSON, as the inspector sends it) into the component with type
+/// path `component`, inserting it if the entity does not have one yet.
+pub(crate) fn set_component(
+    world: &mut World,
+    entity: Entity,
+    component: &str,
+    value: Value,
+) -> anyhow::Result<()> {
+    let component_id = component_id(world, component)?;
+    let has_component = world.entity(entity).contains_id(component_id);
+    InspectorContext::run(world, |ctx, world| {
+        if has_component {
+            UpdateComponent {
+                entity,
+                component: component_id.index(),
+                value,
+            }
+            .execute(ctx, world)
+        } else {
+            InsertComponent {
+                entity,
+                component: component_id.index(),
+                value,
+            }
+            .execute(ctx, world)
+        }
+    })
+}
+
+/// Run an automation script (see module docs). Returns JSON
+/// `{ ok, steps_run, vars: { name: bits }, screenshots: [{ label, request_id }] }`, plus
+/// `error` when a step failed (`steps_run` is then the failing step's index).
+#[wasm_bindgen]
+pub fn run_script(ptr: u64, script_json: &str) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let script: Script = match serde_json::from_str(script_json) {
+        Ok(script) => script,
+        Err(e) => return json!({ "ok": false, "error": e.to_string() }).to_string(),
+    };
+    if app.plugins_state() != bevy::app::PluginsState::Cleaned {
+        return json!({ "ok": false, "error": "App is not ready" }).to_string();
+    }
+
+    let mut run = ScriptRun::default();
+    let mut steps_run = 0;
+    let mut error = None;
+    for step in script.steps {
+        if let Err(e) = run.run(app, step) {
+            error = Some(e.to_string());
+            break;
+        }
+        steps_run += 1;
+    }
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    let vars: Map<String, Value> = run
+        .vars
+        .into_iter()
+        .map(|(name, entity)| (name, entity.to_bits().into()))
+        .collect();
+    json!({
+        "ok": error.is_none(),
+        "steps_run": steps_run,
+        "vars": vars,
+        "screenshots": run.screenshots,
+        "error": error,
+    })
+    .to_string()
+}
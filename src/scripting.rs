@@ -0,0 +1,439 @@
+//! Embedded Rhai runtime for user macros (`scripting` feature).
+//!
+//! `load_user_script(ptr, name, source, trigger)` compiles a script and stores it under
+//! `name`, replacing any previous one. Triggers:
+//! - `manual`: only runs through `run_user_script`;
+//! - `frame`: runs every `Update` (and keeps the app awake while loaded);
+//! - `event:<name>`: runs once per queued event; `emit_script_event` queues host events
+//!   and `selection_changed` is queued when the selected set changes.
+//!
+//! Scripts see the constants `event` (event name, `"frame"` or `"manual"`) and `dt`
+//! (seconds), and these functions:
+//! - `selection()`, `select(entities)`, `select(entities, mode)`
+//! - `entity(bits)`, `spawn()`, `spawn(name)`, `despawn(e)`, `e.bits`
+//! - `get_component(e, type_path)`, `set_component(e, type_path, value)` (reflect values,
+//!   as the inspector sends them)
+//! - `set_color(e, r, g, b)`: gives a 3D entity its own copy of its material, recolored
+//! - `toggle_visibility(e)`, `random()` (0..1)
+//! - `timeline_time()`, `set_timeline_time(t)`, `timeline_play(playing)`
+//!
+//! e.g. randomizing the colors of the selection:
+//! `for e in selection() { set_color(e, random(), random(), random()); }`
+//!
+//! A triggered script that fails is disabled until it is loaded again.
+
+use anyhow::{Context, anyhow};
+use bevy::platform::collections::HashSet;
+use bevy::prelude::*;
+use bevy_remote_inspector::InspectorContext;
+use bevy_remote_inspector::command::{
+    DespawnEntity, DespawnEntityKind, Execute, ReadComponent, SpawnEntity, ToggleVisibity,
+};
+use rhai::{AST, Array, Dynamic, Engine, EvalAltResult, Scope};
+use serde_json::{Value, json};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use wasm_bindgen::prelude::*;
+
+use crate::bevy_app::timeline::TimelineState;
+use crate::entity_id::live_entity;
+use crate::script::{component_id, set_component};
+use crate::{ActivityControl, SelectionState, WorkerApp, apply_selection};
+
+/// Operation limit per run, so a runaway loop errors out instead of hanging the worker.
+const MAX_OPERATIONS: u64 = 5_000_000;
+
+const SELECTION_CHANGED: &str = "selection_changed";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Trigger {
+    Manual,
+    Frame,
+    Event(String),
+}
+
+impl Trigger {
+    fn parse(trigger: &str) -> anyhow::Result<Self> {
+        match trigger {
+            "" | "manual" => Ok(Trigger::Manual),
+            "frame" => Ok(Trigger::Frame),
+            _ => trigger
+                .strip_prefix("event:")
+                .filter(|event| !event.is_empty())
+                .map(|event| Trigger::Event(event.to_string()))
+                .ok_or_else(|| anyhow!("Unknown trigger {trigger:?}")),
+        }
+    }
+}
+
+struct UserScript {
+    ast: AST,
+    trigger: Trigger,
+    enabled: bool,
+}
+
+/// The Rhai engine, loaded scripts (run in name order) and events queued for them.
+#[derive(Resource)]
+pub struct ScriptEngine {
+    engine: Engine,
+    scripts: BTreeMap<String, UserScript>,
+    pending_events: Vec<String>,
+}
+
+impl Default for ScriptEngine {
+    fn default() -> Self {
+        Self {
+            engine: new_engine(),
+            scripts: BTreeMap::new(),
+            pending_events: Vec::new(),
+        }
+    }
+}
+
+pub struct ScriptingPlugin;
+
+impl Plugin for ScriptingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScriptEngine>().add_systems(
+            Update,
+            (queue_selection_changed, run_triggered_scripts).chain(),
+        );
+    }
+}
+
+thread_local! {
+    /// The world of the script being run; null outside `run_with_world`.
+    static WORLD: Cell<*mut World> = const { Cell::new(std::ptr::null_mut()) };
+}
+
+/// Call `f` with the world of the running script.
+fn with_world<T>(f: impl FnOnce(&mut World) -> anyhow::Result<T>) -> Result<T, Box<EvalAltResult>> {
+    let world = WORLD.get();
+    if world.is_null() {
+        return Err("No world outside a running script".into());
+    }
+    // SAFETY: set by `run_with_world` from a `&mut World` it does not touch while the
+    // script runs, and reset before that borrow ends.
+    f(unsafe { &mut *world }).map_err(|e| e.to_string().into())
+}
+
+fn run_with_world(
+    world: &mut World,
+    engine: &Engine,
+    ast: &AST,
+    event: &str,
+) -> Result<(), Box<EvalAltResult>> {
+    let dt = world
+        .get_resource::<Time>()
+        .map_or(0.0, |time| time.delta_secs_f64());
+    let mut scope = Scope::new();
+    scope.push_constant("event", event.to_string());
+    scope.push_constant("dt", dt);
+    let previous = WORLD.replace(world);
+    let result = engine.run_ast_with_scope(&mut scope, ast);
+    WORLD.set(previous);
+    result
+}
+
+fn new_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.on_print(|text| info!("[script] {text}"));
+    engine.on_debug(|text, _, position| debug!("[script] {position:?} {text}"));
+
+    engine
+        .register_type_with_name::<Entity>("Entity")
+        .register_get("bits", |entity: &mut Entity| entity.to_bits() as i64)
+        .register_fn("to_string", |entity: &mut Entity| entity.to_string())
+        .register_fn("==", |a: Entity, b: Entity| a == b)
+        .register_fn("entity", script_entity)
+        .register_fn("selection", selection)
+        .register_fn("select", |entities: Array| select(entities, ""))
+        .register_fn("select", |entities: Array, mode: &str| {
+            select(entities, mode)
+        })
+        .register_fn("spawn", || spawn(None))
+        .register_fn("spawn", |name: &str| spawn(Some(name)))
+        .register_fn("despawn", despawn)
+        .register_fn("get_component", get_component)
+        .register_fn("set_component", script_set_component)
+        .register_fn("set_color", set_color)
+        .register_fn("toggle_visibility", toggle_visibility)
+        .register_fn("random", rand::random::<f64>)
+        .register_fn("timeline_time", timeline_time)
+        .register_fn("set_timeline_time", set_timeline_time)
+        .register_fn("timeline_play", timeline_play);
+    engine
+}
+
+fn script_entity(bits: i64) -> Result<Entity, Box<EvalAltResult>> {
+    with_world(|world| Ok(live_entity(world, bits as u64)?))
+}
+
+fn selection() -> Result<Array, Box<EvalAltResult>> {
+    with_world(|world| {
+        let selection = world.get_resource::<SelectionState>();
+        Ok(selection
+            .into_iter()
+            .flat_map(|selection| selection.selected.keys())
+            .map(|entity| Dynamic::from(*entity))
+            .collect())
+    })
+}
+
+fn select(entities: Array, mode: &str) -> Result<(), Box<EvalAltResult>> {
+    let entities = entities
+        .into_iter()
+        .map(|entity| {
+            let type_name = entity.type_name();
+            entity
+                .try_cast::<Entity>()
+                .ok_or_else(|| format!("Expected an Entity, got {type_name}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    with_world(|world| {
+        if !apply_selection(world, &entities, mode) {
+            anyhow::bail!("No selection state");
+        }
+        Ok(())
+    })
+}
+
+fn spawn(name: Option<&str>) -> Result<Entity, Box<EvalAltResult>> {
+    with_world(|world| {
+        let bits = InspectorContext::run(world, |ctx, world| {
+            SpawnEntity { parent: None }.execute(ctx, world)
+        })?;
+        let entity = Entity::from_bits(bits);
+        if let Some(name) = name {
+            world.entity_mut(entity).insert(Name::new(name.to_string()));
+        }
+        Ok(entity)
+    })
+}
+
+fn despawn(entity: Entity) -> Result<(), Box<EvalAltResult>> {
+    with_world(|world| {
+        InspectorContext::run(world, |ctx, world| {
+            DespawnEntity {
+                entity,
+                kind: DespawnEntityKind::Recursive,
+            }
+            .execute(ctx, world)
+        })
+    })
+}
+
+fn get_component(entity: Entity, component: &str) -> Result<Dynamic, Box<EvalAltResult>> {
+    let value = with_world(|world| {
+        let component = component_id(world, component)?.index();
+        InspectorContext::run(world, |ctx, world| {
+            ReadComponent { entity, component }.execute(ctx, world)
+        })
+    })?;
+    rhai::serde::to_dynamic(value)
+}
+
+fn script_set_component(
+    entity: Entity,
+    component: &str,
+    value: Dynamic,
+) -> Result<(), Box<EvalAltResult>> {
+    let value: Value = rhai::serde::from_dynamic(&value)?;
+    with_world(|world| set_component(world, entity, component, value))
+}
+
+fn set_color(entity: Entity, r: f64, g: f64, b: f64) -> Result<(), Box<EvalAltResult>> {
+    with_world(|world| {
+        let handle = world
+            .get_entity(entity)?
+            .get::<MeshMaterial3d<StandardMaterial>>()
+            .context("Entity has no StandardMaterial")?
+            .0
+            .clone();
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        // Copy rather than edit in place: the material may be shared with other meshes.
+        let mut material = materials
+            .get(&handle)
+            .cloned()
+            .context("Material is not loaded")?;
+        material.base_color = Color::srgb(r as f32, g as f32, b as f32);
+        let handle = materials.add(material);
+        world.entity_mut(entity).insert(MeshMaterial3d(handle));
+        Ok(())
+    })
+}
+
+fn toggle_visibility(entity: Entity) -> Result<(), Box<EvalAltResult>> {
+    with_world(|world| {
+        InspectorContext::run(world, |ctx, world| {
+            ToggleVisibity { entity }.execute(ctx, world)
+        })
+    })
+}
+
+fn timeline_time() -> Result<f64, Box<EvalAltResult>> {
+    with_world(|world| {
+        let timeline = world
+            .get_resource::<TimelineState>()
+            .context("No timeline")?;
+        Ok(timeline.current_time)
+    })
+}
+
+fn set_timeline_time(time: f64) -> Result<(), Box<EvalAltResult>> {
+    with_world(|world| {
+        let mut timeline = world
+            .get_resource_mut::<TimelineState>()
+            .context("No timeline")?;
+        timeline.current_time = time.clamp(0.0, timeline.duration);
+        Ok(())
+    })
+}
+
+fn timeline_play(playing: bool) -> Result<(), Box<EvalAltResult>> {
+    with_world(|world| {
+        let mut timeline = world
+            .get_resource_mut::<TimelineState>()
+            .context("No timeline")?;
+        timeline.playing = playing;
+        Ok(())
+    })
+}
+
+/// Queue `selection_changed` when the selected set differs from last frame's.
+fn queue_selection_changed(
+    selection: Option<Res<SelectionState>>,
+    mut last: Local<HashSet<Entity>>,
+    mut scripts: ResMut<ScriptEngine>,
+) {
+    let Some(selection) = selection.filter(|selection| selection.is_changed()) else {
+        return;
+    };
+    let selected: HashSet<Entity> = selection.selected.keys().copied().collect();
+    if selected != *last {
+        *last = selected;
+        scripts.pending_events.push(SELECTION_CHANGED.to_string());
+    }
+}
+
+fn run_triggered_scripts(world: &mut World) {
+    world.resource_scope(|world, mut scripts: Mut<ScriptEngine>| {
+        let events = std::mem::take(&mut scripts.pending_events);
+        let ScriptEngine {
+            engine, scripts, ..
+        } = &mut *scripts;
+        let mut has_frame_scripts = false;
+        for (name, script) in scripts.iter_mut().filter(|(_, script)| script.enabled) {
+            let runs = match &script.trigger {
+                Trigger::Manual => continue,
+                Trigger::Frame => {
+                    has_frame_scripts = true;
+                    vec!["frame"]
+                }
+                Trigger::Event(trigger) => events
+                    .iter()
+                    .filter(|event| *event == trigger)
+                    .map(String::as_str)
+                    .collect(),
+            };
+            for event in runs {
+                if let Err(e) = run_with_world(world, engine, &script.ast, event) {
+                    warn!("Script {name} failed and was disabled: {e}");
+                    script.enabled = false;
+                    break;
+                }
+            }
+        }
+        if has_frame_scripts && let Some(mut activity) = world.get_resource_mut::<ActivityControl>()
+        {
+            activity.remaining_frames = activity.remaining_frames.max(1);
+        }
+    });
+}
+
+fn result_json(result: anyhow::Result<()>) -> String {
+    match result {
+        Ok(()) => json!({ "ok": true }),
+        Err(e) => json!({ "ok": false, "error": e.to_string() }),
+    }
+    .to_string()
+}
+
+/// Compile and store a user script (see module docs for `trigger`). Returns JSON
+/// `{ ok }`, or `{ ok: false, error }` with the parse error and its position.
+#[wasm_bindgen]
+pub fn load_user_script(ptr: u64, name: &str, source: &str, trigger: &str) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let result = (|| {
+        let trigger = Trigger::parse(trigger)?;
+        let mut scripts = app
+            .world_mut()
+            .get_resource_mut::<ScriptEngine>()
+            .context("Scripting is not initialized")?;
+        let ast = scripts.engine.compile(source)?;
+        scripts.scripts.insert(
+            name.to_string(),
+            UserScript {
+                ast,
+                trigger,
+                enabled: true,
+            },
+        );
+        Ok(())
+    })();
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    result_json(result)
+}
+
+/// Remove a loaded script. Returns false if there was none by that name.
+#[wasm_bindgen]
+pub fn unload_user_script(ptr: u64, name: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    app.world_mut()
+        .get_resource_mut::<ScriptEngine>()
+        .is_some_and(|mut scripts| scripts.scripts.remove(name).is_some())
+}
+
+/// Run a loaded script now, whatever its trigger, with `event` set to `"manual"`.
+/// Returns JSON `{ ok }` or `{ ok: false, error }`.
+#[wasm_bindgen]
+pub fn run_user_script(ptr: u64, name: &str) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world_mut();
+    let result = if world.contains_resource::<ScriptEngine>() {
+        world.resource_scope(|world, scripts: Mut<ScriptEngine>| {
+            let script = scripts
+                .scripts
+                .get(name)
+                .ok_or_else(|| anyhow!("No script named {name}"))?;
+            run_with_world(world, &scripts.engine, &script.ast, "manual")
+                .map_err(|e| anyhow!("{e}"))
+        })
+    } else {
+        Err(anyhow!("Scripting is not initialized"))
+    };
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    result_json(result)
+}
+
+/// Queue a host event for scripts loaded with `event:<name>`; they run next frame.
+#[wasm_bindgen]
+pub fn emit_script_event(ptr: u64, name: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(mut scripts) = app.world_mut().get_resource_mut::<ScriptEngine>() else {
+        return false;
+    };
+    scripts.pending_events.push(name.to_string());
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
@@ -1,11 +1,21 @@
+use crate::bevy_app::guides::{GuideAxis, Guides};
+use crate::bevy_app::history;
 use crate::bevy_app::init_app;
-use crate::panels::{PanelRect, Panels};
-use crate::{ActivityControl, DragState, WorkerApp, canvas_view::*};
+use crate::bevy_app::input_queue::{InputQueue, QueuedInput};
+use crate::bevy_app::options::AppOptions;
+use crate::bevy_app::tools::{Tool, ToolState};
+use crate::bevy_app::coordinates::Frame;
+use crate::bevy_app::{CoordinateMapper, RayHit, raycast_world};
+use crate::entity_id::live_entity_or_warn;
+use crate::panels::{PanelRect, Panels, UiExclusionRects};
+use crate::project::{ProjectDocument, restore_project_on_startup};
+use crate::{ActivityControl, DragState, SelectionState, WorkerApp, canvas_view::*};
 use bevy::app::PluginsState;
 use bevy::ecs::system::SystemState;
 use bevy::platform::collections::HashMap;
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
+use bevy_remote_inspector::InspectorHidden;
 use js_sys::BigInt;
 use wasm_bindgen::prelude::*;
 
@@ -13,7 +23,7 @@ use wasm_bindgen::prelude::*;
 use bevy::input::{
     ButtonState,                                            // Added ButtonState
     keyboard::{Key, KeyCode as BevyKeyCode, KeyboardInput}, // Added Key, BevyKeyCode, KeyboardInput, NativeKey
-    mouse::{MouseButton, MouseButtonInput, MouseScrollUnit, MouseWheel},
+    mouse::{MouseButton, MouseButtonInput, MouseMotion, MouseScrollUnit, MouseWheel},
 };
 use bevy::window::CursorMoved; // CursorMoved is used in mouse_move
 
@@ -27,9 +37,6 @@ use bevy::window::CursorMoved; // CursorMoved is used in mouse_move
 #[wasm_bindgen]
 extern "C" {
     fn alert(s: &str);
-    /// 在 app 初始化完成前，info! 无法使用，打印日志得用它
-    #[wasm_bindgen(js_namespace = console)]
-    pub(crate) fn log(s: &str);
 
     /// 发送 pick 列表
     ///
@@ -49,15 +56,38 @@ extern "C" {
 /// `variant_flags` selects a perf-grid variant (see `bevy_app::VARIANT_*`); 0 = normal app.
 #[wasm_bindgen]
 pub fn init_bevy_app(variant_flags: u32) -> u64 {
-    // info!/log crate may be unavailable in nolog/min variants; always print via console.
-    log(&format!("init_bevy_app variant_flags={variant_flags}"));
-    let app = init_app(variant_flags);
+    let mut app = init_app(variant_flags, &AppOptions::default());
+    app.variant_flags = Some(variant_flags);
+    // After `init_app`: the log subscriber (if the variant has one) exists from here.
+    info!("init_bevy_app variant_flags={variant_flags}");
 
     // 包装成无生命周期的指针
     // english: Wrap it into a non-lifetime pointer
     Box::into_raw(Box::new(app)) as u64
 }
 
+/// Like `init_bevy_app(0)`, configured by a JSON `AppOptions` object (see
+/// `bevy_app::options`); read the outcome back with `get_capabilities`. Returns 0 if
+/// the JSON is invalid or asks for a backend this build wasn't compiled with.
+#[wasm_bindgen]
+pub fn init_bevy_app_with_options(options_json: &str) -> u64 {
+    let options = match serde_json::from_str::<AppOptions>(options_json) {
+        Ok(options) => options,
+        Err(err) => {
+            warn!("init_bevy_app_with_options: invalid options: {err}");
+            return 0;
+        }
+    };
+    if let Err(err) = options.validate() {
+        warn!("init_bevy_app_with_options: {err}");
+        return 0;
+    }
+    let mut app = init_app(0, &options);
+    app.variant_flags = Some(0);
+    info!("init_bevy_app_with_options {options:?}");
+    Box::into_raw(Box::new(app)) as u64
+}
+
 /// Create the single full-window Bevy window from a canvas.
 ///
 /// Called once per app. In worker mode the canvas is a transferred OffscreenCanvas;
@@ -70,6 +100,15 @@ pub fn create_window_by_offscreen_canvas(
     is_in_worker: bool,
 ) {
     let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    attach_canvas_window(app, canvas, scale_factor, is_in_worker);
+}
+
+fn attach_canvas_window(
+    app: &mut WorkerApp,
+    canvas: web_sys::OffscreenCanvas,
+    scale_factor: f32,
+    is_in_worker: bool,
+) {
     app.scale_factor = scale_factor;
 
     let offscreen_canvas = OffscreenCanvas::new(canvas, scale_factor, 1);
@@ -77,12 +116,144 @@ pub fn create_window_by_offscreen_canvas(
 
     let entity = create_canvas_window(app, view_obj);
     app.window = entity;
+    if let Some(mut mapper) = app.world_mut().get_resource_mut::<CoordinateMapper>() {
+        mapper.configure(entity, scale_factor);
+    }
 
     let mut act = ActivityControl::new();
     act.is_in_worker = is_in_worker;
     app.insert_resource(act);
 }
 
+/// Attach a replacement canvas after the old one's context or surface was lost,
+/// rebuilding the window surface. After a GPU device loss the app itself is rebuilt on
+/// a new device instead (see `recover_lost_device`). `canvas_id` is the window id
+/// reported by `get_gpu_info` (0 for the primary window). Progress is reported via
+/// `send_canvas_status_from_worker`: "reinitializing", then "ready" or "failed".
+#[wasm_bindgen]
+pub fn reinit_canvas(ptr: u64, canvas_id: u64, canvas: web_sys::OffscreenCanvas) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    if canvas_id != 0 && canvas_id != app.window.to_bits() {
+        return false;
+    }
+    if is_device_lost(app) {
+        send_canvas_status(serde_json::json!({ "status": "reinitializing", "device": true }));
+        return recover_lost_device(app, canvas);
+    }
+    send_canvas_status(serde_json::json!({ "status": "reinitializing" }));
+
+    let offscreen_canvas = OffscreenCanvas::new(canvas, app.scale_factor, 1);
+    let view_obj = ViewObj::from_offscreen_canvas(offscreen_canvas);
+    let Some(window) = replace_canvas_surface(app, view_obj) else {
+        send_canvas_status(serde_json::json!({
+            "status": "failed",
+            "reason": "no_window",
+            "message": "no canvas window to reinitialize",
+        }));
+        return false;
+    };
+    app.window = window;
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    send_canvas_status(serde_json::json!({ "status": "ready", "window": window.to_bits() }));
+    true
+}
+
+/// Replace the app with a fresh one built from the same options, on a new GPU device
+/// and `canvas`. The project document (guides, graph, timeline, selection sets) carries
+/// over and is restored once the new app's startup systems have run; scene entities
+/// are rebuilt by those systems, and inspector clients other than the host's have to
+/// reconnect. Apps composed with `IronfellAppBuilder` can't be rebuilt (their plugins
+/// were consumed), so the host has to reload those.
+fn recover_lost_device(app: &mut WorkerApp, canvas: web_sys::OffscreenCanvas) -> bool {
+    let (Some(variant_flags), Some(options)) = (
+        app.variant_flags,
+        app.world().get_resource::<AppOptions>().cloned(),
+    ) else {
+        send_canvas_status(serde_json::json!({
+            "status": "failed",
+            "reason": "device_lost",
+            "message": "the GPU device was lost and this app can't be rebuilt; reload it",
+        }));
+        return false;
+    };
+    let document = ProjectDocument::capture(app.world());
+    let is_in_worker = app
+        .world()
+        .get_resource::<ActivityControl>()
+        .is_some_and(|active_info| active_info.is_in_worker);
+
+    let mut fresh = init_app(variant_flags, &options);
+    fresh.variant_flags = Some(variant_flags);
+    restore_project_on_startup(&mut fresh, document);
+    attach_canvas_window(&mut fresh, canvas, app.scale_factor, is_in_worker);
+    if let Some(mut active_info) = fresh.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    // The old app's device is already gone, so it is dropped without the closing
+    // update `release_app` runs.
+    let lost = std::mem::replace(app, fresh);
+    drop(lost);
+
+    send_canvas_status(serde_json::json!({
+        "status": "ready",
+        "window": app.window.to_bits(),
+        "device": true,
+    }));
+    true
+}
+
+/// The window of canvas `canvas_id` (0 for the primary window), if there is one.
+pub(crate) fn canvas_window(app: &WorkerApp, canvas_id: u64) -> Option<Entity> {
+    let window = match canvas_id {
+        0 => app.window,
+        bits => Entity::try_from_bits(bits).ok()?,
+    };
+    app.world().get::<Window>(window).is_some().then_some(window)
+}
+
+/// Pause (`active` false) or resume rendering to a canvas the host has hidden, e.g. a
+/// collapsed panel's. `canvas_id` is the window id as for `reinit_canvas` (0 for the
+/// primary window). A paused canvas presents nothing and its cameras are off; resuming
+/// restores them. Returns false for an unknown canvas.
+#[wasm_bindgen]
+pub fn set_canvas_active(ptr: u64, canvas_id: u64, active: bool) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(window) = canvas_window(app, canvas_id) else {
+        return false;
+    };
+    if active {
+        resume_canvas(app.world_mut(), window);
+    } else {
+        pause_canvas(app.world_mut(), window);
+    }
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
+
+/// Set the coordinate convention host input positions use for the canvas, as JSON
+/// `{ origin, y_axis }` (`origin`: `top_left` | `bottom_left` | `center`, `y_axis`:
+/// `down` | `up`; both default to the DOM's `top_left` / `down`). Positions are still
+/// CSS px; the device pixel ratio comes from `create_window_by_offscreen_canvas`.
+#[wasm_bindgen]
+pub fn set_input_coordinate_frame(ptr: u64, frame_json: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Ok(frame) = serde_json::from_str::<Frame>(frame_json) else {
+        return false;
+    };
+    let window = app.window;
+    let Some(mut mapper) = app.world_mut().get_resource_mut::<CoordinateMapper>() else {
+        return false;
+    };
+    mapper.set_host_frame(window, frame);
+    true
+}
+
 /// Upsert a panel rectangle (physical px, top-left origin, window coordinates).
 /// The `kind` decides what the panel drives ("viewer" = 3D camera viewport,
 /// "timeline" = timeline vello region, ...).
@@ -99,6 +270,26 @@ pub fn set_panel_viewport(ptr: u64, id: String, kind: String, x: f32, y: f32, w:
     }
 }
 
+/// Replace the screen rects covered by host HTML UI: a JSON array of `{ x, y, w, h }`
+/// in physical px (like `set_panel_viewport`); `[]` clears. Pointer presses and wheel
+/// input inside them are ignored by picking, tools and camera controls.
+#[wasm_bindgen]
+pub fn set_ui_exclusion_rects(ptr: u64, rects_json: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Ok(rects) = serde_json::from_str::<Vec<PanelRect>>(rects_json) else {
+        return false;
+    };
+    let Some(mut exclusions) = app.world_mut().get_resource_mut::<UiExclusionRects>() else {
+        return false;
+    };
+    exclusions.0 = rects;
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
+
 /// Remove a panel.
 #[wasm_bindgen]
 pub fn despawn_panel(ptr: u64, id: String) {
@@ -113,6 +304,133 @@ pub fn despawn_panel(ptr: u64, id: String) {
     }
 }
 
+/// Edit the guides as one undoable step.
+fn edit_guides<T>(
+    app: &mut WorkerApp,
+    label: &str,
+    edit: impl FnOnce(&mut Guides) -> T,
+) -> Option<T> {
+    let set = |guides: &mut Guides, value| *guides = value;
+    history::edit_resource(app.world_mut(), label, Guides::clone, set, edit)
+}
+
+/// Add a guide line ("horizontal" | "vertical") at `position` (overlay-world units).
+/// Returns the new guide id, or 0 if the axis is unknown.
+#[wasm_bindgen]
+pub fn add_guide(ptr: u64, axis: String, position: f32) -> u32 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(axis) = GuideAxis::parse(&axis) else {
+        return 0;
+    };
+    let Some(id) = edit_guides(app, "Add guide", |guides| guides.add(axis, position)) else {
+        return 0;
+    };
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    id
+}
+
+/// Move an existing guide along its normal axis.
+#[wasm_bindgen]
+pub fn move_guide(ptr: u64, id: u32, position: f32) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(moved) = edit_guides(app, "Move guide", |guides| guides.set_position(id, position))
+    else {
+        return false;
+    };
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    moved
+}
+
+/// Remove a guide by id.
+#[wasm_bindgen]
+pub fn remove_guide(ptr: u64, id: u32) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(removed) = edit_guides(app, "Remove guide", |guides| guides.remove(id)) else {
+        return false;
+    };
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    removed
+}
+
+/// Remove every guide.
+#[wasm_bindgen]
+pub fn clear_guides(ptr: u64) {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    if edit_guides(app, "Clear guides", Guides::clear).is_none() {
+        return;
+    }
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+}
+
+/// JSON array of `{ id, axis, position }` for every guide.
+#[wasm_bindgen]
+pub fn list_guides(ptr: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    app.world()
+        .get_resource::<Guides>()
+        .and_then(|guides| serde_json::to_string(&guides.guides).ok())
+        .unwrap_or_else(|| "[]".to_string())
+}
+
+/// Switch the active tool ("select" | "pan" | "pen" | "measure" | "gizmo").
+/// Returns false for unknown names; the change is echoed via `send_tool_changed_from_worker`.
+#[wasm_bindgen]
+pub fn set_tool(ptr: u64, name: String) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(tool) = Tool::parse(&name) else {
+        return false;
+    };
+    let Some(mut tools) = app.world_mut().get_resource_mut::<ToolState>() else {
+        return false;
+    };
+    tools.active = tool;
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
+
+/// Revert the most recent undoable edit. Returns false when the history is empty.
+#[wasm_bindgen]
+pub fn undo(ptr: u64) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    // Queued inspector edits are newer than any entry; record them first.
+    crate::ffi_inspector_bridge::flush_component_updates_system(app.world_mut());
+    let undone = crate::op_log::logged(app.world_mut(), crate::bevy_app::history::undo);
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    undone
+}
+
+/// Re-apply the most recently undone edit. Returns false when there is nothing to redo.
+#[wasm_bindgen]
+pub fn redo(ptr: u64) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    // Queued inspector edits are newer than any entry; record them first.
+    crate::ffi_inspector_bridge::flush_component_updates_system(app.world_mut());
+    let redone = crate::op_log::logged(app.world_mut(), crate::bevy_app::history::redo);
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    redone
+}
+
 /// Check if plugin initialization is completed
 /// Frame rendering cannot be called before initialization is complete
 #[wasm_bindgen]
@@ -124,6 +442,7 @@ pub fn is_preparation_completed(ptr: u64) -> u32 {
     if app.plugins_state() == PluginsState::Ready {
         app.finish();
         app.cleanup();
+        watch_device_loss(app);
 
         let mut windows_system_state: SystemState<Query<Entity, With<PrimaryWindow>>> =
             SystemState::from_world(app.world_mut());
@@ -135,32 +454,50 @@ pub fn is_preparation_completed(ptr: u64) -> u32 {
     0
 }
 
+/// Append a host input event to the `InputQueue`; it reaches Bevy at the next frame start.
+fn queue_input(app: &mut WorkerApp, input: impl Into<QueuedInput>) {
+    if let Some(mut queue) = app.world_mut().get_resource_mut::<InputQueue>() {
+        queue.push(input);
+    }
+}
+
+/// `queue_input` for pointer events, with the host event's `performance.now()` timestamp
+/// (NaN when unknown) for the input-latency diagnostics.
+fn queue_pointer_input(app: &mut WorkerApp, input: impl Into<QueuedInput>, timestamp: f64) {
+    if let Some(mut queue) = app.world_mut().get_resource_mut::<InputQueue>() {
+        queue.push_stamped(input, timestamp);
+    }
+}
+
 /// Set mouse position without triggering activity (for batched updates)
 #[wasm_bindgen]
-pub fn set_mouse_position(ptr: u64, x: f32, y: f32) {
+pub fn set_mouse_position(ptr: u64, x: f32, y: f32, timestamp: f64) {
     let app = unsafe { &mut *(ptr as *mut WorkerApp) };
-    let position = app.to_physical_size(x, y);
+    let position = app.to_window_px(x, y);
     let cursor_move = CursorMoved {
         window: app.window,
         position,
         delta: None,
     };
-    app.world_mut().send_event(cursor_move);
+    queue_pointer_input(app, cursor_move, timestamp);
     // Note: No activity trigger - this will be handled by enter_frame
 }
 
 /// 包装一个鼠标事件发送给 app
+///
+/// Like every pointer FFI call, `timestamp` is the DOM event's time in this context's
+/// `performance.now()` clock (NaN when unknown); see `bevy_app::input_latency`.
 #[wasm_bindgen]
-pub fn mouse_move(ptr: u64, x: f32, y: f32) {
+pub fn mouse_move(ptr: u64, x: f32, y: f32, timestamp: f64) {
     let app = unsafe { &mut *(ptr as *mut WorkerApp) };
     // 提前将逻辑像转换成物理像素
-    let position = app.to_physical_size(x, y);
+    let position = app.to_window_px(x, y);
     let cursor_move = CursorMoved {
         window: app.window,
         position,
         delta: None,
     };
-    app.world_mut().send_event(cursor_move);
+    queue_pointer_input(app, cursor_move, timestamp);
 
     let mut active_info = app
         .world_mut()
@@ -169,22 +506,45 @@ pub fn mouse_move(ptr: u64, x: f32, y: f32) {
     active_info.remaining_frames = 10;
 }
 
+/// Raw pointer motion (`movementX/Y`, CSS px), e.g. while the pointer is locked.
+/// The camera controller prefers it over cursor positions for mouse-look.
+#[wasm_bindgen]
+pub fn mouse_raw_delta(ptr: u64, dx: f32, dy: f32, timestamp: f64) {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let delta = app.to_window_delta(dx, dy);
+    queue_pointer_input(app, MouseMotion { delta }, timestamp);
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+}
+
 /// Frame rendering with optional mouse position update
 #[wasm_bindgen]
-pub fn enter_frame_with_mouse(ptr: u64, mouse_x: f32, mouse_y: f32, has_mouse_update: bool) {
+pub fn enter_frame_with_mouse(
+    ptr: u64,
+    mouse_x: f32,
+    mouse_y: f32,
+    has_mouse_update: bool,
+    timestamp: f64,
+) {
     let app = unsafe { &mut *(ptr as *mut WorkerApp) };
-    
+
     // Update mouse position first if provided
     if has_mouse_update {
-        let position = app.to_physical_size(mouse_x, mouse_y);
+        let position = app.to_window_px(mouse_x, mouse_y);
         let cursor_move = CursorMoved {
             window: app.window,
             position,
             delta: None,
         };
-        app.world_mut().send_event(cursor_move);
+        queue_pointer_input(app, cursor_move, timestamp);
     }
-    
+
+    if is_device_lost(app) || is_surface_lost(app) {
+        return;
+    }
+
     // Get a mutable borrow of the Rust object pointed to by the pointer
     {
         // Check conditions for executing frame rendering
@@ -193,6 +553,7 @@ pub fn enter_frame_with_mouse(ptr: u64, mouse_x: f32, mouse_y: f32, has_mouse_up
             .get_resource_mut::<ActivityControl>()
             .unwrap();
         if !active_info.auto_animate && active_info.remaining_frames == 0 {
+            crate::bevy_app::idle::note_frame(app.world_mut(), false);
             return;
         }
         if active_info.remaining_frames > 0 {
@@ -207,9 +568,11 @@ pub fn enter_frame_with_mouse(ptr: u64, mouse_x: f32, mouse_y: f32, has_mouse_up
         } else {
             app.finish();
             app.cleanup();
+            watch_device_loss(app);
         }
     } else {
         app.update();
+        crate::bevy_app::idle::note_frame(app.world_mut(), true);
     }
 }
 
@@ -222,7 +585,7 @@ pub fn enter_frame_with_mouse(ptr: u64, mouse_x: f32, mouse_y: f32, has_mouse_up
 /// - `delta_y`: Y 轴滚动增量
 /// - `delta_mode`: 滚动单位模式
 #[wasm_bindgen]
-pub fn mouse_wheel(ptr: u64, delta_x: f32, delta_y: f32, delta_mode: u32) {
+pub fn mouse_wheel(ptr: u64, delta_x: f32, delta_y: f32, delta_mode: u32, timestamp: f64) {
     let app = unsafe { &mut *(ptr as *mut WorkerApp) };
 
     let unit = match delta_mode {
@@ -239,7 +602,7 @@ pub fn mouse_wheel(ptr: u64, delta_x: f32, delta_y: f32, delta_mode: u32) {
         y: delta_y,
         window: app.window,
     };
-    app.world_mut().send_event(event);
+    queue_pointer_input(app, event, timestamp);
 
     let mut active_info = app
         .world_mut()
@@ -261,14 +624,63 @@ pub fn resize(ptr: u64, _width: f32, _height: f32) {
 
 /// Mouse left button down (no entity id needed; Rust picking determines target)
 #[wasm_bindgen]
-pub fn left_bt_down(ptr: u64) {
+pub fn left_bt_down(ptr: u64, timestamp: f64) {
     let app = unsafe { &mut *(ptr as *mut WorkerApp) };
     let event = MouseButtonInput {
         button: MouseButton::Left,
         state: ButtonState::Pressed,
         window: app.window,
     };
-    app.world_mut().send_event(event);
+    queue_pointer_input(app, event, timestamp);
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+}
+
+/// Switch the mouse wheel between movement-speed adjustment (default) and
+/// zoom-to-cursor dolly for the 3D viewer camera.
+#[wasm_bindgen]
+pub fn set_zoom_to_cursor(ptr: u64, enabled: bool) {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let mut controllers = app
+        .world_mut()
+        .query::<&mut crate::camera_controller::CameraController>();
+    for mut controller in controllers.iter_mut(app.world_mut()) {
+        controller.zoom_to_cursor = enabled;
+    }
+}
+
+/// Generic mouse button event using DOM `MouseEvent.button` numbering:
+/// 0 = left, 1 = middle, 2 = right, 3 = back, 4 = forward, others map to `Other(n)`.
+#[wasm_bindgen]
+pub fn mouse_button(ptr: u64, button: u16, pressed: bool, timestamp: f64) {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let button = match button {
+        0 => MouseButton::Left,
+        1 => MouseButton::Middle,
+        2 => MouseButton::Right,
+        3 => MouseButton::Back,
+        4 => MouseButton::Forward,
+        n => MouseButton::Other(n),
+    };
+    if button == MouseButton::Left
+        && !pressed
+        && let Some(mut drag_state) = app.world_mut().get_resource_mut::<DragState>()
+    {
+        drag_state.target = None;
+        drag_state.kind = None;
+    }
+    let event = MouseButtonInput {
+        button,
+        state: if pressed {
+            ButtonState::Pressed
+        } else {
+            ButtonState::Released
+        },
+        window: app.window,
+    };
+    queue_pointer_input(app, event, timestamp);
+
     if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
         active_info.remaining_frames = 10;
     }
@@ -276,7 +688,7 @@ pub fn left_bt_down(ptr: u64) {
 
 /// 鼠标左键松开
 #[wasm_bindgen]
-pub fn left_bt_up(ptr: u64) {
+pub fn left_bt_up(ptr: u64, timestamp: f64) {
     let app = unsafe { &mut *(ptr as *mut WorkerApp) };
     if let Some(mut drag_state) = app.world_mut().get_resource_mut::<DragState>() {
         drag_state.target = None;
@@ -289,7 +701,7 @@ pub fn left_bt_up(ptr: u64) {
         state: ButtonState::Released,
         window: app.window,
     };
-    app.world_mut().send_event(event);
+    queue_pointer_input(app, event, timestamp);
 
     // If you need to modify active_info again, get it again
     if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
@@ -299,14 +711,14 @@ pub fn left_bt_up(ptr: u64) {
 
 /// 鼠标右键按下
 #[wasm_bindgen]
-pub fn right_bt_down(ptr: u64) {
+pub fn right_bt_down(ptr: u64, timestamp: f64) {
     let app = unsafe { &mut *(ptr as *mut WorkerApp) };
     let event = MouseButtonInput {
         button: MouseButton::Right,
         state: ButtonState::Pressed,
         window: app.window,
     };
-    app.world_mut().send_event(event);
+    queue_pointer_input(app, event, timestamp);
     let mut active_info = app
         .world_mut()
         .get_resource_mut::<ActivityControl>()
@@ -316,20 +728,289 @@ pub fn right_bt_down(ptr: u64) {
 
 /// 鼠标右键松开
 #[wasm_bindgen]
-pub fn right_bt_up(ptr: u64) {
+pub fn right_bt_up(ptr: u64, timestamp: f64) {
     let app = unsafe { &mut *(ptr as *mut WorkerApp) };
     let event = MouseButtonInput {
         button: MouseButton::Right,
         state: ButtonState::Released,
         window: app.window,
     };
-    app.world_mut().send_event(event);
+    queue_pointer_input(app, event, timestamp);
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+}
+
+/// Entity ids from JS: a JSON array of entity bits, as numbers or decimal strings
+/// (strings avoid precision loss above 2^53). Invalid, dead or stale-generation ids
+/// are skipped and reported to the host on behalf of `caller` (see
+/// `live_entity_or_warn`).
+pub(crate) fn parse_entity_ids(world: &World, ids_json: &str, caller: &str) -> Option<Vec<Entity>> {
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Number(u64),
+        Text(String),
+    }
+    let ids: Vec<Id> = serde_json::from_str(ids_json)
+        .map_err(|e| error!("Invalid entity id list: {e}"))
+        .ok()?;
+    Some(
+        ids.into_iter()
+            .filter_map(|id| match id {
+                Id::Number(bits) => Some(bits),
+                Id::Text(text) => text.parse().ok(),
+            })
+            .filter_map(|bits| live_entity_or_warn(world, bits, caller))
+            .collect(),
+    )
+}
+
+/// Drive the viewport selection from the UI (e.g. the hierarchy panel).
+///
+/// `mode` is "replace" (default), "add" or "remove". The resulting selection is
+/// echoed back through `send_selection_from_worker` like any Rust-side change.
+#[wasm_bindgen]
+pub fn set_selection(ptr: u64, ids_json: &str, mode: &str) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(entities) = parse_entity_ids(app.world(), ids_json, "set_selection") else {
+        return false;
+    };
+    if !apply_selection(app.world_mut(), &entities, mode) {
+        return false;
+    }
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
+
+/// `set_selection` on already validated entities.
+pub(crate) fn apply_selection(world: &mut World, entities: &[Entity], mode: &str) -> bool {
+    let Some(mut selection) = world.get_resource_mut::<SelectionState>() else {
+        return false;
+    };
+    match mode {
+        "add" => {
+            for entity in entities {
+                selection.selected.insert(*entity, ());
+            }
+        }
+        "remove" => {
+            for entity in entities {
+                selection.selected.remove(entity);
+            }
+        }
+        _ => {
+            selection.selected.clear();
+            for entity in entities {
+                selection.selected.insert(*entity, ());
+            }
+        }
+    }
+    let primary = selection
+        .last_primary
+        .filter(|e| selection.selected.contains_key(e));
+    selection.last_primary = primary.or_else(|| {
+        entities
+            .last()
+            .copied()
+            .filter(|e| selection.selected.contains_key(e))
+    });
+    true
+}
+
+/// Ancestor chain of an entity for breadcrumbs, as a JSON array of `{ id, name }`
+/// from the root down to the entity itself (`name` is null for unnamed entities), or
+/// `null` for an unknown or stale id.
+#[wasm_bindgen]
+pub fn get_entity_path(ptr: u64, entity_id: u64) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world();
+    let Some(entity) = live_entity_or_warn(world, entity_id, "get_entity_path") else {
+        return "null".to_string();
+    };
+    let mut path: Vec<Entity> = std::iter::successors(Some(entity), |e| {
+        world.get::<ChildOf>(*e).map(ChildOf::parent)
+    })
+    .collect();
+    path.reverse();
+    let path: Vec<serde_json::Value> = path
+        .into_iter()
+        .map(|e| {
+            serde_json::json!({
+                "id": e.to_bits(),
+                "name": world.get::<Name>(e).map(Name::as_str),
+            })
+        })
+        .collect();
+    serde_json::to_string(&path).unwrap_or_else(|_| "null".to_string())
+}
+
+/// Primary selected entity, for keyboard hierarchy navigation.
+fn selection_primary(world: &World) -> Option<Entity> {
+    let selection = world.get_resource::<SelectionState>()?;
+    selection
+        .last_primary
+        .or_else(|| selection.selected.keys().next().copied())
+}
+
+/// Select `entity` alone and wake the app. Returns its bits.
+fn navigate_selection(app: &mut WorkerApp, entity: Entity) -> u64 {
+    apply_selection(app.world_mut(), &[entity], "replace");
+
     if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
         active_info.remaining_frames = 10;
     }
+    entity.to_bits()
+}
+
+/// Select the parent of the primary selected entity. Returns the parent's id, or 0
+/// (selection unchanged) when nothing is selected or it is a root.
+#[wasm_bindgen]
+pub fn select_parent(ptr: u64) -> u64 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world();
+    let Some(parent) = selection_primary(world)
+        .and_then(|e| world.get::<ChildOf>(e))
+        .map(ChildOf::parent)
+    else {
+        return 0;
+    };
+    navigate_selection(app, parent)
 }
 
-// Inbound hover/selection setters removed; Rust is authoritative now. Keep optional FFI if UI wants to force selection later.
+/// Select the `index`th child (0-based, in hierarchy order, skipping inspector-hidden
+/// internals) of the primary selected entity. Returns the child's id, or 0 (selection
+/// unchanged) when there is no such child.
+#[wasm_bindgen]
+pub fn select_child(ptr: u64, index: u32) -> u64 {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let world = app.world();
+    let Some(child) = selection_primary(world)
+        .and_then(|e| world.get::<Children>(e))
+        .and_then(|children| {
+            children
+                .iter()
+                .filter(|child| !world.entity(*child).contains::<InspectorHidden>())
+                .nth(index as usize)
+        })
+    else {
+        return 0;
+    };
+    navigate_selection(app, child)
+}
+
+/// Hover an entity from the UI; 0 (or an unknown or stale id, which is reported to
+/// the host) clears the hover.
+#[wasm_bindgen]
+pub fn hover_entity(ptr: u64, id: u64) {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let entity = (id != 0)
+        .then(|| live_entity_or_warn(app.world(), id, "hover_entity"))
+        .flatten();
+    let Some(mut selection) = app.world_mut().get_resource_mut::<SelectionState>() else {
+        return;
+    };
+    selection.hovered.clear();
+    if let Some(entity) = entity {
+        selection.hovered.insert(entity, ());
+    }
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+}
+
+/// Frame a 3D entity: keep the camera's view direction and move it so the entity sits
+/// at the orbit pivot. Returns false if the entity or camera is missing.
+#[wasm_bindgen]
+pub fn focus_entity(ptr: u64, id: u64) -> bool {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let Some(target) = live_entity_or_warn(app.world(), id, "focus_entity")
+        .and_then(|e| app.world().get::<GlobalTransform>(e))
+        .map(|tf| tf.translation())
+    else {
+        return false;
+    };
+    if !focus_camera_on(app.world_mut(), target) {
+        return false;
+    }
+
+    if let Some(mut active_info) = app.world_mut().get_resource_mut::<ActivityControl>() {
+        active_info.remaining_frames = 10;
+    }
+    true
+}
+
+/// Move the 3D camera along its view direction so `target` sits at the orbit pivot.
+pub(crate) fn focus_camera_on(world: &mut World, target: Vec3) -> bool {
+    let mut cameras = world.query_filtered::<
+        (&mut Transform, &crate::camera_controller::CameraController),
+        With<crate::bevy_app::scene3d::MainCamera3D>,
+    >();
+    let Ok((mut transform, controller)) = cameras.single_mut(world) else {
+        return false;
+    };
+    transform.translation = target - *transform.forward() * controller.orbit_distance;
+    true
+}
+
+fn ray_hits_json(hits: &[RayHit]) -> String {
+    let hits: Vec<serde_json::Value> = hits
+        .iter()
+        .map(|hit| {
+            serde_json::json!({
+                "entity": hit.entity.to_bits(),
+                "distance": hit.distance,
+                "point": hit.point.to_array(),
+            })
+        })
+        .collect();
+    serde_json::to_string(&hits).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Cast a world-space ray (`origin_json` / `dir_json` as `[x, y, z]`) and return every
+/// mesh hit within `max_dist` as a JSON array of `{ entity, distance, point }`, nearest
+/// first. Hits are against bounding boxes, like pointer picking.
+#[wasm_bindgen]
+pub fn raycast(ptr: u64, origin_json: &str, dir_json: &str, max_dist: f32) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let (Ok(origin), Ok(direction)) = (
+        serde_json::from_str::<[f32; 3]>(origin_json),
+        serde_json::from_str::<[f32; 3]>(dir_json),
+    ) else {
+        return "[]".to_string();
+    };
+    let Ok(direction) = Dir3::new(Vec3::from(direction)) else {
+        return "[]".to_string();
+    };
+    let ray = Ray3d::new(Vec3::from(origin), direction);
+    ray_hits_json(&raycast_world(app.world_mut(), ray, max_dist))
+}
+
+/// `raycast` through a canvas position (CSS px, like `mouse_move`) from the 3D viewer
+/// camera. Returns `[]` outside the viewer viewport.
+#[wasm_bindgen]
+pub fn raycast_screen(ptr: u64, x: f32, y: f32) -> String {
+    let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    let position = app.to_window_px(x, y);
+    let mut cameras = app
+        .world_mut()
+        .query_filtered::<(&Camera, &GlobalTransform), With<crate::bevy_app::scene3d::MainCamera3D>>();
+    let Some(mapper) = app.world().get_resource::<CoordinateMapper>() else {
+        return "[]".to_string();
+    };
+    let Some(ray) = cameras
+        .single(app.world())
+        .ok()
+        .and_then(|(camera, transform)| mapper.viewport_ray(camera, transform, position))
+    else {
+        return "[]".to_string();
+    };
+    ray_hits_json(&raycast_world(app.world_mut(), ray, f32::MAX))
+}
 
 /// 打开 / 关闭动画
 #[wasm_bindgen]
@@ -342,24 +1023,51 @@ pub fn set_auto_animation(ptr: u64, needs_animate: u32) {
     active_info.auto_animate = needs_animate > 0;
 }
 
-fn map_key_str_to_bevy_key(key_str: &str) -> Option<(BevyKeyCode, Key)> {
+pub(crate) fn map_key_str_to_bevy_key(key_str: &str) -> Option<(BevyKeyCode, Key)> {
     // This is a simplified mapping. A more comprehensive one might be needed.
     // The `Key` (logical key) part can be more complex depending on desired behavior.
     match key_str.to_lowercase().as_str() {
-        "w" => Some((BevyKeyCode::KeyW, Key::Character("w".into()))),
-        "a" => Some((BevyKeyCode::KeyA, Key::Character("a".into()))),
-        "s" => Some((BevyKeyCode::KeyS, Key::Character("s".into()))),
-        "d" => Some((BevyKeyCode::KeyD, Key::Character("d".into()))),
-        "g" => Some((BevyKeyCode::KeyG, Key::Character("g".into()))),
-        "f" => Some((BevyKeyCode::KeyF, Key::Character("f".into()))),
         " " | "space" => Some((BevyKeyCode::Space, Key::Space)),
+        // Letters and digits (camera keys, tool and `shortcuts` chords)
+        key if key.len() == 1 => {
+            let code = ascii_key_code(key.as_bytes()[0])?;
+            Some((code, Key::Character(key.into())))
+        }
+        "enter" => Some((BevyKeyCode::Enter, Key::Enter)),
+        "escape" | "esc" => Some((BevyKeyCode::Escape, Key::Escape)),
         "shift" | "shiftleft" => Some((BevyKeyCode::ShiftLeft, Key::Shift)), // Assuming ShiftLeft
         "control" | "controlleft" => Some((BevyKeyCode::ControlLeft, Key::Control)), // Assuming ControlLeft
+        "alt" | "altleft" => Some((BevyKeyCode::AltLeft, Key::Alt)),
+        "meta" | "metaleft" => Some((BevyKeyCode::SuperLeft, Key::Super)),
+        // Text editing keys (see `text_edit`)
+        "backspace" => Some((BevyKeyCode::Backspace, Key::Backspace)),
+        "delete" => Some((BevyKeyCode::Delete, Key::Delete)),
+        "arrowleft" => Some((BevyKeyCode::ArrowLeft, Key::ArrowLeft)),
+        "arrowright" => Some((BevyKeyCode::ArrowRight, Key::ArrowRight)),
+        "home" => Some((BevyKeyCode::Home, Key::Home)),
+        "end" => Some((BevyKeyCode::End, Key::End)),
         // Add more mappings as needed
         _ => None,
     }
 }
 
+/// `KeyA`..`KeyZ` / `Digit0`..`Digit9` for a lowercase ASCII letter or digit.
+fn ascii_key_code(c: u8) -> Option<BevyKeyCode> {
+    use BevyKeyCode::*;
+    const LETTERS: [BevyKeyCode; 26] = [
+        KeyA, KeyB, KeyC, KeyD, KeyE, KeyF, KeyG, KeyH, KeyI, KeyJ, KeyK, KeyL, KeyM, KeyN,
+        KeyO, KeyP, KeyQ, KeyR, KeyS, KeyT, KeyU, KeyV, KeyW, KeyX, KeyY, KeyZ,
+    ];
+    const DIGITS: [BevyKeyCode; 10] = [
+        Digit0, Digit1, Digit2, Digit3, Digit4, Digit5, Digit6, Digit7, Digit8, Digit9,
+    ];
+    match c {
+        b'a'..=b'z' => Some(LETTERS[(c - b'a') as usize]),
+        b'0'..=b'9' => Some(DIGITS[(c - b'0') as usize]),
+        _ => None,
+    }
+}
+
 /// Handle key down event
 #[wasm_bindgen]
 pub fn key_down(ptr: u64, key: String) {
@@ -376,7 +1084,7 @@ pub fn key_down(ptr: u64, key: String) {
         };
 
         // info!("sending key event: {:?}", event);
-        app.world_mut().send_event(event);
+        queue_input(app, event);
     }
 
     // Original ActiveInfo update (can be removed if camera controller fully relies on ButtonInput)
@@ -399,7 +1107,7 @@ pub fn key_up(ptr: u64, key: String) {
             text: None,
             repeat: false,
         };
-        app.world_mut().send_event(event);
+        queue_input(app, event);
     }
 
     // Original ActiveInfo update (can be removed if camera controller fully relies on ButtonInput)
@@ -420,6 +1128,9 @@ pub fn enter_frame(ptr: u64) {
     // 获取到指针指代的 Rust 对象的可变借用
     // english: Get a mutable borrow of the Rust object pointed to by the pointer
     let app = unsafe { &mut *(ptr as *mut WorkerApp) };
+    if is_device_lost(app) || is_surface_lost(app) {
+        return;
+    }
     {
         // Check conditions for executing frame rendering
         let mut active_info = app
@@ -427,6 +1138,7 @@ pub fn enter_frame(ptr: u64) {
             .get_resource_mut::<ActivityControl>()
             .unwrap();
         if !active_info.auto_animate && active_info.remaining_frames == 0 {
+            crate::bevy_app::idle::note_frame(app.world_mut(), false);
             return;
         }
         if active_info.remaining_frames > 0 {
@@ -441,9 +1153,11 @@ pub fn enter_frame(ptr: u64) {
         } else {
             app.finish();
             app.cleanup();
+            watch_device_loss(app);
         }
     } else {
         app.update();
+        crate::bevy_app::idle::note_frame(app.world_mut(), true);
     }
 }
 